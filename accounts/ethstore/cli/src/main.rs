@@ -33,10 +33,12 @@ extern crate serde_derive;
 use std::collections::VecDeque;
 use std::io::Read;
 use std::{env, process, fs, fmt};
+use std::path::Path;
 
 use docopt::Docopt;
-use ethstore::accounts_dir::{KeyDirectory, RootDiskDirectory};
+use ethstore::accounts_dir::{KeyDirectory, RootDiskDirectory, DatabaseDirectory, AuditEntry, VaultKey};
 use ethkey::Password;
+use parity_crypto::KEY_ITERATIONS;
 use parity_crypto::publickey::Address;
 use ethstore::{EthStore, SimpleSecretStore, SecretStore, import_accounts, PresaleWallet, SecretVaultRef, StoreAccountRef};
 
@@ -47,27 +49,35 @@ OpenEthereum key management tool.
   Copyright 2015-2020 Parity Technologies (UK) Ltd.
 
 Usage:
-    ethstore insert <secret> <password> [--dir DIR] [--vault VAULT] [--vault-pwd VAULTPWD]
-    ethstore change-pwd <address> <old-pwd> <new-pwd> [--dir DIR] [--vault VAULT] [--vault-pwd VAULTPWD]
-    ethstore list [--dir DIR] [--vault VAULT] [--vault-pwd VAULTPWD]
-    ethstore import [<password>] [--src DIR] [--dir DIR]
-    ethstore import-wallet <path> <password> [--dir DIR] [--vault VAULT] [--vault-pwd VAULTPWD]
+    ethstore insert <secret> <password> [--dir DIR] [--db-pwd DBPWD] [--vault VAULT] [--vault-pwd VAULTPWD]
+    ethstore change-pwd <address> <old-pwd> <new-pwd> [--dir DIR] [--db-pwd DBPWD] [--vault VAULT] [--vault-pwd VAULTPWD]
+    ethstore list [--dir DIR] [--db-pwd DBPWD] [--vault VAULT] [--vault-pwd VAULTPWD]
+    ethstore import [<password>] [--src DIR] [--dir DIR] [--db-pwd DBPWD]
+    ethstore import-wallet <path> <password> [--dir DIR] [--db-pwd DBPWD] [--vault VAULT] [--vault-pwd VAULTPWD]
     ethstore find-wallet-pass <path> <password>
-    ethstore remove <address> <password> [--dir DIR] [--vault VAULT] [--vault-pwd VAULTPWD]
-    ethstore sign <address> <password> <message> [--dir DIR] [--vault VAULT] [--vault-pwd VAULTPWD]
-    ethstore public <address> <password> [--dir DIR] [--vault VAULT] [--vault-pwd VAULTPWD]
-    ethstore list-vaults [--dir DIR]
-    ethstore create-vault <vault> <password> [--dir DIR]
-    ethstore change-vault-pwd <vault> <old-pwd> <new-pwd> [--dir DIR]
-    ethstore move-to-vault <address> <vault> <password> [--dir DIR] [--vault VAULT] [--vault-pwd VAULTPWD]
-    ethstore move-from-vault <address> <vault> <password> [--dir DIR]
+    ethstore remove <address> <password> [--dir DIR] [--db-pwd DBPWD] [--vault VAULT] [--vault-pwd VAULTPWD]
+    ethstore sign <address> <password> <message> [--dir DIR] [--db-pwd DBPWD] [--vault VAULT] [--vault-pwd VAULTPWD]
+    ethstore public <address> <password> [--dir DIR] [--db-pwd DBPWD] [--vault VAULT] [--vault-pwd VAULTPWD]
+    ethstore list-vaults [--dir DIR] [--db-pwd DBPWD]
+    ethstore create-vault <vault> <password> [--dir DIR] [--db-pwd DBPWD]
+    ethstore change-vault-pwd <vault> <old-pwd> <new-pwd> [--dir DIR] [--db-pwd DBPWD]
+    ethstore move-to-vault <address> <vault> <password> [--dir DIR] [--db-pwd DBPWD] [--vault VAULT] [--vault-pwd VAULTPWD]
+    ethstore move-from-vault <address> <vault> <password> [--dir DIR] [--db-pwd DBPWD]
+    ethstore migrate <password> --to DIR [--dir DIR] [--db-pwd DBPWD] [--to-db-pwd TODBPWD]
+    ethstore audit-log [--dir DIR] [--db-pwd DBPWD]
     ethstore [-h | --help]
 
 Options:
     -h, --help               Display this message and exit.
     --dir DIR                Specify the secret store directory. It may be either
-                             parity, parity-(chain), geth, geth-test
-                             or a path [default: parity].
+                             parity, parity-(chain), geth, geth-test,
+                             a path or db:PATH for a key database [default: parity].
+    --db-pwd DBPWD           Specify the key database password to use in this
+                             operation. Required when the directory is a key database.
+    --to DIR                 Specify the secret store directory accounts are migrated
+                             to, in the same format as --dir.
+    --to-db-pwd TODBPWD      Specify the password of the key database accounts are
+                             migrated to.
     --vault VAULT            Specify vault to use in this operation.
     --vault-pwd VAULTPWD     Specify vault password to use in this operation. Please note
                              that this option is required when vault option is set.
@@ -91,6 +101,9 @@ Commands:
     change-vault-pwd   Change vault password.
     move-to-vault      Move account to vault from another vault/root directory.
     move-from-vault    Move account to root directory from given vault.
+    migrate            Copy all accounts unlocked by one of the passwords given
+                       to another secret store directory.
+    audit-log          List the uses of the keys stored in a key database.
 "#;
 
 #[derive(Debug, Deserialize)]
@@ -109,6 +122,8 @@ struct Args {
 	cmd_change_vault_pwd: bool,
 	cmd_move_to_vault: bool,
 	cmd_move_from_vault: bool,
+	cmd_migrate: bool,
+	cmd_audit_log: bool,
 	arg_secret: String,
 	arg_password: String,
 	arg_old_pwd: String,
@@ -121,6 +136,9 @@ struct Args {
 	flag_dir: String,
 	flag_vault: String,
 	flag_vault_pwd: String,
+	flag_db_pwd: String,
+	flag_to: String,
+	flag_to_db_pwd: String,
 }
 
 enum Error {
@@ -166,7 +184,11 @@ fn main() {
 	}
 }
 
-fn key_dir(location: &str, password: Option<Password>) -> Result<Box<dyn KeyDirectory>, Error> {
+fn key_dir(location: &str, password: Option<Password>, db_password: &str, create_db: bool) -> Result<Box<dyn KeyDirectory>, Error> {
+	if location.starts_with("db:") {
+		return Ok(Box::new(database_dir(location, db_password, create_db)?));
+	}
+
 	let dir: RootDiskDirectory = match location {
 		"geth" => RootDiskDirectory::create(dir::geth(false))?,
 		"geth-test" => RootDiskDirectory::create(dir::geth(true))?,
@@ -183,6 +205,26 @@ fn key_dir(location: &str, password: Option<Password>) -> Result<Box<dyn KeyDire
 	Ok(Box::new(dir.with_password(password)))
 }
 
+fn database_dir(location: &str, password: &str, create: bool) -> Result<DatabaseDirectory, Error> {
+	if !location.starts_with("db:") {
+		return Err(ethstore::Error::Custom(format!("'{}' is not a key database, use db:PATH", location)).into());
+	}
+	if password.is_empty() {
+		return Err(ethstore::Error::Custom("A key database password must be given with --db-pwd".into()).into());
+	}
+
+	let path = &location["db:".len()..];
+	let key = VaultKey::new(&load_password(password)?, KEY_ITERATIONS as u32);
+	let dir = if Path::new(path).exists() {
+		DatabaseDirectory::at(path, key)?
+	} else if create {
+		DatabaseDirectory::create(path, key)?
+	} else {
+		return Err(ethstore::Error::Custom(format!("Key database '{}' doesn't exist", path)).into());
+	};
+	Ok(dir)
+}
+
 fn open_args_vault(store: &EthStore, args: &Args) -> Result<SecretVaultRef, Error> {
 	if args.flag_vault.is_empty() {
 		return Ok(SecretVaultRef::Root);
@@ -212,6 +254,13 @@ fn format_vaults(vaults: &[String]) -> String {
 	vaults.join("\n")
 }
 
+fn format_audit_log(log: &[AuditEntry]) -> String {
+	log.iter()
+		.map(|entry| format!("{} 0x{:x} {:?} {}", entry.timestamp, entry.address, entry.operation, if entry.success { "OK" } else { "FAILED" }))
+		.collect::<Vec<String>>()
+		.join("\n")
+}
+
 fn load_password(path: &str) -> Result<Password, Error> {
 	let mut file = fs::File::open(path).map_err(|e| ethstore::Error::Custom(format!("Error opening password file '{}': {}", path, e)))?;
 	let mut password = String::new();
@@ -225,7 +274,24 @@ fn execute<S, I>(command: I) -> Result<String, Error> where I: IntoIterator<Item
 	let args: Args = Docopt::new(USAGE)
 		.and_then(|d| d.argv(command).deserialize())?;
 
-	let store = EthStore::open(key_dir(&args.flag_dir, None)?)?;
+	// these commands open the directories themselves
+	if args.cmd_import {
+		let password = match args.arg_password.as_ref() {
+			"" => None,
+			_ => Some(load_password(&args.arg_password)?)
+		};
+		let src = key_dir(&args.flag_src, password, "", false)?;
+		let dst = key_dir(&args.flag_dir, None, &args.flag_db_pwd, true)?;
+
+		let accounts = import_accounts(&*src, &*dst)?;
+		return Ok(format_accounts(&accounts));
+	} else if args.cmd_audit_log {
+		let dir = database_dir(&args.flag_dir, &args.flag_db_pwd, false)?;
+		return Ok(format_audit_log(&dir.audit_log()?));
+	}
+
+	let adds_keys = args.cmd_insert || args.cmd_import_wallet;
+	let store = EthStore::open(key_dir(&args.flag_dir, None, &args.flag_db_pwd, adds_keys)?)?;
 
 	return if args.cmd_insert {
 		let secret = args.arg_secret.parse().map_err(|_| ethstore::Error::InvalidSecret)?;
@@ -249,16 +315,6 @@ fn execute<S, I>(command: I) -> Result<String, Error> where I: IntoIterator<Item
 			.map(|a| a.address)
 			.collect();
 		Ok(format_accounts(&accounts))
-	} else if args.cmd_import {
-		let password = match args.arg_password.as_ref() {
-			"" => None,
-			_ => Some(load_password(&args.arg_password)?)
-		};
-		let src = key_dir(&args.flag_src, password)?;
-		let dst = key_dir(&args.flag_dir, None)?;
-
-		let accounts = import_accounts(&*src, &*dst)?;
-		Ok(format_accounts(&accounts))
 	} else if args.cmd_import_wallet {
 		let wallet = PresaleWallet::open(&args.arg_path)?;
 		let password = load_password(&args.arg_password)?;
@@ -316,6 +372,29 @@ fn execute<S, I>(command: I) -> Result<String, Error> where I: IntoIterator<Item
 		store.open_vault(&args.arg_vault, &password)?;
 		store.change_account_vault(SecretVaultRef::Root, StoreAccountRef::vault(&args.arg_vault, address))?;
 		Ok("OK".to_owned())
+	} else if args.cmd_migrate {
+		let passwords = load_password(&args.arg_password)?;
+		let passwords = passwords.as_str().lines().map(|line| str::to_owned(line).into()).collect::<Vec<Password>>();
+		let target = EthStore::open(key_dir(&args.flag_to, None, &args.flag_to_db_pwd, true)?)?;
+		let mut migrated = Vec::new();
+		for account_ref in store.accounts()?.into_iter().filter(|a| a.vault == SecretVaultRef::Root) {
+			// accounts copied by a previous run are skipped
+			if target.account_ref(&account_ref.address).is_ok() {
+				continue;
+			}
+			let password = match passwords.iter().find(|p| store.test_password(&account_ref, p).unwrap_or(false)) {
+				Some(password) => password,
+				None => {
+					eprintln!("No password given for 0x{:x}, skipping.", account_ref.address);
+					continue;
+				}
+			};
+			store.copy_account(&target, SecretVaultRef::Root, &account_ref, password, password)?;
+			target.set_name(&account_ref, store.name(&account_ref)?)?;
+			target.set_meta(&account_ref, store.meta(&account_ref)?)?;
+			migrated.push(account_ref.address);
+		}
+		Ok(format_accounts(&migrated))
 	} else {
 		Ok(format!("{}", USAGE))
 	}
@@ -80,3 +80,61 @@ fn cli_cmd() {
 			   "--vault-pwd", test_password]);
 	assert_eq!(output, "0x35f222d88b80151857a2877826d940104887376a94c1cbd2c8c7c192eb701df88a18a4ecb8b05b1466c5b3706042027b5e079fe3a3683e66d822b0e047aa3418\n");
 }
+
+#[test]
+fn cli_migrate() {
+	Command::new("cargo")
+		.arg("build")
+		.output()
+		.unwrap();
+
+	let dir = Builder::new().prefix("test-migrate").tempdir().unwrap();
+
+	let mut passwd = File::create(dir.path().join("test-password")).unwrap();
+	writeln!(passwd, "password").unwrap();
+
+	let mut db_passwd = File::create(dir.path().join("test-db-password")).unwrap();
+	writeln!(db_passwd, "db-password").unwrap();
+
+	let test_password_buf = dir.path().join("test-password");
+	let test_password: &str = test_password_buf.to_str().unwrap();
+	let test_db_password_buf = dir.path().join("test-db-password");
+	let test_db_password: &str = test_db_password_buf.to_str().unwrap();
+	let keys_dir_buf = dir.path().join("keys");
+	let keys_dir: &str = keys_dir_buf.to_str().unwrap();
+	let db_dir = format!("db:{}", dir.path().join("keys.db").to_str().unwrap());
+
+	// reading a missing database fails instead of creating it
+	let output = Command::new("cargo")
+		.args(&["run", "--", "list", "--dir", &db_dir, "--db-pwd", test_db_password])
+		.output()
+		.unwrap();
+	assert!(!output.status.success());
+	assert!(!dir.path().join("keys.db").exists());
+
+	run(&["insert", "7d29fab185a33e2cd955812397354c472d2b84615b645aa135ff539f6b0d70d5",
+		  test_password,
+		  "--dir", keys_dir]);
+
+	let output = run(&["migrate", test_password,
+			   "--dir", keys_dir,
+			   "--to", &db_dir,
+			   "--to-db-pwd", test_db_password]);
+	assert_eq!(output, " 0: 0xa8fa5dd30a87bb9e3288d604eb74949c515ab66e\n");
+
+	let output = run(&["list",
+			   "--dir", &db_dir,
+			   "--db-pwd", test_db_password]);
+	assert_eq!(output, " 0: 0xa8fa5dd30a87bb9e3288d604eb74949c515ab66e\n");
+
+	run(&["sign", "a8fa5dd30a87bb9e3288d604eb74949c515ab66e",
+		  test_password,
+		  "7d29fab185a33e2cd955812397354c472d2b84615b645aa135ff539f6b0d70d5",
+		  "--dir", &db_dir,
+		  "--db-pwd", test_db_password]);
+
+	let output = run(&["audit-log",
+			   "--dir", &db_dir,
+			   "--db-pwd", test_db_password]);
+	assert!(output.trim().ends_with("0xa8fa5dd30a87bb9e3288d604eb74949c515ab66e Sign OK"), "unexpected audit log: {}", output);
+}
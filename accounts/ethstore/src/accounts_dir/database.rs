@@ -0,0 +1,394 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use parking_lot::Mutex;
+use kvdb_rocksdb::{Database, DatabaseConfig};
+use crypto::{self, Keccak256};
+use crypto::publickey::Address;
+use {json, SafeAccount, Error};
+use json::Uuid;
+use random::Random;
+use super::super::account::Crypto;
+use super::{KeyDirectory, KeyOperation, VaultKey};
+
+/// Column of the database key and the audit log index.
+const COL_META: u32 = 0;
+/// Column of the key files, by file name.
+const COL_ACCOUNTS: u32 = 1;
+/// Column of the audit log entries, by index.
+const COL_AUDIT: u32 = 2;
+const NUM_COLUMNS: u32 = 3;
+
+/// Key of the encrypted database key hash, checked when the database is opened.
+const KEY_FILE_KEY: &[u8] = b"key";
+/// Key of the index of the next audit log entry.
+const AUDIT_INDEX_KEY: &[u8] = b"audit_index";
+/// Key of the audit log key, encrypted with the database key.
+const AUDIT_KEY_KEY: &[u8] = b"audit_key";
+
+/// Length of an encoded `AuditEntry`.
+const AUDIT_ENTRY_LEN: usize = 8 + 20 + 1 + 1;
+/// Length of a stored audit log entry: the IV, the encrypted `AuditEntry` and the MAC.
+const ENCRYPTED_AUDIT_ENTRY_LEN: usize = 16 + AUDIT_ENTRY_LEN + 32;
+
+/// An entry of the audit log of a `DatabaseDirectory`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+	/// Seconds since the Unix epoch when the key was used.
+	pub timestamp: u64,
+	/// Address of the used account.
+	pub address: Address,
+	/// What the key was used for.
+	pub operation: KeyOperation,
+	/// Whether the operation succeeded, i.e. the right password was given.
+	pub success: bool,
+}
+
+impl AuditEntry {
+	fn encode(&self) -> Vec<u8> {
+		let mut bytes = Vec::with_capacity(AUDIT_ENTRY_LEN);
+		bytes.extend_from_slice(&self.timestamp.to_be_bytes());
+		bytes.extend_from_slice(self.address.as_bytes());
+		bytes.push(match self.operation {
+			KeyOperation::Sign => 0,
+			KeyOperation::Decrypt => 1,
+			KeyOperation::Copy => 2,
+		});
+		bytes.push(self.success as u8);
+		bytes
+	}
+
+	fn decode(bytes: &[u8]) -> Result<Self, Error> {
+		if bytes.len() != AUDIT_ENTRY_LEN {
+			return Err(Error::Custom(format!("Invalid audit log entry length: {}", bytes.len())));
+		}
+		let mut timestamp = [0u8; 8];
+		timestamp.copy_from_slice(&bytes[..8]);
+		let operation = match bytes[28] {
+			0 => KeyOperation::Sign,
+			1 => KeyOperation::Decrypt,
+			2 => KeyOperation::Copy,
+			op => return Err(Error::Custom(format!("Invalid audit log operation: {}", op))),
+		};
+
+		Ok(AuditEntry {
+			timestamp: u64::from_be_bytes(timestamp),
+			address: Address::from_slice(&bytes[8..28]),
+			operation,
+			success: bytes[29] != 0,
+		})
+	}
+}
+
+/// Keys directory stored in a single RocksDB database.
+///
+/// Key files are stored like in a vault, with their address and metadata encrypted with the
+/// database key. Every use of a key is recorded in the audit log, whose entries are encrypted with
+/// a random key itself encrypted with the database key. So the database doesn't reveal which
+/// accounts it holds nor when they are used, only how many there are.
+pub struct DatabaseDirectory {
+	db: Database,
+	key: VaultKey,
+	// AES-128-CTR key and MAC key of the audit log entries
+	audit_key: [u8; 32],
+	// serializes the audit log writes
+	audit_index: Mutex<u64>,
+}
+
+impl DatabaseDirectory {
+	/// Create a new database at the given path, encrypted with given key
+	pub fn create<P>(path: P, key: VaultKey) -> Result<Self, Error> where P: AsRef<Path> {
+		if path.as_ref().exists() {
+			return Err(Error::CreationFailed);
+		}
+
+		let dir = Self::open_db(path, key)?;
+		let password_hash = dir.key.password.as_bytes().keccak256();
+		let crypto: json::Crypto = Crypto::with_plain(&password_hash, &dir.key.password, dir.key.iterations)?.into();
+		let key_file = json::VaultFile { crypto, meta: None };
+		let mut bytes = Vec::new();
+		key_file.write(&mut bytes).map_err(|e| Error::Custom(format!("{:?}", e)))?;
+
+		let mut transaction = dir.db.transaction();
+		transaction.put(COL_META, KEY_FILE_KEY, &bytes);
+		dir.db.write(transaction)?;
+		dir.load_audit_key()
+	}
+
+	/// Open an existing database at the given path, checking that the key matches
+	pub fn at<P>(path: P, key: VaultKey) -> Result<Self, Error> where P: AsRef<Path> {
+		if !path.as_ref().is_dir() {
+			return Err(Error::CreationFailed);
+		}
+
+		let dir = Self::open_db(path, key)?;
+		let bytes = dir.db.get(COL_META, KEY_FILE_KEY)?.ok_or(Error::CreationFailed)?;
+		let key_file = json::VaultFile::load(&bytes[..]).map_err(|e| Error::Custom(format!("{:?}", e)))?;
+		let crypto: Crypto = key_file.crypto.into();
+		let password_hash = dir.key.password.as_bytes().keccak256();
+		if password_hash != crypto.decrypt(&dir.key.password)?.as_slice() {
+			return Err(Error::InvalidPassword);
+		}
+		dir.load_audit_key()
+	}
+
+	fn open_db<P>(path: P, key: VaultKey) -> Result<Self, Error> where P: AsRef<Path> {
+		let path = path.as_ref();
+		let path_str = path.to_str().ok_or_else(|| Error::Custom(format!("Invalid database path {:?}", path)))?;
+		let db = Database::open(&DatabaseConfig::with_columns(NUM_COLUMNS), path_str)?;
+		let audit_index = match db.get(COL_META, AUDIT_INDEX_KEY)? {
+			Some(ref bytes) if bytes.len() == 8 => {
+				let mut index = [0u8; 8];
+				index.copy_from_slice(bytes);
+				u64::from_be_bytes(index)
+			},
+			_ => 0,
+		};
+
+		Ok(DatabaseDirectory {
+			db,
+			key,
+			audit_key: [0u8; 32],
+			audit_index: Mutex::new(audit_index),
+		})
+	}
+
+	/// Decrypt the audit log key, generating it if the database has none yet.
+	fn load_audit_key(mut self) -> Result<Self, Error> {
+		match self.db.get(COL_META, AUDIT_KEY_KEY)? {
+			Some(bytes) => {
+				let key_file = json::VaultFile::load(&bytes[..]).map_err(|e| Error::Custom(format!("{:?}", e)))?;
+				let crypto: Crypto = key_file.crypto.into();
+				let audit_key = crypto.decrypt(&self.key.password)?;
+				if audit_key.len() != self.audit_key.len() {
+					return Err(Error::Custom("Invalid audit log key in the database".into()));
+				}
+				self.audit_key.copy_from_slice(&audit_key);
+			},
+			None => {
+				self.audit_key = Random::random();
+				let crypto: json::Crypto = Crypto::with_plain(&self.audit_key, &self.key.password, self.key.iterations)?.into();
+				let key_file = json::VaultFile { crypto, meta: None };
+				let mut bytes = Vec::new();
+				key_file.write(&mut bytes).map_err(|e| Error::Custom(format!("{:?}", e)))?;
+
+				let mut transaction = self.db.transaction();
+				transaction.put(COL_META, AUDIT_KEY_KEY, &bytes);
+				self.db.write(transaction)?;
+			},
+		}
+		Ok(self)
+	}
+
+	/// Read the whole audit log, oldest entries first
+	pub fn audit_log(&self) -> Result<Vec<AuditEntry>, Error> {
+		// entries are keyed by their big endian index, so they are iterated in order
+		self.db.iter(COL_AUDIT)
+			.map(|(_, value)| self.decrypt_audit_entry(&value))
+			.collect()
+	}
+
+	fn encrypt_audit_entry(&self, entry: &AuditEntry) -> Result<Vec<u8>, Error> {
+		let iv: [u8; 16] = Random::random();
+		let mut ciphertext = [0u8; AUDIT_ENTRY_LEN];
+		crypto::aes::encrypt_128_ctr(&self.audit_key[..16], &iv, &entry.encode(), &mut ciphertext)?;
+		let mac = crypto::derive_mac(&self.audit_key[16..], &ciphertext).keccak256();
+
+		let mut bytes = Vec::with_capacity(ENCRYPTED_AUDIT_ENTRY_LEN);
+		bytes.extend_from_slice(&iv);
+		bytes.extend_from_slice(&ciphertext);
+		bytes.extend_from_slice(&mac);
+		Ok(bytes)
+	}
+
+	fn decrypt_audit_entry(&self, bytes: &[u8]) -> Result<AuditEntry, Error> {
+		if bytes.len() != ENCRYPTED_AUDIT_ENTRY_LEN {
+			return Err(Error::Custom(format!("Invalid audit log entry length: {}", bytes.len())));
+		}
+		let (iv, rest) = bytes.split_at(16);
+		let (ciphertext, mac) = rest.split_at(AUDIT_ENTRY_LEN);
+		if !crypto::is_equal(&crypto::derive_mac(&self.audit_key[16..], ciphertext).keccak256(), mac) {
+			return Err(Error::Custom("Invalid audit log entry MAC".into()));
+		}
+
+		let mut plain = [0u8; AUDIT_ENTRY_LEN];
+		crypto::aes::decrypt_128_ctr(&self.audit_key[..16], iv, ciphertext, &mut plain)?;
+		AuditEntry::decode(&plain)
+	}
+
+	fn read(&self, filename: String, bytes: &[u8]) -> Result<SafeAccount, Error> {
+		let key_file = json::VaultKeyFile::load(bytes).map_err(|e| Error::Custom(format!("{:?}", e)))?;
+		SafeAccount::from_vault_file(&self.key.password, key_file, Some(filename))
+	}
+
+	fn write(&self, mut account: SafeAccount) -> Result<SafeAccount, Error> {
+		let filename = account.filename.clone().unwrap_or_else(|| Uuid::from(account.id).into());
+		account.filename = Some(filename.clone());
+
+		let key_file = account.clone().into_vault_file(self.key.iterations, &self.key.password)?;
+		let mut bytes = Vec::new();
+		key_file.write(&mut bytes).map_err(|e| Error::Custom(format!("{:?}", e)))?;
+
+		let mut transaction = self.db.transaction();
+		transaction.put(COL_ACCOUNTS, filename.as_bytes(), &bytes);
+		self.db.write(transaction)?;
+		Ok(account)
+	}
+}
+
+impl KeyDirectory for DatabaseDirectory {
+	fn load(&self) -> Result<Vec<SafeAccount>, Error> {
+		self.db.iter(COL_ACCOUNTS)
+			.map(|(key, value)| {
+				let filename = String::from_utf8(key.into_vec())
+					.map_err(|_| Error::Custom("Invalid key file name in the database".into()))?;
+				self.read(filename, &value)
+			})
+			.collect()
+	}
+
+	fn insert(&self, account: SafeAccount) -> Result<SafeAccount, Error> {
+		self.write(account)
+	}
+
+	fn update(&self, account: SafeAccount) -> Result<SafeAccount, Error> {
+		self.write(account)
+	}
+
+	fn remove(&self, account: &SafeAccount) -> Result<(), Error> {
+		let filename = match account.filename {
+			Some(ref filename) => filename.clone(),
+			None => self.load()?.into_iter()
+				.find(|acc| acc == account)
+				.and_then(|acc| acc.filename)
+				.ok_or(Error::InvalidAccount)?,
+		};
+
+		let mut transaction = self.db.transaction();
+		transaction.delete(COL_ACCOUNTS, filename.as_bytes());
+		self.db.write(transaction)?;
+		Ok(())
+	}
+
+	fn unique_repr(&self) -> Result<u64, Error> {
+		// the file names are never reused, so they identify the key files without decrypting them
+		let mut val = 0u64;
+		for (key, _) in self.db.iter(COL_ACCOUNTS) {
+			let mut hash = [0u8; 8];
+			hash.copy_from_slice(&key.keccak256()[..8]);
+			val ^= u64::from_be_bytes(hash);
+		}
+		Ok(val)
+	}
+
+	fn audit(&self, address: &Address, operation: KeyOperation, success: bool) -> Result<(), Error> {
+		let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)
+			.map(|duration| duration.as_secs())
+			.unwrap_or(0);
+		let entry = AuditEntry { timestamp, address: *address, operation, success };
+		let bytes = self.encrypt_audit_entry(&entry)?;
+
+		let mut index = self.audit_index.lock();
+		let mut transaction = self.db.transaction();
+		transaction.put(COL_AUDIT, &index.to_be_bytes(), &bytes);
+		transaction.put(COL_META, AUDIT_INDEX_KEY, &(*index + 1).to_be_bytes());
+		self.db.write(transaction)?;
+		*index += 1;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	extern crate tempfile;
+
+	use super::{AuditEntry, DatabaseDirectory, COL_AUDIT};
+	use accounts_dir::{KeyDirectory, KeyOperation, VaultKey};
+	use crypto::publickey::{Generator, Random};
+	use ethkey::Password;
+	use SafeAccount;
+	use self::tempfile::TempDir;
+
+	fn account(password: &Password) -> SafeAccount {
+		let keypair = Random.generate();
+		SafeAccount::create(&keypair, [0u8; 16], password, 1024, "Test".to_owned(), "{}".to_owned()).unwrap()
+	}
+
+	#[test]
+	fn should_store_accounts() {
+		// given
+		let temp_path = TempDir::new().unwrap();
+		let path = temp_path.path().join("keys.db");
+		let key = VaultKey::new(&"db password".into(), 1024);
+		let password = "password".into();
+		let account = account(&password);
+
+		// when
+		let dir = DatabaseDirectory::create(&path, key.clone()).unwrap();
+		let inserted = dir.insert(account.clone()).unwrap();
+		drop(dir);
+		let dir = DatabaseDirectory::at(&path, key).unwrap();
+		let loaded = dir.load().unwrap();
+
+		// then
+		assert_eq!(loaded, vec![inserted.clone()]);
+		assert_eq!(loaded[0].address, account.address);
+		assert_eq!(loaded[0].name, "Test");
+		assert!(loaded[0].check_password(&password));
+
+		dir.remove(&inserted).unwrap();
+		assert!(dir.load().unwrap().is_empty());
+	}
+
+	#[test]
+	fn should_not_open_with_wrong_key() {
+		let temp_path = TempDir::new().unwrap();
+		let path = temp_path.path().join("keys.db");
+		DatabaseDirectory::create(&path, VaultKey::new(&"db password".into(), 1024)).unwrap();
+
+		assert!(DatabaseDirectory::at(&path, VaultKey::new(&"other".into(), 1024)).is_err());
+		assert!(DatabaseDirectory::create(&path, VaultKey::new(&"db password".into(), 1024)).is_err());
+	}
+
+	#[test]
+	fn should_keep_audit_log() {
+		// given
+		let temp_path = TempDir::new().unwrap();
+		let path = temp_path.path().join("keys.db");
+		let key = VaultKey::new(&"db password".into(), 1024);
+		let address = account(&"password".into()).address;
+
+		// when
+		let dir = DatabaseDirectory::create(&path, key.clone()).unwrap();
+		dir.audit(&address, KeyOperation::Sign, true).unwrap();
+		drop(dir);
+		let dir = DatabaseDirectory::at(&path, key).unwrap();
+		dir.audit(&address, KeyOperation::Decrypt, false).unwrap();
+
+		// then
+		let log: Vec<_> = dir.audit_log().unwrap().into_iter()
+			.map(|AuditEntry { address, operation, success, .. }| (address, operation, success))
+			.collect();
+		assert_eq!(log, vec![
+			(address, KeyOperation::Sign, true),
+			(address, KeyOperation::Decrypt, false),
+		]);
+		// the stored entries don't reveal the account
+		assert!(dir.db.iter(COL_AUDIT).all(|(_, value)| !value.windows(20).any(|w| w == address.as_bytes())));
+	}
+}
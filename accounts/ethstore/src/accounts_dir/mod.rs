@@ -17,9 +17,11 @@
 //! Accounts Directory
 
 use ethkey::Password;
+use crypto::publickey::Address;
 use std::path::{PathBuf};
 use {SafeAccount, Error};
 
+mod database;
 mod disk;
 mod memory;
 mod vault;
@@ -44,6 +46,17 @@ pub struct VaultKey {
 	pub iterations: u32,
 }
 
+/// Use of an account's secret key, recorded in the directory's audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyOperation {
+	/// Message signed with the key
+	Sign,
+	/// Message decrypted with the key
+	Decrypt,
+	/// Key copied to another store
+	Copy,
+}
+
 /// Keys directory
 pub trait KeyDirectory: Send + Sync {
 	/// Read keys from directory
@@ -60,6 +73,8 @@ pub trait KeyDirectory: Send + Sync {
 	fn as_vault_provider(&self) -> Option<&dyn VaultKeyDirectoryProvider> { None }
 	/// Unique representation of directory account collection
 	fn unique_repr(&self) -> Result<u64, Error>;
+	/// Record a use of an account's key, if the directory keeps an audit log
+	fn audit(&self, _address: &Address, _operation: KeyOperation, _success: bool) -> Result<(), Error> { Ok(()) }
 }
 
 /// Vaults provider
@@ -90,6 +105,7 @@ pub trait VaultKeyDirectory: KeyDirectory {
 	fn set_meta(&self, meta: &str) -> Result<(), Error>;
 }
 
+pub use self::database::{DatabaseDirectory, AuditEntry};
 pub use self::disk::{RootDiskDirectory, DiskKeyFileManager, KeyFileManager};
 pub use self::memory::MemoryDirectory;
 pub use self::vault::VaultDiskDirectory;
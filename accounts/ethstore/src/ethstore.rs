@@ -23,7 +23,7 @@ use crypto::KEY_ITERATIONS;
 use random::Random;
 use crypto::publickey::{Signature, Address, Message, Secret, Public, KeyPair, ExtendedKeyPair};
 use ethkey::Password;
-use accounts_dir::{KeyDirectory, VaultKeyDirectory, VaultKey, SetKeyError};
+use accounts_dir::{KeyDirectory, KeyOperation, VaultKeyDirectory, VaultKey, SetKeyError};
 use account::SafeAccount;
 use presale::PresaleWallet;
 use json::{self, Uuid, OpaqueKeyFile};
@@ -99,7 +99,8 @@ impl SimpleSecretStore for EthStore {
 	}
 
 	fn sign(&self, account: &StoreAccountRef, password: &Password, message: &Message) -> Result<Signature, Error> {
-		self.get(account)?.sign(password, message)
+		let result = self.get(account)?.sign(password, message);
+		self.store.audit(account, KeyOperation::Sign, result)
 	}
 
 	fn sign_derived(&self, account_ref: &StoreAccountRef, password: &Password, derivation: Derivation, message: &Message)
@@ -113,8 +114,8 @@ impl SimpleSecretStore for EthStore {
 	}
 
 	fn decrypt(&self, account: &StoreAccountRef, password: &Password, shared_mac: &[u8], message: &[u8]) -> Result<Vec<u8>, Error> {
-		let account = self.get(account)?;
-		account.decrypt(password, shared_mac, message)
+		let result = self.get(account)?.decrypt(password, shared_mac, message);
+		self.store.audit(account, KeyOperation::Decrypt, result)
 	}
 
 	fn create_vault(&self, name: &str, password: &Password) -> Result<(), Error> {
@@ -185,8 +186,8 @@ impl SecretStore for EthStore {
 	}
 
 	fn copy_account(&self, new_store: &dyn SimpleSecretStore, new_vault: SecretVaultRef, account: &StoreAccountRef, password: &Password, new_password: &Password) -> Result<(), Error> {
-		let account = self.get(account)?;
-		let secret = account.crypto.secret(password)?;
+		let result = self.get(account)?.crypto.secret(password);
+		let secret = self.store.audit(account, KeyOperation::Copy, result)?;
 		new_store.insert_account(new_vault, secret, new_password)?;
 		Ok(())
 	}
@@ -374,6 +375,12 @@ impl EthMultiStore {
 		)
 	}
 
+	/// Records the outcome of an operation with the account's key in the directory's audit log.
+	fn audit<T>(&self, account: &StoreAccountRef, operation: KeyOperation, result: Result<T, Error>) -> Result<T, Error> {
+		self.dir.audit(&account.address, operation, result.is_ok())?;
+		result
+	}
+
 	fn import(&self, vault: SecretVaultRef, account: SafeAccount) -> Result<StoreAccountRef, Error> {
 		// save to file
 		let account = match vault {
@@ -488,12 +495,13 @@ impl SimpleSecretStore for EthMultiStore {
 		-> Result<Signature, Error>
 	{
 		let accounts = self.get_matching(&account_ref, password)?;
-		for account in accounts {
-			let extended = self.generate(account.crypto.secret(password)?, derivation)?;
-			let secret = extended.secret().as_raw();
-			return Ok(crypto::publickey::sign(&secret, message)?)
-		}
-		Err(Error::InvalidPassword)
+		let result = match accounts.first() {
+			Some(account) => account.crypto.secret(password)
+				.and_then(|secret| self.generate(secret, derivation))
+				.and_then(|extended| Ok(crypto::publickey::sign(extended.secret().as_raw(), message)?)),
+			None => Err(Error::InvalidPassword),
+		};
+		self.audit(account_ref, KeyOperation::Sign, result)
 	}
 
 	fn account_ref(&self, address: &Address) -> Result<StoreAccountRef, Error> {
@@ -550,18 +558,20 @@ impl SimpleSecretStore for EthMultiStore {
 
 	fn sign(&self, account: &StoreAccountRef, password: &Password, message: &Message) -> Result<Signature, Error> {
 		let accounts = self.get_matching(account, password)?;
-		match accounts.first() {
-			Some(ref account) => account.sign(password, message),
+		let result = match accounts.first() {
+			Some(ref acc) => acc.sign(password, message),
 			None => Err(Error::InvalidPassword),
-		}
+		};
+		self.audit(account, KeyOperation::Sign, result)
 	}
 
 	fn decrypt(&self, account: &StoreAccountRef, password: &Password, shared_mac: &[u8], message: &[u8]) -> Result<Vec<u8>, Error> {
 		let accounts = self.get_matching(account, password)?;
-		match accounts.first() {
-			Some(ref account) => account.decrypt(password, shared_mac, message),
+		let result = match accounts.first() {
+			Some(ref acc) => acc.decrypt(password, shared_mac, message),
 			None => Err(Error::InvalidPassword),
-		}
+		};
+		self.audit(account, KeyOperation::Decrypt, result)
 	}
 
 	fn agree(&self, account: &StoreAccountRef, password: &Password, other: &Public) -> Result<Secret, Error> {
@@ -689,7 +699,7 @@ impl SimpleSecretStore for EthMultiStore {
 mod tests {
 	extern crate tempfile;
 
-	use accounts_dir::{KeyDirectory, MemoryDirectory, RootDiskDirectory};
+	use accounts_dir::{KeyDirectory, KeyOperation, MemoryDirectory, RootDiskDirectory, DatabaseDirectory, VaultKey};
 	use crypto::publickey::{Random, Generator, KeyPair};
 	use secret_store::{SimpleSecretStore, SecretStore, SecretVaultRef, StoreAccountRef, Derivation};
 	use super::{EthStore, EthMultiStore};
@@ -1127,4 +1137,34 @@ mod tests {
 		// then
 		assert!(exported.is_ok(), "Should export single account: {:?}", exported);
 	}
+
+	#[test]
+	fn should_audit_key_usage_in_database_directory() {
+		// given
+		let temp_path = TempDir::new().unwrap();
+		let path = temp_path.path().join("keys.db");
+		let key = VaultKey::new(&"db password".into(), 1024);
+		let source = EthStore::open(Box::new(DatabaseDirectory::create(&path, key.clone()).unwrap())).unwrap();
+		let keypair = keypair();
+		let account = source.insert_account(SecretVaultRef::Root, keypair.secret().clone(), &"test".into()).unwrap();
+		let message = [1u8; 32].into();
+
+		// when
+		assert!(source.sign(&account, &"test".into(), &message).is_ok());
+		assert!(source.sign(&account, &"wrong".into(), &message).is_err());
+		let target = store();
+		source.copy_account(&target, SecretVaultRef::Root, &account, &"test".into(), &"new".into()).unwrap();
+		drop(source);
+
+		// then
+		assert!(target.sign(&account, &"new".into(), &message).is_ok());
+		let log: Vec<_> = DatabaseDirectory::at(&path, key).unwrap().audit_log().unwrap().into_iter()
+			.map(|entry| (entry.address, entry.operation, entry.success))
+			.collect();
+		assert_eq!(log, vec![
+			(keypair.address(), KeyOperation::Sign, true),
+			(keypair.address(), KeyOperation::Sign, false),
+			(keypair.address(), KeyOperation::Copy, true),
+		]);
+	}
 }
@@ -19,6 +19,7 @@
 #![warn(missing_docs)]
 
 extern crate dir;
+extern crate kvdb_rocksdb;
 extern crate libc;
 extern crate parking_lot;
 extern crate rand;
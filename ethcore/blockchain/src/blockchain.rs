@@ -144,8 +144,8 @@ pub trait BlockProvider {
 
 	/// Get a list of uncles for a given block.
 	/// Returns None if block does not exist.
-	fn uncles(&self, hash: &H256) -> Option<Vec<Header>> {
-		self.block_body(hash).map(|body| body.uncles())
+	fn uncles(&self, hash: &H256, eip1559_transition: BlockNumber) -> Option<Vec<Header>> {
+		self.block_body(hash).map(|body| body.uncles(eip1559_transition))
 	}
 
 	/// Get a list of uncle hashes for a given block.
@@ -247,6 +247,8 @@ pub struct BlockChain {
 	pending_block_hashes: RwLock<HashMap<BlockNumber, H256>>,
 	pending_block_details: RwLock<HashMap<H256, BlockDetails>>,
 	pending_transaction_addresses: RwLock<HashMap<H256, Option<TransactionAddress>>>,
+
+	eip1559_transition: BlockNumber,
 }
 
 impl BlockProvider for BlockChain {
@@ -480,7 +482,7 @@ impl<'a> Iterator for AncestryWithMetadataIter<'a> {
 		} else {
 			let details = self.chain.block_details(&self.current);
 			let header = self.chain.block_header_data(&self.current)
-				.map(|h| h.decode().expect("Stored block header data is valid RLP; qed"));
+				.map(|h| h.decode(self.chain.eip1559_transition).expect("Stored block header data is valid RLP; qed"));
 
 			match (details, header) {
 				(Some(details), Some(header)) => {
@@ -570,6 +572,7 @@ impl BlockChain {
 			pending_block_hashes: RwLock::new(HashMap::new()),
 			pending_block_details: RwLock::new(HashMap::new()),
 			pending_transaction_addresses: RwLock::new(HashMap::new()),
+			eip1559_transition: config.eip1559_transition,
 		};
 
 		// load best block
@@ -619,7 +622,7 @@ impl BlockChain {
 			let mut best_block = bc.best_block.write();
 			*best_block = BestBlock {
 				total_difficulty: best_block_total_difficulty,
-				header: best_block_rlp.decode_header(),
+				header: best_block_rlp.decode_header(bc.eip1559_transition),
 				block: best_block_rlp,
 			};
 		}
@@ -899,7 +902,7 @@ impl BlockChain {
 		let mut best_block = self.best_block.write();
 		*best_block = BestBlock {
 			total_difficulty: best_block_total_difficulty,
-			header: best_block_rlp.decode_header(),
+			header: best_block_rlp.decode_header(self.eip1559_transition),
 			block: best_block_rlp,
 		};
 	}
@@ -1231,7 +1234,7 @@ impl BlockChain {
 				batch.put(db::COL_EXTRA, b"best", update.info.hash.as_bytes());
 				*best_block = Some(BestBlock {
 					total_difficulty: update.info.total_difficulty,
-					header: update.block.decode_header(),
+					header: update.block.decode_header(self.eip1559_transition),
 					block: update.block,
 				});
 			}
@@ -1530,6 +1533,11 @@ impl BlockChain {
 		self.best_block.read().header.clone()
 	}
 
+	/// Get the block number from which headers carry the EIP-1559 base fee.
+	pub fn eip1559_transition(&self) -> BlockNumber {
+		self.eip1559_transition
+	}
+
 	/// Get current cache size.
 	pub fn cache_size(&self) -> CacheSize {
 		let mut ops = new_malloc_size_ops();
@@ -2195,6 +2203,7 @@ mod tests {
 				LogEntry { address: Default::default(), topics: vec![], data: vec![1], },
 				LogEntry { address: Default::default(), topics: vec![], data: vec![2], },
 			],
+			tx_type: Default::default(),
 		},
 		Receipt {
 			outcome: TransactionOutcome::StateRoot(H256::zero()),
@@ -2203,6 +2212,7 @@ mod tests {
 			logs: vec![
 				LogEntry { address: Default::default(), topics: vec![], data: vec![3], },
 			],
+			tx_type: Default::default(),
 		}]);
 		insert_block(&db, &bc, b2.last().encoded(), vec![
 			Receipt {
@@ -2212,6 +2222,7 @@ mod tests {
 				logs: vec![
 					LogEntry { address: Default::default(), topics: vec![], data: vec![4], },
 				],
+				tx_type: Default::default(),
 			}
 		]);
 		insert_block(&db, &bc, b3.last().encoded(), vec![
@@ -2222,6 +2233,7 @@ mod tests {
 				logs: vec![
 					LogEntry { address: Default::default(), topics: vec![], data: vec![5], },
 				],
+				tx_type: Default::default(),
 			}
 		]);
 
@@ -189,6 +189,20 @@ pub trait EngineClient: Sync + Send + ChainInfo {
 
 	/// Create block and queue it for sealing. Will return None if a block is already pending.
 	fn create_pending_block_at(&self, txns: Vec<SignedTransaction>, timestamp: u64, block_number: u64) -> Option<Header>;
+
+	/// Get the data the engine persisted with `set_engine_data`, if any.
+	fn engine_data(&self) -> Option<Bytes>;
+
+	/// Persist engine data in the client database, replacing the previously stored data.
+	/// The data is kept across restarts of the client.
+	fn set_engine_data(&self, data: Bytes);
+
+	/// Add a peer, given as enode URL, to which the network keeps a connection.
+	fn add_reserved_peer(&self, enode: String);
+
+	/// Remove a peer, given as enode URL, which was added with `add_reserved_peer`.
+	/// Peers which were reserved before they were added are kept.
+	fn remove_reserved_peer(&self, enode: String);
 }
 
 /// Provides methods to import block into blockchain
@@ -355,6 +369,9 @@ pub trait BlockChainClient:
 	/// List all ready transactions that should be propagated to other peers.
 	fn transactions_to_propagate(&self) -> Vec<Arc<VerifiedTransaction>>;
 
+	/// Get a transaction from the queue by its hash.
+	fn queued_transaction(&self, hash: &H256) -> Option<Arc<VerifiedTransaction>>;
+
 	/// Sorted list of transaction gas prices from at least last sample_size blocks.
 	fn gas_price_corpus(&self, sample_size: usize) -> stats::Corpus<U256> {
 		let mut h = self.chain_info().best_block_hash;
@@ -565,6 +582,16 @@ pub trait ChainNotify: Send + Sync {
 		// does nothing by default
 	}
 
+	/// fires when the engine requests a connection to be kept to the given peer
+	fn add_reserved_peer(&self, _enode: String) {
+		// does nothing by default
+	}
+
+	/// fires when the engine no longer requires a connection to the given peer
+	fn remove_reserved_peer(&self, _enode: String) {
+		// does nothing by default
+	}
+
 	/// fires when new block is about to be imported
 	/// implementations should be light
 	fn block_pre_import(&self, _bytes: &Bytes, _hash: &H256, _difficulty: &U256) {
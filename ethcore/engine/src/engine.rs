@@ -42,6 +42,7 @@ use machine::{
 	Machine,
 	executed_block::ExecutedBlock,
 };
+use stats::PrometheusRegistry;
 use vm::{EnvInfo, Schedule, ActionType, ActionValue};
 
 use crate::signer::EngineSigner;
@@ -157,6 +158,17 @@ pub trait Engine: Sync + Send {
 	/// Additional engine-specific information for the user/developer concerning `header`.
 	fn extra_info(&self, _header: &Header) -> BTreeMap<String, String> { BTreeMap::new() }
 
+	/// Number of faults observed from each validator, by kind of fault.
+	/// Empty for engines which do not hold validators accountable.
+	fn fault_counts(&self) -> BTreeMap<Address, BTreeMap<String, u64>> { BTreeMap::new() }
+
+	/// Engine-specific snapshot of the consensus state, for introspection by the user/developer.
+	/// `None` for engines which do not report their state.
+	fn consensus_status(&self) -> Option<serde_json::Value> { None }
+
+	/// Add engine-specific gauges, e.g. the current consensus round, to the node's metrics.
+	fn prometheus_metrics(&self, _registry: &mut PrometheusRegistry) {}
+
 	/// Maximum number of uncles a block is allowed to declare.
 	fn maximum_uncle_count(&self, _block: BlockNumber) -> usize { 0 }
 
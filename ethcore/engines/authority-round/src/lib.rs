@@ -57,6 +57,7 @@ use io::{IoContext, IoHandler, TimerToken, IoService};
 use itertools::{self, Itertools};
 use rand::rngs::OsRng;
 use rlp::{encode, Decodable, DecoderError, Encodable, RlpStream, Rlp};
+use stats::PrometheusRegistry;
 use ethereum_types::{H256, H512, H520, Address, U128, U256};
 use parity_bytes::Bytes;
 use parking_lot::{Mutex, RwLock};
@@ -624,6 +625,8 @@ struct EpochVerifier {
 	empty_steps_transition: u64,
 	/// First block for which a 2/3 quorum (instead of 1/2) is required.
 	two_thirds_majority_transition: BlockNumber,
+	/// First block whose header carries the base fee.
+	eip1559_transition: BlockNumber,
 }
 
 impl engine::EpochVerifier for EpochVerifier {
@@ -640,7 +643,7 @@ impl engine::EpochVerifier for EpochVerifier {
 		let mut finality_checker = RollingFinality::blank(signers, self.two_thirds_majority_transition);
 		let mut finalized = Vec::new();
 
-		let headers: Vec<Header> = Rlp::new(proof).as_list().ok()?;
+		let headers = Header::decode_rlp_list(&Rlp::new(proof), self.eip1559_transition).ok()?;
 
 		{
 			let mut push_header = |parent_header: &Header, header: Option<&Header>| {
@@ -1296,6 +1299,11 @@ impl Engine for AuthorityRound {
 		info
 	}
 
+	fn prometheus_metrics(&self, r: &mut PrometheusRegistry) {
+		r.register_gauge("aura_step", "The current AuRa step", self.step.inner.load());
+		r.register_gauge("aura_empty_steps", "Empty step messages waiting to be included in a block", self.empty_steps.lock().len());
+	}
+
 	fn maximum_uncle_count(&self, block: BlockNumber) -> usize {
 		if block >= self.maximum_uncle_count_transition {
 			self.maximum_uncle_count
@@ -1900,6 +1908,7 @@ impl Engine for AuthorityRound {
 					subchain_validators: list,
 					empty_steps_transition: self.empty_steps_transition,
 					two_thirds_majority_transition: self.two_thirds_majority_transition,
+					eip1559_transition: self.machine.params().eip1559_transition,
 				});
 
 				match finalize {
@@ -323,7 +323,7 @@ impl Clique {
 							return Err(Error::Block(BlockError::UnknownParent(last_parent_hash)));
 						}
 						Some(next) => {
-							chain.push_front(next.decode()?);
+							chain.push_front(next.decode(self.machine.params().eip1559_transition)?);
 						}
 					}
 				}
@@ -335,7 +335,7 @@ impl Clique {
 
 				let last_checkpoint_header = match c.block_header(BlockId::Hash(last_checkpoint_hash)) {
 					None => return Err(EngineError::CliqueMissingCheckpoint(last_checkpoint_hash).into()),
-					Some(header) => header.decode()?,
+					Some(header) => header.decode(self.machine.params().eip1559_transition)?,
 				};
 
 				let last_checkpoint_state = match block_state_by_hash.get_mut(&last_checkpoint_hash) {
@@ -0,0 +1,239 @@
+//! Generation of the chain spec of a local hbbft testnet.
+//!
+//! The spec is based on a template containing the POSDAO contracts, by default the engine's test
+//! spec. The contracts are set up in the genesis block by the initializer contract, whose
+//! constructor arguments include the genesis validators and their key generation data. These are
+//! replaced by the generated ones, and the validators and their staking addresses are funded.
+
+use ethabi::{ParamType, Token};
+use ethereum_types::{Address, H128, H512, U256};
+use keygen_history_helpers::KeyGenHistoryData;
+use rustc_hex::{FromHex, ToHex};
+use serde_json::{Map, Value};
+use std::fmt::Debug;
+use std::str::FromStr;
+
+/// The engine's test spec, used if no template is given.
+pub const DEFAULT_SPEC_TEMPLATE: &str = include_str!("../../res/honey_badger_bft.json");
+
+/// The address of the initializer contract setting up the POSDAO contracts in the genesis block.
+const INITIALIZER_ADDRESS: &str = "ff00000000000000000000000000000000000000";
+
+/// The number of words in the head of the initializer's constructor arguments.
+const INITIALIZER_HEAD_WORDS: usize = 13;
+
+/// The genesis balance of the validators' mining and staking addresses, in wei.
+const GENESIS_BALANCE: &str = "1000000000000000000000";
+
+/// The configurable engine parameters of the generated spec.
+pub struct SpecParams {
+	/// The minimum time between two blocks, in seconds.
+	pub minimum_block_time: u64,
+	/// The number of queued transactions which triggers the creation of a block.
+	pub transaction_queue_size_trigger: usize,
+}
+
+/// The types of the initializer contract's constructor parameters.
+fn initializer_params() -> Vec<ParamType> {
+	vec![
+		// The addresses of the POSDAO contracts.
+		ParamType::Array(Box::new(ParamType::Address)),
+		// The owner of the contracts.
+		ParamType::Address,
+		// The mining and staking addresses of the validators.
+		ParamType::Array(Box::new(ParamType::Address)),
+		ParamType::Array(Box::new(ParamType::Address)),
+		// The staking parameters.
+		ParamType::FixedArray(Box::new(ParamType::Uint(256)), 5),
+		// The public keys of the validators, two words each.
+		ParamType::Array(Box::new(ParamType::FixedBytes(32))),
+		// The internet addresses of the validators.
+		ParamType::Array(Box::new(ParamType::FixedBytes(16))),
+		// The Parts and Acks of the validators.
+		ParamType::Array(Box::new(ParamType::Bytes)),
+		ParamType::Array(Box::new(ParamType::Array(Box::new(ParamType::Bytes)))),
+	]
+}
+
+/// Splits the initializer's constructor into the contract code and the decoded arguments.
+///
+/// The ABI-encoded arguments are appended to the code, so they start at the first position from
+/// which the remaining data can be decoded and encodes to the same bytes again.
+fn split_constructor(constructor: &[u8]) -> Option<(&[u8], Vec<Token>)> {
+	let params = initializer_params();
+	let first_word = ethabi::encode(&[Token::Uint(U256::from(INITIALIZER_HEAD_WORDS * 32))]);
+	(0..constructor.len())
+		.filter(|pos| constructor[*pos..].starts_with(&first_word))
+		.filter_map(|pos| {
+			let tokens = ethabi::decode(&params, &constructor[pos..]).ok()?;
+			if ethabi::encode(&tokens) == &constructor[pos..] {
+				Some((&constructor[..pos], tokens))
+			} else {
+				None
+			}
+		})
+		.next()
+}
+
+fn parse_hex<T: FromStr>(value: &str) -> T
+where
+	T::Err: Debug,
+{
+	T::from_str(value.trim_start_matches("0x")).expect("Generated values must be valid hex")
+}
+
+fn address_tokens(addresses: &[String]) -> Token {
+	Token::Array(
+		addresses
+			.iter()
+			.map(|address| Token::Address(parse_hex(address)))
+			.collect(),
+	)
+}
+
+/// Returns the initializer's constructor with the arguments set to the given validators.
+fn initializer_constructor(constructor: &[u8], data: &KeyGenHistoryData) -> Vec<u8> {
+	let (code, mut args) = split_constructor(constructor)
+		.expect("The initializer constructor arguments of the spec template must be decodable");
+
+	args[2] = address_tokens(&data.validators);
+	args[3] = address_tokens(&data.staking_addresses);
+	args[5] = Token::Array(
+		data.public_keys
+			.iter()
+			.flat_map(|public| {
+				let public: H512 = parse_hex(public);
+				vec![
+					Token::FixedBytes(public.as_bytes()[..32].to_vec()),
+					Token::FixedBytes(public.as_bytes()[32..].to_vec()),
+				]
+			})
+			.collect(),
+	);
+	args[6] = Token::Array(
+		data.ip_addresses
+			.iter()
+			.map(|ip| Token::FixedBytes(parse_hex::<H128>(ip).as_bytes().to_vec()))
+			.collect(),
+	);
+	args[7] = Token::Array(data.parts.iter().cloned().map(Token::Bytes).collect());
+	args[8] = Token::Array(
+		data.acks
+			.iter()
+			.map(|acks| Token::Array(acks.iter().cloned().map(Token::Bytes).collect()))
+			.collect(),
+	);
+
+	let mut constructor = code.to_vec();
+	constructor.extend(ethabi::encode(&args));
+	constructor
+}
+
+/// Generates the chain spec from the given template, with the given engine parameters and
+/// genesis validators.
+pub fn generate_spec(template: &str, params: &SpecParams, data: &KeyGenHistoryData) -> Value {
+	let mut spec: Value =
+		serde_json::from_str(template).expect("The spec template must be valid JSON");
+
+	let engine_params = spec
+		.pointer_mut("/engine/hbbft/params")
+		.and_then(Value::as_object_mut)
+		.expect("The spec template must use the hbbft engine");
+	// The generated network runs the engine's timer, unlike unit tests.
+	engine_params.remove("isUnitTest");
+	// The validators run on one machine, each on its own port, so they can't be reached at the
+	// common port the engine would connect to. They are connected by the reserved peers file.
+	engine_params.remove("validatorPeerPort");
+	engine_params.insert(
+		"minimumBlockTime".into(),
+		Value::from(params.minimum_block_time),
+	);
+	engine_params.insert(
+		"transactionQueueSizeTrigger".into(),
+		Value::from(params.transaction_queue_size_trigger),
+	);
+
+	let accounts = spec
+		.get_mut("accounts")
+		.and_then(Value::as_object_mut)
+		.expect("The spec template must contain genesis accounts");
+
+	let initializer_address: Address = parse_hex(INITIALIZER_ADDRESS);
+	let initializer = accounts
+		.iter_mut()
+		.find(|(address, _)| {
+			Address::from_str(address.trim_start_matches("0x")).ok() == Some(initializer_address)
+		})
+		.and_then(|(_, account)| account.get_mut("constructor"))
+		.expect("The spec template must contain the initializer contract");
+	let constructor: Vec<u8> = initializer
+		.as_str()
+		.and_then(|constructor| constructor.trim_start_matches("0x").from_hex().ok())
+		.expect("The initializer constructor must be hex encoded");
+	*initializer = Value::String(format!(
+		"0x{}",
+		initializer_constructor(&constructor, data).to_hex::<String>()
+	));
+
+	for address in data.validators.iter().chain(&data.staking_addresses) {
+		let mut account = Map::new();
+		account.insert("balance".into(), Value::String(GENESIS_BALANCE.into()));
+		accounts.insert(address.clone(), Value::Object(account));
+	}
+
+	spec
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_initializer_constructor() {
+		let spec: Value = serde_json::from_str(DEFAULT_SPEC_TEMPLATE).unwrap();
+		let constructor: Vec<u8> = spec["accounts"]["0xFF00000000000000000000000000000000000000"]
+			["constructor"]
+			.as_str()
+			.unwrap()
+			.trim_start_matches("0x")
+			.from_hex()
+			.unwrap();
+		let (code, args) = split_constructor(&constructor).unwrap();
+
+		let data = KeyGenHistoryData {
+			validators: vec![format!("{:?}", Address::from_low_u64_be(10))],
+			staking_addresses: vec![format!("{:?}", Address::from_low_u64_be(1))],
+			public_keys: vec![format!("{:?}", H512::repeat_byte(7))],
+			ip_addresses: vec![format!("{:?}", H128::from_low_u64_be(1))],
+			parts: vec![vec![1, 2, 3]],
+			acks: vec![vec![vec![4, 5], vec![6]]],
+		};
+		let generated = initializer_constructor(&constructor, &data);
+		let (generated_code, generated_args) = split_constructor(&generated).unwrap();
+
+		// The contract code, addresses and staking parameters are kept.
+		assert_eq!(generated_code, code);
+		assert_eq!(generated_args[0], args[0]);
+		assert_eq!(generated_args[1], args[1]);
+		assert_eq!(generated_args[4], args[4]);
+
+		assert_eq!(
+			generated_args[2],
+			Token::Array(vec![Token::Address(Address::from_low_u64_be(10))])
+		);
+		assert_eq!(
+			generated_args[5],
+			Token::Array(vec![
+				Token::FixedBytes(vec![7; 32]),
+				Token::FixedBytes(vec![7; 32])
+			])
+		);
+		assert_eq!(
+			generated_args[8],
+			Token::Array(vec![Token::Array(vec![
+				Token::Bytes(vec![4, 5]),
+				Token::Bytes(vec![6])
+			])])
+		);
+	}
+}
@@ -112,21 +112,22 @@ pub fn enodes_to_pub_keys(
 	)
 }
 
+/// The genesis validators with their key generation data, in the order expected by the contracts.
 #[derive(Serialize, Deserialize)]
-struct KeyGenHistoryData {
-	validators: Vec<String>,
-	staking_addresses: Vec<String>,
-	public_keys: Vec<String>,
-	ip_addresses: Vec<String>,
-	parts: Vec<Vec<u8>>,
-	acks: Vec<Vec<Vec<u8>>>,
+pub struct KeyGenHistoryData {
+	pub validators: Vec<String>,
+	pub staking_addresses: Vec<String>,
+	pub public_keys: Vec<String>,
+	pub ip_addresses: Vec<String>,
+	pub parts: Vec<Vec<u8>>,
+	pub acks: Vec<Vec<Vec<u8>>>,
 }
 
 pub fn key_sync_history_data(
 	parts: BTreeMap<Public, Part>,
 	acks: BTreeMap<Public, Vec<PartOutcome>>,
-	enodes: BTreeMap<Public, Enode>,
-) -> String {
+	enodes: &BTreeMap<Public, Enode>,
+) -> KeyGenHistoryData {
 	let mut data = KeyGenHistoryData {
 		validators: Vec::new(),
 		staking_addresses: Vec::new(),
@@ -204,7 +205,7 @@ pub fn key_sync_history_data(
 		parts_total_bytes + acks_total_bytes
 	);
 
-	serde_json::to_string(&data).expect("Keygen History must convert to JSON")
+	data
 }
 
 #[cfg(test)]
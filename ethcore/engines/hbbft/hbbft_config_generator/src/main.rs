@@ -2,6 +2,7 @@ extern crate bincode;
 #[macro_use]
 extern crate clap;
 extern crate client_traits;
+extern crate ethabi;
 extern crate ethcore;
 extern crate ethereum_types;
 extern crate ethkey;
@@ -14,8 +15,10 @@ extern crate serde;
 extern crate serde_json;
 extern crate toml;
 
+mod chain_spec;
 mod keygen_history_helpers;
 
+use chain_spec::{generate_spec, SpecParams, DEFAULT_SPEC_TEMPLATE};
 use clap::{App, Arg};
 use ethstore::{KeyFile, SafeAccount};
 use keygen_history_helpers::{enodes_to_pub_keys, generate_keygens, key_sync_history_data};
@@ -23,6 +26,8 @@ use parity_crypto::publickey::{Address, Generator, KeyPair, Public, Random, Secr
 use std::collections::BTreeMap;
 use std::fmt::Write;
 use std::fs;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
 use std::str::FromStr;
 use toml::{map::Map, Value};
 
@@ -261,6 +266,54 @@ fn write_json_for_secret(secret: Secret, filename: String) {
 	fs::write(filename, serialized_json_key).expect("Unable to write json key file");
 }
 
+/// Returns a shell script starting the given validators and the RPC node on this machine.
+///
+/// Each node runs in its own directory holding its database and log file, so the paths of the
+/// shared files are passed on the command line.
+fn launcher_script(validator_indices: &[usize]) -> String {
+	let mut script = String::new();
+	script.push_str(
+		r#"#!/bin/sh
+# Starts the generated hbbft testnet on this machine. Stop it with Ctrl+C.
+# Set OPENETHEREUM to the path of the client binary if it is not on the PATH.
+set -e
+cd "$(dirname "$0")"
+OPENETHEREUM=${OPENETHEREUM:-openethereum}
+PIDS=""
+trap 'kill $PIDS 2>/dev/null || true' EXIT INT TERM
+
+run_node() {
+	node_dir=$1
+	config=$2
+	shift 2
+	(cd "$node_dir" && "$OPENETHEREUM" --config "../$config" --chain ../spec.json \
+		--reserved-peers ../reserved-peers --password ../password.txt "$@")
+}
+
+start_node() {
+	run_node "$@" > "$1/output.log" 2>&1 &
+	PIDS="$PIDS $!"
+	echo "Started $1 with pid $!."
+}
+
+mkdir -p node0
+start_node node0 rpc_node.toml
+"#,
+	);
+	for i in validator_indices {
+		writeln!(
+			&mut script,
+			"mkdir -p node{i}\n\
+			 run_node node{i} hbbft_validator_{i}.toml account import ../hbbft_validator_key_{i}.json > /dev/null\n\
+			 start_node node{i} hbbft_validator_{i}.toml",
+			i = i
+		)
+		.expect("launcher script should be written to the string");
+	}
+	script.push_str("wait\n");
+	script
+}
+
 fn main() {
 	let matches = App::new("hbbft parity config generator")
 		.version("1.0")
@@ -290,6 +343,27 @@ fn main() {
 				.required(false)
 				.takes_value(true),
 		)
+		.arg(
+			Arg::with_name("spec_template")
+				.long("spec_template")
+				.help("The chain spec with the POSDAO contracts to base spec.json on")
+				.required(false)
+				.takes_value(true),
+		)
+		.arg(
+			Arg::with_name("minimum_block_time")
+				.long("minimum_block_time")
+				.help("The minimum time between two blocks, in seconds")
+				.default_value("1")
+				.takes_value(true),
+		)
+		.arg(
+			Arg::with_name("transaction_queue_size_trigger")
+				.long("transaction_queue_size_trigger")
+				.help("The number of queued transactions which triggers the creation of a block")
+				.default_value("1")
+				.takes_value(true),
+		)
 		.get_matches();
 
 	let num_nodes: usize = matches
@@ -312,6 +386,20 @@ fn main() {
 				.collect()
 		});
 
+	let spec_params = SpecParams {
+		minimum_block_time: value_t!(matches.value_of("minimum_block_time"), u64)
+			.expect("Minimum block time must be of integer type"),
+		transaction_queue_size_trigger: value_t!(
+			matches.value_of("transaction_queue_size_trigger"),
+			usize
+		)
+		.expect("Transaction queue size trigger must be of integer type"),
+	};
+	let spec_template = match matches.value_of("spec_template") {
+		Some(path) => fs::read_to_string(path).expect("Unable to read spec template file"),
+		None => DEFAULT_SPEC_TEMPLATE.to_string(),
+	};
+
 	// If private keys are specified we expect as many as there are nodes.
 	if private_keys.len() != 0 {
 		assert!(private_keys.len() == num_nodes);
@@ -324,6 +412,7 @@ fn main() {
 	let (sync_keygen, parts, acks) = generate_keygens(pub_keys, &mut rng, (num_nodes - 1) / 3);
 
 	let mut reserved_peers = String::new();
+	let mut validator_indices = Vec::new();
 	for keygen in sync_keygen.iter() {
 		let enode = enodes_map
 			.get(keygen.our_id())
@@ -331,6 +420,7 @@ fn main() {
 		writeln!(&mut reserved_peers, "{}", enode.to_string())
 			.expect("enode should be written to the reserved peers string");
 		let i = enode.idx;
+		validator_indices.push(i);
 		let file_name = format!("hbbft_validator_{}.toml", i);
 		let toml_string = toml::to_string(&to_toml(i, &config_type, external_ip, &enode.address))
 			.expect("TOML string generation should succeed");
@@ -360,11 +450,29 @@ fn main() {
 	// Write the password file
 	fs::write("password.txt", "test").expect("Unable to write password.txt file");
 
+	let keygen_history = key_sync_history_data(parts, acks, &enodes_map);
 	fs::write(
 		"keygen_history.json",
-		key_sync_history_data(parts, acks, enodes_map),
+		serde_json::to_string(&keygen_history).expect("Keygen History must convert to JSON"),
 	)
 	.expect("Unable to write keygen history data file");
+
+	// Write the chain spec with the generated validators
+	let spec = generate_spec(&spec_template, &spec_params, &keygen_history);
+	fs::write(
+		"spec.json",
+		serde_json::to_string_pretty(&spec).expect("Chain spec must convert to JSON"),
+	)
+	.expect("Unable to write spec.json file");
+
+	// Write the launcher script, only the Docker configs unlock the validator accounts themselves
+	if config_type == ConfigType::Docker {
+		fs::write("start-testnet.sh", launcher_script(&validator_indices))
+			.expect("Unable to write start-testnet.sh file");
+		#[cfg(unix)]
+		fs::set_permissions("start-testnet.sh", fs::Permissions::from_mode(0o755))
+			.expect("Unable to make start-testnet.sh executable");
+	}
 }
 
 #[cfg(test)]
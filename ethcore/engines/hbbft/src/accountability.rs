@@ -0,0 +1,166 @@
+//! Accountability for misbehaving validators.
+//!
+//! Faults are detected by Honey Badger BFT itself, or by the engine when it turns the agreed upon
+//! contributions into a block. They are counted per validator, and reported on-chain if a reporting
+//! contract is configured.
+
+use common_types::BlockNumber;
+use ethereum_types::Address;
+use hbbft::{honey_badger, FaultLog};
+use parity_crypto::publickey::public_to_address;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::mem;
+
+use crate::NodeId;
+
+/// The number of blocks after which a fault is not reported anymore.
+///
+/// Reports can't be sent while the node is syncing or has no signer, so they are dropped once
+/// they are that old instead of piling up.
+pub const MAX_REPORT_AGE: BlockNumber = 100;
+
+/// A kind of misbehavior observed from a validator.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FaultKind {
+	/// A fault detected by Honey Badger BFT.
+	HoneyBadger(honey_badger::FaultKind),
+	/// The contribution contained a transaction which is not valid RLP.
+	MalformedTransaction,
+	/// The contribution contained a transaction with an invalid signature.
+	InvalidTransactionSignature,
+	/// The contribution contained less random data than required.
+	InsufficientRandomData,
+}
+
+impl fmt::Display for FaultKind {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			FaultKind::HoneyBadger(kind) => write!(f, "{:?}", kind),
+			FaultKind::MalformedTransaction => write!(f, "MalformedTransaction"),
+			FaultKind::InvalidTransactionSignature => write!(f, "InvalidTransactionSignature"),
+			FaultKind::InsufficientRandomData => write!(f, "InsufficientRandomData"),
+		}
+	}
+}
+
+/// Counts the faults of each validator and keeps track of the faults not reported yet.
+#[derive(Debug, Default)]
+pub struct FaultTracker {
+	counts: BTreeMap<NodeId, BTreeMap<String, u64>>,
+	pending_reports: BTreeSet<(NodeId, BlockNumber)>,
+}
+
+impl FaultTracker {
+	/// Records a fault of the given validator, observed in the given block.
+	pub fn record(&mut self, node_id: NodeId, block_num: BlockNumber, kind: FaultKind) {
+		warn!(target: "consensus", "Validator {:?} misbehaved in block {}: {}", node_id, block_num, kind);
+		*self
+			.counts
+			.entry(node_id)
+			.or_default()
+			.entry(kind.to_string())
+			.or_insert(0) += 1;
+		self.pending_reports.insert((node_id, block_num));
+	}
+
+	/// Records the faults detected by Honey Badger BFT.
+	pub fn record_fault_log(
+		&mut self,
+		fault_log: FaultLog<NodeId, honey_badger::FaultKind>,
+		block_num: BlockNumber,
+	) {
+		for fault in fault_log.0 {
+			self.record(fault.node_id, block_num, FaultKind::HoneyBadger(fault.kind));
+		}
+	}
+
+	/// Returns the number of faults per validator mining address, by kind of fault.
+	pub fn fault_counts(&self) -> BTreeMap<Address, BTreeMap<String, u64>> {
+		self.counts
+			.iter()
+			.map(|(node_id, counts)| (public_to_address(&node_id.0), counts.clone()))
+			.collect()
+	}
+
+	/// Returns the validators and blocks of the faults not reported yet, and clears them.
+	///
+	/// Only faults observed up to the given block are returned, since faults may be observed
+	/// while agreeing on a block which does not exist yet.
+	/// Multiple faults of a validator in the same block result in a single report.
+	pub fn take_pending_reports(&mut self, max_block: BlockNumber) -> BTreeSet<(NodeId, BlockNumber)> {
+		let (ready, pending) = mem::replace(&mut self.pending_reports, BTreeSet::new())
+			.into_iter()
+			.partition(|&(_, block_num)| block_num <= max_block);
+		self.pending_reports = pending;
+		ready
+	}
+
+	/// Drops the reports of faults which are more than `MAX_REPORT_AGE` blocks older than the
+	/// given latest block.
+	pub fn prune_pending_reports(&mut self, latest: BlockNumber) {
+		let min_block = latest.saturating_sub(MAX_REPORT_AGE);
+		self.pending_reports.retain(|&(_, block_num)| block_num >= min_block);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{FaultKind, FaultTracker, MAX_REPORT_AGE};
+	use crate::NodeId;
+	use parity_crypto::publickey::{public_to_address, Generator, Random};
+
+	#[test]
+	fn test_fault_tracker() {
+		let node_a = NodeId(*Random.generate().public());
+		let node_b = NodeId(*Random.generate().public());
+
+		let mut tracker = FaultTracker::default();
+		tracker.record(node_a, 5, FaultKind::MalformedTransaction);
+		tracker.record(node_a, 5, FaultKind::MalformedTransaction);
+		tracker.record(node_a, 6, FaultKind::InsufficientRandomData);
+		tracker.record(node_b, 6, FaultKind::InvalidTransactionSignature);
+
+		let counts = tracker.fault_counts();
+		assert_eq!(counts.len(), 2);
+		let counts_a = &counts[&public_to_address(&node_a.0)];
+		assert_eq!(counts_a["MalformedTransaction"], 2);
+		assert_eq!(counts_a["InsufficientRandomData"], 1);
+		let counts_b = &counts[&public_to_address(&node_b.0)];
+		assert_eq!(counts_b["InvalidTransactionSignature"], 1);
+
+		// Faults in blocks after the given one stay pending.
+		let reports = tracker.take_pending_reports(5);
+		assert_eq!(reports.len(), 1);
+		assert!(reports.contains(&(node_a, 5)));
+
+		// Faults in the same block are reported once, and only once.
+		let reports = tracker.take_pending_reports(6);
+		assert_eq!(reports.len(), 2);
+		assert!(reports.contains(&(node_b, 6)));
+		assert!(tracker.take_pending_reports(6).is_empty());
+
+		// The counts are kept after reporting.
+		assert_eq!(tracker.fault_counts(), counts);
+	}
+
+	#[test]
+	fn test_prune_pending_reports() {
+		let node = NodeId(*Random.generate().public());
+
+		let mut tracker = FaultTracker::default();
+		tracker.record(node, 5, FaultKind::MalformedTransaction);
+		tracker.record(node, 6, FaultKind::MalformedTransaction);
+
+		// Recent faults are kept, outdated ones are dropped.
+		tracker.prune_pending_reports(5 + MAX_REPORT_AGE);
+		assert_eq!(tracker.pending_reports.len(), 2);
+		tracker.prune_pending_reports(6 + MAX_REPORT_AGE);
+		let reports = tracker.take_pending_reports(6 + MAX_REPORT_AGE);
+		assert_eq!(reports.len(), 1);
+		assert!(reports.contains(&(node, 6)));
+
+		// Pruning doesn't change the counts.
+		assert_eq!(tracker.fault_counts()[&public_to_address(&node.0)]["MalformedTransaction"], 2);
+	}
+}
@@ -1,28 +1,30 @@
-use crate::contracts::staking::get_posdao_epoch;
-use crate::contracts::validator_set::{get_validator_pubkeys, ValidatorType};
+use crate::contracts::validator_set::{
+	get_validator_pubkeys, get_validator_pubkeys_with_call, ValidatorType,
+};
 use crate::NodeId;
-use client_traits::{EngineClient, TransactionRequest};
+use client_traits::EngineClient;
 use common_types::ids::BlockId;
 use engine::signer::EngineSigner;
-use ethereum_types::{Address, H512, U256};
+use ethereum_types::{Address, H256, H512, U256};
+use hash::keccak;
 use hbbft::crypto::{PublicKeySet, SecretKeyShare};
 use hbbft::sync_key_gen::{
 	Ack, AckOutcome, Error, Part, PartOutcome, PubKeyMap, PublicKey, SecretKey, SyncKeyGen,
 };
 use hbbft::util::max_faulty;
 use hbbft::NetworkInfo;
-use itertools::Itertools;
 use parity_crypto::publickey::Public;
 use parking_lot::RwLock;
+use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
 use std::collections::BTreeMap;
 use std::str::FromStr;
 use std::sync::Arc;
-use utils::bound_contract::{BoundContract, CallError};
+use utils::bound_contract::{BoundContract, CallError, ContractCall};
 
 use_contract!(key_history_contract, "res/key_history_contract.json");
 
 lazy_static! {
-	static ref KEYGEN_HISTORY_ADDRESS: Address =
+	pub static ref KEYGEN_HISTORY_ADDRESS: Address =
 		Address::from_str("7000000000000000000000000000000000000001").unwrap();
 }
 
@@ -103,11 +105,13 @@ pub fn part_of_address(
 	if serialized_part.is_empty() {
 		return Err(CallError::ReturnValueInvalid);
 	}
-	let deserialized_part: Part = bincode::deserialize(&serialized_part).unwrap();
+	let deserialized_part: Part =
+		bincode::deserialize(&serialized_part).map_err(|_| CallError::ReturnValueInvalid)?;
+	let public = vmap.get(&address).ok_or(CallError::ReturnValueInvalid)?;
 	let mut rng = rand::thread_rng();
 	let outcome = skg
-		.handle_part(vmap.get(&address).unwrap(), deserialized_part, &mut rng)
-		.unwrap();
+		.handle_part(public, deserialized_part, &mut rng)
+		.map_err(|_| CallError::ReturnValueInvalid)?;
 
 	match outcome {
 		PartOutcome::Invalid(_) => Err(CallError::ReturnValueInvalid),
@@ -124,36 +128,10 @@ pub fn has_acks_of_address_data(
 	Ok(serialized_length.low_u64() != 0)
 }
 
-pub fn acks_of_address(
-	client: &dyn EngineClient,
-	address: Address,
-	vmap: &BTreeMap<Address, Public>,
-	skg: &mut SyncKeyGen<Public, PublicWrapper>,
-	block_id: BlockId,
-) -> Result<(), CallError> {
-	let c = BoundContract::bind(client, block_id, *KEYGEN_HISTORY_ADDRESS);
-	let serialized_length = call_const_key_history!(c, get_acks_length, address)?;
-
-	// println!(
-	// 	"Acks for address {} is of size: {:?}",
-	// 	address, serialized_length
-	// );
-	for n in 0..serialized_length.low_u64() {
-		let serialized_ack = call_const_key_history!(c, acks, address, n)?;
-		//println!("Ack #{} for address {}: {:?}", n, address, serialized_ack);
-		if serialized_ack.is_empty() {
-			return Err(CallError::ReturnValueInvalid);
-		}
-		let deserialized_ack: Ack = bincode::deserialize(&serialized_ack).unwrap();
-		let outcome = skg
-			.handle_ack(vmap.get(&address).unwrap(), deserialized_ack)
-			.unwrap();
-		if let AckOutcome::Invalid(fault) = outcome {
-			panic!("Expected Ack Outcome to be valid. {}", fault);
-		}
-	}
-
-	Ok(())
+/// Returns the number of Acks the given validator has written for the pending validator set.
+pub fn acks_of_address_count(client: &dyn EngineClient, address: Address) -> Result<u64, CallError> {
+	let c = BoundContract::bind(client, BlockId::Latest, *KEYGEN_HISTORY_ADDRESS);
+	Ok(call_const_key_history!(c, get_acks_length, address)?.low_u64())
 }
 
 #[derive(Clone)]
@@ -190,123 +168,184 @@ impl<'a> SecretKey for KeyPairWrapper {
 	}
 }
 
-/// Read available keygen data from the blockchain and initialize a SyncKeyGen instance with it.
-pub fn initialize_synckeygen(
-	client: &dyn EngineClient,
-	signer: &Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
-	block_id: BlockId,
-	validator_type: ValidatorType,
-) -> Result<SyncKeyGen<Public, PublicWrapper>, CallError> {
-	let vmap = get_validator_pubkeys(&*client, block_id, validator_type)?;
-	let pub_keys: BTreeMap<_, _> = vmap
-		.values()
-		.map(|p| (*p, PublicWrapper { inner: p.clone() }))
-		.collect();
-
-	// if synckeygen creation fails then either signer or validator pub keys are problematic.
-	// Todo: We should expect up to f clients to write invalid pub keys. Report and re-start pending validator set selection.
-	let (mut synckeygen, _) = engine_signer_to_synckeygen(signer, Arc::new(pub_keys))
-		.map_err(|_| CallError::ReturnValueInvalid)?;
-
-	for v in vmap.keys().sorted() {
-		part_of_address(&*client, *v, &vmap, &mut synckeygen, block_id)?;
-	}
-	for v in vmap.keys().sorted() {
-		acks_of_address(&*client, *v, &vmap, &mut synckeygen, block_id)?;
-	}
-
-	Ok(synckeygen)
+/// The key generation data of a validator set, as written to the key history contract.
+///
+/// This is all that is needed to derive the validator set's threshold public key, so it is used
+/// as epoch transition proof, allowing the key to be derived without access to the chain state.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KeyGenHistoryData {
+	/// The validators' addresses, sorted.
+	pub validators: Vec<Address>,
+	/// The validators' public keys, in the order of `validators`.
+	pub public_keys: Vec<Public>,
+	/// The serialized Part of each validator, in the order of `validators`.
+	pub parts: Vec<Vec<u8>>,
+	/// The serialized Acks of each validator, in the order of `validators`.
+	pub acks: Vec<Vec<Vec<u8>>>,
 }
 
-/// Returns a collection of transactions the pending validator has to submit in order to
-/// complete the keygen history contract data necessary to generate the next key and switch to the new validator set.
-pub fn send_keygen_transactions(
-	client: &dyn EngineClient,
-	signer: &Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
-) -> Result<(), CallError> {
-	// If we have no signer there is nothing for us to send.
-	let address = match signer.read().as_ref() {
-		Some(signer) => signer.address(),
-		None => return Err(CallError::ReturnValueInvalid),
-	};
-
-	let full_client = client.as_full_client().ok_or(CallError::NotFullClient)?;
+impl KeyGenHistoryData {
+	/// Reads the key generation data of the given validator set on the state of the given block.
+	pub fn read(
+		client: &dyn EngineClient,
+		block_id: BlockId,
+		validator_type: ValidatorType,
+	) -> Result<Self, CallError> {
+		let vmap = get_validator_pubkeys(&*client, block_id, validator_type)?;
+		let c = BoundContract::bind(client, block_id, *KEYGEN_HISTORY_ADDRESS);
+		Self::read_from(&c, vmap)
+	}
 
-	// If the chain is still syncing, do not send Parts or Acks.
-	if full_client.is_major_syncing() {
-		return Ok(());
+	/// Same as `read`, but performs the contract calls through the given function.
+	pub fn read_with_call(
+		call: &ContractCall,
+		validator_type: ValidatorType,
+	) -> Result<Self, CallError> {
+		let vmap = get_validator_pubkeys_with_call(call, validator_type)?;
+		let c = BoundContract::bind_call(call, *KEYGEN_HISTORY_ADDRESS);
+		Self::read_from(&c, vmap)
 	}
 
-	let vmap = get_validator_pubkeys(&*client, BlockId::Latest, ValidatorType::Pending)?;
-	let pub_keys: BTreeMap<_, _> = vmap
-		.values()
-		.map(|p| (*p, PublicWrapper { inner: p.clone() }))
-		.collect();
+	fn read_from(
+		c: &BoundContract,
+		vmap: BTreeMap<Address, Public>,
+	) -> Result<Self, CallError> {
+		let mut data = KeyGenHistoryData::default();
+		for (address, public) in vmap {
+			let part = call_const_key_history!(c, parts, address)?;
+			if part.is_empty() {
+				return Err(CallError::ReturnValueInvalid);
+			}
+			let acks_length = call_const_key_history!(c, get_acks_length, address)?;
+			let mut acks = Vec::new();
+			for n in 0..acks_length.low_u64() {
+				let ack = call_const_key_history!(c, acks, address, n)?;
+				if ack.is_empty() {
+					return Err(CallError::ReturnValueInvalid);
+				}
+				acks.push(ack);
+			}
+			data.validators.push(address);
+			data.public_keys.push(public);
+			data.parts.push(part);
+			data.acks.push(acks);
+		}
+		Ok(data)
+	}
 
-	// if synckeygen creation fails then either signer or validator pub keys are problematic.
-	// Todo: We should expect up to f clients to write invalid pub keys. Report and re-start pending validator set selection.
-	let (mut synckeygen, part) = engine_signer_to_synckeygen(signer, Arc::new(pub_keys))
-		.map_err(|_| CallError::ReturnValueInvalid)?;
+	/// Initializes a SyncKeyGen instance with the Parts and Acks.
+	pub fn synckeygen(
+		&self,
+		signer: &Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
+	) -> Result<SyncKeyGen<Public, PublicWrapper>, CallError> {
+		let pub_keys: BTreeMap<_, _> = self
+			.public_keys
+			.iter()
+			.map(|p| (*p, PublicWrapper { inner: p.clone() }))
+			.collect();
+
+		// if synckeygen creation fails then either signer or validator pub keys are problematic.
+		// Todo: We should expect up to f clients to write invalid pub keys. Report and re-start pending validator set selection.
+		let (mut synckeygen, _) = engine_signer_to_synckeygen(signer, Arc::new(pub_keys))
+			.map_err(|_| CallError::ReturnValueInvalid)?;
 
-	// If there is no part then we are not part of the pending validator set and there is nothing for us to do.
-	let part_data = match part {
-		Some(part) => part,
-		None => return Err(CallError::ReturnValueInvalid),
-	};
+		let mut rng = rand::thread_rng();
+		for (public, part) in self.public_keys.iter().zip(&self.parts) {
+			let part: Part =
+				bincode::deserialize(part).map_err(|_| CallError::ReturnValueInvalid)?;
+			match synckeygen.handle_part(public, part, &mut rng) {
+				Ok(PartOutcome::Valid(_)) => (),
+				_ => return Err(CallError::ReturnValueInvalid),
+			}
+		}
+		for (public, acks) in self.public_keys.iter().zip(&self.acks) {
+			for ack in acks {
+				let ack: Ack =
+					bincode::deserialize(ack).map_err(|_| CallError::ReturnValueInvalid)?;
+				match synckeygen.handle_ack(public, ack) {
+					Ok(AckOutcome::Valid) => (),
+					_ => return Err(CallError::ReturnValueInvalid),
+				}
+			}
+		}
 
-	let upcoming_epoch = get_posdao_epoch(client, BlockId::Latest)? + 1;
+		Ok(synckeygen)
+	}
 
-	// Check if we already sent our part.
-	if !has_part_of_address_data(client, address)? {
-		let serialized_part = match bincode::serialize(&part_data) {
-			Ok(part) => part,
-			Err(_) => return Err(CallError::ReturnValueInvalid),
-		};
-		let write_part_data =
-			key_history_contract::functions::write_part::call(upcoming_epoch, serialized_part);
-
-		let part_transaction = TransactionRequest::call(*KEYGEN_HISTORY_ADDRESS, write_part_data.0)
-			.gas(U256::from(7_000_000))
-			.nonce(full_client.next_nonce(&address))
-			.gas_price(U256::from(10000000000u64));
-		full_client
-			.transact_silently(part_transaction)
-			.map_err(|_| CallError::ReturnValueInvalid)?;
+	/// Returns the validator set's threshold public key, if the key generation is complete.
+	pub fn public_key(&self) -> Option<PublicKey> {
+		if self.validators.is_empty() {
+			return None;
+		}
+		self.synckeygen(&Arc::new(RwLock::new(None)))
+			.ok()
+			.filter(|synckeygen| synckeygen.is_ready())
+			.and_then(|synckeygen| synckeygen.generate().ok())
+			.map(|(pks, _)| pks.public_key())
 	}
+}
+
+/// Returns the hash of a threshold public key, as contained in the extra data of the block that
+/// switches to the validator set with that key.
+pub fn public_key_hash(public_key: &PublicKey) -> H256 {
+	keccak(&public_key.to_bytes()[..])
+}
 
-	// Return if any Part is missing.
-	let mut acks = Vec::new();
-	for v in vmap.keys().sorted() {
-		acks.push(
-			match part_of_address(&*client, *v, &vmap, &mut synckeygen, BlockId::Latest)? {
-				Some(ack) => ack,
-				None => return Err(CallError::ReturnValueInvalid),
-			},
-		);
+impl Encodable for KeyGenHistoryData {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(self.validators.len());
+		for i in 0..self.validators.len() {
+			s.begin_list(4)
+				.append(&self.validators[i])
+				.append(&self.public_keys[i])
+				.append(&self.parts[i]);
+			s.begin_list(self.acks[i].len());
+			for ack in &self.acks[i] {
+				s.append(ack);
+			}
+		}
 	}
+}
 
-	// Now we are sure all parts are ready, let's check if we sent our Acks.
-	if !has_acks_of_address_data(client, address)? {
-		let mut serialized_acks = Vec::new();
-		for ack in acks {
-			serialized_acks.push(match bincode::serialize(&ack) {
-				Ok(serialized_ack) => serialized_ack,
-				Err(_) => return Err(CallError::ReturnValueInvalid),
-			})
+impl Decodable for KeyGenHistoryData {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		let mut data = KeyGenHistoryData::default();
+		for validator in rlp.iter() {
+			if validator.item_count()? != 4 {
+				return Err(DecoderError::RlpIncorrectListLen);
+			}
+			data.validators.push(validator.val_at(0)?);
+			data.public_keys.push(validator.val_at(1)?);
+			data.parts.push(validator.val_at(2)?);
+			data.acks.push(validator.list_at(3)?);
 		}
-		let write_acks_data =
-			key_history_contract::functions::write_acks::call(upcoming_epoch, serialized_acks);
-
-		let acks_transaction = TransactionRequest::call(*KEYGEN_HISTORY_ADDRESS, write_acks_data.0)
-			.gas(U256::from(7_000_000))
-			.nonce(full_client.next_nonce(&address))
-			.gas_price(U256::from(10000000000u64));
-		full_client
-			.transact_silently(acks_transaction)
-			.map_err(|_| CallError::ReturnValueInvalid)?;
+		Ok(data)
 	}
+}
 
-	Ok(())
+/// Read available keygen data from the blockchain and initialize a SyncKeyGen instance with it.
+pub fn initialize_synckeygen(
+	client: &dyn EngineClient,
+	signer: &Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
+	block_id: BlockId,
+	validator_type: ValidatorType,
+) -> Result<SyncKeyGen<Public, PublicWrapper>, CallError> {
+	KeyGenHistoryData::read(client, block_id, validator_type)?.synckeygen(signer)
+}
+
+/// Returns the call data of the transaction writing our Part for the upcoming epoch.
+pub fn write_part_data(upcoming_epoch: U256, part: &Part) -> Result<Vec<u8>, CallError> {
+	let serialized_part = bincode::serialize(part).map_err(|_| CallError::ReturnValueInvalid)?;
+	Ok(key_history_contract::functions::write_part::call(upcoming_epoch, serialized_part).0)
+}
+
+/// Returns the call data of the transaction writing our Acks for the upcoming epoch.
+pub fn write_acks_data(upcoming_epoch: U256, acks: &[Ack]) -> Result<Vec<u8>, CallError> {
+	let mut serialized_acks = Vec::new();
+	for ack in acks {
+		serialized_acks.push(bincode::serialize(ack).map_err(|_| CallError::ReturnValueInvalid)?);
+	}
+	Ok(key_history_contract::functions::write_acks::call(upcoming_epoch, serialized_acks).0)
 }
 
 #[cfg(test)]
@@ -339,4 +378,17 @@ mod tests {
 
 		assert!(engine_signer_to_synckeygen(&signer, Arc::new(pub_keys)).is_ok());
 	}
+
+	#[test]
+	fn test_keygen_history_data_rlp() {
+		let data = KeyGenHistoryData {
+			validators: vec![Address::from_low_u64_be(1), Address::from_low_u64_be(2)],
+			public_keys: vec![Public::from_low_u64_be(3), Public::from_low_u64_be(4)],
+			parts: vec![vec![5, 6], vec![7]],
+			acks: vec![vec![vec![8], vec![9, 10]], vec![]],
+		};
+		let encoded = rlp::encode(&data);
+		let decoded: KeyGenHistoryData = rlp::decode(&encoded).expect("decode KeyGenHistoryData");
+		assert_eq!(decoded, data);
+	}
 }
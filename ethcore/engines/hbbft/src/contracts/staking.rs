@@ -1,6 +1,7 @@
 use client_traits::EngineClient;
 use common_types::ids::BlockId;
 use ethereum_types::{Address, U256};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::str::FromStr;
 use utils::bound_contract::{BoundContract, CallError};
 
@@ -35,6 +36,32 @@ pub fn start_time_of_next_phase_transition(client: &dyn EngineClient) -> Result<
 	call_const_staking!(c, start_time_of_next_phase_transition)
 }
 
+/// Returns the internet address the pool with the given staking address registered, if any.
+///
+/// The address is stored as 16 byte IPv6 address, IPv4 addresses in their IPv4-mapped form.
+pub fn get_pool_internet_address(
+	client: &dyn EngineClient,
+	staking_address: &Address,
+) -> Result<Option<IpAddr>, CallError> {
+	let c = BoundContract::bind(client, BlockId::Latest, *STAKING_CONTRACT_ADDRESS);
+	let bytes = call_const_staking!(c, get_pool_internet_address, staking_address.clone())?;
+	Ok(internet_address_from_bytes(bytes))
+}
+
+/// Decodes an internet address as stored in the staking contract.
+pub fn internet_address_from_bytes(bytes: [u8; 16]) -> Option<IpAddr> {
+	let ipv6 = Ipv6Addr::from(bytes);
+	if ipv6.is_unspecified() {
+		return None;
+	}
+	match ipv6.segments() {
+		[0, 0, 0, 0, 0, 0xffff, _, _] => Some(IpAddr::V4(Ipv4Addr::new(
+			bytes[12], bytes[13], bytes[14], bytes[15],
+		))),
+		_ => Some(IpAddr::V6(ipv6)),
+	}
+}
+
 #[cfg(test)]
 pub mod tests {
 	use super::*;
@@ -95,4 +122,22 @@ pub mod tests {
 
 		staker
 	}
+
+	#[test]
+	fn test_internet_address_from_bytes() {
+		assert_eq!(internet_address_from_bytes([0; 16]), None);
+
+		let mut ipv4 = [0u8; 16];
+		ipv4[10..].copy_from_slice(&[0xff, 0xff, 192, 168, 0, 1]);
+		assert_eq!(
+			internet_address_from_bytes(ipv4),
+			Some(IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)))
+		);
+
+		let ipv6 = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+		assert_eq!(
+			internet_address_from_bytes(ipv6.octets()),
+			Some(IpAddr::V6(ipv6))
+		);
+	}
 }
@@ -1,10 +1,11 @@
-use client_traits::EngineClient;
+use client_traits::{EngineClient, TransactionRequest};
 use common_types::ids::BlockId;
-use ethereum_types::Address;
+use common_types::BlockNumber;
+use ethereum_types::{Address, U256};
 use parity_crypto::publickey::Public;
 use std::collections::BTreeMap;
 use std::str::FromStr;
-use utils::bound_contract::{BoundContract, CallError};
+use utils::bound_contract::{BoundContract, CallError, ContractCall};
 
 use_contract!(validator_set_hbbft, "res/validator_set_hbbft.json");
 
@@ -30,6 +31,22 @@ pub fn get_validator_pubkeys(
 	validator_type: ValidatorType,
 ) -> Result<BTreeMap<Address, Public>, CallError> {
 	let c = BoundContract::bind(client, block_id, *VALIDATOR_SET_ADDRESS);
+	validator_pubkeys(&c, validator_type)
+}
+
+/// Same as `get_validator_pubkeys`, but performs the contract calls through the given function.
+pub fn get_validator_pubkeys_with_call(
+	call: &ContractCall,
+	validator_type: ValidatorType,
+) -> Result<BTreeMap<Address, Public>, CallError> {
+	let c = BoundContract::bind_call(call, *VALIDATOR_SET_ADDRESS);
+	validator_pubkeys(&c, validator_type)
+}
+
+fn validator_pubkeys(
+	c: &BoundContract,
+	validator_type: ValidatorType,
+) -> Result<BTreeMap<Address, Public>, CallError> {
 	let validators = match validator_type {
 		ValidatorType::Current => call_const_validator!(c, get_validators)?,
 		ValidatorType::Pending => call_const_validator!(c, get_pending_validators)?,
@@ -58,13 +75,13 @@ pub fn mining_by_staking_address(
 	call_const_validator!(c, mining_by_staking_address, staking_address.clone())
 }
 
-// pub fn staking_by_mining_address(
-// 	client: &dyn EngineClient,
-// 	mining_address: &Address,
-// ) -> Result<Address, CallError> {
-// 	let c = BoundContract::bind(client, BlockId::Latest, *VALIDATOR_SET_ADDRESS);
-// 	call_const_validator!(c, staking_by_mining_address, mining_address.clone())
-// }
+pub fn staking_by_mining_address(
+	client: &dyn EngineClient,
+	mining_address: &Address,
+) -> Result<Address, CallError> {
+	let c = BoundContract::bind(client, BlockId::Latest, *VALIDATOR_SET_ADDRESS);
+	call_const_validator!(c, staking_by_mining_address, mining_address.clone())
+}
 
 pub fn is_pending_validator(
 	client: &dyn EngineClient,
@@ -78,3 +95,43 @@ pub fn get_pending_validators(client: &dyn EngineClient) -> Result<Vec<Address>,
 	let c = BoundContract::bind(client, BlockId::Latest, *VALIDATOR_SET_ADDRESS);
 	call_const_validator!(c, get_pending_validators)
 }
+
+/// Returns whether `reporting` may report `malicious` for misbehavior in the given block.
+///
+/// The reporting contract is expected to implement the reporting interface of the validator set contract.
+pub fn report_malicious_callable(
+	client: &dyn EngineClient,
+	contract_address: Address,
+	reporting: Address,
+	malicious: Address,
+	block_num: BlockNumber,
+) -> Result<bool, CallError> {
+	let c = BoundContract::bind(client, BlockId::Latest, contract_address);
+	let (callable, _) = call_const_validator!(
+		c,
+		report_malicious_callable,
+		reporting,
+		malicious,
+		block_num
+	)?;
+	Ok(callable)
+}
+
+/// Sends a transaction from `reporting`, reporting `malicious` for misbehavior in the given block.
+pub fn send_report_malicious(
+	client: &dyn EngineClient,
+	contract_address: Address,
+	reporting: Address,
+	malicious: Address,
+	block_num: BlockNumber,
+) -> Result<(), CallError> {
+	let full_client = client.as_full_client().ok_or(CallError::NotFullClient)?;
+	let data = validator_set_hbbft::functions::report_malicious::call(malicious, block_num);
+	let transaction = TransactionRequest::call(contract_address, data.0)
+		.gas(U256::from(1_000_000))
+		.nonce(full_client.next_nonce(&reporting))
+		.gas_price(U256::from(10000000000u64));
+	full_client
+		.transact_silently(transaction)
+		.map_err(|e| CallError::CallFailed(format!("{:?}", e)))
+}
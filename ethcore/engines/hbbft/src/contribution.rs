@@ -1,6 +1,7 @@
 use common_types::transaction::SignedTransaction;
-use rand::{self, distributions::Standard, Rng};
+use rand::{self, distributions::Standard, seq::index, Rng};
 use rlp::Encodable;
+use std::cmp::{max, min};
 use std::time::UNIX_EPOCH;
 
 #[derive(Clone, Eq, PartialEq, Debug, Hash, Serialize, Deserialize)]
@@ -9,15 +10,18 @@ pub(crate) struct Contribution {
 	pub timestamp: u64,
 	/// Random data for on-chain randomness.
 	///
-	/// The invariant of `random_data.len()` == RANDOM_BYTES_PER_EPOCH **must** hold true.
+	/// The invariant of `random_data.len()` == the configured random bytes per epoch **must** hold true.
 	pub random_data: Vec<u8>,
 }
 
-/// Number of random bytes to generate per epoch.
+/// Default number of random bytes to generate per epoch.
 ///
-/// Currently, we want twenty u32s worth of random data to generated on each epoch.
-// TODO: Make this configurable somewhere.
-const RANDOM_BYTES_PER_EPOCH: usize = 4 * 20;
+/// By default, we want twenty u32s worth of random data to generated on each epoch.
+pub const DEFAULT_RANDOM_BYTES_PER_EPOCH: usize = 4 * 20;
+
+/// Minimum number of random bytes per epoch, as 32 bytes of each contribution are used for the
+/// block's random number.
+pub const MIN_RANDOM_BYTES_PER_EPOCH: usize = 32;
 
 /// Returns the current UNIX Epoch time, in seconds.
 pub fn unix_now_secs() -> u64 {
@@ -32,28 +36,55 @@ pub fn unix_now_millis() -> u128 {
 		.as_millis()
 }
 
+/// Selects the transactions to propose in our contribution from the queued transactions.
+///
+/// As recommended for Honey Badger BFT, each of the `num_nodes` validators proposes a random subset
+/// of `batch_size / num_nodes` transactions out of the first `candidate_window` queued transactions,
+/// so that the contributions rarely overlap and a batch contains about `batch_size` transactions.
+/// The queue order is kept within the subset. Without a batch size all queued transactions are proposed.
+pub fn select_transactions<R: Rng>(
+	queued: Vec<SignedTransaction>,
+	batch_size: Option<usize>,
+	candidate_window: Option<usize>,
+	num_nodes: usize,
+	rng: &mut R,
+) -> Vec<SignedTransaction> {
+	let batch_size = match batch_size {
+		Some(batch_size) => batch_size,
+		None => return queued,
+	};
+	let num_nodes = max(num_nodes, 1);
+	let proposal_size = max(1, (batch_size + num_nodes - 1) / num_nodes);
+	let window = min(
+		max(candidate_window.unwrap_or(batch_size), proposal_size),
+		queued.len(),
+	);
+	let mut indices = index::sample(rng, window, min(proposal_size, window)).into_vec();
+	indices.sort();
+	indices.into_iter().map(|i| queued[i].clone()).collect()
+}
+
 impl Contribution {
-	pub fn new(txns: &Vec<SignedTransaction>) -> Self {
+	/// Creates a contribution of the given transactions, with random data drawn from `rng`.
+	pub fn new<R: Rng>(txns: &Vec<SignedTransaction>, random_bytes: usize, rng: &mut R) -> Self {
 		let ser_txns: Vec<_> = txns.iter().map(|txn| txn.rlp_bytes()).collect();
-		let mut rng = rand::thread_rng();
 
 		Contribution {
 			transactions: ser_txns,
 			timestamp: unix_now_secs(),
-			random_data: rng
-				.sample_iter(&Standard)
-				.take(RANDOM_BYTES_PER_EPOCH)
-				.collect(),
+			random_data: rng.sample_iter(&Standard).take(random_bytes).collect(),
 		}
 	}
 }
 
 #[cfg(test)]
 mod tests {
+	use super::{select_transactions, DEFAULT_RANDOM_BYTES_PER_EPOCH};
 	use crate::utils::test_helpers::create_transaction;
 	use common_types::transaction::SignedTransaction;
 	use ethereum_types::U256;
 	use parity_crypto::publickey::{Generator, Random};
+	use rand;
 	use rlp::{Decodable, Rlp};
 
 	#[test]
@@ -61,7 +92,11 @@ mod tests {
 		let mut pending: Vec<SignedTransaction> = Vec::new();
 		let keypair = Random.generate();
 		pending.push(create_transaction(&keypair, &U256::from(1)));
-		let contribution = super::Contribution::new(&pending);
+		let contribution = super::Contribution::new(
+			&pending,
+			DEFAULT_RANDOM_BYTES_PER_EPOCH,
+			&mut rand::thread_rng(),
+		);
 
 		let deser_txns: Vec<_> = contribution
 			.transactions
@@ -76,4 +111,31 @@ mod tests {
 			deser_txns.iter().nth(0).unwrap()
 		);
 	}
+
+	#[test]
+	fn test_select_transactions() {
+		let keypair = Random.generate();
+		let queued: Vec<SignedTransaction> = (0..20)
+			.map(|nonce| create_transaction(&keypair, &U256::from(nonce)))
+			.collect();
+		let mut rng = rand::thread_rng();
+
+		// Without batch size everything is proposed.
+		let selected = select_transactions(queued.clone(), None, None, 4, &mut rng);
+		assert_eq!(selected, queued);
+
+		// Each of 4 nodes proposes 2 out of the first 10 transactions, in queue order.
+		let selected = select_transactions(queued.clone(), Some(8), Some(10), 4, &mut rng);
+		assert_eq!(selected.len(), 2);
+		assert!(selected[0].nonce < selected[1].nonce);
+		assert!(selected.iter().all(|txn| txn.nonce < U256::from(10)));
+
+		// The candidate window can not be smaller than the proposal.
+		let selected = select_transactions(queued.clone(), Some(12), Some(1), 2, &mut rng);
+		assert_eq!(selected.len(), 6);
+
+		// Short queues are proposed entirely.
+		let selected = select_transactions(queued[..1].to_vec(), Some(8), None, 1, &mut rng);
+		assert_eq!(selected, queued[..1].to_vec());
+	}
 }
@@ -1,41 +1,62 @@
 use std::cmp::{max, min};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
+use std::mem;
 use std::ops::BitXor;
 use std::sync::{Arc, Weak};
 use std::time::Duration;
 
 use crate::block_reward_hbbft::BlockRewardContract;
+use crate::random_hbbft::RandomHbbftContract;
 use client_traits::{EngineClient, ForceUpdateSealing};
 use common_types::{
-	engines::{params::CommonParams, Seal, SealingState},
+	engines::{
+		machine::Call, params::CommonParams, Headers, PendingTransitionStore, Seal, SealingState,
+	},
 	errors::{BlockError, EngineError, EthcoreError as Error},
 	header::Header,
 	ids::BlockId,
-	transaction::SignedTransaction,
+	receipt::Receipt,
+	snapshot::Snapshotting,
+	transaction::{SignedTransaction, UnverifiedTransaction},
 	BlockNumber,
 };
-use engine::{signer::EngineSigner, Engine};
-use ethereum_types::{H256, H512, U256};
+use engine::{signer::EngineSigner, ConstructedVerifier, Engine, EpochChange, Proof};
+use ethereum_types::{Address, H256, H512, U256};
 use ethjson::spec::HbbftParams;
-use hbbft::{NetworkInfo, Target};
+use hbbft::{Epoched, NetworkInfo, Target};
 use io::{IoContext, IoHandler, IoService, TimerToken};
 use itertools::Itertools;
 use machine::{ExecutedBlock, Machine};
-use parity_crypto::publickey::Signature;
+use parity_crypto::publickey::{public_to_address, Signature};
 use parking_lot::RwLock;
 use rlp::{self, Decodable, Rlp};
 use serde::Deserialize;
 use serde_json;
+use stats::{MetricType, PrometheusRegistry};
+use unexpected::{Mismatch, OutOfBounds};
 
-use crate::contracts::keygen_history::{initialize_synckeygen, send_keygen_transactions};
+use crate::accountability::{FaultKind, FaultTracker};
+use crate::contracts::keygen_history::{
+	acks_of_address_count, has_part_of_address_data, initialize_synckeygen, public_key_hash,
+	KeyGenHistoryData,
+};
 use crate::contracts::staking::start_time_of_next_phase_transition;
 use crate::contracts::validator_set::{
-	get_pending_validators, is_pending_validator, ValidatorType,
+	get_pending_validators, get_validator_pubkeys, is_pending_validator,
+	report_malicious_callable, send_report_malicious, ValidatorType,
+};
+use crate::contribution::{
+	unix_now_millis, unix_now_secs, DEFAULT_RANDOM_BYTES_PER_EPOCH, MIN_RANDOM_BYTES_PER_EPOCH,
 };
-use crate::contribution::{unix_now_millis, unix_now_secs};
-use crate::hbbft_state::{Batch, HbMessage, HbbftState, HoneyBadgerStep};
-use crate::sealing::{self, RlpSig, Sealing};
+use crate::hbbft_state::{encryption_schedule, Batch, HbMessage, HbbftState, HoneyBadgerStep};
+use crate::keygen_transactions::KeyGenTransactionSender;
+use crate::persisted_state::PersistedState;
+use crate::sealing::{self, KeyChangeProof, RlpSig, Sealing, SIGNATURE_LENGTH};
+use crate::status::{
+	ActivityTracker, HbbftStatus, KeyGenStatus, KeyGenValidatorStatus, SealingStatus,
+};
+use crate::validator_peers::ValidatorPeersManager;
 use crate::NodeId;
 
 type TargetedMessage = hbbft::TargetedMessage<Message, NodeId>;
@@ -49,6 +70,62 @@ enum Message {
 	Sealing(BlockNumber, sealing::Message),
 }
 
+/// The maximum number of consensus messages kept until the persisted state is restored on startup.
+///
+/// Nodes without a signer never restore it, so messages beyond that are dropped.
+const MAX_STARTUP_MESSAGES: usize = 10_000;
+
+/// The maximum number of Honey Badger messages persisted per sender and hbbft epoch, for each
+/// validator.
+///
+/// In an epoch a validator sends a few messages about each validator's proposal, and more if the
+/// binary agreement on it takes several rounds. Further messages are handled, but not persisted.
+const PERSISTED_HB_MESSAGES_PER_VALIDATOR: usize = 50;
+
+/// Version prefix of consensus messages in the compact bincode encoding.
+///
+/// Legacy JSON encoded messages always start with `{`, so the version byte must not collide with it.
+const MESSAGE_FORMAT_BINCODE_V1: u8 = 1;
+
+/// The wire format used to encode outgoing consensus messages.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MessageFormat {
+	/// The legacy JSON encoding.
+	Json,
+	/// Bincode encoding, prefixed with a version byte.
+	BincodeV1,
+}
+
+impl Message {
+	/// Encodes the message in the given wire format.
+	fn encode(&self, format: MessageFormat) -> Vec<u8> {
+		match format {
+			MessageFormat::Json => {
+				serde_json::to_vec(self).expect("Serialization of consensus message failed")
+			}
+			MessageFormat::BincodeV1 => {
+				let mut ser = vec![MESSAGE_FORMAT_BINCODE_V1];
+				bincode::serialize_into(&mut ser, self)
+					.expect("Serialization of consensus message failed");
+				ser
+			}
+		}
+	}
+
+	/// Decodes a message in any of the supported wire formats.
+	fn decode(message: &[u8]) -> Result<Self, EngineError> {
+		match message.first() {
+			Some(&MESSAGE_FORMAT_BINCODE_V1) => bincode::deserialize(&message[1..]).map_err(|_| {
+				EngineError::MalformedMessage("Bincode message decoding failed.".into())
+			}),
+			Some(_) => serde_json::from_slice(message).map_err(|_| {
+				EngineError::MalformedMessage("Serde message decoding failed.".into())
+			}),
+			None => Err(EngineError::MalformedMessage("Empty message.".into())),
+		}
+	}
+}
+
 pub struct HoneyBadgerBFT {
 	transition_service: IoService<()>,
 	client: Arc<RwLock<Option<Weak<dyn EngineClient>>>>,
@@ -59,6 +136,94 @@ pub struct HoneyBadgerBFT {
 	params: HbbftParams,
 	message_counter: RwLock<usize>,
 	random_numbers: RwLock<BTreeMap<BlockNumber, U256>>,
+	queued_blocks: RwLock<BTreeMap<BlockNumber, QueuedBlock>>,
+	epoch_transition_proofs: RwLock<BTreeMap<BlockNumber, Vec<u8>>>,
+	fault_tracker: RwLock<FaultTracker>,
+	activity_tracker: RwLock<ActivityTracker>,
+	keygen_transaction_sender: RwLock<KeyGenTransactionSender>,
+	/// Manages the connections to the other validators, if the spec configures their port.
+	validator_peers: Option<RwLock<ValidatorPeersManager>>,
+	/// The consensus state of the blocks in progress, `None` until it has been restored on startup.
+	persisted_state: RwLock<Option<PersistedState>>,
+	/// Messages received before the persisted state was restored, handled once it is.
+	startup_messages: RwLock<Vec<(NodeId, Message)>>,
+}
+
+/// The block content agreed upon in an hbbft epoch, waiting for a pending block to be created.
+#[derive(Clone, Debug)]
+struct QueuedBlock {
+	transactions: Vec<SignedTransaction>,
+	timestamp: u64,
+	random_number: U256,
+}
+
+impl QueuedBlock {
+	/// Decodes and de-duplicates the transactions of a batch and derives the block's timestamp
+	/// and random number from the contributions.
+	///
+	/// Faults of the contributing validators are recorded in the given fault tracker.
+	fn from_batch(batch: &Batch, faults: &mut FaultTracker) -> Option<Self> {
+		// Decode and de-duplicate transactions, remembering whether their signature is valid.
+		let mut transactions = Vec::new();
+		let mut valid_signatures = HashMap::new();
+		for (node_id, contribution) in &batch.contributions {
+			for ser_txn in &contribution.transactions {
+				let txn: UnverifiedTransaction = match Decodable::decode(&Rlp::new(ser_txn)) {
+					Ok(txn) => txn,
+					Err(_) => {
+						faults.record(*node_id, batch.epoch, FaultKind::MalformedTransaction);
+						continue;
+					}
+				};
+				let is_valid = match valid_signatures.get(&txn.hash()) {
+					Some(is_valid) => *is_valid,
+					None => {
+						let hash = txn.hash();
+						let is_valid = match SignedTransaction::new(txn) {
+							Ok(signed) => {
+								transactions.push(signed);
+								true
+							}
+							Err(_) => false,
+						};
+						valid_signatures.insert(hash, is_valid);
+						is_valid
+					}
+				};
+				if !is_valid {
+					faults.record(*node_id, batch.epoch, FaultKind::InvalidTransactionSignature);
+				}
+			}
+		}
+
+		// Validators propose random subsets of their queues, so transactions of the same sender may
+		// end up in different contributions. Order them by nonce to keep them all includable.
+		transactions.sort_by_key(|txn| (txn.sender(), txn.nonce));
+
+		// We use the median of all contributions' timestamps
+		let mut timestamps = batch
+			.contributions
+			.iter()
+			.map(|(_, c)| c.timestamp)
+			.sorted();
+		let timestamp = timestamps.nth(timestamps.len() / 2)?;
+
+		let mut random_number = U256::zero();
+		for (node_id, contribution) in &batch.contributions {
+			if contribution.random_data.len() >= 32 {
+				random_number = U256::from(&contribution.random_data[0..32]).bitxor(random_number);
+			} else {
+				error!(target: "consensus", "Insufficient random data from node {}", node_id);
+				faults.record(*node_id, batch.epoch, FaultKind::InsufficientRandomData);
+			}
+		}
+
+		Some(QueuedBlock {
+			transactions,
+			timestamp,
+			random_number,
+		})
+	}
 }
 
 struct TransitionHandler {
@@ -119,6 +284,9 @@ impl IoHandler<()> for TransitionHandler {
 	fn timeout(&self, io: &IoContext<()>, timer: TimerToken) {
 		if timer == ENGINE_TIMEOUT_TOKEN {
 			//trace!(target: "consensus", "Honey Badger IoHandler timeout called");
+			// Resume the hbbft epoch we took part in before a restart.
+			self.engine.restore_persisted_state();
+
 			// The block may be complete, but not have been ready to seal - trigger a new seal attempt.
 			// TODO: In theory, that should not happen. The seal is ready exactly when the sealing entry is `Complete`.
 			if let Some(ref weak) = *self.client.read() {
@@ -130,12 +298,19 @@ impl IoHandler<()> for TransitionHandler {
 			// Send Keygen transactions if necessary.
 			// Do this *before* calling on_transactions_imported to avoid starting
 			// a new block without giving it a chance to include Keygen transactions.
-			//self.engine.do_keygen();
+			self.engine.send_keygen_transactions();
+
+			// Keep connections to the other validators of the current and pending validator sets.
+			self.engine.update_validator_peers();
 
 			// @todo Trigger block creation when we are not in the keygen phase yet,
 			//       but should be according to the epoch length settings.
 			self.engine.start_hbbft_epoch_if_next_phase();
 
+			// Create a block if none has been created for the maximum block time, even if no
+			// transactions are queued.
+			self.engine.start_hbbft_epoch_if_maximum_block_time_reached();
+
 			// Transactions may have been submitted during creation of the last block, trigger the
 			// creation of a new block if the transaction threshold has been reached.
 			self.engine.on_transactions_imported();
@@ -143,6 +318,16 @@ impl IoHandler<()> for TransitionHandler {
 			// Periodically allow messages received for future epochs to be processed.
 			self.engine.replay_cached_messages();
 
+			// Create pending blocks for batches which were waiting for their parent block.
+			self.engine.try_create_queued_blocks();
+
+			// Report validators which misbehaved since the last timeout.
+			self.engine.send_fault_reports();
+
+			// Write the messages received since the last timeout to the database. Messages received
+			// after that are lost if the node crashes before the next timeout.
+			self.engine.store_persisted_state();
+
 			// The client may not be registered yet on startup, we set the default duration.
 			let mut timer_duration = DEFAULT_DURATION;
 			if let Some(ref weak) = *self.client.read() {
@@ -167,16 +352,60 @@ impl IoHandler<()> for TransitionHandler {
 
 impl HoneyBadgerBFT {
 	pub fn new(params: HbbftParams, machine: Machine) -> Result<Arc<dyn Engine>, Box<Error>> {
+		Self::new_engine(params, machine).map(|engine| engine as Arc<dyn Engine>)
+	}
+
+	/// Draws the randomness of our contributions from the given generator instead of the thread RNG.
+	#[cfg(any(test, feature = "test-helpers"))]
+	pub(crate) fn set_rng(&self, rng: rand::rngs::StdRng) {
+		self.hbbft_state.write().set_rng(rng);
+	}
+
+	/// Creates the engine, without erasing its type.
+	pub(crate) fn new_engine(params: HbbftParams, machine: Machine) -> Result<Arc<Self>, Box<Error>> {
+		if params.random_bytes_per_epoch.unwrap_or(DEFAULT_RANDOM_BYTES_PER_EPOCH)
+			< MIN_RANDOM_BYTES_PER_EPOCH
+		{
+			return Err(Box::new(
+				EngineError::Custom(format!(
+					"randomBytesPerEpoch must be at least {}.",
+					MIN_RANDOM_BYTES_PER_EPOCH
+				))
+				.into(),
+			));
+		}
+		let encryption_schedule = encryption_schedule(&params)
+			.map_err(|e| Box::new(EngineError::Custom(e).into()))?;
+		if params
+			.maximum_block_time
+			.map_or(false, |maximum| maximum < params.minimum_block_time)
+		{
+			return Err(Box::new(
+				EngineError::Custom("maximumBlockTime must be at least minimumBlockTime.".into())
+					.into(),
+			));
+		}
+		let validator_peers = params
+			.validator_peer_port
+			.map(|port| RwLock::new(ValidatorPeersManager::new(port)));
 		let engine = Arc::new(HoneyBadgerBFT {
 			transition_service: IoService::<()>::start().map_err(|err| Box::new(err.into()))?,
 			client: Arc::new(RwLock::new(None)),
 			signer: Arc::new(RwLock::new(None)),
 			machine,
-			hbbft_state: RwLock::new(HbbftState::new()),
+			hbbft_state: RwLock::new(HbbftState::new(encryption_schedule)),
 			sealing: RwLock::new(BTreeMap::new()),
 			params,
 			message_counter: RwLock::new(0),
 			random_numbers: RwLock::new(BTreeMap::new()),
+			queued_blocks: RwLock::new(BTreeMap::new()),
+			epoch_transition_proofs: RwLock::new(BTreeMap::new()),
+			fault_tracker: RwLock::new(FaultTracker::default()),
+			activity_tracker: RwLock::new(ActivityTracker::default()),
+			keygen_transaction_sender: RwLock::new(KeyGenTransactionSender::new()),
+			validator_peers,
+			persisted_state: RwLock::new(None),
+			startup_messages: RwLock::new(Vec::new()),
 		});
 
 		if !engine.params.is_unit_test.unwrap_or(false) {
@@ -199,69 +428,80 @@ impl HoneyBadgerBFT {
 		output: Vec<Batch>,
 		network_info: &NetworkInfo<NodeId>,
 	) {
-		// TODO: Multiple outputs are possible,
-		//       process all outputs, respecting their epoch context.
-		if output.len() > 1 {
-			error!(target: "consensus", "UNHANDLED EPOCH OUTPUTS!");
-			panic!("UNHANDLED EPOCH OUTPUTS!");
+		// Multiple outputs are possible, e.g. when a lagging validator catches up by replaying
+		// cached messages. Each batch results in its own block, so process them in epoch order.
+		for batch in output.into_iter().sorted_by_key(|batch| batch.epoch) {
+			trace!(target: "consensus", "Batch received for epoch {}, creating new Block.", batch.epoch);
+			{
+				let mut activity_tracker = self.activity_tracker.write();
+				for node_id in batch.contributions.keys() {
+					activity_tracker.record_contribution(*node_id);
+				}
+			}
+
+			let queued_block = match QueuedBlock::from_batch(&batch, &mut self.fault_tracker.write())
+			{
+				Some(queued_block) => queued_block,
+				None => {
+					error!(target: "consensus", "Error calculating the block timestamp for hbbft epoch {}", batch.epoch);
+					continue;
+				}
+			};
+
+			self.random_numbers
+				.write()
+				.insert(batch.epoch, queued_block.random_number);
+			self.queued_blocks.write().insert(batch.epoch, queued_block);
 		}
-		let batch = match output.first() {
-			None => return,
-			Some(batch) => batch,
-		};
 
-		trace!(target: "consensus", "Batch received for epoch {}, creating new Block.", batch.epoch);
+		self.create_queued_blocks(client, network_info);
+	}
 
-		// Decode and de-duplicate transactions
-		let batch_txns: Vec<_> = batch
-			.contributions
-			.iter()
-			.flat_map(|(_, c)| &c.transactions)
-			.filter_map(|ser_txn| {
-				// TODO: Report proposers of malformed transactions.
-				Decodable::decode(&Rlp::new(ser_txn)).ok()
-			})
-			.unique()
-			.filter_map(|txn| {
-				// TODO: Report proposers of invalidly signed transactions.
-				SignedTransaction::new(txn).ok()
-			})
-			.collect();
+	/// Creates pending blocks for queued batches, in epoch order.
+	///
+	/// A pending block can only be created once its parent block has been imported, so blocks
+	/// for later epochs stay queued until the previous block has been sealed and imported.
+	fn create_queued_blocks(&self, client: Arc<dyn EngineClient>, network_info: &NetworkInfo<NodeId>) {
+		loop {
+			let latest = match client.block_number(BlockId::Latest) {
+				Some(latest) => latest,
+				None => return,
+			};
 
-		// We use the median of all contributions' timestamps
-		let mut timestamps = batch
-			.contributions
-			.iter()
-			.map(|(_, c)| c.timestamp)
-			.sorted();
+			let block_num = {
+				let mut queued_blocks = self.queued_blocks.write();
+				// Batches for blocks which are already imported are obsolete.
+				*queued_blocks = queued_blocks.split_off(&(latest + 1));
+				match queued_blocks.keys().next() {
+					Some(block_num) => *block_num,
+					None => return,
+				}
+			};
 
-		let timestamp = match timestamps.nth(timestamps.len() / 2) {
-			Some(t) => t,
-			None => {
-				error!(target: "consensus", "Error calculating the block timestamp");
+			// Wait for the parent block to be imported.
+			if block_num != latest + 1 {
+				trace!(target: "consensus", "Block #{} is queued, waiting for its parent to be imported.", block_num);
 				return;
 			}
-		};
 
-		let random_number = batch
-			.contributions
-			.iter()
-			.fold(U256::zero(), |acc, (n, c)| {
-				if c.random_data.len() >= 32 {
-					U256::from(&c.random_data[0..32]).bitxor(acc)
-				} else {
-					// TODO: Report malicious behavior by node!
-					error!(target: "consensus", "Insufficient random data from node {}", n);
-					acc
-				}
-			});
+			let queued_block = match self.queued_blocks.read().get(&block_num) {
+				Some(queued_block) => queued_block.clone(),
+				None => return,
+			};
 
-		self.random_numbers
-			.write()
-			.insert(batch.epoch, random_number);
+			let header = match client.create_pending_block_at(
+				queued_block.transactions,
+				queued_block.timestamp,
+				block_num,
+			) {
+				Some(header) => header,
+				None => {
+					error!(target: "consensus", "Could not create pending block for hbbft epoch {}: ", block_num);
+					return;
+				}
+			};
+			self.queued_blocks.write().remove(&block_num);
 
-		if let Some(header) = client.create_pending_block_at(batch_txns, timestamp, batch.epoch) {
-			let block_num = header.number();
 			let hash = header.bare_hash();
 			trace!(target: "consensus", "Sending signature share of {} for block {}", hash, block_num);
 			let step = match self
@@ -278,12 +518,28 @@ impl HoneyBadgerBFT {
 					return;
 				}
 			};
-			self.process_seal_step(client, step, block_num, network_info);
-		} else {
-			error!(target: "consensus", "Could not create pending block for hbbft epoch {}: ", batch.epoch);
+			self.process_seal_step(client.clone(), step, block_num, network_info);
+
+			// If the block got sealed and imported right away, continue with the next queued block.
+			match client.block_number(BlockId::Latest) {
+				Some(new_latest) if new_latest >= block_num => continue,
+				_ => return,
+			}
 		}
 	}
 
+	/// Attempts to create pending blocks for batches which were waiting for their parent block.
+	fn try_create_queued_blocks(&self) -> Option<()> {
+		let client = self.client_arc()?;
+		let next_block = self.queued_blocks.read().keys().next().cloned()?;
+		let network_info =
+			self.hbbft_state
+				.write()
+				.network_info_for(client.clone(), &self.signer, next_block)?;
+		self.create_queued_blocks(client, &network_info);
+		Some(())
+	}
+
 	fn process_hb_message(
 		&self,
 		msg_idx: usize,
@@ -292,12 +548,20 @@ impl HoneyBadgerBFT {
 	) -> Result<(), EngineError> {
 		let client = self.client_arc().ok_or(EngineError::RequiresClient)?;
 		trace!(target: "consensus", "Received message of idx {}  {:?} from {}", msg_idx, message, sender_id);
+		self.activity_tracker.write().record_hb_message(sender_id);
+		let persisted_message = match *self.persisted_state.read() {
+			Some(_) => Some(message.clone()),
+			None => None,
+		};
 		let step = self.hbbft_state.write().process_message(
 			client.clone(),
 			&self.signer,
 			sender_id,
 			message,
 		);
+		if let Some(message) = persisted_message {
+			self.persist_hb_message(&client, sender_id, message);
+		}
 
 		if let Some((step, network_info)) = step {
 			self.process_step(client, step, &network_info);
@@ -306,6 +570,37 @@ impl HoneyBadgerBFT {
 		Ok(())
 	}
 
+	/// Records a received Honey Badger message in the persisted state, if it is from a current
+	/// validator and for the hbbft epoch in progress or the next one.
+	///
+	/// Other messages can't help completing the epoch after a restart, and recording them would
+	/// allow any peer to grow the persisted state without bound.
+	fn persist_hb_message(
+		&self,
+		client: &Arc<dyn EngineClient>,
+		sender_id: NodeId,
+		message: HbMessage,
+	) -> Option<()> {
+		let latest = client.block_number(BlockId::Latest)?;
+		let epoch = message.epoch();
+		if epoch <= latest || epoch > latest + 2 {
+			return None;
+		}
+		let max_per_sender = {
+			let hbbft_state = self.hbbft_state.read();
+			let network_info = hbbft_state.network_info()?;
+			if !network_info.all_ids().any(|node_id| *node_id == sender_id) {
+				return None;
+			}
+			network_info.num_nodes() * PERSISTED_HB_MESSAGES_PER_VALIDATOR
+		};
+		self.persisted_state
+			.write()
+			.as_mut()?
+			.record_hb_message(epoch, sender_id, message, max_per_sender);
+		Some(())
+	}
+
 	fn process_sealing_message(
 		&self,
 		message: sealing::Message,
@@ -314,6 +609,7 @@ impl HoneyBadgerBFT {
 	) -> Result<(), EngineError> {
 		let client = self.client_arc().ok_or(EngineError::RequiresClient)?;
 		trace!(target: "consensus", "Received sealing message  {:?} from {}", message, sender_id);
+		self.activity_tracker.write().record_sealing_message(sender_id);
 		if let Some(latest) = client.block_number(BlockId::Latest) {
 			if latest >= block_num {
 				return Ok(()); // Message is obsolete.
@@ -333,6 +629,9 @@ impl HoneyBadgerBFT {
 		};
 
 		trace!(target: "consensus", "Received signature share for block {} from {}", block_num, sender_id);
+		if let Some(persisted_state) = self.persisted_state.write().as_mut() {
+			persisted_state.record_sealing_message(block_num, sender_id, message.clone());
+		}
 		let step_result = self
 			.sealing
 			.write()
@@ -343,6 +642,8 @@ impl HoneyBadgerBFT {
 			Ok(step) => self.process_seal_step(client, step, block_num, &network_info),
 			Err(err) => error!(target: "consensus", "Error on ThresholdSign step: {:?}", err), // TODO: Errors
 		}
+		// The block may have been imported, allowing the next queued block to be created.
+		self.try_create_queued_blocks();
 		Ok(())
 	}
 
@@ -354,9 +655,9 @@ impl HoneyBadgerBFT {
 	) where
 		I: IntoIterator<Item = TargetedMessage>,
 	{
+		let format = self.message_format(client);
 		for m in messages {
-			let ser =
-				serde_json::to_vec(&m.message).expect("Serialization of consensus message failed");
+			let ser = m.message.encode(format);
 			match m.target {
 				Target::Nodes(set) => {
 					trace!(target: "consensus", "Dispatching message {:?} to {:?}", m.message, set);
@@ -414,6 +715,13 @@ impl HoneyBadgerBFT {
 			}
 		});
 		self.dispatch_messages(&client, messages, network_info);
+		if !step.fault_log.is_empty() {
+			// The faults were observed while agreeing on the block following the latest one.
+			let block_num = client.block_number(BlockId::Latest).map_or(0, |n| n + 1);
+			self.fault_tracker
+				.write()
+				.record_fault_log(step.fault_log, block_num);
+		}
 		self.process_output(client, step.output, network_info);
 	}
 
@@ -424,11 +732,23 @@ impl HoneyBadgerBFT {
 		if self.is_syncing(&client) {
 			return Ok(());
 		}
-		let step = self
-			.hbbft_state
-			.write()
-			.contribute_if_contribution_threshold_reached(client.clone(), &self.signer);
+		self.restore_persisted_state();
+		let step = match self.persisted_state.write().as_mut() {
+			Some(persisted_state) => self
+				.hbbft_state
+				.write()
+				.contribute_if_contribution_threshold_reached(
+					client.clone(),
+					&self.signer,
+					&self.params,
+					persisted_state,
+				),
+			// Our contribution from before a restart may not be known yet.
+			None => return Ok(()),
+		};
 		if let Some((step, network_info)) = step {
+			// Persist our contribution before sending it, so the same one is proposed after a restart.
+			self.store_persisted_state();
 			self.process_step(client, step, &network_info)
 		}
 		Ok(())
@@ -438,15 +758,98 @@ impl HoneyBadgerBFT {
 		if self.is_syncing(&client) {
 			return;
 		}
-		let step = self
-			.hbbft_state
-			.write()
-			.try_send_contribution(client.clone(), &self.signer);
+		self.restore_persisted_state();
+		let step = match self.persisted_state.write().as_mut() {
+			Some(persisted_state) => self.hbbft_state.write().try_send_contribution(
+				client.clone(),
+				&self.signer,
+				&self.params,
+				persisted_state,
+			),
+			// Our contribution from before a restart may not be known yet.
+			None => return,
+		};
 		if let Some((step, network_info)) = step {
+			// Persist our contribution before sending it, so the same one is proposed after a restart.
+			self.store_persisted_state();
 			self.process_step(client, step, &network_info)
 		}
 	}
 
+	/// Loads the consensus state persisted before a restart and resumes the hbbft epoch in progress:
+	/// Our contribution is proposed again, and the received messages are handled again.
+	///
+	/// This is done once, as soon as the client and our signer are available.
+	fn restore_persisted_state(&self) -> Option<()> {
+		if self.persisted_state.read().is_some() {
+			return Some(());
+		}
+		let client = self.client_arc()?;
+		if self.signer.read().is_none() {
+			return None;
+		}
+		let latest = client.block_number(BlockId::Latest)?;
+
+		let mut state = PersistedState::load(&*client);
+		state.prune(latest);
+		let (hb_messages, sealing_messages) = state.take_messages();
+		let has_contribution = state.proposal(latest + 1).is_some();
+		{
+			let mut persisted_state = self.persisted_state.write();
+			if persisted_state.is_some() {
+				return Some(());
+			}
+			*persisted_state = Some(state);
+		}
+
+		if has_contribution || !hb_messages.is_empty() || !sealing_messages.is_empty() {
+			debug!(target: "engine", "Resuming hbbft epoch {} with {} Honey Badger messages and {} signature shares from before the restart.",
+				   latest + 1, hb_messages.len(), sealing_messages.len());
+		}
+		if has_contribution {
+			self.start_hbbft_epoch(client);
+		}
+		for (sender_id, message) in hb_messages {
+			if let Err(e) = self.process_hb_message(0, message, sender_id) {
+				error!(target: "engine", "Error handling persisted message: {}", e);
+			}
+		}
+		for (block_num, sender_id, message) in sealing_messages {
+			if let Err(e) = self.process_sealing_message(message, sender_id, block_num) {
+				error!(target: "engine", "Error handling persisted signature share: {}", e);
+			}
+		}
+		let startup_messages = mem::replace(&mut *self.startup_messages.write(), Vec::new());
+		for (sender_id, message) in startup_messages {
+			if let Err(e) = self.process_message(message, sender_id) {
+				error!(target: "engine", "Error handling message received on startup: {}", e);
+			}
+		}
+		Some(())
+	}
+
+	fn process_message(&self, message: Message, sender_id: NodeId) -> Result<(), EngineError> {
+		match message {
+			Message::HoneyBadger(msg_idx, hb_msg) => {
+				self.process_hb_message(msg_idx, hb_msg, sender_id)
+			}
+			Message::Sealing(block_num, seal_msg) => {
+				self.process_sealing_message(seal_msg, sender_id, block_num)
+			}
+		}
+	}
+
+	/// Writes the consensus state of the blocks in progress to the client database, if it changed.
+	fn store_persisted_state(&self) -> Option<()> {
+		let client = self.client_arc()?;
+		let latest = client.block_number(BlockId::Latest)?;
+		self.persisted_state
+			.write()
+			.as_mut()?
+			.store(&*client, latest);
+		Some(())
+	}
+
 	fn transaction_queue_and_time_thresholds_reached(
 		&self,
 		client: &Arc<dyn EngineClient>,
@@ -462,6 +865,31 @@ impl HoneyBadgerBFT {
 		}
 	}
 
+	/// Returns true if the maximum block time elapsed since the latest block.
+	fn maximum_block_time_reached(&self, client: &Arc<dyn EngineClient>) -> bool {
+		let maximum_block_time = match self.params.maximum_block_time {
+			Some(maximum_block_time) => maximum_block_time,
+			None => return false,
+		};
+		client
+			.block_header(BlockId::Latest)
+			.map_or(false, |block_header| {
+				block_header.timestamp() + maximum_block_time <= unix_now_secs()
+			})
+	}
+
+	/// Returns the wire format to use for outgoing messages, based on the block being produced.
+	fn message_format(&self, client: &Arc<dyn EngineClient>) -> MessageFormat {
+		let transition = match self.params.binary_message_transition {
+			Some(transition) => transition,
+			None => return MessageFormat::Json,
+		};
+		match client.block_number(BlockId::Latest) {
+			Some(latest) if latest + 1 >= transition => MessageFormat::BincodeV1,
+			_ => MessageFormat::Json,
+		}
+	}
+
 	fn new_sealing(&self, network_info: &NetworkInfo<NodeId>) -> Sealing {
 		Sealing::new(network_info.clone())
 	}
@@ -488,6 +916,17 @@ impl HoneyBadgerBFT {
 		}
 	}
 
+	/// Starts an hbbft epoch if the maximum block time elapsed, so that an idle chain keeps
+	/// producing blocks with empty contributions.
+	fn start_hbbft_epoch_if_maximum_block_time_reached(&self) {
+		if let Some(client) = self.client_arc() {
+			if self.maximum_block_time_reached(&client) {
+				trace!(target: "consensus", "Maximum block time reached, starting an hbbft epoch.");
+				self.start_hbbft_epoch(client);
+			}
+		}
+	}
+
 	fn replay_cached_messages(&self) -> Option<()> {
 		let client = self.client_arc()?;
 		let steps = self
@@ -545,22 +984,71 @@ impl HoneyBadgerBFT {
 					}
 				}
 
-				// Otherwise check if we are in the pending validator set and send Parts and Acks transactions.
-				// @todo send_keygen_transactions initializes another synckeygen structure, a potentially
-				//       time consuming process. Move sending of keygen transactions into a separate function
-				//       and call it periodically using timer events instead of on close block.
-				if let Some(signer) = self.signer.read().as_ref() {
-					if let Ok(is_pending) = is_pending_validator(&*client, &signer.address()) {
-						if is_pending {
-							let _err = send_keygen_transactions(&*client, &self.signer);
-						}
-					}
-				}
+				// Parts and Acks are sent by `send_keygen_transactions`, triggered by the timer.
 				false
 			}
 		}
 	}
 
+	/// Sends our Part and Acks transactions if we are in the pending validator set.
+	fn send_keygen_transactions(&self) {
+		let client = match self.client_arc() {
+			None => return,
+			Some(client) => client,
+		};
+		if let Err(e) = self
+			.keygen_transaction_sender
+			.write()
+			.send_keygen_transactions(&*client, &self.signer)
+		{
+			error!(target: "engine", "Error sending key generation transactions: {:?}", e);
+		}
+	}
+
+	/// Adds the validators of the current and pending validator sets as reserved peers.
+	fn update_validator_peers(&self) {
+		let validator_peers = match self.validator_peers {
+			None => return,
+			Some(ref validator_peers) => validator_peers,
+		};
+		let client = match self.client_arc() {
+			None => return,
+			Some(client) => client,
+		};
+		if self.is_syncing(&client) {
+			return;
+		}
+		let our_address = self.signer.read().as_ref().map(|signer| signer.address());
+		if let Err(e) = validator_peers.write().update(&*client, our_address) {
+			error!(target: "engine", "Error updating the validator peers: {:?}", e);
+		}
+	}
+
+	/// Stores the key generation data of the pending validator set as epoch transition proof of
+	/// the given block, which switches to the pending validator set.
+	fn record_epoch_transition_proof(&self, block_num: BlockNumber, parent: &Header) {
+		let client = match self.client_arc() {
+			None => return,
+			Some(client) => client,
+		};
+		match KeyGenHistoryData::read(
+			&*client,
+			BlockId::Hash(parent.hash()),
+			ValidatorType::Pending,
+		) {
+			Ok(data) => {
+				trace!(target: "engine", "Block #{} switches to a new validator set.", block_num);
+				self.epoch_transition_proofs
+					.write()
+					.insert(block_num, rlp::encode(&data));
+			}
+			Err(e) => {
+				warn!(target: "engine", "Reading the keygen data for the epoch transition at block #{} failed: {:?}", block_num, e);
+				warn!(target: "engine", "Snapshots produced by this client may be incomplete");
+			}
+		}
+	}
+
 	fn check_for_epoch_change(&self) -> Option<()> {
 		let client = self.client_arc()?;
 		if let None = self.hbbft_state.write().update_honeybadger(
@@ -574,6 +1062,195 @@ impl HoneyBadgerBFT {
 		Some(())
 	}
 
+	/// Reports the validators which misbehaved to the reporting contract, if one is configured.
+	fn send_fault_reports(&self) -> Option<()> {
+		let client = self.client_arc()?;
+		let latest = client.block_number(BlockId::Latest)?;
+		// Faults which can't be reported are dropped, so they don't pile up.
+		let contract_address = match self.params.reporting_contract_address {
+			Some(contract_address) => contract_address,
+			None => {
+				self.fault_tracker.write().take_pending_reports(latest);
+				return None;
+			}
+		};
+		self.fault_tracker.write().prune_pending_reports(latest);
+		if self.is_syncing(&client) {
+			return None;
+		}
+		let our_address: Address = self.signer.read().as_ref()?.address();
+
+		let reports = self.fault_tracker.write().take_pending_reports(latest);
+		for (node_id, block_num) in reports {
+			let malicious = public_to_address(&node_id.0);
+			if malicious == our_address {
+				continue;
+			}
+			match report_malicious_callable(
+				&*client,
+				contract_address,
+				our_address,
+				malicious,
+				block_num,
+			) {
+				Ok(true) => {}
+				Ok(false) => {
+					trace!(target: "engine", "Reporting validator {} for block {} is not callable.", malicious, block_num);
+					continue;
+				}
+				Err(e) => {
+					error!(target: "engine", "Could not check whether validator {} can be reported: {:?}", malicious, e);
+					continue;
+				}
+			}
+			trace!(target: "engine", "Reporting validator {} for misbehavior in block {}.", malicious, block_num);
+			if let Err(e) =
+				send_report_malicious(&*client, contract_address, our_address, malicious, block_num)
+			{
+				error!(target: "engine", "Failed to report validator {}: {:?}", malicious, e);
+			}
+		}
+		Some(())
+	}
+
+	/// Returns the address of the randomness contract used for the given block, if any.
+	fn randomness_contract_address(&self, block_num: BlockNumber) -> Option<Address> {
+		self.params
+			.randomness_contract_address
+			.as_ref()?
+			.range(..=block_num)
+			.last()
+			.map(|(_, address)| *address)
+	}
+
+	/// Returns the random number to store in the given block.
+	///
+	/// Imported blocks carry the random number in their seal, while for blocks created locally it
+	/// is the one agreed upon in the hbbft epoch.
+	fn block_random_number(&self, header: &Header) -> Result<U256, Error> {
+		if let Some(random_rlp) = header.seal().get(1) {
+			return Ok(rlp::decode(random_rlp)?);
+		}
+		match self.random_numbers.read().get(&header.number()) {
+			Some(random_number) => Ok(*random_number),
+			None => Err(EngineError::Custom(
+				"No value available for calling randomness contract.".into(),
+			)
+			.into()),
+		}
+	}
+
+	/// Returns the extra data of the block following the given parent, if the engine determines it.
+	///
+	/// From the `epochSignalTransition` block on, a block switching to a new validator set contains
+	/// the hash of the new threshold public key, and all other blocks have empty extra data.
+	fn engine_extra_data(&self, parent: &Header) -> Option<Vec<u8>> {
+		let transition = self.params.epoch_signal_transition?;
+		if parent.number() + 1 < transition {
+			return None;
+		}
+		// Validator set changes take effect in the block reward contract call.
+		if self.params.block_reward_contract_address.is_none() {
+			return Some(Vec::new());
+		}
+		let client = self.client_arc()?;
+		// If the key generation of the pending validator set is complete, the block switches to it.
+		let extra_data = KeyGenHistoryData::read(
+			&*client,
+			BlockId::Hash(parent.hash()),
+			ValidatorType::Pending,
+		)
+		.ok()
+		.and_then(|data| data.public_key())
+		.map_or_else(Vec::new, |public_key| {
+			public_key_hash(&public_key).as_bytes().to_vec()
+		});
+		Some(extra_data)
+	}
+
+	/// Returns the threshold public key hash of the new validator set, if the header signals a
+	/// validator set change in its extra data.
+	fn signalled_public_key_hash(&self, header: &Header) -> Option<H256> {
+		let transition = self.params.epoch_signal_transition?;
+		if header.number() < transition || header.extra_data().len() != H256::len_bytes() {
+			return None;
+		}
+		Some(H256::from_slice(header.extra_data()))
+	}
+
+	/// Returns a snapshot of the consensus state.
+	fn status(&self) -> HbbftStatus {
+		let (node_id, address) = match self.signer.read().as_ref() {
+			Some(signer) => (signer.public().map(NodeId), Some(signer.address())),
+			None => (None, None),
+		};
+		let (hbbft_epoch, posdao_epoch, future_messages) = {
+			let hbbft_state = self.hbbft_state.read();
+			(
+				hbbft_state.hbbft_epoch(),
+				hbbft_state.posdao_epoch(),
+				hbbft_state.future_messages_count(),
+			)
+		};
+		let sealing = self
+			.sealing
+			.read()
+			.iter()
+			.map(|(block_num, sealing)| {
+				let status = match sealing.signature() {
+					Some(_) => SealingStatus::Complete,
+					None => SealingStatus::Ongoing,
+				};
+				(*block_num, status)
+			})
+			.collect();
+
+		let client = self.client_arc();
+		let is_current_validator = match (&client, address) {
+			(Some(client), Some(address)) => {
+				get_validator_pubkeys(&**client, BlockId::Latest, ValidatorType::Current)
+					.map_or(false, |validators| validators.contains_key(&address))
+			}
+			_ => false,
+		};
+		let is_pending_validator = match (&client, address) {
+			(Some(client), Some(address)) => {
+				is_pending_validator(&**client, &address).unwrap_or(false)
+			}
+			_ => false,
+		};
+		let keygen = client.and_then(|client| self.keygen_status(&*client));
+
+		HbbftStatus {
+			hbbft_epoch,
+			posdao_epoch,
+			node_id,
+			address,
+			is_current_validator,
+			is_pending_validator,
+			validators: self.activity_tracker.read().activity(),
+			sealing,
+			future_messages,
+			keygen,
+		}
+	}
+
+	/// Returns the Parts and Acks the pending validators have written to the key history contract.
+	fn keygen_status(&self, client: &dyn EngineClient) -> Option<KeyGenStatus> {
+		let mut validators = BTreeMap::new();
+		for validator in get_pending_validators(client).ok()? {
+			let status = KeyGenValidatorStatus {
+				part: has_part_of_address_data(client, validator).ok()?,
+				acks: acks_of_address_count(client, validator).ok()?,
+			};
+			validators.insert(validator, status);
+		}
+		Some(KeyGenStatus {
+			phase: self.keygen_transaction_sender.read().phase(),
+			validators,
+		})
+	}
+
 	fn is_syncing(&self, client: &Arc<dyn EngineClient>) -> bool {
 		match client.as_full_client() {
 			Some(full_client) => full_client.is_major_syncing(),
@@ -598,12 +1275,46 @@ impl Engine for HoneyBadgerBFT {
 	}
 
 	/// Phase 1 Checks
-	fn verify_block_basic(&self, _header: &Header) -> Result<(), Error> {
+	/// The structure of the seal and the author are checked here, which is cheap.
+	fn verify_block_basic(&self, header: &Header) -> Result<(), Error> {
+		let expected_seal_fields = self.seal_fields(header);
+		if header.seal().len() != expected_seal_fields {
+			return Err(BlockError::InvalidSealArity(Mismatch {
+				expected: expected_seal_fields,
+				found: header.seal().len(),
+			})
+			.into());
+		}
+
+		let sig_len = Rlp::new(&header.seal()[0]).data()?.len();
+		if sig_len != SIGNATURE_LENGTH {
+			return Err(EngineError::BadSealFieldSize(OutOfBounds {
+				min: Some(SIGNATURE_LENGTH),
+				max: Some(SIGNATURE_LENGTH),
+				found: sig_len,
+			})
+			.into());
+		}
+		if let Some(random_rlp) = header.seal().get(1) {
+			let _random_number: U256 = rlp::decode(random_rlp)?;
+		}
+
+		// Blocks are created by all validators together, so they have no author.
+		if !header.author().is_zero() {
+			return Err(EngineError::NotProposer(Mismatch {
+				expected: Address::zero(),
+				found: *header.author(),
+			})
+			.into());
+		}
 		Ok(())
 	}
 
 	/// Pase 2 Checks
-	fn verify_block_unordered(&self, _header: &Header) -> Result<(), Error> {
+	/// The seal must contain a valid signature, which is expensive to decode.
+	fn verify_block_unordered(&self, header: &Header) -> Result<(), Error> {
+		let _sig: RlpSig<hbbft::crypto::Signature> =
+			rlp::decode(header.seal().first().ok_or(BlockError::InvalidSeal)?)?;
 		Ok(())
 	}
 
@@ -611,6 +1322,10 @@ impl Engine for HoneyBadgerBFT {
 	/// We check the signature here since at this point the blocks are imported in-order.
 	/// To verify the signature we need the parent block already imported on the chain.
 	fn verify_block_family(&self, header: &Header, _parent: &Header) -> Result<(), Error> {
+		// The timestamp is checked against the parent's by the generic family verification, using
+		// `is_timestamp_valid`, and against the local clock by the header time verification. There is
+		// no upper bound relative to the parent: blocks are only produced once the validators agree on
+		// a batch, which can take arbitrarily long if too few of them are online.
 		let client = self.client_arc().ok_or(EngineError::RequiresClient)?;
 
 		let latest_block_nr = client.block_number(BlockId::Latest).expect("must succeed");
@@ -620,10 +1335,33 @@ impl Engine for HoneyBadgerBFT {
 			return Err(BlockError::InvalidSeal.into());
 		}
 
-		if header.seal().len() != 1 {
+		if header.seal().len() != self.seal_fields(header) {
 			return Err(BlockError::InvalidSeal.into());
 		}
 
+		// If we took part in agreeing on the block, its random number must match ours.
+		if let Some(random_rlp) = header.seal().get(1) {
+			let random_number: U256 = rlp::decode(random_rlp)?;
+			if let Some(ours) = self.random_numbers.read().get(&header.number()) {
+				if *ours != random_number {
+					error!(target: "engine", "Random number of block #{} does not match the one agreed upon!", header.number());
+					return Err(BlockError::InvalidSeal.into());
+				}
+			}
+		}
+
+		// Light clients have no access to the chain state, and verify the seal against the threshold
+		// key of the parent's epoch instead.
+		if client.as_full_client().is_none() {
+			let transition = client
+				.epoch_transition_for(*header.parent_hash())
+				.ok_or(BlockError::UnknownEpochTransition(header.number()))?;
+			return self
+				.epoch_verifier(header, &transition.proof)
+				.known_confirmed()?
+				.verify_light(header);
+		}
+
 		let RlpSig(sig) = rlp::decode(header.seal().first().ok_or(BlockError::InvalidSeal)?)?;
 		if self
 			.hbbft_state
@@ -638,10 +1376,135 @@ impl Engine for HoneyBadgerBFT {
 	}
 
 	// Phase 4
+	// All checks, including the threshold signature, are done in the previous phases.
 	fn verify_block_external(&self, _header: &Header) -> Result<(), Error> {
 		Ok(())
 	}
 
+	fn genesis_epoch_data(&self, _header: &Header, call: &Call) -> Result<Vec<u8>, String> {
+		let call = |address, data| call(address, data).map(|(output, _)| output);
+		KeyGenHistoryData::read_with_call(&call, ValidatorType::Current)
+			.map(|data| rlp::encode(&data))
+			.map_err(|e| format!("Reading the genesis keygen data failed: {:?}", e))
+	}
+
+	fn signals_epoch_end(&self, header: &Header, _receipts: Option<&[Receipt]>) -> EpochChange {
+		// The proof is recorded when the block is closed, since the switch to the new validator
+		// set happens in the block reward contract call.
+		if let Some(proof) = self.epoch_transition_proofs.read().get(&header.number()) {
+			return EpochChange::Yes(Proof::Known(proof.clone()));
+		}
+		// Nodes which did not execute the block, like light clients, rely on the signal in the
+		// extra data, and fetch the proof from their peers.
+		match self.signalled_public_key_hash(header) {
+			Some(hash) => EpochChange::Yes(Proof::WithState(Arc::new(KeyChangeProof::new(hash)))),
+			None => EpochChange::No,
+		}
+	}
+
+	fn is_epoch_end(
+		&self,
+		chain_head: &Header,
+		_finalized: &[H256],
+		_chain: &Headers<Header>,
+		transition_store: &PendingTransitionStore,
+	) -> Option<Vec<u8>> {
+		// Blocks are final as soon as they are sealed, so signalled transitions apply immediately.
+		let mut proofs = self.epoch_transition_proofs.write();
+		*proofs = proofs.split_off(&(chain_head.number() + 1));
+		transition_store(chain_head.hash()).map(|pending| pending.proof)
+	}
+
+	fn is_epoch_end_light(
+		&self,
+		chain_head: &Header,
+		chain: &Headers<Header>,
+		transition_store: &PendingTransitionStore,
+	) -> Option<Vec<u8>> {
+		// No finality needs to be built, since blocks are final as soon as they are sealed.
+		self.is_epoch_end(chain_head, &[], chain, transition_store)
+	}
+
+	fn epoch_verifier<'a>(&self, _header: &Header, proof: &'a [u8]) -> ConstructedVerifier<'a> {
+		let data: KeyGenHistoryData = match rlp::decode(proof) {
+			Ok(data) => data,
+			Err(e) => return ConstructedVerifier::Err(e.into()),
+		};
+		match data.public_key() {
+			Some(public_key) => {
+				ConstructedVerifier::Trusted(Box::new(sealing::EpochVerifier::new(public_key)))
+			}
+			None => ConstructedVerifier::Err(
+				EngineError::Custom("Invalid hbbft epoch transition proof.".into()).into(),
+			),
+		}
+	}
+
+	fn fault_counts(&self) -> BTreeMap<Address, BTreeMap<String, u64>> {
+		self.fault_tracker.read().fault_counts()
+	}
+
+	fn consensus_status(&self) -> Option<serde_json::Value> {
+		serde_json::to_value(self.status()).ok()
+	}
+
+	fn prometheus_metrics(&self, r: &mut PrometheusRegistry) {
+		let (hbbft_epoch, posdao_epoch, future_messages) = {
+			let hbbft_state = self.hbbft_state.read();
+			(
+				hbbft_state.hbbft_epoch(),
+				hbbft_state.posdao_epoch(),
+				hbbft_state.future_messages_count(),
+			)
+		};
+		if let Some(hbbft_epoch) = hbbft_epoch {
+			r.register_gauge(
+				"hbbft_epoch",
+				"The hbbft epoch, i.e. the number of the block being agreed upon",
+				hbbft_epoch,
+			);
+		}
+		r.register_gauge(
+			"hbbft_posdao_epoch",
+			"The POSDAO staking epoch of the hbbft state",
+			posdao_epoch,
+		);
+		r.register_gauge(
+			"hbbft_future_messages",
+			"Cached messages for future hbbft epochs",
+			future_messages,
+		);
+		r.register_gauge(
+			"hbbft_sealing_blocks",
+			"Blocks for which signature shares are being collected",
+			self.sealing.read().len(),
+		);
+
+		let activity = self.activity_tracker.read().activity();
+		r.register_labeled(
+			MetricType::Counter,
+			"hbbft_contributions",
+			"Contributions included in batches, by validator",
+			"validator",
+			activity
+				.iter()
+				.map(|(address, activity)| (format!("{:?}", address), activity.contributions)),
+		);
+		r.register_labeled(
+			MetricType::Counter,
+			"hbbft_messages",
+			"Honey Badger messages received, by validator",
+			"validator",
+			activity
+				.iter()
+				.map(|(address, activity)| (format!("{:?}", address), activity.hb_messages)),
+		);
+	}
+
+	fn snapshot_mode(&self) -> Snapshotting {
+		Snapshotting::Hbbft
+	}
+
 	fn register_client(&self, client: Weak<dyn EngineClient>) {
 		*self.client.write() = Some(client.clone());
 		if let Some(client) = self.client_arc() {
@@ -685,14 +1548,17 @@ impl Engine for HoneyBadgerBFT {
 		block: &ExecutedBlock,
 	) -> Result<Vec<SignedTransaction>, Error> {
 		self.check_for_epoch_change();
-		let _random_number = match self.random_numbers.read().get(&block.header.number()) {
-			None => {
-				return Err(Error::Engine(EngineError::Custom(
-					"No value available for calling randomness contract.".into(),
-				)))
-			}
-			Some(r) => r,
-		};
+		// Blocks are only created for batches output by hbbft, which determine the random number.
+		// It is stored in the randomness contract by a system call in `on_close_block`.
+		if !self
+			.random_numbers
+			.read()
+			.contains_key(&block.header.number())
+		{
+			return Err(Error::Engine(EngineError::Custom(
+				"No value available for calling randomness contract.".into(),
+			)));
+		}
 		Ok(Vec::new())
 	}
 
@@ -718,6 +1584,13 @@ impl Engine for HoneyBadgerBFT {
 		SealingState::NotReady
 	}
 
+	fn step(&self) {
+		// Allows triggering the timer driven work without timer, e.g. in unit tests.
+		self.restore_persisted_state();
+		self.send_keygen_transactions();
+		self.store_persisted_state();
+	}
+
 	fn on_transactions_imported(&self) {
 		self.check_for_epoch_change();
 		if let Some(client) = self.client_arc() {
@@ -730,21 +1603,29 @@ impl Engine for HoneyBadgerBFT {
 	fn handle_message(&self, message: &[u8], node_id: Option<H512>) -> Result<(), EngineError> {
 		self.check_for_epoch_change();
 		let node_id = NodeId(node_id.ok_or(EngineError::UnexpectedMessage)?);
-		match serde_json::from_slice(message) {
-			Ok(Message::HoneyBadger(msg_idx, hb_msg)) => {
-				self.process_hb_message(msg_idx, hb_msg, node_id)
-			}
-			Ok(Message::Sealing(block_num, seal_msg)) => {
-				self.process_sealing_message(seal_msg, node_id, block_num)
+		let message = Message::decode(message)?;
+		if self.restore_persisted_state().is_none() {
+			// Keep the message until the state is restored, so it is recorded as well.
+			let mut startup_messages = self.startup_messages.write();
+			// Once restored, the kept messages are not looked at again.
+			if self.persisted_state.read().is_none() {
+				if startup_messages.len() < MAX_STARTUP_MESSAGES {
+					startup_messages.push((node_id, message));
+				} else {
+					debug!(target: "engine", "Dropping message from {} received before the consensus state was restored.", node_id);
+				}
+				return Ok(());
 			}
-			Err(_) => Err(EngineError::MalformedMessage(
-				"Serde message decoding failed.".into(),
-			)),
 		}
+		self.process_message(message, node_id)
 	}
 
-	fn seal_fields(&self, _header: &Header) -> usize {
-		1
+	fn seal_fields(&self, header: &Header) -> usize {
+		// The threshold signature, followed by the random number if a randomness contract is used.
+		match self.randomness_contract_address(header.number()) {
+			Some(_) => 2,
+			None => 1,
+		}
 	}
 
 	fn generate_seal(&self, block: &ExecutedBlock, _parent: &Header) -> Seal {
@@ -768,7 +1649,17 @@ impl Engine for HoneyBadgerBFT {
 			return Seal::None;
 		}
 		trace!(target: "consensus", "Returning generated seal for block {}.", block_num);
-		Seal::Regular(vec![rlp::encode(&RlpSig(sig))])
+		let mut seal = vec![rlp::encode(&RlpSig(sig))];
+		if self.randomness_contract_address(block_num).is_some() {
+			match self.random_numbers.read().get(&block_num) {
+				Some(random_number) => seal.push(rlp::encode(random_number)),
+				None => {
+					error!(target: "consensus", "generate_seal: No random number available for block {}.", block_num);
+					return Seal::None;
+				}
+			}
+		}
+		Seal::Regular(seal)
 	}
 
 	fn should_miner_prepare_blocks(&self) -> bool {
@@ -783,12 +1674,37 @@ impl Engine for HoneyBadgerBFT {
 		false
 	}
 
-	fn on_close_block(&self, block: &mut ExecutedBlock, _parent: &Header) -> Result<(), Error> {
+	fn populate_from_parent(&self, header: &mut Header, parent: &Header) {
+		if let Some(extra_data) = self.engine_extra_data(parent) {
+			header.set_extra_data(extra_data);
+		}
+	}
+
+	fn on_close_block(&self, block: &mut ExecutedBlock, parent: &Header) -> Result<(), Error> {
 		self.check_for_epoch_change();
+		if let Some(extra_data) = self.engine_extra_data(parent) {
+			if *block.header.extra_data() != extra_data {
+				return Err(EngineError::Custom(format!(
+					"Extra data of block #{} does not signal the validator set change correctly.",
+					block.header.number()
+				))
+				.into());
+			}
+		}
+		if let Some(address) = self.randomness_contract_address(block.header.number()) {
+			let random_number = self.block_random_number(&block.header)?;
+			let mut call = engine::default_system_or_code_call(&self.machine, block);
+			let contract = RandomHbbftContract::new_from_address(address);
+			contract.set_current_seed(&mut call, random_number)?;
+		}
 		if let Some(address) = self.params.block_reward_contract_address {
+			let is_epoch_end = self.do_keygen();
+			if is_epoch_end {
+				self.record_epoch_transition_proof(block.header.number(), parent);
+			}
 			let mut call = engine::default_system_or_code_call(&self.machine, block);
 			let contract = BlockRewardContract::new_from_address(address);
-			let _total_reward = contract.reward(&mut call, self.do_keygen())?;
+			let _total_reward = contract.reward(&mut call, is_epoch_end)?;
 		}
 		Ok(())
 	}
@@ -796,14 +1712,29 @@ impl Engine for HoneyBadgerBFT {
 
 #[cfg(test)]
 mod tests {
-	use crate::contribution::Contribution;
-	use crate::utils::test_helpers::create_transaction;
+	use super::{Message, MessageFormat, QueuedBlock};
+	use crate::accountability::FaultTracker;
+	use crate::contribution::{Contribution, DEFAULT_RANDOM_BYTES_PER_EPOCH};
+	use crate::hbbft_state::Batch;
+	use crate::persisted_state::{PersistedState, Proposal};
+	use crate::sealing;
+	use crate::contribution::unix_now_secs;
+	use crate::utils::test_helpers::{
+		create_transaction, hbbft_client, hbbft_client_with_engine, MASTER_OF_CEREMONIES_KEYPAIR,
+	};
+	use crate::NodeId;
+	use client_traits::{BlockInfo, EngineClient, Nonce};
+	use common_types::ids::BlockId;
 	use common_types::transaction::SignedTransaction;
+	use engine::signer::from_keypair;
+	use engine::Engine;
 	use ethereum_types::U256;
+	use hbbft::crypto::SecretKeySet;
 	use hbbft::honey_badger::{HoneyBadger, HoneyBadgerBuilder};
 	use hbbft::NetworkInfo;
-	use parity_crypto::publickey::{Generator, Random};
+	use parity_crypto::publickey::{public_to_address, Generator, Random};
 	use rand;
+	use std::collections::BTreeMap;
 	use std::sync::Arc;
 
 	#[test]
@@ -824,7 +1755,8 @@ mod tests {
 		let mut pending: Vec<SignedTransaction> = Vec::new();
 		let keypair = Random.generate();
 		pending.push(create_transaction(&keypair, &U256::from(1)));
-		let input_contribution = Contribution::new(&pending);
+		let input_contribution =
+			Contribution::new(&pending, DEFAULT_RANDOM_BYTES_PER_EPOCH, &mut rng);
 
 		let step = honey_badger
 			.propose(&input_contribution, &mut rng)
@@ -837,4 +1769,266 @@ mod tests {
 		assert_eq!(out.contributions.len(), 1);
 		assert_eq!(out.contributions.get(&0).unwrap(), &input_contribution);
 	}
+
+	#[test]
+	fn test_queued_blocks_from_multiple_batches() {
+		let mut rng = rand::thread_rng();
+		let node_id = NodeId(*Random.generate().public());
+		let net_infos = NetworkInfo::generate_map(vec![node_id], &mut rng)
+			.expect("NetworkInfo generation is expected to always succeed");
+
+		let net_info = net_infos
+			.get(&node_id)
+			.expect("A NetworkInfo must exist for our node");
+
+		let mut builder: HoneyBadgerBuilder<Contribution, _> =
+			HoneyBadger::builder(Arc::new(net_info.clone()));
+
+		let mut honey_badger = builder.build();
+
+		let keypair = Random.generate();
+		let mut batches = Vec::new();
+		for nonce in 0..2 {
+			let pending = vec![create_transaction(&keypair, &U256::from(nonce))];
+			let step = honey_badger
+				.propose(
+					&Contribution::new(&pending, DEFAULT_RANDOM_BYTES_PER_EPOCH, &mut rng),
+					&mut rng,
+				)
+				.expect("Since there is only one validator we expect an immediate result");
+			batches.extend(step.output);
+		}
+
+		// Each batch is turned into its own block, in epoch order.
+		assert_eq!(batches.len(), 2);
+		for (epoch, batch) in batches.iter().enumerate() {
+			assert_eq!(batch.epoch, epoch as u64);
+			let mut faults = FaultTracker::default();
+			let queued_block =
+				QueuedBlock::from_batch(batch, &mut faults).expect("Batch must be decodable");
+			assert_eq!(queued_block.transactions.len(), 1);
+			assert_eq!(queued_block.transactions[0].nonce, U256::from(epoch));
+			assert!(faults.fault_counts().is_empty());
+		}
+	}
+
+	#[test]
+	fn test_process_output_with_multiple_batches() {
+		let keypair = MASTER_OF_CEREMONIES_KEYPAIR.clone();
+		let (client, engine) = hbbft_client_with_engine();
+		engine.set_signer(Some(from_keypair(keypair.clone())));
+		engine.register_client(Arc::downgrade(&client) as _);
+		let engine_client = client.clone() as Arc<dyn EngineClient>;
+		let network_info = engine
+			.hbbft_state
+			.write()
+			.network_info_for(engine_client.clone(), &engine.signer, 1)
+			.expect("Our node must be the validator of block 1");
+
+		// One output holding the batches for blocks 1 and 2, as when catching up with cached messages.
+		let nonce = client.latest_nonce(&keypair.address());
+		let transactions: Vec<_> = (0..2u64)
+			.map(|i| create_transaction(&keypair, &(nonce + i)))
+			.collect();
+		let start_time = unix_now_secs() - 10;
+		let batches = (1..3u64)
+			.rev()
+			.map(|epoch| {
+				let transaction = transactions[epoch as usize - 1].clone();
+				let mut contribution = Contribution::new(
+					&vec![transaction],
+					DEFAULT_RANDOM_BYTES_PER_EPOCH,
+					&mut rand::thread_rng(),
+				);
+				contribution.timestamp = start_time + epoch;
+				let mut contributions = BTreeMap::new();
+				contributions.insert(*network_info.our_id(), contribution);
+				Batch {
+					epoch,
+					contributions,
+				}
+			})
+			.collect();
+		engine.process_output(engine_client, batches, &network_info);
+
+		// Both blocks are created in epoch order, each on top of the previous one.
+		assert_eq!(client.chain().best_block_number(), 2);
+		let header = |num| {
+			client
+				.block_header(BlockId::Number(num))
+				.expect("Block must exist")
+				.decode(engine.params().eip1559_transition)
+				.expect("Header must be decodable")
+		};
+		for num in 1..3u64 {
+			assert_eq!(header(num).parent_hash(), &header(num - 1).hash());
+			assert_eq!(header(num).timestamp(), start_time + num);
+			let block = client
+				.block(BlockId::Number(num))
+				.expect("Block must exist");
+			assert_eq!(
+				block.transaction_hashes(),
+				vec![transactions[num as usize - 1].hash()]
+			);
+		}
+		assert!(engine.queued_blocks.read().is_empty());
+	}
+
+	#[test]
+	fn test_contribution_faults() {
+		let mut rng = rand::thread_rng();
+		let node_id = NodeId(*Random.generate().public());
+		let net_infos = NetworkInfo::generate_map(vec![node_id], &mut rng)
+			.expect("NetworkInfo generation is expected to always succeed");
+
+		let net_info = net_infos
+			.get(&node_id)
+			.expect("A NetworkInfo must exist for our node");
+
+		let mut builder: HoneyBadgerBuilder<Contribution, _> =
+			HoneyBadger::builder(Arc::new(net_info.clone()));
+
+		let mut honey_badger = builder.build();
+
+		// A contribution with a valid and a malformed transaction, and too little random data.
+		let keypair = Random.generate();
+		let mut contribution = Contribution::new(
+			&vec![create_transaction(&keypair, &U256::from(0))],
+			DEFAULT_RANDOM_BYTES_PER_EPOCH,
+			&mut rng,
+		);
+		contribution.transactions.push(vec![1, 2, 3]);
+		contribution.random_data.truncate(16);
+
+		let step = honey_badger
+			.propose(&contribution, &mut rng)
+			.expect("Since there is only one validator we expect an immediate result");
+		let batch = step.output.first().expect("A batch must be output");
+
+		let mut faults = FaultTracker::default();
+		let queued_block =
+			QueuedBlock::from_batch(batch, &mut faults).expect("Batch must be decodable");
+		assert_eq!(queued_block.transactions.len(), 1);
+		assert_eq!(queued_block.random_number, U256::zero());
+
+		let counts = faults.fault_counts();
+		let node_counts = &counts[&public_to_address(&node_id.0)];
+		assert_eq!(node_counts["MalformedTransaction"], 1);
+		assert_eq!(node_counts["InsufficientRandomData"], 1);
+		assert_eq!(faults.take_pending_reports(batch.epoch).len(), 1);
+	}
+
+	#[test]
+	fn test_random_number_combines_contributions() {
+		let contributions: BTreeMap<_, _> = [0x01u8, 0x02, 0x04]
+			.iter()
+			.map(|byte| {
+				let mut contribution = Contribution::new(
+					&Vec::new(),
+					DEFAULT_RANDOM_BYTES_PER_EPOCH,
+					&mut rand::thread_rng(),
+				);
+				contribution.random_data = vec![*byte; DEFAULT_RANDOM_BYTES_PER_EPOCH];
+				(NodeId(*Random.generate().public()), contribution)
+			})
+			.collect();
+		let batch = Batch {
+			epoch: 0,
+			contributions,
+		};
+
+		// The random number is the XOR of the first 32 random bytes of all contributions.
+		let mut faults = FaultTracker::default();
+		let queued_block =
+			QueuedBlock::from_batch(&batch, &mut faults).expect("Batch must be decodable");
+		assert_eq!(queued_block.random_number, U256::from(&[0x07u8; 32][..]));
+		assert!(faults.fault_counts().is_empty());
+	}
+
+	#[test]
+	fn test_restore_persisted_state() {
+		let keypair = MASTER_OF_CEREMONIES_KEYPAIR.clone();
+		let client = hbbft_client();
+		let engine = client.engine();
+		engine.register_client(Arc::downgrade(&client) as _);
+
+		// Our contribution to block 1 from before the restart.
+		let transaction = create_transaction(&keypair, &client.latest_nonce(&keypair.address()));
+		let mut state = PersistedState::default();
+		let mut rng = rand::thread_rng();
+		let contribution = Contribution::new(
+			&vec![transaction.clone()],
+			DEFAULT_RANDOM_BYTES_PER_EPOCH,
+			&mut rng,
+		);
+		state.record_proposal(1, Proposal::new(contribution, &mut rng));
+		state.store(&*client, 0);
+
+		// Without a signer the state can't be restored yet, so received messages are kept.
+		let sender_id = NodeId(*Random.generate().public());
+		let sk_share = SecretKeySet::random(0, &mut rand::thread_rng()).secret_key_share(0);
+		let share = sealing::Message(sk_share.sign(b"block 2"));
+		let message = Message::Sealing(2, share.clone()).encode(MessageFormat::BincodeV1);
+		engine
+			.handle_message(&message, Some(sender_id.0))
+			.expect("Message must be accepted");
+		assert_eq!(client.chain().best_block_number(), 0);
+
+		// Once the signer is set, the persisted contribution is proposed again.
+		engine.set_signer(Some(from_keypair(keypair)));
+		engine.step();
+		assert_eq!(client.chain().best_block_number(), 1);
+		let block = client
+			.block(BlockId::Number(1))
+			.expect("Block 1 must exist");
+		assert_eq!(block.transaction_hashes(), vec![transaction.hash()]);
+
+		// The message received on startup has been handled and recorded.
+		let (_, sealing_messages) = PersistedState::load(&*client).take_messages();
+		assert_eq!(sealing_messages, vec![(2, sender_id, share)]);
+	}
+
+	#[test]
+	fn test_message_encoding() {
+		let mut rng = rand::thread_rng();
+		let net_infos = NetworkInfo::generate_map(0..4usize, &mut rng)
+			.expect("NetworkInfo generation is expected to always succeed");
+		let net_info = net_infos
+			.get(&0)
+			.expect("A NetworkInfo must exist for node 0");
+
+		let mut builder: HoneyBadgerBuilder<Contribution, _> =
+			HoneyBadger::builder(Arc::new(net_info.clone()));
+
+		let mut honey_badger = builder.build();
+
+		let keypair = Random.generate();
+		let pending = vec![create_transaction(&keypair, &U256::from(1))];
+		let step = honey_badger
+			.propose(
+				&Contribution::new(&pending, DEFAULT_RANDOM_BYTES_PER_EPOCH, &mut rng),
+				&mut rng,
+			)
+			.expect("Proposing a contribution must succeed");
+		let hb_msg = step
+			.messages
+			.into_iter()
+			.next()
+			.expect("A contribution must result in outgoing messages")
+			.message;
+		let message = Message::HoneyBadger(1, hb_msg);
+
+		let json = message.encode(MessageFormat::Json);
+		let binary = message.encode(MessageFormat::BincodeV1);
+		assert!(binary.len() < json.len());
+
+		// Both formats must be decodable, so nodes can talk to peers which did not switch yet.
+		for encoded in &[json, binary] {
+			let decoded = Message::decode(encoded).expect("Message decoding must succeed");
+			assert_eq!(format!("{:?}", decoded), format!("{:?}", message));
+		}
+
+		assert!(Message::decode(&[]).is_err());
+		assert!(Message::decode(&[1, 2, 3]).is_err());
+	}
 }
@@ -2,17 +2,25 @@ use client_traits::EngineClient;
 use common_types::header::Header;
 use common_types::ids::BlockId;
 use engine::signer::EngineSigner;
+use ethjson::spec::{HbbftEncryptionSchedule, HbbftParams};
 use hbbft::crypto::{PublicKey, Signature};
-use hbbft::honey_badger::{self, HoneyBadgerBuilder};
+use hbbft::honey_badger::{self, EncryptionSchedule, HoneyBadgerBuilder};
+use hbbft::sync_key_gen::SyncKeyGen;
 use hbbft::{Epoched, NetworkInfo};
+use parity_crypto::publickey::Public;
 use parking_lot::RwLock;
+use rand::rngs::StdRng;
+use rand::RngCore;
 use std::collections::BTreeMap;
 use std::sync::Arc;
 
-use crate::contracts::keygen_history::{initialize_synckeygen, synckeygen_to_network_info};
+use crate::contracts::keygen_history::{
+	initialize_synckeygen, synckeygen_to_network_info, KeyGenHistoryData, PublicWrapper,
+};
 use crate::contracts::staking::{get_posdao_epoch, get_posdao_epoch_start};
 use crate::contracts::validator_set::ValidatorType;
-use crate::contribution::Contribution;
+use crate::contribution::{select_transactions, Contribution, DEFAULT_RANDOM_BYTES_PER_EPOCH};
+use crate::persisted_state::{PersistedState, Proposal};
 use crate::NodeId;
 
 pub type HbMessage = honey_badger::Message<NodeId>;
@@ -21,28 +29,92 @@ pub(crate) type Batch = honey_badger::Batch<Contribution, NodeId>;
 pub(crate) type HoneyBadgerStep = honey_badger::Step<Contribution, NodeId>;
 pub(crate) type HoneyBadgerResult = honey_badger::Result<HoneyBadgerStep>;
 
+/// Initializes a SyncKeyGen instance for the validator set of the POSDAO epoch the given block is part of.
+///
+/// The key generation data is read from the key history contract at the start of the POSDAO epoch.
+/// If that state is not available, e.g. after restoring from a snapshot, the data is taken from the
+/// epoch transition proof stored in the chain instead.
+pub(crate) fn synckeygen_for_block(
+	client: &dyn EngineClient,
+	signer: &Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
+	block_id: BlockId,
+) -> Option<SyncKeyGen<Public, PublicWrapper>> {
+	let posdao_epoch_start = get_posdao_epoch_start(client, block_id).ok()?;
+	match initialize_synckeygen(
+		client,
+		signer,
+		BlockId::Number(posdao_epoch_start.low_u64()),
+		ValidatorType::Current,
+	) {
+		Ok(synckeygen) => Some(synckeygen),
+		Err(e) => {
+			trace!(target: "engine", "Reading keygen data at block #{} failed: {:?}, falling back to the epoch transition proof.", posdao_epoch_start, e);
+			let header = client.block_header(block_id)?;
+			let transition = client.epoch_transition_for(header.hash())?;
+			let data: KeyGenHistoryData = rlp::decode(&transition.proof).ok()?;
+			data.synckeygen(signer).ok()
+		}
+	}
+}
+
+/// Returns the configured schedule of the epochs in which the contributions are threshold encrypted.
+pub(crate) fn encryption_schedule(
+	params: &HbbftParams,
+) -> Result<Option<EncryptionSchedule>, String> {
+	let schedule = match params.encryption_schedule {
+		None => return Ok(None),
+		Some(HbbftEncryptionSchedule::Always) => EncryptionSchedule::Always,
+		Some(HbbftEncryptionSchedule::Never) => EncryptionSchedule::Never,
+		Some(HbbftEncryptionSchedule::EveryNthEpoch(0)) => {
+			return Err("encryptionSchedule everyNthEpoch must be non-zero.".into())
+		}
+		Some(HbbftEncryptionSchedule::EveryNthEpoch(n)) => EncryptionSchedule::EveryNthEpoch(n),
+		Some(HbbftEncryptionSchedule::TickTock(0, 0)) => {
+			return Err("encryptionSchedule tickTock must not be all zero.".into())
+		}
+		Some(HbbftEncryptionSchedule::TickTock(on, off)) => EncryptionSchedule::TickTock(on, off),
+	};
+	Ok(Some(schedule))
+}
+
 pub(crate) struct HbbftState {
 	network_info: Option<NetworkInfo<NodeId>>,
 	honey_badger: Option<HoneyBadger>,
 	public_master_key: Option<PublicKey>,
 	current_posdao_epoch: u64,
 	future_messages_cache: BTreeMap<u64, Vec<(NodeId, HbMessage)>>,
+	/// The encryption schedule from the chain spec, or `None` to use the hbbft default.
+	encryption_schedule: Option<EncryptionSchedule>,
+	/// The random number generator for our contributions, or `None` to use the thread RNG.
+	rng: Option<StdRng>,
 }
 
 impl HbbftState {
-	pub fn new() -> Self {
+	pub fn new(encryption_schedule: Option<EncryptionSchedule>) -> Self {
 		HbbftState {
 			network_info: None,
 			honey_badger: None,
 			public_master_key: None,
 			current_posdao_epoch: 0,
 			future_messages_cache: BTreeMap::new(),
+			encryption_schedule,
+			rng: None,
 		}
 	}
 
+	/// Draws the randomness of our contributions from the given generator, so that a seeded
+	/// simulation proposes the same contributions every time.
+	#[cfg(any(test, feature = "test-helpers"))]
+	pub fn set_rng(&mut self, rng: StdRng) {
+		self.rng = Some(rng);
+	}
+
 	fn new_honey_badger(&self, network_info: NetworkInfo<NodeId>) -> Option<HoneyBadger> {
 		let mut builder: HoneyBadgerBuilder<Contribution, _> =
 			HoneyBadger::builder(Arc::new(network_info));
+		if let Some(encryption_schedule) = self.encryption_schedule {
+			builder.encryption_schedule(encryption_schedule);
+		}
 		return Some(builder.build());
 	}
 
@@ -60,14 +132,7 @@ impl HbbftState {
 			return Some(());
 		}
 
-		let posdao_epoch_start = get_posdao_epoch_start(&*client, block_id).ok()?;
-		let synckeygen = initialize_synckeygen(
-			&*client,
-			signer,
-			BlockId::Number(posdao_epoch_start.low_u64()),
-			ValidatorType::Current,
-		)
-		.ok()?;
+		let synckeygen = synckeygen_for_block(&*client, signer, block_id)?;
 		assert!(synckeygen.is_ready());
 
 		let (pks, sks) = synckeygen.generate().ok()?;
@@ -216,21 +281,30 @@ impl HbbftState {
 		&mut self,
 		client: Arc<dyn EngineClient>,
 		signer: &Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
+		params: &HbbftParams,
+		persisted_state: &mut PersistedState,
 	) -> Option<(HoneyBadgerStep, NetworkInfo<NodeId>)> {
 		// If honey_badger is None we are not a validator, nothing to do.
 		let honey_badger = self.honey_badger.as_mut()?;
 		let network_info = self.network_info.as_ref()?;
 
 		if honey_badger.received_proposals() > network_info.num_faulty() {
-			return self.try_send_contribution(client, signer);
+			return self.try_send_contribution(client, signer, params, persisted_state);
 		}
 		None
 	}
 
+	/// Proposes our contribution for the current hbbft epoch, unless we already did.
+	///
+	/// If we proposed a contribution for this epoch before a restart, the same one is proposed
+	/// again, and encrypted the same way. Otherwise the new proposal is recorded in the persisted
+	/// state.
 	pub fn try_send_contribution(
 		&mut self,
 		client: Arc<dyn EngineClient>,
 		signer: &Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
+		params: &HbbftParams,
+		persisted_state: &mut PersistedState,
 	) -> Option<(HoneyBadgerStep, NetworkInfo<NodeId>)> {
 		// Make sure we are in the most current epoch.
 		self.skip_to_current_epoch(client.clone(), signer)?;
@@ -256,20 +330,43 @@ impl HbbftState {
 
 		let network_info = self.network_info.as_ref()?.clone();
 
-		trace!(target: "consensus", "Writing contribution for hbbft epoch(block) {}.", honey_badger.epoch());
-
-		// Now we can select the transactions to include in our contribution.
-		// TODO: Select a random *subset* of transactions to propose
-		let input_contribution = Contribution::new(
-			&client
-				.queued_transactions()
-				.iter()
-				.map(|txn| txn.signed().clone())
-				.collect(),
-		);
+		let mut thread_rng = rand::thread_rng();
+		let mut rng: &mut dyn RngCore = match self.rng {
+			Some(ref mut rng) => rng,
+			None => &mut thread_rng,
+		};
+		let proposal = match persisted_state.proposal(honey_badger.epoch()) {
+			Some(proposal) => {
+				trace!(target: "consensus", "Proposing the persisted contribution for hbbft epoch(block) {} again.", honey_badger.epoch());
+				proposal.clone()
+			}
+			None => {
+				trace!(target: "consensus", "Writing contribution for hbbft epoch(block) {}.", honey_badger.epoch());
+
+				// Now we can select the transactions to include in our contribution.
+				let transactions = select_transactions(
+					client
+						.queued_transactions()
+						.iter()
+						.map(|txn| txn.signed().clone())
+						.collect(),
+					params.batch_size,
+					params.contribution_candidate_window,
+					network_info.num_nodes(),
+					&mut rng,
+				);
+				let random_bytes = params
+					.random_bytes_per_epoch
+					.unwrap_or(DEFAULT_RANDOM_BYTES_PER_EPOCH);
+				let contribution = Contribution::new(&transactions, random_bytes, &mut rng);
+				let proposal = Proposal::new(contribution, &mut rng);
+				persisted_state.record_proposal(honey_badger.epoch(), proposal.clone());
+				proposal
+			}
+		};
 
-		let mut rng = rand::thread_rng();
-		let step = honey_badger.propose(&input_contribution, &mut rng);
+		// Encrypting with the proposal's own randomness makes the messages the same as before a restart.
+		let step = honey_badger.propose(&proposal.contribution, &mut proposal.rng());
 		match step {
 			Ok(step) => Some((step, network_info)),
 			_ => {
@@ -302,24 +399,14 @@ impl HbbftState {
 		if self.current_posdao_epoch != target_posdao_epoch {
 			trace!(target: "consensus", "verify_seal - hbbft state epoch does not match epoch at the header's parent, attempting to reconstruct the appropriate public key share from scratch.");
 			// If the requested block nr is already imported we try to generate the public master key from scratch.
-			let posdao_epoch_start =
-				match get_posdao_epoch_start(&*client, BlockId::Number(parent_block_nr)) {
-					Ok(epoch_start) => epoch_start,
-					Err(e) => {
-						error!(target: "consensus", "Querying epoch start block failed with error: {:?}", e);
-						return false;
-					}
-				};
-
-			let synckeygen = match initialize_synckeygen(
+			let synckeygen = match synckeygen_for_block(
 				&*client,
 				&Arc::new(RwLock::new(Option::None)),
-				BlockId::Number(posdao_epoch_start.low_u64()),
-				ValidatorType::Current,
+				BlockId::Number(parent_block_nr),
 			) {
-				Ok(synckeygen) => synckeygen,
-				Err(e) => {
-					error!(target: "consensus", "Synckeygen failed with error: {:?}", e);
+				Some(synckeygen) => synckeygen,
+				None => {
+					error!(target: "consensus", "Synckeygen failed for the POSDAO epoch of block #{}", parent_block_nr);
 					return false;
 				}
 			};
@@ -350,6 +437,26 @@ impl HbbftState {
 		}
 	}
 
+	/// Returns the hbbft epoch of the Honey Badger instance, if we are a validator.
+	pub fn hbbft_epoch(&self) -> Option<u64> {
+		self.honey_badger.as_ref().map(|honey_badger| honey_badger.epoch())
+	}
+
+	/// Returns the network info of the current hbbft epoch, if we are a validator.
+	pub fn network_info(&self) -> Option<&NetworkInfo<NodeId>> {
+		self.network_info.as_ref()
+	}
+
+	/// Returns the POSDAO epoch the state has been set up for.
+	pub fn posdao_epoch(&self) -> u64 {
+		self.current_posdao_epoch
+	}
+
+	/// Returns the number of cached messages for future hbbft epochs.
+	pub fn future_messages_count(&self) -> usize {
+		self.future_messages_cache.values().map(Vec::len).sum()
+	}
+
 	pub fn network_info_for(
 		&mut self,
 		client: Arc<dyn EngineClient>,
@@ -371,3 +478,220 @@ impl HbbftState {
 		self.network_info.clone()
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::{Batch, HbMessage, HbbftState, HoneyBadger, HoneyBadgerStep};
+	use crate::contribution::{Contribution, DEFAULT_RANDOM_BYTES_PER_EPOCH};
+	use crate::persisted_state::{PersistedState, Proposal};
+	use crate::utils::test_helpers::create_transaction;
+	use crate::NodeId;
+	use ethcore::test_helpers::TestBlockChainClient;
+	use ethereum_types::U256;
+	use hbbft::honey_badger::EncryptionSchedule;
+	use hbbft::{NetworkInfo, Target};
+	use parity_crypto::publickey::{Generator, Random};
+	use std::collections::{BTreeMap, VecDeque};
+
+	/// Four validators running a single hbbft epoch, with full control over message delivery.
+	struct TestNetwork {
+		nodes: BTreeMap<NodeId, HoneyBadger>,
+		contributions: BTreeMap<NodeId, Contribution>,
+		queue: VecDeque<(NodeId, NodeId, HbMessage)>,
+		sent: Vec<HbMessage>,
+		outputs: BTreeMap<NodeId, Vec<Batch>>,
+	}
+
+	impl TestNetwork {
+		fn new(encryption_schedule: EncryptionSchedule) -> Self {
+			let mut rng = rand::thread_rng();
+			let ids: Vec<NodeId> = (0..4).map(|_| NodeId(*Random.generate().public())).collect();
+			let state = HbbftState::new(Some(encryption_schedule));
+			let nodes = NetworkInfo::generate_map(ids, &mut rng)
+				.expect("NetworkInfo generation is expected to always succeed")
+				.into_iter()
+				.map(|(id, net_info)| (id, state.new_honey_badger(net_info).unwrap()))
+				.collect();
+			TestNetwork {
+				nodes,
+				contributions: BTreeMap::new(),
+				queue: VecDeque::new(),
+				sent: Vec::new(),
+				outputs: BTreeMap::new(),
+			}
+		}
+
+		/// Lets every validator propose a contribution with a transaction of its own.
+		fn propose_all(&mut self) {
+			let ids: Vec<NodeId> = self.nodes.keys().cloned().collect();
+			for id in ids {
+				let keypair = Random.generate();
+				let contribution = Contribution::new(
+					&vec![create_transaction(&keypair, &U256::zero())],
+					DEFAULT_RANDOM_BYTES_PER_EPOCH,
+					&mut rand::thread_rng(),
+				);
+				let step = self
+					.nodes
+					.get_mut(&id)
+					.unwrap()
+					.propose(&contribution, &mut rand::thread_rng())
+					.expect("Proposing a contribution must succeed");
+				self.contributions.insert(id, contribution);
+				self.dispatch(id, step);
+			}
+		}
+
+		fn dispatch(&mut self, sender: NodeId, step: HoneyBadgerStep) {
+			for msg in step.messages {
+				let recipients: Vec<NodeId> = match msg.target {
+					Target::Nodes(ref ids) => ids.iter().cloned().collect(),
+					Target::AllExcept(ref ids) => self
+						.nodes
+						.keys()
+						.filter(|id| !ids.contains(*id))
+						.cloned()
+						.collect(),
+				};
+				self.sent.push(msg.message.clone());
+				for recipient in recipients.into_iter().filter(|id| *id != sender) {
+					self.queue
+						.push_back((sender, recipient, msg.message.clone()));
+				}
+			}
+			self.outputs
+				.entry(sender)
+				.or_default()
+				.extend(step.output);
+		}
+
+		/// Delivers the queued messages accepted by the filter until none are left, and returns
+		/// the withheld ones.
+		fn run<F>(&mut self, deliver: F) -> Vec<(NodeId, NodeId, HbMessage)>
+		where
+			F: Fn(&HbMessage) -> bool,
+		{
+			let mut withheld = Vec::new();
+			while let Some((sender, recipient, message)) = self.queue.pop_front() {
+				if !deliver(&message) {
+					withheld.push((sender, recipient, message));
+					continue;
+				}
+				let step = self
+					.nodes
+					.get_mut(&recipient)
+					.unwrap()
+					.handle_message(&sender, message)
+					.expect("Handling a message from an honest validator must succeed");
+				self.dispatch(recipient, step);
+			}
+			withheld
+		}
+
+		/// Asserts that every validator output the same batch, with the proposed contributions of
+		/// at least `N - f` validators.
+		fn assert_batches(&self) {
+			assert_eq!(self.outputs.len(), self.nodes.len());
+			let batch = &self.outputs.values().next().unwrap()[0];
+			assert!(batch.contributions.len() >= 3);
+			for (id, contribution) in &batch.contributions {
+				assert_eq!(contribution, &self.contributions[id]);
+			}
+			for batches in self.outputs.values() {
+				assert_eq!(batches.len(), 1);
+				assert_eq!(batches[0].contributions, batch.contributions);
+			}
+		}
+	}
+
+	fn has_content(message: &HbMessage, content: &str) -> bool {
+		serde_json::to_string(message)
+			.expect("Honey Badger messages must be serializable")
+			.contains(&format!("\"{}\"", content))
+	}
+
+	#[test]
+	fn test_threshold_encryption() {
+		let mut network = TestNetwork::new(EncryptionSchedule::Always);
+		network.propose_all();
+
+		// Each validator receives the encrypted contributions of all others, but before they
+		// agreed on the set of contributions no decryption shares are sent.
+		let agreement = network.run(|message| has_content(message, "Broadcast"));
+		assert!(!agreement.is_empty());
+		assert!(!network
+			.sent
+			.iter()
+			.any(|message| has_content(message, "DecryptionShare")));
+
+		// After the agreement the contributions can still not be read without the decryption
+		// shares of the other validators.
+		network.queue.extend(agreement);
+		let shares = network.run(|message| !has_content(message, "DecryptionShare"));
+		assert!(!shares.is_empty());
+		assert!(network.outputs.values().all(Vec::is_empty));
+
+		network.queue.extend(shares);
+		assert!(network.run(|_| true).is_empty());
+		network.assert_batches();
+	}
+
+	#[test]
+	fn test_encrypted_proposal_after_restart() {
+		let mut rng = rand::thread_rng();
+		let ids: Vec<NodeId> = (0..4).map(|_| NodeId(*Random.generate().public())).collect();
+		let net_info = NetworkInfo::generate_map(ids.clone(), &mut rng)
+			.expect("NetworkInfo generation is expected to always succeed")
+			.remove(&ids[0])
+			.expect("NetworkInfo must exist for our id");
+		let state = HbbftState::new(Some(EncryptionSchedule::Always));
+		let propose = |proposal: &Proposal| -> Vec<String> {
+			let step = state
+				.new_honey_badger(net_info.clone())
+				.unwrap()
+				.propose(&proposal.contribution, &mut proposal.rng())
+				.expect("Proposing a contribution must succeed");
+			step.messages
+				.iter()
+				.map(|msg| serde_json::to_string(&msg.message).unwrap())
+				.collect()
+		};
+
+		let contribution = Contribution::new(
+			&vec![create_transaction(&Random.generate(), &U256::zero())],
+			DEFAULT_RANDOM_BYTES_PER_EPOCH,
+			&mut rng,
+		);
+		let proposal = Proposal::new(contribution, &mut rng);
+		let sent = propose(&proposal);
+		assert!(!sent.is_empty());
+
+		// After a restart, the persisted proposal is encrypted the same way, so the validator
+		// sends the same messages again instead of equivocating.
+		let client = TestBlockChainClient::new();
+		let mut persisted_state = PersistedState::default();
+		persisted_state.record_proposal(1, proposal.clone());
+		persisted_state.store(&client, 0);
+		let restored = PersistedState::load(&client)
+			.proposal(1)
+			.cloned()
+			.expect("The proposal must be persisted");
+		assert_eq!(propose(&restored), sent);
+
+		// Encrypting the same contribution with other randomness results in different messages.
+		let reencrypted = Proposal::new(proposal.contribution.clone(), &mut rng);
+		assert_ne!(propose(&reencrypted), sent);
+	}
+
+	#[test]
+	fn test_no_encryption() {
+		let mut network = TestNetwork::new(EncryptionSchedule::Never);
+		network.propose_all();
+		assert!(network.run(|_| true).is_empty());
+		network.assert_batches();
+		assert!(!network
+			.sent
+			.iter()
+			.any(|message| has_content(message, "DecryptionShare")));
+	}
+}
@@ -0,0 +1,253 @@
+//! Sending of the key generation transactions of a pending validator.
+//!
+//! While a pending validator set is selected, each of its validators writes its Part to the key
+//! history contract, and once all Parts are available, its Acks for all of them. After all Acks
+//! have been written the threshold key of the pending validator set can be generated.
+
+use client_traits::{BlockChainClient, EngineClient, Nonce, TransactionRequest};
+use common_types::ids::BlockId;
+use common_types::transaction::Error as TransactionError;
+use common_types::BlockNumber;
+use engine::signer::EngineSigner;
+use ethereum_types::{Address, U256};
+use hbbft::sync_key_gen::{Ack, Part, SyncKeyGen};
+use parity_crypto::publickey::Public;
+use parking_lot::RwLock;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::contracts::keygen_history::{
+	engine_signer_to_synckeygen, has_acks_of_address_data, has_part_of_address_data,
+	part_of_address, write_acks_data, write_part_data, PublicWrapper, KEYGEN_HISTORY_ADDRESS,
+};
+use crate::contracts::staking::get_posdao_epoch;
+use crate::contracts::validator_set::{get_validator_pubkeys, is_pending_validator, ValidatorType};
+use utils::bound_contract::CallError;
+
+/// Number of blocks after which a key generation transaction which did not take effect is sent again.
+const KEYGEN_TRANSACTION_RESEND_DELAY: BlockNumber = 10;
+
+/// Progress of the key generation of the pending validator set, as seen by our node.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KeyGenPhase {
+	/// We are not part of the pending validator set.
+	NotPending,
+	/// Our Part has been sent, but is not part of the chain state yet.
+	WritingPart,
+	/// Waiting for the Parts of other validators.
+	WaitingForParts,
+	/// Our Acks have been sent, but are not part of the chain state yet.
+	WritingAcks,
+	/// Our Acks are written, waiting for the other validators to complete the key generation.
+	WaitingForAcks,
+}
+
+/// The kinds of key generation transactions.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+enum KeyGenTransaction {
+	Part,
+	Acks,
+}
+
+/// A key generation transaction which has been added to the transaction queue.
+#[derive(Clone, Copy, Debug)]
+struct SentTransaction {
+	nonce: U256,
+	block_num: BlockNumber,
+}
+
+/// Our key generation state for the pending validator set of an upcoming staking epoch.
+struct EpochKeyGen {
+	upcoming_epoch: U256,
+	validators: BTreeMap<Address, Public>,
+	synckeygen: SyncKeyGen<Public, PublicWrapper>,
+	part: Part,
+	/// Our Acks for the Parts handled so far, by the address of the Part's author.
+	acks: BTreeMap<Address, Ack>,
+	sent: BTreeMap<KeyGenTransaction, SentTransaction>,
+}
+
+/// Writes our Part and Acks to the key history contract while we are a pending validator.
+///
+/// The `SyncKeyGen` instance, and with it our Part, is kept for the whole key generation phase of
+/// an upcoming staking epoch. Transactions which did not take effect after a while are sent again.
+pub struct KeyGenTransactionSender {
+	epoch_keygen: Option<EpochKeyGen>,
+	phase: KeyGenPhase,
+}
+
+impl KeyGenTransactionSender {
+	pub fn new() -> Self {
+		KeyGenTransactionSender {
+			epoch_keygen: None,
+			phase: KeyGenPhase::NotPending,
+		}
+	}
+
+	/// Returns the key generation phase as of the last call to `send_keygen_transactions`.
+	pub fn phase(&self) -> KeyGenPhase {
+		self.phase
+	}
+
+	/// Sends the key generation transactions due at the latest block, and returns the resulting phase.
+	pub fn send_keygen_transactions(
+		&mut self,
+		client: &dyn EngineClient,
+		signer: &Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
+	) -> Result<KeyGenPhase, CallError> {
+		let phase = self.next_phase(client, signer)?;
+		if phase != self.phase {
+			debug!(target: "engine", "Key generation phase changed from {:?} to {:?}.", self.phase, phase);
+			self.phase = phase;
+		}
+		Ok(phase)
+	}
+
+	fn next_phase(
+		&mut self,
+		client: &dyn EngineClient,
+		signer: &Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
+	) -> Result<KeyGenPhase, CallError> {
+		// If we have no signer there is nothing for us to send.
+		let address = match signer.read().as_ref() {
+			Some(signer) => signer.address(),
+			None => return Ok(KeyGenPhase::NotPending),
+		};
+
+		let full_client = client.as_full_client().ok_or(CallError::NotFullClient)?;
+
+		// If the chain is still syncing, do not send Parts or Acks.
+		if full_client.is_major_syncing() {
+			return Ok(self.phase);
+		}
+
+		if !is_pending_validator(client, &address)? {
+			self.epoch_keygen = None;
+			return Ok(KeyGenPhase::NotPending);
+		}
+
+		let latest = client
+			.block_number(BlockId::Latest)
+			.ok_or_else(|| CallError::CallFailed("Latest block number not available".into()))?;
+		let upcoming_epoch = get_posdao_epoch(client, BlockId::Latest)? + 1;
+
+		// Start over if the pending validator set is for a new staking epoch.
+		if self
+			.epoch_keygen
+			.as_ref()
+			.map_or(true, |keygen| keygen.upcoming_epoch != upcoming_epoch)
+		{
+			self.epoch_keygen = Some(EpochKeyGen::new(client, signer, upcoming_epoch)?);
+		}
+		let keygen = self
+			.epoch_keygen
+			.as_mut()
+			.expect("The key generation state has been initialized above; qed");
+
+		if !has_part_of_address_data(client, address)? {
+			let data = write_part_data(upcoming_epoch, &keygen.part)?;
+			keygen.send(full_client, address, KeyGenTransaction::Part, data, latest)?;
+			return Ok(KeyGenPhase::WritingPart);
+		}
+
+		// Handle each Part once it is available, creating our Ack for it.
+		let validators: Vec<Address> = keygen.validators.keys().cloned().collect();
+		for validator in validators {
+			if keygen.acks.contains_key(&validator) {
+				continue;
+			}
+			if !has_part_of_address_data(client, validator)? {
+				return Ok(KeyGenPhase::WaitingForParts);
+			}
+			let ack = part_of_address(
+				client,
+				validator,
+				&keygen.validators,
+				&mut keygen.synckeygen,
+				BlockId::Latest,
+			)?
+			.ok_or(CallError::ReturnValueInvalid)?;
+			keygen.acks.insert(validator, ack);
+		}
+
+		if !has_acks_of_address_data(client, address)? {
+			let acks: Vec<Ack> = keygen.acks.values().cloned().collect();
+			let data = write_acks_data(upcoming_epoch, &acks)?;
+			keygen.send(full_client, address, KeyGenTransaction::Acks, data, latest)?;
+			return Ok(KeyGenPhase::WritingAcks);
+		}
+
+		Ok(KeyGenPhase::WaitingForAcks)
+	}
+}
+
+impl EpochKeyGen {
+	fn new(
+		client: &dyn EngineClient,
+		signer: &Arc<RwLock<Option<Box<dyn EngineSigner>>>>,
+		upcoming_epoch: U256,
+	) -> Result<Self, CallError> {
+		let validators = get_validator_pubkeys(client, BlockId::Latest, ValidatorType::Pending)?;
+		let pub_keys: BTreeMap<_, _> = validators
+			.values()
+			.map(|p| (*p, PublicWrapper { inner: p.clone() }))
+			.collect();
+
+		// if synckeygen creation fails then either signer or validator pub keys are problematic.
+		// Todo: We should expect up to f clients to write invalid pub keys. Report and re-start pending validator set selection.
+		let (synckeygen, part) = engine_signer_to_synckeygen(signer, Arc::new(pub_keys))
+			.map_err(|_| CallError::ReturnValueInvalid)?;
+
+		// If there is no part then we are not part of the pending validator set.
+		let part = part.ok_or(CallError::ReturnValueInvalid)?;
+
+		Ok(EpochKeyGen {
+			upcoming_epoch,
+			validators,
+			synckeygen,
+			part,
+			acks: BTreeMap::new(),
+			sent: BTreeMap::new(),
+		})
+	}
+
+	/// Sends the given key generation transaction, unless it has been sent recently.
+	///
+	/// A transaction is sent again with the same nonce, so it replaces the earlier one if that is
+	/// still queued. Only if the nonce has been used up in the meantime a new one is taken.
+	fn send(
+		&mut self,
+		full_client: &dyn BlockChainClient,
+		address: Address,
+		kind: KeyGenTransaction,
+		data: Vec<u8>,
+		latest: BlockNumber,
+	) -> Result<(), CallError> {
+		let nonce = match self.sent.get(&kind) {
+			Some(sent) if sent.block_num + KEYGEN_TRANSACTION_RESEND_DELAY > latest => {
+				return Ok(())
+			}
+			Some(sent) if full_client.latest_nonce(&address) <= sent.nonce => sent.nonce,
+			_ => full_client.next_nonce(&address),
+		};
+
+		trace!(target: "engine", "Sending key generation transaction {:?} with nonce {} for staking epoch {}.", kind, nonce, self.upcoming_epoch);
+		let transaction = TransactionRequest::call(*KEYGEN_HISTORY_ADDRESS, data)
+			.gas(U256::from(7_000_000))
+			.nonce(nonce)
+			.gas_price(U256::from(10000000000u64));
+		match full_client.transact_silently(transaction) {
+			Ok(()) | Err(TransactionError::AlreadyImported) => {}
+			Err(e) => return Err(CallError::CallFailed(format!("{:?}", e))),
+		}
+		self.sent.insert(
+			kind,
+			SentTransaction {
+				nonce,
+				block_num: latest,
+			},
+		);
+		Ok(())
+	}
+}
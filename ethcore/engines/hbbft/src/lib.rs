@@ -26,25 +26,37 @@ extern crate serde;
 extern crate ethjson;
 extern crate rlp;
 extern crate serde_json;
+extern crate stats;
+extern crate unexpected;
 
 #[cfg(test)]
 extern crate ethcore;
 #[cfg(test)]
 extern crate ethcore_accounts as accounts;
 #[cfg(test)]
+extern crate ethcore_blockchain as blockchain;
+#[cfg(test)]
 extern crate proptest;
 #[cfg(test)]
+extern crate snapshot;
+#[cfg(test)]
 extern crate spec;
 #[cfg(test)]
 extern crate toml;
 
+mod accountability;
 mod block_reward_hbbft;
 mod contracts;
 mod contribution;
 mod hbbft_engine;
 mod hbbft_state;
+mod keygen_transactions;
+mod persisted_state;
+mod random_hbbft;
 mod sealing;
+mod status;
 mod utils;
+mod validator_peers;
 
 pub use hbbft_engine::HoneyBadgerBFT;
 use parity_crypto::publickey::Public;
@@ -68,56 +80,40 @@ impl fmt::Display for NodeId {
 #[cfg(test)]
 mod tests {
 	use crate::contribution::unix_now_secs;
-	use crate::utils::test_helpers::{create_hbbft_client, hbbft_client_setup, HbbftTestClient};
-	use client_traits::BlockInfo;
+	use crate::utils::simulation::{garbage_contribution, Byzantine, NetworkConfig, Simulation};
+	use crate::random_hbbft::tests::current_seed;
+	use crate::utils::test_helpers::{
+		create_hbbft_client, create_hbbft_client_with_spec, hbbft_client_with_spec, hbbft_spec,
+		hbbft_spec_with_randomness, hbbft_spec_with_signalling, HbbftTestClient, LightEngineClient,
+		MASTER_OF_CEREMONIES_KEYPAIR, RANDOMNESS_CONTRACT_ADDRESS,
+	};
+	use blockchain::{BlockChain, BlockProvider};
+	use client_traits::{BlockInfo, ImportBlock};
+	use common_types::block::Block;
+	use common_types::engines::epoch::Transition as EpochTransition;
+	use common_types::header::Header;
 	use common_types::ids::BlockId;
+	use common_types::snapshot::{ManifestData, Progress};
+	use common_types::verification::Unverified;
+	use ethcore::test_helpers::new_db;
+	use contracts::keygen_history::{public_key_hash, KeyGenHistoryData};
 	use contracts::staking::tests::{create_staker, is_pool_active};
 	use contracts::staking::{get_posdao_epoch, start_time_of_next_phase_transition};
 	use contracts::validator_set::{is_pending_validator, mining_by_staking_address};
-	use ethereum_types::{Address, H256, U256};
-	use hash::keccak;
-	use hbbft::NetworkInfo;
+	use engine::{EpochChange, Proof};
+	use ethereum_types::{Address, U256};
 	use hbbft_testing::proptest::{gen_seed, TestRng, TestRngSeed};
-	use parity_crypto::publickey::{Generator, KeyPair, Public, Random, Secret};
+	use parity_crypto::publickey::{Generator, KeyPair, Random};
+	use parking_lot::RwLock;
 	use proptest::{prelude::ProptestConfig, proptest};
 	use rand::{Rng, SeedableRng};
-	use std::collections::BTreeMap;
 	use std::str::FromStr;
+	use machine::ExecutedBlock;
+	use std::sync::atomic::AtomicBool;
+	use std::sync::Arc;
 
-	lazy_static! {
-		static ref MASTER_OF_CEREMONIES_KEYPAIR: KeyPair = KeyPair::from_secret(
-			Secret::from_str("18f059a4d72d166a96c1edfb9803af258a07b5ec862a961b3a1d801f443a1762")
-				.expect("Secret from hex string must succeed")
-		)
-		.expect("KeyPair generation from secret must succeed");
-	}
-
-	fn generate_nodes<R: Rng>(size: usize, rng: &mut R) -> BTreeMap<Public, HbbftTestClient> {
-		let keypairs: Vec<KeyPair> = (1..=size)
-			.map(|i| {
-				let secret = Secret::from(<[u8; 32]>::from(keccak(i.to_string())));
-				KeyPair::from_secret(secret).expect("KeyPair generation must succeed")
-			})
-			.collect();
-		let ips_map: BTreeMap<Public, String> = keypairs
-			.iter()
-			.map(|kp| (*kp.public(), format!("{}", kp.public())))
-			.collect();
-		let net_infos = NetworkInfo::generate_map(ips_map.keys().cloned(), rng)
-			.expect("NetworkInfo generation to always succeed");
-		keypairs
-			.into_iter()
-			.map(|kp| {
-				let netinfo = net_infos[kp.public()].clone();
-				(*kp.public(), hbbft_client_setup(kp, netinfo))
-			})
-			.collect()
-	}
-
-	// Returns `true` if the node has any unsent messages left.
-	fn has_messages(node: &HbbftTestClient) -> bool {
-		!node.notify.targeted_messages.read().is_empty()
-	}
+	/// Upper bound for the number of simulated network rounds in the multi-validator tests.
+	const MAX_ROUNDS: u64 = 1000;
 
 	#[test]
 	fn test_miner_transaction_injection() {
@@ -140,6 +136,97 @@ mod tests {
 		assert_eq!(block.transactions_count(), 1);
 	}
 
+	#[test]
+	fn test_block_verification() {
+		let mut test_data = create_hbbft_client(MASTER_OF_CEREMONIES_KEYPAIR.clone());
+		test_data.create_some_transaction(None);
+		let engine = test_data.client.engine();
+		let header = |num| {
+			test_data
+				.client
+				.block_header(BlockId::Number(num))
+				.expect("Block must exist")
+				.decode(engine.params().eip1559_transition)
+				.expect("Header must be decodable")
+		};
+		let parent = header(0);
+		let good = header(1);
+
+		// A sealed block passes all checks.
+		assert!(engine.verify_block_basic(&good).is_ok());
+		assert!(engine.verify_block_unordered(&good).is_ok());
+		assert!(engine.verify_block_family(&good, &parent).is_ok());
+
+		// Blocks have no author.
+		let mut bad = good.clone();
+		bad.set_author(Address::from_low_u64_be(1));
+		assert!(engine.verify_block_basic(&bad).is_err());
+
+		// The seal must consist of an encoded threshold signature.
+		let mut bad = good.clone();
+		bad.set_seal(Vec::new());
+		assert!(engine.verify_block_basic(&bad).is_err());
+		bad.set_seal(vec![rlp::encode(&vec![0xffu8; 95])]);
+		assert!(engine.verify_block_basic(&bad).is_err());
+		bad.set_seal(vec![rlp::encode(&vec![0xffu8; 96])]);
+		assert!(engine.verify_block_basic(&bad).is_ok());
+		assert!(engine.verify_block_unordered(&bad).is_err());
+
+		// The timestamp must be later than the parent's.
+		assert!(engine.is_timestamp_valid(good.timestamp(), parent.timestamp()));
+		assert!(!engine.is_timestamp_valid(parent.timestamp(), parent.timestamp()));
+	}
+
+	#[test]
+	fn test_random_number_stored_and_verified() {
+		let mut moc = create_hbbft_client_with_spec(
+			MASTER_OF_CEREMONIES_KEYPAIR.clone(),
+			hbbft_spec_with_randomness,
+		);
+		moc.create_some_transaction(None);
+		assert_eq!(moc.client.chain().best_block_number(), 1);
+
+		let eip1559_transition = moc.client.engine().params().eip1559_transition;
+		let block = moc
+			.client
+			.block(BlockId::Number(1))
+			.expect("Block 1 must exist")
+			.decode(eip1559_transition)
+			.expect("Block must be decodable");
+
+		// The seal consists of the threshold signature and the agreed random number.
+		assert_eq!(block.header.seal().len(), 2);
+		let random_number: U256 =
+			rlp::decode(&block.header.seal()[1]).expect("Random number must be decodable");
+		assert!(!random_number.is_zero());
+
+		// The random number is stored in the randomness contract.
+		let address = Address::from_str(RANDOMNESS_CONTRACT_ADDRESS).unwrap();
+		assert_eq!(
+			current_seed(moc.client.as_ref(), BlockId::Number(1), address)
+				.expect("Constant call must succeed"),
+			random_number
+		);
+
+		// Other nodes store the random number from the seal when importing the block, so a
+		// different one results in a different state root and the block is rejected.
+		let importer = create_hbbft_client_with_spec(Random.generate(), hbbft_spec_with_randomness);
+		let import = |block: &Block| {
+			importer
+				.client
+				.import_block(Unverified::from_rlp(block.rlp_bytes(), eip1559_transition).unwrap())
+				.expect("Block must be queued");
+			importer.client.flush_queue();
+			importer.client.chain().best_block_number()
+		};
+		let mut tampered = block.clone();
+		let mut seal = tampered.header.seal().to_vec();
+		seal[1] = rlp::encode(&(random_number ^ U256::one()));
+		tampered.header.set_seal(seal);
+		assert_eq!(import(&tampered), 0);
+		assert_eq!(import(&block), 1);
+	}
+
 	#[test]
 	fn test_staking_account_creation() {
 		// Create Master of Ceremonies
@@ -233,17 +320,16 @@ mod tests {
 			U256::from(0)
 		);
 
-		// First the validator realizes it is in the next validator set and sends his part.
-		moc.create_some_transaction(Some(&transactor));
+		// First the validator realizes it is in the next validator set and sends its part.
+		moc.client.engine().step();
 
-		// The part will be included in the block triggered by this transaction, but not part of the global state yet,
-		// so it sends the transaction another time.
+		// The part will be included in the block triggered by this transaction.
 		moc.create_some_transaction(Some(&transactor));
 
 		// Now the part is part of the global chain state, and we send our acks.
-		moc.create_some_transaction(Some(&transactor));
+		moc.client.engine().step();
 
-		// The acks will be included in the block triggered by this transaction, but not part of the global state yet.
+		// The acks will be included in the block triggered by this transaction.
 		moc.create_some_transaction(Some(&transactor));
 
 		// Now the acks are part of the global block state, and the key generation is complete and the next epoch begins
@@ -259,25 +345,249 @@ mod tests {
 		moc.create_some_transaction(Some(&transactor));
 	}
 
-	fn crank_network_single_step(nodes: &BTreeMap<Public, HbbftTestClient>) {
-		for (from, n) in nodes {
-			let mut targeted_messages = n.notify.targeted_messages.write();
-			for m in targeted_messages.drain(..) {
-				nodes
-					.get(&m.1.expect("The Message target node id must be set"))
-					.expect("Message target not found in nodes map")
-					.client
-					.engine()
-					.handle_message(&m.0, Some(*from))
-					.expect("Message handling to succeed");
-			}
+	/// Runs the key generation of the next validator set, which consists of the MoC only, and
+	/// starts the next POSDAO epoch. The transactor is used to trigger the block creation.
+	fn complete_key_generation(moc: &mut HbbftTestClient, transactor: &KeyPair) {
+		moc.transfer_to(&transactor.address(), &U256::from(9000000000000000000u64));
+		moc.client.engine().step();
+		moc.create_some_transaction(Some(transactor));
+		moc.client.engine().step();
+		moc.create_some_transaction(Some(transactor));
+		moc.create_some_transaction(Some(transactor));
+		assert_eq!(
+			get_posdao_epoch(moc.client.as_ref(), BlockId::Latest).expect("Constant call must succeed"),
+			U256::from(1)
+		);
+	}
+
+	#[test]
+	fn test_genesis_epoch_transition_proof() {
+		let moc = create_hbbft_client(MASTER_OF_CEREMONIES_KEYPAIR.clone());
+
+		let genesis = moc
+			.client
+			.block_header(BlockId::Number(0))
+			.expect("Genesis header must exist")
+			.decode(moc.client.engine().params().eip1559_transition)
+			.expect("Genesis header must be decodable");
+		let transition = moc
+			.client
+			.chain()
+			.epoch_transition(0, genesis.hash())
+			.expect("Genesis epoch transition must exist");
+		assert!(!transition.proof.is_empty());
+
+		// The threshold public key of the genesis validator set must be derivable from the proof.
+		assert!(moc
+			.client
+			.engine()
+			.epoch_verifier(&genesis, &transition.proof)
+			.known_confirmed()
+			.is_ok());
+	}
+
+	#[test]
+	fn test_snapshot_restore_after_key_change() {
+		let mut moc = create_hbbft_client(MASTER_OF_CEREMONIES_KEYPAIR.clone());
+		let transactor: KeyPair = Random.generate();
+		complete_key_generation(&mut moc, &transactor);
+		moc.create_some_transaction(Some(&transactor));
+
+		let client = moc.client.clone();
+		let engine = client.engine();
+		let chain = client.chain();
+		let header = |hash| {
+			chain
+				.block_header_data(&hash)
+				.expect("Header must exist")
+				.decode(engine.params().eip1559_transition)
+				.expect("Header must be decodable")
+		};
+
+		// Take the secondary chunks at the current best block. The tiny chunk size puts each
+		// transition into its own chunk, so the key change is only verified when finalizing.
+		let best_hash = chain.best_block_hash();
+		let mut chunker = snapshot::chunker(engine.snapshot_mode()).expect("hbbft supports snapshots");
+		let mut chunks = Vec::new();
+		chunker
+			.chunk_all(
+				&chain,
+				best_hash,
+				&mut |chunk: &[u8]| {
+					chunks.push(chunk.to_vec());
+					Ok(())
+				},
+				&RwLock::new(Progress::new()),
+				1,
+			)
+			.expect("Chunking must succeed");
+		assert_eq!(chunks.len(), 2);
+
+		// Restore them into a fresh database.
+		let manifest = ManifestData {
+			version: chunker.current_version(),
+			state_hashes: Vec::new(),
+			block_hashes: Vec::new(),
+			state_root: *header(best_hash).state_root(),
+			block_number: chain.best_block_number(),
+			block_hash: best_hash,
+		};
+		let genesis = hbbft_spec().genesis_block();
+		let db = new_db();
+		let restored = BlockChain::new(Default::default(), &genesis, db.clone());
+		let mut rebuilder = chunker
+			.rebuilder(restored, db.clone(), &manifest)
+			.expect("Rebuilder must be created");
+		let flag = AtomicBool::new(true);
+		for chunk in &chunks {
+			rebuilder
+				.feed(chunk, engine, &flag)
+				.expect("Chunk must be restored");
 		}
+		rebuilder.finalize().expect("Restored chain must verify");
+
+		let restored = BlockChain::new(Default::default(), &genesis, db);
+		let transitions: Vec<_> = restored.epoch_transitions().map(|(_, t)| t).collect();
+		assert_eq!(transitions.len(), 2);
+		let verifier = |transition: &EpochTransition| {
+			engine
+				.epoch_verifier(&header(transition.block_hash), &transition.proof)
+				.known_confirmed()
+				.expect("Restored transition proof must be valid")
+		};
+
+		// The first block after the restored one is sealed with the restored threshold key,
+		// and not with the key of the genesis validator set.
+		moc.create_some_transaction(Some(&transactor));
+		let next = header(client.chain().best_block_hash());
+		assert_eq!(*next.parent_hash(), best_hash);
+		assert!(verifier(&transitions[1]).verify_heavy(&next).is_ok());
+		assert!(verifier(&transitions[0]).verify_heavy(&next).is_err());
+	}
+
+	/// Completes a key generation on a chain which signals validator set changes in the extra data,
+	/// and returns the client and the epoch transition to the new validator set.
+	fn signalled_key_change() -> (HbbftTestClient, EpochTransition) {
+		let mut moc = create_hbbft_client_with_spec(
+			MASTER_OF_CEREMONIES_KEYPAIR.clone(),
+			hbbft_spec_with_signalling,
+		);
+		let transactor: KeyPair = Random.generate();
+		complete_key_generation(&mut moc, &transactor);
+		moc.create_some_transaction(Some(&transactor));
+
+		let mut transitions: Vec<_> = moc.client.chain().epoch_transitions().map(|(_, t)| t).collect();
+		assert_eq!(transitions.len(), 2);
+		let transition = transitions.pop().expect("The key change transition must exist");
+		(moc, transition)
+	}
+
+	fn decoded_header(moc: &HbbftTestClient, number: u64) -> Header {
+		moc.client
+			.block_header(BlockId::Number(number))
+			.expect("Block must exist")
+			.decode(moc.client.engine().params().eip1559_transition)
+			.expect("Header must be decodable")
 	}
 
-	fn crank_network(nodes: &BTreeMap<Public, HbbftTestClient>) {
-		while nodes.iter().any(|(_, test_data)| has_messages(test_data)) {
-			crank_network_single_step(nodes);
+	#[test]
+	fn test_key_change_signalled_in_extra_data() {
+		let (moc, transition) = signalled_key_change();
+
+		// The block switching to the new validator set contains the hash of its threshold key.
+		let data: KeyGenHistoryData =
+			rlp::decode(&transition.proof).expect("The proof must be key generation data");
+		let public_key = data.public_key().expect("The key generation must be complete");
+		let change = decoded_header(&moc, transition.block_number);
+		assert_eq!(change.extra_data(), public_key_hash(&public_key).as_bytes());
+		for number in 1..=moc.client.chain().best_block_number() {
+			if number != transition.block_number {
+				assert!(decoded_header(&moc, number).extra_data().is_empty());
+			}
 		}
+
+		// Nodes which did not execute the block check the key generation data fetched from their
+		// peers against the signalled hash.
+		let observer = hbbft_client_with_spec(hbbft_spec_with_signalling);
+		let proof = match observer.engine().signals_epoch_end(&change, None) {
+			EpochChange::Yes(Proof::WithState(proof)) => proof,
+			_ => panic!("The block must signal the key change"),
+		};
+		let machine = observer.engine().machine();
+		assert!(proof.check_proof(machine, &transition.proof).is_ok());
+		let genesis_transition = moc
+			.client
+			.chain()
+			.epoch_transition(0, decoded_header(&moc, 0).hash())
+			.expect("Genesis epoch transition must exist");
+		assert!(proof.check_proof(machine, &genesis_transition.proof).is_err());
+	}
+
+	#[test]
+	fn test_light_client_verification_after_key_change() {
+		let (mut moc, transition) = signalled_key_change();
+		moc.create_some_transaction(None);
+		let best = decoded_header(&moc, moc.client.chain().best_block_number());
+		let parent = decoded_header(&moc, best.number() - 1);
+		assert!(best.number() > transition.block_number);
+
+		// Without access to the chain state, the seal is verified against the threshold key of the
+		// latest epoch transition, which has the key generation data as its proof.
+		let light = Arc::new(LightEngineClient(moc.client.clone()));
+		let observer = hbbft_client_with_spec(hbbft_spec_with_signalling);
+		let engine = observer.engine();
+		engine.register_client(Arc::downgrade(&light) as _);
+		assert!(engine.verify_block_family(&best, &parent).is_ok());
+
+		// A seal which does not sign the header is rejected.
+		let mut bad = best.clone();
+		bad.set_seal(parent.seal().to_vec());
+		assert!(engine.verify_block_family(&bad, &parent).is_err());
+	}
+
+	#[test]
+	fn test_close_block_checks_extra_data() {
+		let (moc, transition) = signalled_key_change();
+		let engine = moc.client.engine();
+		let signal = decoded_header(&moc, transition.block_number).extra_data().clone();
+		let close = |parent: &Header, extra_data: Vec<u8>| {
+			let state = moc
+				.client
+				.state_at(BlockId::Hash(parent.hash()))
+				.expect("The parent state must exist");
+			let mut block = ExecutedBlock::new(state, Arc::new(vec![parent.hash()]), false);
+			block.header.set_parent_hash(parent.hash());
+			block.header.set_number(parent.number() + 1);
+			block.header.set_extra_data(extra_data);
+			engine.on_close_block(&mut block, parent)
+		};
+
+		// The block switching to the new validator set must signal its threshold key.
+		let parent = decoded_header(&moc, transition.block_number - 1);
+		assert!(close(&parent, Vec::new()).is_err());
+		assert!(close(&parent, vec![0; 32]).is_err());
+
+		// Other blocks must not signal a key change.
+		let parent = decoded_header(&moc, transition.block_number);
+		assert!(close(&parent, signal).is_err());
+	}
+
+	#[test]
+	fn test_consensus_status() {
+		let moc = create_hbbft_client(MASTER_OF_CEREMONIES_KEYPAIR.clone());
+
+		let status = moc
+			.client
+			.engine()
+			.consensus_status()
+			.expect("The hbbft engine must report its status");
+		assert_eq!(
+			status["address"],
+			serde_json::to_value(MASTER_OF_CEREMONIES_KEYPAIR.address()).unwrap()
+		);
+		assert_eq!(status["posdaoEpoch"], 0);
+		assert_eq!(status["isCurrentValidator"], true);
+		assert_eq!(status["futureMessages"], 0);
 	}
 
 	proptest! {
@@ -305,34 +615,63 @@ mod tests {
 		fn test_trigger_at_contribution_threshold(seed in gen_seed()) {
 			do_test_trigger_at_contribution_threshold(seed)
 		}
+
+		#[test]
+		#[ignore]
+		#[allow(clippy::unnecessary_operation)]
+		fn test_unreliable_network(seed in gen_seed()) {
+			do_test_unreliable_network(seed)
+		}
+
+		#[test]
+		#[ignore]
+		#[allow(clippy::unnecessary_operation)]
+		fn test_crashed_and_restarted_node(seed in gen_seed()) {
+			do_test_crashed_and_restarted_node(seed)
+		}
+
+		#[test]
+		#[ignore]
+		#[allow(clippy::unnecessary_operation)]
+		fn test_partition(seed in gen_seed()) {
+			do_test_partition(seed)
+		}
+
+		#[test]
+		#[ignore]
+		#[allow(clippy::unnecessary_operation)]
+		fn test_byzantine_nodes(seed in gen_seed()) {
+			do_test_byzantine_nodes(seed)
+		}
+
+		#[test]
+		#[ignore]
+		#[allow(clippy::unnecessary_operation)]
+		fn test_reproducible_runs(seed in gen_seed()) {
+			do_test_reproducible_runs(seed)
+		}
 	}
 
 	fn test_with_size<R: Rng>(rng: &mut R, size: usize) {
-		let mut nodes = generate_nodes(size, rng);
+		let mut sim = Simulation::new(size, NetworkConfig::default(), rng);
 
-		for (_, n) in &mut nodes {
+		for id in sim.node_ids() {
 			// Verify that we actually start at block 0.
-			assert_eq!(n.client.chain().best_block_number(), 0);
-			// Inject transactions to kick off block creation.
-			n.create_some_transaction(None);
+			assert_eq!(sim.node(&id).client.client.chain().best_block_number(), 0);
 		}
+		// Inject transactions to kick off block creation.
+		sim.create_transactions();
 
 		// Rudimentary network simulation.
-		crank_network(&nodes);
+		sim.crank_until_idle(MAX_ROUNDS);
 
 		// All nodes need to have produced a block.
-		for (_, n) in &nodes {
-			assert_eq!(n.client.chain().best_block_number(), 1);
+		for id in sim.node_ids() {
+			assert_eq!(sim.node(&id).client.client.chain().best_block_number(), 1);
 		}
 
 		// All nodes need to produce the same block with the same hash.
-		let mut expected: Option<H256> = None;
-		for (_, n) in &nodes {
-			match expected {
-				None => expected = Some(n.client.chain().best_block_hash()),
-				Some(h) => assert_eq!(n.client.chain().best_block_hash(), h),
-			}
-		}
+		sim.check_safety();
 	}
 
 	fn do_test_two_clients(seed: TestRngSeed) {
@@ -355,32 +694,135 @@ mod tests {
 		// Other nodes should *not* join the epoch if they receive only
 		// one contribution, but if 2 or more are received they should!
 		let network_size: usize = 4;
-		let mut nodes = generate_nodes(network_size, &mut rng);
+		let mut sim = Simulation::new(network_size, NetworkConfig::default(), &mut rng);
+		let ids = sim.node_ids();
 
-		// Get the first node as mutable reference and send a transaction to it.
-		let first_node: &mut HbbftTestClient = nodes.iter_mut().nth(0).unwrap().1;
-		first_node.create_some_transaction(None);
+		// Send a transaction to the first node.
+		sim.node_mut(&ids[0]).client.create_some_transaction(None);
 
 		// Crank the network until no node has any input
-		crank_network(&nodes);
-
-		// Cannot re-use the mutable reference to an element of nodes, re-acquire as immutable.
-		let first_node = nodes.iter().nth(0).unwrap().1;
+		sim.crank_until_idle(MAX_ROUNDS);
 
 		// We expect no new block being generated in this case!
-		assert_eq!(first_node.client.chain().best_block_number(), 0);
+		assert_eq!(sim.node(&ids[0]).client.client.chain().best_block_number(), 0);
 
-		// Get the second node and send a transaction to it.
-		let second_node = nodes.iter_mut().nth(1).unwrap().1;
-		second_node.create_some_transaction(None);
+		// Send a transaction to the second node.
+		sim.node_mut(&ids[1]).client.create_some_transaction(None);
 
 		// Crank the network until no node has any input
-		crank_network(&nodes);
-
-		// Need to re-aquire the immutable reference to allow the second node to be acquired mutable.
-		let first_node = nodes.iter().nth(0).unwrap().1;
+		sim.crank_until_idle(MAX_ROUNDS);
 
 		// This time we do expect a new block has been generated
-		assert_eq!(first_node.client.chain().best_block_number(), 1);
+		assert_eq!(sim.node(&ids[0]).client.client.chain().best_block_number(), 1);
+	}
+
+	fn do_test_unreliable_network(seed: TestRngSeed) {
+		let mut rng = TestRng::from_seed(seed);
+		let config = NetworkConfig {
+			min_delay: 0,
+			max_delay: 5,
+			drop_probability: 0.0,
+			reorder: true,
+		};
+		let mut sim = Simulation::new(4, config, &mut rng);
+		sim.strict = false;
+
+		// Delayed and reordered messages must not prevent agreement on the same blocks.
+		for block_num in 1..=3 {
+			sim.create_transactions();
+			sim.assert_liveness(block_num, MAX_ROUNDS);
+		}
+		sim.check_safety();
+	}
+
+	fn do_test_crashed_and_restarted_node(seed: TestRngSeed) {
+		let mut rng = TestRng::from_seed(seed);
+		let mut sim = Simulation::new(4, NetworkConfig::default(), &mut rng);
+		sim.strict = false;
+		let crashed = sim.node_ids()[3];
+
+		// The remaining three validators make progress without the crashed one.
+		sim.crash(&crashed);
+		sim.create_transactions();
+		sim.assert_liveness(1, MAX_ROUNDS);
+
+		// After a restart the validator catches up and takes part again.
+		sim.restart(&crashed);
+		sim.create_transactions();
+		sim.assert_liveness(2, MAX_ROUNDS);
+		assert_eq!(sim.node(&crashed).client.client.chain().best_block_number(), 2);
+		sim.check_safety();
+	}
+
+	fn do_test_partition(seed: TestRngSeed) {
+		let mut rng = TestRng::from_seed(seed);
+		let mut sim = Simulation::new(4, NetworkConfig::default(), &mut rng);
+		sim.strict = false;
+		let ids = sim.node_ids();
+
+		// Without a quorum on either side of the partition no block is created.
+		sim.network.partition(vec![
+			ids[..2].iter().cloned().collect(),
+			ids[2..].iter().cloned().collect(),
+		]);
+		sim.create_transactions();
+		assert_eq!(sim.crank_until_block(1, 50), None);
+
+		// Once the partition heals, new messages lead to progress.
+		sim.network.heal();
+		sim.create_transactions();
+		sim.assert_liveness(1, MAX_ROUNDS);
+		sim.check_safety();
+	}
+
+	fn do_test_byzantine_nodes(seed: TestRngSeed) {
+		let mut rng = TestRng::from_seed(seed);
+		let mut sim = Simulation::new(7, NetworkConfig::default(), &mut rng);
+		sim.strict = false;
+		let ids = sim.node_ids();
+
+		// Up to two faulty validators out of seven are tolerated.
+		sim.set_byzantine(&ids[0], Byzantine::GarbageMessages);
+		sim.set_byzantine(&ids[1], Byzantine::GarbageContribution);
+		let contribution = garbage_contribution(sim.network.rng());
+		sim.restart_with_contribution(&ids[1], contribution);
+
+		for block_num in 1..=2 {
+			sim.create_transactions();
+			sim.assert_liveness(block_num, MAX_ROUNDS);
+		}
+		sim.check_safety();
+	}
+
+	fn do_test_reproducible_runs(seed: TestRngSeed) {
+		// Returns the number of rounds each block took, and the transactions of each block.
+		let run = || {
+			let mut rng = TestRng::from_seed(seed);
+			let config = NetworkConfig {
+				min_delay: 0,
+				max_delay: 3,
+				drop_probability: 0.0,
+				reorder: true,
+			};
+			let mut sim = Simulation::new(4, config, &mut rng);
+			sim.strict = false;
+			let mut rounds = Vec::new();
+			for block_num in 1..=2 {
+				sim.create_transactions();
+				rounds.push(sim.crank_until_block(block_num, MAX_ROUNDS));
+			}
+			let client = &sim.node(&sim.node_ids()[0]).client.client;
+			let transactions: Vec<_> = (1..=2)
+				.map(|block_num| {
+					client
+						.block(BlockId::Number(block_num))
+						.map(|block| block.transaction_hashes())
+				})
+				.collect();
+			(rounds, transactions, sim.network.round())
+		};
+
+		// The same seed leads to the same messages, delivered in the same order.
+		assert_eq!(run(), run());
 	}
 }
@@ -0,0 +1,292 @@
+//! Persistence of the consensus state of the hbbft epochs in progress.
+//!
+//! A validator restarting in the middle of an hbbft epoch would lose its contribution, the messages
+//! received so far and the signature shares for the block being sealed, and could only take part
+//! again in the next epoch. The engine stores them in the client database, and on startup proposes
+//! the same contribution again and replays the received messages.
+//!
+//! With threshold encryption, the contribution is encrypted with randomness derived from a seed
+//! stored alongside it, so proposing it again sends the same messages as before the restart. A
+//! newly encrypted contribution would be reported as equivocation by the other validators.
+//!
+//! Our contribution is written before it is sent. Received messages are only written on each
+//! consensus timer tick in which new ones were recorded, so after a crash the ones received since
+//! the last tick are lost. In the worst case the node then doesn't complete the epoch and catches
+//! up with the next block. Only Honey Badger messages of the current validators for the epoch in
+//! progress and the next one are recorded, up to a limit per sender and epoch.
+
+use bincode;
+use client_traits::EngineClient;
+use common_types::BlockNumber;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeMap;
+use std::mem;
+
+use crate::contribution::Contribution;
+use crate::hbbft_state::HbMessage;
+use crate::sealing;
+use crate::NodeId;
+
+/// Version prefix of the persisted state, so data written by an incompatible version is ignored.
+/// Version 1 stored the contributions without the seed of their encryption.
+const PERSISTED_STATE_V2: u8 = 2;
+
+/// The recorded Honey Badger messages with their sender, and the recorded signature shares with
+/// their block number and sender.
+pub(crate) type PersistedMessages = (
+	Vec<(NodeId, HbMessage)>,
+	Vec<(BlockNumber, NodeId, sealing::Message)>,
+);
+
+/// Our contribution to an hbbft epoch, with the seed of the randomness it is encrypted with.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub(crate) struct Proposal {
+	pub contribution: Contribution,
+	seed: [u8; 32],
+}
+
+impl Proposal {
+	/// Creates a proposal of the given contribution, with a new encryption seed drawn from `rng`.
+	pub fn new<R: Rng>(contribution: Contribution, rng: &mut R) -> Self {
+		Proposal {
+			contribution,
+			seed: rng.gen(),
+		}
+	}
+
+	/// Returns the random number generator to encrypt the contribution with.
+	///
+	/// It always produces the same randomness, so the encrypted contribution is the same every
+	/// time it is proposed.
+	pub fn rng(&self) -> StdRng {
+		StdRng::from_seed(self.seed)
+	}
+}
+
+/// Our contributions and the received consensus messages of the blocks not imported yet.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(crate) struct PersistedState {
+	/// Our proposals, by hbbft epoch.
+	proposals: BTreeMap<u64, Proposal>,
+	/// The received Honey Badger messages, by hbbft epoch.
+	hb_messages: BTreeMap<u64, Vec<(NodeId, HbMessage)>>,
+	/// The received signature shares, by block number.
+	sealing_messages: BTreeMap<BlockNumber, Vec<(NodeId, sealing::Message)>>,
+	/// The number of recorded Honey Badger messages, by hbbft epoch and sender.
+	#[serde(skip)]
+	hb_message_counts: BTreeMap<(u64, NodeId), usize>,
+	/// Whether the state changed since it was last written to the database.
+	#[serde(skip)]
+	dirty: bool,
+}
+
+impl PersistedState {
+	/// Reads the persisted state from the client database.
+	///
+	/// Returns an empty state if nothing has been stored yet or the stored data can not be decoded.
+	pub fn load(client: &dyn EngineClient) -> Self {
+		let data = match client.engine_data() {
+			Some(data) => data,
+			None => return PersistedState::default(),
+		};
+		match data.split_first() {
+			Some((&PERSISTED_STATE_V2, state)) => bincode::deserialize(state).unwrap_or_else(|e| {
+				warn!(target: "engine", "Discarding persisted consensus state which could not be decoded: {}", e);
+				PersistedState::default()
+			}),
+			_ => {
+				warn!(target: "engine", "Discarding persisted consensus state of an unknown version.");
+				PersistedState::default()
+			}
+		}
+	}
+
+	/// Removes the data of blocks up to the latest one, and writes the state to the client
+	/// database if it changed.
+	pub fn store(&mut self, client: &dyn EngineClient, latest: BlockNumber) {
+		self.prune(latest);
+		if !self.dirty {
+			return;
+		}
+		let mut data = vec![PERSISTED_STATE_V2];
+		bincode::serialize_into(&mut data, self)
+			.expect("Serialization of the consensus state failed");
+		client.set_engine_data(data);
+		self.dirty = false;
+	}
+
+	/// Returns our proposal for the given hbbft epoch, if we already made one.
+	pub fn proposal(&self, epoch: u64) -> Option<&Proposal> {
+		self.proposals.get(&epoch)
+	}
+
+	/// Records our proposal for the given hbbft epoch.
+	pub fn record_proposal(&mut self, epoch: u64, proposal: Proposal) {
+		self.proposals.insert(epoch, proposal);
+		self.dirty = true;
+	}
+
+	/// Records a Honey Badger message received for the given hbbft epoch, unless `max_per_sender`
+	/// messages of the sender have already been recorded for that epoch.
+	pub fn record_hb_message(
+		&mut self,
+		epoch: u64,
+		sender_id: NodeId,
+		message: HbMessage,
+		max_per_sender: usize,
+	) {
+		let count = self.hb_message_counts.entry((epoch, sender_id)).or_insert(0);
+		if *count >= max_per_sender {
+			return;
+		}
+		*count += 1;
+		self.hb_messages
+			.entry(epoch)
+			.or_default()
+			.push((sender_id, message));
+		self.dirty = true;
+	}
+
+	/// Records a signature share received for the given block.
+	pub fn record_sealing_message(
+		&mut self,
+		block_num: BlockNumber,
+		sender_id: NodeId,
+		message: sealing::Message,
+	) {
+		self.sealing_messages
+			.entry(block_num)
+			.or_default()
+			.push((sender_id, message));
+		self.dirty = true;
+	}
+
+	/// Returns the recorded messages in epoch and block order, and removes them from the state.
+	///
+	/// Replaying the messages records them again.
+	pub fn take_messages(&mut self) -> PersistedMessages {
+		if self.hb_messages.is_empty() && self.sealing_messages.is_empty() {
+			return (Vec::new(), Vec::new());
+		}
+		self.hb_message_counts.clear();
+		let hb_messages = mem::replace(&mut self.hb_messages, BTreeMap::new())
+			.into_iter()
+			.flat_map(|(_, messages)| messages)
+			.collect();
+		let sealing_messages = mem::replace(&mut self.sealing_messages, BTreeMap::new())
+			.into_iter()
+			.flat_map(|(block_num, messages)| {
+				messages
+					.into_iter()
+					.map(move |(sender_id, message)| (block_num, sender_id, message))
+			})
+			.collect();
+		self.dirty = true;
+		(hb_messages, sealing_messages)
+	}
+
+	/// Removes the data of the blocks up to the given one, which are no longer in progress.
+	pub fn prune(&mut self, latest: BlockNumber) {
+		let len_before = self.len();
+		self.proposals = self.proposals.split_off(&(latest + 1));
+		self.hb_messages = self.hb_messages.split_off(&(latest + 1));
+		self.sealing_messages = self.sealing_messages.split_off(&(latest + 1));
+		self.hb_message_counts = self
+			.hb_message_counts
+			.split_off(&(latest + 1, NodeId::default()));
+		if self.len() != len_before {
+			self.dirty = true;
+		}
+	}
+
+	/// Returns the number of epochs and blocks with recorded data.
+	fn len(&self) -> usize {
+		self.proposals.len() + self.hb_messages.len() + self.sealing_messages.len()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{PersistedState, Proposal};
+	use crate::contribution::Contribution;
+	use crate::sealing;
+	use crate::NodeId;
+	use client_traits::EngineClient;
+	use ethcore::test_helpers::TestBlockChainClient;
+	use hbbft::crypto::SecretKeySet;
+	use hbbft::honey_badger::{HoneyBadger, HoneyBadgerBuilder};
+	use hbbft::NetworkInfo;
+	use parity_crypto::publickey::{Generator, Random};
+	use std::sync::Arc;
+
+	#[test]
+	fn test_persisted_state_round_trip() {
+		let client = TestBlockChainClient::new();
+		let node_id = NodeId(*Random.generate().public());
+		let sk_share = SecretKeySet::random(0, &mut rand::thread_rng()).secret_key_share(0);
+		let share = sealing::Message(sk_share.sign(b"block 5"));
+
+		// Nothing has been stored yet.
+		let mut state = PersistedState::load(&client);
+		assert!(state.proposal(5).is_none());
+
+		let mut rng = rand::thread_rng();
+		state.record_proposal(4, Proposal::new(Contribution::new(&Vec::new(), 32, &mut rng), &mut rng));
+		state.record_proposal(5, Proposal::new(Contribution::new(&Vec::new(), 32, &mut rng), &mut rng));
+		state.record_sealing_message(5, node_id, share.clone());
+		state.store(&client, 4);
+
+		// Only the data of the block in progress has been stored.
+		let mut restored = PersistedState::load(&client);
+		assert!(restored.proposal(4).is_none());
+		assert_eq!(restored.proposal(5), state.proposal(5));
+		let (hb_messages, sealing_messages) = restored.take_messages();
+		assert!(hb_messages.is_empty());
+		assert_eq!(sealing_messages, vec![(5, node_id, share)]);
+
+		// Once block 5 is imported there is nothing left to persist.
+		restored.store(&client, 5);
+		assert!(PersistedState::load(&client).proposal(5).is_none());
+
+		// Data of an unknown version is discarded.
+		client.set_engine_data(vec![0xff, 1, 2, 3]);
+		assert!(PersistedState::load(&client).proposal(5).is_none());
+	}
+
+	#[test]
+	fn test_hb_messages_per_sender_limit() {
+		let mut rng = rand::thread_rng();
+		let node_ids: Vec<_> = (0..4).map(|_| NodeId(*Random.generate().public())).collect();
+		let net_infos = NetworkInfo::generate_map(node_ids.clone(), &mut rng)
+			.expect("NetworkInfo generation is expected to always succeed");
+		let mut builder: HoneyBadgerBuilder<Contribution, _> =
+			HoneyBadger::builder(Arc::new(net_infos[&node_ids[0]].clone()));
+		let step = builder
+			.build()
+			.propose(&Contribution::new(&Vec::new(), 32, &mut rng), &mut rng)
+			.expect("Proposing a contribution must succeed");
+		let message = step
+			.messages
+			.into_iter()
+			.next()
+			.expect("A contribution must result in outgoing messages")
+			.message;
+
+		// Messages beyond the limit are not recorded, separately for each sender and epoch.
+		let mut state = PersistedState::default();
+		for _ in 0..3 {
+			state.record_hb_message(1, node_ids[0], message.clone(), 2);
+		}
+		state.record_hb_message(1, node_ids[1], message.clone(), 2);
+		state.record_hb_message(2, node_ids[0], message.clone(), 2);
+		let (hb_messages, _) = state.take_messages();
+		assert_eq!(hb_messages.len(), 4);
+		assert_eq!(hb_messages.iter().filter(|(id, _)| *id == node_ids[0]).count(), 3);
+
+		// Replaying the taken messages records them again.
+		state.record_hb_message(1, node_ids[0], message.clone(), 2);
+		state.record_hb_message(1, node_ids[0], message, 2);
+		assert_eq!(state.take_messages().0.len(), 2);
+	}
+}
@@ -0,0 +1,72 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A client interface for the randomness contract, which stores the random number
+//! agreed upon by the validators in each block.
+
+use common_types::errors::{EngineError, EthcoreError as Error};
+use engine::{SystemOrCodeCall, SystemOrCodeCallKind};
+use ethabi_contract::use_contract;
+use ethereum_types::{Address, U256};
+
+use_contract!(random_hbbft_contract, "res/random_hbbft.json");
+
+/// A client for the randomness contract.
+#[derive(PartialEq, Debug)]
+pub struct RandomHbbftContract {
+	kind: SystemOrCodeCallKind,
+}
+
+impl RandomHbbftContract {
+	/// Create a new randomness contract client targeting the contract address.
+	pub fn new_from_address(address: Address) -> RandomHbbftContract {
+		RandomHbbftContract {
+			kind: SystemOrCodeCallKind::Address(address),
+		}
+	}
+
+	/// Stores the random number of the current block in the randomness contract. The randomness
+	/// contract *must* be called by the system address so the `caller` must ensure that
+	/// (e.g. using `machine.execute_as_system`).
+	pub fn set_current_seed(&self, caller: &mut SystemOrCodeCall, seed: U256) -> Result<(), Error> {
+		let (input, _) = random_hbbft_contract::functions::set_current_seed::call(seed);
+
+		caller(self.kind.clone(), input)
+			.map_err(Into::into)
+			.map_err(EngineError::FailedSystemCall)?;
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+pub mod tests {
+	use super::random_hbbft_contract;
+	use crate::utils::bound_contract::{BoundContract, CallError};
+	use client_traits::EngineClient;
+	use common_types::ids::BlockId;
+	use ethereum_types::{Address, U256};
+
+	/// Returns the random number stored in the randomness contract at the given block.
+	pub fn current_seed(
+		client: &dyn EngineClient,
+		block_id: BlockId,
+		address: Address,
+	) -> Result<U256, CallError> {
+		let c = BoundContract::bind(client, block_id, address);
+		c.call_const(random_hbbft_contract::functions::current_seed::call())
+	}
+}
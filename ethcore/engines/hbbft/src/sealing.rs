@@ -1,7 +1,15 @@
+use crate::contracts::keygen_history::{public_key_hash, KeyGenHistoryData};
+use crate::contracts::validator_set::ValidatorType;
 use crate::NodeId;
-use hbbft::crypto::Signature;
+use common_types::engines::machine::Call;
+use common_types::errors::{BlockError, EthcoreError};
+use common_types::header::Header;
+use engine;
+use ethereum_types::{Address, H256};
+use hbbft::crypto::{PublicKey, Signature};
 use hbbft::threshold_sign::ThresholdSign;
 use hbbft::NetworkInfo;
+use machine::Machine;
 use rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
 use std::result;
 use std::sync::Arc;
@@ -63,15 +71,84 @@ impl<'a> Encodable for RlpSig<&'a Signature> {
 
 const RLP_ERR: &str = "RLP bytes don't encode a valid signature";
 
+/// The length of an encoded threshold signature, in bytes.
+pub const SIGNATURE_LENGTH: usize = 96;
+
 impl Decodable for RlpSig<Signature> {
 	fn decode(rlp: &Rlp) -> result::Result<Self, DecoderError> {
-		let mut seal_bytes = [0u8; 96];
-		seal_bytes.copy_from_slice(rlp.data()?);
+		let data = rlp.data()?;
+		if data.len() != SIGNATURE_LENGTH {
+			return Err(DecoderError::RlpInvalidLength);
+		}
+		let mut seal_bytes = [0u8; SIGNATURE_LENGTH];
+		seal_bytes.copy_from_slice(data);
 		let sig = Signature::from_bytes(seal_bytes).map_err(|_| DecoderError::Custom(RLP_ERR))?;
 		Ok(RlpSig(sig))
 	}
 }
 
+/// Verifies block seals against the threshold public key of a validator set.
+pub struct EpochVerifier {
+	public_key: PublicKey,
+}
+
+impl EpochVerifier {
+	/// Creates a verifier for blocks sealed by the validator set with the given threshold public key.
+	pub fn new(public_key: PublicKey) -> Self {
+		EpochVerifier { public_key }
+	}
+}
+
+impl engine::EpochVerifier for EpochVerifier {
+	fn verify_light(&self, header: &Header) -> result::Result<(), EthcoreError> {
+		// The number of seal fields is checked in the basic verification.
+		let RlpSig(sig) = rlp::decode(header.seal().first().ok_or(BlockError::InvalidSeal)?)?;
+		if self.public_key.verify(&sig, header.bare_hash()) {
+			Ok(())
+		} else {
+			Err(BlockError::InvalidSeal.into())
+		}
+	}
+}
+
+/// The proof of a validator set change signalled in a block's extra data.
+///
+/// Nodes which did not execute the block, like light clients, fetch the key generation data of the
+/// new validator set from their peers. It is checked against the threshold public key hash in the
+/// extra data, which is signed by the previous validator set.
+pub struct KeyChangeProof {
+	public_key_hash: H256,
+}
+
+impl KeyChangeProof {
+	/// Creates a proof checker for the switch to the validator set with the given public key hash.
+	pub fn new(public_key_hash: H256) -> Self {
+		KeyChangeProof { public_key_hash }
+	}
+}
+
+impl engine::StateDependentProof for KeyChangeProof {
+	fn generate_proof(&self, caller: &Call) -> result::Result<Vec<u8>, String> {
+		// The call is made on the state after the block, in which the new validator set is current.
+		let call = |address: Address, data: Vec<u8>| caller(address, data).map(|(output, _)| output);
+		KeyGenHistoryData::read_with_call(&call, ValidatorType::Current)
+			.map(|data| rlp::encode(&data))
+			.map_err(|e| format!("Reading the keygen data failed: {:?}", e))
+	}
+
+	fn check_proof(&self, _machine: &Machine, proof: &[u8]) -> result::Result<(), String> {
+		let data: KeyGenHistoryData =
+			rlp::decode(proof).map_err(|e| format!("proof incorrectly encoded: {}", e))?;
+		let public_key = data
+			.public_key()
+			.ok_or_else(|| "incomplete key generation data".to_string())?;
+		if public_key_hash(&public_key) != self.public_key_hash {
+			return Err("public key does not match the signalled one".into());
+		}
+		Ok(())
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -85,4 +162,16 @@ mod tests {
 		let decoded: RlpSig<Signature> = rlp::decode(&encoded).expect("decode RlpSignature");
 		assert_eq!(decoded.0, sig);
 	}
+
+	#[test]
+	fn test_key_change_proof_rejects_invalid_data() {
+		use engine::StateDependentProof;
+
+		let machine = spec::new_test_machine();
+		let proof = KeyChangeProof::new(H256::zero());
+		assert!(proof.check_proof(&machine, &[0xff, 0x01]).is_err());
+		// Key generation data without validators does not define a threshold public key.
+		let empty = rlp::encode(&KeyGenHistoryData::default());
+		assert!(proof.check_proof(&machine, &empty).is_err());
+	}
 }
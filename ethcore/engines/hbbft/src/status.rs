@@ -0,0 +1,146 @@
+//! Introspection of the engine's consensus state, as reported by the `parity_hbbftStatus` RPC.
+
+use common_types::BlockNumber;
+use ethereum_types::Address;
+use parity_crypto::publickey::public_to_address;
+use std::collections::BTreeMap;
+
+use crate::keygen_transactions::KeyGenPhase;
+use crate::NodeId;
+
+/// The consensus data received from a validator since the engine started.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorActivity {
+	/// The number of the validator's contributions included in batches.
+	pub contributions: u64,
+	/// The number of Honey Badger messages received from the validator.
+	pub hb_messages: u64,
+	/// The number of signature shares received from the validator.
+	pub sealing_messages: u64,
+}
+
+/// The state of sealing a block.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SealingStatus {
+	/// Signature shares are still being collected.
+	Ongoing,
+	/// The signature is ready to be used as the block's seal.
+	Complete,
+}
+
+/// The key generation progress of a pending validator, as written to the key history contract.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyGenValidatorStatus {
+	/// Whether the validator's Part has been written.
+	pub part: bool,
+	/// The number of Acks the validator has written.
+	pub acks: u64,
+}
+
+/// The key generation progress of the pending validator set.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyGenStatus {
+	/// Our own key generation phase.
+	pub phase: KeyGenPhase,
+	/// The progress of each pending validator, by mining address.
+	pub validators: BTreeMap<Address, KeyGenValidatorStatus>,
+}
+
+/// A snapshot of the engine's consensus state.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HbbftStatus {
+	/// The hbbft epoch, i.e. the number of the block being agreed upon, if we are a validator.
+	pub hbbft_epoch: Option<u64>,
+	/// The POSDAO staking epoch of the hbbft state.
+	pub posdao_epoch: u64,
+	/// Our node id, if we have a signer.
+	pub node_id: Option<NodeId>,
+	/// Our mining address, if we have a signer.
+	pub address: Option<Address>,
+	/// Whether we are part of the current validator set.
+	pub is_current_validator: bool,
+	/// Whether we are part of the pending validator set.
+	pub is_pending_validator: bool,
+	/// The consensus data received from each validator, by mining address.
+	pub validators: BTreeMap<Address, ValidatorActivity>,
+	/// The state of sealing each block in progress.
+	pub sealing: BTreeMap<BlockNumber, SealingStatus>,
+	/// The number of cached messages for future hbbft epochs.
+	pub future_messages: usize,
+	/// The key generation progress, if the pending validator set could be read.
+	pub keygen: Option<KeyGenStatus>,
+}
+
+/// Counts the consensus data received from each validator.
+#[derive(Debug, Default)]
+pub struct ActivityTracker {
+	activity: BTreeMap<NodeId, ValidatorActivity>,
+}
+
+impl ActivityTracker {
+	/// Records a contribution of the given validator included in a batch.
+	pub fn record_contribution(&mut self, node_id: NodeId) {
+		self.activity.entry(node_id).or_default().contributions += 1;
+	}
+
+	/// Records a Honey Badger message received from the given validator.
+	pub fn record_hb_message(&mut self, node_id: NodeId) {
+		self.activity.entry(node_id).or_default().hb_messages += 1;
+	}
+
+	/// Records a signature share received from the given validator.
+	pub fn record_sealing_message(&mut self, node_id: NodeId) {
+		self.activity.entry(node_id).or_default().sealing_messages += 1;
+	}
+
+	/// Returns the recorded activity, by validator mining address.
+	pub fn activity(&self) -> BTreeMap<Address, ValidatorActivity> {
+		self.activity
+			.iter()
+			.map(|(node_id, activity)| (public_to_address(&node_id.0), activity.clone()))
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{ActivityTracker, ValidatorActivity};
+	use crate::NodeId;
+	use parity_crypto::publickey::{public_to_address, Generator, Random};
+
+	#[test]
+	fn test_activity_tracker() {
+		let node_a = NodeId(*Random.generate().public());
+		let node_b = NodeId(*Random.generate().public());
+
+		let mut tracker = ActivityTracker::default();
+		tracker.record_contribution(node_a);
+		tracker.record_hb_message(node_a);
+		tracker.record_hb_message(node_a);
+		tracker.record_sealing_message(node_b);
+
+		let activity = tracker.activity();
+		assert_eq!(activity.len(), 2);
+		assert_eq!(
+			activity[&public_to_address(&node_a.0)],
+			ValidatorActivity {
+				contributions: 1,
+				hb_messages: 2,
+				sealing_messages: 0,
+			}
+		);
+		assert_eq!(
+			activity[&public_to_address(&node_b.0)],
+			ValidatorActivity {
+				contributions: 0,
+				hb_messages: 0,
+				sealing_messages: 1,
+			}
+		);
+	}
+}
@@ -11,17 +11,27 @@ use common_types::ids::BlockId;
 use ethabi;
 use ethereum_types::Address;
 
+/// A function performing a constant contract call, returning the raw output.
+pub type ContractCall<'a> = dyn Fn(Address, ethabi::Bytes) -> Result<ethabi::Bytes, String> + 'a;
+
 /// A contract bound to a client and block number.
 ///
 /// A bound contract is a combination of a `Client` reference, a `BlockId` and a contract `Address`.
 /// These three parts are enough to call a contract's function; return values are automatically
 /// decoded.
 pub struct BoundContract<'a> {
-	client: &'a dyn EngineClient,
-	block_id: BlockId,
+	caller: Caller<'a>,
 	contract_addr: Address,
 }
 
+/// The means by which a bound contract's functions are called.
+enum Caller<'a> {
+	/// Calls are executed by the client, on the state of the given block.
+	Client(&'a dyn EngineClient, BlockId),
+	/// Calls are executed by a plain function, e.g. a state proving caller.
+	Call(&'a ContractCall<'a>),
+}
+
 /// Contract call failed error.
 #[derive(Debug)]
 pub enum CallError {
@@ -37,11 +47,19 @@ pub enum CallError {
 
 impl<'a> fmt::Debug for BoundContract<'a> {
 	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-		fmt.debug_struct("BoundContract")
-			.field("client", &(self.client as *const dyn EngineClient))
-			.field("block_id", &self.block_id)
-			.field("contract_addr", &self.contract_addr)
-			.finish()
+		match self.caller {
+			Caller::Client(client, block_id) => fmt
+				.debug_struct("BoundContract")
+				.field("client", &(client as *const dyn EngineClient))
+				.field("block_id", &block_id)
+				.field("contract_addr", &self.contract_addr)
+				.finish(),
+			Caller::Call(call) => fmt
+				.debug_struct("BoundContract")
+				.field("call", &(call as *const ContractCall))
+				.field("contract_addr", &self.contract_addr)
+				.finish(),
+		}
 	}
 }
 
@@ -54,8 +72,16 @@ impl<'a> BoundContract<'a> {
 		contract_addr: Address,
 	) -> BoundContract {
 		BoundContract {
-			client,
-			block_id,
+			caller: Caller::Client(client, block_id),
+			contract_addr,
+		}
+	}
+
+	/// Create a new `BoundContract` which performs its calls through the given function.
+	#[inline]
+	pub fn bind_call(call: &'a ContractCall<'a>, contract_addr: Address) -> BoundContract<'a> {
+		BoundContract {
+			caller: Caller::Call(call),
 			contract_addr,
 		}
 	}
@@ -71,12 +97,14 @@ impl<'a> BoundContract<'a> {
 	{
 		let (data, output_decoder) = call;
 
-		let call_return = self
-			.client
-			.as_full_client()
-			.ok_or(CallError::NotFullClient)?
-			.call_contract(self.block_id, self.contract_addr, data)
-			.map_err(CallError::CallFailed)?;
+		let call_return = match self.caller {
+			Caller::Client(client, block_id) => client
+				.as_full_client()
+				.ok_or(CallError::NotFullClient)?
+				.call_contract(block_id, self.contract_addr, data),
+			Caller::Call(call) => call(self.contract_addr, data),
+		}
+		.map_err(CallError::CallFailed)?;
 
 		// Decode the result and return it.
 		output_decoder
@@ -1,3 +1,5 @@
 pub mod bound_contract;
 #[cfg(any(test, feature = "test-helpers"))]
 pub mod test_helpers;
+#[cfg(any(test, feature = "test-helpers"))]
+pub mod simulation;
@@ -0,0 +1,539 @@
+//! In-process simulation of a network of hbbft validators.
+//!
+//! All validators run in the same process, and their consensus messages are delivered by a seeded
+//! network model, which can delay, reorder and drop messages and partition the network. The
+//! engines draw the randomness of their contributions and of the encryption from generators seeded
+//! by the simulation as well, so a seed reproduces a run. Only the contributions' timestamps are
+//! taken from the clock. Validators can crash and be restarted, and Byzantine validators can send
+//! garbage instead of their messages or propose garbage contributions. The simulation checks that no two validators seal different
+//! blocks with the same number, and offers assertions for progress within a number of rounds.
+
+use client_traits::{EngineClient, ImportBlock};
+use common_types::verification::Unverified;
+use common_types::BlockNumber;
+use ethereum_types::H256;
+use hash::keccak;
+use hbbft::NetworkInfo;
+use parity_crypto::publickey::{KeyPair, Public, Secret};
+use rand::distributions::Standard;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::contribution::Contribution;
+use crate::persisted_state::{PersistedState, Proposal};
+use crate::utils::test_helpers::{hbbft_client_setup_with_rng, HbbftTestClient};
+
+/// The behavior of the simulated network, with delays measured in rounds.
+#[derive(Clone, Debug)]
+pub struct NetworkConfig {
+	/// The minimum number of rounds it takes to deliver a message.
+	pub min_delay: u64,
+	/// The maximum number of rounds it takes to deliver a message.
+	pub max_delay: u64,
+	/// The probability that a message is lost.
+	pub drop_probability: f64,
+	/// Whether messages delivered in the same round are delivered in random order.
+	pub reorder: bool,
+}
+
+impl Default for NetworkConfig {
+	/// A perfect network, delivering all messages immediately and in order.
+	fn default() -> Self {
+		NetworkConfig {
+			min_delay: 0,
+			max_delay: 0,
+			drop_probability: 0.0,
+			reorder: false,
+		}
+	}
+}
+
+/// A message on its way to its recipient.
+#[derive(Clone, Debug)]
+struct InFlight {
+	deliver_at: u64,
+	from: Public,
+	to: Public,
+	payload: Vec<u8>,
+}
+
+/// The network model: Messages sent in a round are delivered after a random delay, unless they are
+/// dropped or the network is partitioned.
+pub struct NetworkModel<R: Rng> {
+	config: NetworkConfig,
+	rng: R,
+	round: u64,
+	in_flight: Vec<InFlight>,
+	/// If the network is partitioned, the groups of nodes which can reach each other.
+	partition: Option<Vec<BTreeSet<Public>>>,
+}
+
+impl<R: Rng> NetworkModel<R> {
+	pub fn new(config: NetworkConfig, rng: R) -> Self {
+		NetworkModel {
+			config,
+			rng,
+			round: 0,
+			in_flight: Vec::new(),
+			partition: None,
+		}
+	}
+
+	/// Returns the current round.
+	pub fn round(&self) -> u64 {
+		self.round
+	}
+
+	/// Returns the random number generator of the network.
+	pub fn rng(&mut self) -> &mut R {
+		&mut self.rng
+	}
+
+	/// Returns whether no messages are in flight.
+	pub fn is_idle(&self) -> bool {
+		self.in_flight.is_empty()
+	}
+
+	/// Splits the network into groups of nodes which can only reach nodes of the same group.
+	///
+	/// Nodes which are not part of any group can not reach any other node.
+	pub fn partition(&mut self, groups: Vec<BTreeSet<Public>>) {
+		self.partition = Some(groups);
+	}
+
+	/// Reconnects all nodes.
+	pub fn heal(&mut self) {
+		self.partition = None;
+	}
+
+	/// Returns whether a message from `from` can currently reach `to`.
+	pub fn can_reach(&self, from: &Public, to: &Public) -> bool {
+		match self.partition {
+			None => true,
+			Some(ref groups) => groups
+				.iter()
+				.any(|group| group.contains(from) && group.contains(to)),
+		}
+	}
+
+	/// Sends a message, which is delivered after a random delay unless it is dropped.
+	pub fn send(&mut self, from: Public, to: Public, payload: Vec<u8>) {
+		if !self.can_reach(&from, &to) {
+			trace!(target: "consensus", "Simulation: Partition drops message from {} to {}", from, to);
+			return;
+		}
+		if self.config.drop_probability > 0.0 && self.rng.gen_bool(self.config.drop_probability) {
+			trace!(target: "consensus", "Simulation: Dropping message from {} to {}", from, to);
+			return;
+		}
+		let delay = if self.config.max_delay > self.config.min_delay {
+			self.rng
+				.gen_range(self.config.min_delay, self.config.max_delay + 1)
+		} else {
+			self.config.min_delay
+		};
+		self.in_flight.push(InFlight {
+			deliver_at: self.round + delay,
+			from,
+			to,
+			payload,
+		});
+	}
+
+	/// Removes and returns the messages due in the current round, and advances to the next round.
+	///
+	/// Messages to nodes which became unreachable while the message was in flight are dropped.
+	pub fn next_round(&mut self) -> Vec<(Public, Public, Vec<u8>)> {
+		let round = self.round;
+		let (due, pending): (Vec<_>, Vec<_>) = self
+			.in_flight
+			.drain(..)
+			.partition(|message| message.deliver_at <= round);
+		self.in_flight = pending;
+		let mut due: Vec<_> = due
+			.into_iter()
+			.filter(|message| self.can_reach(&message.from, &message.to))
+			.map(|message| (message.from, message.to, message.payload))
+			.collect();
+		if self.config.reorder {
+			due.shuffle(&mut self.rng);
+		}
+		self.round += 1;
+		due
+	}
+}
+
+/// The misbehavior of a Byzantine validator.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Byzantine {
+	/// The validator does not send any messages.
+	Silent,
+	/// The validator sends random bytes instead of its messages.
+	GarbageMessages,
+	/// The validator proposes garbage contributions, see `restart_with_contribution`.
+	GarbageContribution,
+}
+
+/// A validator taking part in the simulation.
+pub struct SimNode {
+	pub client: HbbftTestClient,
+	/// The validator's network info, used again when it is restarted.
+	netinfo: NetworkInfo<Public>,
+	pub crashed: bool,
+	pub byzantine: Option<Byzantine>,
+}
+
+/// A network of validators, connected by a simulated network.
+pub struct Simulation<R: Rng> {
+	pub nodes: BTreeMap<Public, SimNode>,
+	pub network: NetworkModel<R>,
+	/// Whether messages from non-Byzantine validators must be handled without errors.
+	///
+	/// With crashed nodes or an unreliable network, honest validators may receive messages they
+	/// can not handle yet, so this should be disabled for such scenarios.
+	pub strict: bool,
+	/// The hash of each block sealed so far, by block number.
+	sealed: BTreeMap<BlockNumber, H256>,
+}
+
+impl<R: Rng> Simulation<R> {
+	/// Creates a network of `size` validators with deterministic keys, and a network with the
+	/// given behavior. The network model and the validators draw their randomness from `rng`.
+	pub fn new(size: usize, config: NetworkConfig, mut rng: R) -> Self {
+		let keypairs: Vec<KeyPair> = (1..=size)
+			.map(|i| {
+				let secret = Secret::from(<[u8; 32]>::from(keccak(i.to_string())));
+				KeyPair::from_secret(secret).expect("KeyPair generation must succeed")
+			})
+			.collect();
+		let net_infos = NetworkInfo::generate_map(keypairs.iter().map(|kp| *kp.public()), &mut rng)
+			.expect("NetworkInfo generation to always succeed");
+		let nodes = keypairs
+			.into_iter()
+			.map(|kp| {
+				let netinfo = net_infos[kp.public()].clone();
+				let node_rng = StdRng::seed_from_u64(rng.gen());
+				let node = SimNode {
+					client: hbbft_client_setup_with_rng(kp.clone(), netinfo.clone(), node_rng),
+					netinfo,
+					crashed: false,
+					byzantine: None,
+				};
+				(*kp.public(), node)
+			})
+			.collect();
+		Simulation {
+			nodes,
+			network: NetworkModel::new(config, rng),
+			strict: true,
+			sealed: BTreeMap::new(),
+		}
+	}
+
+	/// Returns the ids of all validators.
+	pub fn node_ids(&self) -> Vec<Public> {
+		self.nodes.keys().cloned().collect()
+	}
+
+	/// Returns the given validator.
+	pub fn node(&self, id: &Public) -> &SimNode {
+		self.nodes.get(id).expect("Node must exist")
+	}
+
+	/// Returns the given validator as mutable reference.
+	pub fn node_mut(&mut self, id: &Public) -> &mut SimNode {
+		self.nodes.get_mut(id).expect("Node must exist")
+	}
+
+	/// Returns the ids of the validators which are neither crashed nor Byzantine.
+	pub fn honest_ids(&self) -> Vec<Public> {
+		self.nodes
+			.iter()
+			.filter(|(_, node)| !node.crashed && node.byzantine.is_none())
+			.map(|(id, _)| *id)
+			.collect()
+	}
+
+	/// Makes the given validator Byzantine.
+	pub fn set_byzantine(&mut self, id: &Public, byzantine: Byzantine) {
+		self.node_mut(id).byzantine = Some(byzantine);
+	}
+
+	/// Adds a transaction to the queue of each honest validator, to trigger block creation.
+	pub fn create_transactions(&mut self) {
+		for id in self.honest_ids() {
+			self.node_mut(&id).client.create_some_transaction(None);
+		}
+	}
+
+	/// Returns the highest block number all honest validators have imported.
+	pub fn min_honest_block(&self) -> BlockNumber {
+		self.honest_ids()
+			.iter()
+			.map(|id| self.node(id).client.client.chain().best_block_number())
+			.min()
+			.unwrap_or(0)
+	}
+
+	/// Returns whether any messages are waiting to be sent or delivered.
+	pub fn has_messages(&self) -> bool {
+		!self.network.is_idle()
+			|| self.nodes.values().any(|node| {
+				!node.crashed && !node.client.notify.targeted_messages.read().is_empty()
+			})
+	}
+
+	/// Runs a single round: The messages sent by the validators are passed to the network, and the
+	/// messages due in this round are delivered.
+	pub fn crank(&mut self) {
+		for (id, node) in &self.nodes {
+			let messages: Vec<_> = node
+				.client
+				.notify
+				.targeted_messages
+				.write()
+				.drain(..)
+				.collect();
+			if node.crashed {
+				continue;
+			}
+			for (payload, target) in messages {
+				let to = target.expect("The Message target node id must be set");
+				let payload = match node.byzantine {
+					None | Some(Byzantine::GarbageContribution) => payload,
+					Some(Byzantine::Silent) => continue,
+					Some(Byzantine::GarbageMessages) => self
+						.network
+						.rng()
+						.sample_iter(&Standard)
+						.take(payload.len())
+						.collect(),
+				};
+				self.network.send(*id, to, payload);
+			}
+		}
+
+		for (from, to, payload) in self.network.next_round() {
+			let node = match self.nodes.get(&to) {
+				Some(node) if !node.crashed => node,
+				_ => continue,
+			};
+			if let Err(e) = node.client.client.engine().handle_message(&payload, Some(from)) {
+				let honest_sender = self
+					.nodes
+					.get(&from)
+					.map_or(false, |sender| sender.byzantine.is_none());
+				if self.strict && honest_sender {
+					panic!("Message from {} to {} failed: {}", from, to, e);
+				}
+				trace!(target: "consensus", "Simulation: Message from {} to {} failed: {}", from, to, e);
+			}
+		}
+
+		self.check_safety();
+	}
+
+	/// Runs rounds until no messages are left, or at most `max_rounds` rounds.
+	pub fn crank_until_idle(&mut self, max_rounds: u64) {
+		for _ in 0..max_rounds {
+			if !self.has_messages() {
+				return;
+			}
+			self.crank();
+		}
+	}
+
+	/// Runs rounds until all honest validators imported the given block, and returns the number of
+	/// rounds it took, or `None` if they did not within `max_rounds` rounds.
+	pub fn crank_until_block(&mut self, block_num: BlockNumber, max_rounds: u64) -> Option<u64> {
+		for round in 0..max_rounds {
+			if self.min_honest_block() >= block_num {
+				return Some(round);
+			}
+			self.crank();
+		}
+		if self.min_honest_block() >= block_num {
+			Some(max_rounds)
+		} else {
+			None
+		}
+	}
+
+	/// Asserts that all honest validators import the given block within `max_rounds` rounds.
+	pub fn assert_liveness(&mut self, block_num: BlockNumber, max_rounds: u64) {
+		if self.crank_until_block(block_num, max_rounds).is_none() {
+			panic!(
+				"Honest validators did not reach block {} within {} rounds, lowest block is {}",
+				block_num,
+				max_rounds,
+				self.min_honest_block()
+			);
+		}
+	}
+
+	/// Panics if two validators imported different blocks with the same number.
+	pub fn check_safety(&mut self) {
+		for (id, node) in &self.nodes {
+			let chain = node.client.client.chain();
+			for block_num in 1..=chain.best_block_number() {
+				let hash = match chain.block_hash(block_num) {
+					Some(hash) => hash,
+					None => continue,
+				};
+				let sealed = self.sealed.entry(block_num).or_insert(hash);
+				assert_eq!(
+					*sealed, hash,
+					"Validator {} imported a conflicting block #{}",
+					id, block_num
+				);
+			}
+		}
+	}
+
+	/// Crashes the given validator: It neither sends nor receives messages until it is restarted.
+	pub fn crash(&mut self, id: &Public) {
+		self.node_mut(id).crashed = true;
+	}
+
+	/// Restarts the given validator with a new client, which keeps the consensus state persisted
+	/// by the engine and imports the chain from an honest validator, as if it had synced.
+	pub fn restart(&mut self, id: &Public) {
+		let engine_data = self.node(id).client.client.engine_data();
+		self.restart_with_engine_data(id, engine_data);
+	}
+
+	/// Restarts the given validator, which then proposes the given contribution for the next block.
+	///
+	/// This allows a Byzantine validator to propose a garbage contribution.
+	pub(crate) fn restart_with_contribution(&mut self, id: &Public, contribution: Contribution) {
+		let proposal = Proposal::new(contribution, self.network.rng());
+		let latest = self.node(id).client.client.chain().best_block_number();
+		let old_client = &self.node(id).client.client;
+		let mut state = PersistedState::load(&**old_client);
+		state.record_proposal(latest + 1, proposal);
+		state.store(&**old_client, latest);
+		let engine_data = old_client.engine_data();
+		self.restart_with_engine_data(id, engine_data);
+	}
+
+	fn restart_with_engine_data(&mut self, id: &Public, engine_data: Option<Vec<u8>>) {
+		let node_rng = StdRng::seed_from_u64(self.network.rng().gen());
+		let node = self.node(id);
+		let client = hbbft_client_setup_with_rng(
+			node.client.keypair.clone(),
+			node.netinfo.clone(),
+			node_rng,
+		);
+		if let Some(data) = engine_data {
+			client.client.set_engine_data(data);
+		}
+
+		// Import the chain of the honest validator with the most blocks.
+		let source = self
+			.honest_ids()
+			.into_iter()
+			.filter(|other| other != id)
+			.max_by_key(|other| self.node(other).client.client.chain().best_block_number());
+		if let Some(source) = source {
+			let source_chain = self.node(&source).client.client.chain();
+			for block_num in 1..=source_chain.best_block_number() {
+				let block = source_chain
+					.block_hash(block_num)
+					.and_then(|hash| source_chain.block(&hash))
+					.expect("Blocks up to the best block must exist");
+				let eip1559_transition = client.client.engine().params().eip1559_transition;
+				let unverified = Unverified::from_rlp(block.into_inner(), eip1559_transition)
+					.expect("Stored block must decode");
+				client
+					.client
+					.import_block(unverified)
+					.expect("Blocks of an honest validator must be importable");
+				client.client.flush_queue();
+			}
+		}
+
+		let node = self.node_mut(id);
+		node.client = client;
+		node.crashed = false;
+	}
+}
+
+/// Returns a contribution with transactions which are not valid RLP and without random data.
+pub(crate) fn garbage_contribution<R: Rng>(rng: &mut R) -> Contribution {
+	Contribution {
+		transactions: vec![rng.sample_iter(&Standard).take(32).collect()],
+		timestamp: 0,
+		random_data: Vec::new(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{NetworkConfig, NetworkModel};
+	use parity_crypto::publickey::{Generator, Public, Random};
+	use rand::rngs::StdRng;
+	use rand::SeedableRng;
+	use std::collections::BTreeSet;
+
+	fn rng() -> StdRng {
+		StdRng::seed_from_u64(7)
+	}
+
+	#[test]
+	fn test_perfect_network() {
+		let a = *Random.generate().public();
+		let b = *Random.generate().public();
+		let mut network = NetworkModel::new(NetworkConfig::default(), rng());
+		network.send(a, b, vec![1]);
+		network.send(b, a, vec![2]);
+		assert_eq!(
+			network.next_round(),
+			vec![(a, b, vec![1]), (b, a, vec![2])]
+		);
+		assert!(network.is_idle());
+		assert_eq!(network.round(), 1);
+	}
+
+	#[test]
+	fn test_delays_and_drops() {
+		let a = *Random.generate().public();
+		let b = *Random.generate().public();
+		let config = NetworkConfig {
+			min_delay: 2,
+			max_delay: 4,
+			drop_probability: 0.5,
+			reorder: true,
+		};
+		let mut network = NetworkModel::new(config, rng());
+		for i in 0..100 {
+			network.send(a, b, vec![i]);
+		}
+
+		// No message arrives before the minimum delay, all arrive until the maximum delay.
+		assert!(network.next_round().is_empty());
+		assert!(network.next_round().is_empty());
+		let delivered: usize = (0..3).map(|_| network.next_round().len()).sum();
+		assert!(network.is_idle());
+		assert!(delivered > 0 && delivered < 100);
+	}
+
+	#[test]
+	fn test_partition() {
+		let nodes: Vec<Public> = (0..3).map(|_| *Random.generate().public()).collect();
+		let mut network = NetworkModel::new(NetworkConfig::default(), rng());
+		let group: BTreeSet<Public> = nodes[..2].iter().cloned().collect();
+		network.partition(vec![group]);
+		network.send(nodes[0], nodes[1], vec![1]);
+		network.send(nodes[0], nodes[2], vec![2]);
+		network.send(nodes[2], nodes[0], vec![3]);
+		assert_eq!(network.next_round(), vec![(nodes[0], nodes[1], vec![1])]);
+
+		// Messages in flight are dropped if the recipient becomes unreachable.
+		network.heal();
+		network.send(nodes[0], nodes[2], vec![4]);
+		network.partition(Vec::new());
+		assert!(network.next_round().is_empty());
+	}
+}
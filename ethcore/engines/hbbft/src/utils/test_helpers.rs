@@ -1,17 +1,39 @@
-use client_traits::{Balance, Nonce, StateOrBlock};
+use client_traits::{
+	Balance, BlockChainClient, ChainInfo, EngineClient, ForceUpdateSealing, Nonce, StateOrBlock,
+};
+use common_types::blockchain_info::BlockChainInfo;
+use common_types::encoded;
+use common_types::engines::epoch::Transition as EpochTransition;
+use common_types::header::Header;
 use common_types::ids::BlockId;
 use common_types::transaction::{Action, SignedTransaction, Transaction};
+use common_types::BlockNumber;
 use engine::signer::from_keypair;
+use ethabi::Bytes;
 use ethcore::client::{ChainSyncing, Client};
 use ethcore::miner::{Miner, MinerService};
 use ethcore::test_helpers::generate_dummy_client_with_spec;
 use ethcore::test_helpers::TestNotify;
-use ethereum_types::{Address, U256};
+use ethcore_miner::pool::VerifiedTransaction;
+use ethereum_types::{Address, H256, H512, U256};
 use hbbft::NetworkInfo;
-use parity_crypto::publickey::{KeyPair, Public};
+use parity_crypto::publickey::{KeyPair, Public, Secret};
+use rand::rngs::StdRng;
 use spec::Spec;
+use std::str::FromStr;
 use std::sync::Arc;
 
+use crate::HoneyBadgerBFT;
+
+lazy_static! {
+	/// The validator of the genesis block in the test chain spec.
+	pub static ref MASTER_OF_CEREMONIES_KEYPAIR: KeyPair = KeyPair::from_secret(
+		Secret::from_str("18f059a4d72d166a96c1edfb9803af258a07b5ec862a961b3a1d801f443a1762")
+			.expect("Secret from hex string must succeed")
+	)
+	.expect("KeyPair generation from secret must succeed");
+}
+
 pub fn hbbft_spec() -> Spec {
 	Spec::load(
 		&::std::env::temp_dir(),
@@ -20,6 +42,34 @@ pub fn hbbft_spec() -> Spec {
 	.expect(concat!("Chain spec is invalid."))
 }
 
+/// The address of the randomness contract in the test chain spec.
+pub const RANDOMNESS_CONTRACT_ADDRESS: &str = "3000000000000000000000000000000000000001";
+
+/// The test chain spec, with the randomness contract used from the genesis block on.
+pub fn hbbft_spec_with_randomness() -> Spec {
+	hbbft_spec_with_params(|params| {
+		let mut addresses = serde_json::Map::new();
+		addresses.insert("0".into(), format!("0x{}", RANDOMNESS_CONTRACT_ADDRESS).into());
+		params["randomnessContractAddress"] = addresses.into();
+	})
+}
+
+/// The test chain spec, with validator set changes signalled in the extra data from the genesis
+/// block on.
+pub fn hbbft_spec_with_signalling() -> Spec {
+	hbbft_spec_with_params(|params| params["epochSignalTransition"] = 0.into())
+}
+
+/// Loads the test chain spec, with the engine parameters modified by `edit`.
+fn hbbft_spec_with_params<F: FnOnce(&mut serde_json::Value)>(edit: F) -> Spec {
+	let mut spec: serde_json::Value =
+		serde_json::from_slice(include_bytes!("../../res/honey_badger_bft.json"))
+			.expect("Chain spec is valid JSON.");
+	edit(&mut spec["engine"]["hbbft"]["params"]);
+	Spec::load(&::std::env::temp_dir(), spec.to_string().as_bytes())
+		.expect("Chain spec is invalid.")
+}
+
 struct SyncProviderWrapper();
 impl ChainSyncing for SyncProviderWrapper {
 	fn is_major_syncing(&self) -> bool {
@@ -28,11 +78,109 @@ impl ChainSyncing for SyncProviderWrapper {
 }
 
 pub fn hbbft_client() -> std::sync::Arc<ethcore::client::Client> {
-	let client = generate_dummy_client_with_spec(hbbft_spec);
+	hbbft_client_with_spec(hbbft_spec)
+}
+
+pub fn hbbft_client_with_spec(spec: fn() -> Spec) -> std::sync::Arc<ethcore::client::Client> {
+	let client = generate_dummy_client_with_spec(spec);
 	client.set_sync_provider(Box::new(SyncProviderWrapper()));
 	client
 }
 
+/// A client for the test chain spec, together with its engine, for tests of the engine internals.
+pub(crate) fn hbbft_client_with_engine() -> (Arc<Client>, Arc<HoneyBadgerBFT>) {
+	let spec_json = include_bytes!("../../res/honey_badger_bft.json") as &[u8];
+	let params = match ethjson::spec::Spec::load(spec_json)
+		.expect("Chain spec is valid.")
+		.engine
+	{
+		ethjson::spec::Engine::Hbbft(hbbft) => hbbft.params,
+		_ => panic!("The test chain spec uses the hbbft engine."),
+	};
+	let machine = Spec::load_machine(spec_json).expect("Chain spec is valid.");
+	let engine =
+		HoneyBadgerBFT::new_engine(params, machine).expect("Engine parameters must be valid.");
+	let client = generate_dummy_client_with_spec(|| {
+		let mut spec = hbbft_spec();
+		spec.engine = engine.clone();
+		spec
+	});
+	client.set_sync_provider(Box::new(SyncProviderWrapper()));
+	(client, engine)
+}
+
+/// Delegates to a full client without exposing it, so that the engine treats it like a light client.
+pub struct LightEngineClient(pub Arc<Client>);
+
+impl ChainInfo for LightEngineClient {
+	fn chain_info(&self) -> BlockChainInfo {
+		ChainInfo::chain_info(&*self.0)
+	}
+}
+
+impl EngineClient for LightEngineClient {
+	fn update_sealing(&self, force: ForceUpdateSealing) {
+		EngineClient::update_sealing(&*self.0, force)
+	}
+
+	fn submit_seal(&self, block_hash: H256, seal: Vec<Bytes>) {
+		EngineClient::submit_seal(&*self.0, block_hash, seal)
+	}
+
+	fn broadcast_consensus_message(&self, message: Bytes) {
+		EngineClient::broadcast_consensus_message(&*self.0, message)
+	}
+
+	fn send_consensus_message(&self, message: Bytes, node_id: Option<H512>) {
+		EngineClient::send_consensus_message(&*self.0, message, node_id)
+	}
+
+	fn epoch_transition_for(&self, parent_hash: H256) -> Option<EpochTransition> {
+		EngineClient::epoch_transition_for(&*self.0, parent_hash)
+	}
+
+	fn as_full_client(&self) -> Option<&dyn BlockChainClient> {
+		None
+	}
+
+	fn block_number(&self, id: BlockId) -> Option<BlockNumber> {
+		EngineClient::block_number(&*self.0, id)
+	}
+
+	fn block_header(&self, id: BlockId) -> Option<encoded::Header> {
+		EngineClient::block_header(&*self.0, id)
+	}
+
+	fn queued_transactions(&self) -> Vec<Arc<VerifiedTransaction>> {
+		EngineClient::queued_transactions(&*self.0)
+	}
+
+	fn create_pending_block_at(
+		&self,
+		txns: Vec<SignedTransaction>,
+		timestamp: u64,
+		block_number: u64,
+	) -> Option<Header> {
+		EngineClient::create_pending_block_at(&*self.0, txns, timestamp, block_number)
+	}
+
+	fn engine_data(&self) -> Option<Bytes> {
+		EngineClient::engine_data(&*self.0)
+	}
+
+	fn set_engine_data(&self, data: Bytes) {
+		EngineClient::set_engine_data(&*self.0, data)
+	}
+
+	fn add_reserved_peer(&self, enode: String) {
+		EngineClient::add_reserved_peer(&*self.0, enode)
+	}
+
+	fn remove_reserved_peer(&self, enode: String) {
+		EngineClient::remove_reserved_peer(&*self.0, enode)
+	}
+}
+
 pub struct HbbftTestClient {
 	pub client: Arc<Client>,
 	pub notify: Arc<TestNotify>,
@@ -70,7 +218,7 @@ impl HbbftTestClient {
 		&mut self,
 		caller: &KeyPair,
 		receiver: &Address,
-		abi_call: ethabi::Bytes,
+		abi_call: Bytes,
 		amount: &U256,
 	) {
 		let cur_nonce = self
@@ -98,7 +246,11 @@ impl HbbftTestClient {
 }
 
 pub fn create_hbbft_client(keypair: KeyPair) -> HbbftTestClient {
-	let client = hbbft_client();
+	create_hbbft_client_with_spec(keypair, hbbft_spec)
+}
+
+pub fn create_hbbft_client_with_spec(keypair: KeyPair, spec: fn() -> Spec) -> HbbftTestClient {
+	let client = hbbft_client_with_spec(spec);
 	let miner = client.miner();
 	let engine = client.engine();
 	let signer = from_keypair(keypair.clone());
@@ -117,8 +269,26 @@ pub fn create_hbbft_client(keypair: KeyPair) -> HbbftTestClient {
 }
 
 pub fn hbbft_client_setup(keypair: KeyPair, net_info: NetworkInfo<Public>) -> HbbftTestClient {
+	setup_client(hbbft_client(), keypair, net_info)
+}
+
+/// Like `hbbft_client_setup`, but the engine draws the randomness of its contributions from `rng`.
+pub(crate) fn hbbft_client_setup_with_rng(
+	keypair: KeyPair,
+	net_info: NetworkInfo<Public>,
+	rng: StdRng,
+) -> HbbftTestClient {
+	let (client, engine) = hbbft_client_with_engine();
+	engine.set_rng(rng);
+	setup_client(client, keypair, net_info)
+}
+
+fn setup_client(
+	client: Arc<Client>,
+	keypair: KeyPair,
+	net_info: NetworkInfo<Public>,
+) -> HbbftTestClient {
 	assert_eq!(keypair.public(), net_info.our_id());
-	let client = hbbft_client();
 
 	// Get miner reference
 	let miner = client.miner();
@@ -175,7 +345,7 @@ pub fn create_transfer(
 pub fn create_call(
 	keypair: &KeyPair,
 	receiver: &Address,
-	abi_call: ethabi::Bytes,
+	abi_call: Bytes,
 	amount: &U256,
 	nonce: &U256,
 ) -> SignedTransaction {
@@ -0,0 +1,179 @@
+//! Management of the reserved peers connecting the validators.
+//!
+//! Honey Badger BFT requires a connection between every pair of validators. Instead of relying on
+//! a static reserved peers file, the engine adds the current and pending validators as reserved
+//! peers, using the public keys from the validator set contract and the internet addresses the
+//! pools registered with the staking contract. Validators which left both sets are removed again,
+//! unless they were reserved peers already, e.g. configured with `--reserved-peers`.
+//!
+//! The staking contract does not store the validators' ports, so all validators are expected to
+//! listen on the port configured in the chain spec. Networks with validators on other ports, like
+//! a local testnet with several validators on one machine, don't configure it and rely on the
+//! reserved peers file instead.
+
+use client_traits::EngineClient;
+use common_types::ids::BlockId;
+use common_types::BlockNumber;
+use ethereum_types::Address;
+use parity_crypto::publickey::Public;
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+
+use crate::contracts::staking::get_pool_internet_address;
+use crate::contracts::validator_set::{
+	get_validator_pubkeys, staking_by_mining_address, ValidatorType,
+};
+use utils::bound_contract::CallError;
+
+/// Number of blocks after which the internet addresses are read again, even if the validator sets
+/// did not change.
+const VALIDATOR_PEERS_REFRESH_INTERVAL: BlockNumber = 100;
+
+/// Keeps the other validators of the current and pending validator sets as reserved peers.
+pub struct ValidatorPeersManager {
+	/// The devp2p port the validators listen on.
+	port: u16,
+	/// The enode URLs of the reserved peers we added, by mining address.
+	peers: BTreeMap<Address, String>,
+	/// The validators of the current and pending sets as of the last update.
+	validators: BTreeMap<Address, Public>,
+	/// The block number of the last update.
+	last_update: Option<BlockNumber>,
+}
+
+impl ValidatorPeersManager {
+	pub fn new(port: u16) -> Self {
+		ValidatorPeersManager {
+			port,
+			peers: BTreeMap::new(),
+			validators: BTreeMap::new(),
+			last_update: None,
+		}
+	}
+
+	/// Updates the reserved peers to the validators as of the latest block, if it changed since
+	/// the last update.
+	///
+	/// Only validators need connections to each other, so if we are neither a current nor a
+	/// pending validator all peers we added are removed.
+	pub fn update(
+		&mut self,
+		client: &dyn EngineClient,
+		our_address: Option<Address>,
+	) -> Result<(), CallError> {
+		let latest = client
+			.block_number(BlockId::Latest)
+			.ok_or_else(|| CallError::CallFailed("Latest block number not available".into()))?;
+		if self.last_update == Some(latest) {
+			return Ok(());
+		}
+
+		let mut validators = get_validator_pubkeys(client, BlockId::Latest, ValidatorType::Current)?;
+		validators.extend(get_validator_pubkeys(
+			client,
+			BlockId::Latest,
+			ValidatorType::Pending,
+		)?);
+		let refresh_due = self
+			.last_update
+			.map_or(true, |block_num| block_num + VALIDATOR_PEERS_REFRESH_INTERVAL <= latest);
+		if validators == self.validators && !refresh_due {
+			return Ok(());
+		}
+
+		let mut peers = BTreeMap::new();
+		if our_address.map_or(false, |address| validators.contains_key(&address)) {
+			for (address, public) in &validators {
+				if Some(*address) == our_address {
+					continue;
+				}
+				// A validator whose address can't be read is skipped, and retried on the next refresh.
+				let internet_address = staking_by_mining_address(client, address).and_then(
+					|staking_address| get_pool_internet_address(client, &staking_address),
+				);
+				match internet_address {
+					Ok(Some(ip)) => {
+						peers.insert(*address, enode_url(public, ip, self.port));
+					}
+					Ok(None) => {
+						debug!(target: "engine", "Validator {} did not register an internet address.", address);
+					}
+					Err(e) => {
+						warn!(target: "engine", "Reading the internet address of validator {} failed: {:?}", address, e);
+					}
+				}
+			}
+		}
+		self.set_peers(client, peers);
+		self.validators = validators;
+		self.last_update = Some(latest);
+		Ok(())
+	}
+
+	/// Replaces the reserved peers we added with the given ones.
+	fn set_peers(&mut self, client: &dyn EngineClient, peers: BTreeMap<Address, String>) {
+		for (address, enode) in &self.peers {
+			if peers.get(address) != Some(enode) {
+				debug!(target: "engine", "Removing validator {} as reserved peer {}.", address, enode);
+				client.remove_reserved_peer(enode.clone());
+			}
+		}
+		for (address, enode) in &peers {
+			if self.peers.get(address) != Some(enode) {
+				debug!(target: "engine", "Adding validator {} as reserved peer {}.", address, enode);
+				client.add_reserved_peer(enode.clone());
+			}
+		}
+		self.peers = peers;
+	}
+}
+
+/// Returns the enode URL of the node with the given public key, listening on the given address.
+fn enode_url(public: &Public, ip: IpAddr, port: u16) -> String {
+	format!("enode://{:x}@{}", public, SocketAddr::new(ip, port))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{enode_url, ValidatorPeersManager};
+	use ethcore::test_helpers::TestBlockChainClient;
+	use ethereum_types::Address;
+	use parity_crypto::publickey::{Generator, Random};
+	use std::collections::BTreeMap;
+	use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+	#[test]
+	fn test_enode_url() {
+		let public = *Random.generate().public();
+		assert_eq!(
+			enode_url(&public, IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), 30303),
+			format!("enode://{:x}@10.0.0.1:30303", public)
+		);
+		assert_eq!(
+			enode_url(&public, IpAddr::V6(Ipv6Addr::LOCALHOST), 30305),
+			format!("enode://{:x}@[::1]:30305", public)
+		);
+	}
+
+	#[test]
+	fn test_set_peers() {
+		let client = TestBlockChainClient::new();
+		let mut manager = ValidatorPeersManager::new(30303);
+		let validator_a = Address::from_low_u64_be(1);
+		let validator_b = Address::from_low_u64_be(2);
+
+		let mut peers = BTreeMap::new();
+		peers.insert(validator_a, "enode://a@10.0.0.1:30303".to_string());
+		peers.insert(validator_b, "enode://b@10.0.0.2:30303".to_string());
+		manager.set_peers(&client, peers.clone());
+		assert_eq!(client.reserved_peers.read().len(), 2);
+
+		// A validator which changed its address is replaced, one which left the sets is removed.
+		peers.remove(&validator_a);
+		peers.insert(validator_b, "enode://b@10.0.0.3:30303".to_string());
+		manager.set_peers(&client, peers);
+		let reserved_peers = client.reserved_peers.read();
+		assert_eq!(reserved_peers.len(), 1);
+		assert!(reserved_peers.contains("enode://b@10.0.0.3:30303"));
+	}
+}
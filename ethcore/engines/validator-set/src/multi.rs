@@ -178,6 +178,7 @@ mod tests {
 		header::Header,
 		ids::BlockId,
 		verification::Unverified,
+		BlockNumber,
 	};
 	use client_traits::{
 		BlockChainClient, BlockInfo, ChainInfo, ImportBlock, EngineClient, ForceUpdateSealing, TransactionRequest
@@ -235,7 +236,7 @@ mod tests {
 		let sync_client = generate_dummy_client_with_spec(spec::new_validator_multi);
 		sync_client.engine().register_client(Arc::downgrade(&sync_client) as _);
 		for i in 1..4 {
-			sync_client.import_block(Unverified::from_rlp(client.block(BlockId::Number(i)).unwrap().into_inner()).unwrap()).unwrap();
+			sync_client.import_block(Unverified::from_rlp(client.block(BlockId::Number(i)).unwrap().into_inner(), BlockNumber::max_value()).unwrap()).unwrap();
 		}
 		sync_client.flush_queue();
 		assert_eq!(sync_client.chain_info().best_block_number, 3);
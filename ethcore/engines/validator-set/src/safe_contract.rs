@@ -78,7 +78,7 @@ impl engine::StateDependentProof for StateProof {
 	}
 
 	fn check_proof(&self, machine: &Machine, proof: &[u8]) -> Result<(), String> {
-		let (header, state_items) = decode_first_proof(&Rlp::new(proof))
+		let (header, state_items) = decode_first_proof(&Rlp::new(proof), machine.params().eip1559_transition)
 			.map_err(|e| format!("proof incorrectly encoded: {}", e))?;
 		if &header != &self.header {
 			return Err("wrong header in proof".into());
@@ -134,6 +134,7 @@ fn check_first_proof(machine: &Machine, contract_address: Address, old_header: H
 			Arc::new(last_hashes)
 		},
 		gas_used: 0.into(),
+		base_fee: None,
 	};
 
 	// check state proof using given machine.
@@ -165,8 +166,8 @@ fn check_first_proof(machine: &Machine, contract_address: Address, old_header: H
 	}
 }
 
-fn decode_first_proof(rlp: &Rlp) -> Result<(Header, Vec<DBValue>), EthcoreError> {
-	let header = rlp.val_at(0)?;
+fn decode_first_proof(rlp: &Rlp, eip1559_transition: BlockNumber) -> Result<(Header, Vec<DBValue>), EthcoreError> {
+	let header = Header::decode_rlp(&rlp.at(0)?, eip1559_transition)?;
 	let state_items = rlp.at(1)?
 		.iter()
 		.map(|x| Ok(x.data()?.to_vec()) )
@@ -184,8 +185,8 @@ fn encode_proof(header: &Header, receipts: &[Receipt]) -> Bytes {
 	stream.drain()
 }
 
-fn decode_proof(rlp: &Rlp) -> Result<(Header, Vec<Receipt>), EthcoreError> {
-	Ok((rlp.val_at(0)?, rlp.list_at(1)?))
+fn decode_proof(rlp: &Rlp, eip1559_transition: BlockNumber) -> Result<(Header, Vec<Receipt>), EthcoreError> {
+	Ok((Header::decode_rlp(&rlp.at(0)?, eip1559_transition)?, rlp.list_at(1)?))
 }
 
 // given a provider and caller, generate proof. this will just be a state proof
@@ -478,11 +479,12 @@ impl ValidatorSet for ValidatorSafeContract {
 		-> Result<(SimpleList, Option<H256>), EthcoreError>
 	{
 		let rlp = Rlp::new(proof);
+		let eip1559_transition = machine.params().eip1559_transition;
 
 		if first {
 			trace!(target: "engine", "Recovering initial epoch set");
 
-			let (old_header, state_items) = decode_first_proof(&rlp)?;
+			let (old_header, state_items) = decode_first_proof(&rlp, eip1559_transition)?;
 			let number = old_header.number();
 			let old_hash = old_header.hash();
 			let addresses = check_first_proof(machine, self.contract_address, old_header, &state_items)
@@ -493,12 +495,12 @@ impl ValidatorSet for ValidatorSafeContract {
 
 			Ok((SimpleList::new(addresses), Some(old_hash)))
 		} else {
-			let (old_header, receipts) = decode_proof(&rlp)?;
+			let (old_header, receipts) = decode_proof(&rlp, eip1559_transition)?;
 
 			// ensure receipts match header.
 			// TODO: optimize? these were just decoded.
 			let found_root = triehash::ordered_trie_root(
-				receipts.iter().map(::rlp::encode)
+				receipts.iter().map(|r| r.envelope())
 			);
 			if found_root != *old_header.receipts_root() {
 				return Err(EthcoreError::Block(BlockError::InvalidReceiptsRoot(Mismatch {
@@ -644,6 +646,7 @@ mod tests {
 		log_entry::LogEntry,
 		transaction::{Transaction, Action},
 		verification::Unverified,
+		BlockNumber,
 	};
 	use client_traits::{BlockInfo, ChainInfo, ImportBlock, EngineClient, ForceUpdateSealing};
 	use engine::{EpochChange, Proof};
@@ -736,7 +739,7 @@ mod tests {
 		let sync_client = generate_dummy_client_with_spec(spec::new_validator_safe_contract);
 		sync_client.engine().register_client(Arc::downgrade(&sync_client) as _);
 		for i in 1..4 {
-			sync_client.import_block(Unverified::from_rlp(client.block(BlockId::Number(i)).unwrap().into_inner()).unwrap()).unwrap();
+			sync_client.import_block(Unverified::from_rlp(client.block(BlockId::Number(i)).unwrap().into_inner(), BlockNumber::max_value()).unwrap()).unwrap();
 		}
 		sync_client.flush_queue();
 		assert_eq!(sync_client.chain_info().best_block_number, 3);
@@ -109,6 +109,7 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 	pub fn requirements(
 		&mut self,
 		ext: &dyn vm::Ext,
+		contract: &Address,
 		instruction: Instruction,
 		info: &InstructionInfo,
 		stack: &dyn Stack<U256>,
@@ -131,7 +132,7 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 				let newval = stack.peek(1);
 				let val = ext.storage_at(&address)?.into_uint();
 
-				let gas = if schedule.eip1283 {
+				let mut gas = if schedule.eip1283 {
 					let orig = ext.initial_storage_at(&address)?.into_uint();
 					calculate_eip1283_sstore_gas(schedule, &orig, &val, &newval)
 				} else {
@@ -143,25 +144,40 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 						schedule.sstore_reset_gas
 					}
 				};
+				if ext.al_is_enabled() && !ext.al_contains_storage_key(contract, &address) {
+					gas += schedule.cold_sload_cost;
+				}
 				Request::Gas(Gas::from(gas))
 			},
 			instructions::SLOAD => {
-				Request::Gas(Gas::from(schedule.sload_gas))
+				let key = BigEndianHash::from_uint(stack.peek(0));
+				let gas = if ext.al_is_enabled() && !ext.al_contains_storage_key(contract, &key) {
+					schedule.cold_sload_cost
+				} else {
+					schedule.sload_gas
+				};
+				Request::Gas(Gas::from(gas))
 			},
 			instructions::BALANCE => {
-				Request::Gas(Gas::from(schedule.balance_gas))
+				let address = u256_to_address(stack.peek(0));
+				Request::Gas(Gas::from(account_access_gas(ext, &address, schedule.balance_gas)))
 			},
 			instructions::EXTCODESIZE => {
-				Request::Gas(Gas::from(schedule.extcodesize_gas))
+				let address = u256_to_address(stack.peek(0));
+				Request::Gas(Gas::from(account_access_gas(ext, &address, schedule.extcodesize_gas)))
 			},
 			instructions::EXTCODEHASH => {
-				Request::Gas(Gas::from(schedule.extcodehash_gas))
+				let address = u256_to_address(stack.peek(0));
+				Request::Gas(Gas::from(account_access_gas(ext, &address, schedule.extcodehash_gas)))
 			},
 			instructions::SUICIDE => {
 				let mut gas = Gas::from(schedule.suicide_gas);
 
 				let is_value_transfer = !ext.origin_balance()?.is_zero();
 				let address = u256_to_address(stack.peek(0));
+				if ext.al_is_enabled() && !ext.al_contains_address(&address) {
+					gas = overflowing!(gas.overflow_add(schedule.cold_account_access_cost.into()));
+				}
 				if (
 					!schedule.no_empty && !ext.exists(&address)?
 				) || (
@@ -190,7 +206,9 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 				Request::GasMemCopy(default_gas, mem_needed(stack.peek(0), stack.peek(2))?, Gas::from_u256(*stack.peek(2))?)
 			},
 			instructions::EXTCODECOPY => {
-				Request::GasMemCopy(schedule.extcodecopy_base_gas.into(), mem_needed(stack.peek(1), stack.peek(3))?, Gas::from_u256(*stack.peek(3))?)
+				let address = u256_to_address(stack.peek(0));
+				let base_gas = account_access_gas(ext, &address, schedule.extcodecopy_base_gas);
+				Request::GasMemCopy(base_gas.into(), mem_needed(stack.peek(1), stack.peek(3))?, Gas::from_u256(*stack.peek(3))?)
 			},
 			instructions::LOG0 | instructions::LOG1 | instructions::LOG2 | instructions::LOG3 | instructions::LOG4 => {
 				let no_of_topics = instruction.log_topics().expect("log_topics always return some for LOG* instructions; qed");
@@ -201,13 +219,13 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 				Request::GasMem(gas, mem_needed(stack.peek(0), stack.peek(1))?)
 			},
 			instructions::CALL | instructions::CALLCODE => {
-				let mut gas = Gas::from(schedule.call_gas);
+				let address = u256_to_address(stack.peek(1));
+				let mut gas = Gas::from(account_access_gas(ext, &address, schedule.call_gas));
 				let mem = cmp::max(
 					mem_needed(stack.peek(5), stack.peek(6))?,
 					mem_needed(stack.peek(3), stack.peek(4))?
 				);
 
-				let address = u256_to_address(stack.peek(1));
 				let is_value_transfer = !stack.peek(2).is_zero();
 
 				if instruction == instructions::CALL && (
@@ -227,7 +245,8 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 				Request::GasMemProvide(gas, mem, Some(requested))
 			},
 			instructions::DELEGATECALL => {
-				let gas = Gas::from(schedule.call_gas);
+				let code_address = u256_to_address(stack.peek(1));
+				let gas = Gas::from(account_access_gas(ext, &code_address, schedule.call_gas));
 				let mem = cmp::max(
 					mem_needed(stack.peek(4), stack.peek(5))?,
 					mem_needed(stack.peek(2), stack.peek(3))?
@@ -238,7 +257,10 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 			},
 			instructions::STATICCALL => {				
 				let code_address = u256_to_address(stack.peek(1));
-				let gas = if code_address <= PRECOMPILES_ADDRESS_LIMIT {
+				let gas = if ext.al_is_enabled() {
+					// Precompiles are warm from the start of the transaction.
+					Gas::from(account_access_gas(ext, &code_address, schedule.call_gas))
+				} else if code_address <= PRECOMPILES_ADDRESS_LIMIT {
 					Gas::from(schedule.staticcall_precompile_gas)
 				} else {
 					Gas::from(schedule.call_gas)
@@ -361,6 +383,19 @@ impl<Gas: evm::CostType> Gasometer<Gas> {
 	}
 }
 
+/// Cost of an instruction accessing an account: with EIP-2929 it depends on whether the account was already
+/// accessed in the transaction, otherwise it is the fixed `default_gas`.
+#[inline]
+fn account_access_gas(ext: &dyn vm::Ext, address: &Address, default_gas: usize) -> usize {
+	if !ext.al_is_enabled() {
+		default_gas
+	} else if ext.al_contains_address(address) {
+		ext.schedule().warm_storage_read_cost
+	} else {
+		ext.schedule().cold_account_access_cost
+	}
+}
+
 #[inline]
 fn mem_needed_const<Gas: evm::CostType>(mem: &U256, add: usize) -> vm::Result<Gas> {
 	Gas::from_u256(overflowing!(mem.overflowing_add(U256::from(add))))
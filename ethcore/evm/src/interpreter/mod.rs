@@ -356,7 +356,7 @@ impl<Cost: CostType> Interpreter<Cost> {
 				};
 
 				// Calculate gas cost
-				let requirements = match self.gasometer.as_mut().expect(GASOMETER_PROOF).requirements(ext, instruction, info, &self.stack, self.mem.size()) {
+				let requirements = match self.gasometer.as_mut().expect(GASOMETER_PROOF).requirements(ext, &self.params.address, instruction, info, &self.stack, self.mem.size()) {
 					Ok(t) => t,
 					Err(e) => return InterpreterResult::Done(Err(e)),
 				};
@@ -641,6 +641,7 @@ impl<Cost: CostType> Interpreter<Cost> {
 				let call_gas = provided.expect("`provided` comes through Self::exec from `Gasometer::get_gas_cost_mem`; `gas_gas_mem_cost` guarantees `Some` when instruction is `CALL`/`CALLCODE`/`DELEGATECALL`/`CREATE`; this is one of `CALL`/`CALLCODE`/`DELEGATECALL`; qed");
 				let code_address = self.stack.pop_back();
 				let code_address = u256_to_address(&code_address);
+				ext.al_insert_address(code_address);
 
 				let value = if instruction == instructions::DELEGATECALL {
 					None
@@ -739,8 +740,9 @@ impl<Cost: CostType> Interpreter<Cost> {
 				return Ok(InstructionResult::StopExecution);
 			},
 			instructions::SUICIDE => {
-				let address = self.stack.pop_back();
-				ext.suicide(&u256_to_address(&address))?;
+				let address = u256_to_address(&self.stack.pop_back());
+				ext.al_insert_address(address);
+				ext.suicide(&address)?;
 				return Ok(InstructionResult::StopExecution);
 			},
 			instructions::LOG0 | instructions::LOG1 | instructions::LOG2 | instructions::LOG3 | instructions::LOG4 => {
@@ -791,6 +793,7 @@ impl<Cost: CostType> Interpreter<Cost> {
 			},
 			instructions::SLOAD => {
 				let key = BigEndianHash::from_uint(&self.stack.pop_back());
+				ext.al_insert_storage_key(self.params.address, key);
 				let word = ext.storage_at(&key)?.into_uint();
 				self.stack.push(word);
 			},
@@ -809,6 +812,7 @@ impl<Cost: CostType> Interpreter<Cost> {
 						ext.add_sstore_refund(sstore_clears_schedule);
 					}
 				}
+				ext.al_insert_storage_key(self.params.address, address);
 				ext.set_storage(address, BigEndianHash::from_uint(&val))?;
 			},
 			instructions::PC => {
@@ -825,6 +829,7 @@ impl<Cost: CostType> Interpreter<Cost> {
 			},
 			instructions::BALANCE => {
 				let address = u256_to_address(&self.stack.pop_back());
+				ext.al_insert_address(address);
 				let balance = ext.balance(&address)?;
 				self.stack.push(balance);
 			},
@@ -864,11 +869,13 @@ impl<Cost: CostType> Interpreter<Cost> {
 			},
 			instructions::EXTCODESIZE => {
 				let address = u256_to_address(&self.stack.pop_back());
+				ext.al_insert_address(address);
 				let len = ext.extcodesize(&address)?.unwrap_or(0);
 				self.stack.push(U256::from(len));
 			},
 			instructions::EXTCODEHASH => {
 				let address = u256_to_address(&self.stack.pop_back());
+				ext.al_insert_address(address);
 				let hash = ext.extcodehash(&address)?.unwrap_or_else(H256::zero);
 				self.stack.push(hash.into_uint());
 			},
@@ -891,6 +898,7 @@ impl<Cost: CostType> Interpreter<Cost> {
 			},
 			instructions::EXTCODECOPY => {
 				let address = u256_to_address(&self.stack.pop_back());
+				ext.al_insert_address(address);
 				let code = ext.extcode(&address)?;
 				Self::copy_data_to_memory(
 					&mut self.mem,
@@ -947,6 +947,34 @@ fn test_subs_walk_into_subroutine(factory: super::Factory) {
 	assert_eq!(current, expected);
 }
 
+evm_test!{test_access_list_cold_and_warm: test_access_list_cold_and_warm_int}
+fn test_access_list_cold_and_warm(factory: super::Factory) {
+	// as defined in https://eips.ethereum.org/EIPS/eip-2929
+	// 60 00    PUSH 0
+	// 54       SLOAD (cold)
+	// 60 00    PUSH 0
+	// 54       SLOAD (warm)
+	// 60 0f    PUSH 0x0f
+	// 31       BALANCE (cold)
+	// 60 0f    PUSH 0x0f
+	// 31       BALANCE (warm)
+	let code = hex!("600054600054600f31600f31").to_vec();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(10_000);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new_berlin();
+
+	let gas_left = {
+		let vm = factory.create(params, ext.schedule(), ext.depth());
+		test_finalize(vm.exec(&mut ext).ok().unwrap()).unwrap()
+	};
+
+	assert_eq!(gas_left, U256::from(10_000 - 12 - 2100 - 100 - 2600 - 100));
+	assert_set_contains(&ext.accessed_addresses, &Address::from_low_u64_be(0x0f));
+	assert_set_contains(&ext.accessed_storage_keys, &(Address::zero(), H256::zero()));
+}
+
 evm_test!{test_calls: test_calls_int}
 fn test_calls(factory: super::Factory) {
 	let code = hex!("600054602d57600160005560006000600060006050610998610100f160006000600060006050610998610100f25b").to_vec();
@@ -27,7 +27,8 @@ use account_state::{CleanupMode, State};
 use common_types::{
 	header::Header,
 	transaction::SignedTransaction,
-	verification::Unverified
+	verification::Unverified,
+	BlockNumber,
 };
 use ethcore::test_helpers::new_temp_db;
 use ethcore_db as db;
@@ -72,6 +73,7 @@ fn build_env_info(header: &Header) -> vm::EnvInfo {
 		gas_limit: *header.gas_limit() * 10,
 		last_hashes: std::sync::Arc::new(vec![]),
 		gas_used: *header.gas_used(),
+		base_fee: header.base_fee(),
 	}
 }
 
@@ -96,7 +98,7 @@ macro_rules! bench_tx_apply {
 
 fn execute_8481475(c: &mut Criterion) {
 	// Block from the Constantinople era; 202 transactions, 32k RLP
-	let constantinople_block = Unverified::from_rlp(include_bytes!("./8481475.rlp").to_vec()).unwrap();
+	let constantinople_block = Unverified::from_rlp(include_bytes!("./8481475.rlp").to_vec(), BlockNumber::max_value()).unwrap();
 	let mut state = build_state();
 	let env_info = build_env_info(&constantinople_block.header);
 	let signed_txs = setup_state_for_block(&mut state, constantinople_block);
@@ -122,7 +124,7 @@ fn execute_8481475(c: &mut Criterion) {
 
 fn execute_9532543(c: &mut Criterion) {
 	// Block from the Istanbul era; 139 transactions, 38k RLP
-	let istanbul_block = Unverified::from_rlp(include_bytes!("./9532543.rlp").to_vec()).unwrap();
+	let istanbul_block = Unverified::from_rlp(include_bytes!("./9532543.rlp").to_vec(), BlockNumber::max_value()).unwrap();
 	let mut state = build_state();
 	let env_info = build_env_info(&istanbul_block.header);
 	let signed_txs = setup_state_for_block(&mut state, istanbul_block);
@@ -218,7 +218,7 @@ impl<B: Backend> ExecutiveState for State<B> {
 		};
 
 		let output = e.output;
-		let receipt = Receipt::new(outcome, e.cumulative_gas_used, e.logs);
+		let receipt = Receipt::new(outcome, e.cumulative_gas_used, e.logs).with_tx_type(t.tx_type());
 		trace!(target: "state", "Transaction receipt: {:?}", receipt);
 
 		Ok(ApplyOutcome {
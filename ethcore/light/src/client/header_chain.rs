@@ -40,6 +40,7 @@ use common_types::{
 	errors::{EthcoreError as Error, BlockError, EthcoreResult},
 	header::Header,
 	ids::BlockId,
+	BlockNumber,
 };
 use spec::{Spec, SpecHardcodedSync};
 use ethereum_types::{H256, H264, U256};
@@ -190,10 +191,10 @@ fn encode_canonical_transition(header: &Header, proof: &[u8]) -> Vec<u8> {
 }
 
 // decode last canonical transition entry.
-fn decode_canonical_transition(t: &[u8]) -> Result<(Header, &[u8]), DecoderError> {
+fn decode_canonical_transition(t: &[u8], eip1559_transition: BlockNumber) -> Result<(Header, &[u8]), DecoderError> {
 	let rlp = Rlp::new(t);
 
-	Ok((rlp.val_at(0)?, rlp.at(1)?.data()?))
+	Ok((Header::decode_rlp(&rlp.at(0)?, eip1559_transition)?, rlp.at(1)?.data()?))
 }
 
 /// Pending changes from `insert` to be applied after the database write has finished.
@@ -223,6 +224,7 @@ pub struct HeaderChain {
 	col: u32,
 	#[ignore_malloc_size_of = "ignored for performance reason"]
 	cache: Arc<Mutex<Cache>>,
+	eip1559_transition: BlockNumber,
 }
 
 impl HeaderChain {
@@ -291,6 +293,7 @@ impl HeaderChain {
 				db,
 				col,
 				cache,
+				eip1559_transition: spec.params().eip1559_transition,
 			}
 
 		} else {
@@ -306,6 +309,7 @@ impl HeaderChain {
 				db: db.clone(),
 				col,
 				cache,
+				eip1559_transition: spec.params().eip1559_transition,
 			};
 
 			// insert the hardcoded sync into the database.
@@ -317,7 +321,7 @@ impl HeaderChain {
 					batch.put(col, cht_key(cht_num as u64).as_bytes(), &::rlp::encode(cht_root));
 				}
 
-				let decoded_header = hardcoded_sync.header.decode()?;
+				let decoded_header = hardcoded_sync.header.decode(chain.eip1559_transition)?;
 				let decoded_header_num = decoded_header.number();
 
 				// write the block in the DB.
@@ -538,7 +542,8 @@ impl HeaderChain {
 										Ok(None) => panic!("stored candidates always have corresponding headers; qed"),
 										Ok(Some(header)) => Some((
 											epoch_transition,
-											::rlp::decode(&header).expect("decoding value from db failed")
+											Header::decode_rlp(&Rlp::new(&header), self.eip1559_transition)
+												.expect("decoding value from db failed")
 										)),
 									};
 								}
@@ -597,7 +602,7 @@ impl HeaderChain {
 						return Err(msg.into());
 					};
 
-					let decoded = header.decode().expect("decoding db value failed");
+					let decoded = header.decode(self.eip1559_transition).expect("decoding db value failed");
 
 					let entry: Entry = {
 						let bytes = self.db.get(self.col, era_key(h_num).as_bytes())?
@@ -827,7 +832,7 @@ impl HeaderChain {
 
 		for hdr in self.ancestry_iter(BlockId::Hash(parent_hash)) {
 			if let Some(transition) = live_proofs.get(&hdr.hash()).cloned() {
-				return hdr.decode().map(|decoded_hdr| {
+				return hdr.decode(self.eip1559_transition).map(|decoded_hdr| {
 					(decoded_hdr, transition.proof)
 				}).ok();
 			}
@@ -838,7 +843,7 @@ impl HeaderChain {
 			Ok(x) => {
 				let x = x.expect("last canonical transition always instantiated; qed");
 
-				let (hdr, proof) = decode_canonical_transition(&x)
+				let (hdr, proof) = decode_canonical_transition(&x, self.eip1559_transition)
 					.expect("last canonical transition always encoded correctly; qed");
 
 				Some((hdr, proof.to_vec()))
@@ -878,6 +883,7 @@ mod tests {
 	use cache::Cache;
 	use common_types::header::Header;
 	use common_types::ids::BlockId;
+	use common_types::BlockNumber;
 	use spec;
 	use ethereum_types::U256;
 	use kvdb::KeyValueDB;
@@ -1232,7 +1238,7 @@ mod tests {
 		let hardcoded_sync = chain.read_hardcoded_sync().expect("failed reading hardcoded sync").expect("failed unwrapping hardcoded sync");
 		assert_eq!(hardcoded_sync.chts.len(), 3);
 		assert_eq!(hardcoded_sync.total_difficulty, total_difficulty);
-		let decoded: Header = hardcoded_sync.header.decode().expect("decoding failed");
+		let decoded: Header = hardcoded_sync.header.decode(BlockNumber::max_value()).expect("decoding failed");
 		assert_eq!(decoded.number(), h_num);
 	}
 }
@@ -330,7 +330,7 @@ impl<T: ChainDataFetcher> Client<T> {
 
 			let epoch_proof = self.engine.is_epoch_end_light(
 				&verified_header,
-				&|h| self.chain.block_header(BlockId::Hash(h)).and_then(|hdr| hdr.decode().ok()),
+				&|h| self.chain.block_header(BlockId::Hash(h)).and_then(|hdr| hdr.decode(self.engine.params().eip1559_transition).ok()),
 				&|h| self.chain.pending_transition(h),
 			);
 
@@ -408,6 +408,7 @@ impl<T: ChainDataFetcher> Client<T> {
 			last_hashes: self.build_last_hashes(header.parent_hash()),
 			gas_used: Default::default(),
 			gas_limit: header.gas_limit(),
+			base_fee: header.base_fee(self.engine.params().eip1559_transition),
 		})
 	}
 
@@ -448,7 +449,7 @@ impl<T: ChainDataFetcher> Client<T> {
 		// Verify Block Family
 
 		let verify_family_result = {
-			parent_header.decode()
+			parent_header.decode(self.engine.params().eip1559_transition)
 				.map_err(|dec_err| dec_err.into())
 				.and_then(|decoded| {
 					self.engine.verify_block_family(&verified_header, &decoded)
@@ -640,6 +641,16 @@ impl<T: ChainDataFetcher> client_traits::EngineClient for Client<T> {
 		warn!(target: "client", "No miner available in light clients.");
 		None
 	}
+
+	fn engine_data(&self) -> Option<Vec<u8>> {
+		None
+	}
+
+	fn set_engine_data(&self, _data: Vec<u8>) { }
+
+	fn add_reserved_peer(&self, _enode: String) { }
+
+	fn remove_reserved_peer(&self, _enode: String) { }
 }
 
 impl<T> client_traits::Tick for Client<T> {}
@@ -753,14 +753,15 @@ pub mod header {
 
 	impl Decodable for Response {
 		fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
-			use common_types::header::Header as FullHeader;
+			use common_types::{BlockNumber, header::Header as FullHeader};
 
 			let mut headers = Vec::new();
 
 			for item in rlp.iter() {
-				// check that it's a valid encoding.
+				// check that it's a valid encoding. the base fee is taken as a seal field here,
+				// which is fine since only well-formedness is checked.
 				// TODO: just return full headers here?
-				let _: FullHeader = item.as_val()?;
+				FullHeader::decode_rlp(&item, BlockNumber::max_value())?;
 				headers.push(encoded::Header::new(item.as_raw().to_owned()));
 			}
 
@@ -1089,12 +1090,13 @@ pub mod block_body {
 
 	impl Decodable for Response {
 		fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
-			use common_types::header::Header as FullHeader;
+			use common_types::{BlockNumber, header::Header as FullHeader};
 			use common_types::transaction::UnverifiedTransaction;
 
-			// check body validity.
+			// check body validity. only well-formedness of the uncles is checked, so
+			// their base fee may be taken as a seal field.
 			let _: Vec<UnverifiedTransaction> = rlp.list_at(0)?;
-			let _: Vec<FullHeader> = rlp.list_at(1)?;
+			FullHeader::decode_rlp_list(&rlp.at(1)?, BlockNumber::max_value())?;
 
 			Ok(Response {
 				body: encoded::Body::new(rlp.as_raw().to_owned()),
@@ -345,7 +345,7 @@ impl<'a> CallCreateExecutive<'a> {
 		Ok(())
 	}
 
-	fn enact_result<B: 'a + StateBackend>(result: &vm::Result<FinalizationResult>, state: &mut State<B>, substate: &mut Substate, un_substate: Substate) {
+	fn enact_result<B: 'a + StateBackend>(result: &vm::Result<FinalizationResult>, state: &mut State<B>, substate: &mut Substate, mut un_substate: Substate) {
 		match *result {
 			Err(vm::Error::OutOfGas)
 				| Err(vm::Error::BadJumpDestination {..})
@@ -367,9 +367,11 @@ impl<'a> CallCreateExecutive<'a> {
 						}	
 					}
 					state.revert_to_checkpoint();
+					un_substate.exit_accessed(substate, false);
 			},
 			Ok(_) | Err(vm::Error::Internal(_)) => {
 				state.discard_checkpoint();
+				un_substate.exit_accessed(substate, true);
 				substate.accrue(un_substate);
 			}
 		}
@@ -490,6 +492,8 @@ impl<'a> CallCreateExecutive<'a> {
 					}
 				}
 
+				unconfirmed_substate.enter_accessed(substate);
+
 				let origin_info = OriginInfo::from(&params);
 				let exec = self.factory.create(params, self.schedule, self.depth);
 
@@ -541,6 +545,8 @@ impl<'a> CallCreateExecutive<'a> {
 					}
 				}
 
+				unconfirmed_substate.enter_accessed(substate);
+
 				let origin_info = OriginInfo::from(&params);
 				let exec = self.factory.create(params, self.schedule, self.depth);
 
@@ -834,6 +840,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		self.transact_with_tracer(
 			t,
 			options.check_nonce,
+			true,
 			options.output_from_init_contract,
 			options.tracer,
 			options.vm_tracer
@@ -842,6 +849,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 
 	/// Execute a transaction in a "virtual" context.
 	/// This will ensure the caller has enough balance to execute the desired transaction.
+	/// Transactions with a zero gas price are not checked against the base fee of the block.
 	/// Used for extra-block executions for things like consensus contracts and RPCs
 	pub fn transact_virtual<T, V>(&'a mut self, t: &SignedTransaction, options: TransactOptions<T, V>)
 		-> Result<Executed<T::Output, V::Output>, ExecutionError> where T: Tracer, V: VMTracer,
@@ -854,7 +862,14 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 			self.state.add_balance(&sender, &(needed_balance - balance), CleanupMode::NoEmpty)?;
 		}
 
-		self.transact(t, options)
+		self.transact_with_tracer(
+			t,
+			options.check_nonce,
+			!t.gas_price.is_zero(),
+			options.output_from_init_contract,
+			options.tracer,
+			options.vm_tracer
+		)
 	}
 
 	/// Execute transaction/call with tracing enabled
@@ -862,6 +877,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		&'a mut self,
 		t: &SignedTransaction,
 		check_nonce: bool,
+		check_base_fee: bool,
 		output_from_create: bool,
 		mut tracer: T,
 		mut vm_tracer: V
@@ -896,19 +912,46 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 			});
 		}
 
+		// the maximum fee per gas must cover the base fee of the block
+		let base_fee = if schedule.eip1559 { self.info.base_fee } else { None };
+		if let Some(base_fee) = base_fee {
+			if check_base_fee && t.gas_price < base_fee {
+				return Err(ExecutionError::GasPriceLowerThanBaseFee { gas_price: t.gas_price, base_fee });
+			}
+		}
+		let gas_price = t.effective_gas_price(base_fee);
+
 		// TODO: we might need bigints here, or at least check overflows.
 		let balance = self.state.balance(&sender)?;
-		let gas_cost = t.gas.full_mul(t.gas_price);
-		let total_cost = U512::from(t.value) + gas_cost;
+		// the sender has to afford the maximum fee, though only the effective gas price is charged
+		let max_gas_cost = t.gas.full_mul(t.gas_price);
+		let total_cost = U512::from(t.value) + max_gas_cost;
 
 		// avoid unaffordable transactions
 		let balance512 = U512::from(balance);
 		if balance512 < total_cost {
 			return Err(ExecutionError::NotEnoughCash { required: total_cost, got: balance512 });
 		}
+		let gas_cost = t.gas.full_mul(gas_price);
 
 		let mut substate = Substate::new();
 
+		if schedule.eip2929 {
+			substate.accessed.insert_address(sender);
+			if let Action::Call(ref address) = t.action {
+				substate.accessed.insert_address(*address);
+			}
+			for address in self.machine.builtins().keys() {
+				substate.accessed.insert_address(*address);
+			}
+			for item in t.access_list().into_iter().flatten() {
+				substate.accessed.insert_address(item.address);
+				for key in &item.storage_keys {
+					substate.accessed.insert_storage_key(item.address, *key);
+				}
+			}
+		}
+
 		self.state.inc_nonce(&sender)?;
 
 		self.state.sub_balance(
@@ -920,6 +963,9 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		let (result, output) = match t.action {
 			Action::Create => {
 				let (new_address, code_hash) = contract_address(CreateContractAddress::FromSenderAndNonce, &sender, &nonce, &t.data);
+				if schedule.eip2929 {
+					substate.accessed.insert_address(new_address);
+				}
 				let params = ActionParams {
 					code_address: new_address.clone(),
 					code_hash: code_hash,
@@ -927,7 +973,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 					sender: sender.clone(),
 					origin: sender.clone(),
 					gas: init_gas,
-					gas_price: t.gas_price,
+					gas_price,
 					value: ActionValue::Transfer(t.value),
 					code: Some(Arc::new(t.data.clone())),
 					code_version: schedule.latest_version,
@@ -949,7 +995,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 					sender: sender.clone(),
 					origin: sender.clone(),
 					gas: init_gas,
-					gas_price: t.gas_price,
+					gas_price,
 					value: ActionValue::Transfer(t.value),
 					code: self.state.code(address)?,
 					code_hash: self.state.code_hash(address)?,
@@ -1177,11 +1223,15 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		let gas_left = gas_left_prerefund + refunded;
 
 		let gas_used = t.gas.saturating_sub(gas_left);
-		let (refund_value, overflow_1) = gas_left.overflowing_mul(t.gas_price);
-		let (fees_value, overflow_2) = gas_used.overflowing_mul(t.gas_price);
+		let base_fee = if schedule.eip1559 { self.info.base_fee } else { None };
+		let gas_price = t.effective_gas_price(base_fee);
+		let (refund_value, overflow_1) = gas_left.overflowing_mul(gas_price);
+		let (fees_value, overflow_2) = gas_used.overflowing_mul(gas_price.saturating_sub(base_fee.unwrap_or_default()));
 		if overflow_1 || overflow_2 {
 			return Err(ExecutionError::TransactionMalformed("U256 Overflow".to_string()));
 		}
+		// the base fee is burnt, unless the chain collects it
+		let burnt_value = gas_used.saturating_mul(cmp::min(gas_price, base_fee.unwrap_or_default()));
 
 
 		trace!(target: "executive", "exec::finalize: t.gas={}, sstore_refunds={}, suicide_refunds={}, refunds_bound={}, gas_left_prerefund={}, refunded={}, gas_left={}, gas_used={}, refund_value={}, fees_value={}\n",
@@ -1193,6 +1243,10 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		self.state.add_balance(&sender, &refund_value, CleanupMode::NoEmpty)?;
 		trace!(target: "executive", "exec::finalize: Compensating author: fees_value={}, author={}\n", fees_value, &self.info.author);
 		self.state.add_balance(&self.info.author, &fees_value, cleanup_mode(&mut substate, &schedule))?;
+		if let Some(collector) = self.machine.params().eip1559_fee_collector {
+			trace!(target: "executive", "exec::finalize: Collecting base fee: burnt_value={}, collector={}\n", burnt_value, collector);
+			self.state.add_balance(&collector, &burnt_value, cleanup_mode(&mut substate, &schedule))?;
+		}
 
 		// perform suicides
 		for address in &substate.suicides {
@@ -1253,7 +1307,7 @@ mod tests {
 	use std::{
 		sync::Arc,
 		str::FromStr,
-		collections::HashSet,
+		collections::{BTreeMap, HashSet},
 	};
 
 	use account_state::CleanupMode;
@@ -2131,6 +2185,54 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn test_base_fee_is_collected() {
+		let collector = Address::from_low_u64_be(0xfee);
+		let author = Address::from_low_u64_be(0xa);
+		let mut params = new_byzantium_test_machine().params().clone();
+		params.eip1559_transition = 0;
+		params.eip1559_fee_collector = Some(collector);
+		let machine = Machine::regular(params, BTreeMap::new());
+
+		let keypair = Random.generate();
+		let transaction = |gas_price: u64| Transaction {
+			action: Action::Call(Address::from_low_u64_be(0x1234)),
+			value: U256::zero(),
+			data: vec![],
+			gas: U256::from(21_000),
+			gas_price: U256::from(gas_price),
+			nonce: U256::zero(),
+		}.sign(keypair.secret(), None);
+		let sender = keypair.address();
+
+		let mut state = get_temp_state();
+		state.add_balance(&sender, &U256::from(1_000_000), CleanupMode::NoEmpty).unwrap();
+		let mut info = EnvInfo::default();
+		info.author = author;
+		info.gas_limit = U256::from(100_000);
+		info.base_fee = Some(U256::from(7));
+		let schedule = machine.schedule(info.number);
+
+		let res = {
+			let mut ex = Executive::new(&mut state, &info, &machine, &schedule);
+			ex.transact(&transaction(5), TransactOptions::with_no_tracing())
+		};
+		match res {
+			Err(ExecutionError::GasPriceLowerThanBaseFee { gas_price, base_fee })
+				if gas_price == U256::from(5) && base_fee == U256::from(7) => (),
+			_ => panic!("Expected gas price lower than base fee error. {:?}", res),
+		}
+
+		let executed = {
+			let mut ex = Executive::new(&mut state, &info, &machine, &schedule);
+			ex.transact(&transaction(10), TransactOptions::with_no_tracing()).unwrap()
+		};
+		assert_eq!(executed.gas_used, U256::from(21_000));
+		assert_eq!(state.balance(&sender).unwrap(), U256::from(1_000_000 - 21_000 * 10));
+		assert_eq!(state.balance(&author).unwrap(), U256::from(21_000 * 3));
+		assert_eq!(state.balance(&collector).unwrap(), U256::from(21_000 * 7));
+	}
+
 	evm_test!{test_keccak: test_keccak_int}
 	fn test_keccak(factory: Factory) {
 		let code = hex!("6064640fffffffff20600055").to_vec();
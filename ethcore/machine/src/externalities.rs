@@ -270,6 +270,8 @@ impl<'a, T: 'a, V: 'a, B: 'a> Ext for Externalities<'a, T, V, B>
 			}
 		}
 
+		self.al_insert_address(address);
+
 		if trap {
 			return Err(TrapKind::Create(params, address));
 		}
@@ -454,6 +456,30 @@ impl<'a, T: 'a, V: 'a, B: 'a> Ext for Externalities<'a, T, V, B>
 	fn is_static(&self) -> bool {
 		return self.static_flag
 	}
+
+	fn al_is_enabled(&self) -> bool {
+		self.schedule.eip2929
+	}
+
+	fn al_contains_storage_key(&self, address: &Address, key: &H256) -> bool {
+		self.substate.accessed.contains_storage_key(address, key)
+	}
+
+	fn al_insert_storage_key(&mut self, address: Address, key: H256) {
+		if self.al_is_enabled() {
+			self.substate.accessed.insert_storage_key(address, key);
+		}
+	}
+
+	fn al_contains_address(&self, address: &Address) -> bool {
+		self.substate.accessed.contains_address(address)
+	}
+
+	fn al_insert_address(&mut self, address: Address) {
+		if self.al_is_enabled() {
+			self.substate.accessed.insert_address(address);
+		}
+	}
 }
 
 #[cfg(test)]
@@ -490,6 +516,7 @@ mod tests {
 			last_hashes: Arc::new(vec![]),
 			gas_used: 0.into(),
 			gas_limit: 0.into(),
+			base_fee: None,
 		}
 	}
 
@@ -18,10 +18,10 @@
 
 use std::collections::BTreeMap;
 use std::cmp;
+use std::convert::TryFrom;
 use std::sync::Arc;
 
-use ethereum_types::{U256, H256, Address};
-use rlp::Rlp;
+use ethereum_types::{U256, U512, H256, Address};
 use log::debug;
 
 use common_types::{
@@ -32,7 +32,7 @@ use common_types::{
 		params::CommonParams,
 	},
 	errors::{EngineError, EthcoreError as Error},
-	transaction::{self, SYSTEM_ADDRESS, UNSIGNED_SENDER, UnverifiedTransaction, SignedTransaction},
+	transaction::{self, SYSTEM_ADDRESS, UNSIGNED_SENDER, TypedTxId, UnverifiedTransaction, SignedTransaction},
 };
 use vm::{ActionType, ActionParams, ActionValue, ParamsType};
 use vm::{EnvInfo, Schedule};
@@ -219,7 +219,8 @@ impl Machine {
 	/// The gas floor target must not be lower than the engine's minimum gas limit.
 	pub fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_floor_target: U256, gas_ceil_target: U256) {
 		header.set_difficulty(parent.difficulty().clone());
-		let gas_limit = parent.gas_limit().clone();
+		header.set_base_fee(self.calc_base_fee(parent));
+		let gas_limit = self.parent_gas_limit(parent);
 		assert!(!gas_limit.is_zero(), "Gas limit should be > 0");
 
 		if let Some(ref ethash_params) = self.ethash_extensions {
@@ -304,6 +305,51 @@ impl Machine {
 			.and_then(|b| if b.is_active(block_number) { Some(b) } else { None })
 	}
 
+	/// The gas limit of the parent the gas limit of a block is bounded by. At the EIP-1559
+	/// transition the gas target of the block, rather than its gas limit, is that of the parent.
+	pub fn parent_gas_limit(&self, parent: &Header) -> U256 {
+		let params = self.params();
+		if parent.number() + 1 == params.eip1559_transition {
+			parent.gas_limit().saturating_mul(params.eip1559_elasticity_multiplier)
+		} else {
+			*parent.gas_limit()
+		}
+	}
+
+	/// The base fee of the child of the given block, or `None` before the EIP-1559 transition.
+	///
+	/// The base fee changes by up to `1 / eip1559_base_fee_max_change_denominator` per block,
+	/// depending on how far the gas used by the parent is off its gas target.
+	pub fn calc_base_fee(&self, parent: &Header) -> Option<U256> {
+		let params = self.params();
+		let number = parent.number() + 1;
+		if number < params.eip1559_transition {
+			return None;
+		}
+		let parent_base_fee = match parent.base_fee() {
+			Some(base_fee) if number > params.eip1559_transition => base_fee,
+			_ => return Some(params.eip1559_base_fee_initial_value),
+		};
+
+		let gas_target = *parent.gas_limit() / params.eip1559_elasticity_multiplier;
+		let gas_used = *parent.gas_used();
+		let denominator = params.eip1559_base_fee_max_change_denominator;
+		if gas_target.is_zero() || denominator.is_zero() || gas_used == gas_target {
+			return Some(parent_base_fee);
+		}
+
+		let base_fee_delta = |gas_delta: U256| {
+			let delta = parent_base_fee.full_mul(gas_delta) / U512::from(gas_target) / U512::from(denominator);
+			U256::try_from(delta).unwrap_or_else(|_| U256::max_value())
+		};
+		if gas_used > gas_target {
+			let delta = cmp::max(base_fee_delta(gas_used - gas_target), U256::one());
+			Some(parent_base_fee.saturating_add(delta))
+		} else {
+			Some(parent_base_fee.saturating_sub(base_fee_delta(gas_target - gas_used)))
+		}
+	}
+
 	/// Some intrinsic operation parameters; by default they take their value from the `spec()`'s `engine_params`.
 	pub fn maximum_extra_data_size(&self) -> usize { self.params().maximum_extra_data_size }
 
@@ -345,6 +391,22 @@ impl Machine {
 		};
 		t.verify_basic(check_low_s, chain_id)?;
 
+		if t.tx_type() != TypedTxId::Legacy && header.number() < self.params().eip2930_transition {
+			return Err(transaction::Error::TransactionTypeNotEnabled);
+		}
+
+		if t.tx_type() == TypedTxId::EIP1559Transaction {
+			if header.number() < self.params().eip1559_transition {
+				return Err(transaction::Error::TransactionTypeNotEnabled);
+			}
+			if t.max_priority_fee_per_gas() > t.gas_price {
+				return Err(transaction::Error::PriorityFeeAboveMaxFee {
+					max_fee: t.gas_price,
+					max_priority_fee: t.max_priority_fee_per_gas(),
+				});
+			}
+		}
+
 		Ok(())
 	}
 
@@ -364,16 +426,15 @@ impl Machine {
 		Ok(())
 	}
 
-	/// Performs pre-validation of RLP encoded transaction before other
+	/// Performs pre-validation of an encoded transaction before other
 	/// processing: check length against `max_transaction_size` and decode the
-	/// RLP.
+	/// legacy RLP or typed envelope.
 	pub fn decode_transaction(&self, transaction: &[u8]) -> Result<UnverifiedTransaction, transaction::Error> {
-		let rlp = Rlp::new(&transaction);
-		if rlp.as_raw().len() > self.params().max_transaction_size {
-			debug!("Rejected oversized transaction of {} bytes", rlp.as_raw().len());
+		if transaction.len() > self.params().max_transaction_size {
+			debug!("Rejected oversized transaction of {} bytes", transaction.len());
 			return Err(transaction::Error::TooBig)
 		}
-		rlp.as_val().map_err(|e| transaction::Error::InvalidRlp(e.to_string()))
+		UnverifiedTransaction::from_envelope(transaction).map_err(|e| transaction::Error::InvalidRlp(e.to_string()))
 	}
 
 	/// Get the balance, in base units, associated with an account.
@@ -482,4 +543,33 @@ mod tests {
 		machine.populate_from_parent(&mut header, &parent, U256::from(150_000), U256::from(150_002));
 		assert_eq!(*header.gas_limit(), U256::from(150_002));
 	}
+
+	#[test]
+	fn calculates_base_fee_from_parent() {
+		let mut params = spec::new_homestead_test().params().clone();
+		params.eip1559_transition = 10;
+		let machine = Machine::regular(params, Default::default());
+
+		let mut parent = Header::new();
+		parent.set_number(8);
+		parent.set_gas_limit(U256::from(20_000_000));
+		assert_eq!(machine.calc_base_fee(&parent), None);
+		assert_eq!(machine.parent_gas_limit(&parent), U256::from(20_000_000));
+
+		// The first block of the transition doubles the gas limit of its parent.
+		parent.set_number(9);
+		assert_eq!(machine.calc_base_fee(&parent), Some(U256::from(1_000_000_000)));
+		assert_eq!(machine.parent_gas_limit(&parent), U256::from(40_000_000));
+
+		parent.set_number(10);
+		parent.set_base_fee(Some(U256::from(1_000_000_000)));
+		parent.set_gas_used(U256::from(10_000_000));
+		assert_eq!(machine.calc_base_fee(&parent), Some(U256::from(1_000_000_000)));
+
+		parent.set_gas_used(U256::from(20_000_000));
+		assert_eq!(machine.calc_base_fee(&parent), Some(U256::from(1_125_000_000)));
+
+		parent.set_gas_used(U256::zero());
+		assert_eq!(machine.calc_base_fee(&parent), Some(U256::from(875_000_000)));
+	}
 }
@@ -17,7 +17,8 @@
 //! Execution environment substate.
 
 use std::collections::HashSet;
-use ethereum_types::Address;
+use std::mem;
+use ethereum_types::{Address, H256};
 use common_types::log_entry::LogEntry;
 
 /// State changes which should be applied in finalize,
@@ -38,6 +39,80 @@ pub struct Substate {
 
 	/// Created contracts.
 	pub contracts_created: Vec<Address>,
+
+	/// Accounts and storage slots accessed so far in the transaction (EIP-2929). Only the substate of the frame
+	/// being executed holds them; a frame takes them over from its parent on entry and hands them back when it ends.
+	pub accessed: AccessedSet,
+}
+
+/// An entry of the `AccessedSet` journal.
+#[derive(Debug)]
+enum Access {
+	Address(Address),
+	StorageKey(Address, H256),
+}
+
+/// Accounts and storage slots accessed in a transaction, journaled so that the accesses of a failed call can be
+/// reverted without copying the sets for every call.
+#[derive(Debug, Default)]
+pub struct AccessedSet {
+	addresses: HashSet<Address>,
+	storage_keys: HashSet<(Address, H256)>,
+	/// Accesses made since the first checkpoint, in order.
+	journal: Vec<Access>,
+	/// Journal length at each checkpoint.
+	checkpoints: Vec<usize>,
+}
+
+impl AccessedSet {
+	/// Whether the account was accessed.
+	pub fn contains_address(&self, address: &Address) -> bool {
+		self.addresses.contains(address)
+	}
+
+	/// Whether the storage slot was accessed.
+	pub fn contains_storage_key(&self, address: &Address, key: &H256) -> bool {
+		self.storage_keys.contains(&(*address, *key))
+	}
+
+	/// Marks the account as accessed.
+	pub fn insert_address(&mut self, address: Address) {
+		if self.addresses.insert(address) && !self.checkpoints.is_empty() {
+			self.journal.push(Access::Address(address));
+		}
+	}
+
+	/// Marks the storage slot as accessed.
+	pub fn insert_storage_key(&mut self, address: Address, key: H256) {
+		if self.storage_keys.insert((address, key)) && !self.checkpoints.is_empty() {
+			self.journal.push(Access::StorageKey(address, key));
+		}
+	}
+
+	/// Create a recoverable checkpoint. Accesses made after it are undone by `revert_to_checkpoint`.
+	pub fn checkpoint(&mut self) {
+		self.checkpoints.push(self.journal.len());
+	}
+
+	/// Merge the last checkpoint into the previous one, keeping the accesses made since.
+	pub fn discard_checkpoint(&mut self) {
+		self.checkpoints.pop();
+		if self.checkpoints.is_empty() {
+			self.journal.clear();
+		}
+	}
+
+	/// Forget the accesses made since the last checkpoint and remove it.
+	pub fn revert_to_checkpoint(&mut self) {
+		if let Some(len) = self.checkpoints.pop() {
+			for access in self.journal.drain(len..) {
+				match access {
+					Access::Address(address) => { self.addresses.remove(&address); },
+					Access::StorageKey(address, key) => { self.storage_keys.remove(&(address, key)); },
+				}
+			}
+		}
+	}
 }
 
 impl Substate {
@@ -54,11 +129,30 @@ impl Substate {
 		self.sstore_clears_refund += s.sstore_clears_refund;
 		self.contracts_created.extend(s.contracts_created);
 	}
+
+	/// Take over the accessed accounts and storage slots of the parent call, checkpointing them so that the
+	/// accesses of this call can be reverted.
+	pub fn enter_accessed(&mut self, parent: &mut Substate) {
+		self.accessed = mem::take(&mut parent.accessed);
+		self.accessed.checkpoint();
+	}
+
+	/// Hand the accessed accounts and storage slots back to the parent call, reverting the accesses of this call
+	/// unless it succeeded.
+	pub fn exit_accessed(&mut self, parent: &mut Substate, success: bool) {
+		let mut accessed = mem::take(&mut self.accessed);
+		if success {
+			accessed.discard_checkpoint();
+		} else {
+			accessed.revert_to_checkpoint();
+		}
+		parent.accessed = accessed;
+	}
 }
 
 #[cfg(test)]
 mod tests {
-	use ethereum_types::Address;
+	use ethereum_types::{Address, H256};
 	use common_types::log_entry::LogEntry;
 	use super::Substate;
 
@@ -94,4 +188,43 @@ mod tests {
 		assert_eq!(sub_state.sstore_clears_refund, (15000 * 12).into());
 		assert_eq!(sub_state.suicides.len(), 1);
 	}
+
+	#[test]
+	fn accessed_set_is_kept_by_successful_calls() {
+		let mut parent = Substate::new();
+		parent.accessed.insert_address(Address::from_low_u64_be(1));
+
+		let mut child = Substate::new();
+		child.enter_accessed(&mut parent);
+		assert!(child.accessed.contains_address(&Address::from_low_u64_be(1)));
+		child.accessed.insert_address(Address::from_low_u64_be(2));
+		child.accessed.insert_storage_key(Address::from_low_u64_be(2), H256::from_low_u64_be(3));
+
+		child.exit_accessed(&mut parent, true);
+		assert!(parent.accessed.contains_address(&Address::from_low_u64_be(1)));
+		assert!(parent.accessed.contains_address(&Address::from_low_u64_be(2)));
+		assert!(parent.accessed.contains_storage_key(&Address::from_low_u64_be(2), &H256::from_low_u64_be(3)));
+	}
+
+	#[test]
+	fn accessed_set_is_reverted_by_failed_calls() {
+		let mut parent = Substate::new();
+		parent.accessed.insert_address(Address::from_low_u64_be(1));
+
+		let mut child = Substate::new();
+		child.enter_accessed(&mut parent);
+		child.accessed.insert_address(Address::from_low_u64_be(1));
+		child.accessed.insert_address(Address::from_low_u64_be(2));
+
+		let mut grandchild = Substate::new();
+		grandchild.enter_accessed(&mut child);
+		grandchild.accessed.insert_storage_key(Address::from_low_u64_be(2), H256::from_low_u64_be(3));
+		grandchild.exit_accessed(&mut child, true);
+		assert!(child.accessed.contains_storage_key(&Address::from_low_u64_be(2), &H256::from_low_u64_be(3)));
+
+		child.exit_accessed(&mut parent, false);
+		assert!(parent.accessed.contains_address(&Address::from_low_u64_be(1)));
+		assert!(!parent.accessed.contains_address(&Address::from_low_u64_be(2)));
+		assert!(!parent.accessed.contains_storage_key(&Address::from_low_u64_be(2), &H256::from_low_u64_be(3)));
+	}
 }
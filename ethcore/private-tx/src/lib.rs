@@ -787,7 +787,7 @@ impl Provider {
 		let executed = self.execute_private(source, TransactOptions::with_no_tracing(), block)?;
 		let header = self.client.block_header(block)
 			.ok_or(Error::StatePruned)
-			.and_then(|h| h.decode().map_err(|_| Error::StateIncorrect).into())?;
+			.and_then(|h| h.decode(self.client.engine().params().eip1559_transition).map_err(|_| Error::StateIncorrect).into())?;
 		let (executed_code, executed_state) = (executed.code.unwrap_or_default(), executed.state);
 		let tx_data = Self::generate_constructor(validators, executed_code.clone(), executed_state.clone());
 		let mut tx = Transaction {
@@ -77,6 +77,11 @@ impl pool::ScoredTransaction for VerifiedPrivateTransaction {
 		&self.transaction.gas_price
 	}
 
+	/// Gets the gas price paid to the block author on top of the given base fee.
+	fn effective_priority_fee(&self, block_base_fee: Option<U256>) -> U256 {
+		self.transaction.effective_priority_fee(block_base_fee)
+	}
+
 	/// Gets transaction nonce.
 	fn nonce(&self) -> U256 {
 		self.transaction.nonce
@@ -133,7 +138,7 @@ impl Default for VerificationStore {
 			verification_pool: RwLock::new(
 				txpool::Pool::new(
 					txpool::NoopListener,
-					pool::scoring::NonceAndGasPrice(pool::PrioritizationStrategy::GasPriceOnly),
+					pool::scoring::NonceAndGasPrice::new(pool::PrioritizationStrategy::GasPriceOnly),
 					pool::Options {
 						max_count: MAX_QUEUE_LEN,
 						max_per_sender: MAX_QUEUE_LEN / 10,
@@ -178,7 +183,7 @@ impl VerificationStore {
 			transaction_sender: signed_sender,
 		};
 		let replace = pool::replace::ReplaceByScoreAndReadiness::new(
-			self.verification_pool.read().scoring().clone(), client);
+			self.verification_pool.read().scoring().clone(), client, None);
 		self.verification_pool.write().import(verified, &replace)?;
 		Ok(())
 	}
@@ -25,6 +25,7 @@ use common_types::{
 	block::Block,
 	view,
 	views::BlockView,
+	BlockNumber,
 };
 
 fn encode_block(b: &Block) -> Bytes {
@@ -37,8 +38,8 @@ fn empty_block_abridging() {
 	let receipts_root = b.header.receipts_root().clone();
 	let encoded = encode_block(&b);
 
-	let abridged = AbridgedBlock::from_block_view(&view!(BlockView, &encoded));
-	assert_eq!(abridged.to_block(H256::zero(), 0, receipts_root).unwrap(), b);
+	let abridged = AbridgedBlock::from_block_view(&view!(BlockView, &encoded), BlockNumber::max_value());
+	assert_eq!(abridged.to_block(H256::zero(), 0, receipts_root, BlockNumber::max_value()).unwrap(), b);
 }
 
 #[test]
@@ -48,8 +49,20 @@ fn wrong_number() {
 	let receipts_root = b.header.receipts_root().clone();
 	let encoded = encode_block(&b);
 
-	let abridged = AbridgedBlock::from_block_view(&view!(BlockView, &encoded));
-	assert_eq!(abridged.to_block(H256::zero(), 2, receipts_root).unwrap(), b);
+	let abridged = AbridgedBlock::from_block_view(&view!(BlockView, &encoded), BlockNumber::max_value());
+	assert_eq!(abridged.to_block(H256::zero(), 2, receipts_root, BlockNumber::max_value()).unwrap(), b);
+}
+
+#[test]
+fn with_base_fee() {
+	let mut b = Block::default();
+	b.header.set_number(5);
+	b.header.set_base_fee(Some(U256::from(1_000_000_000)));
+	let receipts_root = b.header.receipts_root().clone();
+	let encoded = encode_block(&b);
+
+	let abridged = AbridgedBlock::from_block_view(&view!(BlockView, &encoded), 5);
+	assert_eq!(abridged.to_block(H256::zero(), 5, receipts_root, 5).unwrap(), b);
 }
 
 #[test]
@@ -84,6 +97,6 @@ fn with_transactions() {
 
 	let encoded = encode_block(&b);
 
-	let abridged = AbridgedBlock::from_block_view(&view!(BlockView, &encoded[..]));
-	assert_eq!(abridged.to_block(H256::zero(), 0, receipts_root).unwrap(), b);
+	let abridged = AbridgedBlock::from_block_view(&view!(BlockView, &encoded[..]), BlockNumber::max_value());
+	assert_eq!(abridged.to_block(H256::zero(), 0, receipts_root, BlockNumber::max_value()).unwrap(), b);
 }
@@ -22,7 +22,7 @@ use std::sync::atomic::AtomicBool;
 
 use account_db::AccountDBMut;
 use account_state;
-use blockchain::{BlockChain, BlockChainDB};
+use blockchain::{BlockChain, BlockChainDB, Config as BlockChainConfig};
 use client_traits::ChainInfo;
 use common_types::{
 	ids::BlockId,
@@ -171,7 +171,11 @@ pub fn restore(
 
 	let mut state = StateRebuilder::new(db.key_value().clone(), journaldb::Algorithm::Archive);
 	let mut secondary = {
-		let chain = BlockChain::new(Default::default(), genesis, db.clone());
+		let chain_config = BlockChainConfig {
+			eip1559_transition: engine.params().eip1559_transition,
+			..Default::default()
+		};
+		let chain = BlockChain::new(chain_config, genesis, db.clone());
 		chunker.rebuilder(chain, db, manifest).unwrap()
 	};
 
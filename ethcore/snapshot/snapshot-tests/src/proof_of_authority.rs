@@ -21,8 +21,12 @@ use std::sync::Arc;
 use std::str::FromStr;
 
 use accounts::AccountProvider;
-use client_traits::{BlockChainClient, ChainInfo};
-use common_types::transaction::{Transaction, Action, SignedTransaction};
+use blockchain::{BlockChain, BlockProvider, Config as BlockChainConfig};
+use client_traits::{BlockChainClient, BlockInfo, ChainInfo};
+use common_types::{
+	ids::BlockId,
+	transaction::{Transaction, Action, SignedTransaction},
+};
 use ethabi_contract::use_contract;
 use ethcore::{
 	client::Client,
@@ -44,6 +48,7 @@ use_contract!(test_validator_set, "../../res/contracts/test_validator_set.json")
 const PASS: &'static str = "";
 const TRANSITION_BLOCK_1: usize = 2; // block at which the contract becomes activated.
 const TRANSITION_BLOCK_2: usize = 10; // block at which the second contract activates.
+const EIP1559_TRANSITION: u64 = 4; // first block with a base fee, in `spec_fixed_to_contract_eip1559`.
 
 macro_rules! secret {
 	($e: expr) => { Secret::from(keccak($e).0) }
@@ -70,6 +75,19 @@ fn spec_fixed_to_contract() -> Spec {
 	Spec::load(&tempdir.path(), &data[..]).unwrap()
 }
 
+/// Like `spec_fixed_to_contract`, but headers carry the base fee from block `EIP1559_TRANSITION` on.
+/// The base fee starts at zero and the blocks stay below the gas target, so it remains zero and
+/// the transactions here stay valid.
+fn spec_fixed_to_contract_eip1559() -> Spec {
+	let data = String::from_utf8(include_bytes!("test_validator_contract.json").to_vec()).unwrap()
+		.replace(
+			r#""networkID" : "0x69""#,
+			&format!(r#""networkID" : "0x69", "eip1559Transition": "{:#x}", "eip1559BaseFeeInitialValue": "0x0""#, EIP1559_TRANSITION),
+		);
+	let tempdir = TempDir::new().unwrap();
+	Spec::load(&tempdir.path(), data.as_bytes()).unwrap()
+}
+
 // creates an account provider, filling it with accounts from all the given
 // secrets and password `PASS`.
 // returns addresses corresponding to secrets.
@@ -94,8 +112,8 @@ enum Transition {
 }
 
 // create a chain with the given transitions and some blocks beyond that transition.
-fn make_chain(accounts: Arc<AccountProvider>, blocks_beyond: usize, transitions: Vec<Transition>) -> Arc<Client> {
-	let client = generate_dummy_client_with_spec(spec_fixed_to_contract);
+fn make_chain(spec: fn() -> Spec, accounts: Arc<AccountProvider>, blocks_beyond: usize, transitions: Vec<Transition>) -> Arc<Client> {
+	let client = generate_dummy_client_with_spec(spec);
 
 	let mut cur_signers = vec![*RICH_ADDR];
 	{
@@ -219,7 +237,7 @@ fn fixed_to_contract_only() {
 
 	assert!(provider.has_account(*RICH_ADDR));
 
-	let client = make_chain(provider, 3, vec![
+	let client = make_chain(spec_fixed_to_contract, provider, 3, vec![
 		Transition::Manual(3, vec![addrs[2], addrs[3], addrs[5], addrs[7]]),
 		Transition::Manual(6, vec![addrs[0], addrs[1], addrs[4], addrs[6]]),
 	]);
@@ -252,7 +270,7 @@ fn fixed_to_contract_to_contract() {
 
 	assert!(provider.has_account(*RICH_ADDR));
 
-	let client = make_chain(provider, 3, vec![
+	let client = make_chain(spec_fixed_to_contract, provider, 3, vec![
 		Transition::Manual(3, vec![addrs[2], addrs[3], addrs[5], addrs[7]]),
 		Transition::Manual(6, vec![addrs[0], addrs[1], addrs[4], addrs[6]]),
 		Transition::Implicit(10, vec![addrs[0]]),
@@ -267,3 +285,43 @@ fn fixed_to_contract_to_contract() {
 	for _ in 0..16 { spec.engine.step() }
 	snapshot_helpers::restore(new_db, &*spec.engine, &*reader, &spec.genesis_block()).unwrap();
 }
+
+#[test]
+fn fixed_to_contract_with_base_fee() {
+	let (provider, addrs) = make_accounts(&[
+		RICH_SECRET.clone(),
+		secret!("foo"),
+		secret!("bar"),
+		secret!("test"),
+		secret!("signer"),
+		secret!("crypto"),
+		secret!("wizard"),
+		secret!("dog42"),
+	]);
+
+	assert!(provider.has_account(*RICH_ADDR));
+
+	// the transition at 6, its finality proof and the warp target all carry a base fee.
+	let client = make_chain(spec_fixed_to_contract_eip1559, provider, 3, vec![
+		Transition::Manual(3, vec![addrs[2], addrs[3], addrs[5], addrs[7]]),
+		Transition::Manual(6, vec![addrs[0], addrs[1], addrs[4], addrs[6]]),
+	]);
+
+	assert_eq!(client.chain_info().best_block_number, 11);
+	let best_hash = client.chain_info().best_block_hash;
+	let best_header = client.block_header(BlockId::Hash(best_hash)).unwrap();
+	assert_eq!(best_header.base_fee(EIP1559_TRANSITION), Some(0.into()));
+	let (reader, _tempdir) = snapshot_helpers::snap(&*client);
+
+	let new_db = test_helpers::new_db();
+	let spec = spec_fixed_to_contract_eip1559();
+
+	for _ in 0..11 { spec.engine.step() }
+	snapshot_helpers::restore(new_db.clone(), &*spec.engine, &*reader, &spec.genesis_block()).unwrap();
+
+	let chain_config = BlockChainConfig { eip1559_transition: EIP1559_TRANSITION, ..Default::default() };
+	let new_chain = BlockChain::new(chain_config, &spec.genesis_block(), new_db);
+	let restored = new_chain.block_header_data(&best_hash).unwrap();
+	assert_eq!(restored.hash(), best_hash);
+	assert_eq!(restored.base_fee(EIP1559_TRANSITION), Some(0.into()));
+}
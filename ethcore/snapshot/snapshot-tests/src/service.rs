@@ -29,6 +29,7 @@ use common_types::{
 	snapshot::Progress,
 	verification::Unverified,
 	snapshot::{ManifestData, RestorationStatus},
+	BlockNumber,
 };
 use snapshot::{
 	chunk_state, chunk_secondary, SnapshotService,
@@ -318,7 +319,7 @@ fn keep_ancient_blocks() {
 	for block_number in 1..50 {
 		let block_hash = bc.block_hash(block_number).unwrap();
 		let block = bc.block(&block_hash).unwrap();
-		client2.import_block(Unverified::from_rlp(block.into_inner()).unwrap()).unwrap();
+		client2.import_block(Unverified::from_rlp(block.into_inner(), BlockNumber::max_value()).unwrap()).unwrap();
 	}
 
 	client2.flush_queue();
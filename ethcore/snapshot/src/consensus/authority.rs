@@ -107,7 +107,7 @@ impl SnapshotComponents for PoaSnapshot {
 		let (block, receipts) = chain.block(&block_at)
 			.and_then(|b| chain.block_receipts(&block_at).map(|r| (b, r)))
 			.ok_or_else(||SnapshotError::BlockNotFound(block_at))?;
-		let block = block.decode()?;
+		let block = block.decode(chain.eip1559_transition())?;
 
 		let parent_td = chain.block_details(block.header.parent_hash())
 			.map(|d| d.total_difficulty)
@@ -135,15 +135,7 @@ impl SnapshotComponents for PoaSnapshot {
 		db: Arc<dyn BlockChainDB>,
 		manifest: &ManifestData,
 	) -> Result<Box<dyn Rebuilder>, EthcoreError> {
-		Ok(Box::new(ChunkRebuilder {
-			manifest: manifest.clone(),
-			warp_target: None,
-			chain,
-			db: db.key_value().clone(),
-			had_genesis: false,
-			unverified_firsts: Vec::new(),
-			last_epochs: Vec::new(),
-		}))
+		Ok(Box::new(ChunkRebuilder::new(chain, db, manifest, TransitionCheck::FinalityProof)))
 	}
 
 	fn min_supported_version(&self) -> u64 { 3 }
@@ -163,19 +155,47 @@ fn write_chunk(last: bool, chunk_data: &mut Vec<Bytes>, sink: &mut ChunkSink) ->
 	(sink)(stream.out().as_slice()).map_err(Into::into)
 }
 
+/// How a rebuilder checks that an epoch transition was agreed on by the validators
+/// of the epoch prior.
+#[derive(Clone, Copy, PartialEq)]
+pub(super) enum TransitionCheck {
+	/// Transitions are confirmed by a finality proof contained in the epoch data.
+	FinalityProof,
+	/// Blocks are final as soon as they are sealed, so the transition header's seal is checked.
+	Seal,
+}
+
+// a check of a transition against the validators of the epoch prior.
+enum PendingCheck {
+	FinalityProof(Bytes, H256),
+	Seal,
+}
+
+impl PendingCheck {
+	// whether the transition at `header` is proven to the validators behind `verifier`.
+	fn passes(&self, verifier: &dyn EpochVerifier, header: &Header) -> bool {
+		match *self {
+			PendingCheck::FinalityProof(ref proof, hash) =>
+				verifier.check_finality_proof(proof).map_or(false, |hashes| hashes.contains(&hash)),
+			PendingCheck::Seal => verifier.verify_heavy(header).is_ok(),
+		}
+	}
+}
+
 // rebuilder checks state proofs for all transitions, and checks that each
 // transition header is verifiable from the epoch data of the one prior.
-struct ChunkRebuilder {
+pub(super) struct ChunkRebuilder {
 	manifest: ManifestData,
 	warp_target: Option<Header>,
 	chain: BlockChain,
 	db: Arc<dyn KeyValueDB>,
 	had_genesis: bool,
+	check: TransitionCheck,
 
 	// sorted vectors of unverified first blocks in a chunk
 	// and epoch data from last blocks in chunks.
 	// verification for these will be done at the end.
-	unverified_firsts: Vec<(Header, Bytes, H256)>,
+	unverified_firsts: Vec<(Header, PendingCheck)>,
 	last_epochs: Vec<(Header, Box<dyn EpochVerifier>)>,
 }
 
@@ -186,6 +206,24 @@ struct Verified {
 }
 
 impl ChunkRebuilder {
+	pub(super) fn new(
+		chain: BlockChain,
+		db: Arc<dyn BlockChainDB>,
+		manifest: &ManifestData,
+		check: TransitionCheck,
+	) -> Self {
+		ChunkRebuilder {
+			manifest: manifest.clone(),
+			warp_target: None,
+			chain,
+			db: db.key_value().clone(),
+			had_genesis: false,
+			check,
+			unverified_firsts: Vec::new(),
+			last_epochs: Vec::new(),
+		}
+	}
+
 	fn verify_transition(
 		&mut self,
 		last_verifier: &mut Option<Box<dyn EpochVerifier>>,
@@ -195,38 +233,38 @@ impl ChunkRebuilder {
 		use engine::ConstructedVerifier;
 
 		// decode.
-		let header: Header = transition_rlp.val_at(0)?;
+		let header = Header::decode_rlp(&transition_rlp.at(0)?, engine.params().eip1559_transition)?;
 		let epoch_data: Bytes = transition_rlp.val_at(1)?;
 
 		trace!(target: "snapshot", "verifying transition to epoch at block {}", header.number());
 
-		// check current transition against validators of last epoch.
-		let new_verifier = match engine.epoch_verifier(&header, &epoch_data) {
-			ConstructedVerifier::Trusted(v) => v,
-			ConstructedVerifier::Unconfirmed(v, finality_proof, hash) => {
-				match *last_verifier {
-					Some(ref last) =>
-						if last.check_finality_proof(finality_proof).map_or(true, |hashes| !hashes.contains(&hash))
-					{
-						return Err(SnapshotError::BadEpochProof(header.number()).into());
-					},
-					None if header.number() != 0 => {
-						// genesis never requires additional validation.
-
-						let idx = self.unverified_firsts
-							.binary_search_by_key(&header.number(), |&(ref h, _, _)| h.number())
-							.unwrap_or_else(|x| x);
+		let (new_verifier, pending) = match engine.epoch_verifier(&header, &epoch_data) {
+			ConstructedVerifier::Err(e) => return Err(e),
+			constructed if self.check == TransitionCheck::Seal =>
+				(constructed.known_confirmed()?, Some(PendingCheck::Seal)),
+			ConstructedVerifier::Trusted(v) => (v, None),
+			ConstructedVerifier::Unconfirmed(v, finality_proof, hash) =>
+				(v, Some(PendingCheck::FinalityProof(finality_proof.to_owned(), hash))),
+		};
 
-						let entry = (header.clone(), finality_proof.to_owned(), hash);
-						self.unverified_firsts.insert(idx, entry);
-					}
-					None => {}
+		// check current transition against validators of last epoch.
+		if let Some(pending) = pending {
+			match *last_verifier {
+				Some(ref last) => if !pending.passes(&**last, &header) {
+					return Err(SnapshotError::BadEpochProof(header.number()).into());
+				},
+				None if header.number() != 0 => {
+					// genesis never requires additional validation.
+
+					let idx = self.unverified_firsts
+						.binary_search_by_key(&header.number(), |&(ref h, _)| h.number())
+						.unwrap_or_else(|x| x);
+
+					self.unverified_firsts.insert(idx, (header.clone(), pending));
 				}
-
-				v
+				None => {}
 			}
-			ConstructedVerifier::Err(e) => return Err(e),
-		};
+		}
 
 		// create new epoch verifier.
 		*last_verifier = Some(new_verifier);
@@ -325,11 +363,12 @@ impl Rebuilder for ChunkRebuilder {
 		if is_last_chunk {
 			use common_types::block::Block;
 
+			let eip1559_transition = engine.params().eip1559_transition;
 			let last_rlp = rlp.at(num_items - 1)?;
 			let block = Block {
-				header: last_rlp.val_at(0)?,
+				header: Header::decode_rlp(&last_rlp.at(0)?, eip1559_transition)?,
 				transactions: last_rlp.list_at(1)?,
-				uncles: last_rlp.list_at(2)?,
+				uncles: Header::decode_rlp_list(&last_rlp.at(2)?, eip1559_transition)?,
 			};
 			let block_data = block.rlp_bytes();
 			let receipts: Vec<Receipt> = last_rlp.list_at(3)?;
@@ -369,11 +408,11 @@ impl Rebuilder for ChunkRebuilder {
 		// we store all last verifiers, but not all firsts.
 		// match each unverified first epoch with a last epoch verifier.
 		let mut lasts_reversed = self.last_epochs.iter().rev();
-		for &(ref header, ref finality_proof, hash) in self.unverified_firsts.iter().rev() {
+		for &(ref header, ref pending) in self.unverified_firsts.iter().rev() {
 			let mut found = false;
 			while let Some(&(ref last_header, ref last_verifier)) = lasts_reversed.next() {
 				if last_header.number() < header.number() {
-					if last_verifier.check_finality_proof(&finality_proof).map_or(true, |hashes| !hashes.contains(&hash)) {
+					if !pending.passes(&**last_verifier, header) {
 						return Err(SnapshotError::BadEpochProof(header.number()).into());
 					}
 					found = true;
@@ -0,0 +1,77 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Secondary chunk creation and restoration, implementation for hbbft chains.
+//!
+//! The chunks have the same format as the proof-of-authority chunks: the epoch transitions
+//! along with their proofs, which for hbbft contain the key generation data needed to derive
+//! the threshold public key of each validator set. The validator set and key generation history
+//! contracts themselves are part of the state chunks.
+//!
+//! Blocks are final as soon as they are sealed, so instead of finality proofs every transition
+//! header is verified against the threshold public key of the previous validator set.
+
+use std::sync::Arc;
+
+use blockchain::{BlockChain, BlockChainDB};
+use common_types::{
+	errors::{SnapshotError, EthcoreError},
+	snapshot::{ChunkSink, Progress, ManifestData}
+};
+use ethereum_types::H256;
+use parking_lot::RwLock;
+
+use crate::{SnapshotComponents, Rebuilder};
+use super::PoaSnapshot;
+use super::authority::{ChunkRebuilder, TransitionCheck};
+
+/// Snapshot creation and restoration for hbbft chains.
+/// Chunk format:
+///
+/// [FLAG, [header, epoch data], ...]
+///   - Header data at which transition occurred,
+///   - epoch data (the key generation data of the new validator set)
+///
+/// FLAG is a bool: true for last chunk, false otherwise.
+///
+/// The last item of the last chunk will be a list containing data for the warp target block:
+/// [header, transactions, uncles, receipts, parent_td].
+pub struct HbbftSnapshot;
+
+impl SnapshotComponents for HbbftSnapshot {
+	fn chunk_all(
+		&mut self,
+		chain: &BlockChain,
+		block_at: H256,
+		sink: &mut ChunkSink,
+		progress: &RwLock<Progress>,
+		preferred_size: usize,
+	) -> Result<(), SnapshotError> {
+		PoaSnapshot.chunk_all(chain, block_at, sink, progress, preferred_size)
+	}
+
+	fn rebuilder(
+		&self,
+		chain: BlockChain,
+		db: Arc<dyn BlockChainDB>,
+		manifest: &ManifestData,
+	) -> Result<Box<dyn Rebuilder>, EthcoreError> {
+		Ok(Box::new(ChunkRebuilder::new(chain, db, manifest, TransitionCheck::Seal)))
+	}
+
+	fn min_supported_version(&self) -> u64 { 3 }
+	fn current_version(&self) -> u64 { 3 }
+}
@@ -544,7 +544,7 @@ pub fn verify_old_block(rng: &mut OsRng, header: &Header, engine: &dyn Engine, c
 	if always || rng.gen::<f32>() <= POW_VERIFY_RATE {
 		engine.verify_block_unordered(header)?;
 		match chain.block_header_data(header.parent_hash()) {
-			Some(parent) => engine.verify_block_family(header, &parent.decode()?).map_err(Into::into),
+			Some(parent) => engine.verify_block_family(header, &parent.decode(engine.params().eip1559_transition)?).map_err(Into::into),
 			None => Ok(()),
 		}
 	} else {
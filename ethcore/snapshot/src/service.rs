@@ -24,7 +24,7 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::cmp;
 
-use blockchain::{BlockChain, BlockChainDB, BlockChainDBHandler};
+use blockchain::{BlockChain, BlockChainDB, BlockChainDBHandler, Config as BlockChainConfig};
 use bytes::Bytes;
 use common_types::{
 	io_message::ClientIoMessage,
@@ -124,7 +124,11 @@ impl Restoration {
 
 		let raw_db = params.db;
 
-		let chain = BlockChain::new(Default::default(), params.genesis, raw_db.clone());
+		let chain_config = BlockChainConfig {
+			eip1559_transition: params.engine.params().eip1559_transition,
+			..Default::default()
+		};
+		let chain = BlockChain::new(chain_config, params.genesis, raw_db.clone());
 		let chunker = chunker(params.engine.snapshot_mode())
 			.ok_or_else(|| Error::Snapshot(SnapshotError::SnapshotsUnsupported))?;
 
@@ -369,7 +373,11 @@ impl<C> Service<C> where C: SnapshotClient + ChainInfo {
 		let cur_chain_info = self.client.chain_info();
 
 		let next_db = self.restoration_db_handler.open(&rest_db)?;
-		let next_chain = BlockChain::new(Default::default(), &[], next_db.clone());
+		let chain_config = BlockChainConfig {
+			eip1559_transition: self.engine.params().eip1559_transition,
+			..Default::default()
+		};
+		let next_chain = BlockChain::new(chain_config, &[], next_db.clone());
 		let next_chain_info = next_chain.chain_info();
 
 		// The old database looks like this:
@@ -147,6 +147,7 @@ fn run_constructors<T: Backend>(
 			last_hashes: Default::default(),
 			gas_used: U256::zero(),
 			gas_limit: U256::max_value(),
+			base_fee: None,
 		};
 
 		let from = Address::zero();
@@ -410,6 +411,9 @@ impl Spec {
 			params.eip2046_transition,
 			params.eip2200_advance_transition,
 			params.eip2315_transition,
+			params.eip2929_transition,
+			params.eip2930_transition,
+			params.eip1559_transition,
 			params.dust_protection_transition,
 			params.wasm_activation_transition,
 			params.kip4_transition,
@@ -635,6 +639,7 @@ impl Spec {
 				gas_limit: U256::max_value(),
 				last_hashes: Arc::new(Vec::new()),
 				gas_used: 0.into(),
+				base_fee: None,
 			};
 
 			let from = Address::zero();
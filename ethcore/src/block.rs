@@ -47,7 +47,7 @@ use unexpected::{Mismatch, OutOfBounds};
 use vm::LastHashes;
 
 use hash::keccak;
-use rlp::{RlpStream, Encodable, encode_list};
+use rlp::{RlpStream, encode_list};
 use types::{
 	errors::{EthcoreError as Error, BlockError},
 	transaction::{SignedTransaction, Error as TransactionError},
@@ -251,11 +251,11 @@ impl<'x> OpenBlock<'x> {
 		s.engine.on_close_block(&mut s.block, &s.parent)?;
 		s.block.state.commit()?;
 
-		s.block.header.set_transactions_root(ordered_trie_root(s.block.transactions.iter().map(|e| e.rlp_bytes())));
+		s.block.header.set_transactions_root(ordered_trie_root(s.block.transactions.iter().map(|e| e.envelope())));
 		let uncle_bytes = encode_list(&s.block.uncles);
 		s.block.header.set_uncles_hash(keccak(&uncle_bytes));
 		s.block.header.set_state_root(s.block.state.root().clone());
-		s.block.header.set_receipts_root(ordered_trie_root(s.block.receipts.iter().map(|r| r.rlp_bytes())));
+		s.block.header.set_receipts_root(ordered_trie_root(s.block.receipts.iter().map(|r| r.envelope())));
 		s.block.header.set_log_bloom(s.block.receipts.iter().fold(Bloom::zero(), |mut b, r| {
 			b.accrue_bloom(&r.log_bloom);
 			b
@@ -334,7 +334,7 @@ impl LockedBlock {
 			receipt.outcome = TransactionOutcome::Unknown;
 		}
 		self.block.header.set_receipts_root(
-			ordered_trie_root(self.block.receipts.iter().map(|r| r.rlp_bytes()))
+			ordered_trie_root(self.block.receipts.iter().map(|r| r.envelope()))
 		);
 	}
 
@@ -490,7 +490,7 @@ mod tests {
 		factories: Factories,
 	) -> Result<LockedBlock, Error> {
 
-		let block = Unverified::from_rlp(block_bytes)?;
+		let block = Unverified::from_rlp(block_bytes, engine.params().eip1559_transition)?;
 		let header = block.header;
 		let transactions: Result<Vec<_>, Error> = block
 			.transactions
@@ -541,7 +541,7 @@ mod tests {
 		last_hashes: Arc<LastHashes>,
 		factories: Factories,
 	) -> Result<SealedBlock, Error> {
-		let header = Unverified::from_rlp(block_bytes.clone())?.header;
+		let header = Unverified::from_rlp(block_bytes.clone(), engine.params().eip1559_transition)?.header;
 		Ok(enact_bytes(block_bytes, engine, tracing, db, parent, last_hashes, factories)?
 			.seal(engine, header.seal().to_vec())?)
 	}
@@ -606,7 +606,7 @@ mod tests {
 
 		let bytes = e.rlp_bytes();
 		assert_eq!(bytes, orig_bytes);
-		let uncles = view!(BlockView, &bytes).uncles();
+		let uncles = view!(BlockView, &bytes).uncles(engine.params().eip1559_transition);
 		assert_eq!(uncles[1].extra_data(), b"uncle2");
 
 		let db = e.drain().state.drop().1;
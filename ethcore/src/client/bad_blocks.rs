@@ -21,25 +21,26 @@ use ethereum_types::H256;
 use itertools::Itertools;
 use memory_cache::MemoryLruCache;
 use parking_lot::RwLock;
-use types::verification::Unverified;
+use types::{verification::Unverified, BlockNumber};
 
 /// Recently seen bad blocks.
 pub struct BadBlocks {
 	last_blocks: RwLock<MemoryLruCache<H256, (Unverified, String)>>,
+	eip1559_transition: BlockNumber,
 }
 
-impl Default for BadBlocks {
-	fn default() -> Self {
+impl BadBlocks {
+	/// Create a new cache of bad blocks of a chain with the given EIP-1559 transition.
+	pub fn new(eip1559_transition: BlockNumber) -> Self {
 		BadBlocks {
 			last_blocks: RwLock::new(MemoryLruCache::new(8 * 1024 * 1024)),
+			eip1559_transition,
 		}
 	}
-}
 
-impl BadBlocks {
 	/// Reports given RLP as invalid block.
 	pub fn report(&self, raw: Bytes, message: String) {
-		match Unverified::from_rlp(raw) {
+		match Unverified::from_rlp(raw, self.eip1559_transition) {
 			Ok(unverified) => {
 				error!(
 					target: "client",
@@ -71,11 +72,7 @@ impl BadBlocks {
 		self.last_blocks.read()
 			.backstore()
 			.iter()
-			.map(|(_k, (unverified, message))| (
-				Unverified::from_rlp(unverified.bytes.clone())
-					.expect("Bytes coming from UnverifiedBlock so decodable; qed"),
-				message.clone(),
-			))
+			.map(|(_k, (unverified, message))| (unverified.clone(), message.clone()))
 			.collect()
 	}
 }
@@ -29,7 +29,7 @@ use bytes::ToPretty;
 use ethereum_types::{Address, H256, H264, H512, U256};
 use hash::keccak;
 use hash_db::EMPTY_PREFIX;
-use kvdb::{DBTransaction, DBValue, KeyValueDB};
+use kvdb::{DBTransaction, DBValue, IoStatsKind, KeyValueDB};
 use parking_lot::{Mutex, RwLock};
 use rand::rngs::OsRng;
 use rlp::PayloadInfo;
@@ -100,6 +100,7 @@ use registrar::RegistrarClient;
 use snapshot::{self, SnapshotClient, SnapshotWriter};
 use spec::Spec;
 use state_db::StateDB;
+use stats::{MetricType, PrometheusMetrics, PrometheusRegistry};
 use trace::{self, Database as TraceDatabase, ImportRequest as TraceImportRequest, LocalizedTrace, TraceDB};
 use trie_vm_factories::{Factories, VmFactory};
 use types::{
@@ -143,6 +144,8 @@ const MAX_ANCIENT_BLOCKS_QUEUE_SIZE: usize = 4096;
 const MAX_ANCIENT_BLOCKS_TO_IMPORT: usize = 4;
 const MAX_QUEUE_SIZE_TO_SLEEP_ON: usize = 2;
 const MIN_HISTORY_SIZE: u64 = 8;
+// Key of the consensus engine's persisted data in the node info column.
+const ENGINE_DATA_KEY: &[u8] = b"ENGINE_DATA";
 
 struct SleepState {
 	last_activity: Option<Instant>,
@@ -274,8 +277,8 @@ impl Importer {
 			block_queue,
 			miner,
 			ancient_verifier: AncientVerifier::new(engine.clone()),
+			bad_blocks: bad_blocks::BadBlocks::new(engine.params().eip1559_transition),
 			engine,
-			bad_blocks: Default::default(),
 		})
 	}
 
@@ -640,6 +643,7 @@ impl Importer {
 							last_hashes: client.build_last_hashes(*header.parent_hash()),
 							gas_used: U256::default(),
 							gas_limit: u64::max_value().into(),
+							base_fee: header.base_fee(),
 						};
 
 						let call = move |addr, data| {
@@ -726,12 +730,14 @@ impl Client {
 	/// Create a new client with given parameters.
 	/// The database is assumed to have been initialized with the correct columns.
 	pub fn new(
-		config: ClientConfig,
+		mut config: ClientConfig,
 		spec: &Spec,
 		db: Arc<dyn BlockChainDB>,
 		miner: Arc<Miner>,
 		message_channel: IoChannel<ClientIoMessage<Self>>,
 	) -> Result<Arc<Client>, EthcoreError> {
+		config.blockchain.eip1559_transition = spec.params().eip1559_transition;
+
 		let trie_spec = match config.fat_db {
 			true => TrieSpec::Fat,
 			false => TrieSpec::Secure,
@@ -915,15 +921,16 @@ impl Client {
 	/// The env info as of a given block.
 	/// returns `None` if the block unknown.
 	pub fn env_info(&self, id: BlockId) -> Option<EnvInfo> {
-		self.block_header(id).map(|header| {
+		self.block_header_decoded(id).map(|header| {
 			EnvInfo {
 				number: header.number(),
-				author: header.author(),
+				author: *header.author(),
 				timestamp: header.timestamp(),
-				difficulty: header.difficulty(),
-				last_hashes: self.build_last_hashes(header.parent_hash()),
+				difficulty: *header.difficulty(),
+				last_hashes: self.build_last_hashes(*header.parent_hash()),
 				gas_used: U256::default(),
-				gas_limit: header.gas_limit(),
+				gas_limit: *header.gas_limit(),
+				base_fee: header.base_fee(),
 			}
 		})
 	}
@@ -1299,7 +1306,7 @@ impl Client {
 				=> Some(self.chain.read().best_block_header()),
 			BlockId::Number(number) if number == self.chain.read().best_block_number()
 				=> Some(self.chain.read().best_block_header()),
-			_   => self.block_header(id).and_then(|h| h.decode().ok())
+			_   => self.block_header(id).and_then(|h| h.decode(self.engine.params().eip1559_transition).ok())
 		}
 	}
 }
@@ -1399,6 +1406,40 @@ impl BlockChainReset for Client {
 	}
 }
 
+impl PrometheusMetrics for Client {
+	fn prometheus_metrics(&self, r: &mut PrometheusRegistry) {
+		let report = self.report.read().clone();
+		let chain = self.chain.read();
+		r.register_gauge("chain_block", "Number of the best block", chain.best_block_number());
+		r.register_counter("chain_imported_blocks", "Blocks imported since the node started", report.blocks_imported);
+		r.register_counter("chain_applied_transactions", "Transactions applied since the node started", report.transactions_applied);
+		r.register_counter("chain_processed_gas", "Gas processed since the node started", report.gas_processed);
+		r.register_gauge("chain_cache_bytes", "Memory used by the blockchain cache", chain.cache_size().total());
+		drop(chain);
+
+		let queue = self.importer.block_queue.queue_info();
+		r.register_labeled(MetricType::Gauge, "queue_blocks", "Blocks in the verification queue", "stage", vec![
+			("unverified", queue.unverified_queue_size),
+			("verifying", queue.verifying_queue_size),
+			("verified", queue.verified_queue_size),
+		]);
+		r.register_gauge("queue_mem_bytes", "Memory used by the verification queue", queue.mem_used);
+
+		r.register_gauge("state_db_mem_bytes", "Memory used by the state database", self.state_db.read().mem_used());
+
+		let io_stats = self.db.read().key_value().io_stats(IoStatsKind::Overall);
+		r.register_counter("db_transactions", "Database write transactions", io_stats.transactions);
+		r.register_counter("db_reads", "Database read operations", io_stats.reads);
+		r.register_counter("db_cache_reads", "Database reads served from the cache", io_stats.cache_reads);
+		r.register_counter("db_writes", "Database write operations", io_stats.writes);
+		r.register_counter("db_read_bytes", "Bytes read from the database", io_stats.bytes_read);
+		r.register_counter("db_cache_read_bytes", "Bytes read from the database cache", io_stats.cache_read_bytes);
+		r.register_counter("db_written_bytes", "Bytes written to the database", io_stats.bytes_written);
+
+		self.engine.prometheus_metrics(r);
+	}
+}
+
 impl Nonce for Client {
 	fn nonce(&self, address: &Address, id: BlockId) -> Option<U256> {
 		self.state_at(id).and_then(|s| s.nonce(address).ok())
@@ -1542,6 +1583,7 @@ impl Call for Client {
 			last_hashes: self.build_last_hashes(*header.parent_hash()),
 			gas_used: U256::default(),
 			gas_limit: U256::max_value(),
+			base_fee: header.base_fee(),
 		};
 		let machine = self.engine.machine();
 
@@ -1557,6 +1599,7 @@ impl Call for Client {
 			last_hashes: self.build_last_hashes(*header.parent_hash()),
 			gas_used: U256::default(),
 			gas_limit: U256::max_value(),
+			base_fee: header.base_fee(),
 		};
 
 		let mut results = Vec::with_capacity(transactions.len());
@@ -1584,6 +1627,7 @@ impl Call for Client {
 				last_hashes: self.build_last_hashes(*header.parent_hash()),
 				gas_used: U256::default(),
 				gas_limit: max,
+				base_fee: header.base_fee(),
 			};
 
 			(init, max, env_info)
@@ -1594,7 +1638,7 @@ impl Call for Client {
 
 		let exec = |gas| {
 			let mut tx = t.as_unsigned().clone();
-			tx.gas = gas;
+			tx.tx_mut().gas = gas;
 			let tx = tx.fake_sign(sender);
 
 			let mut clone = state.clone();
@@ -1919,6 +1963,7 @@ impl BlockChainClient for Client {
 		let hash = address.block_hash;
 		let chain = self.chain.read();
 		let number = chain.block_number(&hash)?;
+		let base_fee = chain.block_header_data(&hash)?.base_fee(self.engine.params().eip1559_transition);
 		let body = chain.block_body(&hash)?;
 		let mut receipts = chain.block_receipts(&hash)?.receipts;
 		receipts.truncate(address.index + 1);
@@ -1928,7 +1973,7 @@ impl BlockChainClient for Client {
 		let gas_used = receipts.last().map_or_else(|| 0.into(), |r| r.gas_used);
 		let no_of_logs = receipts.into_iter().map(|receipt| receipt.logs.len()).sum::<usize>();
 
-		let receipt = transaction_receipt(transaction, receipt, gas_used, no_of_logs);
+		let receipt = transaction_receipt(transaction, receipt, gas_used, no_of_logs, base_fee);
 		Some(receipt)
 	}
 
@@ -1938,6 +1983,7 @@ impl BlockChainClient for Client {
 		let chain = self.chain.read();
 		let receipts = chain.block_receipts(&hash)?;
 		let number = chain.block_number(&hash)?;
+		let base_fee = chain.block_header_data(&hash)?.base_fee(self.engine.params().eip1559_transition);
 		let body = chain.block_body(&hash)?;
 
 		let mut gas_used = 0.into();
@@ -1949,7 +1995,7 @@ impl BlockChainClient for Client {
 			.into_iter()
 			.zip(receipts.receipts)
 			.map(move |(transaction, receipt)| {
-				let result = transaction_receipt(transaction, receipt, gas_used, no_of_logs);
+				let result = transaction_receipt(transaction, receipt, gas_used, no_of_logs, base_fee);
 				gas_used = result.cumulative_gas_used;
 				no_of_logs += result.logs.len();
 				result
@@ -2165,6 +2211,10 @@ impl BlockChainClient for Client {
 		self.importer.miner.ready_transactions(self, max_len, PendingOrdering::Priority)
 	}
 
+	fn queued_transaction(&self, hash: &H256) -> Option<Arc<VerifiedTransaction>> {
+		self.importer.miner.transaction(hash)
+	}
+
 	fn signing_chain_id(&self) -> Option<u64> {
 		self.engine.signing_chain_id(&self.latest_env_info())
 	}
@@ -2177,7 +2227,7 @@ impl BlockChainClient for Client {
 	fn uncle_extra_info(&self, id: UncleId) -> Option<BTreeMap<String, String>> {
 		self.uncle(id)
 			.and_then(|h| {
-				h.decode().map(|dh| {
+				h.decode(self.engine.params().eip1559_transition).map(|dh| {
 					self.engine.extra_info(&dh)
 				}).ok()
 			})
@@ -2365,7 +2415,7 @@ impl ReopenBlock for Client {
 			for h in uncles {
 				if !block.uncles.iter().any(|header| header.hash() == h) {
 					let uncle = chain.block_header_data(&h).expect("find_uncle_hashes only returns hashes for existing headers; qed");
-					let uncle = uncle.decode().expect("decoding failure");
+					let uncle = uncle.decode(engine.params().eip1559_transition).expect("decoding failure");
 					block.push_uncle(uncle).expect("pushing up to maximum_uncle_count;
 												push_uncle is not ok only if more than maximum_uncle_count is pushed;
 												so all push_uncle are Ok;
@@ -2407,7 +2457,7 @@ impl PrepareOpenBlock for Client {
 			.iter()
 			.take(engine.maximum_uncle_count(open_block.header.number()))
 			.for_each(|h| {
-				open_block.push_uncle(h.decode().expect("decoding failure")).expect("pushing maximum_uncle_count;
+				open_block.push_uncle(h.decode(self.engine.params().eip1559_transition).expect("decoding failure")).expect("pushing maximum_uncle_count;
 												open_block was just created;
 												push_uncle is not ok only if more than maximum_uncle_count is pushed;
 												so all push_uncle are Ok;
@@ -2564,6 +2614,32 @@ impl client_traits::EngineClient for Client {
 	fn create_pending_block_at(&self, txns: Vec<SignedTransaction>, timestamp: u64, block_number: u64) -> Option<Header> {
 		self.importer.miner.create_pending_block_at(self, txns, timestamp, block_number)
 	}
+
+	fn engine_data(&self) -> Option<Bytes> {
+		match self.db.read().key_value().get(::db::COL_NODE_INFO, ENGINE_DATA_KEY) {
+			Ok(data) => data,
+			Err(e) => {
+				warn!(target: "client", "Failed to read engine data: {}", e);
+				None
+			}
+		}
+	}
+
+	fn set_engine_data(&self, data: Bytes) {
+		let mut batch = DBTransaction::new();
+		batch.put_vec(::db::COL_NODE_INFO, ENGINE_DATA_KEY, data);
+		if let Err(e) = self.db.read().key_value().write(batch) {
+			warn!(target: "client", "Failed to write engine data: {}", e);
+		}
+	}
+
+	fn add_reserved_peer(&self, enode: String) {
+		self.notify(|notify| notify.add_reserved_peer(enode.clone()));
+	}
+
+	fn remove_reserved_peer(&self, enode: String) {
+		self.notify(|notify| notify.remove_reserved_peer(enode.clone()));
+	}
 }
 
 impl ProvingBlockChainClient for Client {
@@ -2734,7 +2810,7 @@ impl ImportExportBlocks for Client {
 		};
 
 		let do_import = |bytes: Vec<u8>| {
-			let block = Unverified::from_rlp(bytes).map_err(|_| "Invalid block rlp")?;
+			let block = Unverified::from_rlp(bytes, self.engine.params().eip1559_transition).map_err(|_| "Invalid block rlp")?;
 			let number = block.header.number();
 			while self.queue_info().is_full() {
 				std::thread::sleep(Duration::from_secs(1));
@@ -2815,6 +2891,7 @@ fn transaction_receipt(
 	receipt: Receipt,
 	prior_gas_used: U256,
 	prior_no_of_logs: usize,
+	base_fee: Option<U256>,
 ) -> LocalizedReceipt {
 	let sender = tx.sender();
 	let transaction_hash = tx.hash();
@@ -2849,6 +2926,8 @@ fn transaction_receipt(
 		}).collect(),
 		log_bloom: receipt.log_bloom,
 		outcome: receipt.outcome,
+		transaction_type: receipt.tx_type,
+		effective_gas_price: tx.effective_gas_price(base_fee),
 	}
 }
 
@@ -3012,10 +3091,11 @@ mod tests {
 			gas_used,
 			log_bloom: Default::default(),
 			logs: logs.clone(),
+			tx_type: Default::default(),
 		};
 
 		// when
-		let receipt = transaction_receipt(transaction, receipt, 5.into(), 1);
+		let receipt = transaction_receipt(transaction, receipt, 5.into(), 1, None);
 
 		// then
 		assert_eq!(receipt, LocalizedReceipt {
@@ -3050,6 +3130,8 @@ mod tests {
 			}],
 			log_bloom: Default::default(),
 			outcome: TransactionOutcome::StateRoot(state_root),
+			transaction_type: Default::default(),
+			effective_gas_price: tx1.gas_price,
 		});
 	}
 
@@ -233,6 +233,26 @@ impl<'a, T: 'a, V: 'a, B: 'a> Ext for TestExt<'a, T, V, B>
 	fn sub_sstore_refund(&mut self, value: usize) {
 		self.ext.sub_sstore_refund(value)
 	}
+
+	fn al_is_enabled(&self) -> bool {
+		self.ext.al_is_enabled()
+	}
+
+	fn al_contains_storage_key(&self, address: &Address, key: &H256) -> bool {
+		self.ext.al_contains_storage_key(address, key)
+	}
+
+	fn al_insert_storage_key(&mut self, address: Address, key: H256) {
+		self.ext.al_insert_storage_key(address, key)
+	}
+
+	fn al_contains_address(&self, address: &Address) -> bool {
+		self.ext.al_contains_address(address)
+	}
+
+	fn al_insert_address(&mut self, address: Address) {
+		self.ext.al_insert_address(address)
+	}
 }
 
 fn do_json_test<H: FnMut(&str, HookType)>(
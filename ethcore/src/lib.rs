@@ -51,6 +51,7 @@ extern crate serde;
 extern crate snapshot;
 extern crate spec;
 extern crate state_db;
+extern crate stats;
 extern crate trace;
 extern crate trie_vm_factories;
 extern crate triehash_ethereum as triehash;
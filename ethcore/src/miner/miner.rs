@@ -782,7 +782,7 @@ impl Miner {
 
 		let parent_header = match chain.block_header(BlockId::Hash(*block.header.parent_hash())) {
 			Some(h) => {
-				match h.decode() {
+				match h.decode(self.engine.params().eip1559_transition) {
 					Ok(decoded_hdr) => decoded_hdr,
 					Err(e) => {
 						error!(target: "miner", "seal_block_internally: Block #{}, Could not decode header from parent block (hash={}): {:?}", block_number, block.header.parent_hash(), e);
@@ -1312,6 +1312,8 @@ impl miner::MinerService for Miner {
 						logs: receipt.logs.clone(),
 						log_bloom: receipt.log_bloom,
 						outcome: receipt.outcome.clone(),
+						transaction_type: receipt.tx_type,
+						effective_gas_price: tx.effective_gas_price(pending.header.base_fee()),
 					}
 				})
 				.collect()
@@ -1450,8 +1452,11 @@ impl miner::MinerService for Miner {
 		}
 
 		// First update gas limit in transaction queue and minimal gas price.
-		let gas_limit = *chain.best_block_header().gas_limit();
+		let best_block_header = chain.best_block_header();
+		let gas_limit = *best_block_header.gas_limit();
 		self.update_transaction_queue_limits(gas_limit);
+		// And the base fee the pending transactions are prioritized by.
+		self.transaction_queue.update_block_base_fee(self.engine.machine().calc_base_fee(&best_block_header));
 
 		// Then import all transactions from retracted blocks.
 		let client = self.pool_client(chain);
@@ -48,6 +48,7 @@ use parking_lot::RwLock;
 use rlp::{self, RlpStream};
 use self::tempfile::TempDir;
 use types::{
+	BlockNumber,
 	chain_notify::ChainMessageType,
 	transaction::{Action, Transaction, SignedTransaction},
 	encoded,
@@ -199,11 +200,11 @@ pub fn generate_dummy_client_with_spec_and_data<F>(
 
 		let b = b.close_and_lock().unwrap().seal(test_engine, vec![]).unwrap();
 
-		if let Err(e) = client.import_block(Unverified::from_rlp(b.rlp_bytes()).unwrap()) {
+		if let Err(e) = client.import_block(Unverified::from_rlp(b.rlp_bytes(), BlockNumber::max_value()).unwrap()) {
 			panic!("error importing block which is valid by definition: {:?}", e);
 		}
 
-		last_header = view!(BlockView, &b.rlp_bytes()).header();
+		last_header = view!(BlockView, &b.rlp_bytes()).header(BlockNumber::max_value());
 		db = b.drain().state.drop().1;
 	}
 	client.flush_queue();
@@ -234,7 +235,7 @@ pub fn push_blocks_to_client(client: &Arc<Client>, timestamp_salt: u64, starting
 		rolling_block_number = rolling_block_number + 1;
 		rolling_timestamp = rolling_timestamp + 10;
 
-		if let Err(e) = client.import_block(Unverified::from_rlp(create_test_block(&header)).unwrap()) {
+		if let Err(e) = client.import_block(Unverified::from_rlp(create_test_block(&header), BlockNumber::max_value()).unwrap()) {
 			panic!("error importing block which is valid by definition: {:?}", e);
 		}
 	}
@@ -254,7 +255,7 @@ pub fn push_block_with_transactions(client: &Arc<Client>, transactions: &[Signed
 	}
 	let b = b.close_and_lock().unwrap().seal(test_engine, vec![]).unwrap();
 
-	if let Err(e) = client.import_block(Unverified::from_rlp(b.rlp_bytes()).unwrap()) {
+	if let Err(e) = client.import_block(Unverified::from_rlp(b.rlp_bytes(), BlockNumber::max_value()).unwrap()) {
 		panic!("error importing block which is valid by definition: {:?}", e);
 	}
 
@@ -275,7 +276,7 @@ pub fn get_test_client_with_blocks(blocks: Vec<Bytes>) -> Arc<Client> {
 	).unwrap();
 
 	for block in blocks {
-		if let Err(e) = client.import_block(Unverified::from_rlp(block).unwrap()) {
+		if let Err(e) = client.import_block(Unverified::from_rlp(block, BlockNumber::max_value()).unwrap()) {
 			panic!("error importing block which is well-formed: {:?}", e);
 		}
 	}
@@ -19,7 +19,7 @@
 use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrder};
 use std::sync::Arc;
-use std::collections::{HashMap, BTreeMap};
+use std::collections::{HashMap, BTreeMap, BTreeSet};
 use blockchain::BlockProvider;
 
 use blockchain::{TreeRoute, BlockReceipts};
@@ -130,6 +130,10 @@ pub struct TestBlockChainClient {
 	pub history: RwLock<Option<u64>>,
 	/// Is disabled
 	pub disabled: AtomicBool,
+	/// Data persisted by the consensus engine.
+	pub engine_data: RwLock<Option<Bytes>>,
+	/// Reserved peers added by the engine.
+	pub reserved_peers: RwLock<BTreeSet<String>>,
 }
 
 /// Used for generating test client blocks.
@@ -199,6 +203,8 @@ impl TestBlockChainClient {
 			history: RwLock::new(None),
 			disabled: AtomicBool::new(false),
 			error_on_logs: RwLock::new(None),
+			engine_data: RwLock::new(None),
+			reserved_peers: RwLock::new(BTreeSet::new()),
 		};
 
 		// insert genesis hash.
@@ -322,7 +328,7 @@ impl TestBlockChainClient {
 		rlp.append(&header);
 		rlp.append_raw(&txs, 1);
 		rlp.append_raw(uncles.as_raw(), 1);
-		let unverified = Unverified::from_rlp(rlp.out()).unwrap();
+		let unverified = Unverified::from_rlp(rlp.out(), BlockNumber::max_value()).unwrap();
 		self.import_block(unverified).unwrap();
 	}
 
@@ -336,7 +342,7 @@ impl TestBlockChainClient {
 	/// Make a bad block by setting invalid parent hash.
 	pub fn corrupt_block_parent(&self, n: BlockNumber) {
 		let hash = self.block_hash(BlockId::Number(n)).unwrap();
-		let mut header: Header = self.block_header(BlockId::Number(n)).unwrap().decode().expect("decoding failed");
+		let mut header: Header = self.block_header(BlockId::Number(n)).unwrap().decode(BlockNumber::max_value()).expect("decoding failed");
 		header.set_parent_hash(H256::from_low_u64_be(42));
 		let mut rlp = RlpStream::new_list(3);
 		rlp.append(&header);
@@ -513,7 +519,7 @@ impl BlockInfo for TestBlockChainClient {
 	fn best_block_header(&self) -> Header {
 		self.block_header(BlockId::Hash(self.chain_info().best_block_hash))
 			.expect("Best block always has header.")
-			.decode()
+			.decode(BlockNumber::max_value())
 			.expect("decoding failed")
 	}
 
@@ -567,7 +573,7 @@ impl ImportBlock for TestBlockChainClient {
 		if number > 0 {
 			match self.blocks.read().get(header.parent_hash()) {
 				Some(parent) => {
-					let parent = view!(BlockView, parent).header();
+					let parent = view!(BlockView, parent).header(BlockNumber::max_value());
 					if parent.number() != (header.number() - 1) {
 						panic!("Unexpected block parent");
 					}
@@ -592,7 +598,7 @@ impl ImportBlock for TestBlockChainClient {
 				while n > 0 && self.numbers.read()[&n] != parent_hash {
 					*self.numbers.write().get_mut(&n).unwrap() = parent_hash.clone();
 					n -= 1;
-					parent_hash = view!(BlockView, &self.blocks.read()[&parent_hash]).header().parent_hash().clone();
+					parent_hash = view!(BlockView, &self.blocks.read()[&parent_hash]).header(BlockNumber::max_value()).parent_hash().clone();
 				}
 			}
 		}
@@ -654,7 +660,7 @@ impl StateClient for TestBlockChainClient {
 
 impl EngineInfo for TestBlockChainClient {
 	fn engine(&self) -> &dyn Engine {
-		unimplemented!()
+		&*self.spec.engine
 	}
 }
 
@@ -797,7 +803,7 @@ impl BlockChainClient for TestBlockChainClient {
 
 	fn block_extra_info(&self, id: BlockId) -> Option<BTreeMap<String, String>> {
 		self.block(id)
-			.map(|block| block.view().header())
+			.map(|block| block.view().header(BlockNumber::max_value()))
 			.map(|header| self.spec.engine.extra_info(&header))
 	}
 
@@ -891,6 +897,10 @@ impl BlockChainClient for TestBlockChainClient {
 		self.miner.ready_transactions(self, 4096, miner::PendingOrdering::Priority)
 	}
 
+	fn queued_transaction(&self, hash: &H256) -> Option<Arc<VerifiedTransaction>> {
+		self.miner.transaction(hash)
+	}
+
 	fn signing_chain_id(&self) -> Option<u64> { None }
 
 	fn mode(&self) -> Mode { Mode::Active }
@@ -1019,4 +1029,20 @@ impl client_traits::EngineClient for TestBlockChainClient {
 	fn create_pending_block_at(&self, txns: Vec<SignedTransaction>, timestamp: u64, block_number: u64) -> Option<Header> {
 		self.miner.create_pending_block_at(self, txns, timestamp, block_number)
 	}
+
+	fn engine_data(&self) -> Option<Bytes> {
+		self.engine_data.read().clone()
+	}
+
+	fn set_engine_data(&self, data: Bytes) {
+		*self.engine_data.write() = Some(data);
+	}
+
+	fn add_reserved_peer(&self, enode: String) {
+		self.reserved_peers.write().insert(enode);
+	}
+
+	fn remove_reserved_peer(&self, enode: String) {
+		self.reserved_peers.write().remove(&enode);
+	}
 }
@@ -32,6 +32,7 @@ use trace::{RewardType, LocalizedTrace};
 use trace::trace::Action::Reward;
 use test_helpers;
 use types::{
+	BlockNumber,
 	ids::BlockId,
 	transaction::{Action, Transaction},
 	trace_filter::Filter as TraceFilter,
@@ -97,11 +98,11 @@ fn can_trace_block_and_uncle_reward() {
 
 	let root_block = root_block.close_and_lock().unwrap().seal(engine, vec![]).unwrap();
 
-	if let Err(e) = client.import_block(Unverified::from_rlp(root_block.rlp_bytes()).unwrap()) {
+	if let Err(e) = client.import_block(Unverified::from_rlp(root_block.rlp_bytes(), BlockNumber::max_value()).unwrap()) {
 		panic!("error importing block which is valid by definition: {:?}", e);
 	}
 
-	last_header = view!(BlockView, &root_block.rlp_bytes()).header();
+	last_header = view!(BlockView, &root_block.rlp_bytes()).header(BlockNumber::max_value());
 	let root_header = last_header.clone();
 	db = root_block.drain().state.drop().1;
 
@@ -125,11 +126,11 @@ fn can_trace_block_and_uncle_reward() {
 
 	let parent_block = parent_block.close_and_lock().unwrap().seal(engine, vec![]).unwrap();
 
-	if let Err(e) = client.import_block(Unverified::from_rlp(parent_block.rlp_bytes()).unwrap()) {
+	if let Err(e) = client.import_block(Unverified::from_rlp(parent_block.rlp_bytes(), BlockNumber::max_value()).unwrap()) {
 		panic!("error importing block which is valid by definition: {:?}", e);
 	}
 
-	last_header = view!(BlockView,&parent_block.rlp_bytes()).header();
+	last_header = view!(BlockView,&parent_block.rlp_bytes()).header(BlockNumber::max_value());
 	db = parent_block.drain().state.drop().1;
 
 	last_hashes.push(last_header.hash());
@@ -172,7 +173,7 @@ fn can_trace_block_and_uncle_reward() {
 
 	let block = block.close_and_lock().unwrap().seal(engine, vec![]).unwrap();
 
-	let res = client.import_block(Unverified::from_rlp(block.rlp_bytes()).unwrap());
+	let res = client.import_block(Unverified::from_rlp(block.rlp_bytes(), BlockNumber::max_value()).unwrap());
 	if res.is_err() {
 		panic!("error importing block: {:#?}", res.err().unwrap());
 	}
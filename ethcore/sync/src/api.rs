@@ -15,7 +15,7 @@
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::{Arc, mpsc, atomic};
-use std::collections::{BTreeSet, HashMap, BTreeMap};
+use std::collections::{BTreeSet, HashMap, HashSet, BTreeMap};
 use std::io;
 use std::ops::RangeInclusive;
 use std::time::Duration;
@@ -27,10 +27,10 @@ use crate::sync_io::NetSyncIo;
 use crate::light_sync::{self, SyncInfo};
 use crate::private_tx::PrivateTxHandler;
 use crate::chain::{
-	fork_filter::ForkFilterApi,
+	fork_filter::{ForkFilterApi, ForkId},
 	sync_packet::SyncPacket::{PrivateTransactionPacket, SignedPrivateTransactionPacket},
 	ChainSyncApi, SyncState, SyncStatus as EthSyncStatus,
-	ETH_PROTOCOL_VERSION_63, ETH_PROTOCOL_VERSION_64,
+	ETH_PROTOCOL_VERSION_63, ETH_PROTOCOL_VERSION_64, ETH_PROTOCOL_VERSION_65, ETH_PROTOCOL_VERSION_66,
 	PAR_PROTOCOL_VERSION_1, PAR_PROTOCOL_VERSION_2, PAR_PROTOCOL_VERSION_3, PAR_PROTOCOL_VERSION_4,
 };
 
@@ -54,10 +54,12 @@ use network::{
 	client_version::ClientVersion,
 	NetworkProtocolHandler, NetworkContext, PeerId, ProtocolId,
 	NetworkConfiguration as BasicNetworkConfiguration, NonReservedPeerMode, Error,
-	ConnectionFilter, IpFilter, NatType
+	ConnectionFilter, EnrFilter, IpFilter, NatType
 };
 use snapshot::SnapshotService;
+use stats::{MetricType, PrometheusRegistry};
 use parking_lot::{RwLock, Mutex};
+use rlp::RlpStream;
 use parity_runtime::Executor;
 use trace_time::trace_time;
 use common_types::{
@@ -129,6 +131,8 @@ pub struct SyncConfig {
 	pub warp_sync: WarpSync,
 	/// Enable light client server.
 	pub serve_light: bool,
+	/// Block number from which headers carry the EIP-1559 base fee.
+	pub eip1559_transition: BlockNumber,
 }
 
 impl Default for SyncConfig {
@@ -142,6 +146,7 @@ impl Default for SyncConfig {
 			fork_block: None,
 			warp_sync: WarpSync::Disabled,
 			serve_light: false,
+			eip1559_transition: BlockNumber::max_value(),
 		}
 	}
 }
@@ -168,6 +173,9 @@ pub trait SyncProvider: Send + Sync {
 
 	/// are we in the middle of a major sync?
 	fn is_major_syncing(&self) -> bool;
+
+	/// Add the sync and network metrics.
+	fn prometheus_metrics(&self, _registry: &mut PrometheusRegistry) {}
 }
 
 /// Transaction stats
@@ -298,7 +306,18 @@ pub struct EthSync {
 	/// Priority tasks notification channel
 	priority_tasks: Mutex<mpsc::Sender<PriorityTask>>,
 	/// Track the sync state: are we importing or verifying blocks?
-	is_major_syncing: Arc<AtomicBool>
+	is_major_syncing: Arc<AtomicBool>,
+	/// Reserved peers added on behalf of the consensus engine, which it may remove again.
+	engine_reserved_peers: Mutex<HashSet<String>>,
+	/// Fork id advertised in the `eth` entry of the node record.
+	advertised_fork_id: Mutex<ForkId>,
+}
+
+/// The `eth` entry of the node record, advertising the fork id as discovery v5 peers filter on it.
+fn eth_enr_entry(fork_id: &ForkId) -> Vec<u8> {
+	let mut eth_entry = RlpStream::new_list(1);
+	eth_entry.append(fork_id);
+	eth_entry.out()
 }
 
 fn light_params(
@@ -351,7 +370,8 @@ impl EthSync {
 			})
 		};
 
-		let fork_filter = ForkFilterApi::new(&*params.chain, params.forks);
+		let mut fork_filter = ForkFilterApi::new(&*params.chain, params.forks);
+		let fork_id = fork_filter.current(&*params.chain);
 
 		let (priority_tasks_tx, priority_tasks_rx) = mpsc::channel();
 		let sync = ChainSyncApi::new(
@@ -385,7 +405,9 @@ impl EthSync {
 				return Err(())
 			}));
 		}
-		let service = NetworkService::new(params.network_config.clone().into_basic()?, connection_filter)?;
+		let mut network_config = params.network_config.clone().into_basic()?;
+		network_config.enr_entries.push(("eth".into(), eth_enr_entry(&fork_id)));
+		let service = NetworkService::new(network_config, connection_filter)?;
 
 		let sync = Arc::new(EthSync {
 			network: service,
@@ -401,7 +423,9 @@ impl EthSync {
 			subprotocol_name: params.config.subprotocol_name,
 			light_subprotocol_name: params.config.light_subprotocol_name,
 			priority_tasks: Mutex::new(priority_tasks_tx),
-			is_major_syncing
+			is_major_syncing,
+			engine_reserved_peers: Mutex::new(HashSet::new()),
+			advertised_fork_id: Mutex::new(fork_id),
 		});
 
 		Ok(sync)
@@ -460,6 +484,28 @@ impl SyncProvider for EthSync {
 	fn is_major_syncing(&self) -> bool {
 		self.is_major_syncing.load(Ordering::SeqCst)
 	}
+
+	fn prometheus_metrics(&self, r: &mut PrometheusRegistry) {
+		let status = self.status();
+		r.register_gauge("sync_peers", "Connected peers", status.num_peers);
+		r.register_gauge("sync_active_peers", "Peers currently used for syncing", status.num_active_peers);
+		if let Some(highest_block_number) = status.highest_block_number {
+			r.register_gauge("sync_highest_block", "Highest block number known from peers", highest_block_number);
+		}
+		r.register_gauge("sync_mem_bytes", "Memory used by the sync", status.mem_used);
+
+		let traffic: BTreeMap<_, _> = self.network.traffic().into_iter()
+			.map(|(protocol, traffic)| (String::from_utf8_lossy(&protocol).into_owned(), traffic))
+			.collect();
+		r.register_labeled(MetricType::Counter, "network_sent_packets", "Packets sent, by protocol", "protocol",
+			traffic.iter().map(|(protocol, traffic)| (protocol, traffic.packets_sent)));
+		r.register_labeled(MetricType::Counter, "network_sent_bytes", "Payload bytes sent, by protocol", "protocol",
+			traffic.iter().map(|(protocol, traffic)| (protocol, traffic.bytes_sent)));
+		r.register_labeled(MetricType::Counter, "network_received_packets", "Packets received, by protocol", "protocol",
+			traffic.iter().map(|(protocol, traffic)| (protocol, traffic.packets_received)));
+		r.register_labeled(MetricType::Counter, "network_received_bytes", "Payload bytes received, by protocol", "protocol",
+			traffic.iter().map(|(protocol, traffic)| (protocol, traffic.bytes_received)));
+	}
 }
 
 const PEERS_TIMER: TimerToken = 0;
@@ -573,6 +619,32 @@ impl NetworkProtocolHandler for SyncProtocolHandler {
 }
 
 impl ChainNotify for EthSync {
+	fn add_reserved_peer(&self, enode: String) {
+		// A peer that is reserved already, e.g. by the operator, is left alone, so the engine
+		// doesn't remove it when it no longer needs the connection.
+		match self.network.is_reserved_peer(&enode) {
+			Ok(false) => {},
+			Ok(true) => return,
+			Err(e) => {
+				warn!(target: "sync", "Failed to add reserved peer {}: {:?}", enode, e);
+				return;
+			},
+		}
+		match ManageNetwork::add_reserved_peer(self, enode.clone()) {
+			Ok(()) => { self.engine_reserved_peers.lock().insert(enode); },
+			Err(e) => warn!(target: "sync", "Failed to add reserved peer {}: {}", enode, e),
+		}
+	}
+
+	fn remove_reserved_peer(&self, enode: String) {
+		if !self.engine_reserved_peers.lock().remove(&enode) {
+			return;
+		}
+		if let Err(e) = ManageNetwork::remove_reserved_peer(self, enode.clone()) {
+			warn!(target: "sync", "Failed to remove reserved peer {}: {}", enode, e);
+		}
+	}
+
 	fn block_pre_import(&self, bytes: &Bytes, hash: &H256, difficulty: &U256) {
 		let task = PriorityTask::PropagateBlock {
 			started: ::std::time::Instant::now(),
@@ -606,6 +678,16 @@ impl ChainNotify for EthSync {
 				&new_blocks.proposed);
 		});
 
+		// The new head may activate a fork, which changes the advertised fork id.
+		{
+			let fork_id = self.eth_handler.sync.write().current_fork_id(&*self.eth_handler.chain);
+			let mut advertised_fork_id = self.advertised_fork_id.lock();
+			if *advertised_fork_id != fork_id {
+				*advertised_fork_id = fork_id;
+				self.network.set_enr_entry("eth", eth_enr_entry(&fork_id));
+			}
+		}
+
 		self.network.with_context(self.light_subprotocol_name, |context| {
 			let light_proto = match self.light_proto.as_ref() {
 				Some(lp) => lp,
@@ -638,8 +720,10 @@ impl ChainNotify for EthSync {
 			},
 			_ => {},
 		}
+		// The fork id may have changed while the network was stopped.
+		self.network.set_enr_entry("eth", eth_enr_entry(&*self.advertised_fork_id.lock()));
 
-		self.network.register_protocol(self.eth_handler.clone(), self.subprotocol_name, &[ETH_PROTOCOL_VERSION_63, ETH_PROTOCOL_VERSION_64])
+		self.network.register_protocol(self.eth_handler.clone(), self.subprotocol_name, &[ETH_PROTOCOL_VERSION_63, ETH_PROTOCOL_VERSION_64, ETH_PROTOCOL_VERSION_65, ETH_PROTOCOL_VERSION_66])
 			.unwrap_or_else(|e| warn!("Error registering ethereum protocol: {:?}", e));
 		// register the warp sync subprotocol
 		self.network.register_protocol(self.eth_handler.clone(), WARP_SYNC_PROTOCOL_ID, &[PAR_PROTOCOL_VERSION_1, PAR_PROTOCOL_VERSION_2, PAR_PROTOCOL_VERSION_3, PAR_PROTOCOL_VERSION_4])
@@ -729,7 +813,7 @@ struct TxRelay(Arc<dyn BlockChainClient>);
 impl LightHandler for TxRelay {
 	fn on_transactions(&self, ctx: &dyn EventContext, relay: &[UnverifiedTransaction]) {
 		trace!(target: "pip", "Relaying {} transactions from peer {}", relay.len(), ctx.peer());
-		self.0.queue_transactions(relay.iter().map(|tx| rlp::encode(tx)).collect(), ctx.peer())
+		self.0.queue_transactions(relay.iter().map(|tx| tx.envelope()).collect(), ctx.peer())
 	}
 }
 
@@ -819,6 +903,14 @@ pub struct NetworkConfiguration {
 	pub nat_type: NatType,
 	/// Enable discovery
 	pub discovery_enabled: bool,
+	/// Enable discovery v4 when discovery is enabled
+	pub discovery_v4_enabled: bool,
+	/// Enable discovery v5
+	pub discovery_v5_enabled: bool,
+	/// Port for discovery v5, shares the discovery socket by default
+	pub discovery_v5_port: Option<u16>,
+	/// Only nodes with a matching record found through discovery v5 are added to the node table
+	pub discovery_v5_filter: Option<EnrFilter>,
 	/// List of initial node addresses
 	pub boot_nodes: Vec<String>,
 	/// Use provided node key instead of default
@@ -863,6 +955,11 @@ impl NetworkConfiguration {
 			nat_enabled: self.nat_enabled,
 			nat_type: self.nat_type,
 			discovery_enabled: self.discovery_enabled,
+			discovery_v4_enabled: self.discovery_v4_enabled,
+			discovery_v5_enabled: self.discovery_v5_enabled,
+			discovery_v5_port: self.discovery_v5_port,
+			discovery_v5_filter: self.discovery_v5_filter,
+			enr_entries: Vec::new(),
 			boot_nodes: self.boot_nodes,
 			use_secret: self.use_secret,
 			max_peers: self.max_peers,
@@ -892,6 +989,10 @@ impl From<BasicNetworkConfiguration> for NetworkConfiguration {
 			nat_enabled: other.nat_enabled,
 			nat_type: other.nat_type,
 			discovery_enabled: other.discovery_enabled,
+			discovery_v4_enabled: other.discovery_v4_enabled,
+			discovery_v5_enabled: other.discovery_v5_enabled,
+			discovery_v5_port: other.discovery_v5_port,
+			discovery_v5_filter: other.discovery_v5_filter,
 			boot_nodes: other.boot_nodes,
 			use_secret: other.use_secret,
 			max_peers: other.max_peers,
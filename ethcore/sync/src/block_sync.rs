@@ -37,6 +37,7 @@ use common_types::{
 	block_status::BlockStatus,
 	ids::BlockId,
 	errors::{EthcoreError, BlockError, ImportError},
+	transaction::envelope_of,
 };
 
 const MAX_HEADERS_TO_REQUEST: usize = 128;
@@ -149,12 +150,14 @@ pub struct BlockDownloader {
 	retract_step: u64,
 	/// consecutive useless headers this round
 	useless_headers_count: usize,
+	/// Block number from which headers carry the EIP-1559 base fee.
+	eip1559_transition: BlockNumber,
 }
 
 impl BlockDownloader {
 	/// Create a new instance of syncing strategy.
 	/// For BlockSet::NewBlocks this won't reorganize to before the last kept state.
-	pub fn new(block_set: BlockSet, start_hash: &H256, start_number: BlockNumber) -> Self {
+	pub fn new(block_set: BlockSet, start_hash: &H256, start_number: BlockNumber, eip1559_transition: BlockNumber) -> Self {
 		let sync_receipts = match block_set {
 			BlockSet::NewBlocks => false,
 			BlockSet::OldBlocks => true
@@ -174,6 +177,7 @@ impl BlockDownloader {
 			target_hash: None,
 			retract_step: 1,
 			useless_headers_count: 0,
+			eip1559_transition,
 		}
 	}
 
@@ -263,7 +267,7 @@ impl BlockDownloader {
 		let mut hashes = Vec::new();
 		let mut last_header = None;
 		for i in 0..item_count {
-			let info = SyncHeader::from_rlp(r.at(i)?.as_raw().to_vec())?;
+			let info = SyncHeader::from_rlp(r.at(i)?.as_raw().to_vec(), self.eip1559_transition)?;
 			let number = BlockNumber::from(info.header.number());
 			let hash = info.header.hash();
 
@@ -374,7 +378,7 @@ impl BlockDownloader {
 		} else {
 			let mut bodies = Vec::with_capacity(item_count);
 			for i in 0..item_count {
-				let body = SyncBody::from_rlp(r.at(i)?.as_raw())?;
+				let body = SyncBody::from_rlp(r.at(i)?.as_raw(), self.eip1559_transition)?;
 				bodies.push(body);
 			}
 
@@ -643,7 +647,8 @@ fn all_expected<A, B, F>(values: &[A], expected_values: &[B], is_expected: F) ->
 mod tests {
 	use super::{
 		BlockSet, BlockDownloader, BlockDownloaderImportError, DownloadAction, SyncIo, H256,
-		MAX_HEADERS_TO_REQUEST, MAX_USELESS_HEADERS_PER_ROUND, SUBCHAIN_SIZE, State, Rlp, VecDeque
+		MAX_HEADERS_TO_REQUEST, MAX_USELESS_HEADERS_PER_ROUND, SUBCHAIN_SIZE, State, Rlp, VecDeque,
+		BlockNumber,
 	};
 
 	use crate::tests::{helpers::TestIo, snapshot::TestSnapshotService};
@@ -696,7 +701,7 @@ mod tests {
 		let spec = spec::new_test();
 		let genesis_hash = spec.genesis_header().hash();
 
-		let mut downloader = BlockDownloader::new(BlockSet::NewBlocks, &genesis_hash, 0);
+		let mut downloader = BlockDownloader::new(BlockSet::NewBlocks, &genesis_hash, 0, BlockNumber::max_value());
 		downloader.state = State::ChainHead;
 
 		let mut chain = TestBlockChainClient::new();
@@ -778,7 +783,7 @@ mod tests {
 		let parent_hash = headers[1].hash();
 		headers.push(dummy_header(129, parent_hash));
 
-		let mut downloader = BlockDownloader::new(BlockSet::NewBlocks, &H256::random(), 0);
+		let mut downloader = BlockDownloader::new(BlockSet::NewBlocks, &H256::random(), 0, BlockNumber::max_value());
 		downloader.state = State::Blocks;
 		downloader.blocks.reset_to(vec![headers[0].hash()]);
 
@@ -833,7 +838,7 @@ mod tests {
 			};
 
 			let txs = encode_list(&[dummy_signed_tx()]);
-			let tx_root = ordered_trie_root(Rlp::new(&txs).iter().map(|r| r.as_raw()));
+			let tx_root = ordered_trie_root(Rlp::new(&txs).iter().map(|r| envelope_of(&r)));
 
 			let mut rlp = RlpStream::new_list(2);
 			rlp.append_raw(&txs, 1);
@@ -848,7 +853,7 @@ mod tests {
 			headers.push(header);
 		}
 
-		let mut downloader = BlockDownloader::new(BlockSet::NewBlocks, &headers[0].hash(), 0);
+		let mut downloader = BlockDownloader::new(BlockSet::NewBlocks, &headers[0].hash(), 0, BlockNumber::max_value());
 		downloader.state = State::Blocks;
 		downloader.blocks.reset_to(vec![headers[0].hash()]);
 
@@ -902,7 +907,7 @@ mod tests {
 			} else {
 				encode_list(&[i as u32])
 			};
-			let receipts_root = ordered_trie_root(Rlp::new(&receipts_rlp).iter().map(|r| r.as_raw()));
+			let receipts_root = ordered_trie_root(Rlp::new(&receipts_rlp).iter().map(|r| envelope_of(&r)));
 			receipts.push(receipts_rlp);
 
 			// Construct the block header.
@@ -912,7 +917,7 @@ mod tests {
 			headers.push(header);
 		}
 
-		let mut downloader = BlockDownloader::new(BlockSet::OldBlocks, &headers[0].hash(), 0);
+		let mut downloader = BlockDownloader::new(BlockSet::OldBlocks, &headers[0].hash(), 0, BlockNumber::max_value());
 		downloader.state = State::Blocks;
 		downloader.blocks.reset_to(vec![headers[0].hash()]);
 
@@ -945,7 +950,7 @@ mod tests {
 		let spec = spec::new_test();
 		let genesis_hash = spec.genesis_header().hash();
 
-		let mut downloader = BlockDownloader::new(BlockSet::NewBlocks, &genesis_hash, 0);
+		let mut downloader = BlockDownloader::new(BlockSet::NewBlocks, &genesis_hash, 0, BlockNumber::max_value());
 		downloader.state = State::ChainHead;
 
 		let mut chain = TestBlockChainClient::new();
@@ -985,7 +990,7 @@ mod tests {
 		let spec = spec::new_test();
 		let genesis_hash = spec.genesis_header().hash();
 
-		let mut downloader = BlockDownloader::new(BlockSet::NewBlocks, &genesis_hash, 0);
+		let mut downloader = BlockDownloader::new(BlockSet::NewBlocks, &genesis_hash, 0, BlockNumber::max_value());
 		downloader.state = State::ChainHead;
 
 		let mut chain = TestBlockChainClient::new();
@@ -24,9 +24,10 @@ use parity_util_mem::MallocSizeOf;
 use rlp::{Rlp, RlpStream, DecoderError};
 use triehash_ethereum::ordered_trie_root;
 use common_types::{
-	transaction::UnverifiedTransaction,
+	transaction::{UnverifiedTransaction, envelope_of},
 	header::Header as BlockHeader,
 	verification::Unverified,
+	BlockNumber,
 };
 
 malloc_size_of_is_0!(HeaderId);
@@ -39,9 +40,9 @@ pub struct SyncHeader {
 }
 
 impl SyncHeader {
-	pub fn from_rlp(bytes: Bytes) -> Result<Self, DecoderError> {
+	pub fn from_rlp(bytes: Bytes, eip1559_transition: BlockNumber) -> Result<Self, DecoderError> {
 		let result = SyncHeader {
-			header: rlp::decode(&bytes)?,
+			header: BlockHeader::decode_rlp(&Rlp::new(&bytes), eip1559_transition)?,
 			bytes,
 		};
 
@@ -58,7 +59,7 @@ pub struct SyncBody {
 }
 
 impl SyncBody {
-	pub fn from_rlp(bytes: &[u8]) -> Result<Self, DecoderError> {
+	pub fn from_rlp(bytes: &[u8], eip1559_transition: BlockNumber) -> Result<Self, DecoderError> {
 		let rlp = Rlp::new(bytes);
 		let transactions_rlp = rlp.at(0)?;
 		let uncles_rlp = rlp.at(1)?;
@@ -67,7 +68,7 @@ impl SyncBody {
 			transactions_bytes: transactions_rlp.as_raw().to_vec(),
 			transactions: transactions_rlp.as_list()?,
 			uncles_bytes: uncles_rlp.as_raw().to_vec(),
-			uncles: uncles_rlp.as_list()?,
+			uncles: BlockHeader::decode_rlp_list(&uncles_rlp, eip1559_transition)?,
 		};
 
 		Ok(result)
@@ -384,7 +385,7 @@ impl BlockCollection {
 
 	fn insert_body(&mut self, body: SyncBody) -> Result<H256, network::Error> {
 		let header_id = {
-			let tx_root = ordered_trie_root(Rlp::new(&body.transactions_bytes).iter().map(|r| r.as_raw()));
+			let tx_root = ordered_trie_root(Rlp::new(&body.transactions_bytes).iter().map(|r| envelope_of(&r)));
 			let uncles = keccak(&body.uncles_bytes);
 			HeaderId {
 				transactions_root: tx_root,
@@ -417,7 +418,7 @@ impl BlockCollection {
 	fn insert_receipt(&mut self, r: &[u8]) -> Result<Vec<H256>, network::Error> {
 		let receipt_root = {
 			let receipts = Rlp::new(&r);
-			ordered_trie_root(receipts.iter().map(|r| r.as_raw()))
+			ordered_trie_root(receipts.iter().map(|r| envelope_of(&r)))
 		};
 		self.downloading_receipts.remove(&receipt_root);
 		match self.receipt_ids.entry(receipt_root) {
@@ -580,7 +581,7 @@ mod test {
 		let blocks: Vec<_> = (0..nblocks)
 			.map(|i| (&client as &dyn BlockChainClient).block(BlockId::Number(i as BlockNumber)).unwrap().into_inner())
 			.collect();
-		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec()).unwrap()).collect();
+		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec(), BlockNumber::max_value()).unwrap()).collect();
 		let hashes: Vec<_> = headers.iter().map(|h| h.header.hash()).collect();
 		let heads: Vec<_> = hashes.iter().enumerate().filter_map(|(i, h)| if i % 20 == 0 { Some(*h) } else { None }).collect();
 		bc.reset_to(heads);
@@ -608,7 +609,7 @@ mod test {
 
 		assert_eq!(
 			bc.drain().into_iter().map(|b| b.block).collect::<Vec<_>>(),
-			blocks[0..6].iter().map(|b| Unverified::from_rlp(b.to_vec()).unwrap()).collect::<Vec<_>>()
+			blocks[0..6].iter().map(|b| Unverified::from_rlp(b.to_vec(), BlockNumber::max_value()).unwrap()).collect::<Vec<_>>()
 		);
 		assert!(!bc.contains(&hashes[0]));
 		assert_eq!(hashes[5], bc.head.unwrap());
@@ -622,7 +623,7 @@ mod test {
 		bc.insert_headers(headers[5..10].into_iter().map(Clone::clone).collect());
 		assert_eq!(
 			bc.drain().into_iter().map(|b| b.block).collect::<Vec<_>>(),
-			blocks[6..16].iter().map(|b| Unverified::from_rlp(b.to_vec()).unwrap()).collect::<Vec<_>>()
+			blocks[6..16].iter().map(|b| Unverified::from_rlp(b.to_vec(), BlockNumber::max_value()).unwrap()).collect::<Vec<_>>()
 		);
 
 		assert_eq!(hashes[15], bc.heads[0]);
@@ -642,7 +643,7 @@ mod test {
 		let blocks: Vec<_> = (0..nblocks)
 			.map(|i| (&client as &dyn BlockChainClient).block(BlockId::Number(i as BlockNumber)).unwrap().into_inner())
 			.collect();
-		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec()).unwrap()).collect();
+		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec(), BlockNumber::max_value()).unwrap()).collect();
 		let hashes: Vec<_> = headers.iter().map(|h| h.header.hash()).collect();
 		let heads: Vec<_> = hashes.iter().enumerate().filter_map(|(i, h)| if i % 20 == 0 { Some(*h) } else { None }).collect();
 		bc.reset_to(heads);
@@ -666,7 +667,7 @@ mod test {
 		let blocks: Vec<_> = (0..nblocks)
 			.map(|i| (&client as &dyn BlockChainClient).block(BlockId::Number(i as BlockNumber)).unwrap().into_inner())
 			.collect();
-		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec()).unwrap()).collect();
+		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec(), BlockNumber::max_value()).unwrap()).collect();
 		let hashes: Vec<_> = headers.iter().map(|h| h.header.hash()).collect();
 		let heads: Vec<_> = hashes.iter().enumerate().filter_map(|(i, h)| if i % 20 == 0 { Some(*h) } else { None }).collect();
 		bc.reset_to(heads);
@@ -24,15 +24,18 @@ use crate::{
 	block_sync::{BlockDownloaderImportError as DownloaderImportError, DownloadAction},
 	chain::{
 		sync_packet::{
+			split_request_id,
 			PacketInfo,
 			SyncPacket::{
 				self, BlockBodiesPacket, BlockHeadersPacket, NewBlockHashesPacket, NewBlockPacket,
+				NewPooledTransactionHashesPacket, PooledTransactionsPacket,
 				PrivateStatePacket, PrivateTransactionPacket, ReceiptsPacket, SignedPrivateTransactionPacket,
 				SnapshotDataPacket, SnapshotManifestPacket, StatusPacket,
 			}
 		},
 		BlockSet, ChainSync, ForkConfirmation, PacketDecodeError, PeerAsking, PeerInfo, SyncRequester,
-		SyncState, ETH_PROTOCOL_VERSION_63, ETH_PROTOCOL_VERSION_64, MAX_NEW_BLOCK_AGE, MAX_NEW_HASHES,
+		SyncState, ETH_PROTOCOL_VERSION_63, ETH_PROTOCOL_VERSION_64, ETH_PROTOCOL_VERSION_66, MAX_NEW_BLOCK_AGE,
+		MAX_NEW_HASHES, MAX_UNFETCHED_POOLED_TRANSACTIONS,
 		PAR_PROTOCOL_VERSION_1, PAR_PROTOCOL_VERSION_3, PAR_PROTOCOL_VERSION_4,
 	}
 };
@@ -52,6 +55,7 @@ use common_types::{
 	errors::{EthcoreError, ImportError, BlockError},
 	verification::Unverified,
 	snapshot::{ManifestData, RestorationStatus},
+	transaction::envelope_of,
 };
 
 
@@ -61,8 +65,28 @@ pub struct SyncHandler;
 impl SyncHandler {
 	/// Handle incoming packet from peer
 	pub fn on_packet(sync: &mut ChainSync, io: &mut dyn SyncIo, peer: PeerId, packet_id: u8, data: &[u8]) {
-		let rlp = Rlp::new(data);
+		let mut rlp = Rlp::new(data);
 		if let Some(packet_id) = SyncPacket::from_u8(packet_id) {
+			// Since eth/66 the responses carry the id of the request they answer.
+			if packet_id.has_request_id() {
+				if let Some(peer_info) = sync.peers.get(&peer) {
+					if peer_info.eth_protocol_version >= ETH_PROTOCOL_VERSION_66.0 {
+						let (request_id, response) = match split_request_id(&rlp) {
+							Ok(split) => split,
+							Err(e) => {
+								debug!(target:"sync", "{} -> Malformed packet {} : {}", peer, packet_id.id(), e);
+								return;
+							}
+						};
+						if peer_info.asking_request_id != Some(request_id) {
+							trace!(target: "sync", "{}: Ignored response with unexpected request id {}", peer, request_id);
+							return;
+						}
+						rlp = response;
+					}
+				}
+			}
+
 			let result = match packet_id {
 				StatusPacket => SyncHandler::on_peer_status(sync, io, peer, &rlp),
 				BlockHeadersPacket => SyncHandler::on_peer_block_headers(sync, io, peer, &rlp),
@@ -70,6 +94,8 @@ impl SyncHandler {
 				ReceiptsPacket => SyncHandler::on_peer_block_receipts(sync, io, peer, &rlp),
 				NewBlockPacket => SyncHandler::on_peer_new_block(sync, io, peer, &rlp),
 				NewBlockHashesPacket => SyncHandler::on_peer_new_hashes(sync, io, peer, &rlp),
+				NewPooledTransactionHashesPacket => SyncHandler::on_peer_new_pooled_transaction_hashes(sync, io, peer, &rlp),
+				PooledTransactionsPacket => SyncHandler::on_peer_pooled_transactions(sync, io, peer, &rlp),
 				SnapshotManifestPacket => SyncHandler::on_snapshot_manifest(sync, io, peer, &rlp),
 				SnapshotDataPacket => SyncHandler::on_snapshot_data(sync, io, peer, &rlp),
 				PrivateTransactionPacket => SyncHandler::on_private_transaction(sync, io, peer, &rlp),
@@ -115,6 +141,7 @@ impl SyncHandler {
 		if sync.peers.contains_key(&peer_id) {
 			debug!(target: "sync", "Disconnected {}", peer_id);
 			sync.clear_peer_download(peer_id);
+			sync.requeue_pooled_transactions(peer_id);
 			sync.peers.remove(&peer_id);
 			sync.active_peers.remove(&peer_id);
 
@@ -157,7 +184,7 @@ impl SyncHandler {
 				peer.difficulty = Some(difficulty);
 			}
 		}
-		let block = Unverified::from_rlp(r.at(0)?.as_raw().to_vec())?;
+		let block = Unverified::from_rlp(r.at(0)?.as_raw().to_vec(), sync.eip1559_transition)?;
 		let hash = block.header.hash();
 		let number = block.header.number();
 		trace!(target: "sync", "{} -> NewBlock ({})", peer_id, hash);
@@ -607,6 +634,10 @@ impl SyncHandler {
 			block_set: None,
 			private_tx_enabled,
 			client_version: ClientVersion::from(io.peer_version(peer_id)),
+			eth_protocol_version,
+			asking_pooled_transactions: Vec::new(),
+			asking_request_id: None,
+			unfetched_pooled_transactions: Default::default(),
 		};
 
 		trace!(target: "sync", "New peer {} (\
@@ -653,7 +684,7 @@ impl SyncHandler {
 
 		if false
 			|| (warp_protocol && (peer.protocol_version < PAR_PROTOCOL_VERSION_1.0 || peer.protocol_version > PAR_PROTOCOL_VERSION_4.0))
-			|| (!warp_protocol && (peer.protocol_version < ETH_PROTOCOL_VERSION_63.0 || peer.protocol_version > ETH_PROTOCOL_VERSION_64.0))
+			|| (!warp_protocol && (peer.protocol_version < ETH_PROTOCOL_VERSION_63.0 || peer.protocol_version > ETH_PROTOCOL_VERSION_66.0))
 		{
 			trace!(target: "sync", "Peer {} unsupported eth protocol ({})", peer_id, peer.protocol_version);
 			return Err(DownloaderImportError::Invalid);
@@ -693,10 +724,80 @@ impl SyncHandler {
 		let mut transactions = Vec::with_capacity(item_count);
 		for i in 0 .. item_count {
 			let rlp = tx_rlp.at(i)?;
-			let tx = rlp.as_raw().to_vec();
+			let tx = envelope_of(&rlp).to_vec();
+			transactions.push(tx);
+		}
+		io.chain().queue_transactions(transactions, peer_id);
+		Ok(())
+	}
+
+	/// Called when peer announces transactions by their hashes (eth/65)
+	fn on_peer_new_pooled_transaction_hashes(sync: &mut ChainSync, io: &mut dyn SyncIo, peer_id: PeerId, r: &Rlp) -> Result<(), DownloaderImportError> {
+		// Accept transactions only when fully synced
+		if !io.is_chain_queue_empty() || (sync.state != SyncState::Idle && sync.state != SyncState::NewBlocks) {
+			trace!(target: "sync", "{} Ignoring transaction hashes while syncing", peer_id);
+			return Ok(());
+		}
+		let peer = match sync.peers.get_mut(&peer_id) {
+			Some(peer) if peer.can_sync() => peer,
+			_ => {
+				trace!(target: "sync", "{} Ignoring transaction hashes from unconfirmed/unknown peer", peer_id);
+				return Ok(());
+			}
+		};
+
+		let item_count = r.item_count()?;
+		trace!(target: "sync", "{:02} -> NewPooledTransactionHashes ({} entries)", peer_id, item_count);
+		for hash in r.iter() {
+			let hash: H256 = hash.as_val()?;
+			// The peer knows the transaction, so there is no need to propagate it back.
+			peer.last_sent_transactions.insert(hash);
+			if peer.unfetched_pooled_transactions.len() < MAX_UNFETCHED_POOLED_TRANSACTIONS
+				&& io.chain().queued_transaction(&hash).is_none()
+			{
+				peer.unfetched_pooled_transactions.insert(hash);
+			}
+		}
+		sync.request_pooled_transactions(io, peer_id);
+		Ok(())
+	}
+
+	/// Called when peer sends us the transactions we requested by their hashes (eth/65)
+	fn on_peer_pooled_transactions(sync: &mut ChainSync, io: &mut dyn SyncIo, peer_id: PeerId, r: &Rlp) -> Result<(), DownloaderImportError> {
+		let allowed = sync.peers.get(&peer_id).map(|p| p.is_allowed()).unwrap_or(false);
+		if !sync.reset_peer_asking(peer_id, PeerAsking::PooledTransactions) || !allowed {
+			trace!(target: "sync", "{}: Ignored unexpected pooled transactions", peer_id);
+			return Ok(());
+		}
+		let peer = match sync.peers.get_mut(&peer_id) {
+			Some(peer) => peer,
+			None => {
+				trace!(target: "sync", "{}: Ignored unexpected pooled transactions (peer not found)", peer_id);
+				return Ok(());
+			}
+		};
+		let expected_hashes = mem::replace(&mut peer.asking_pooled_transactions, Vec::new());
+
+		let item_count = r.item_count()?;
+		trace!(target: "sync", "{:02} -> PooledTransactions ({} entries)", peer_id, item_count);
+		if item_count > expected_hashes.len() {
+			trace!(target: "sync", "{}: Peer sent more transactions than requested", peer_id);
+			return Err(DownloaderImportError::Invalid);
+		}
+		let mut transactions = Vec::with_capacity(item_count);
+		for i in 0 .. item_count {
+			let rlp = r.at(i)?;
+			let tx = envelope_of(&rlp).to_vec();
+			let hash = keccak(&tx);
+			if !expected_hashes.contains(&hash) {
+				trace!(target: "sync", "{}: Peer sent unrequested transaction {}", peer_id, hash);
+				return Err(DownloaderImportError::Invalid);
+			}
+			peer.last_sent_transactions.insert(hash);
 			transactions.push(tx);
 		}
 		io.chain().queue_transactions(transactions, peer_id);
+		sync.request_pooled_transactions(io, peer_id);
 		Ok(())
 	}
 
@@ -814,7 +915,11 @@ mod tests {
 	use std::collections::VecDeque;
 
 	use super::{
-		super::tests::{dummy_sync_with_peer, get_dummy_block, get_dummy_blocks, get_dummy_hashes},
+		super::{
+			tests::{dummy_sync_with_peer, get_dummy_block, get_dummy_blocks, get_dummy_hashes, insert_dummy_peer},
+			sync_packet::{PacketInfo, SyncPacket::GetPooledTransactionsPacket},
+			PeerAsking,
+		},
 		SyncHandler
 	};
 
@@ -822,8 +927,9 @@ mod tests {
 
 	use client_traits::ChainInfo;
 	use ethcore::test_helpers::{EachBlockWith, TestBlockChainClient};
+	use ethereum_types::H256;
 	use parking_lot::RwLock;
-	use rlp::Rlp;
+	use rlp::{Rlp, RlpStream};
 
 	#[test]
 	fn handles_peer_new_hashes() {
@@ -912,4 +1018,58 @@ mod tests {
 
 		assert!(result.is_ok());
 	}
+
+	#[test]
+	fn requests_announced_pooled_transactions() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Uncle);
+		let queue = RwLock::new(VecDeque::new());
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None, None);
+
+		let hashes = vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)];
+		let mut rlp = RlpStream::new();
+		rlp.append_list(&hashes);
+		let data = rlp.out();
+
+		SyncHandler::on_peer_new_pooled_transaction_hashes(&mut sync, &mut io, 0, &Rlp::new(&data))
+			.expect("result to be ok");
+
+		assert_eq!(io.packets.len(), 1);
+		assert_eq!(io.packets[0].packet_id, GetPooledTransactionsPacket.id());
+		assert_eq!(io.packets[0].recipient, 0);
+		let peer = &sync.peers[&0];
+		assert_eq!(peer.asking, PeerAsking::PooledTransactions);
+		assert_eq!(peer.asking_pooled_transactions.len(), 2);
+		assert!(peer.unfetched_pooled_transactions.is_empty());
+	}
+
+	#[test]
+	fn requeues_pooled_transactions_of_aborting_peer() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Uncle);
+		let queue = RwLock::new(VecDeque::new());
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		insert_dummy_peer(&mut sync, 1, client.block_hash_delta_minus(5));
+		insert_dummy_peer(&mut sync, 2, client.block_hash_delta_minus(5));
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None, None);
+
+		let known = H256::from_low_u64_be(1);
+		let unknown = H256::from_low_u64_be(2);
+		{
+			let peer = sync.peers.get_mut(&0).unwrap();
+			peer.asking = PeerAsking::PooledTransactions;
+			peer.asking_pooled_transactions = vec![known, unknown];
+		}
+		sync.peers.get_mut(&1).unwrap().last_sent_transactions.insert(known);
+
+		SyncHandler::on_peer_aborting(&mut sync, &mut io, 0);
+
+		assert!(!sync.peers.contains_key(&0));
+		assert!(sync.peers[&1].unfetched_pooled_transactions.contains(&known));
+		assert!(!sync.peers[&1].unfetched_pooled_transactions.contains(&unknown));
+		assert!(sync.peers[&2].unfetched_pooled_transactions.is_empty());
+	}
 }
@@ -16,7 +16,7 @@
 
 //! `BlockChain` synchronization strategy.
 //! Syncs to peers and keeps up to date.
-//! This implementation uses ethereum protocol v63
+//! This implementation uses ethereum protocols v63 to v66
 //!
 //! Syncing strategy summary.
 //! Split the chain into ranges of N blocks each. Download ranges sequentially. Split each range into subchains of M blocks. Download subchains in parallel.
@@ -98,13 +98,14 @@ pub mod sync_packet;
 use std::sync::{Arc, mpsc};
 use std::collections::{HashSet, HashMap, BTreeMap};
 use std::cmp;
+use std::mem;
 use std::time::{Duration, Instant};
 
 use crate::{
 	ETH_PROTOCOL, EthProtocolInfo as PeerInfoDigest, PriorityTask, SyncConfig, WarpSync, WARP_SYNC_PROTOCOL_ID,
 	api::{Notification, PRIORITY_TIMER_INTERVAL},
 	block_sync::{BlockDownloader, DownloadAction},
-	chain::fork_filter::ForkFilterApi,
+	chain::fork_filter::{ForkFilterApi, ForkId},
 	sync_io::SyncIo,
 	snapshot_sync::Snapshot,
 	transactions_stats::{TransactionsStats, Stats as TransactionStats},
@@ -135,7 +136,7 @@ use common_types::{
 };
 
 use self::handler::SyncHandler;
-use self::sync_packet::{PacketInfo, SyncPacket};
+use self::sync_packet::{PacketInfo, RequestId, SyncPacket};
 use self::sync_packet::SyncPacket::{
 	NewBlockPacket,
 	StatusPacket,
@@ -149,6 +150,10 @@ malloc_size_of_is_0!(PeerInfo);
 
 pub type PacketDecodeError = DecoderError;
 
+/// Version 66 of the Ethereum protocol and number of packet IDs reserved by the protocol (packet count).
+pub const ETH_PROTOCOL_VERSION_66: (u8, u8) = (66, 0x11);
+/// Version 65 of the Ethereum protocol and number of packet IDs reserved by the protocol (packet count).
+pub const ETH_PROTOCOL_VERSION_65: (u8, u8) = (65, 0x11);
 /// Version 64 of the Ethereum protocol and number of packet IDs reserved by the protocol (packet count).
 pub const ETH_PROTOCOL_VERSION_64: (u8, u8) = (64, 0x11);
 /// Version 63 of the Ethereum protocol and number of packet IDs reserved by the protocol (packet count).
@@ -176,6 +181,10 @@ const MAX_PEERS_PROPAGATION: usize = 128;
 const MAX_PEER_LAG_PROPAGATION: BlockNumber = 20;
 const MAX_NEW_HASHES: usize = 64;
 const MAX_NEW_BLOCK_AGE: BlockNumber = 20;
+/// Maximum number of transactions requested from a peer in a single GetPooledTransactions request.
+const MAX_TRANSACTIONS_TO_REQUEST: usize = 256;
+/// Maximum number of transactions announced by a peer which are kept until they are requested.
+const MAX_UNFETCHED_POOLED_TRANSACTIONS: usize = 4096;
 // maximal packet size with transactions (cannot be greater than 16MB - protocol limitation).
 // keep it under 8MB as well, cause it seems that it may result oversized after compression.
 const MAX_TRANSACTION_PACKET_SIZE: usize = 5 * 1024 * 1024;
@@ -200,6 +209,7 @@ const STATUS_TIMEOUT: Duration = Duration::from_secs(10);
 const HEADERS_TIMEOUT: Duration = Duration::from_secs(15);
 const BODIES_TIMEOUT: Duration = Duration::from_secs(20);
 const RECEIPTS_TIMEOUT: Duration = Duration::from_secs(10);
+const POOLED_TRANSACTIONS_TIMEOUT: Duration = Duration::from_secs(10);
 const FORK_HEADER_TIMEOUT: Duration = Duration::from_secs(3);
 /// Max time to wait for the Snapshot Manifest packet to arrive from a peer after it's being asked.
 const SNAPSHOT_MANIFEST_TIMEOUT: Duration = Duration::from_secs(5);
@@ -301,6 +311,7 @@ pub enum PeerAsking {
 	BlockHeaders,
 	BlockBodies,
 	BlockReceipts,
+	PooledTransactions,
 	SnapshotManifest,
 	SnapshotData,
 	PrivateState,
@@ -329,6 +340,9 @@ pub enum ForkConfirmation {
 pub struct PeerInfo {
 	/// eth protocol version
 	protocol_version: u8,
+	/// Version of the eth subprotocol, which differs from `protocol_version` for peers
+	/// supporting the warp sync protocol.
+	eth_protocol_version: u8,
 	/// Peer chain genesis hash
 	genesis: H256,
 	/// Peer network id
@@ -347,10 +361,16 @@ pub struct PeerInfo {
 	asking_private_state: Option<H256>,
 	/// Holds requested snapshot chunk hash if any.
 	asking_snapshot_data: Option<H256>,
+	/// Holds the hashes of the requested pooled transactions.
+	asking_pooled_transactions: Vec<H256>,
+	/// Request id of the pending request, if the peer supports eth/66.
+	asking_request_id: Option<RequestId>,
 	/// Request timestamp
 	ask_time: Instant,
 	/// Holds a set of transactions recently sent to this peer to avoid spamming.
 	last_sent_transactions: H256FastSet,
+	/// Holds a set of transactions announced by this peer which we haven't requested yet.
+	unfetched_pooled_transactions: H256FastSet,
 	/// Holds a set of private transactions and their signatures recently sent to this peer to avoid spamming.
 	last_sent_private_transactions: H256FastSet,
 	/// Pending request is expired and result should be ignored
@@ -382,6 +402,7 @@ impl PeerInfo {
 		self.asking_blocks.clear();
 		self.asking_hash = None;
 		self.asking_private_state = None;
+		self.asking_pooled_transactions.clear();
 		// mark any pending requests as expired
 		if self.asking != PeerAsking::Nothing && self.is_allowed() {
 			self.expired = true;
@@ -683,6 +704,10 @@ pub struct ChainSync {
 	private_tx_handler: Option<Arc<dyn PrivateTxHandler>>,
 	/// Enable warp sync.
 	warp_sync: WarpSync,
+	/// Block number from which headers carry the EIP-1559 base fee.
+	pub(crate) eip1559_transition: BlockNumber,
+	/// Id of the last eth/66 request sent to a peer.
+	last_request_id: RequestId,
 
 	#[ignore_malloc_size_of = "mpsc unmettered, ignoring"]
 	status_sinks: Vec<futures_mpsc::UnboundedSender<SyncState>>
@@ -707,7 +732,7 @@ impl ChainSync {
 			peers: HashMap::new(),
 			handshaking_peers: HashMap::new(),
 			active_peers: HashSet::new(),
-			new_blocks: BlockDownloader::new(BlockSet::NewBlocks, &chain_info.best_block_hash, chain_info.best_block_number),
+			new_blocks: BlockDownloader::new(BlockSet::NewBlocks, &chain_info.best_block_hash, chain_info.best_block_number, config.eip1559_transition),
 			old_blocks: None,
 			last_sent_block_number: 0,
 			network_id: config.network_id,
@@ -719,6 +744,8 @@ impl ChainSync {
 			transactions_stats: TransactionsStats::default(),
 			private_tx_handler,
 			warp_sync: config.warp_sync,
+			eip1559_transition: config.eip1559_transition,
+			last_request_id: 0,
 			status_sinks: Vec::new()
 		};
 		sync.update_targets(chain);
@@ -730,7 +757,7 @@ impl ChainSync {
 		let last_imported_number = self.new_blocks.last_imported_block_number();
 		SyncStatus {
 			state: self.state.clone(),
-			protocol_version: ETH_PROTOCOL_VERSION_64.0,
+			protocol_version: ETH_PROTOCOL_VERSION_66.0,
 			network_id: self.network_id,
 			start_block_number: self.starting_block,
 			last_imported_block_number: Some(last_imported_number),
@@ -975,13 +1002,13 @@ impl ChainSync {
 	pub fn update_targets(&mut self, chain: &dyn BlockChainClient) {
 		// Do not assume that the block queue/chain still has our last_imported_block
 		let chain = chain.chain_info();
-		self.new_blocks = BlockDownloader::new(BlockSet::NewBlocks, &chain.best_block_hash, chain.best_block_number);
+		self.new_blocks = BlockDownloader::new(BlockSet::NewBlocks, &chain.best_block_hash, chain.best_block_number, self.eip1559_transition);
 		self.old_blocks = None;
 		if self.download_old_blocks {
 			if let (Some(ancient_block_hash), Some(ancient_block_number)) = (chain.ancient_block_hash, chain.ancient_block_number) {
 
 				trace!(target: "sync", "Downloading old blocks from {:?} (#{}) till {:?} (#{:?})", ancient_block_hash, ancient_block_number, chain.first_block_hash, chain.first_block_number);
-				let mut downloader = BlockDownloader::new(BlockSet::OldBlocks, &ancient_block_hash, ancient_block_number);
+				let mut downloader = BlockDownloader::new(BlockSet::OldBlocks, &ancient_block_hash, ancient_block_number, self.eip1559_transition);
 				if let Some(hash) = chain.first_block_hash {
 					trace!(target: "sync", "Downloader target set to {:?}", hash);
 					downloader.set_target(&hash);
@@ -1154,6 +1181,50 @@ impl ChainSync {
 		}
 	}
 
+	/// Request the transactions announced by a peer which are not in our queue yet.
+	fn request_pooled_transactions(&mut self, io: &mut dyn SyncIo, peer_id: PeerId) {
+		let hashes: Vec<H256> = match self.peers.get_mut(&peer_id) {
+			Some(peer) if peer.asking == PeerAsking::Nothing && peer.can_sync() => {
+				let hashes: Vec<H256> = peer.unfetched_pooled_transactions.iter()
+					.take(MAX_TRANSACTIONS_TO_REQUEST)
+					.cloned()
+					.collect();
+				for hash in &hashes {
+					peer.unfetched_pooled_transactions.remove(hash);
+				}
+				hashes
+			},
+			_ => return,
+		};
+		let hashes: Vec<H256> = hashes.into_iter()
+			.filter(|hash| io.chain().queued_transaction(hash).is_none())
+			.collect();
+
+		if !hashes.is_empty() {
+			SyncRequester::request_pooled_transactions(self, io, peer_id, hashes);
+		}
+	}
+
+	/// Hand the transactions requested from a peer which is going away over to the other peers which know them,
+	/// so that they are fetched from there.
+	fn requeue_pooled_transactions(&mut self, peer_id: PeerId) {
+		let hashes = match self.peers.get_mut(&peer_id) {
+			Some(peer) => mem::replace(&mut peer.asking_pooled_transactions, Vec::new()),
+			None => return,
+		};
+		for hash in hashes {
+			let alternative = self.peers.iter_mut()
+				.find(|(id, peer)| **id != peer_id
+					&& peer.can_sync()
+					&& peer.last_sent_transactions.contains(&hash)
+					&& peer.unfetched_pooled_transactions.len() < MAX_UNFETCHED_POOLED_TRANSACTIONS);
+			match alternative {
+				Some((_, peer)) => { peer.unfetched_pooled_transactions.insert(hash); },
+				None => trace!(target: "sync", "No other peer to fetch transaction {} from", hash),
+			}
+		}
+	}
+
 	/// Clear all blocks/headers marked as being downloaded by us from a peer.
 	fn clear_peer_download(&mut self, peer_id: PeerId) {
 		if let Some(peer) = self.peers.get(&peer_id) {
@@ -1290,6 +1361,7 @@ impl ChainSync {
 				PeerAsking::BlockHeaders => elapsed > HEADERS_TIMEOUT,
 				PeerAsking::BlockBodies => elapsed > BODIES_TIMEOUT,
 				PeerAsking::BlockReceipts => elapsed > RECEIPTS_TIMEOUT,
+				PeerAsking::PooledTransactions => elapsed > POOLED_TRANSACTIONS_TIMEOUT,
 				PeerAsking::Nothing => false,
 				PeerAsking::ForkHeader => elapsed > FORK_HEADER_TIMEOUT,
 				PeerAsking::SnapshotManifest => elapsed > SNAPSHOT_MANIFEST_TIMEOUT,
@@ -1306,6 +1378,15 @@ impl ChainSync {
 			SyncHandler::on_peer_aborting(self, io, p);
 		}
 
+		// Fetch the announced transactions from the peers which are not busy, whatever we are syncing.
+		let announcing: Vec<PeerId> = self.peers.iter()
+			.filter(|(_, peer)| !peer.unfetched_pooled_transactions.is_empty())
+			.map(|(peer_id, _)| *peer_id)
+			.collect();
+		for peer_id in announcing {
+			self.request_pooled_transactions(io, peer_id);
+		}
+
 		// Check for handshake timeouts
 		for (peer, &ask_time) in &self.handshaking_peers {
 			let elapsed = (tick - ask_time) / 1_000_000_000;
@@ -1420,6 +1501,11 @@ impl ChainSync {
 		self.check_resume(io);
 	}
 
+	/// The fork id of our chain at its current head.
+	pub fn current_fork_id(&mut self, chain: &dyn BlockChainClient) -> ForkId {
+		self.fork_filter.current(chain)
+	}
+
 	/// called when block is imported to chain - propagates the blocks and updates transactions sent to peers
 	pub fn chain_new_blocks(&mut self, io: &mut dyn SyncIo, _imported: &[H256], invalid: &[H256], enacted: &[H256], _retracted: &[H256], sealed: &[H256], proposed: &[Bytes]) {
 		let queue_info = io.chain().queue_info();
@@ -1620,6 +1706,7 @@ pub mod tests {
 		sync.peers.insert(peer_id,
 			PeerInfo {
 				protocol_version: 0,
+				eth_protocol_version: 0,
 				genesis: H256::zero(),
 				network_id: 0,
 				latest_hash: peer_latest_hash,
@@ -1630,6 +1717,7 @@ pub mod tests {
 				asking_private_state: None,
 				ask_time: Instant::now(),
 				last_sent_transactions: Default::default(),
+				unfetched_pooled_transactions: Default::default(),
 				last_sent_private_transactions: Default::default(),
 				expired: false,
 				private_tx_enabled: false,
@@ -1637,6 +1725,8 @@ pub mod tests {
 				snapshot_number: None,
 				snapshot_hash: None,
 				asking_snapshot_data: None,
+				asking_pooled_transactions: Vec::new(),
+				asking_request_id: None,
 				block_set: None,
 				client_version: ClientVersion::from(""),
 			});
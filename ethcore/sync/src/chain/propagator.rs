@@ -32,6 +32,7 @@ use common_types::{blockchain_info::BlockChainInfo, transaction::SignedTransacti
 use super::sync_packet::SyncPacket::{
 	NewBlockHashesPacket,
 	TransactionsPacket,
+	NewPooledTransactionHashesPacket,
 	NewBlockPacket,
 	ConsensusDataPacket,
 };
@@ -39,6 +40,7 @@ use super::sync_packet::SyncPacket::{
 use super::{
 	random,
 	ChainSync,
+	ETH_PROTOCOL_VERSION_65,
 	MAX_TRANSACTION_PACKET_SIZE,
 	MAX_PEER_LAG_PROPAGATION,
 	MAX_PEERS_PROPAGATION,
@@ -153,14 +155,24 @@ impl SyncPropagator {
 			for tx in &transactions { packet.append(&**tx); }
 			packet.out()
 		};
+		let all_transactions_hashes_rlp = {
+			let mut packet = RlpStream::new_list(transactions.len());
+			for tx in &transactions { packet.append(&tx.hash()); }
+			packet.out()
+		};
 
 		// Clear old transactions from stats
 		sync.transactions_stats.retain(&all_transactions_hashes);
 
-		let send_packet = |io: &mut dyn SyncIo, peer_id: PeerId, sent: usize, rlp: Bytes| {
+		let send_packet = |io: &mut dyn SyncIo, peer_id: PeerId, is_hashes: bool, sent: usize, rlp: Bytes| {
 			let size = rlp.len();
-			SyncPropagator::send_packet(io, peer_id, TransactionsPacket, rlp);
-			trace!(target: "sync", "{:02} <- Transactions ({} entries; {} bytes)", peer_id, sent, size);
+			if is_hashes {
+				SyncPropagator::send_packet(io, peer_id, NewPooledTransactionHashesPacket, rlp);
+				trace!(target: "sync", "{:02} <- NewPooledTransactionHashes ({} entries; {} bytes)", peer_id, sent, size);
+			} else {
+				SyncPropagator::send_packet(io, peer_id, TransactionsPacket, rlp);
+				trace!(target: "sync", "{:02} <- Transactions ({} entries; {} bytes)", peer_id, sent, size);
+			}
 		};
 
 		let block_number = io.chain().chain_info().best_block_number;
@@ -177,6 +189,8 @@ impl SyncPropagator {
 			let stats = &mut sync.transactions_stats;
 			let peer_info = sync.peers.get_mut(&peer_id)
 				.expect("peer_id is form peers; peers is result of select_peers_for_transactions; select_peers_for_transactions selects peers from self.peers; qed");
+			// Since eth/65 transactions are announced by their hashes and fetched by the peer on demand.
+			let is_hashes = peer_info.eth_protocol_version >= ETH_PROTOCOL_VERSION_65.0;
 
 			// Send all transactions, if the peer doesn't know about anything
 			if peer_info.last_sent_transactions.is_empty() {
@@ -187,7 +201,8 @@ impl SyncPropagator {
 				}
 				peer_info.last_sent_transactions = all_transactions_hashes.clone();
 
-				send_packet(io, peer_id, all_transactions_hashes.len(), all_transactions_rlp.clone());
+				let rlp = if is_hashes { all_transactions_hashes_rlp.clone() } else { all_transactions_rlp.clone() };
+				send_packet(io, peer_id, is_hashes, all_transactions_hashes.len(), rlp);
 				sent_to_peers.insert(peer_id);
 				max_sent = cmp::max(max_sent, all_transactions_hashes.len());
 				continue;
@@ -210,9 +225,13 @@ impl SyncPropagator {
 				for tx in &transactions {
 					let hash = tx.hash();
 					if to_send.contains(&hash) {
-						let mut transaction = RlpStream::new();
-						tx.rlp_append(&mut transaction);
-						let appended = packet.append_raw_checked(&transaction.drain(), 1, MAX_TRANSACTION_PACKET_SIZE);
+						let mut item = RlpStream::new();
+						if is_hashes {
+							item.append(&hash);
+						} else {
+							tx.rlp_append(&mut item);
+						}
+						let appended = packet.append_raw_checked(&item.drain(), 1, MAX_TRANSACTION_PACKET_SIZE);
 						if !appended {
 							// Maximal packet size reached just proceed with sending
 							debug!(target: "sync", "Transaction packet size limit reached. Sending incomplete set of {}/{} transactions.", pushed, to_send.len());
@@ -238,7 +257,7 @@ impl SyncPropagator {
 				.chain(&to_send)
 				.cloned()
 				.collect();
-			send_packet(io, peer_id, to_send.len(), packet.out());
+			send_packet(io, peer_id, is_hashes, to_send.len(), packet.out());
 			sent_to_peers.insert(peer_id);
 			max_sent = cmp::max(max_sent, to_send.len());
 
@@ -435,6 +454,7 @@ mod tests {
 			PeerInfo {
 				// Messaging protocol
 				protocol_version: 2,
+				eth_protocol_version: 0,
 				genesis: H256::zero(),
 				network_id: 0,
 				latest_hash: client.block_hash_delta_minus(1),
@@ -445,6 +465,7 @@ mod tests {
 				asking_private_state: None,
 				ask_time: Instant::now(),
 				last_sent_transactions: Default::default(),
+				unfetched_pooled_transactions: Default::default(),
 				last_sent_private_transactions: Default::default(),
 				expired: false,
 				private_tx_enabled: false,
@@ -452,6 +473,8 @@ mod tests {
 				snapshot_number: None,
 				snapshot_hash: None,
 				asking_snapshot_data: None,
+				asking_pooled_transactions: Vec::new(),
+				asking_request_id: None,
 				block_set: None,
 				client_version: ClientVersion::from(""),
 			});
@@ -492,6 +515,26 @@ mod tests {
 		assert_eq!(0x02, io.packets[0].packet_id);
 	}
 
+	#[test]
+	fn announces_transaction_hashes_to_eth65_peers() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		client.insert_transaction_to_queue();
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(1), &client);
+		sync.peers.get_mut(&0).unwrap().eth_protocol_version = 65;
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None, None);
+		let peer_count = SyncPropagator::propagate_new_transactions(&mut sync, &mut io, || true);
+
+		assert_eq!(1, peer_count);
+		assert_eq!(1, io.packets.len());
+		// NEW_POOLED_TRANSACTION_HASHES_PACKET
+		assert_eq!(0x08, io.packets[0].packet_id);
+		let hashes: Vec<H256> = Rlp::new(&io.packets[0].data).as_list().unwrap();
+		assert_eq!(hashes, vec![io.chain.transactions_to_propagate()[0].signed().hash()]);
+	}
+
 	#[test]
 	fn does_not_propagate_new_transactions_after_new_block() {
 		let mut client = TestBlockChainClient::new();
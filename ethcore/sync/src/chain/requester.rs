@@ -28,10 +28,11 @@ use network::{PeerId};
 use rlp::RlpStream;
 use common_types::BlockNumber;
 
-use super::sync_packet::SyncPacket;
+use super::sync_packet::{with_request_id, SyncPacket};
 use super::sync_packet::SyncPacket::{
 	GetBlockHeadersPacket,
 	GetBlockBodiesPacket,
+	GetPooledTransactionsPacket,
 	GetReceiptsPacket,
 	GetSnapshotManifestPacket,
 	GetSnapshotDataPacket,
@@ -42,6 +43,7 @@ use super::{
 	BlockSet,
 	ChainSync,
 	PeerAsking,
+	ETH_PROTOCOL_VERSION_66,
 };
 
 /// The Chain Sync Requester: requesting data to other peers
@@ -140,6 +142,18 @@ impl SyncRequester {
 		peer.block_set = Some(set);
 	}
 
+	/// Request transactions announced by a peer
+	pub fn request_pooled_transactions(sync: &mut ChainSync, io: &mut dyn SyncIo, peer_id: PeerId, hashes: Vec<H256>) {
+		trace!(target: "sync", "{} <- GetPooledTransactions: {} entries", peer_id, hashes.len());
+		let mut rlp = RlpStream::new_list(hashes.len());
+		for h in &hashes {
+			rlp.append(h);
+		}
+		SyncRequester::send_request(sync, io, peer_id, PeerAsking::PooledTransactions, GetPooledTransactionsPacket, rlp.out());
+		let peer = sync.peers.get_mut(&peer_id).expect("peer_id may originate either from on_packet, where it is already validated or from enumerating self.peers. qed");
+		peer.asking_pooled_transactions = hashes;
+	}
+
 	/// Request snapshot chunk from a peer.
 	fn request_snapshot_chunk(sync: &mut ChainSync, io: &mut dyn SyncIo, peer_id: PeerId, chunk: &H256) {
 		trace!(target: "sync", "{} <- GetSnapshotData {:?}", peer_id, chunk);
@@ -150,6 +164,7 @@ impl SyncRequester {
 
 	/// Generic request sender
 	fn send_request(sync: &mut ChainSync, io: &mut dyn SyncIo, peer_id: PeerId, asking: PeerAsking, packet_id: SyncPacket, packet: Bytes) {
+		let request_id = sync.last_request_id.wrapping_add(1);
 		if let Some(ref mut peer) = sync.peers.get_mut(&peer_id) {
 			if peer.asking != PeerAsking::Nothing {
 				warn!(target:"sync", "Asking {:?} while requesting {:?}", peer.asking, asking);
@@ -157,6 +172,14 @@ impl SyncRequester {
 			peer.asking = asking;
 			peer.ask_time = Instant::now();
 
+			let packet = if packet_id.has_request_id() && peer.eth_protocol_version >= ETH_PROTOCOL_VERSION_66.0 {
+				peer.asking_request_id = Some(request_id);
+				sync.last_request_id = request_id;
+				with_request_id(request_id, &packet)
+			} else {
+				peer.asking_request_id = None;
+				packet
+			};
 			let result = io.send(peer_id, packet_id, packet);
 
 			if let Err(e) = result {
@@ -17,7 +17,7 @@
 use std::cmp;
 use std::time::{Duration, Instant};
 
-use crate::sync_io::SyncIo;
+use crate::{api::ETH_PROTOCOL, sync_io::SyncIo};
 
 use bytes::Bytes;
 use num_traits::FromPrimitive;
@@ -28,10 +28,12 @@ use parking_lot::RwLock;
 use rlp::{Rlp, RlpStream};
 use common_types::{ids::BlockId, BlockNumber};
 
-use super::sync_packet::{PacketInfo, SyncPacket};
+use super::sync_packet::{split_request_id, with_request_id, PacketInfo, RequestId, SyncPacket};
 use super::sync_packet::SyncPacket::{
 	StatusPacket,
 	TransactionsPacket,
+	GetPooledTransactionsPacket,
+	PooledTransactionsPacket,
 	GetBlockHeadersPacket,
 	BlockHeadersPacket,
 	GetBlockBodiesPacket,
@@ -60,6 +62,8 @@ use super::{
 	MAX_NODE_DATA_TOTAL_DURATION,
 	MAX_NODE_DATA_SINGLE_DURATION,
 	MAX_RECEIPTS_HEADERS_TO_SEND,
+	MAX_TRANSACTIONS_TO_REQUEST,
+	ETH_PROTOCOL_VERSION_66,
 };
 
 /// The Chain Sync Supplier: answers requests from peers with available data
@@ -73,39 +77,57 @@ impl SyncSupplier {
 		let rlp = Rlp::new(data);
 
 		if let Some(id) = SyncPacket::from_u8(packet_id) {
+			// Since eth/66 the requests carry an id, which is sent back with the response.
+			let (request, request_id) = if id.has_request_id() && io.protocol_version(&ETH_PROTOCOL, peer) >= ETH_PROTOCOL_VERSION_66.0 {
+				match split_request_id(&rlp) {
+					Ok((request_id, request)) => (request, Some(request_id)),
+					Err(e) => {
+						debug!(target:"sync", "{} -> Malformed packet {} : {}", peer, packet_id, e);
+						return;
+					}
+				}
+			} else {
+				(rlp.clone(), None)
+			};
+
 			let result = match id {
 				GetBlockBodiesPacket => SyncSupplier::return_rlp(
-					io, &rlp, peer,
+					io, &request, request_id, peer,
 					SyncSupplier::return_block_bodies,
 					|e| format!("Error sending block bodies: {:?}", e)),
 
 				GetBlockHeadersPacket => SyncSupplier::return_rlp(
-					io, &rlp, peer,
+					io, &request, request_id, peer,
 					SyncSupplier::return_block_headers,
 					|e| format!("Error sending block headers: {:?}", e)),
 
 				GetReceiptsPacket => SyncSupplier::return_rlp(
-					io, &rlp, peer,
+					io, &request, request_id, peer,
 					SyncSupplier::return_receipts,
 					|e| format!("Error sending receipts: {:?}", e)),
 
 				GetNodeDataPacket => SyncSupplier::return_rlp(
-					io, &rlp, peer,
+					io, &request, request_id, peer,
 					SyncSupplier::return_node_data,
 					|e| format!("Error sending nodes: {:?}", e)),
 
+				GetPooledTransactionsPacket => SyncSupplier::return_rlp(
+					io, &request, request_id, peer,
+					SyncSupplier::return_pooled_transactions,
+					|e| format!("Error sending pooled transactions: {:?}", e)),
+
 				GetSnapshotManifestPacket => SyncSupplier::return_rlp(
-					io, &rlp, peer,
+					io, &request, request_id, peer,
 					SyncSupplier::return_snapshot_manifest,
 					|e| format!("Error sending snapshot manifest: {:?}", e)),
 
 				GetSnapshotDataPacket => SyncSupplier::return_rlp(
-					io, &rlp, peer,
+					io, &request, request_id, peer,
 					SyncSupplier::return_snapshot_data,
 					|e| format!("Error sending snapshot data: {:?}", e)),
 
 				GetPrivateStatePacket => SyncSupplier::return_rlp(
-					io, &rlp, peer,
+					io, &request, request_id, peer,
 					SyncSupplier::return_private_state,
 					|e| format!("Error sending private state data: {:?}", e)),
 
@@ -305,6 +327,34 @@ impl SyncSupplier {
 		Ok(Some((NodeDataPacket.id(), rlp)))
 	}
 
+	/// Respond to GetPooledTransactions request
+	fn return_pooled_transactions(io: &dyn SyncIo, r: &Rlp, peer_id: PeerId) -> RlpResponseResult {
+		let payload_soft_limit = io.payload_soft_limit();
+		let mut count = r.item_count().unwrap_or(0);
+		trace!(target: "sync", "{} -> GetPooledTransactions: {} entries", peer_id, count);
+		if count == 0 {
+			debug!(target: "sync", "Empty GetPooledTransactions request, ignoring.");
+			return Ok(None);
+		}
+		count = cmp::min(count, MAX_TRANSACTIONS_TO_REQUEST);
+		let mut added = 0usize;
+		let mut data = Bytes::new();
+		for i in 0..count {
+			if let Some(tx) = io.chain().queued_transaction(&r.val_at::<H256>(i)?) {
+				data.append(&mut ::rlp::encode(tx.signed()));
+				added += 1;
+				// Check that the packet won't be oversized
+				if data.len() > payload_soft_limit {
+					break;
+				}
+			}
+		}
+		let mut rlp = RlpStream::new_list(added);
+		rlp.append_raw(&data, added);
+		trace!(target: "sync", "{} -> GetPooledTransactions: returned {} entries", peer_id, added);
+		Ok(Some((PooledTransactionsPacket.id(), rlp)))
+	}
+
 	fn return_receipts(io: &dyn SyncIo, rlp: &Rlp, peer_id: PeerId) -> RlpResponseResult {
 		let payload_soft_limit = io.payload_soft_limit();
 		let mut count = rlp.item_count().unwrap_or(0);
@@ -393,7 +443,7 @@ impl SyncSupplier {
 		})
 	}
 
-	fn return_rlp<FRlp, FError>(io: &mut dyn SyncIo, rlp: &Rlp, peer: PeerId, rlp_func: FRlp, error_func: FError) -> Result<(), PacketDecodeError>
+	fn return_rlp<FRlp, FError>(io: &mut dyn SyncIo, rlp: &Rlp, request_id: Option<RequestId>, peer: PeerId, rlp_func: FRlp, error_func: FError) -> Result<(), PacketDecodeError>
 		where FRlp : Fn(&dyn SyncIo, &Rlp, PeerId) -> RlpResponseResult,
 			FError : FnOnce(network::Error) -> String
 	{
@@ -401,7 +451,11 @@ impl SyncSupplier {
 		match response {
 			Err(e) => Err(e),
 			Ok(Some((packet_id, rlp_stream))) => {
-				io.respond(packet_id, rlp_stream.out()).unwrap_or_else(
+				let payload = match request_id {
+					Some(request_id) => with_request_id(request_id, &rlp_stream.out()),
+					None => rlp_stream.out(),
+				};
+				io.respond(packet_id, payload).unwrap_or_else(
 					|e| debug!(target: "sync", "{:?}", error_func(e)));
 				Ok(())
 			}
@@ -416,12 +470,12 @@ mod test {
 
 	use crate::{
 		blocks::SyncHeader,
-		chain::RlpResponseResult,
+		chain::{sync_packet::{split_request_id, with_request_id}, RlpResponseResult, ETH_PROTOCOL_VERSION_66},
 		tests::{helpers::TestIo, snapshot::TestSnapshotService}
 	};
 
 	use super::{
-		SyncPacket::{GetReceiptsPacket, GetNodeDataPacket},
+		SyncPacket::{GetReceiptsPacket, GetNodeDataPacket, PooledTransactionsPacket},
 		BlockNumber, BlockId, SyncSupplier, PacketInfo
 	};
 
@@ -455,14 +509,14 @@ mod test {
 		}
 
 		fn to_header_vec(rlp: RlpResponseResult) -> Vec<SyncHeader> {
-			Rlp::new(&rlp.unwrap().unwrap().1.out()).iter().map(|r| SyncHeader::from_rlp(r.as_raw().to_vec()).unwrap()).collect()
+			Rlp::new(&rlp.unwrap().unwrap().1.out()).iter().map(|r| SyncHeader::from_rlp(r.as_raw().to_vec(), BlockNumber::max_value()).unwrap()).collect()
 		}
 
 		let mut client = TestBlockChainClient::new();
 		client.add_blocks(100, EachBlockWith::Nothing);
 		let blocks: Vec<_> = (0 .. 100)
 			.map(|i| (&client as &dyn BlockChainClient).block(BlockId::Number(i as BlockNumber)).map(|b| b.into_inner()).unwrap()).collect();
-		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec()).unwrap()).collect();
+		let headers: Vec<_> = blocks.iter().map(|b| SyncHeader::from_rlp(Rlp::new(b).at(0).unwrap().as_raw().to_vec(), BlockNumber::max_value()).unwrap()).collect();
 		let hashes: Vec<_> = headers.iter().map(|h| h.header.hash()).collect();
 
 		let queue = RwLock::new(VecDeque::new());
@@ -609,4 +663,52 @@ mod test {
 		SyncSupplier::dispatch_packet(&RwLock::new(sync), &mut io, 0usize, GetReceiptsPacket.id(), &receipts_request, None);
 		assert_eq!(1, io.packets.len());
 	}
+
+	#[test]
+	fn return_receipts_with_request_id() {
+		let mut client = TestBlockChainClient::new();
+		let queue = RwLock::new(VecDeque::new());
+		let sync = dummy_sync_with_peer(H256::zero(), &client);
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None, None);
+		io.eth_protocol_version = ETH_PROTOCOL_VERSION_66.0;
+
+		let mut receipt_list = RlpStream::new_list(1);
+		receipt_list.append(&H256::from_str("ff00000000000000000000000000000000000000000000000000000000000000").unwrap());
+		let receipts_request = with_request_id(42, &receipt_list.out());
+
+		io.sender = Some(2usize);
+		SyncSupplier::dispatch_packet(&RwLock::new(sync), &mut io, 0usize, GetReceiptsPacket.id(), &receipts_request, None);
+		assert_eq!(1, io.packets.len());
+
+		let (request_id, receipts) = split_request_id(&Rlp::new(&io.packets[0].data)).unwrap();
+		assert_eq!(42, request_id);
+		assert_eq!(1, receipts.item_count().unwrap());
+	}
+
+	#[test]
+	fn return_pooled_transactions() {
+		let mut client = TestBlockChainClient::new();
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let hash = client.insert_transaction_to_queue();
+		let io = TestIo::new(&mut client, &ss, &queue, None, None);
+
+		let mut request = RlpStream::new_list(2);
+		request.append(&hash);
+		request.append(&H256::from_low_u64_be(1));
+
+		// Unknown transactions are skipped.
+		let (packet_id, rlp) = SyncSupplier::return_pooled_transactions(&io, &Rlp::new(&request.out()), 0)
+			.unwrap()
+			.unwrap();
+		assert_eq!(PooledTransactionsPacket.id(), packet_id);
+		let response = rlp.out();
+		let transactions = Rlp::new(&response);
+		assert_eq!(1, transactions.item_count().unwrap());
+		assert_eq!(hash, keccak_hash::keccak(transactions.at(0).unwrap().as_raw()));
+
+		let result = SyncSupplier::return_pooled_transactions(&io, &Rlp::new(&[0xc0]), 0);
+		assert!(result.unwrap().is_none());
+	}
 }
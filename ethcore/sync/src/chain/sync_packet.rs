@@ -25,8 +25,13 @@
 use crate::api::{ETH_PROTOCOL, WARP_SYNC_PROTOCOL_ID};
 use self::SyncPacket::*;
 
+use bytes::Bytes;
 use enum_primitive_derive::Primitive;
 use network::{PacketId, ProtocolId};
+use rlp::{DecoderError, Rlp, RlpStream};
+
+/// Id matching the response to a request, sent along with both since eth/66.
+pub type RequestId = u64;
 
 /// An enum that defines all known packet ids in the context of
 /// synchronization and provides a mechanism to convert from
@@ -44,6 +49,9 @@ pub enum SyncPacket {
 	GetBlockBodiesPacket = 0x05,
 	BlockBodiesPacket = 0x06,
 	NewBlockPacket = 0x07,
+	NewPooledTransactionHashesPacket = 0x08,
+	GetPooledTransactionsPacket = 0x09,
+	PooledTransactionsPacket = 0x0a,
 
 	GetNodeDataPacket = 0x0d,
 	NodeDataPacket = 0x0e,
@@ -61,6 +69,39 @@ pub enum SyncPacket {
 	PrivateStatePacket = 0x19,
 }
 
+impl SyncPacket {
+	/// Whether the packet is a request or a response of the eth protocol. Since eth/66 these
+	/// carry a request id.
+	pub fn has_request_id(&self) -> bool {
+		match self {
+			GetBlockHeadersPacket |
+			BlockHeadersPacket |
+			GetBlockBodiesPacket |
+			BlockBodiesPacket |
+			GetPooledTransactionsPacket |
+			PooledTransactionsPacket |
+			GetNodeDataPacket |
+			NodeDataPacket |
+			GetReceiptsPacket |
+			ReceiptsPacket => true,
+			_ => false,
+		}
+	}
+}
+
+/// Wraps the payload of an eth/66 request or response: `[request_id, payload]`.
+pub fn with_request_id(request_id: RequestId, payload: &[u8]) -> Bytes {
+	let mut rlp = RlpStream::new_list(2);
+	rlp.append(&request_id);
+	rlp.append_raw(payload, 1);
+	rlp.out()
+}
+
+/// Splits an eth/66 request or response into the request id and the payload.
+pub fn split_request_id<'a>(rlp: &Rlp<'a>) -> Result<(RequestId, Rlp<'a>), DecoderError> {
+	Ok((rlp.val_at(0)?, rlp.at(1)?))
+}
+
 
 /// Provide both subprotocol and packet id information within the
 /// same object.
@@ -85,6 +126,9 @@ impl PacketInfo for SyncPacket {
 			GetBlockBodiesPacket |
 			BlockBodiesPacket |
 			NewBlockPacket |
+			NewPooledTransactionHashesPacket |
+			GetPooledTransactionsPacket |
+			PooledTransactionsPacket |
 
 			GetNodeDataPacket|
 			NodeDataPacket |
@@ -139,6 +183,19 @@ mod tests {
 		assert_eq!(StatusPacket.protocol(), ETH_PROTOCOL);
 	}
 
+	#[test]
+	fn request_id_is_split_from_payload() {
+		let payload = rlp::encode_list::<u8, _>(&[1, 2, 3]);
+		let packet = with_request_id(42, &payload);
+
+		let rlp = Rlp::new(&packet);
+		let (request_id, inner) = split_request_id(&rlp).unwrap();
+		assert_eq!(request_id, 42);
+		assert_eq!(inner.as_raw(), &payload[..]);
+		assert!(GetPooledTransactionsPacket.has_request_id());
+		assert!(!NewPooledTransactionHashesPacket.has_request_id());
+	}
+
 	#[test]
 	fn when_consensus_data_packet_then_id_and_protocol_match() {
 		assert_eq!(ConsensusDataPacket.id(), ConsensusDataPacket as PacketId);
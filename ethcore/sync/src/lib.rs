@@ -40,6 +40,6 @@ mod tests;
 
 pub use api::*;
 pub use chain::{SyncStatus, SyncState};
-pub use devp2p::validate_node_url;
+pub use devp2p::{validate_node_url, validate_node_list_url, export_node_list, NodeList};
 pub use network::{NonReservedPeerMode, Error, ConnectionFilter, ConnectionDirection};
 pub use private_tx::{PrivateTxHandler, NoopPrivateTxHandler, SimplePrivateTxHandler};
@@ -138,7 +138,7 @@ impl AncestorSearch {
 		match self {
 			AncestorSearch::Awaiting(id, start, req) => {
 				if &id == ctx.req_id() {
-					match response::verify(ctx.data(), &req) {
+					match response::verify(ctx.data(), &req, client.engine().params().eip1559_transition) {
 						Ok(headers) => {
 							for header in &headers {
 								if client.is_known(&header.hash()) {
@@ -450,7 +450,10 @@ impl<L: AsLightClient + Send + Sync> Handler for LightSync<L> {
 				SyncState::Idle => SyncState::Idle,
 				SyncState::AncestorSearch(search) =>
 					SyncState::AncestorSearch(search.process_response(&ctx, &*self.client)),
-				SyncState::Rounds(round) => SyncState::Rounds(round.process_response(&ctx)),
+				SyncState::Rounds(round) => {
+					let eip1559_transition = self.client.as_light_client().engine().params().eip1559_transition;
+					SyncState::Rounds(round.process_response(&ctx, eip1559_transition))
+				}
 			};
 			self.set_state(&mut state, next_state);
 		}
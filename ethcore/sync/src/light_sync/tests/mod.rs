@@ -18,7 +18,7 @@ use crate::tests::helpers::TestNet;
 
 use ethcore::test_helpers::EachBlockWith;
 use client_traits::BlockInfo;
-use common_types::ids::BlockId;
+use common_types::{ids::BlockId, BlockNumber};
 
 mod test_net;
 
@@ -47,7 +47,7 @@ fn fork_post_cht() {
 	for id in (0..CHAIN_LENGTH).map(|x| x + 1).map(BlockId::Number) {
 		let (light_peer, full_peer) = (net.peer(0), net.peer(1));
 		let light_chain = light_peer.light_chain();
-		let header = full_peer.chain().block_header(id).unwrap().decode().expect("decoding failure");
+		let header = full_peer.chain().block_header(id).unwrap().decode(BlockNumber::max_value()).expect("decoding failure");
 		let _  = light_chain.import_header(header);
 		light_chain.flush_queue();
 		light_chain.import_verified();
@@ -75,6 +75,7 @@ pub struct TestIo<'p, C> where C: FlushingBlockChainClient, C: 'p {
 	pub packets: Vec<TestPacket>,
 	pub peers_info: HashMap<PeerId, String>,
 	pub private_state_db: Option<Arc<PrivateStateDB>>,
+	pub eth_protocol_version: u8,
 	overlay: RwLock<HashMap<BlockNumber, Bytes>>,
 }
 
@@ -95,6 +96,7 @@ impl<'p, C> TestIo<'p, C> where C: FlushingBlockChainClient, C: 'p {
 			packets: Vec::new(),
 			peers_info: HashMap::new(),
 			private_state_db,
+			eth_protocol_version: ETH_PROTOCOL_VERSION_64.0,
 			overlay: RwLock::new(HashMap::new()),
 		}
 	}
@@ -157,7 +159,7 @@ impl<'p, C> SyncIo for TestIo<'p, C> where C: FlushingBlockChainClient, C: 'p {
 	}
 
 	fn protocol_version(&self, protocol: &ProtocolId, _peer_id: PeerId) -> u8 {
-		if protocol == &WARP_SYNC_PROTOCOL_ID { PAR_PROTOCOL_VERSION_4.0 } else { ETH_PROTOCOL_VERSION_64.0 }
+		if protocol == &WARP_SYNC_PROTOCOL_ID { PAR_PROTOCOL_VERSION_4.0 } else { self.eth_protocol_version }
 	}
 
 	fn is_expired(&self) -> bool {
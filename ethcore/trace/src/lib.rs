@@ -38,6 +38,7 @@ pub use crate::{
 		Tracing,
 		error::Error as TraceError,
 		localized,
+		struct_log::StructLog,
 		trace::{self, VMTrace, VMOperation, VMExecutedOperation, MemoryDiff, StorageDiff, RewardType},
 		flat::{self, FlatTrace, FlatTransactionTraces, FlatBlockTraces},
 		filter::{self, Filter, AddressesFilter},
@@ -21,6 +21,7 @@ pub mod filter;
 pub mod flat;
 pub mod trace;
 pub mod localized;
+pub mod struct_log;
 
 use self::flat::FlatTransactionTraces;
 
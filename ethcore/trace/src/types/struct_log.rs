@@ -0,0 +1,188 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Instruction level logs, as reported by the struct logger of go-ethereum.
+
+use std::collections::BTreeMap;
+use ethereum_types::U256;
+use evm::Instruction;
+use super::trace::VMTrace;
+
+/// The state of the VM before the execution of a single instruction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructLog {
+	/// The program counter.
+	pub pc: usize,
+	/// The instruction.
+	pub instruction: u8,
+	/// The gas left before the execution of the instruction.
+	pub gas: U256,
+	/// The gas cost of the instruction.
+	pub gas_cost: U256,
+	/// The depth of the call, starting at 1.
+	pub depth: usize,
+	/// The stack, from the bottom to the top.
+	pub stack: Vec<U256>,
+	/// The memory.
+	pub memory: Vec<u8>,
+	/// The storage slots of the executed code read or written so far.
+	pub storage: BTreeMap<U256, U256>,
+}
+
+impl StructLog {
+	/// The mnemonic name of the instruction.
+	pub fn op_name(&self) -> &'static str {
+		Instruction::from_u8(self.instruction).map_or("INVALID", |i| i.info().name)
+	}
+
+	/// Returns the logs of the instructions of a VM trace, in the order of their execution.
+	///
+	/// The VM trace records only the effects of each instruction, so the stack, the memory and the
+	/// storage are rebuilt by applying them one after another.
+	pub fn from_vm_trace(trace: &VMTrace) -> Vec<StructLog> {
+		let mut logs = Vec::new();
+		Self::push_frame(trace, 1, &mut logs);
+		logs
+	}
+
+	fn push_frame(trace: &VMTrace, depth: usize, logs: &mut Vec<StructLog>) {
+		let mut stack = Vec::new();
+		let mut memory = Vec::new();
+		let mut storage = BTreeMap::new();
+		let mut gas_left = None;
+		let mut subs = trace.subs.iter().peekable();
+
+		for (step, operation) in trace.operations.iter().enumerate() {
+			let executed = operation.executed.as_ref();
+			// The gas left is only recorded after an instruction, so the first one adds its cost back.
+			let gas = gas_left.unwrap_or_else(|| executed.map_or(operation.gas_cost, |e| e.gas_used + operation.gas_cost));
+			let instruction = Instruction::from_u8(operation.instruction);
+
+			if let Some(executed) = executed {
+				match instruction {
+					Some(Instruction::SLOAD) => if let (Some(key), Some(value)) = (stack.last(), executed.stack_push.first()) {
+						storage.insert(*key, *value);
+					},
+					_ => if let Some(ref diff) = executed.store_diff {
+						storage.insert(diff.location, diff.value);
+					},
+				}
+			}
+
+			logs.push(StructLog {
+				pc: operation.pc,
+				instruction: operation.instruction,
+				gas,
+				gas_cost: operation.gas_cost,
+				depth,
+				stack: stack.clone(),
+				memory: memory.clone(),
+				storage: storage.clone(),
+			});
+
+			while subs.peek().map_or(false, |sub| sub.parent_step == step) {
+				let sub = subs.next().expect("peeked above; qed");
+				Self::push_frame(sub, depth + 1, logs);
+			}
+
+			let executed = match executed {
+				Some(executed) => executed,
+				// The instruction failed, which ends the execution of the code.
+				None => break,
+			};
+			let args = instruction.map_or(0, |i| i.info().args);
+			let len = stack.len().saturating_sub(args);
+			stack.truncate(len);
+			stack.extend_from_slice(&executed.stack_push);
+			if let Some(ref diff) = executed.mem_diff {
+				let end = diff.offset + diff.data.len();
+				if memory.len() < end {
+					// Memory is expanded in words of 32 bytes.
+					memory.resize((end + 31) / 32 * 32, 0);
+				}
+				memory[diff.offset..end].copy_from_slice(&diff.data);
+			}
+			gas_left = Some(executed.gas_used);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::trace::{VMOperation, VMExecutedOperation, MemoryDiff, StorageDiff};
+
+	fn operation(pc: usize, instruction: Instruction, gas_cost: u64, gas_left: u64, stack_push: Vec<U256>) -> VMOperation {
+		VMOperation {
+			pc,
+			instruction: instruction as u8,
+			gas_cost: gas_cost.into(),
+			executed: Some(VMExecutedOperation {
+				gas_used: gas_left.into(),
+				stack_push,
+				mem_diff: None,
+				store_diff: None,
+			}),
+		}
+	}
+
+	#[test]
+	fn rebuilds_stack_memory_and_storage() {
+		// PUSH1 2 PUSH1 1 SSTORE PUSH1 1 PUSH1 0 MSTORE CALL STOP
+		let mut operations = vec![
+			operation(0, Instruction::PUSH1, 3, 97, vec![2.into()]),
+			operation(2, Instruction::PUSH1, 3, 94, vec![1.into()]),
+			operation(4, Instruction::SSTORE, 20000, 74, vec![]),
+			operation(5, Instruction::PUSH1, 3, 71, vec![1.into()]),
+			operation(7, Instruction::PUSH1, 3, 68, vec![0.into()]),
+			operation(9, Instruction::MSTORE, 6, 62, vec![]),
+			operation(10, Instruction::CALL, 40, 50, vec![1.into()]),
+			operation(11, Instruction::STOP, 0, 50, vec![]),
+		];
+		operations[2].executed.as_mut().unwrap().store_diff = Some(StorageDiff { location: 1.into(), value: 2.into() });
+		let mut word = vec![0u8; 32];
+		word[31] = 1;
+		operations[5].executed.as_mut().unwrap().mem_diff = Some(MemoryDiff { offset: 0, data: word.clone() });
+		let trace = VMTrace {
+			parent_step: 0,
+			code: vec![],
+			operations,
+			subs: vec![VMTrace {
+				parent_step: 6,
+				code: vec![],
+				operations: vec![operation(0, Instruction::STOP, 0, 20, vec![])],
+				subs: vec![],
+			}],
+		};
+
+		let logs = StructLog::from_vm_trace(&trace);
+		assert_eq!(logs.len(), 9);
+		assert_eq!(logs[0].gas, 100.into());
+		assert_eq!(logs[0].op_name(), "PUSH1");
+		assert_eq!(logs[2].stack, vec![2.into(), 1.into()]);
+		assert_eq!(logs[2].gas, 94.into());
+		assert_eq!(logs[2].storage.get(&1.into()), Some(&2.into()));
+		assert!(logs[3].stack.is_empty());
+		assert_eq!(logs[6].memory, word);
+		assert_eq!(logs[6].depth, 1);
+		// The instructions of the call follow the call instruction.
+		assert_eq!(logs[7].depth, 2);
+		assert!(logs[7].stack.is_empty());
+		assert_eq!(logs[8].op_name(), "STOP");
+		assert_eq!(logs[8].stack, vec![1.into()]);
+		assert_eq!(logs[8].gas, 50.into());
+	}
+}
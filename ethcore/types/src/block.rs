@@ -28,7 +28,7 @@ use crate::{
 use bytes::Bytes;
 use ethereum_types::{H256, U256};
 use parity_util_mem::MallocSizeOf;
-use rlp::{Rlp, RlpStream, Decodable, DecoderError};
+use rlp::{Rlp, RlpStream, DecoderError};
 
 /// A block, encoded as it is on the block chain.
 #[derive(Default, Debug, Clone, PartialEq)]
@@ -52,8 +52,9 @@ impl Block {
 	}
 }
 
-impl Decodable for Block {
-	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+impl Block {
+	/// Decode a block from RLP, with the base fee in headers starting at `eip1559_transition`.
+	pub fn decode_rlp(rlp: &Rlp, eip1559_transition: BlockNumber) -> Result<Self, DecoderError> {
 		if rlp.as_raw().len() != rlp.payload_info()?.total() {
 			return Err(DecoderError::RlpIsTooBig);
 		}
@@ -61,9 +62,9 @@ impl Decodable for Block {
 			return Err(DecoderError::RlpIncorrectListLen);
 		}
 		Ok(Block {
-			header: rlp.val_at(0)?,
+			header: Header::decode_rlp(&rlp.at(0)?, eip1559_transition)?,
 			transactions: rlp.list_at(1)?,
-			uncles: rlp.list_at(2)?,
+			uncles: Header::decode_rlp_list(&rlp.at(2)?, eip1559_transition)?,
 		})
 	}
 }
@@ -106,6 +106,20 @@ pub struct CommonParams {
 	pub eip2200_advance_transition: BlockNumber,
 	/// Number of first block where EIP-2028 rules begin.
 	pub eip2315_transition: BlockNumber,
+	/// Number of first block where EIP-2929 rules begin: https://github.com/ethereum/EIPs/blob/master/EIPS/eip-2929.md
+	pub eip2929_transition: BlockNumber,
+	/// Number of first block where EIP-2930 access list transactions are accepted: https://github.com/ethereum/EIPs/blob/master/EIPS/eip-2930.md
+	pub eip2930_transition: BlockNumber,
+	/// Number of first block where the EIP-1559 fee market begins: https://github.com/ethereum/EIPs/blob/master/EIPS/eip-1559.md
+	pub eip1559_transition: BlockNumber,
+	/// Bound divisor of the change of the base fee between two blocks (EIP-1559).
+	pub eip1559_base_fee_max_change_denominator: U256,
+	/// Ratio of the gas limit to the gas target of a block (EIP-1559).
+	pub eip1559_elasticity_multiplier: U256,
+	/// Base fee of the first block of the EIP-1559 transition.
+	pub eip1559_base_fee_initial_value: U256,
+	/// Account credited with the base fee instead of burning it (EIP-1559).
+	pub eip1559_fee_collector: Option<Address>,
 	/// Number of first block where dust cleanup rules (EIP-168 and EIP169) begin.
 	pub dust_protection_transition: BlockNumber,
 	/// Nonce cap increase per block. Nonce cap is only checked if dust protection is enabled.
@@ -182,6 +196,8 @@ impl CommonParams {
 			 !(block_number >= self.eip1283_disable_transition)) ||
 			block_number >= self.eip1283_reenable_transition;
 		schedule.eip1706 = block_number >= self.eip1706_transition;
+		schedule.eip2930 = block_number >= self.eip2930_transition;
+		schedule.eip1559 = block_number >= self.eip1559_transition;
 
 		if block_number >= self.eip1884_transition {
 			schedule.have_selfbalance = true;
@@ -196,6 +212,12 @@ impl CommonParams {
 			schedule.sload_gas = 800;
 			schedule.sstore_dirty_gas = Some(800);
 		}
+		if block_number >= self.eip2929_transition {
+			schedule.eip2929 = true;
+			schedule.sload_gas = schedule.warm_storage_read_cost;
+			schedule.sstore_dirty_gas = Some(schedule.warm_storage_read_cost);
+			schedule.sstore_reset_gas = 5000 - schedule.cold_sload_cost;
+		}
 		if block_number >= self.eip2046_transition {
 			schedule.staticcall_precompile_gas = 40;
 		}
@@ -348,6 +370,22 @@ impl From<ethjson::spec::Params> for CommonParams {
 				BlockNumber::max_value,
 				Into::into,
 			),
+			eip2929_transition: p.eip2929_transition.map_or_else(
+				BlockNumber::max_value,
+				Into::into,
+			),
+			eip2930_transition: p.eip2930_transition.map_or_else(
+				BlockNumber::max_value,
+				Into::into,
+			),
+			eip1559_transition: p.eip1559_transition.map_or_else(
+				BlockNumber::max_value,
+				Into::into,
+			),
+			eip1559_base_fee_max_change_denominator: p.eip1559_base_fee_max_change_denominator.map_or(8.into(), Into::into),
+			eip1559_elasticity_multiplier: p.eip1559_elasticity_multiplier.map_or(2.into(), Into::into),
+			eip1559_base_fee_initial_value: p.eip1559_base_fee_initial_value.map_or(1_000_000_000.into(), Into::into),
+			eip1559_fee_collector: p.eip1559_fee_collector.map(Into::into),
 			dust_protection_transition: p.dust_protection_transition.map_or_else(
 				BlockNumber::max_value,
 				Into::into,
@@ -62,6 +62,9 @@ pub enum BlockError {
 	/// Gas used header field is invalid.
 	#[display(fmt = "Invalid gas used in header: {}", _0)]
 	InvalidGasUsed(Mismatch<U256>),
+	/// Base fee header field is invalid.
+	#[display(fmt = "Invalid base fee in header: {:?}", _0)]
+	InvalidBaseFee(Mismatch<Option<U256>>),
 	/// Transactions root header field is invalid.
 	#[display(fmt = "Invalid transactions root in header: {}", _0)]
 	InvalidTransactionsRoot(Mismatch<H256>),
@@ -18,6 +18,7 @@
 
 use crate::{
 	log_entry::{LogEntry, LocalizedLogEntry},
+	transaction::{typed_envelope, TypedTxId},
 	BlockNumber,
 };
 use ethereum_types::{H160, H256, U256, Address, Bloom};
@@ -46,10 +47,12 @@ pub struct Receipt {
 	pub logs: Vec<LogEntry>,
 	/// Transaction outcome.
 	pub outcome: TransactionOutcome,
+	/// Type of the transaction, typed receipts are encoded like typed transactions (EIP-2718).
+	pub tx_type: TypedTxId,
 }
 
 impl Receipt {
-	/// Create a new receipt.
+	/// Create a new receipt of a legacy transaction.
 	pub fn new(outcome: TransactionOutcome, gas_used: U256, logs: Vec<LogEntry>) -> Self {
 		Self {
 			gas_used,
@@ -59,12 +62,40 @@ impl Receipt {
 			}),
 			logs,
 			outcome,
+			tx_type: TypedTxId::Legacy,
 		}
 	}
-}
 
-impl Encodable for Receipt {
-	fn rlp_append(&self, s: &mut RlpStream) {
+	/// Set the type of the transaction the receipt belongs to.
+	pub fn with_tx_type(mut self, tx_type: TypedTxId) -> Self {
+		self.tx_type = tx_type;
+		self
+	}
+
+	/// The bytes of the receipt as included in the receipts trie: the RLP list for legacy
+	/// transactions and the type byte followed by the RLP list for typed ones.
+	pub fn envelope(&self) -> Vec<u8> {
+		let mut s = RlpStream::new();
+		self.rlp_append_fields(&mut s);
+		match self.tx_type {
+			TypedTxId::Legacy => s.out(),
+			tx_type => typed_envelope(tx_type, &s.out()),
+		}
+	}
+
+	/// Decode a receipt from its envelope, see `envelope`.
+	pub fn from_envelope(bytes: &[u8]) -> Result<Self, DecoderError> {
+		let first = *bytes.first().ok_or(DecoderError::RlpIsTooShort)?;
+		if first >= 0xc0 {
+			return Receipt::decode_fields(&Rlp::new(bytes), TypedTxId::Legacy);
+		}
+		match TypedTxId::from_envelope_byte(first) {
+			Some(tx_type) => Receipt::decode_fields(&Rlp::new(&bytes[1..]), tx_type),
+			None => Err(DecoderError::Custom("Unknown transaction type")),
+		}
+	}
+
+	fn rlp_append_fields(&self, s: &mut RlpStream) {
 		match self.outcome {
 			TransactionOutcome::Unknown => {
 				s.begin_list(3);
@@ -82,16 +113,15 @@ impl Encodable for Receipt {
 		s.append(&self.log_bloom);
 		s.append_list(&self.logs);
 	}
-}
 
-impl Decodable for Receipt {
-	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+	fn decode_fields(rlp: &Rlp, tx_type: TypedTxId) -> Result<Self, DecoderError> {
 		if rlp.item_count()? == 3 {
 			Ok(Receipt {
 				outcome: TransactionOutcome::Unknown,
 				gas_used: rlp.val_at(0)?,
 				log_bloom: rlp.val_at(1)?,
 				logs: rlp.list_at(2)?,
+				tx_type,
 			})
 		} else {
 			Ok(Receipt {
@@ -105,12 +135,33 @@ impl Decodable for Receipt {
 					} else {
 						TransactionOutcome::StateRoot(first.as_val()?)
 					}
-				}
+				},
+				tx_type,
 			})
 		}
 	}
 }
 
+impl Encodable for Receipt {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		// Typed receipts are appended as a string containing their envelope.
+		match self.tx_type {
+			TypedTxId::Legacy => self.rlp_append_fields(s),
+			_ => { s.append(&self.envelope()); },
+		}
+	}
+}
+
+impl Decodable for Receipt {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		if rlp.is_list() {
+			Receipt::decode_fields(rlp, TypedTxId::Legacy)
+		} else {
+			Receipt::from_envelope(rlp.data()?)
+		}
+	}
+}
+
 /// Receipt with additional info.
 #[derive(Debug, Clone, PartialEq)]
 pub struct RichReceipt {
@@ -131,6 +182,10 @@ pub struct RichReceipt {
 	pub log_bloom: Bloom,
 	/// Transaction outcome.
 	pub outcome: TransactionOutcome,
+	/// Type of the transaction.
+	pub transaction_type: TypedTxId,
+	/// The gas price paid by the sender, less than the maximum fee of dynamic fee transactions.
+	pub effective_gas_price: U256,
 	/// Receiver address
 	/// NOTE: It is an Option because only `Action::Call` transactions has a receiver address
 	pub to: Option<H160>,
@@ -162,6 +217,10 @@ pub struct LocalizedReceipt {
 	pub log_bloom: Bloom,
 	/// Transaction outcome.
 	pub outcome: TransactionOutcome,
+	/// Type of the transaction.
+	pub transaction_type: TypedTxId,
+	/// The gas price paid by the sender, less than the maximum fee of dynamic fee transactions.
+	pub effective_gas_price: U256,
 	/// Receiver address
 	/// NOTE: It is an Option because only `Action::Call` transactions has a receiver address
 	pub to: Option<H160>,
@@ -173,7 +232,7 @@ pub struct LocalizedReceipt {
 mod tests {
 	use std::str::FromStr;
 
-	use super::{Receipt, TransactionOutcome, Address, H256};
+	use super::{Receipt, TransactionOutcome, TypedTxId, Address, H256};
 	use crate::log_entry::LogEntry;
 	use rustc_hex::FromHex;
 
@@ -227,4 +286,27 @@ mod tests {
 		let decoded: Receipt = rlp::decode(&encoded).expect("decoding receipt failed");
 		assert_eq!(decoded, r);
 	}
+
+	#[test]
+	fn test_typed_receipt() {
+		let r = Receipt::new(
+			TransactionOutcome::StatusCode(1),
+			0x40cae.into(),
+			vec![LogEntry {
+				address: Address::from_str("dcf421d093428b096ca501a7cd1a740855a7976f").unwrap(),
+				topics: vec![],
+				data: vec![0u8; 32]
+			}]
+		).with_tx_type(TypedTxId::AccessList);
+
+		let envelope = r.envelope();
+		assert_eq!(envelope[0], 0x01);
+		assert_eq!(&envelope[1..], &Receipt { tx_type: TypedTxId::Legacy, ..r.clone() }.envelope()[..]);
+		assert_eq!(Receipt::from_envelope(&envelope).expect("decoding receipt failed"), r);
+
+		let encoded = rlp::encode(&r);
+		assert_eq!(&encoded[..], &rlp::encode(&envelope)[..]);
+		let decoded: Receipt = rlp::decode(&encoded).expect("decoding receipt failed");
+		assert_eq!(decoded, r);
+	}
 }
@@ -36,6 +36,9 @@ pub enum Snapshotting {
 	},
 	/// Snapshots for proof-of-authority chains
 	PoA,
+	/// Snapshots for hbbft chains, verifying the epoch transitions against the threshold
+	/// public keys of the validator sets.
+	Hbbft,
 }
 
 /// A progress indicator for snapshots.
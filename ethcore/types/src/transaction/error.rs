@@ -85,6 +85,15 @@ pub enum Error {
 	TooBig,
 	/// Invalid RLP encoding
 	InvalidRlp(String),
+	/// Transaction type is not enabled on this chain yet.
+	TransactionTypeNotEnabled,
+	/// The maximum priority fee of the transaction is higher than its maximum fee (EIP-1559).
+	PriorityFeeAboveMaxFee {
+		/// Maximum fee per gas of the transaction.
+		max_fee: U256,
+		/// Maximum priority fee per gas of the transaction.
+		max_priority_fee: U256,
+	},
 }
 
 impl From<EthPublicKeyCryptoError> for Error {
@@ -128,6 +137,9 @@ impl fmt::Display for Error {
 			NotAllowed => "Sender does not have permissions to execute this type of transaction".into(),
 			TooBig => "Transaction too big".into(),
 			InvalidRlp(ref err) => format!("Transaction has invalid RLP structure: {}.", err),
+			TransactionTypeNotEnabled => "Transaction type is not enabled for the current block".into(),
+			PriorityFeeAboveMaxFee { max_fee, max_priority_fee } =>
+				format!("Max priority fee per gas {} is higher than max fee per gas {}", max_priority_fee, max_fee),
 		};
 
 		f.write_fmt(format_args!("Transaction error ({})", msg))
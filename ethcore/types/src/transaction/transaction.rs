@@ -17,6 +17,7 @@
 //! Transaction data structure.
 
 use std::{
+	cmp,
 	ops::Deref,
 	hash::{Hash, Hasher},
 };
@@ -105,6 +106,84 @@ pub mod signature {
 	}
 }
 
+/// Type of a transaction, the first byte of a typed transaction envelope (EIP-2718).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, MallocSizeOf)]
+pub enum TypedTxId {
+	/// Transaction with an access list (EIP-2930).
+	AccessList = 0x01,
+	/// Transaction with a dynamic fee and an access list (EIP-1559).
+	EIP1559Transaction = 0x02,
+	/// Transaction predating EIP-2718, encoded as a plain RLP list.
+	Legacy = 0x80,
+}
+
+impl Default for TypedTxId {
+	fn default() -> TypedTxId { TypedTxId::Legacy }
+}
+
+impl TypedTxId {
+	/// The type of a typed transaction envelope starting with the given byte, if it is known.
+	pub fn from_envelope_byte(n: u8) -> Option<TypedTxId> {
+		match n {
+			0x01 => Some(TypedTxId::AccessList),
+			0x02 => Some(TypedTxId::EIP1559Transaction),
+			_ => None,
+		}
+	}
+
+	/// The type with the given number as used in RPC, where legacy transactions are type 0.
+	pub fn from_u64(n: u64) -> Option<TypedTxId> {
+		match n {
+			0 => Some(TypedTxId::Legacy),
+			0x01 => Some(TypedTxId::AccessList),
+			0x02 => Some(TypedTxId::EIP1559Transaction),
+			_ => None,
+		}
+	}
+
+	/// The number of the type as used in RPC, where legacy transactions are type 0.
+	pub fn to_u64(&self) -> u64 {
+		match *self {
+			TypedTxId::Legacy => 0,
+			TypedTxId::AccessList => 0x01,
+			TypedTxId::EIP1559Transaction => 0x02,
+		}
+	}
+}
+
+/// An account and the storage keys of it a transaction plans to access (EIP-2930).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, RlpEncodable, RlpDecodable, MallocSizeOf)]
+pub struct AccessListItem {
+	/// Accessed account.
+	pub address: Address,
+	/// Accessed storage keys of the account.
+	pub storage_keys: Vec<H256>,
+}
+
+/// Accounts and storage keys a transaction plans to access (EIP-2930).
+pub type AccessList = Vec<AccessListItem>;
+
+/// Returns the bytes of a transaction or receipt as hashed and included in the tries of a block,
+/// given the RLP item it is encoded as in a list of them.
+///
+/// Legacy items are RLP lists which are included as they are. Typed items are wrapped in an RLP
+/// string, whose payload is the type byte followed by the RLP encoded fields (EIP-2718).
+pub fn envelope_of<'a>(item: &Rlp<'a>) -> &'a [u8] {
+	if item.is_list() {
+		item.as_raw()
+	} else {
+		item.data().unwrap_or_else(|_| item.as_raw())
+	}
+}
+
+/// Prepends the type byte to the RLP encoded fields of a typed transaction or receipt.
+pub fn typed_envelope(tx_type: TypedTxId, payload: &[u8]) -> Bytes {
+	let mut envelope = Vec::with_capacity(payload.len() + 1);
+	envelope.push(tx_type as u8);
+	envelope.extend_from_slice(payload);
+	envelope
+}
+
 /// A set of information describing an externally-originating message call
 /// or contract creation operation.
 #[derive(Default, Debug, Clone, PartialEq, Eq, MallocSizeOf)]
@@ -123,6 +202,245 @@ pub struct Transaction {
 	pub data: Bytes,
 }
 
+/// A transaction with an access list (EIP-2930).
+#[derive(Default, Debug, Clone, PartialEq, Eq, MallocSizeOf)]
+pub struct AccessListTx {
+	/// The fields shared with legacy transactions.
+	pub transaction: Transaction,
+	/// Accounts and storage keys which are warm from the start of the execution.
+	pub access_list: AccessList,
+}
+
+impl AccessListTx {
+	/// Create a new access list transaction.
+	pub fn new(transaction: Transaction, access_list: AccessList) -> Self {
+		AccessListTx { transaction, access_list }
+	}
+
+	/// Append the fields of the transaction, followed by the signature if given, into RLP stream
+	fn rlp_append_payload(&self, s: &mut RlpStream, chain_id: u64, signature: Option<(u64, &U256, &U256)>) {
+		s.begin_list(if signature.is_none() { 8 } else { 11 });
+		s.append(&chain_id);
+		s.append(&self.transaction.nonce);
+		s.append(&self.transaction.gas_price);
+		s.append(&self.transaction.gas);
+		s.append(&self.transaction.action);
+		s.append(&self.transaction.value);
+		s.append(&self.transaction.data);
+		s.append_list(&self.access_list);
+		if let Some((y_parity, r, sig_s)) = signature {
+			s.append(&y_parity);
+			s.append(r);
+			s.append(sig_s);
+		}
+	}
+}
+
+/// A transaction with a dynamic fee (EIP-1559).
+///
+/// The gas price of the wrapped transaction is the maximum fee per gas the sender pays, the base
+/// fee of the block included.
+#[derive(Default, Debug, Clone, PartialEq, Eq, MallocSizeOf)]
+pub struct EIP1559TransactionTx {
+	/// The fields shared with access list transactions.
+	pub transaction: AccessListTx,
+	/// Maximum fee per gas paid to the block author on top of the base fee.
+	pub max_priority_fee_per_gas: U256,
+}
+
+impl EIP1559TransactionTx {
+	/// Create a new dynamic fee transaction, `transaction.gas_price` is the maximum fee per gas.
+	pub fn new(transaction: Transaction, access_list: AccessList, max_priority_fee_per_gas: U256) -> Self {
+		EIP1559TransactionTx {
+			transaction: AccessListTx::new(transaction, access_list),
+			max_priority_fee_per_gas,
+		}
+	}
+
+	/// Append the fields of the transaction, followed by the signature if given, into RLP stream
+	fn rlp_append_payload(&self, s: &mut RlpStream, chain_id: u64, signature: Option<(u64, &U256, &U256)>) {
+		let tx = &self.transaction.transaction;
+		s.begin_list(if signature.is_none() { 9 } else { 12 });
+		s.append(&chain_id);
+		s.append(&tx.nonce);
+		s.append(&self.max_priority_fee_per_gas);
+		s.append(&tx.gas_price);
+		s.append(&tx.gas);
+		s.append(&tx.action);
+		s.append(&tx.value);
+		s.append(&tx.data);
+		s.append_list(&self.transaction.access_list);
+		if let Some((y_parity, r, sig_s)) = signature {
+			s.append(&y_parity);
+			s.append(r);
+			s.append(sig_s);
+		}
+	}
+}
+
+/// A transaction of any of the supported types, before signing.
+#[derive(Debug, Clone, PartialEq, Eq, MallocSizeOf)]
+pub enum TypedTransaction {
+	/// Transaction predating EIP-2718.
+	Legacy(Transaction),
+	/// Transaction with an access list (EIP-2930).
+	AccessList(AccessListTx),
+	/// Transaction with a dynamic fee (EIP-1559).
+	EIP1559Transaction(EIP1559TransactionTx),
+}
+
+impl From<Transaction> for TypedTransaction {
+	fn from(tx: Transaction) -> Self {
+		TypedTransaction::Legacy(tx)
+	}
+}
+
+impl TypedTransaction {
+	/// The type of the transaction.
+	pub fn tx_type(&self) -> TypedTxId {
+		match *self {
+			TypedTransaction::Legacy(_) => TypedTxId::Legacy,
+			TypedTransaction::AccessList(_) => TypedTxId::AccessList,
+			TypedTransaction::EIP1559Transaction(_) => TypedTxId::EIP1559Transaction,
+		}
+	}
+
+	/// The fields shared by all transaction types.
+	pub fn tx(&self) -> &Transaction {
+		match *self {
+			TypedTransaction::Legacy(ref tx) => tx,
+			TypedTransaction::AccessList(ref tx) => &tx.transaction,
+			TypedTransaction::EIP1559Transaction(ref tx) => &tx.transaction.transaction,
+		}
+	}
+
+	/// The fields shared by all transaction types.
+	pub fn tx_mut(&mut self) -> &mut Transaction {
+		match *self {
+			TypedTransaction::Legacy(ref mut tx) => tx,
+			TypedTransaction::AccessList(ref mut tx) => &mut tx.transaction,
+			TypedTransaction::EIP1559Transaction(ref mut tx) => &mut tx.transaction.transaction,
+		}
+	}
+
+	/// The access list of the transaction, if its type has one.
+	pub fn access_list(&self) -> Option<&AccessList> {
+		match *self {
+			TypedTransaction::Legacy(_) => None,
+			TypedTransaction::AccessList(ref tx) => Some(&tx.access_list),
+			TypedTransaction::EIP1559Transaction(ref tx) => Some(&tx.transaction.access_list),
+		}
+	}
+
+	/// The maximum fee per gas paid to the block author on top of the base fee. The gas price for
+	/// transactions without a dynamic fee.
+	pub fn max_priority_fee_per_gas(&self) -> U256 {
+		match *self {
+			TypedTransaction::EIP1559Transaction(ref tx) => tx.max_priority_fee_per_gas,
+			_ => self.tx().gas_price,
+		}
+	}
+
+	/// The gas price paid by the sender in a block with the given base fee.
+	pub fn effective_gas_price(&self, base_fee: Option<U256>) -> U256 {
+		match (self, base_fee) {
+			(&TypedTransaction::EIP1559Transaction(ref tx), Some(base_fee)) => cmp::min(
+				tx.transaction.transaction.gas_price,
+				base_fee.saturating_add(tx.max_priority_fee_per_gas),
+			),
+			_ => self.tx().gas_price,
+		}
+	}
+
+	/// The gas price paid to the block author in a block with the given base fee, the effective
+	/// gas price less the base fee.
+	pub fn effective_priority_fee(&self, base_fee: Option<U256>) -> U256 {
+		self.effective_gas_price(base_fee).saturating_sub(base_fee.unwrap_or_default())
+	}
+
+	/// The message hash of the transaction.
+	/// Typed transactions always include a chain ID, `None` is signed as chain ID 0.
+	pub fn hash(&self, chain_id: Option<u64>) -> H256 {
+		match *self {
+			TypedTransaction::Legacy(ref tx) => tx.hash(chain_id),
+			TypedTransaction::AccessList(ref tx) => {
+				let mut stream = RlpStream::new();
+				tx.rlp_append_payload(&mut stream, chain_id.unwrap_or(0), None);
+				keccak(typed_envelope(TypedTxId::AccessList, &stream.out()))
+			}
+			TypedTransaction::EIP1559Transaction(ref tx) => {
+				let mut stream = RlpStream::new();
+				tx.rlp_append_payload(&mut stream, chain_id.unwrap_or(0), None);
+				keccak(typed_envelope(TypedTxId::EIP1559Transaction, &stream.out()))
+			}
+		}
+	}
+
+	/// Signs the transaction as coming from `sender`.
+	pub fn sign(self, secret: &Secret, chain_id: Option<u64>) -> SignedTransaction {
+		let sig = parity_crypto::publickey::sign(secret, &self.hash(chain_id))
+			.expect("data is valid and context has signing capabilities; qed");
+		SignedTransaction::new(self.with_signature(sig, chain_id))
+			.expect("secret is valid so it's recoverable")
+	}
+
+	/// Signs the transaction with signature.
+	pub fn with_signature(self, sig: Signature, chain_id: Option<u64>) -> UnverifiedTransaction {
+		UnverifiedTransaction {
+			unsigned: self,
+			r: sig.r().into(),
+			s: sig.s().into(),
+			v: signature::add_chain_replay_protection(sig.v() as u64, chain_id),
+			hash: H256::zero(),
+		}.compute_hash()
+	}
+
+	/// Useful for test incorrectly signed transactions.
+	#[cfg(test)]
+	pub fn invalid_sign(self) -> UnverifiedTransaction {
+		UnverifiedTransaction {
+			unsigned: self,
+			r: U256::one(),
+			s: U256::one(),
+			v: 0,
+			hash: H256::zero(),
+		}.compute_hash()
+	}
+
+	/// Specify the sender; this won't survive the serialize/deserialize process, but can be cloned.
+	pub fn fake_sign(self, from: Address) -> SignedTransaction {
+		SignedTransaction {
+			transaction: UnverifiedTransaction {
+				unsigned: self,
+				r: U256::one(),
+				s: U256::one(),
+				v: 0,
+				hash: H256::zero(),
+			}.compute_hash(),
+			sender: from,
+			public: None,
+		}
+	}
+
+	/// Legacy EIP-86 compatible empty signature.
+	/// This method is used in json tests as well as
+	/// signature verification tests.
+	#[cfg(any(test, feature = "test-helpers"))]
+	pub fn null_sign(self, chain_id: u64) -> SignedTransaction {
+		SignedTransaction {
+			transaction: UnverifiedTransaction {
+				unsigned: self,
+				r: U256::zero(),
+				s: U256::zero(),
+				v: chain_id,
+				hash: H256::zero(),
+			}.compute_hash(),
+			sender: UNSIGNED_SENDER,
+			public: None,
+		}
+	}
+}
+
 impl Transaction {
 	/// Append object with a without signature into RLP stream
 	pub fn rlp_append_unsigned_transaction(&self, s: &mut RlpStream, chain_id: Option<u64>) {
@@ -168,7 +486,7 @@ impl From<ethjson::transaction::Transaction> for UnverifiedTransaction {
 	fn from(t: ethjson::transaction::Transaction) -> Self {
 		let to: Option<ethjson::hash::Address> = t.to.into();
 		UnverifiedTransaction {
-			unsigned: Transaction {
+			unsigned: TypedTransaction::Legacy(Transaction {
 				nonce: t.nonce.into(),
 				gas_price: t.gas_price.into(),
 				gas: t.gas_limit.into(),
@@ -178,7 +496,7 @@ impl From<ethjson::transaction::Transaction> for UnverifiedTransaction {
 				},
 				value: t.value.into(),
 				data: t.data.into(),
-			},
+			}),
 			r: t.r.into(),
 			s: t.s.into(),
 			v: t.v.into(),
@@ -197,48 +515,23 @@ impl Transaction {
 
 	/// Signs the transaction as coming from `sender`.
 	pub fn sign(self, secret: &Secret, chain_id: Option<u64>) -> SignedTransaction {
-		let sig = parity_crypto::publickey::sign(secret, &self.hash(chain_id))
-			.expect("data is valid and context has signing capabilities; qed");
-		SignedTransaction::new(self.with_signature(sig, chain_id))
-			.expect("secret is valid so it's recoverable")
+		TypedTransaction::Legacy(self).sign(secret, chain_id)
 	}
 
 	/// Signs the transaction with signature.
 	pub fn with_signature(self, sig: Signature, chain_id: Option<u64>) -> UnverifiedTransaction {
-		UnverifiedTransaction {
-			unsigned: self,
-			r: sig.r().into(),
-			s: sig.s().into(),
-			v: signature::add_chain_replay_protection(sig.v() as u64, chain_id),
-			hash: H256::zero(),
-		}.compute_hash()
+		TypedTransaction::Legacy(self).with_signature(sig, chain_id)
 	}
 
 	/// Useful for test incorrectly signed transactions.
 	#[cfg(test)]
 	pub fn invalid_sign(self) -> UnverifiedTransaction {
-		UnverifiedTransaction {
-			unsigned: self,
-			r: U256::one(),
-			s: U256::one(),
-			v: 0,
-			hash: H256::zero(),
-		}.compute_hash()
+		TypedTransaction::Legacy(self).invalid_sign()
 	}
 
 	/// Specify the sender; this won't survive the serialize/deserialize process, but can be cloned.
 	pub fn fake_sign(self, from: Address) -> SignedTransaction {
-		SignedTransaction {
-			transaction: UnverifiedTransaction {
-				unsigned: self,
-				r: U256::one(),
-				s: U256::one(),
-				v: 0,
-				hash: H256::zero(),
-			}.compute_hash(),
-			sender: from,
-			public: None,
-		}
+		TypedTransaction::Legacy(self).fake_sign(from)
 	}
 
 	/// Legacy EIP-86 compatible empty signature.
@@ -246,17 +539,7 @@ impl Transaction {
 	/// signature verification tests.
 	#[cfg(any(test, feature = "test-helpers"))]
 	pub fn null_sign(self, chain_id: u64) -> SignedTransaction {
-		SignedTransaction {
-			transaction: UnverifiedTransaction {
-				unsigned: self,
-				r: U256::zero(),
-				s: U256::zero(),
-				v: chain_id,
-				hash: H256::zero(),
-			}.compute_hash(),
-			sender: UNSIGNED_SENDER,
-			public: None,
-		}
+		TypedTransaction::Legacy(self).null_sign(chain_id)
 	}
 }
 
@@ -264,9 +547,11 @@ impl Transaction {
 #[derive(Debug, Clone, Eq, PartialEq, MallocSizeOf)]
 pub struct UnverifiedTransaction {
 	/// Plain Transaction.
-	unsigned: Transaction,
+	unsigned: TypedTransaction,
 	/// The V field of the signature; the LS bit described which half of the curve our point falls
 	/// in. The MS bits describe which chain this transaction is for. If 27/28, its for all chains.
+	/// Typed transactions encode the chain ID and the y parity separately, they are combined into
+	/// `v` the same way to handle all transaction types alike.
 	v: u64,
 	/// The R field of the signature; helps describe the point on the curve.
 	r: U256,
@@ -280,7 +565,7 @@ impl Deref for UnverifiedTransaction {
 	type Target = Transaction;
 
 	fn deref(&self) -> &Self::Target {
-		&self.unsigned
+		self.unsigned.tx()
 	}
 }
 
@@ -292,37 +577,157 @@ impl Hash for UnverifiedTransaction {
 
 impl rlp::Decodable for UnverifiedTransaction {
 	fn decode(d: &Rlp) -> Result<Self, DecoderError> {
+		if d.is_list() {
+			UnverifiedTransaction::decode_legacy(d)
+		} else {
+			UnverifiedTransaction::from_envelope(d.data()?)
+		}
+	}
+}
+
+impl rlp::Encodable for UnverifiedTransaction {
+	fn rlp_append(&self, s: &mut RlpStream) { self.rlp_append_sealed_transaction(s) }
+}
+
+impl UnverifiedTransaction {
+	/// Used to compute hash of created transactions
+	fn compute_hash(mut self) -> UnverifiedTransaction {
+		let hash = keccak(&self.envelope());
+		self.hash = hash;
+		self
+	}
+
+	fn decode_legacy(d: &Rlp) -> Result<Self, DecoderError> {
 		if d.item_count()? != 9 {
 			return Err(DecoderError::RlpIncorrectListLen);
 		}
 		let hash = keccak(d.as_raw());
 		Ok(UnverifiedTransaction {
-			unsigned: Transaction {
+			unsigned: TypedTransaction::Legacy(Transaction {
 				nonce: d.val_at(0)?,
 				gas_price: d.val_at(1)?,
 				gas: d.val_at(2)?,
 				action: d.val_at(3)?,
 				value: d.val_at(4)?,
 				data: d.val_at(5)?,
-			},
+			}),
 			v: d.val_at(6)?,
 			r: d.val_at(7)?,
 			s: d.val_at(8)?,
 			hash,
 		})
 	}
-}
 
-impl rlp::Encodable for UnverifiedTransaction {
-	fn rlp_append(&self, s: &mut RlpStream) { self.rlp_append_sealed_transaction(s) }
-}
+	/// Decode a transaction from its envelope, i.e. the bytes which are hashed and included in
+	/// the transactions trie. These are the RLP list for legacy transactions and the type byte
+	/// followed by the RLP encoded fields for typed transactions.
+	pub fn from_envelope(bytes: &[u8]) -> Result<Self, DecoderError> {
+		let first = *bytes.first().ok_or(DecoderError::RlpIsTooShort)?;
+		if first >= 0xc0 {
+			return UnverifiedTransaction::decode_legacy(&Rlp::new(bytes));
+		}
+		match TypedTxId::from_envelope_byte(first) {
+			Some(TypedTxId::AccessList) => {
+				let d = Rlp::new(&bytes[1..]);
+				if d.item_count()? != 11 {
+					return Err(DecoderError::RlpIncorrectListLen);
+				}
+				let chain_id: u64 = d.val_at(0)?;
+				let y_parity: u64 = d.val_at(8)?;
+				if y_parity > 1 || chain_id > (u64::max_value() - 36) / 2 {
+					return Err(DecoderError::Custom("Invalid signature of typed transaction"));
+				}
+				Ok(UnverifiedTransaction {
+					unsigned: TypedTransaction::AccessList(AccessListTx {
+						transaction: Transaction {
+							nonce: d.val_at(1)?,
+							gas_price: d.val_at(2)?,
+							gas: d.val_at(3)?,
+							action: d.val_at(4)?,
+							value: d.val_at(5)?,
+							data: d.val_at(6)?,
+						},
+						access_list: d.list_at(7)?,
+					}),
+					v: signature::add_chain_replay_protection(y_parity, Some(chain_id)),
+					r: d.val_at(9)?,
+					s: d.val_at(10)?,
+					hash: keccak(bytes),
+				})
+			}
+			Some(TypedTxId::EIP1559Transaction) => {
+				let d = Rlp::new(&bytes[1..]);
+				if d.item_count()? != 12 {
+					return Err(DecoderError::RlpIncorrectListLen);
+				}
+				let chain_id: u64 = d.val_at(0)?;
+				let y_parity: u64 = d.val_at(9)?;
+				if y_parity > 1 || chain_id > (u64::max_value() - 36) / 2 {
+					return Err(DecoderError::Custom("Invalid signature of typed transaction"));
+				}
+				Ok(UnverifiedTransaction {
+					unsigned: TypedTransaction::EIP1559Transaction(EIP1559TransactionTx {
+						transaction: AccessListTx {
+							transaction: Transaction {
+								nonce: d.val_at(1)?,
+								gas_price: d.val_at(3)?,
+								gas: d.val_at(4)?,
+								action: d.val_at(5)?,
+								value: d.val_at(6)?,
+								data: d.val_at(7)?,
+							},
+							access_list: d.list_at(8)?,
+						},
+						max_priority_fee_per_gas: d.val_at(2)?,
+					}),
+					v: signature::add_chain_replay_protection(y_parity, Some(chain_id)),
+					r: d.val_at(10)?,
+					s: d.val_at(11)?,
+					hash: keccak(bytes),
+				})
+			}
+			_ => Err(DecoderError::Custom("Unknown transaction type")),
+		}
+	}
 
-impl UnverifiedTransaction {
-	/// Used to compute hash of created transactions
-	fn compute_hash(mut self) -> UnverifiedTransaction {
-		let hash = keccak(&*self.rlp_bytes());
-		self.hash = hash;
-		self
+	/// The envelope of the transaction, see `from_envelope`.
+	pub fn envelope(&self) -> Bytes {
+		let mut stream = RlpStream::new();
+		let signature = (self.standard_v() as u64, &self.r, &self.s);
+		match self.unsigned {
+			TypedTransaction::Legacy(_) => return self.rlp_bytes(),
+			TypedTransaction::AccessList(ref tx) =>
+				tx.rlp_append_payload(&mut stream, self.chain_id().unwrap_or(0), Some(signature)),
+			TypedTransaction::EIP1559Transaction(ref tx) =>
+				tx.rlp_append_payload(&mut stream, self.chain_id().unwrap_or(0), Some(signature)),
+		}
+		typed_envelope(self.tx_type(), &stream.out())
+	}
+
+	/// The type of the transaction.
+	pub fn tx_type(&self) -> TypedTxId {
+		self.unsigned.tx_type()
+	}
+
+	/// The access list of the transaction, if its type has one.
+	pub fn access_list(&self) -> Option<&AccessList> {
+		self.unsigned.access_list()
+	}
+
+	/// The maximum fee per gas paid to the block author, see `TypedTransaction`.
+	pub fn max_priority_fee_per_gas(&self) -> U256 {
+		self.unsigned.max_priority_fee_per_gas()
+	}
+
+	/// The gas price paid in a block with the given base fee, see `TypedTransaction`.
+	pub fn effective_gas_price(&self, base_fee: Option<U256>) -> U256 {
+		self.unsigned.effective_gas_price(base_fee)
+	}
+
+	/// The gas price paid to the block author in a block with the given base fee, see
+	/// `TypedTransaction`.
+	pub fn effective_priority_fee(&self, base_fee: Option<U256>) -> U256 {
+		self.unsigned.effective_priority_fee(base_fee)
 	}
 
 	/// Returns transaction receiver, if any
@@ -333,8 +738,13 @@ impl UnverifiedTransaction {
 		}
 	}
 
-	/// Append object with a signature into RLP stream
+	/// Append object with a signature into RLP stream.
+	/// Typed transactions are appended as a string containing their envelope.
 	fn rlp_append_sealed_transaction(&self, s: &mut RlpStream) {
+		if self.tx_type() != TypedTxId::Legacy {
+			s.append(&self.envelope());
+			return;
+		}
 		s.begin_list(9);
 		s.append(&self.nonce);
 		s.append(&self.gas_price);
@@ -348,15 +758,20 @@ impl UnverifiedTransaction {
 	}
 
 	///	Reference to unsigned part of this transaction.
-	pub fn as_unsigned(&self) -> &Transaction {
+	pub fn as_unsigned(&self) -> &TypedTransaction {
 		&self.unsigned
 	}
 
 	/// Returns standardized `v` value (0, 1 or 4 (invalid))
 	pub fn standard_v(&self) -> u8 { signature::check_replay_protection(self.v) }
 
-	/// The `v` value that appears in the RLP.
-	pub fn original_v(&self) -> u64 { self.v }
+	/// The `v` value that appears in the RLP, the y parity for typed transactions.
+	pub fn original_v(&self) -> u64 {
+		match self.unsigned {
+			TypedTransaction::Legacy(_) => self.v,
+			_ => self.standard_v() as u64,
+		}
+	}
 
 	/// The chain ID, or `None` if this is a global transaction.
 	pub fn chain_id(&self) -> Option<u64> {
@@ -664,6 +1079,89 @@ mod tests {
 		assert_eq!(t.chain_id(), Some(69));
 	}
 
+	#[test]
+	fn access_list_transaction_roundtrip() {
+		use parity_crypto::publickey::{Random, Generator};
+
+		let key = Random.generate();
+		let t = TypedTransaction::AccessList(AccessListTx::new(Transaction {
+			action: Action::Call(Address::from_low_u64_be(0x42)),
+			nonce: U256::from(42),
+			gas_price: U256::from(3000),
+			gas: U256::from(50_000),
+			value: U256::from(1),
+			data: b"Hello!".to_vec()
+		}, vec![AccessListItem {
+			address: Address::from_low_u64_be(0x42),
+			storage_keys: vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)],
+		}])).sign(&key.secret(), Some(69));
+		assert_eq!(t.tx_type(), TypedTxId::AccessList);
+		assert_eq!(t.chain_id(), Some(69));
+
+		let envelope = t.envelope();
+		assert_eq!(envelope[0], 0x01);
+		assert_eq!(t.hash(), keccak(&envelope));
+		assert!(t.original_v() <= 1);
+
+		// The envelope is wrapped into a string in lists of transactions.
+		let encoded = rlp::encode(&t.transaction);
+		assert_eq!(envelope_of(&Rlp::new(&encoded)), &envelope[..]);
+		let decoded: UnverifiedTransaction = rlp::decode(&encoded).unwrap();
+		assert_eq!(decoded, t.transaction);
+
+		let decoded = UnverifiedTransaction::from_envelope(&envelope).unwrap();
+		assert_eq!(decoded.hash(), t.hash());
+		assert_eq!(decoded.access_list(), t.access_list());
+		assert_eq!(SignedTransaction::new(decoded).unwrap().sender(), t.sender());
+	}
+
+	#[test]
+	fn eip1559_transaction_roundtrip() {
+		use parity_crypto::publickey::{Random, Generator};
+
+		let key = Random.generate();
+		let t = TypedTransaction::EIP1559Transaction(EIP1559TransactionTx::new(Transaction {
+			action: Action::Call(Address::from_low_u64_be(0x42)),
+			nonce: U256::from(42),
+			gas_price: U256::from(3000),
+			gas: U256::from(50_000),
+			value: U256::from(1),
+			data: b"Hello!".to_vec()
+		}, vec![], U256::from(100))).sign(&key.secret(), Some(69));
+		assert_eq!(t.tx_type(), TypedTxId::EIP1559Transaction);
+		assert_eq!(t.max_priority_fee_per_gas(), U256::from(100));
+
+		let envelope = t.envelope();
+		assert_eq!(envelope[0], 0x02);
+		assert_eq!(t.hash(), keccak(&envelope));
+
+		let decoded = UnverifiedTransaction::from_envelope(&envelope).unwrap();
+		assert_eq!(decoded, t.transaction);
+		assert_eq!(SignedTransaction::new(decoded).unwrap().sender(), t.sender());
+
+		// The sender pays the base fee and the tip, up to the maximum fee.
+		assert_eq!(t.effective_gas_price(None), U256::from(3000));
+		assert_eq!(t.effective_gas_price(Some(U256::from(1000))), U256::from(1100));
+		assert_eq!(t.effective_priority_fee(Some(U256::from(1000))), U256::from(100));
+		assert_eq!(t.effective_gas_price(Some(U256::from(2950))), U256::from(3000));
+		assert_eq!(t.effective_priority_fee(Some(U256::from(2950))), U256::from(50));
+	}
+
+	#[test]
+	fn legacy_envelope_is_rlp_list() {
+		let t = Transaction {
+			action: Action::Create,
+			nonce: U256::from(42),
+			gas_price: U256::from(3000),
+			gas: U256::from(50_000),
+			value: U256::from(1),
+			data: b"Hello!".to_vec()
+		}.fake_sign(Address::from_low_u64_be(0x69));
+		assert_eq!(t.tx_type(), TypedTxId::Legacy);
+		assert_eq!(t.envelope(), rlp::encode(&t.transaction));
+		assert!(UnverifiedTransaction::from_envelope(&[0x7f, 0xc0]).is_err());
+	}
+
 	#[test]
 	fn should_agree_with_vitalik() {
 		let test_vector = |tx_data: &str, address: &'static str| {
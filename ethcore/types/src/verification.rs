@@ -17,6 +17,7 @@
 //! Verification types
 
 use crate::{
+	BlockNumber,
 	header::Header,
 	transaction::UnverifiedTransaction,
 };
@@ -70,14 +71,15 @@ pub struct Unverified {
 }
 
 impl Unverified {
-	/// Create an `Unverified` from raw bytes.
-	pub fn from_rlp(bytes: Bytes) -> Result<Self, rlp::DecoderError> {
+	/// Create an `Unverified` from raw bytes, with the base fee in headers starting at
+	/// `eip1559_transition`.
+	pub fn from_rlp(bytes: Bytes, eip1559_transition: BlockNumber) -> Result<Self, rlp::DecoderError> {
 		use rlp::Rlp;
 		let (header, transactions, uncles) = {
 			let rlp = Rlp::new(&bytes);
-			let header = rlp.val_at(0)?;
+			let header = Header::decode_rlp(&rlp.at(0)?, eip1559_transition)?;
 			let transactions = rlp.list_at(1)?;
-			let uncles = rlp.list_at(2)?;
+			let uncles = Header::decode_rlp_list(&rlp.at(2)?, eip1559_transition)?;
 			(header, transactions, uncles)
 		};
 
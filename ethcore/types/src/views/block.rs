@@ -18,8 +18,9 @@
 
 use super::ViewRlp;
 use crate::{
+	BlockNumber,
 	header::Header,
-	transaction::{UnverifiedTransaction, LocalizedTransaction},
+	transaction::{envelope_of, UnverifiedTransaction, LocalizedTransaction},
 	views::{TransactionView, HeaderView}
 };
 use bytes::Bytes;
@@ -65,8 +66,8 @@ impl<'a> BlockView<'a> {
 	}
 
 	/// Create new Header object from header rlp.
-	pub fn header(&self) -> Header {
-		self.rlp.val_at(0)
+	pub fn header(&self, eip1559_transition: BlockNumber) -> Header {
+		self.rlp.expect_valid_rlp(Header::decode_rlp(&self.rlp.at(0).rlp, eip1559_transition))
 	}
 
 	/// Return header rlp.
@@ -118,7 +119,7 @@ impl<'a> BlockView<'a> {
 
 	/// Return transaction hashes.
 	pub fn transaction_hashes(&self) -> Vec<H256> {
-		self.transactions_rlp().iter().map(|rlp| keccak(rlp.as_raw())).collect()
+		self.transactions_rlp().iter().map(|rlp| keccak(envelope_of(&rlp.rlp))).collect()
 	}
 
 	/// Returns transaction at given index without deserializing unnecessary data.
@@ -146,8 +147,8 @@ impl<'a> BlockView<'a> {
 	}
 
 	/// Return list of uncles of given block.
-	pub fn uncles(&self) -> Vec<Header> {
-		self.rlp.list_at(2)
+	pub fn uncles(&self, eip1559_transition: BlockNumber) -> Vec<Header> {
+		self.rlp.expect_valid_rlp(Header::decode_rlp_list(&self.rlp.at(2).rlp, eip1559_transition))
 	}
 
 	/// Return number of uncles in given block, without deserializing them.
@@ -166,8 +167,9 @@ impl<'a> BlockView<'a> {
 	}
 
 	/// Return nth uncle.
-	pub fn uncle_at(&self, index: usize) -> Option<Header> {
-		self.uncles_rlp().iter().nth(index).map(|rlp| rlp.as_val())
+	pub fn uncle_at(&self, index: usize, eip1559_transition: BlockNumber) -> Option<Header> {
+		self.uncles_rlp().iter().nth(index)
+			.map(|rlp| rlp.expect_valid_rlp(Header::decode_rlp(&rlp.rlp, eip1559_transition)))
 	}
 
 	/// Return nth uncle rlp.
@@ -20,7 +20,7 @@ use super::ViewRlp;
 use crate::{
 	BlockNumber,
 	header::Header,
-	transaction::{LocalizedTransaction, UnverifiedTransaction},
+	transaction::{envelope_of, LocalizedTransaction, UnverifiedTransaction},
 	views::{TransactionView, HeaderView},
 };
 use bytes::Bytes;
@@ -95,7 +95,7 @@ impl<'a> BodyView<'a> {
 
 	/// Return transaction hashes.
 	pub fn transaction_hashes(&self) -> Vec<H256> {
-		self.transactions_rlp().iter().map(|rlp| keccak(rlp.as_raw())).collect()
+		self.transactions_rlp().iter().map(|rlp| keccak(envelope_of(&rlp.rlp))).collect()
 	}
 
 	/// Returns transaction at given index without deserializing unnecessary data.
@@ -120,8 +120,8 @@ impl<'a> BodyView<'a> {
 	}
 
 	/// Return list of uncles of given block.
-	pub fn uncles(&self) -> Vec<Header> {
-		self.rlp.list_at(1)
+	pub fn uncles(&self, eip1559_transition: BlockNumber) -> Vec<Header> {
+		self.rlp.expect_valid_rlp(Header::decode_rlp_list(&self.rlp.at(1).rlp, eip1559_transition))
 	}
 
 	/// Return number of uncles in given block, without deserializing them.
@@ -140,8 +140,9 @@ impl<'a> BodyView<'a> {
 	}
 
 	/// Return nth uncle.
-	pub fn uncle_at(&self, index: usize) -> Option<Header> {
-		self.uncles_rlp().iter().nth(index).map(|rlp| rlp.as_val())
+	pub fn uncle_at(&self, index: usize, eip1559_transition: BlockNumber) -> Option<Header> {
+		self.uncles_rlp().iter().nth(index)
+			.map(|rlp| rlp.expect_valid_rlp(Header::decode_rlp(&rlp.rlp, eip1559_transition)))
 	}
 
 	/// Return nth uncle rlp.
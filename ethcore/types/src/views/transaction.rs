@@ -19,10 +19,17 @@ use bytes::Bytes;
 use ethereum_types::{H256, U256};
 use hash::keccak;
 use super::ViewRlp;
+use crate::transaction::{envelope_of, TypedTxId};
 
 /// View onto transaction rlp.
+///
+/// Typed transactions are viewed through their fields, the ones shared with legacy transactions
+/// are at the same positions after skipping the chain ID, and the maximum priority fee of dynamic
+/// fee transactions. Their gas price is the maximum fee per gas.
 pub struct TransactionView<'a> {
-	rlp: ViewRlp<'a>
+	rlp: ViewRlp<'a>,
+	envelope: &'a [u8],
+	tx_type: TypedTxId,
 }
 
 impl<'a> TransactionView<'a> {
@@ -43,50 +50,92 @@ impl<'a> TransactionView<'a> {
 	/// }
 	/// ```
 	pub fn new(rlp: ViewRlp<'a>) -> TransactionView<'a> {
+		let envelope = envelope_of(&rlp.rlp);
+		if rlp.rlp.is_list() {
+			return TransactionView { rlp, envelope, tx_type: TypedTxId::Legacy };
+		}
+		let tx_type = envelope.first().cloned().and_then(TypedTxId::from_envelope_byte)
+			.expect("View rlp is trusted and should be a valid transaction");
 		TransactionView {
-			rlp: rlp
+			rlp: rlp.new_from_bytes(&envelope[1..]),
+			envelope,
+			tx_type,
 		}
 	}
 
-	/// Return reference to underlaying rlp.
+	/// Return reference to underlaying rlp, the list of fields for typed transactions.
 	pub fn rlp(&self) -> &ViewRlp<'a> {
 		&self.rlp
 	}
 
+	/// Returns the type of the transaction.
+	pub fn tx_type(&self) -> TypedTxId {
+		self.tx_type
+	}
+
 	/// Returns transaction hash.
 	pub fn hash(&self) -> H256 {
-		keccak(self.rlp.as_raw())
+		keccak(self.envelope)
+	}
+
+	fn field_at<T: rlp::Decodable>(&self, index: usize) -> T {
+		match self.tx_type {
+			TypedTxId::Legacy => self.rlp.val_at(index),
+			TypedTxId::AccessList => self.rlp.val_at(index + 1),
+			TypedTxId::EIP1559Transaction if index == 0 => self.rlp.val_at(1),
+			TypedTxId::EIP1559Transaction => self.rlp.val_at(index + 2),
+		}
 	}
 
 	/// Get the nonce field of the transaction.
-	pub fn nonce(&self) -> U256 { self.rlp.val_at(0) }
+	pub fn nonce(&self) -> U256 { self.field_at(0) }
 
 	/// Get the gas_price field of the transaction.
-	pub fn gas_price(&self) -> U256 { self.rlp.val_at(1) }
+	pub fn gas_price(&self) -> U256 { self.field_at(1) }
 
 	/// Get the gas field of the transaction.
-	pub fn gas(&self) -> U256 { self.rlp.val_at(2) }
+	pub fn gas(&self) -> U256 { self.field_at(2) }
 
 	/// Get the value field of the transaction.
-	pub fn value(&self) -> U256 { self.rlp.val_at(4) }
+	pub fn value(&self) -> U256 { self.field_at(4) }
 
 	/// Get the data field of the transaction.
-	pub fn data(&self) -> Bytes { self.rlp.val_at(5) }
-
-	/// Get the v field of the transaction.
-	pub fn v(&self) -> u8 { let r: u16 = self.rlp.val_at(6); r as u8 }
+	pub fn data(&self) -> Bytes { self.field_at(5) }
+
+	/// Get the v field of the transaction, the y parity for typed transactions.
+	pub fn v(&self) -> u8 {
+		let r: u16 = match self.tx_type {
+			TypedTxId::Legacy => self.rlp.val_at(6),
+			TypedTxId::AccessList => self.rlp.val_at(8),
+			TypedTxId::EIP1559Transaction => self.rlp.val_at(9),
+		};
+		r as u8
+	}
 
 	/// Get the r field of the transaction.
-	pub fn r(&self) -> U256 { self.rlp.val_at(7) }
+	pub fn r(&self) -> U256 {
+		match self.tx_type {
+			TypedTxId::Legacy => self.rlp.val_at(7),
+			TypedTxId::AccessList => self.rlp.val_at(9),
+			TypedTxId::EIP1559Transaction => self.rlp.val_at(10),
+		}
+	}
 
 	/// Get the s field of the transaction.
-	pub fn s(&self) -> U256 { self.rlp.val_at(8) }
+	pub fn s(&self) -> U256 {
+		match self.tx_type {
+			TypedTxId::Legacy => self.rlp.val_at(8),
+			TypedTxId::AccessList => self.rlp.val_at(10),
+			TypedTxId::EIP1559Transaction => self.rlp.val_at(11),
+		}
+	}
 }
 
 #[cfg(test)]
 mod tests {
 	use rustc_hex::FromHex;
 	use super::TransactionView;
+	use crate::transaction::{Action, AccessListTx, Transaction, TypedTransaction, TypedTxId};
 
 	#[test]
 	fn test_transaction_view() {
@@ -102,4 +151,28 @@ mod tests {
 		assert_eq!(view.s(), "efffd310ac743f371de3b9f7f9cb56c0b28ad43601b4ab949f53faa07bd2c804".into());
 		assert_eq!(view.v(), 0x1b);
 	}
+
+	#[test]
+	fn test_typed_transaction_view() {
+		let t = TypedTransaction::AccessList(AccessListTx::new(Transaction {
+			action: Action::Create,
+			nonce: 5.into(),
+			gas_price: 3000.into(),
+			gas: 50_000.into(),
+			value: 1.into(),
+			data: b"Hello!".to_vec(),
+		}, vec![])).fake_sign(Default::default());
+		let rlp = ::rlp::encode(&*t);
+
+		let view = view!(TransactionView, &rlp);
+		assert_eq!(view.tx_type(), TypedTxId::AccessList);
+		assert_eq!(view.hash(), t.hash());
+		assert_eq!(view.nonce(), 5.into());
+		assert_eq!(view.gas_price(), 3000.into());
+		assert_eq!(view.gas(), 50_000.into());
+		assert_eq!(view.value(), 1.into());
+		assert_eq!(view.data(), b"Hello!".to_vec());
+		assert_eq!(view.r(), 1.into());
+		assert_eq!(view.s(), 1.into());
+	}
 }
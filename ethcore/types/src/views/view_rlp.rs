@@ -37,6 +37,11 @@ impl<'a, 'view> ViewRlp<'a> where 'a : 'view {
 		}
 	}
 
+	/// Returns a new instance over the given bytes, maintaining debug info
+	pub(crate) fn new_from_bytes(&self, bytes: &'a [u8]) -> Self {
+		self.new_from_rlp(Rlp::new(bytes))
+	}
+
 	/// Returns a new instance replacing existing rlp with new rlp, maintaining debug info
 	fn new_from_rlp(&self, rlp: Rlp<'a>) -> Self {
 		ViewRlp {
@@ -52,7 +57,7 @@ impl<'a, 'view> ViewRlp<'a> where 'a : 'view {
 			.ok()
 	}
 
-	fn expect_valid_rlp<T>(&self, r: Result<T, DecoderError>) -> T {
+	pub(crate) fn expect_valid_rlp<T>(&self, r: Result<T, DecoderError>) -> T {
 		r.unwrap_or_else(|e| panic!(
 			"View rlp is trusted and should be valid. Constructed in {} on line {}: {}",
 			self.file,
@@ -18,7 +18,7 @@
 
 use std::collections::BTreeMap;
 
-use common_types::verification::Unverified;
+use common_types::{verification::Unverified, BlockNumber};
 use criterion::{Criterion, criterion_group, criterion_main};
 use ethash::{EthashParams, Ethash};
 use ethereum_types::U256;
@@ -95,7 +95,7 @@ fn block_verification(c: &mut Criterion) {
 
 	// Phase 1 verification
 	c.bench_function("verify_block_basic", |b| {
-		let block = Unverified::from_rlp(rlp_8481476.clone()).expect(PROOF);
+		let block = Unverified::from_rlp(rlp_8481476.clone(), BlockNumber::max_value()).expect(PROOF);
 		b.iter(|| {
 			assert!(verification::verify_block_basic(
 				&block,
@@ -107,7 +107,7 @@ fn block_verification(c: &mut Criterion) {
 
 	// Phase 2 verification
 	c.bench_function("verify_block_unordered", |b| {
-		let block = Unverified::from_rlp(rlp_8481476.clone()).expect(PROOF);
+		let block = Unverified::from_rlp(rlp_8481476.clone(), BlockNumber::max_value()).expect(PROOF);
 		b.iter( || {
 			assert!(verification::verify_block_unordered(
 				block.clone(),
@@ -118,9 +118,9 @@ fn block_verification(c: &mut Criterion) {
 	});
 
 	// Phase 3 verification
-	let block = Unverified::from_rlp(rlp_8481476.clone()).expect(PROOF);
+	let block = Unverified::from_rlp(rlp_8481476.clone(), BlockNumber::max_value()).expect(PROOF);
 	let preverified = verification::verify_block_unordered(block, &ethash, true).expect(PROOF).0;
-	let parent = Unverified::from_rlp(rlp_8481475.clone()).expect(PROOF);
+	let parent = Unverified::from_rlp(rlp_8481475.clone(), BlockNumber::max_value()).expect(PROOF);
 
 	let mut block_provider = TestBlockChain::new();
 	block_provider.insert(rlp_8481476.clone()); // block to verify
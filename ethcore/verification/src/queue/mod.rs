@@ -759,6 +759,7 @@ mod tests {
 		verification::Unverified,
 		view,
 		views::BlockView,
+		BlockNumber,
 	};
 	use spec;
 
@@ -781,7 +782,7 @@ mod tests {
 	}
 
 	fn new_unverified(bytes: Bytes) -> Unverified {
-		Unverified::from_rlp(bytes).expect("Should be valid rlp")
+		Unverified::from_rlp(bytes, BlockNumber::max_value()).expect("Should be valid rlp")
 	}
 
 	#[test]
@@ -823,7 +824,7 @@ mod tests {
 	fn returns_total_difficulty() {
 		let queue = get_test_queue(false);
 		let block = get_good_dummy_block();
-		let hash = view!(BlockView, &block).header().hash().clone();
+		let hash = view!(BlockView, &block).header(BlockNumber::max_value()).hash().clone();
 		if let Err(e) = queue.import(new_unverified(block)) {
 			panic!("error importing block that is valid by definition({:?})", e);
 		}
@@ -839,7 +840,7 @@ mod tests {
 	fn returns_ok_for_drained_duplicates() {
 		let queue = get_test_queue(false);
 		let block = get_good_dummy_block();
-		let hash = view!(BlockView, &block).header().hash().clone();
+		let hash = view!(BlockView, &block).header(BlockNumber::max_value()).hash().clone();
 		if let Err(e) = queue.import(new_unverified(block)) {
 			panic!("error importing block that is valid by definition({:?})", e);
 		}
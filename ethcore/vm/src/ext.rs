@@ -175,4 +175,19 @@ pub trait Ext {
 
 	/// Check if running in static context.
 	fn is_static(&self) -> bool;
+
+	/// Check if EIP-2929 access tracking is enabled.
+	fn al_is_enabled(&self) -> bool;
+
+	/// Check if the storage slot was already accessed in the current transaction.
+	fn al_contains_storage_key(&self, address: &Address, key: &H256) -> bool;
+
+	/// Mark the storage slot as accessed in the current transaction.
+	fn al_insert_storage_key(&mut self, address: Address, key: H256);
+
+	/// Check if the account was already accessed in the current transaction.
+	fn al_contains_address(&self, address: &Address) -> bool;
+
+	/// Mark the account as accessed in the current transaction.
+	fn al_insert_address(&mut self, address: Address);
 }
@@ -140,6 +140,22 @@ pub struct Schedule {
 	pub eip1283: bool,
 	/// Enable EIP-1706 rules
 	pub eip1706: bool,
+	/// Enable EIP-2929 rules
+	pub eip2929: bool,
+	/// Enable EIP-2930 rules for optional access lists
+	pub eip2930: bool,
+	/// Enable EIP-1559 rules for the base fee and dynamic fee transactions
+	pub eip1559: bool,
+	/// Gas price for loading from storage a slot which was not accessed before (EIP-2929)
+	pub cold_sload_cost: usize,
+	/// Gas price for accessing an account which was not accessed before (EIP-2929)
+	pub cold_account_access_cost: usize,
+	/// Gas price for accessing an account or a storage slot which was already accessed (EIP-2929)
+	pub warm_storage_read_cost: usize,
+	/// Cost for each address in the access list of a transaction (EIP-2930)
+	pub tx_access_list_address_gas: usize,
+	/// Cost for each storage key in the access list of a transaction (EIP-2930)
+	pub tx_access_list_storage_key_gas: usize,
 	/// Latest VM version for contract creation transaction.
 	pub latest_version: U256,
 	/// All supported non-legacy VM versions.
@@ -283,6 +299,14 @@ impl Schedule {
 			kill_dust: CleanDustMode::Off,
 			eip1283: false,
 			eip1706: false,
+			eip2929: false,
+			eip2930: false,
+			eip1559: false,
+			cold_sload_cost: 2100,
+			cold_account_access_cost: 2600,
+			warm_storage_read_cost: 100,
+			tx_access_list_address_gas: 2400,
+			tx_access_list_storage_key_gas: 1900,
 			latest_version: U256::zero(),
 			versions: HashMap::new(),
 			wasm: None,
@@ -323,6 +347,11 @@ impl Schedule {
 		let mut schedule = Self::new_istanbul();
 		schedule.staticcall_precompile_gas = 40; // EIPs 2046 1352
 		schedule.have_subs = true; // EIP 2315
+		schedule.eip2929 = true; // EIP 2929
+		schedule.eip2930 = true; // EIP 2930
+		schedule.sload_gas = schedule.warm_storage_read_cost; // EIP 2929
+		schedule.sstore_dirty_gas = Some(schedule.warm_storage_read_cost); // EIP 2929
+		schedule.sstore_reset_gas = 5000 - schedule.cold_sload_cost; // EIP 2929
 		schedule
 	}
 	
@@ -384,6 +413,14 @@ impl Schedule {
 			kill_dust: CleanDustMode::Off,
 			eip1283: false,
 			eip1706: false,
+			eip2929: false,
+			eip2930: false,
+			eip1559: false,
+			cold_sload_cost: 2100,
+			cold_account_access_cost: 2600,
+			warm_storage_read_cost: 100,
+			tx_access_list_address_gas: 2400,
+			tx_access_list_storage_key_gas: 1900,
 			latest_version: U256::zero(),
 			versions: HashMap::new(),
 			wasm: None,
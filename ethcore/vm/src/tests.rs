@@ -67,6 +67,8 @@ pub struct FakeExt {
 	pub balances: HashMap<Address, U256>,
 	pub tracing: bool,
 	pub is_static: bool,
+	pub accessed_addresses: HashSet<Address>,
+	pub accessed_storage_keys: HashSet<(Address, H256)>,
 
 	chain_id: u64,
 }
@@ -269,4 +271,24 @@ impl Ext for FakeExt {
 	fn trace_next_instruction(&mut self, _pc: usize, _instruction: u8, _gas: U256) -> bool {
 		self.tracing
 	}
+
+	fn al_is_enabled(&self) -> bool {
+		self.schedule.eip2929
+	}
+
+	fn al_contains_storage_key(&self, address: &Address, key: &H256) -> bool {
+		self.accessed_storage_keys.contains(&(*address, *key))
+	}
+
+	fn al_insert_storage_key(&mut self, address: Address, key: H256) {
+		self.accessed_storage_keys.insert((address, key));
+	}
+
+	fn al_contains_address(&self, address: &Address) -> bool {
+		self.accessed_addresses.contains(address)
+	}
+
+	fn al_insert_address(&mut self, address: Address) {
+		self.accessed_addresses.insert(address);
+	}
 }
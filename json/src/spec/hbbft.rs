@@ -16,9 +16,27 @@
 
 //! Hbbft parameter deserialization.
 
-use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Deserializer};
+use serde::de::{Error, Unexpected};
 use ethereum_types::Address;
 
+/// The hbbft epochs in which the contributions are threshold encrypted.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HbbftEncryptionSchedule {
+    /// Encrypt the contributions of every epoch.
+    Always,
+    /// Never encrypt the contributions.
+    Never,
+    /// Encrypt the contributions of every n-th epoch, starting with epoch 0.
+    EveryNthEpoch(u32),
+    /// Encrypt the contributions of the given number of epochs, followed by the given number of
+    /// epochs without encryption, repeatedly.
+    TickTock(u32, u32),
+}
+
 /// Hbbft parameters.
 #[derive(Debug, PartialEq, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -26,12 +44,62 @@ use ethereum_types::Address;
 pub struct HbbftParams {
     /// The minimum time duration between blocks, in seconds.
     pub minimum_block_time: u64,
+    /// The maximum time duration between blocks, in seconds. Once it elapsed since the latest block,
+    /// an epoch is started even if no transactions are queued, so that an idle chain keeps producing
+    /// empty blocks. Must be non-zero and at least `minimumBlockTime`.
+    /// If not set, blocks are only created once enough transactions are queued.
+    #[serde(default, deserialize_with = "validate_optional_non_zero")]
+    pub maximum_block_time: Option<u64>,
     /// The length of the transaction queue at which block creation should be triggered.
     pub transaction_queue_size_trigger: usize,
     /// Should be true when running unit tests to avoid starting timers.
     pub is_unit_test: Option<bool>,
     /// Block reward contract address.
     pub block_reward_contract_address: Option<Address>,
+    /// Block number from which on consensus messages are sent in the compact binary encoding.
+    /// JSON encoded messages are still accepted from peers which did not switch yet.
+    pub binary_message_transition: Option<u64>,
+    /// Address of the contract misbehaving validators are reported to.
+    /// If not set, faults are only counted locally.
+    pub reporting_contract_address: Option<Address>,
+    /// The randomness contract's address, by the block number from which on it is used.
+    /// The random number agreed upon for each block is stored in the contract, and included in the
+    /// block's seal so that importing nodes can verify it.
+    pub randomness_contract_address: Option<BTreeMap<u64, Address>>,
+    /// The targeted number of transactions per block. Each validator proposes a random subset of
+    /// `batchSize / N` transactions, N being the number of validators.
+    /// If not set, each validator proposes all of its queued transactions.
+    pub batch_size: Option<usize>,
+    /// The number of transactions at the front of the queue from which the proposed subset is selected.
+    /// Defaults to `batchSize`.
+    pub contribution_candidate_window: Option<usize>,
+    /// The number of random bytes each validator contributes per block, at least 32. Defaults to 80.
+    pub random_bytes_per_epoch: Option<usize>,
+    /// Block number from which on the extra data of a block signals validator set changes: Blocks
+    /// switching to a new validator set contain the hash of its threshold public key, and all other
+    /// blocks have empty extra data. This allows light clients to follow the validator set changes.
+    pub epoch_signal_transition: Option<u64>,
+    /// The epochs in which the contributions are threshold encrypted. Encrypted contributions are
+    /// only decrypted after the validators agreed on the set of contributions, so no validator can
+    /// censor or front-run transactions based on their content. All validators must use the same
+    /// schedule. If not set, the default schedule of the hbbft crate is used.
+    pub encryption_schedule: Option<HbbftEncryptionSchedule>,
+    /// The devp2p port the validators listen on. The engine keeps the current and pending validators
+    /// connected as reserved peers, using the internet addresses they registered with the staking
+    /// contract and this port. If not set, the connections between the validators are left to the
+    /// configured reserved peers.
+    pub validator_peer_port: Option<u16>,
+}
+
+/// Deserialize and validate that the value is non-zero
+fn validate_optional_non_zero<'de, D>(d: D) -> Result<Option<u64>, D::Error> where D: Deserializer<'de> {
+    let value: Option<u64> = Option::deserialize(d)?;
+
+    if value == Some(0) {
+        return Err(Error::invalid_value(Unexpected::Unsigned(0), &"a non-zero value"))
+    }
+
+    Ok(value)
 }
 
 /// Hbbft engine config.
@@ -44,23 +112,85 @@ pub struct Hbbft {
 
 #[cfg(test)]
 mod tests {
-    use super::Hbbft;
+    use super::{Hbbft, HbbftEncryptionSchedule};
+    use ethereum_types::Address;
+    use std::str::FromStr;
 
     #[test]
     fn hbbft_deserialization() {
         let s = r#"{
 			"params": {
 				"minimumBlockTime": 0,
+				"maximumBlockTime": 600,
 				"transactionQueueSizeTrigger": 1,
 				"isUnitTest": true,
 				"blockRewardContractAddress": "0x2000000000000000000000000000000000000002",
+				"binaryMessageTransition": 10,
+				"reportingContractAddress": "0x1000000000000000000000000000000000000001",
+				"randomnessContractAddress": {
+					"20": "0x3000000000000000000000000000000000000001"
+				},
+				"batchSize": 100,
+				"contributionCandidateWindow": 200,
+				"randomBytesPerEpoch": 64,
+				"epochSignalTransition": 30,
+				"encryptionSchedule": { "tickTock": [1, 2] },
+				"validatorPeerPort": 30303
 			}
 		}"#;
 
         let deserialized: Hbbft = serde_json::from_str(s).unwrap();
         assert_eq!(deserialized.params.minimum_block_time, 0);
+        assert_eq!(deserialized.params.maximum_block_time, Some(600));
         assert_eq!(deserialized.params.transaction_queue_size_trigger, 1);
         assert_eq!(deserialized.params.is_unit_test, Some(true));
-        assert_eq!(deserialized.params.block_reward_contract_address, Address::from("0x2000000000000000000000000000000000000002"));
+        assert_eq!(deserialized.params.block_reward_contract_address, Some(Address::from_str("2000000000000000000000000000000000000002").unwrap()));
+        assert_eq!(deserialized.params.binary_message_transition, Some(10));
+        assert_eq!(deserialized.params.reporting_contract_address, Some(Address::from_str("1000000000000000000000000000000000000001").unwrap()));
+        assert_eq!(
+            deserialized.params.randomness_contract_address,
+            Some(vec![(20, Address::from_str("3000000000000000000000000000000000000001").unwrap())].into_iter().collect())
+        );
+        assert_eq!(deserialized.params.batch_size, Some(100));
+        assert_eq!(deserialized.params.contribution_candidate_window, Some(200));
+        assert_eq!(deserialized.params.random_bytes_per_epoch, Some(64));
+        assert_eq!(deserialized.params.epoch_signal_transition, Some(30));
+        assert_eq!(deserialized.params.encryption_schedule, Some(HbbftEncryptionSchedule::TickTock(1, 2)));
+        assert_eq!(deserialized.params.validator_peer_port, Some(30303));
+    }
+
+    #[test]
+    fn hbbft_encryption_schedule_deserialization() {
+        let schedules: Vec<HbbftEncryptionSchedule> = serde_json::from_str(
+            r#"["always", "never", { "everyNthEpoch": 3 }, { "tickTock": [2, 1] }]"#
+        ).unwrap();
+        assert_eq!(schedules, vec![
+            HbbftEncryptionSchedule::Always,
+            HbbftEncryptionSchedule::Never,
+            HbbftEncryptionSchedule::EveryNthEpoch(3),
+            HbbftEncryptionSchedule::TickTock(2, 1),
+        ]);
+        assert!(serde_json::from_str::<HbbftEncryptionSchedule>(r#""sometimes""#).is_err());
+    }
+
+    #[test]
+    fn hbbft_maximum_block_time_validation() {
+        let s = r#"{
+			"params": {
+				"minimumBlockTime": 0,
+				"transactionQueueSizeTrigger": 1
+			}
+		}"#;
+        let deserialized: Hbbft = serde_json::from_str(s).unwrap();
+        assert_eq!(deserialized.params.maximum_block_time, None);
+
+        let s = r#"{
+			"params": {
+				"minimumBlockTime": 0,
+				"maximumBlockTime": 0,
+				"transactionQueueSizeTrigger": 1
+			}
+		}"#;
+        assert!(serde_json::from_str::<Hbbft>(s).is_err());
     }
 }
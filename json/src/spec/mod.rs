@@ -52,4 +52,4 @@ pub use self::null_engine::{NullEngine, NullEngineParams};
 pub use self::instant_seal::{InstantSeal, InstantSealParams};
 pub use self::hardcoded_sync::HardcodedSync;
 pub use self::step_duration::StepDuration;
-pub use self::hbbft::{Hbbft, HbbftParams};
+pub use self::hbbft::{Hbbft, HbbftEncryptionSchedule, HbbftParams};
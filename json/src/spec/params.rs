@@ -113,6 +113,20 @@ pub struct Params {
 	/// See `CommonParams` docs.
 	pub eip2315_transition: Option<Uint>,
 	/// See `CommonParams` docs.
+	pub eip2929_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	pub eip2930_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	pub eip1559_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	pub eip1559_base_fee_max_change_denominator: Option<Uint>,
+	/// See `CommonParams` docs.
+	pub eip1559_elasticity_multiplier: Option<Uint>,
+	/// See `CommonParams` docs.
+	pub eip1559_base_fee_initial_value: Option<Uint>,
+	/// See `CommonParams` docs.
+	pub eip1559_fee_collector: Option<Address>,
+	/// See `CommonParams` docs.
 	pub dust_protection_transition: Option<Uint>,
 	/// See `CommonParams` docs.
 	pub nonce_cap_increment: Option<Uint>,
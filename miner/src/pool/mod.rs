@@ -117,6 +117,9 @@ pub trait ScoredTransaction {
 	/// Gets transaction gas price.
 	fn gas_price(&self) -> &U256;
 
+	/// Gets the gas price paid to the block author on top of the given base fee.
+	fn effective_priority_fee(&self, block_base_fee: Option<U256>) -> U256;
+
 	/// Gets transaction nonce.
 	fn nonce(&self) -> U256;
 }
@@ -194,6 +197,11 @@ impl ScoredTransaction for VerifiedTransaction {
 		&self.transaction.gas_price
 	}
 
+	/// Gets the gas price paid to the block author on top of the given base fee.
+	fn effective_priority_fee(&self, block_base_fee: Option<U256>) -> U256 {
+		self.transaction.effective_priority_fee(block_base_fee)
+	}
+
 	/// Gets transaction nonce.
 	fn nonce(&self) -> U256 {
 		self.transaction.nonce
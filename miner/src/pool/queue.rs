@@ -216,7 +216,7 @@ impl TransactionQueue {
 		let max_count = limits.max_count;
 		TransactionQueue {
 			insertion_id: Default::default(),
-			pool: RwLock::new(txpool::Pool::new(Default::default(), scoring::NonceAndGasPrice(strategy), limits)),
+			pool: RwLock::new(txpool::Pool::new(Default::default(), scoring::NonceAndGasPrice::new(strategy), limits)),
 			options: RwLock::new(verification_options),
 			cached_pending: RwLock::new(CachedPending::none()),
 			recently_rejected: RecentlyRejected::new(cmp::max(MIN_REJECTED_CACHE_SIZE, max_count / 4)),
@@ -270,7 +270,9 @@ impl TransactionQueue {
 			transaction_to_replace,
 		);
 
-		let mut replace = replace::ReplaceByScoreAndReadiness::new(self.pool.read().scoring().clone(), client);
+		let scoring = self.pool.read().scoring().clone();
+		let block_base_fee = scoring.block_base_fee;
+		let mut replace = replace::ReplaceByScoreAndReadiness::new(scoring, client, block_base_fee);
 
 		let results = transactions
 			.into_iter()
@@ -363,7 +365,7 @@ impl TransactionQueue {
 		// In case we don't have a cached set, but we don't care about order
 		// just return the unordered set.
 		if let PendingOrdering::Unordered = ordering {
-			let ready = Self::ready(client, block_number, current_timestamp, nonce_cap);
+			let ready = Self::ready(client, block_number, current_timestamp, nonce_cap, self.block_base_fee());
 			return self.pool.read().unordered_pending(ready).take(max_len).collect();
 		}
 
@@ -405,7 +407,7 @@ impl TransactionQueue {
 	{
 		debug!(target: "txqueue", "Re-computing pending set for block: {}", block_number);
 		trace_time!("pool::collect_pending");
-		let ready = Self::ready(client, block_number, current_timestamp, nonce_cap);
+		let ready = Self::ready(client, block_number, current_timestamp, nonce_cap, self.block_base_fee());
 		collect(self.pool.read().pending(ready))
 	}
 
@@ -414,10 +416,11 @@ impl TransactionQueue {
 		block_number: u64,
 		current_timestamp: u64,
 		nonce_cap: Option<U256>,
+		block_base_fee: Option<U256>,
 	) -> (ready::Condition, ready::State<C>) where
 		C: client::NonceClient,
 	{
-		let pending_readiness = ready::Condition::new(block_number, current_timestamp);
+		let pending_readiness = ready::Condition::new(block_number, current_timestamp, block_base_fee);
 		// don't mark any transactions as stale at this point.
 		let stale_id = None;
 		let state_readiness = ready::State::new(client, stale_id, nonce_cap);
@@ -527,10 +530,35 @@ impl TransactionQueue {
 	pub fn penalize<'a, T: IntoIterator<Item = &'a Address>>(&self, senders: T) {
 		let mut pool = self.pool.write();
 		for sender in senders {
-			pool.update_scores(sender, ());
+			pool.update_scores(sender, scoring::ScoringEvent::Penalize);
 		}
 	}
 
+	/// Returns the base fee of the pending block the transactions are prioritized by.
+	pub fn block_base_fee(&self) -> Option<U256> {
+		self.pool.read().scoring().block_base_fee
+	}
+
+	/// Update the base fee of the pending block (EIP-1559).
+	///
+	/// Transactions are prioritized by the priority fee they pay on top of the base fee,
+	/// and those not covering the base fee are not pending.
+	pub fn update_block_base_fee(&self, block_base_fee: Option<U256>) {
+		{
+			let mut pool = self.pool.write();
+			if pool.scoring().block_base_fee == block_base_fee {
+				return;
+			}
+
+			let scoring = scoring::NonceAndGasPrice {
+				block_base_fee,
+				..pool.scoring().clone()
+			};
+			pool.set_scoring(scoring, scoring::ScoringEvent::BlockBaseFeeChanged);
+		}
+		self.cached_pending.write().clear();
+	}
+
 	/// Returns gas price of currently the worst transaction in the pool.
 	pub fn current_worst_gas_price(&self) -> U256 {
 		match self.pool.read().worst_transaction() {
@@ -103,24 +103,32 @@ impl<C: NonceClient> txpool::Ready<VerifiedTransaction> for State<C> {
 }
 
 /// Checks readines of Pending transactions by comparing it with current time and block number.
+/// Transactions paying less than the base fee of the block are not ready either.
 #[derive(Debug)]
 pub struct Condition {
 	block_number: u64,
 	now: u64,
+	block_base_fee: Option<U256>,
 }
 
 impl Condition {
-	/// Create a new condition checker given current block number and UTC timestamp.
-	pub fn new(block_number: u64, now: u64) -> Self {
+	/// Create a new condition checker given current block number, UTC timestamp and block base fee.
+	pub fn new(block_number: u64, now: u64, block_base_fee: Option<U256>) -> Self {
 		Condition {
 			block_number,
 			now,
+			block_base_fee,
 		}
 	}
 }
 
 impl txpool::Ready<VerifiedTransaction> for Condition {
 	fn is_ready(&mut self, tx: &VerifiedTransaction) -> txpool::Readiness {
+		match self.block_base_fee {
+			Some(base_fee) if tx.transaction.gas_price < base_fee => return txpool::Readiness::Future,
+			_ => {},
+		}
+
 		match tx.transaction.condition {
 			Some(transaction::Condition::Number(block)) if block > self.block_number => txpool::Readiness::Future,
 			Some(transaction::Condition::Timestamp(time)) if time > self.now => txpool::Readiness::Future,
@@ -239,10 +247,12 @@ mod tests {
 		let tx3 = v(transaction::PendingTransaction::new(tx.clone(), None));
 
 		// when/then
-		assert_eq!(Condition::new(0, 0).is_ready(&tx1), txpool::Readiness::Future);
-		assert_eq!(Condition::new(0, 0).is_ready(&tx2), txpool::Readiness::Future);
-		assert_eq!(Condition::new(0, 0).is_ready(&tx3), txpool::Readiness::Ready);
-		assert_eq!(Condition::new(5, 0).is_ready(&tx1), txpool::Readiness::Ready);
-		assert_eq!(Condition::new(0, 3).is_ready(&tx2), txpool::Readiness::Ready);
+		assert_eq!(Condition::new(0, 0, None).is_ready(&tx1), txpool::Readiness::Future);
+		assert_eq!(Condition::new(0, 0, None).is_ready(&tx2), txpool::Readiness::Future);
+		assert_eq!(Condition::new(0, 0, None).is_ready(&tx3), txpool::Readiness::Ready);
+		assert_eq!(Condition::new(5, 0, None).is_ready(&tx1), txpool::Readiness::Ready);
+		assert_eq!(Condition::new(0, 3, None).is_ready(&tx2), txpool::Readiness::Ready);
+		assert_eq!(Condition::new(0, 0, Some(1.into())).is_ready(&tx3), txpool::Readiness::Ready);
+		assert_eq!(Condition::new(0, 0, Some(2.into())).is_ready(&tx3), txpool::Readiness::Future);
 	}
 }
@@ -36,12 +36,14 @@ use super::{client, ScoredTransaction};
 pub struct ReplaceByScoreAndReadiness<S, C> {
 	scoring: S,
 	client: C,
+	/// Base fee of the pending block, transactions are compared by the priority fee on top of it.
+	block_base_fee: Option<U256>,
 }
 
 impl<S, C> ReplaceByScoreAndReadiness<S, C> {
 	/// Create a new `ReplaceByScoreAndReadiness`
-	pub fn new(scoring: S, client: C) -> Self {
-		ReplaceByScoreAndReadiness { scoring, client }
+	pub fn new(scoring: S, client: C, block_base_fee: Option<U256>) -> Self {
+		ReplaceByScoreAndReadiness { scoring, client, block_base_fee }
 	}
 }
 
@@ -68,8 +70,8 @@ where
 		} else if both_local {
 			Choice::InsertNew
 		} else {
-			let old_score = (old.priority(), old.gas_price());
-			let new_score = (new.priority(), new.gas_price());
+			let old_score = (old.priority(), old.effective_priority_fee(self.block_base_fee));
+			let new_score = (new.priority(), new.effective_priority_fee(self.block_base_fee));
 			if new_score > old_score {
 				// Check if this is a replacement transaction.
 				//
@@ -143,9 +145,9 @@ mod tests {
 
 	#[test]
 	fn should_always_accept_local_transactions_unless_same_sender_and_nonce() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let client = TestClient::new().with_nonce(1);
-		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
+		let replace = ReplaceByScoreAndReadiness::new(scoring, client, None);
 
 		// same sender txs
 		let keypair = Random.generate();
@@ -196,9 +198,9 @@ mod tests {
 
 	#[test]
 	fn should_replace_same_sender_by_nonce() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let client = TestClient::new().with_nonce(1);
-		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
+		let replace = ReplaceByScoreAndReadiness::new(scoring, client, None);
 
 		let tx1 = Tx {
 			nonce: 1,
@@ -239,9 +241,9 @@ mod tests {
 	#[test]
 	fn should_replace_different_sender_by_priority_and_gas_price() {
 		// given
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let client = TestClient::new().with_nonce(0);
-		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
+		let replace = ReplaceByScoreAndReadiness::new(scoring, client, None);
 
 		let tx_regular_low_gas = {
 			let tx = Tx {
@@ -292,9 +294,9 @@ mod tests {
 
 	#[test]
 	fn should_not_replace_ready_transaction_with_future_transaction() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let client = TestClient::new().with_nonce(1);
-		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
+		let replace = ReplaceByScoreAndReadiness::new(scoring, client, None);
 
 		let tx_ready_low_score = {
 			let tx = Tx {
@@ -318,9 +320,9 @@ mod tests {
 
 	#[test]
 	fn should_compute_readiness_with_pooled_transactions_from_the_same_sender_as_the_existing_transaction() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let client = TestClient::new().with_nonce(1);
-		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
+		let replace = ReplaceByScoreAndReadiness::new(scoring, client, None);
 
 		let old_sender = Random.generate();
 		let tx_old_ready_1 = {
@@ -373,9 +375,9 @@ mod tests {
 
 	#[test]
 	fn should_compute_readiness_with_pooled_transactions_from_the_same_sender_as_the_new_transaction() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let client = TestClient::new().with_nonce(1);
-		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
+		let replace = ReplaceByScoreAndReadiness::new(scoring, client, None);
 
 		// current transaction is ready but has a lower gas price than the new one
 		let old_tx = {
@@ -429,9 +431,9 @@ mod tests {
 
 	#[test]
 	fn should_accept_local_tx_with_same_sender_and_nonce_with_better_gas_price() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let client = TestClient::new().with_nonce(1);
-		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
+		let replace = ReplaceByScoreAndReadiness::new(scoring, client, None);
 
 		// current transaction is ready
 		let old_tx = {
@@ -471,9 +473,9 @@ mod tests {
 
 	#[test]
 	fn should_reject_local_tx_with_same_sender_and_nonce_with_worse_gas_price() {
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let client = TestClient::new().with_nonce(1);
-		let replace = ReplaceByScoreAndReadiness::new(scoring, client);
+		let replace = ReplaceByScoreAndReadiness::new(scoring, client, None);
 
 		// current transaction is ready
 		let old_tx = {
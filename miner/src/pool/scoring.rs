@@ -26,6 +26,9 @@
 //! Transactions between senders are prioritized using `gas price`. Higher `gas price`
 //! yields more profits for miners. Additionally we prioritize transactions that originate
 //! from our local node (own transactions).
+//!
+//! After the EIP-1559 transition only the part of the gas price above the block base fee
+//! (the effective priority fee) is paid to the miner, so it is used for prioritization instead.
 
 use std::cmp;
 
@@ -43,14 +46,36 @@ fn bump_gas_price(old_gp: U256) -> U256 {
 	old_gp.saturating_add(old_gp >> GAS_PRICE_BUMP_SHIFT)
 }
 
+/// Events changing the scores of the transactions in the pool.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScoringEvent {
+	/// Lower the priority of the (non-local) transactions of a sender.
+	Penalize,
+	/// The base fee of the pending block has changed, recompute all scores.
+	BlockBaseFeeChanged,
+}
+
 /// Simple, gas-price based scoring for transactions.
 ///
 /// NOTE: Currently penalization does not apply to new transactions that enter the pool.
 /// We might want to store penalization status in some persistent state.
 #[derive(Debug, Clone)]
-pub struct NonceAndGasPrice(pub PrioritizationStrategy);
+pub struct NonceAndGasPrice {
+	/// Strategy for prioritization.
+	pub strategy: PrioritizationStrategy,
+	/// Base fee of the pending block, `None` before the EIP-1559 transition.
+	pub block_base_fee: Option<U256>,
+}
 
 impl NonceAndGasPrice {
+	/// Create a new scoring with no block base fee.
+	pub fn new(strategy: PrioritizationStrategy) -> Self {
+		NonceAndGasPrice {
+			strategy,
+			block_base_fee: None,
+		}
+	}
+
 	/// Decide if the transaction should even be considered into the pool (if the pool is full).
 	///
 	/// Used by Verifier to quickly reject transactions that don't have any chance to get into the pool later on,
@@ -63,13 +88,22 @@ impl NonceAndGasPrice {
 			return true
 		}
 
-		&old.transaction.gas_price > new.gas_price()
+		old.transaction.effective_priority_fee(self.block_base_fee) > new.effective_priority_fee(self.block_base_fee)
+	}
+
+	fn score<P: ScoredTransaction>(&self, tx: &P) -> U256 {
+		let boost = match tx.priority() {
+			super::Priority::Local => 15,
+			super::Priority::Retracted => 10,
+			super::Priority::Regular => 0,
+		};
+		tx.effective_priority_fee(self.block_base_fee) << boost
 	}
 }
 
 impl<P> txpool::Scoring<P> for NonceAndGasPrice where P: ScoredTransaction + txpool::VerifiedTransaction {
 	type Score = U256;
-	type Event = ();
+	type Event = ScoringEvent;
 
 	fn compare(&self, old: &P, other: &P) -> cmp::Ordering {
 		old.nonce().cmp(&other.nonce())
@@ -101,17 +135,10 @@ impl<P> txpool::Scoring<P> for NonceAndGasPrice where P: ScoredTransaction + txp
 				assert!(i < txs.len());
 				assert!(i < scores.len());
 
-				scores[i] = *txs[i].transaction.gas_price();
-				let boost = match txs[i].priority() {
-					super::Priority::Local => 15,
-					super::Priority::Retracted => 10,
-					super::Priority::Regular => 0,
-				};
-				scores[i] = scores[i] << boost;
+				scores[i] = self.score(&*txs[i].transaction);
 			},
-			// We are only sending an event in case of penalization.
-			// So just lower the priority of all non-local transactions.
-			Change::Event(_) => {
+			// Lower the priority of all non-local transactions.
+			Change::Event(ScoringEvent::Penalize) => {
 				for (score, tx) in scores.iter_mut().zip(txs) {
 					// Never penalize local transactions.
 					if !tx.priority().is_local() {
@@ -119,6 +146,12 @@ impl<P> txpool::Scoring<P> for NonceAndGasPrice where P: ScoredTransaction + txp
 					}
 				}
 			},
+			// The effective priority fees depend on the base fee.
+			Change::Event(ScoringEvent::BlockBaseFeeChanged) => {
+				for (score, tx) in scores.iter_mut().zip(txs) {
+					*score = self.score(&*tx.transaction);
+				}
+			},
 		}
 	}
 
@@ -138,7 +171,7 @@ mod tests {
 	#[test]
 	fn should_calculate_score_correctly() {
 		// given
-		let scoring = NonceAndGasPrice(PrioritizationStrategy::GasPriceOnly);
+		let scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
 		let (tx1, tx2, tx3) = Tx::default().signed_triple();
 		let transactions = vec![tx1, tx2, tx3].into_iter().enumerate().map(|(i, tx)| {
 			let mut verified = tx.verified();
@@ -184,7 +217,28 @@ mod tests {
 		assert_eq!(scores, vec![32768.into(), 1024.into(), 1.into()]);
 
 		// Check penalization
-		scoring.update_scores(&transactions, &mut *scores, scoring::Change::Event(()));
+		scoring.update_scores(&transactions, &mut *scores, scoring::Change::Event(ScoringEvent::Penalize));
 		assert_eq!(scores, vec![32768.into(), 128.into(), 0.into()]);
 	}
+
+	#[test]
+	fn should_score_by_effective_priority_fee() {
+		// given
+		let mut scoring = NonceAndGasPrice::new(PrioritizationStrategy::GasPriceOnly);
+		let tx = Tx::gas_price(10).signed().verified();
+		let transactions = vec![txpool::Transaction {
+			insertion_id: 0,
+			transaction: Arc::new(tx),
+		}];
+		let mut scores = vec![U256::zero()];
+		scoring.update_scores(&transactions, &mut *scores, scoring::Change::InsertedAt(0));
+		assert_eq!(scores, vec![(10 << 10).into()]);
+
+		// when
+		scoring.block_base_fee = Some(7.into());
+		scoring.update_scores(&transactions, &mut *scores, scoring::Change::Event(ScoringEvent::BlockBaseFeeChanged));
+
+		// then
+		assert_eq!(scores, vec![(3 << 10).into()]);
+	}
 }
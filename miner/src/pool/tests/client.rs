@@ -18,7 +18,7 @@ use std::sync::{atomic, Arc};
 
 use ethereum_types::{U256, H256, Address};
 use rlp::Rlp;
-use types::transaction::{self, Transaction, SignedTransaction, UnverifiedTransaction};
+use types::transaction::{self, SignedTransaction, UnverifiedTransaction};
 
 use pool;
 use pool::client::AccountDetails;
@@ -125,7 +125,7 @@ impl pool::client::Client for TestClient {
 		details
 	}
 
-	fn required_gas(&self, _tx: &Transaction) -> U256 {
+	fn required_gas(&self, _tx: &UnverifiedTransaction) -> U256 {
 		self.gas_required
 	}
 
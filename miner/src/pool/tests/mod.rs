@@ -1074,3 +1074,25 @@ fn should_not_reject_early_in_case_gas_price_is_less_than_min_effective() {
 	assert_eq!(txq.status().status.transaction_count, 2);
 	assert!(client.was_verification_triggered());
 }
+
+#[test]
+fn should_not_return_transactions_below_block_base_fee_as_pending() {
+	// given
+	let txq = new_queue();
+	let tx1 = Tx::gas_price(3).signed();
+	let tx2 = Tx::gas_price(5).signed();
+	let hash2 = tx2.hash();
+	let res = txq.import(TestClient::new(), vec![tx1, tx2].local());
+	assert_eq!(res, vec![Ok(()), Ok(())]);
+	assert_eq!(txq.pending(TestClient::new(), PendingSettings::all_prioritized(0, 0)).len(), 2);
+
+	// when
+	txq.update_block_base_fee(Some(4.into()));
+
+	// then
+	assert_eq!(txq.block_base_fee(), Some(4.into()));
+	let top = txq.pending(TestClient::new(), PendingSettings::all_prioritized(0, 0));
+	assert_eq!(top.len(), 1);
+	assert_eq!(top[0].hash, hash2);
+	assert_eq!(txq.status().status.transaction_count, 2);
+}
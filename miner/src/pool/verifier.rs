@@ -27,7 +27,6 @@ use std::sync::Arc;
 use std::sync::atomic::{self, AtomicUsize};
 
 use ethereum_types::{U256, H256};
-use rlp::Encodable;
 use txpool;
 use types::transaction;
 
@@ -97,6 +96,11 @@ impl Transaction {
 		}
 	}
 
+	/// Return the gas price paid to the block author on top of the given base fee.
+	pub fn effective_priority_fee(&self, block_base_fee: Option<U256>) -> U256 {
+		self.transaction().effective_priority_fee(block_base_fee)
+	}
+
 	fn gas(&self) -> &U256 {
 		match *self {
 			Transaction::Unverified(ref tx) => &tx.gas,
@@ -105,7 +109,7 @@ impl Transaction {
 		}
 	}
 
-	fn transaction(&self) -> &transaction::Transaction {
+	fn transaction(&self) -> &transaction::UnverifiedTransaction {
 		match *self {
 			Transaction::Unverified(ref tx) => &*tx,
 			Transaction::Retracted(ref tx) => &*tx,
@@ -260,7 +264,7 @@ impl<C: Client> txpool::Verifier<Transaction> for Verifier<C, ::pool::scoring::N
 		};
 
 		// Verify RLP payload
-		if let Err(err) = self.client.decode_transaction(&transaction.rlp_bytes()) {
+		if let Err(err) = self.client.decode_transaction(&transaction.envelope()) {
 			debug!(target: "txqueue", "[{:?}] Rejected transaction's rlp payload", err);
 			return Err(err)
 		}
@@ -216,6 +216,8 @@ fn execute_import_light(cmd: ImportBlockchain) -> Result<(), String> {
 	let service = LightClientService::start(config, &spec, fetch, db, cache)
 		.map_err(|e| format!("Failed to start client: {}", e))?;
 
+	let eip1559_transition = spec.params().eip1559_transition;
+
 	// free up the spec in memory.
 	drop(spec);
 
@@ -245,7 +247,8 @@ fn execute_import_light(cmd: ImportBlockchain) -> Result<(), String> {
 	let do_import = |bytes: Vec<u8>| {
 		while client.queue_info().is_full() { sleep(Duration::from_secs(1)); }
 
-		let header: ::types::header::Header = ::rlp::Rlp::new(&bytes).val_at(0)
+		let header = ::rlp::Rlp::new(&bytes).at(0)
+			.and_then(|rlp| ::types::header::Header::decode_rlp(&rlp, eip1559_transition))
 			.map_err(|e| format!("Bad block: {}", e))?;
 
 		if client.best_block_header().number() >= header.number() { return Ok(()) }
@@ -211,6 +211,19 @@ usage! {
 				"<FILE>",
 				"File",
 			}
+
+			CMD cmd_tools_enrtree
+			{
+				"Build and sign an EIP-1459 node list of the node table of the given --chain (default: mainnet), printed as DNS TXT records. Only nodes found through discovery v5 or node lists are included",
+
+				ARG arg_tools_enrtree_seq: (Option<u64>) = None,
+				"--seq=[NUM]",
+				"Sequence number of the list, which must increase with every published version. Defaults to the current UNIX time.",
+
+				ARG arg_tools_enrtree_domain: (Option<String>) = None,
+				"<DOMAIN>",
+				"Domain to publish the node list under",
+			}
 		}
 
 		CMD cmd_db
@@ -407,6 +420,22 @@ usage! {
 			"--no-discovery",
 			"Disable new peer discovery.",
 
+			FLAG flag_no_discovery_v4: (bool) = false, or |c: &Config| c.network.as_ref()?.discovery_v4.map(|d| !d).clone(),
+			"--no-discovery-v4",
+			"Disable peer discovery using the discovery v4 protocol, e.g. to find peers only through discovery v5 and node lists.",
+
+			FLAG flag_discovery_v5: (bool) = false, or |c: &Config| c.network.as_ref()?.discovery_v5.clone(),
+			"--discovery-v5",
+			"Enable peer discovery using the discovery v5 protocol alongside discovery v4.",
+
+			ARG arg_discovery_v5_port: (Option<u16>) = None, or |c: &Config| c.network.as_ref()?.discovery_v5_port.clone(),
+			"--discovery-v5-port=[PORT]",
+			"Run discovery v5 on a separate UDP port. By default it shares the discovery v4 port.",
+
+			ARG arg_discovery_v5_filter: (Option<String>) = None, or |c: &Config| c.network.as_ref()?.discovery_v5_filter.clone(),
+			"--discovery-v5-filter=[KEY[=HEX]]",
+			"Only add nodes found through discovery v5 whose node record has entry KEY, optionally containing the hex encoded value HEX, e.g. eth=<fork hash>.",
+
 			FLAG flag_reserved_only: (bool) = false, or |c: &Config| c.network.as_ref()?.reserved_only.clone(),
 			"--reserved-only",
 			"Connect only to reserved nodes.",
@@ -461,7 +490,7 @@ usage! {
 
 			ARG arg_bootnodes: (Option<String>) = None, or |c: &Config| c.network.as_ref()?.bootnodes.as_ref().map(|vec| vec.join(",")),
 			"--bootnodes=[NODES]",
-			"Override the bootnodes from our chain. NODES should be comma-delimited enodes.",
+			"Override the bootnodes from our chain. NODES should be comma-delimited enodes or enrtree:// node list URLs (EIP-1459).",
 
 			ARG arg_node_key: (Option<String>) = None, or |c: &Config| c.network.as_ref()?.node_key.clone(),
 			"--node-key=[KEY]",
@@ -580,6 +609,19 @@ usage! {
 			"--ipc-apis=[APIS]",
 			"Specify custom API set available via JSON-RPC over IPC using a comma-delimited list of API names. Possible names are: all, safe, web3, net, eth, pubsub, personal, signer, parity, parity_pubsub, parity_accounts, parity_set, traces, rpc, secretstore. You can also disable a specific API by putting '-' in the front, example: all,-personal. 'safe' enables the following APIs: web3, net, eth, pubsub, parity, parity_pubsub, traces, rpc",
 
+		["Metrics Options"]
+			FLAG flag_metrics: (bool) = false, or |c: &Config| c.metrics.as_ref()?.enable.clone(),
+			"--metrics",
+			"Enable the Prometheus metrics endpoint at /metrics.",
+
+			ARG arg_metrics_port: (u16) = 3000u16, or |c: &Config| c.metrics.as_ref()?.port.clone(),
+			"--metrics-port=[PORT]",
+			"Specify the port portion of the metrics server.",
+
+			ARG arg_metrics_interface: (String) = "local", or |c: &Config| c.metrics.as_ref()?.interface.clone(),
+			"--metrics-interface=[IP]",
+			"Specify the hostname portion of the metrics server, IP should be an interface's IP address, or all (all interfaces) or local.",
+
 		["Light Client Options"]
 			ARG arg_on_demand_response_time_window: (Option<u64>) = None, or |c: &Config| c.light.as_ref()?.on_demand_response_time_window,
 			"--on-demand-time-window=[S]",
@@ -801,6 +843,10 @@ usage! {
 			"--engine-signer=[ADDRESS]",
 			"Specify the address which should be used to sign consensus messages and issue blocks. Relevant only to non-PoW chains.",
 
+			ARG arg_engine_signer_url: (Option<String>) = None, or |c: &Config| c.mining.as_ref()?.engine_signer_url.clone(),
+			"--engine-signer-url=[URL]",
+			"Use the key of the --engine-signer address held by an external signing service instead of a local account. URL is either an HTTP(S) URL or the path of a Unix socket prefixed with ipc:, e.g. ipc:/run/signer.ipc.",
+
 			ARG arg_tx_gas_limit: (Option<String>) = None, or |c: &Config| c.mining.as_ref()?.tx_gas_limit.clone(),
 			"--tx-gas-limit=[GAS]",
 			"Apply a limit of GAS as the maximum amount of gas a single transaction may have for it to be mined.",
@@ -1143,6 +1189,7 @@ struct Config {
 	rpc: Option<Rpc>,
 	websockets: Option<Ws>,
 	ipc: Option<Ipc>,
+	metrics: Option<Metrics>,
 	dapps: Option<Dapps>,
 	secretstore: Option<SecretStore>,
 	private_tx: Option<PrivateTransactions>,
@@ -1238,6 +1285,10 @@ struct Network {
 	id: Option<u64>,
 	bootnodes: Option<Vec<String>>,
 	discovery: Option<bool>,
+	discovery_v4: Option<bool>,
+	discovery_v5: Option<bool>,
+	discovery_v5_port: Option<u16>,
+	discovery_v5_filter: Option<String>,
 	node_key: Option<String>,
 	reserved_peers: Option<String>,
 	reserved_only: Option<bool>,
@@ -1282,6 +1333,14 @@ struct Ipc {
 	apis: Option<Vec<String>>,
 }
 
+#[derive(Default, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Metrics {
+	enable: Option<bool>,
+	port: Option<u16>,
+	interface: Option<String>,
+}
+
 #[derive(Default, Debug, PartialEq, Deserialize)]
 #[serde(deny_unknown_fields)]
 struct Dapps {
@@ -1332,6 +1391,7 @@ struct SecretStore {
 struct Mining {
 	author: Option<String>,
 	engine_signer: Option<String>,
+	engine_signer_url: Option<String>,
 	force_sealing: Option<bool>,
 	reseal_on_uncle: Option<bool>,
 	reseal_on_txs: Option<String>,
@@ -1694,6 +1754,7 @@ mod tests {
 			cmd_restore: false,
 			cmd_tools: false,
 			cmd_tools_hash: false,
+			cmd_tools_enrtree: false,
 			cmd_db: false,
 			cmd_db_kill: false,
 			cmd_db_reset: false,
@@ -1710,6 +1771,8 @@ mod tests {
 			arg_snapshot_file: None,
 			arg_restore_file: None,
 			arg_tools_hash_file: None,
+			arg_tools_enrtree_seq: None,
+			arg_tools_enrtree_domain: None,
 
 			arg_enable_signing_queue: false,
 			arg_signer_sign_id: None,
@@ -1784,6 +1847,10 @@ mod tests {
 			arg_network_id: Some(1),
 			arg_bootnodes: Some("".into()),
 			flag_no_discovery: false,
+			flag_no_discovery_v4: false,
+			flag_discovery_v5: false,
+			arg_discovery_v5_port: None,
+			arg_discovery_v5_filter: None,
 			arg_node_key: None,
 			arg_reserved_peers: Some("./path_to_file".into()),
 			flag_reserved_only: false,
@@ -1820,6 +1887,12 @@ mod tests {
 			arg_ipc_path: "$HOME/.parity/jsonrpc.ipc".into(),
 			arg_ipc_apis: "web3,eth,net,parity,parity_accounts,personal,traces,rpc,secretstore".into(),
 			arg_ipc_chmod: "660".into(),
+
+			// METRICS
+			flag_metrics: false,
+			arg_metrics_port: 3000u16,
+			arg_metrics_interface: "local".into(),
+
 			// DAPPS
 			arg_dapps_path: Some("$HOME/.parity/dapps".into()),
 			flag_no_dapps: false,
@@ -1848,6 +1921,7 @@ mod tests {
 			// -- Sealing/Mining Options
 			arg_author: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
 			arg_engine_signer: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
+			arg_engine_signer_url: None,
 			flag_force_sealing: true,
 			arg_reseal_on_txs: "all".into(),
 			arg_reseal_min_period: 4000u64,
@@ -2053,6 +2127,10 @@ mod tests {
 				id: None,
 				bootnodes: None,
 				discovery: Some(true),
+				discovery_v4: None,
+				discovery_v5: None,
+				discovery_v5_port: None,
+				discovery_v5_filter: None,
 				node_key: None,
 				reserved_peers: Some("./path/to/reserved_peers".into()),
 				reserved_only: Some(true),
@@ -2087,6 +2165,7 @@ mod tests {
 				chmod: None,
 				apis: Some(vec!["rpc".into(), "eth".into()]),
 			}),
+			metrics: None,
 			dapps: Some(Dapps {
 				_legacy_disable: None,
 				_legacy_port: Some(8080),
@@ -2122,6 +2201,7 @@ mod tests {
 			mining: Some(Mining {
 				author: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
 				engine_signer: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
+				engine_signer_url: None,
 				force_sealing: Some(true),
 				reseal_on_txs: Some("all".into()),
 				reseal_on_uncle: None,
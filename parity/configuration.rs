@@ -23,6 +23,7 @@ use std::iter::FromIterator;
 use std::cmp;
 use cli::{Args, ArgsError};
 use hash::keccak;
+use rustc_hex::FromHex;
 use ethereum_types::{U256, H256, Address};
 use parity_version::{version_data, version};
 use bytes::Bytes;
@@ -45,14 +46,16 @@ use dir::{self, Directories, default_hypervisor_path, default_local_path, defaul
 use ethcore_private_tx::{ProviderConfig, EncryptorConfig};
 use secretstore::{NodeSecretKey, Configuration as SecretStoreConfiguration, ContractAddress as SecretStoreContractAddress};
 use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
+use metrics::MetricsConfiguration;
 use run::RunCmd;
 use types::data_format::DataFormat;
 use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, ExportState, ResetBlockchain};
 use export_hardcoded_sync::ExportHsyncCmd;
+use node_list::NodeListCmd;
 use presale::ImportWallet;
 use account::{AccountCmd, NewAccount, ListAccounts, ImportAccounts, ImportFromGethAccounts};
 use snapshot_cmd::{self, SnapshotCommand};
-use network::{IpFilter, NatType};
+use network::{EnrFilter, IpFilter, NatType};
 
 const DEFAULT_MAX_PEERS: u16 = 50;
 const DEFAULT_MIN_PEERS: u16 = 25;
@@ -84,6 +87,7 @@ pub enum Cmd {
 	Snapshot(SnapshotCommand),
 	Hash(Option<String>),
 	ExportHardcodedSync(ExportHsyncCmd),
+	NodeList(NodeListCmd),
 }
 
 pub struct Execute {
@@ -179,6 +183,14 @@ impl Configuration {
 			}
 		} else if self.args.cmd_tools && self.args.cmd_tools_hash {
 			Cmd::Hash(self.args.arg_tools_hash_file)
+		} else if self.args.cmd_tools && self.args.cmd_tools_enrtree {
+			Cmd::NodeList(NodeListCmd {
+				dirs,
+				spec,
+				net_conf,
+				domain: self.args.arg_tools_enrtree_domain.clone().expect("CLI argument is required; qed"),
+				seq: self.args.arg_tools_enrtree_seq,
+			})
 		} else if self.args.cmd_db && self.args.cmd_db_reset {
 			Cmd::Blockchain(BlockchainCmd::Reset(ResetBlockchain {
 				dirs,
@@ -383,6 +395,7 @@ impl Configuration {
 				gas_pricer_conf: self.gas_pricer_config()?,
 				miner_extras: self.miner_extras()?,
 				stratum: self.stratum_options()?,
+				metrics_conf: self.metrics_config(),
 				update_policy,
 				allow_missing_blocks: self.args.flag_jsonrpc_allow_missing_blocks,
 				mode,
@@ -432,6 +445,7 @@ impl Configuration {
 			extra_data: self.extra_data()?,
 			gas_range_target: (floor, ceil),
 			engine_signer: self.engine_signer()?,
+			engine_signer_url: self.args.arg_engine_signer_url.clone(),
 			work_notify: self.work_notify(),
 			local_accounts: HashSet::from_iter(to_addresses(&self.args.arg_tx_queue_locals)?.into_iter()),
 		};
@@ -504,6 +518,23 @@ impl Configuration {
 		}
 	}
 
+	fn discovery_v5_filter(&self) -> Result<Option<EnrFilter>, String> {
+		let filter = match self.args.arg_discovery_v5_filter {
+			Some(ref filter) => filter,
+			None => return Ok(None),
+		};
+		let mut parts = filter.splitn(2, '=');
+		let key = parts.next().unwrap_or("").to_owned();
+		if key.is_empty() {
+			return Err(format!("Invalid discovery v5 filter: {}", filter));
+		}
+		let value = match parts.next() {
+			Some(value) => Some(value.trim_start_matches("0x").from_hex().map_err(|e| format!("Invalid discovery v5 filter: {}", e))?),
+			None => None,
+		};
+		Ok(Some(EnrFilter { key, value }))
+	}
+
 	fn min_peers(&self) -> u32 {
 		self.args.arg_min_peers
 			.or(cmp::min(self.args.arg_max_peers, Some(DEFAULT_MIN_PEERS)))
@@ -750,6 +781,10 @@ impl Configuration {
 			Some(Err(err)) => return Err(err),
 		};
 		ret.discovery_enabled = !self.args.flag_no_discovery && !self.args.flag_nodiscover;
+		ret.discovery_v4_enabled = !self.args.flag_no_discovery_v4 && ret.discovery_enabled;
+		ret.discovery_v5_enabled = self.args.flag_discovery_v5 && ret.discovery_enabled;
+		ret.discovery_v5_port = self.args.arg_discovery_v5_port;
+		ret.discovery_v5_filter = self.discovery_v5_filter()?;
 		ret.max_peers = self.max_peers();
 		ret.min_peers = self.min_peers();
 		ret.snapshot_peers = self.snapshot_peers();
@@ -902,6 +937,14 @@ impl Configuration {
 		Ok(conf)
 	}
 
+	fn metrics_config(&self) -> MetricsConfiguration {
+		MetricsConfiguration {
+			enabled: self.args.flag_metrics,
+			interface: self.metrics_interface(),
+			port: self.args.arg_ports_shift + self.args.arg_metrics_port,
+		}
+	}
+
 	fn private_provider_config(&self) -> Result<(ProviderConfig, EncryptorConfig, bool), String> {
 		let dirs = self.directories();
 		let provider_conf = ProviderConfig {
@@ -1099,6 +1142,10 @@ impl Configuration {
 		self.interface(&self.args.arg_stratum_interface)
 	}
 
+	fn metrics_interface(&self) -> String {
+		self.interface(&self.args.arg_metrics_interface)
+	}
+
 	fn rpc_enabled(&self) -> bool {
 		!self.args.flag_jsonrpc_off && !self.args.flag_no_jsonrpc
 	}
@@ -1261,6 +1308,20 @@ mod tests {
 		}));
 	}
 
+	#[test]
+	fn test_command_tools_enrtree() {
+		let args = vec!["parity", "tools", "enrtree", "nodes.example.org", "--seq", "3"];
+		let conf = parse(&args);
+		let net_conf = conf.net_config().unwrap();
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::NodeList(NodeListCmd {
+			dirs: Default::default(),
+			spec: Default::default(),
+			net_conf,
+			domain: "nodes.example.org".into(),
+			seq: Some(3),
+		}));
+	}
+
 	#[test]
 	fn test_command_blockchain_import() {
 		let args = vec!["parity", "import", "blockchain.json"];
@@ -1441,6 +1502,7 @@ mod tests {
 			fat_db: Default::default(),
 			snapshot_conf: Default::default(),
 			stratum: None,
+			metrics_conf: Default::default(),
 			check_seal: true,
 			download_old_blocks: true,
 			verifier_settings: Default::default(),
@@ -1522,6 +1584,37 @@ mod tests {
 		}));
 	}
 
+	#[test]
+	fn should_parse_discovery_v5_filter() {
+		// given
+		let key_only = parse(&["parity", "--discovery-v5", "--discovery-v5-filter", "eth"]);
+		let with_value = parse(&["parity", "--discovery-v5", "--discovery-v5-filter", "eth=0xfc64ec04"]);
+		let invalid = parse(&["parity", "--discovery-v5", "--discovery-v5-filter", "eth=zz"]);
+
+		// then
+		let net = key_only.net_config().unwrap();
+		assert!(net.discovery_v5_enabled);
+		assert_eq!(net.discovery_v5_filter, Some(EnrFilter { key: "eth".into(), value: None }));
+		assert_eq!(with_value.net_config().unwrap().discovery_v5_filter, Some(EnrFilter {
+			key: "eth".into(),
+			value: Some(vec![0xfc, 0x64, 0xec, 0x04]),
+		}));
+		assert!(invalid.net_config().is_err());
+	}
+
+	#[test]
+	fn should_disable_discovery_v4() {
+		// given
+		let v5_only = parse(&["parity", "--discovery-v5", "--no-discovery-v4"]);
+		let defaults = parse(&["parity", "--discovery-v5"]);
+
+		// then
+		let net = v5_only.net_config().unwrap();
+		assert!(!net.discovery_v4_enabled);
+		assert!(net.discovery_v5_enabled);
+		assert!(defaults.net_config().unwrap().discovery_v4_enabled);
+	}
+
 	#[test]
 	fn should_parse_rpc_settings_with_geth_compatiblity() {
 		// given
@@ -1773,6 +1866,7 @@ mod tests {
 		assert_eq!(conf0.secretstore_config().unwrap().port, 8084);
 		assert_eq!(conf0.secretstore_config().unwrap().http_port, 8083);
 		assert_eq!(conf0.stratum_options().unwrap().unwrap().port, 8009);
+		assert_eq!(conf0.metrics_config().port, 3001);
 
 		assert_eq!(conf1.net_addresses().unwrap().0.port(), 30304);
 		assert_eq!(conf1.network_settings().unwrap().network_port, 30304);
@@ -1783,6 +1877,19 @@ mod tests {
 		assert_eq!(conf1.secretstore_config().unwrap().http_port, 8083);
 	}
 
+	#[test]
+	fn should_parse_metrics_config() {
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--metrics", "--metrics-port", "9090", "--metrics-interface", "all"]);
+
+		assert_eq!(conf0.metrics_config(), MetricsConfiguration::default());
+		assert_eq!(conf1.metrics_config(), MetricsConfiguration {
+			enabled: true,
+			interface: "0.0.0.0".into(),
+			port: 9090,
+		});
+	}
+
 	#[test]
 	fn should_resolve_external_nat_hosts() {
 		// Ip works
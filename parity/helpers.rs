@@ -29,7 +29,7 @@ use cache::CacheConfig;
 use dir::DatabaseDirectories;
 use dir::helpers::replace_home;
 use upgrade::{upgrade, upgrade_data_paths};
-use sync::{validate_node_url, self};
+use sync::{validate_node_url, validate_node_list_url, self};
 use db::migrate;
 use path;
 use ethkey::Password;
@@ -189,7 +189,8 @@ pub fn parity_ipc_path(base: &str, path: &str, shift: u16) -> String {
 pub fn to_bootnodes(bootnodes: &Option<String>) -> Result<Vec<String>, String> {
 	match *bootnodes {
 		Some(ref x) if !x.is_empty() => x.split(',').map(|s| {
-			match validate_node_url(s).map(Into::into) {
+			let error = if s.starts_with("enrtree://") { validate_node_list_url(s) } else { validate_node_url(s) };
+			match error.map(Into::into) {
 				None => Ok(s.to_owned()),
 				Some(sync::Error::AddressResolve(_)) => Err(format!("Failed to resolve hostname of a boot node: {}", s)),
 				Some(_) => Err(format!("Invalid node address format given for a boot node: {}", s)),
@@ -214,6 +215,10 @@ pub fn default_network_config() -> ::sync::NetworkConfiguration {
 		nat_enabled: true,
 		nat_type: NatType::Any,
 		discovery_enabled: true,
+		discovery_v4_enabled: true,
+		discovery_v5_enabled: false,
+		discovery_v5_port: None,
+		discovery_v5_filter: None,
 		boot_nodes: Vec::new(),
 		use_secret: None,
 		max_peers: 50,
@@ -499,6 +504,10 @@ but the first password is trimmed
 		assert_eq!(to_bootnodes(&None), Ok(vec![]));
 		assert_eq!(to_bootnodes(&Some(one_bootnode.into())), Ok(vec![one_bootnode.into()]));
 		assert_eq!(to_bootnodes(&Some(two_bootnodes.into())), Ok(vec![one_bootnode.into(), one_bootnode.into()]));
+
+		let node_list = "enrtree://AM5FCQLWIZX2QFPNJAP7VUERCCRNGRHWZG3YYHIUV7BVDQ5FDPRT2@nodes.example.org";
+		assert_eq!(to_bootnodes(&Some(node_list.into())), Ok(vec![node_list.into()]));
+		assert!(to_bootnodes(&Some("enrtree://AAAA@nodes.example.org".into())).is_err());
 	}
 
 	#[test]
@@ -23,6 +23,7 @@ extern crate docopt;
 extern crate clap;
 extern crate dir;
 extern crate futures;
+extern crate hyper;
 extern crate atty;
 extern crate jsonrpc_core;
 extern crate num_cpus;
@@ -74,6 +75,7 @@ extern crate parity_version;
 extern crate registrar;
 extern crate snapshot;
 extern crate spec;
+extern crate stats;
 extern crate verification;
 
 #[macro_use]
@@ -109,7 +111,9 @@ mod deprecated;
 mod helpers;
 mod informant;
 mod light_helpers;
+mod metrics;
 mod modules;
+mod node_list;
 mod params;
 mod presale;
 mod rpc;
@@ -216,6 +220,7 @@ fn execute<Cr, Rr>(
 		Cmd::SignerReject { id, port, authfile } => cli_signer::signer_reject(id, port, authfile).map(|s| ExecutionAction::Instant(Some(s))),
 		Cmd::Snapshot(snapshot_cmd) => snapshot_cmd::execute(snapshot_cmd).map(|s| ExecutionAction::Instant(Some(s))),
 		Cmd::ExportHardcodedSync(export_hs_cmd) => export_hardcoded_sync::execute(export_hs_cmd).map(|s| ExecutionAction::Instant(Some(s))),
+		Cmd::NodeList(node_list_cmd) => node_list::execute(node_list_cmd).map(|s| ExecutionAction::Instant(Some(s))),
 	}
 }
 
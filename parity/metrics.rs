@@ -0,0 +1,153 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Prometheus metrics endpoint.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use ethcore::client::Client;
+use ethcore::miner::{Miner, MinerService};
+use futures::sync::oneshot;
+use futures::Future;
+use hyper::{service::service_fn_ok, Body, Method, Request, Response, Server, StatusCode};
+use parity_runtime::Executor;
+use snapshot::SnapshotService as SS;
+use snapshot::service::Service as SnapshotService;
+use stats::{MetricType, PrometheusMetrics, PrometheusRegistry};
+use sync::SyncProvider;
+use types::snapshot::RestorationStatus;
+
+/// Prefix of the names of all metrics.
+const METRICS_PREFIX: &str = "oe_";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricsConfiguration {
+	/// Is the metrics endpoint enabled (default is false)?
+	pub enabled: bool,
+	/// The IP of the network interface used (default is 127.0.0.1).
+	pub interface: String,
+	/// The network port (default is 3000).
+	pub port: u16,
+}
+
+impl Default for MetricsConfiguration {
+	fn default() -> Self {
+		MetricsConfiguration {
+			enabled: false,
+			interface: "127.0.0.1".into(),
+			port: 3000,
+		}
+	}
+}
+
+impl MetricsConfiguration {
+	pub fn address(&self) -> Option<SocketAddr> {
+		if !self.enabled {
+			return None;
+		}
+
+		format!("{}:{}", self.interface, self.port).parse().ok()
+	}
+}
+
+/// The components reporting metrics.
+pub struct MetricsDependencies {
+	pub client: Arc<Client>,
+	pub sync: Arc<dyn SyncProvider>,
+	pub miner: Arc<Miner>,
+	pub snapshot: Arc<SnapshotService<Client>>,
+}
+
+impl PrometheusMetrics for MetricsDependencies {
+	fn prometheus_metrics(&self, r: &mut PrometheusRegistry) {
+		self.client.prometheus_metrics(r);
+		self.sync.prometheus_metrics(r);
+
+		let queue = self.miner.queue_status();
+		r.register_gauge("txpool_transactions", "Transactions in the pool", queue.status.transaction_count);
+		r.register_gauge("txpool_senders", "Senders of the transactions in the pool", queue.status.senders);
+		r.register_gauge("txpool_mem_bytes", "Memory used by the transactions in the pool", queue.status.mem_usage);
+		r.register_gauge("txpool_max_transactions", "Maximum number of transactions in the pool", queue.limits.max_count);
+
+		let (state_chunks, block_chunks, state_chunks_done, block_chunks_done) = match self.snapshot.status() {
+			RestorationStatus::Ongoing { state_chunks, block_chunks, state_chunks_done, block_chunks_done } =>
+				(state_chunks, block_chunks, state_chunks_done, block_chunks_done),
+			RestorationStatus::Initializing { state_chunks, block_chunks, .. } =>
+				(state_chunks, block_chunks, 0, 0),
+			_ => (0, 0, 0, 0),
+		};
+		r.register_labeled(MetricType::Gauge, "snapshot_chunks", "Chunks of the snapshot being restored", "kind", vec![
+			("state", state_chunks),
+			("block", block_chunks),
+		]);
+		r.register_labeled(MetricType::Gauge, "snapshot_chunks_done", "Restored chunks of the snapshot", "kind", vec![
+			("state", state_chunks_done),
+			("block", block_chunks_done),
+		]);
+	}
+}
+
+/// Running metrics server. Dropping it shuts the server down.
+pub struct MetricsServer {
+	_shutdown: oneshot::Sender<()>,
+}
+
+fn handle_request(req: Request<Body>, deps: &MetricsDependencies) -> Response<Body> {
+	match (req.method(), req.uri().path()) {
+		(&Method::GET, "/metrics") => {
+			let mut registry = PrometheusRegistry::new(METRICS_PREFIX);
+			deps.prometheus_metrics(&mut registry);
+			Response::builder()
+				.header("Content-Type", "text/plain; version=0.0.4")
+				.body(Body::from(registry.render()))
+				.expect("Static headers are valid; qed")
+		}
+		_ => Response::builder()
+			.status(StatusCode::NOT_FOUND)
+			.body(Body::empty())
+			.expect("Static status is valid; qed"),
+	}
+}
+
+/// Start the metrics server, if enabled.
+pub fn start(
+	conf: MetricsConfiguration,
+	deps: MetricsDependencies,
+	executor: Executor,
+) -> Result<Option<MetricsServer>, String> {
+	let addr = match conf.address() {
+		Some(addr) => addr,
+		None if conf.enabled => return Err(format!("Invalid metrics listen host/port given: {}:{}", conf.interface, conf.port)),
+		None => return Ok(None),
+	};
+
+	let deps = Arc::new(deps);
+	let (shutdown, shutdown_signal) = oneshot::channel();
+	let server = Server::try_bind(&addr)
+		.map_err(|e| format!("Metrics address {} is already in use, make sure that another instance of an Ethereum client is not running or change the address using the --metrics-port and --metrics-interface options: {}", addr, e))?
+		.serve(move || {
+			let deps = deps.clone();
+			service_fn_ok(move |req| handle_request(req, &deps))
+		})
+		.with_graceful_shutdown(shutdown_signal.then(|_| Ok::<_, ()>(())))
+		.map_err(|e| warn!("Metrics server error: {}", e));
+
+	info!("Metrics server listening on http://{}/metrics", addr);
+	executor.spawn(server);
+
+	Ok(Some(MetricsServer { _shutdown: shutdown }))
+}
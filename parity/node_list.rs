@@ -0,0 +1,84 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use dir::Directories;
+use params::SpecType;
+use spec::SpecParams;
+use sync::{self, NetworkConfiguration};
+use types::engines::OptimizeFor;
+
+/// Maximum length of a character string in a TXT record.
+const MAX_TXT_STRING: usize = 255;
+
+#[derive(Debug, PartialEq)]
+pub struct NodeListCmd {
+	pub dirs: Directories,
+	pub spec: SpecType,
+	pub net_conf: NetworkConfiguration,
+	pub domain: String,
+	pub seq: Option<u64>,
+}
+
+/// Builds the signed node list of the node table and returns it as zone file records.
+pub fn execute(cmd: NodeListCmd) -> Result<String, String> {
+	// load spec
+	let spec = cmd.spec.spec(SpecParams::new(cmd.dirs.cache.as_ref(), OptimizeFor::Memory))?;
+
+	// load genesis hash
+	let genesis_hash = spec.genesis_header().hash();
+
+	// database paths
+	let db_dirs = cmd.dirs.database(genesis_hash, cmd.spec.legacy_fork_name(), spec.data_dir.clone());
+
+	let mut net_conf = cmd.net_conf;
+	net_conf.net_config_path = Some(db_dirs.network_path().to_string_lossy().into_owned());
+	let net_conf = net_conf.into_basic().map_err(|e| format!("Failed to produce network config: {}", e))?;
+
+	let seq = cmd.seq.unwrap_or_else(|| {
+		SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+	});
+	let list = sync::export_node_list(&net_conf, &cmd.domain, seq)
+		.map_err(|e| format!("Failed to build node list: {}", e))?;
+
+	let mut zone = format!("; {}\n", list.url);
+	for (name, content) in list.records {
+		zone.push_str(&format!("{}. IN TXT {}\n", name, txt_strings(&content)));
+	}
+	Ok(zone)
+}
+
+/// Quotes TXT record content, split into character strings of the maximum length.
+fn txt_strings(content: &str) -> String {
+	content.as_bytes()
+		.chunks(MAX_TXT_STRING)
+		.map(|chunk| format!("\"{}\"", String::from_utf8_lossy(chunk)))
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::txt_strings;
+
+	#[test]
+	fn splits_long_txt_records() {
+		assert_eq!(txt_strings("enrtree-branch:"), "\"enrtree-branch:\"");
+		let record = "a".repeat(300);
+		assert_eq!(txt_strings(&record), format!("\"{}\" \"{}\"", "a".repeat(255), "a".repeat(45)));
+	}
+}
@@ -33,6 +33,7 @@ use hash_fetch::{self, fetch};
 use informant::{Informant, LightNodeInformantData, FullNodeInformantData};
 use journaldb::Algorithm;
 use light::Cache as LightDataCache;
+use metrics::{self, MetricsConfiguration};
 use miner::external::ExternalMiner;
 use miner::work_notify::WorkPoster;
 use node_filter::NodeFilter;
@@ -46,6 +47,7 @@ use types::{
 use parity_rpc::{
 	Origin, Metadata, NetworkSettings, informant, PubSubSession, FutureResult, FutureResponse, FutureOutput
 };
+use parity_rpc::signer::RemoteEngineSigner;
 use updater::{UpdateFilter, UpdatePolicy, Updater};
 use parity_version::version;
 use ethcore_private_tx::{ProviderConfig, EncryptorConfig, SecretStoreEncryptor};
@@ -122,6 +124,7 @@ pub struct RunCmd {
 	pub name: String,
 	pub custom_bootnodes: bool,
 	pub stratum: Option<stratum::Options>,
+	pub metrics_conf: MetricsConfiguration,
 	pub snapshot_conf: SnapshotConfiguration,
 	pub check_seal: bool,
 	pub allow_missing_blocks: bool,
@@ -172,6 +175,10 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 	use sync::{LightSyncParams, LightSync, ManageNetwork};
 	use parking_lot::{Mutex, RwLock};
 
+	if cmd.metrics_conf.enabled {
+		warn!("The metrics endpoint is not available for light clients. Ignoring --metrics.");
+	}
+
 	// load spec
 	let spec = cmd.spec.spec(SpecParams::new(cmd.dirs.cache.as_ref(), OptimizeFor::Memory))?;
 
@@ -462,6 +469,7 @@ fn execute_impl<Cr, Rr>(
 	}
 
 	sync_config.fork_block = spec.fork_block();
+	sync_config.eip1559_transition = spec.params().eip1559_transition;
 	let snapshot_supported =
 		if let Snapshotting::Unsupported = spec.engine.snapshot_mode() {
 			false
@@ -527,7 +535,14 @@ fn execute_impl<Cr, Rr>(
 	}
 
 	let engine_signer = cmd.miner_extras.engine_signer;
-	if engine_signer != Default::default() {
+	if let Some(ref url) = cmd.miner_extras.engine_signer_url {
+		if engine_signer == Default::default() {
+			return Err("--engine-signer-url requires the signer's address to be given with --engine-signer".into());
+		}
+		let signer = RemoteEngineSigner::new(url, engine_signer)?;
+		info!("Using the remote signer at {} for consensus key {:?}", url, engine_signer);
+		miner.set_author(miner::Author::Sealer(Box::new(signer)));
+	} else if engine_signer != Default::default() {
 		if let Some(author) = account_utils::miner_author(&cmd.spec, &cmd.dirs, &account_provider, engine_signer, &passwords)? {
 			miner.set_author(author);
 		}
@@ -782,6 +797,15 @@ fn execute_impl<Cr, Rr>(
 	};
 	let secretstore_key_server = secretstore::start(cmd.secretstore_conf.clone(), secretstore_deps, runtime.executor())?;
 
+	// the metrics server
+	let metrics_deps = metrics::MetricsDependencies {
+		client: client.clone(),
+		sync: sync_provider.clone(),
+		miner: miner.clone(),
+		snapshot: snapshot_service.clone(),
+	};
+	let metrics_server = metrics::start(cmd.metrics_conf.clone(), metrics_deps, runtime.executor())?;
+
 	// the informant
 	let informant = Arc::new(Informant::new(
 		FullNodeInformantData {
@@ -845,7 +869,7 @@ fn execute_impl<Cr, Rr>(
 			informant,
 			client,
 			client_service: Arc::new(service),
-			keep_alive: Box::new((watcher, updater, ws_server, http_server, ipc_server, secretstore_key_server, runtime)),
+			keep_alive: Box::new((watcher, updater, ws_server, http_server, ipc_server, metrics_server, secretstore_key_server, runtime)),
 		}
 	})
 }
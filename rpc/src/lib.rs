@@ -81,6 +81,7 @@ extern crate parity_runtime;
 extern crate parity_updater as updater;
 extern crate parity_version as version;
 extern crate eip_712;
+extern crate engine;
 extern crate rlp;
 extern crate account_state;
 
@@ -104,9 +105,6 @@ extern crate serde_derive;
 #[cfg(test)]
 extern crate rand_xorshift;
 
-#[cfg(test)]
-extern crate engine;
-
 #[cfg(test)]
 extern crate ethjson;
 #[cfg(test)]
@@ -112,6 +112,8 @@ impl<C: miner::BlockChainClient + BlockChainClient, M: MinerService> Dispatcher
 			value: request.value.unwrap_or_else(|| 0.into()),
 			data: request.data.unwrap_or_else(Vec::new),
 			condition: request.condition,
+			access_list: request.access_list,
+			max_priority_fee_per_gas: request.max_priority_fee_per_gas,
 		}))
 	}
 
@@ -169,6 +169,8 @@ where
 				value: request.value.unwrap_or_default(),
 				data: request.data.unwrap_or_else(Vec::new),
 				condition: request.condition,
+				access_list: request.access_list,
+				max_priority_fee_per_gas: request.max_priority_fee_per_gas,
 			}
 		};
 
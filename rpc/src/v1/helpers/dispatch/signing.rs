@@ -21,7 +21,7 @@ use bytes::Bytes;
 use crypto::DEFAULT_MAC;
 use ethereum_types::{H256, U256, Address};
 use crypto::publickey::Signature;
-use types::transaction::{Transaction, Action, SignedTransaction};
+use types::transaction::{Transaction, TypedTransaction, AccessListTx, EIP1559TransactionTx, Action, SignedTransaction};
 
 use jsonrpc_core::Result;
 use v1::helpers::{errors, FilledTransactionRequest};
@@ -50,6 +50,13 @@ impl super::Accounts for Signer {
 			value: filled.value,
 			data: filled.data,
 		};
+		let t = match (filled.max_priority_fee_per_gas, filled.access_list) {
+			(Some(max_priority_fee_per_gas), access_list) => TypedTransaction::EIP1559Transaction(
+				EIP1559TransactionTx::new(t, access_list.unwrap_or_default(), max_priority_fee_per_gas)
+			),
+			(None, Some(access_list)) => TypedTransaction::AccessList(AccessListTx::new(t, access_list)),
+			(None, None) => TypedTransaction::Legacy(t),
+		};
 
 		let hash = t.hash(chain_id);
 		let signature = signature(&*self.accounts, filled.from, hash, password)?;
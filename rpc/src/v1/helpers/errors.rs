@@ -453,6 +453,10 @@ pub fn transaction_message(error: &TransactionError) -> String {
 		NotAllowed => "Transaction is not permitted.".into(),
 		TooBig => "Transaction is too big, see chain specification for the limit.".into(),
 		InvalidRlp(ref descr) => format!("Invalid RLP data: {}", descr),
+		TransactionTypeNotEnabled => "Transaction type is not enabled on this chain yet.".into(),
+		PriorityFeeAboveMaxFee { max_fee, max_priority_fee } => {
+			format!("Max priority fee per gas ({}) is higher than max fee per gas ({}). Try decreasing the priority fee.", max_priority_fee, max_fee)
+		}
 	}
 }
 
@@ -231,6 +231,8 @@ mod test {
 			data: vec![],
 			nonce: None,
 			condition: None,
+			access_list: None,
+			max_priority_fee_per_gas: None,
 		})
 	}
 
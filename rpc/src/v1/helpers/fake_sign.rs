@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
-use types::transaction::{Transaction, SignedTransaction, Action};
+use types::transaction::{Transaction, TypedTransaction, AccessListTx, EIP1559TransactionTx, SignedTransaction, Action};
 use std::cmp::min;
 
 use ethereum_types::U256;
@@ -26,12 +26,21 @@ pub fn sign_call(request: CallRequest) -> Result<SignedTransaction, Error> {
 	let gas = min(request.gas.unwrap_or(max_gas), max_gas);
 	let from = request.from.unwrap_or_default();
 
-	Ok(Transaction {
+	let t = Transaction {
 		nonce: request.nonce.unwrap_or_default(),
 		action: request.to.map_or(Action::Create, Action::Call),
 		gas,
 		gas_price: request.gas_price.unwrap_or_default(),
 		value: request.value.unwrap_or_default(),
 		data: request.data.unwrap_or_default(),
-	}.fake_sign(from))
+	};
+	let t = match (request.max_priority_fee_per_gas, request.access_list) {
+		(Some(max_priority_fee_per_gas), access_list) => TypedTransaction::EIP1559Transaction(
+			EIP1559TransactionTx::new(t, access_list.unwrap_or_default(), max_priority_fee_per_gas)
+		),
+		(None, Some(access_list)) => TypedTransaction::AccessList(AccessListTx::new(t, access_list)),
+		(None, None) => TypedTransaction::Legacy(t),
+	};
+
+	Ok(t.fake_sign(from))
 }
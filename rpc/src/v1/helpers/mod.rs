@@ -28,6 +28,7 @@ pub mod external_signer;
 pub mod fake_sign;
 pub mod light_fetch;
 pub mod nonce;
+pub mod remote_engine_signer;
 #[cfg(any(test, feature = "accounts"))]
 pub mod secretstore;
 
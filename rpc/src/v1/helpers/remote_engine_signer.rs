@@ -0,0 +1,337 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! An `EngineSigner` keeping the consensus key in an external signing service, e.g. one backed
+//! by an HSM.
+//!
+//! The service is called with JSON-RPC 2.0, either over HTTP(S) or over a Unix socket
+//! (`ipc:<path>`), where each connection carries a single newline-terminated request and
+//! response. It has to implement the following methods, with all binary data hex encoded:
+//!
+//! - `signer_public(address)`: the 64 byte public key of the address.
+//! - `signer_sign(address, hash)`: the 65 byte signature `r || s || v` of the hash.
+//! - `signer_decrypt(address, authData, cipher)`: the ECIES decrypted message.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+
+use crypto::publickey::{self, Address, Message, Public, Signature, Error};
+use fetch::{self, Fetch, BodyReader, Url};
+use futures::Future;
+use http::hyper::header::{CONTENT_TYPE, HeaderValue};
+use rustc_hex::{FromHex, ToHex};
+use serde_json;
+
+/// How long to wait for the signing service to respond.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The maximum size of a response from the signing service.
+const MAX_RESPONSE_SIZE: usize = 64 * 1024;
+
+enum Transport {
+	Http(fetch::Client, Url),
+	#[cfg(unix)]
+	Ipc(PathBuf),
+}
+
+#[derive(Serialize)]
+struct RpcRequest<'a> {
+	jsonrpc: &'static str,
+	id: usize,
+	method: &'a str,
+	params: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct RpcResponse {
+	result: Option<String>,
+	error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+	code: i64,
+	message: String,
+}
+
+/// An implementation of `EngineSigner` delegating to an external signing service.
+pub struct RemoteEngineSigner {
+	transport: Transport,
+	address: Address,
+	public: Public,
+	next_id: AtomicUsize,
+}
+
+impl RemoteEngineSigner {
+	/// Connects to the signing service at `url` holding the key of `address`.
+	///
+	/// Fails if the service is not reachable or doesn't return a public key matching the address.
+	pub fn new(url: &str, address: Address) -> Result<Self, String> {
+		let transport = Self::transport(url)?;
+		let mut signer = RemoteEngineSigner {
+			transport,
+			address,
+			public: Public::default(),
+			next_id: AtomicUsize::new(1),
+		};
+
+		let public = signer.call("signer_public", vec![format!("{:?}", address)])?;
+		if public.len() != 64 {
+			return Err(format!("Invalid public key of length {} returned by the remote signer", public.len()));
+		}
+		signer.public = Public::from_slice(&public);
+		if publickey::public_to_address(&signer.public) != address {
+			return Err(format!("The remote signer returned a public key which doesn't belong to {:?}", address));
+		}
+		Ok(signer)
+	}
+
+	fn transport(url: &str) -> Result<Transport, String> {
+		#[cfg(unix)]
+		{
+			if url.starts_with("ipc:") {
+				return Ok(Transport::Ipc(PathBuf::from(&url["ipc:".len()..])));
+			}
+		}
+
+		let url = Url::parse(url).map_err(|e| format!("Invalid remote signer URL {}: {}", url, e))?;
+		match url.scheme() {
+			"http" | "https" => {
+				let client = fetch::Client::new(1).map_err(|e| format!("Error starting the HTTP client: {}", e))?;
+				Ok(Transport::Http(client, url))
+			},
+			scheme => Err(format!("Unsupported remote signer URL scheme: {}", scheme)),
+		}
+	}
+
+	/// Calls a method of the signing service and returns the decoded result.
+	fn call(&self, method: &str, params: Vec<String>) -> Result<Vec<u8>, String> {
+		let request = RpcRequest {
+			jsonrpc: "2.0",
+			id: self.next_id.fetch_add(1, Ordering::Relaxed),
+			method,
+			params,
+		};
+		let request = serde_json::to_vec(&request).expect("Serialization of a request can't fail; qed");
+
+		let response = match self.transport {
+			Transport::Http(ref client, ref url) => Self::call_http(client, url, request),
+			#[cfg(unix)]
+			Transport::Ipc(ref path) => Self::call_ipc(path, request),
+		}.map_err(|e| format!("Remote signer request {} failed: {}", method, e))?;
+
+		let response: RpcResponse = serde_json::from_slice(&response)
+			.map_err(|e| format!("Invalid response of the remote signer to {}: {}", method, e))?;
+		match (response.result, response.error) {
+			(_, Some(error)) => Err(format!("Remote signer error {}: {}", error.code, error.message)),
+			(Some(result), None) => result.trim_start_matches("0x").from_hex()
+				.map_err(|e| format!("Invalid result of the remote signer to {}: {}", method, e)),
+			(None, None) => Err(format!("Empty response of the remote signer to {}", method)),
+		}
+	}
+
+	fn call_http(client: &fetch::Client, url: &Url, request: Vec<u8>) -> Result<Vec<u8>, String> {
+		let abort = fetch::Abort::default()
+			.with_max_duration(REQUEST_TIMEOUT)
+			.with_max_size(MAX_RESPONSE_SIZE);
+		let request = fetch::Request::post(url.clone())
+			.with_header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+			.with_body(request);
+		let response = client.fetch(request, abort).wait().map_err(|e| e.to_string())?;
+		if !response.is_success() {
+			return Err(format!("HTTP status {}", response.status()));
+		}
+
+		let mut body = Vec::new();
+		BodyReader::new(response).read_to_end(&mut body).map_err(|e| e.to_string())?;
+		Ok(body)
+	}
+
+	#[cfg(unix)]
+	fn call_ipc(path: &Path, mut request: Vec<u8>) -> Result<Vec<u8>, String> {
+		let mut stream = UnixStream::connect(path).map_err(|e| e.to_string())?;
+		stream.set_read_timeout(Some(REQUEST_TIMEOUT)).map_err(|e| e.to_string())?;
+		stream.set_write_timeout(Some(REQUEST_TIMEOUT)).map_err(|e| e.to_string())?;
+
+		request.push(b'\n');
+		stream.write_all(&request).map_err(|e| e.to_string())?;
+
+		let mut response = Vec::new();
+		BufReader::new(stream.take(MAX_RESPONSE_SIZE as u64))
+			.read_until(b'\n', &mut response)
+			.map_err(|e| e.to_string())?;
+		Ok(response)
+	}
+}
+
+impl engine::signer::EngineSigner for RemoteEngineSigner {
+	fn sign(&self, message: Message) -> Result<Signature, Error> {
+		let signature = self.call("signer_sign", vec![format!("{:?}", self.address), format!("{:?}", message)])
+			.map_err(Error::Custom)?;
+		if signature.len() != 65 {
+			return Err(Error::InvalidSignature);
+		}
+		let signature = Signature::from_electrum(&signature);
+		// Don't pass on a signature of another key, which would only be rejected by our peers.
+		if publickey::recover(&signature, &message)? != self.public {
+			return Err(Error::Custom(format!("The remote signer didn't sign with the key of {:?}", self.address)));
+		}
+		Ok(signature)
+	}
+
+	fn decrypt(&self, auth_data: &[u8], cipher: &[u8]) -> Result<Vec<u8>, Error> {
+		self.call("signer_decrypt", vec![
+			format!("{:?}", self.address),
+			format!("0x{}", auth_data.to_hex::<String>()),
+			format!("0x{}", cipher.to_hex::<String>()),
+		]).map_err(Error::Custom)
+	}
+
+	fn address(&self) -> Address {
+		self.address
+	}
+
+	fn public(&self) -> Option<Public> {
+		Some(self.public)
+	}
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+	use std::net::TcpListener;
+	use std::os::unix::net::UnixListener;
+	use std::thread;
+
+	use crypto::publickey::{ecies, Generator, KeyPair, Random};
+	use engine::signer::EngineSigner;
+	use ethereum_types::H256;
+	use serde_json::Value;
+	use super::*;
+
+	/// Answers a request to the signing service holding `keypair`, whatever address is requested.
+	fn mock_response(keypair: &KeyPair, request: &[u8]) -> String {
+		let request: Value = serde_json::from_slice(request).unwrap();
+		let params: Vec<Vec<u8>> = request["params"].as_array().unwrap().iter()
+			.map(|param| param.as_str().unwrap().trim_start_matches("0x").from_hex().unwrap())
+			.collect();
+
+		let result = match request["method"].as_str().unwrap() {
+			"signer_public" => keypair.public().as_bytes().to_vec(),
+			"signer_sign" => publickey::sign(keypair.secret(), &H256::from_slice(&params[1])).unwrap().to_vec(),
+			"signer_decrypt" => ecies::decrypt(keypair.secret(), &params[1], &params[2]).unwrap(),
+			method => panic!("unexpected method {}", method),
+		};
+		format!(r#"{{"jsonrpc":"2.0","id":{},"result":"0x{}"}}"#, request["id"], result.to_hex::<String>())
+	}
+
+	fn ipc_signer(keypair: KeyPair, requests: usize) -> (tempfile::TempDir, String) {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("signer.ipc");
+		let listener = UnixListener::bind(&path).unwrap();
+		thread::spawn(move || {
+			for stream in listener.incoming().take(requests) {
+				let mut stream = stream.unwrap();
+				let mut request = Vec::new();
+				BufReader::new(&mut stream).read_until(b'\n', &mut request).unwrap();
+				writeln!(stream, "{}", mock_response(&keypair, &request)).unwrap();
+			}
+		});
+		(dir, format!("ipc:{}", path.display()))
+	}
+
+	fn http_signer(keypair: KeyPair, requests: usize) -> String {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let url = format!("http://{}/", listener.local_addr().unwrap());
+		thread::spawn(move || {
+			for stream in listener.incoming().take(requests) {
+				let mut stream = stream.unwrap();
+				let mut reader = BufReader::new(&mut stream);
+				let mut content_length = 0;
+				loop {
+					let mut line = String::new();
+					reader.read_line(&mut line).unwrap();
+					let line = line.trim().to_lowercase();
+					if line.is_empty() {
+						break;
+					}
+					if line.starts_with("content-length:") {
+						content_length = line["content-length:".len()..].trim().parse().unwrap();
+					}
+				}
+				let mut request = vec![0; content_length];
+				reader.read_exact(&mut request).unwrap();
+
+				let response = mock_response(&keypair, &request);
+				write!(
+					stream,
+					"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+					response.len(),
+					response,
+				).unwrap();
+			}
+		});
+		url
+	}
+
+	fn check_signer(signer: &RemoteEngineSigner, keypair: &KeyPair) {
+		assert_eq!(signer.address(), keypair.address());
+		assert_eq!(signer.public(), Some(*keypair.public()));
+
+		let hash = H256::repeat_byte(7);
+		let signature = signer.sign(hash).unwrap();
+		assert_eq!(publickey::recover(&signature, &hash).unwrap(), *keypair.public());
+
+		let cipher = ecies::encrypt(keypair.public(), b"auth", b"secret").unwrap();
+		assert_eq!(signer.decrypt(b"auth", &cipher).unwrap(), b"secret".to_vec());
+	}
+
+	#[test]
+	fn should_sign_and_decrypt_over_ipc() {
+		let keypair = Random.generate();
+		let (_dir, url) = ipc_signer(keypair.clone(), 3);
+
+		let signer = RemoteEngineSigner::new(&url, keypair.address()).unwrap();
+		check_signer(&signer, &keypair);
+	}
+
+	#[test]
+	fn should_sign_and_decrypt_over_http() {
+		let keypair = Random.generate();
+		let url = http_signer(keypair.clone(), 3);
+
+		let signer = RemoteEngineSigner::new(&url, keypair.address()).unwrap();
+		check_signer(&signer, &keypair);
+	}
+
+	#[test]
+	fn should_reject_key_of_another_address() {
+		let keypair = Random.generate();
+		let other = Random.generate();
+		let (_dir, url) = ipc_signer(keypair, 1);
+
+		assert!(RemoteEngineSigner::new(&url, other.address()).is_err());
+	}
+
+	#[test]
+	fn should_reject_unsupported_url() {
+		assert!(RemoteEngineSigner::new("ftp://localhost", Address::zero()).is_err());
+	}
+}
@@ -16,6 +16,7 @@
 
 use ethereum_types::{U256, H256, Address};
 use bytes::Bytes;
+use types::transaction::AccessList;
 
 use v1::types::{Origin, TransactionCondition};
 
@@ -38,6 +39,11 @@ pub struct TransactionRequest {
 	pub nonce: Option<U256>,
 	/// Delay until this condition is met.
 	pub condition: Option<TransactionCondition>,
+	/// Access list, sends an access list transaction (EIP-2930) if given.
+	pub access_list: Option<AccessList>,
+	/// Maximum priority fee per gas, sends a dynamic fee transaction (EIP-1559) if given.
+	/// The gas price is its maximum fee per gas then.
+	pub max_priority_fee_per_gas: Option<U256>,
 }
 
 /// Transaction request coming from RPC with default values filled in.
@@ -61,6 +67,11 @@ pub struct FilledTransactionRequest {
 	pub nonce: Option<U256>,
 	/// Delay until this condition is met.
 	pub condition: Option<TransactionCondition>,
+	/// Access list, sends an access list transaction (EIP-2930) if given.
+	pub access_list: Option<AccessList>,
+	/// Maximum priority fee per gas, sends a dynamic fee transaction (EIP-1559) if given.
+	/// The gas price is its maximum fee per gas then.
+	pub max_priority_fee_per_gas: Option<U256>,
 }
 
 impl From<FilledTransactionRequest> for TransactionRequest {
@@ -74,6 +85,8 @@ impl From<FilledTransactionRequest> for TransactionRequest {
 			data: Some(r.data),
 			nonce: r.nonce,
 			condition: r.condition,
+			access_list: r.access_list,
+			max_priority_fee_per_gas: r.max_priority_fee_per_gas,
 		}
 	}
 }
@@ -95,6 +108,11 @@ pub struct CallRequest {
 	pub data: Option<Vec<u8>>,
 	/// Nonce
 	pub nonce: Option<U256>,
+	/// Access list, executes an access list transaction (EIP-2930) if given.
+	pub access_list: Option<AccessList>,
+	/// Maximum priority fee per gas, executes a dynamic fee transaction (EIP-1559) if given.
+	/// The gas price is its maximum fee per gas then.
+	pub max_priority_fee_per_gas: Option<U256>,
 }
 
 /// Confirmation object
@@ -16,15 +16,27 @@
 
 //! Debug APIs RPC implementation
 
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
-use client_traits::BlockChainClient;
+use account_state::state::StateInfo;
+use client_traits::{BlockChainClient, StateClient};
+use ethcore::client::{Call, EngineInfo};
+use ethereum_types::{Address, H160, H256, U256};
+use machine::executed::Executed;
+use types::account_diff::Diff;
 use types::header::Header;
-use types::transaction::LocalizedTransaction;
+use types::ids::{BlockId, TransactionId};
+use types::state_diff::StateDiff;
+use types::transaction::{CallError, LocalizedTransaction};
 
 use jsonrpc_core::Result;
+use v1::helpers::{errors, fake_sign};
 use v1::traits::Debug;
-use v1::types::{Block, Bytes, RichBlock, BlockTransactions, Transaction};
+use v1::types::{
+	Block, BlockNumber, Bytes, CallFrame, CallRequest, ExecutionTrace, GethTrace, GethTraceWithTransactionHash,
+	PrestateAccount, RichBlock, BlockTransactions, TraceConfig, Tracer, Transaction,
+};
 
 /// Debug rpc implementation.
 pub struct DebugClient<C> {
@@ -40,7 +52,148 @@ impl<C> DebugClient<C> {
 	}
 }
 
-impl<C: BlockChainClient + 'static> Debug for DebugClient<C> {
+impl<C, S> DebugClient<C> where
+	S: StateInfo + 'static,
+	C: BlockChainClient + StateClient<State=S> + Call<State=S> + EngineInfo + 'static,
+{
+	/// Replays the transactions of a block and traces them.
+	fn trace_block(&self, block: BlockId, config: &TraceConfig) -> Result<Vec<GethTraceWithTransactionHash>> {
+		let executed = self.client.replay_block_transactions(block, config.call_analytics())
+			.map_err(errors::call)?;
+
+		if config.tracer != Some(Tracer::PrestateTracer) {
+			return executed
+				.map(|(tx_hash, executed)| geth_trace(executed, config).map(|result| GethTraceWithTransactionHash { tx_hash, result }))
+				.collect();
+		}
+
+		// The state before each transaction is the state before the block with the changes of
+		// the preceding transactions applied.
+		let header = self.client.block_header(block).ok_or_else(errors::state_pruned)?;
+		let state = self.client.state_at(BlockId::Hash(header.parent_hash())).ok_or_else(errors::state_pruned)?;
+		let mut prestate = Prestate::new(&state);
+		executed
+			.map(|(tx_hash, executed)| -> Result<_> {
+				let diff = executed.state_diff.ok_or_else(|| errors::internal("State diff is missing", ""))?;
+				let result = GethTrace::Prestate(prestate.accounts(&diff)?);
+				prestate.apply(diff);
+				Ok(GethTraceWithTransactionHash { tx_hash, result })
+			})
+			.collect()
+	}
+}
+
+/// Converts a traced execution into the output of the requested tracer, other than the prestate tracer.
+fn geth_trace(executed: Executed, config: &TraceConfig) -> Result<GethTrace> {
+	match config.tracer {
+		None => Ok(GethTrace::Execution(ExecutionTrace::new(executed, config))),
+		Some(Tracer::CallTracer) => CallFrame::from_traces(executed.trace)
+			.map(GethTrace::Call)
+			.ok_or_else(|| errors::internal("Transaction trace is missing", "")),
+		Some(Tracer::PrestateTracer) => Err(errors::internal("Prestate tracer requires the state", "")),
+	}
+}
+
+/// Values of the account fields changed by the preceding transactions.
+#[derive(Default)]
+struct AccountChanges {
+	balance: Option<U256>,
+	nonce: Option<U256>,
+	code: Option<Vec<u8>>,
+}
+
+/// Builds the output of the prestate tracer from the state diffs of the transactions.
+///
+/// The diffs contain the previous values of the fields changed by a transaction, the other fields
+/// are looked up in the state. Unlike the `prestateTracer` of go-ethereum, which records every
+/// account and storage slot the execution looks at, only the accounts and storage slots changed by
+/// the transaction are reported: accounts which are only read, such as the recipient of a call which
+/// transfers no value, and storage slots which are only loaded are left out.
+struct Prestate<'a, S> {
+	state: &'a S,
+	changes: BTreeMap<Address, AccountChanges>,
+}
+
+impl<'a, S: StateInfo> Prestate<'a, S> {
+	fn new(state: &'a S) -> Self {
+		Prestate {
+			state,
+			changes: BTreeMap::new(),
+		}
+	}
+
+	/// The accounts touched by a transaction, before it was executed.
+	fn accounts(&self, diff: &StateDiff) -> Result<BTreeMap<H160, PrestateAccount>> {
+		fn pre<T: Clone>(diff: &Diff<T>) -> Option<T> {
+			match *diff {
+				Diff::Changed(ref pre, _) | Diff::Died(ref pre) => Some(pre.clone()),
+				Diff::Same | Diff::Born(_) => None,
+			}
+		}
+
+		let mut accounts = BTreeMap::new();
+		for (address, account) in &diff.raw {
+			// Accounts created by the transaction didn't exist before it.
+			if let Diff::Born(_) = account.balance {
+				accounts.insert(*address, PrestateAccount::default());
+				continue;
+			}
+			let changes = self.changes.get(address);
+			let balance = match pre(&account.balance).or_else(|| changes.and_then(|c| c.balance)) {
+				Some(balance) => balance,
+				None => self.state.balance(address).map_err(|_| errors::state_corrupt())?,
+			};
+			let nonce = match pre(&account.nonce).or_else(|| changes.and_then(|c| c.nonce)) {
+				Some(nonce) => nonce,
+				None => self.state.nonce(address).map_err(|_| errors::state_corrupt())?,
+			};
+			let code = match pre(&account.code).or_else(|| changes.and_then(|c| c.code.clone())) {
+				Some(code) => code,
+				None => self.state.code(address)
+					.map_err(|_| errors::state_corrupt())?
+					.map_or_else(Vec::new, |code| (*code).clone()),
+			};
+			accounts.insert(*address, PrestateAccount {
+				balance,
+				nonce: if nonce.is_zero() { None } else { Some(nonce.low_u64()) },
+				code: if code.is_empty() { None } else { Some(code.into()) },
+				storage: account.storage.iter()
+					.filter_map(|(key, value)| pre(value).map(|value| (*key, value)))
+					.collect(),
+			});
+		}
+		Ok(accounts)
+	}
+
+	/// Records the changes of a transaction, for the accounts of the next ones.
+	fn apply(&mut self, diff: StateDiff) {
+		fn post<T: Default>(diff: Diff<T>) -> Option<T> {
+			match diff {
+				Diff::Born(post) | Diff::Changed(_, post) => Some(post),
+				Diff::Died(_) => Some(T::default()),
+				Diff::Same => None,
+			}
+		}
+
+		for (address, account) in diff.raw {
+			let changes = self.changes.entry(address).or_insert_with(AccountChanges::default);
+			if let Some(balance) = post(account.balance) {
+				changes.balance = Some(balance);
+			}
+			if let Some(nonce) = post(account.nonce) {
+				changes.nonce = Some(nonce);
+			}
+			if let Some(code) = post(account.code) {
+				changes.code = Some(code);
+			}
+		}
+	}
+}
+
+impl<C, S> Debug for DebugClient<C> where
+	S: StateInfo + 'static,
+	C: BlockChainClient + StateClient<State=S> + Call<State=S> + EngineInfo + 'static,
+{
 	fn bad_blocks(&self) -> Result<Vec<RichBlock>> {
 		fn cast<O, T: Copy + Into<O>>(t: &T) -> O {
 			(*t).into()
@@ -66,6 +219,7 @@ impl<C: BlockChainClient + 'static> Debug for DebugClient<C> {
 					timestamp: block.header.timestamp().into(),
 					difficulty: cast(block.header.difficulty()),
 					total_difficulty: None,
+					base_fee_per_gas: block.header.base_fee(),
 					seal_fields: block.header.seal().iter().cloned().map(Into::into).collect(),
 					uncles: block.uncles.iter().map(Header::hash).collect(),
 					transactions: BlockTransactions::Full(block.transactions
@@ -90,6 +244,65 @@ impl<C: BlockChainClient + 'static> Debug for DebugClient<C> {
 			}
 		}).collect())
 	}
+
+	fn trace_transaction(&self, transaction_hash: H256, config: Option<TraceConfig>) -> Result<GethTrace> {
+		let config = config.unwrap_or_default();
+		let id = TransactionId::Hash(transaction_hash);
+
+		if config.tracer == Some(Tracer::PrestateTracer) {
+			let block = self.client.transaction_block(id).ok_or_else(|| errors::call(CallError::TransactionNotFound))?;
+			return self.trace_block(BlockId::Hash(block), &config)?
+				.into_iter()
+				.find(|trace| trace.tx_hash == transaction_hash)
+				.map(|trace| trace.result)
+				.ok_or_else(|| errors::call(CallError::TransactionNotFound));
+		}
+
+		let executed = self.client.replay(id, config.call_analytics()).map_err(errors::call)?;
+		geth_trace(executed, &config)
+	}
+
+	fn trace_call(&self, request: CallRequest, block: Option<BlockNumber>, config: Option<TraceConfig>) -> Result<GethTrace> {
+		let config = config.unwrap_or_default();
+		let block = block.unwrap_or_default();
+
+		let request = CallRequest::into(request);
+		let signed = fake_sign::sign_call(request)?;
+
+		let id = match block {
+			BlockNumber::Hash { hash, .. } => BlockId::Hash(hash),
+			BlockNumber::Num(num) => BlockId::Number(num),
+			BlockNumber::Earliest => BlockId::Earliest,
+			BlockNumber::Latest => BlockId::Latest,
+
+			BlockNumber::Pending => return Err(errors::invalid_params("`BlockNumber::Pending` is not supported", ())),
+		};
+
+		let mut state = self.client.state_at(id).ok_or_else(errors::state_pruned)?;
+		let header = self.client.block_header(id).ok_or_else(errors::state_pruned)?;
+		let header = header.decode(self.client.engine().params().eip1559_transition).map_err(errors::decode)?;
+
+		let executed = self.client.call(&signed, config.call_analytics(), &mut state, &header).map_err(errors::call)?;
+		if config.tracer == Some(Tracer::PrestateTracer) {
+			let diff = executed.state_diff.ok_or_else(|| errors::internal("State diff is missing", ""))?;
+			// The fields which the call didn't change are the same after it.
+			return Prestate::new(&state).accounts(&diff).map(GethTrace::Prestate);
+		}
+		geth_trace(executed, &config)
+	}
+
+	fn trace_block_by_number(&self, block_number: BlockNumber, config: Option<TraceConfig>) -> Result<Vec<GethTraceWithTransactionHash>> {
+		let id = match block_number {
+			BlockNumber::Hash { hash, .. } => BlockId::Hash(hash),
+			BlockNumber::Num(num) => BlockId::Number(num),
+			BlockNumber::Earliest => BlockId::Earliest,
+			BlockNumber::Latest => BlockId::Latest,
+
+			BlockNumber::Pending => return Err(errors::invalid_params("`BlockNumber::Pending` is not supported", ())),
+		};
+
+		self.trace_block(id, &config.unwrap_or_default())
+	}
 }
 
 fn serialize<T: ::serde::Serialize>(t: &T) -> String {
@@ -16,11 +16,11 @@
 
 //! Eth rpc implementation.
 
+use std::cmp;
 use std::thread;
 use std::time::{Instant, Duration, SystemTime, UNIX_EPOCH};
 use std::sync::Arc;
 
-use rlp::Rlp;
 use ethereum_types::{Address, H64, H160, H256, U64, U256, BigEndianHash};
 use parking_lot::Mutex;
 
@@ -40,7 +40,8 @@ use types::{
 	header::Header,
 	ids::{BlockId, TransactionId, UncleId},
 	filter::Filter as EthcoreFilter,
-	transaction::{SignedTransaction, LocalizedTransaction},
+	receipt::LocalizedReceipt,
+	transaction::{SignedTransaction, LocalizedTransaction, UnverifiedTransaction},
 	snapshot::RestorationStatus,
 };
 
@@ -54,12 +55,15 @@ use v1::traits::Eth;
 use v1::types::{
 	RichBlock, Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, SyncInfo,
 	Transaction, CallRequest, Index, Filter, Log, Receipt, Work, EthAccount, StorageProof,
-	block_number_to_id
+	EthFeeHistory, block_number_to_id
 };
 use v1::metadata::Metadata;
 
 const EXTRA_INFO_PROOF: &str = "Object exists in blockchain (fetched earlier), extra_info is always available if object exists; qed";
 
+/// Maximal number of blocks returned by `eth_feeHistory`.
+const MAX_FEE_HISTORY_BLOCKS: u64 = 1024;
+
 /// Eth RPC options
 #[derive(Copy, Clone)]
 pub struct EthClientOptions {
@@ -266,6 +270,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T> EthClient<C, SN, S, M, EM> where
 		match (block, difficulty) {
 			(Some(block), Some(total_difficulty)) => {
 				let view = block.header_view();
+				let eip1559_transition = self.client.engine().params().eip1559_transition;
 				Ok(Some(RichBlock {
 					inner: Block {
 						hash: match is_pending {
@@ -293,7 +298,8 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T> EthClient<C, SN, S, M, EM> where
 						timestamp: view.timestamp().into(),
 						difficulty: view.difficulty(),
 						total_difficulty: Some(total_difficulty),
-						seal_fields: view.seal().into_iter().map(Into::into).collect(),
+						base_fee_per_gas: view.base_fee(eip1559_transition),
+						seal_fields: view.seal(eip1559_transition).into_iter().map(Into::into).collect(),
 						uncles: block.uncle_hashes(),
 						transactions: match include_txs {
 							true => BlockTransactions::Full(block.view().localized_transactions().into_iter().map(Transaction::from_localized).collect()),
@@ -391,7 +397,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T> EthClient<C, SN, S, M, EM> where
 				let uncle_id = UncleId { block: block_id, position };
 
 				let uncle = match client.uncle(uncle_id) {
-					Some(hdr) => match hdr.decode() {
+					Some(hdr) => match hdr.decode(self.client.engine().params().eip1559_transition) {
 						Ok(h) => h,
 						Err(e) => return Err(errors::decode(e))
 					},
@@ -430,6 +436,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T> EthClient<C, SN, S, M, EM> where
 				timestamp: uncle.timestamp().into(),
 				difficulty: *uncle.difficulty(),
 				total_difficulty: Some(uncle.difficulty() + parent_difficulty),
+				base_fee_per_gas: uncle.base_fee(),
 				receipts_root: *uncle.receipts_root(),
 				extra_data: uncle.extra_data().clone().into(),
 				seal_fields: uncle.seal().iter().cloned().map(Into::into).collect(),
@@ -503,6 +510,38 @@ pub fn pending_logs<M>(miner: &M, best_block: EthBlockNumber, filter: &EthcoreFi
 		.collect()
 }
 
+/// Ratio of the gas used to the gas limit of a block.
+fn gas_used_ratio(gas_used: U256, gas_limit: U256) -> f64 {
+	if gas_limit.is_zero() {
+		return 0.0;
+	}
+	// Drop the low bits of values not fitting into `u64`, they don't affect the ratio much.
+	let shift = gas_limit.bits().saturating_sub(64);
+	(gas_used >> shift).low_u64() as f64 / (gas_limit >> shift).low_u64() as f64
+}
+
+/// Priority fees paid in a block at the given percentiles of its gas usage. The transactions are
+/// weighted by the gas they used.
+fn fee_history_rewards(receipts: &[LocalizedReceipt], base_fee: Option<U256>, percentiles: &[f64]) -> Vec<U256> {
+	let mut fees: Vec<(U256, U256)> = receipts.iter()
+		.map(|r| (r.effective_gas_price.saturating_sub(base_fee.unwrap_or_default()), r.gas_used))
+		.collect();
+	fees.sort_by_key(|&(fee, _)| fee);
+	let total_gas_used = fees.iter().fold(U256::zero(), |acc, &(_, gas_used)| acc + gas_used);
+
+	percentiles.iter().map(|percentile| {
+		let threshold = total_gas_used * U256::from((percentile * 100.0) as u64) / 10_000;
+		let mut cumulative_gas_used = U256::zero();
+		fees.iter()
+			.find(|&&(_, gas_used)| {
+				cumulative_gas_used = cumulative_gas_used + gas_used;
+				cumulative_gas_used >= threshold
+			})
+			.or_else(|| fees.last())
+			.map_or_else(U256::zero, |&(fee, _)| fee)
+	}).collect()
+}
+
 fn check_known<C>(client: &C, number: BlockNumber) -> Result<()> where C: BlockChainClient {
 	use types::block_status::BlockStatus;
 
@@ -605,6 +644,75 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 		Box::new(future::ok(default_gas_price(&*self.client, &*self.miner, self.options.gas_price_percentile)))
 	}
 
+	fn max_priority_fee_per_gas(&self) -> BoxFuture<U256> {
+		let gas_price = default_gas_price(&*self.client, &*self.miner, self.options.gas_price_percentile);
+		let base_fee = self.client.engine().machine().calc_base_fee(&self.client.best_block_header());
+
+		Box::new(future::ok(gas_price.saturating_sub(base_fee.unwrap_or_default())))
+	}
+
+	fn fee_history(&self, block_count: U256, newest_block: BlockNumber, reward_percentiles: Option<Vec<f64>>) -> BoxFuture<EthFeeHistory> {
+		if let Some(ref percentiles) = reward_percentiles {
+			let valid = percentiles.iter().all(|p| *p >= 0.0 && *p <= 100.0)
+				&& percentiles.windows(2).all(|w| w[0] <= w[1]);
+			if !valid {
+				return Box::new(future::err(errors::invalid_params(
+					"rewardPercentiles",
+					"Percentiles must be increasing values between 0 and 100",
+				)));
+			}
+		}
+
+		let newest_id = match newest_block {
+			BlockNumber::Pending => BlockId::Latest,
+			number => block_number_to_id(number),
+		};
+		let newest = match self.client.block_number(newest_id) {
+			Some(newest) => newest,
+			None => return Box::new(future::err(errors::unknown_block())),
+		};
+		let block_count = cmp::min(cmp::min(block_count, MAX_FEE_HISTORY_BLOCKS.into()).low_u64(), newest + 1);
+		let oldest = newest + 1 - block_count;
+		let eip1559_transition = self.client.engine().params().eip1559_transition;
+
+		let mut base_fee_per_gas = Vec::with_capacity(block_count as usize + 1);
+		let mut gas_used_ratios = Vec::with_capacity(block_count as usize);
+		let mut rewards = Vec::with_capacity(block_count as usize);
+		let mut newest_header = None;
+		for number in oldest..=newest {
+			let header = match self.client.block_header(BlockId::Number(number)) {
+				Some(header) => header,
+				None => return Box::new(future::err(errors::unknown_block())),
+			};
+			let base_fee = header.base_fee(eip1559_transition);
+			base_fee_per_gas.push(base_fee.unwrap_or_default());
+			gas_used_ratios.push(gas_used_ratio(header.gas_used(), header.gas_limit()));
+
+			if let Some(ref percentiles) = reward_percentiles {
+				let receipts = match self.client.localized_block_receipts(BlockId::Number(number)) {
+					Some(receipts) => receipts,
+					None => return Box::new(future::err(errors::unknown_block())),
+				};
+				rewards.push(fee_history_rewards(&receipts, base_fee, percentiles));
+			}
+			newest_header = Some(header);
+		}
+
+		// The base fee of the block following the newest one is known already.
+		if let Some(header) = newest_header {
+			let header = try_bf!(header.decode(eip1559_transition).map_err(errors::decode));
+			let next_base_fee = self.client.engine().machine().calc_base_fee(&header);
+			base_fee_per_gas.push(next_base_fee.unwrap_or_default());
+		}
+
+		Box::new(future::ok(EthFeeHistory {
+			oldest_block: oldest.into(),
+			base_fee_per_gas,
+			gas_used_ratio: gas_used_ratios,
+			reward: reward_percentiles.map(|_| rewards),
+		}))
+	}
+
 	fn accounts(&self) -> Result<Vec<H160>> {
 		self.deprecation_notice.print("eth_accounts", deprecated::msgs::ACCOUNTS);
 
@@ -941,7 +1049,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 	}
 
 	fn send_raw_transaction(&self, raw: Bytes) -> Result<H256> {
-		Rlp::new(&raw.into_vec()).as_val()
+		UnverifiedTransaction::from_envelope(&raw.into_vec())
 			.map_err(errors::rlp)
 			.and_then(|tx| SignedTransaction::new(tx).map_err(errors::transaction))
 			.and_then(|signed_transaction| {
@@ -981,7 +1089,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 				let state = try_bf!(self.client.state_at(id).ok_or_else(errors::state_pruned));
 				let header = try_bf!(
 					self.client.block_header(id).ok_or_else(errors::state_pruned)
-						.and_then(|h| h.decode().map_err(errors::decode))
+						.and_then(|h| h.decode(self.client.engine().params().eip1559_transition).map_err(errors::decode))
 				);
 
 				(state, header)
@@ -1021,7 +1129,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> Eth for EthClient<
 								.ok_or_else(errors::state_pruned));
 			let header = try_bf!(self.client.block_header(id)
 								 .ok_or_else(errors::state_pruned)
-								 .and_then(|h| h.decode().map_err(errors::decode)));
+								 .and_then(|h| h.decode(self.client.engine().params().eip1559_transition).map_err(errors::decode)));
 			(state, header)
 		};
 
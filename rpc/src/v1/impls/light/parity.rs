@@ -30,7 +30,7 @@ use ethereum_types::{H64, H160, H256, H512, U64, U256};
 use ethcore::miner::FilterOptions;
 use ethcore_logger::RotatingLogger;
 
-use jsonrpc_core::{Result, BoxFuture};
+use jsonrpc_core::{Result, BoxFuture, Value};
 use jsonrpc_core::futures::{future, Future};
 use light::on_demand::OnDemandRequester;
 use v1::helpers::{self, errors, NetworkSettings, verify_signature};
@@ -338,7 +338,7 @@ where
 
 		let engine = self.light_dispatch.client.engine().clone();
 		let from_encoded = move |encoded: encoded::Header| {
-			let header = encoded.decode().map_err(errors::decode)?;
+			let header = encoded.decode(engine.params().eip1559_transition).map_err(errors::decode)?;
 			let extra_info = engine.extra_info(&header);
 			Ok(RichHeader {
 				inner: Header {
@@ -357,6 +357,7 @@ where
 					logs_bloom: *header.log_bloom(),
 					timestamp: header.timestamp().into(),
 					difficulty: *header.difficulty(),
+					base_fee_per_gas: header.base_fee(),
 					seal_fields: header.seal().iter().cloned().map(Into::into).collect(),
 					extra_data: Bytes::new(header.extra_data().clone()),
 				},
@@ -414,4 +415,12 @@ where
 	fn submit_raw_block(&self, _block: Bytes) -> Result<H256> {
 		Err(light_unimplemented(None))
 	}
+
+	fn validator_faults(&self) -> Result<BTreeMap<H160, BTreeMap<String, u64>>> {
+		Ok(self.light_dispatch.client.engine().fault_counts())
+	}
+
+	fn hbbft_status(&self) -> Result<Option<Value>> {
+		Ok(self.light_dispatch.client.engine().consensus_status())
+	}
 }
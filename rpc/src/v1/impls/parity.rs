@@ -20,7 +20,7 @@ use std::collections::BTreeMap;
 
 use crypto::DEFAULT_MAC;
 use ethereum_types::{H64, H160, H256, H512, U64, U256};
-use ethcore::client::Call;
+use ethcore::client::{Call, EngineInfo};
 use client_traits::{BlockChainClient, StateClient};
 use ethcore::miner::{self, MinerService, FilterOptions};
 use snapshot::SnapshotService;
@@ -30,7 +30,7 @@ use ethkey::Brain;
 use crypto::publickey::{ecies, Generator};
 use ethstore::random_phrase;
 use jsonrpc_core::futures::future;
-use jsonrpc_core::{BoxFuture, Result};
+use jsonrpc_core::{BoxFuture, Result, Value};
 use sync::{SyncProvider, ManageNetwork};
 use types::{
 	ids::BlockId,
@@ -50,7 +50,7 @@ use v1::types::{
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
 	OperationsInfo, ChainStatus, Log, Filter,
-	RichHeader, Receipt, RecoveredAccount,
+	Header, RichHeader, Receipt, RecoveredAccount,
 	block_number_to_id
 };
 use Host;
@@ -102,7 +102,7 @@ impl<C, M, U> ParityClient<C, M, U> where
 
 impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 	S: StateInfo + 'static,
-	C: miner::BlockChainClient + BlockChainClient + StateClient<State=S> + Call<State=S> + 'static,
+	C: miner::BlockChainClient + BlockChainClient + StateClient<State=S> + Call<State=S> + EngineInfo + 'static,
 	M: MinerService<State=S> + 'static,
 	U: UpdateService + 'static,
 {
@@ -369,7 +369,7 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		};
 
 		Box::new(future::ok(RichHeader {
-			inner: header.into(),
+			inner: Header::new(&header, self.client.engine().params().eip1559_transition),
 			extra_info: extra.unwrap_or_default(),
 		}))
 	}
@@ -423,7 +423,8 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 			};
 
 			let state = self.client.state_at(id).ok_or_else(errors::state_pruned)?;
-			let header = self.client.block_header(id).ok_or_else(errors::state_pruned)?.decode().map_err(errors::decode)?;
+			let header = self.client.block_header(id).ok_or_else(errors::state_pruned)?
+				.decode(self.client.engine().params().eip1559_transition).map_err(errors::decode)?;
 
 			(state, header)
 		};
@@ -476,8 +477,16 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 
 	fn submit_raw_block(&self, block: Bytes) -> Result<H256> {
 		let result = self.client.import_block(
-			Unverified::from_rlp(block.into_vec()).map_err(errors::rlp)?
+			Unverified::from_rlp(block.into_vec(), self.client.engine().params().eip1559_transition).map_err(errors::rlp)?
 		);
 		Ok(result.map_err(errors::cannot_submit_block)?)
 	}
+
+	fn validator_faults(&self) -> Result<BTreeMap<H160, BTreeMap<String, u64>>> {
+		Ok(self.client.engine().fault_counts())
+	}
+
+	fn hbbft_status(&self) -> Result<Option<Value>> {
+		Ok(self.client.engine().consensus_status())
+	}
 }
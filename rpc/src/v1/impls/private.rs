@@ -90,6 +90,9 @@ impl Private for PrivateClient {
 			value: Some(transaction.value),
 			data: Some(transaction.data.into()),
 			condition: None,
+			access_list: None,
+			max_fee_per_gas: None,
+			max_priority_fee_per_gas: None,
 		};
 
 		Ok(PrivateTransactionReceiptAndTransaction {
@@ -19,14 +19,13 @@
 use std::sync::Arc;
 
 use account_state::state::StateInfo;
-use ethcore::client::Call;
+use ethcore::client::{Call, EngineInfo};
 use client_traits::{BlockChainClient, StateClient};
 use ethereum_types::H256;
-use rlp::Rlp;
 use types::{
 	call_analytics::CallAnalytics,
 	ids::{BlockId, TransactionId, TraceId},
-	transaction::SignedTransaction,
+	transaction::{SignedTransaction, UnverifiedTransaction},
 };
 
 use jsonrpc_core::Result;
@@ -60,7 +59,7 @@ impl<C> TracesClient<C> {
 
 impl<C, S> Traces for TracesClient<C> where
 	S: StateInfo + 'static,
-	C: BlockChainClient + StateClient<State=S> + Call<State=S> + 'static
+	C: BlockChainClient + StateClient<State=S> + Call<State=S> + EngineInfo + 'static
 {
 	type Metadata = Metadata;
 
@@ -112,7 +111,7 @@ impl<C, S> Traces for TracesClient<C> where
 		let mut state = self.client.state_at(id).ok_or_else(errors::state_pruned)?;
 		let header = self.client.block_header(id).ok_or_else(errors::state_pruned)?;
 
-		self.client.call(&signed, to_call_analytics(flags), &mut state, &header.decode().map_err(errors::decode)?)
+		self.client.call(&signed, to_call_analytics(flags), &mut state, &header.decode(self.client.engine().params().eip1559_transition).map_err(errors::decode)?)
 			.map(TraceResults::from)
 			.map_err(errors::call)
 	}
@@ -140,7 +139,7 @@ impl<C, S> Traces for TracesClient<C> where
 		let mut state = self.client.state_at(id).ok_or_else(errors::state_pruned)?;
 		let header = self.client.block_header(id).ok_or_else(errors::state_pruned)?;
 
-		self.client.call_many(&requests, &mut state, &header.decode().map_err(errors::decode)?)
+		self.client.call_many(&requests, &mut state, &header.decode(self.client.engine().params().eip1559_transition).map_err(errors::decode)?)
 			.map(|results| results.into_iter().map(TraceResults::from).collect())
 			.map_err(errors::call)
 	}
@@ -148,7 +147,7 @@ impl<C, S> Traces for TracesClient<C> where
 	fn raw_transaction(&self, raw_transaction: Bytes, flags: TraceOptions, block: Option<BlockNumber>) -> Result<TraceResults> {
 		let block = block.unwrap_or_default();
 
-		let tx = Rlp::new(&raw_transaction.into_vec()).as_val().map_err(|e| errors::invalid_params("Transaction is not valid RLP", e))?;
+		let tx = UnverifiedTransaction::from_envelope(&raw_transaction.into_vec()).map_err(|e| errors::invalid_params("Transaction is not valid RLP", e))?;
 		let signed = SignedTransaction::new(tx).map_err(errors::transaction)?;
 
 		let id = match block {
@@ -163,7 +162,7 @@ impl<C, S> Traces for TracesClient<C> where
 		let mut state = self.client.state_at(id).ok_or_else(errors::state_pruned)?;
 		let header = self.client.block_header(id).ok_or_else(errors::state_pruned)?;
 
-		self.client.call(&signed, to_call_analytics(flags), &mut state, &header.decode().map_err(errors::decode)?)
+		self.client.call(&signed, to_call_analytics(flags), &mut state, &header.decode(self.client.engine().params().eip1559_transition).map_err(errors::decode)?)
 			.map(TraceResults::from)
 			.map_err(errors::call)
 	}
@@ -53,6 +53,7 @@ pub use self::extractors::{RpcExtractor, WsExtractor, WsStats, WsDispatcher};
 pub mod signer {
 	#[cfg(any(test, feature = "accounts"))]
 	pub use super::helpers::engine_signer::EngineSigner;
+	pub use super::helpers::remote_engine_signer::RemoteEngineSigner;
 	pub use super::helpers::external_signer::{SignerService, ConfirmationsQueue};
 	pub use super::types::{ConfirmationRequest, TransactionModification, TransactionCondition};
 }
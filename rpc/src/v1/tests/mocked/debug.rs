@@ -17,13 +17,19 @@
 use std::sync::Arc;
 
 use ethcore::test_helpers::TestBlockChainClient;
+use ethereum_types::{Address, H256};
+use machine::executed::Executed;
+use trace::{FlatTrace, LocalizedTrace, VMTrace, VMOperation, VMExecutedOperation};
+use trace::trace::{Action, Call, CallResult, CallType, Res};
 
 use jsonrpc_core::IoHandler;
 use v1::{Debug, DebugClient};
 
 fn io() -> IoHandler {
-	let client = Arc::new(TestBlockChainClient::new());
+	io_with_client(Arc::new(TestBlockChainClient::new()))
+}
 
+fn io_with_client(client: Arc<TestBlockChainClient>) -> IoHandler {
 	let mut io = IoHandler::new();
 	io.extend_with(DebugClient::new(client).to_delegate());
 	io
@@ -35,3 +41,104 @@ fn rpc_debug_get_bad_blocks() {
 	let response = "{\"jsonrpc\":\"2.0\",\"result\":[{\"author\":\"0x0000000000000000000000000000000000000000\",\"difficulty\":\"0x0\",\"extraData\":\"0x\",\"gasLimit\":\"0x0\",\"gasUsed\":\"0x0\",\"hash\":\"0x27bfb37e507ce90da141307204b1c6ba24194380613590ac50ca4b1d7198ff65\",\"logsBloom\":\"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000\",\"miner\":\"0x0000000000000000000000000000000000000000\",\"number\":\"0x0\",\"parentHash\":\"0x0000000000000000000000000000000000000000000000000000000000000000\",\"reason\":\"Invalid block\",\"receiptsRoot\":\"0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421\",\"rlp\":\"\\\"0x010203\\\"\",\"sealFields\":[],\"sha3Uncles\":\"0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347\",\"size\":\"0x3\",\"stateRoot\":\"0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421\",\"timestamp\":\"0x0\",\"totalDifficulty\":null,\"transactions\":[],\"transactionsRoot\":\"0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421\",\"uncles\":[]}],\"id\":1}";
 	assert_eq!(io().handle_request_sync(request), Some(response.to_owned()));
 }
+
+fn executed(trace: Vec<FlatTrace>, vm_trace: Option<VMTrace>) -> Executed {
+	Executed {
+		exception: None,
+		gas: 30_000.into(),
+		gas_used: 21_003.into(),
+		refunded: 0.into(),
+		cumulative_gas_used: 21_003.into(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: vec![1],
+		trace,
+		vm_trace,
+		state_diff: None,
+	}
+}
+
+fn call(to: u64) -> Call {
+	Call {
+		from: Address::from_low_u64_be(0xf),
+		to: Address::from_low_u64_be(to),
+		value: 0x1.into(),
+		gas: 0x100.into(),
+		input: vec![1, 2, 3],
+		call_type: Some(CallType::Call).into(),
+	}
+}
+
+#[test]
+fn rpc_debug_trace_transaction() {
+	let client = Arc::new(TestBlockChainClient::new());
+	client.set_execution_result(Ok(executed(vec![], Some(VMTrace {
+		parent_step: 0,
+		code: vec![0x60, 0x01, 0x00],
+		// PUSH1 1 STOP
+		operations: vec![
+			VMOperation {
+				pc: 0,
+				instruction: 0x60,
+				gas_cost: 3.into(),
+				executed: Some(VMExecutedOperation {
+					gas_used: 97.into(),
+					stack_push: vec![1.into()],
+					mem_diff: None,
+					store_diff: None,
+				}),
+			},
+			VMOperation {
+				pc: 2,
+				instruction: 0x00,
+				gas_cost: 0.into(),
+				executed: Some(VMExecutedOperation {
+					gas_used: 97.into(),
+					stack_push: vec![],
+					mem_diff: None,
+					store_diff: None,
+				}),
+			},
+		],
+		subs: vec![],
+	}))));
+
+	let request = r#"{"jsonrpc":"2.0","method":"debug_traceTransaction","params":["0x0000000000000000000000000000000000000000000000000000000000000005", {"disableMemory": true, "disableStorage": true}],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"gas":21003,"failed":false,"returnValue":"01","structLogs":[{"pc":0,"op":"PUSH1","gas":100,"gasCost":3,"depth":1,"stack":[]},{"pc":2,"op":"STOP","gas":97,"gasCost":0,"depth":1,"stack":["0x1"]}]},"id":1}"#;
+
+	assert_eq!(io_with_client(client).handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_debug_trace_block_by_number_with_call_tracer() {
+	let client = Arc::new(TestBlockChainClient::new());
+	*client.traces.write() = Some(vec![LocalizedTrace {
+		action: Action::Call(call(0x10)),
+		result: Res::None,
+		subtraces: 0,
+		trace_address: vec![],
+		transaction_number: Some(0),
+		transaction_hash: Some(H256::from_low_u64_be(5)),
+		block_number: 10,
+		block_hash: H256::from_low_u64_be(10),
+	}]);
+	client.set_execution_result(Ok(executed(vec![
+		FlatTrace {
+			action: Action::Call(call(0x10)),
+			result: Res::Call(CallResult { gas_used: 0x50.into(), output: vec![] }),
+			subtraces: 1,
+			trace_address: vec![],
+		},
+		FlatTrace {
+			action: Action::Call(call(0x11)),
+			result: Res::Call(CallResult { gas_used: 0x10.into(), output: vec![4] }),
+			subtraces: 0,
+			trace_address: vec![0],
+		},
+	], None)));
+
+	let request = r#"{"jsonrpc":"2.0","method":"debug_traceBlockByNumber","params":["0xa", {"tracer": "callTracer"}],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"txHash":"0x0000000000000000000000000000000000000000000000000000000000000005","result":{"type":"CALL","from":"0x000000000000000000000000000000000000000f","to":"0x0000000000000000000000000000000000000010","value":"0x1","gas":"0x100","gasUsed":"0x50","input":"0x010203","output":"0x","calls":[{"type":"CALL","from":"0x000000000000000000000000000000000000000f","to":"0x0000000000000000000000000000000000000011","value":"0x1","gas":"0x100","gasUsed":"0x10","input":"0x010203","output":"0x04"}]}}],"id":1}"#;
+
+	assert_eq!(io_with_client(client).handle_request_sync(request), Some(response.to_owned()));
+}
@@ -93,6 +93,8 @@ fn should_return_list_of_items_to_confirm() {
 		data: vec![],
 		nonce: None,
 		condition: None,
+		access_list: None,
+		max_priority_fee_per_gas: None,
 	}), Origin::Unknown).unwrap();
 	let _sign_future = tester.signer.add_request(ConfirmationPayload::EthSignMessage(Address::from_low_u64_be(1), vec![5].into()), Origin::Unknown).unwrap();
 
@@ -123,6 +125,8 @@ fn should_reject_transaction_from_queue_without_dispatching() {
 		data: vec![],
 		nonce: None,
 		condition: None,
+		access_list: None,
+		max_priority_fee_per_gas: None,
 	}), Origin::Unknown).unwrap();
 	assert_eq!(tester.signer.requests().len(), 1);
 
@@ -150,6 +154,8 @@ fn should_not_remove_transaction_if_password_is_invalid() {
 		data: vec![],
 		nonce: None,
 		condition: None,
+		access_list: None,
+		max_priority_fee_per_gas: None,
 	}), Origin::Unknown).unwrap();
 	assert_eq!(tester.signer.requests().len(), 1);
 
@@ -194,6 +200,8 @@ fn should_confirm_transaction_and_dispatch() {
 		data: vec![],
 		nonce: None,
 		condition: None,
+		access_list: None,
+		max_priority_fee_per_gas: None,
 	}), Origin::Unknown).unwrap();
 
 	let t = Transaction {
@@ -240,6 +248,8 @@ fn should_alter_the_sender_and_nonce() {
 		data: vec![],
 		nonce: Some(10.into()),
 		condition: None,
+		access_list: None,
+		max_priority_fee_per_gas: None,
 	}), Origin::Unknown).unwrap();
 
 	let t = Transaction {
@@ -290,6 +300,8 @@ fn should_confirm_transaction_with_token() {
 		data: vec![],
 		nonce: None,
 		condition: None,
+		access_list: None,
+		max_priority_fee_per_gas: None,
 	}), Origin::Unknown).unwrap();
 
 	let t = Transaction {
@@ -339,6 +351,8 @@ fn should_confirm_transaction_with_rlp() {
 		data: vec![],
 		nonce: None,
 		condition: None,
+		access_list: None,
+		max_priority_fee_per_gas: None,
 	}), Origin::Unknown).unwrap();
 
 	let t = Transaction {
@@ -386,6 +400,8 @@ fn should_return_error_when_sender_does_not_match() {
 		data: vec![],
 		nonce: None,
 		condition: None,
+		access_list: None,
+		max_priority_fee_per_gas: None,
 	}), Origin::Unknown).unwrap();
 
 	let t = Transaction {
@@ -433,6 +449,8 @@ fn should_confirm_sign_transaction_with_rlp() {
 		data: vec![],
 		nonce: None,
 		condition: None,
+		access_list: None,
+		max_priority_fee_per_gas: None,
 	}), Origin::Unknown).unwrap();
 	assert_eq!(tester.signer.requests().len(), 1);
 
@@ -471,7 +489,7 @@ fn should_confirm_sign_transaction_with_rlp() {
 		&format!("\"raw\":\"0x{}\",", rlp.to_hex()) +
 		&format!("\"s\":\"0x{:x}\",", U256::from(signature.s())) +
 		&format!("\"standardV\":\"0x{:x}\",", U256::from(t.standard_v())) +
-		r#""to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","transactionIndex":null,"# +
+		r#""to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","transactionIndex":null,"type":"0x0","# +
 		&format!("\"v\":\"0x{:x}\",", U256::from(t.original_v())) +
 		r#""value":"0x1""# +
 		r#"}},"id":1}"#;
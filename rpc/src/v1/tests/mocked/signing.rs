@@ -393,7 +393,7 @@ fn should_add_sign_transaction_to_the_queue() {
 		&format!("\"raw\":\"0x{}\",", rlp.to_hex()) +
 		&format!("\"s\":\"0x{:x}\",", U256::from(signature.s())) +
 		&format!("\"standardV\":\"0x{:x}\",", U256::from(t.standard_v())) +
-		r#""to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","transactionIndex":null,"# +
+		r#""to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","transactionIndex":null,"type":"0x0","# +
 		&format!("\"v\":\"0x{:x}\",", U256::from(t.original_v())) +
 		r#""value":"0x9184e72a""# +
 		r#"}},"id":1}"#;
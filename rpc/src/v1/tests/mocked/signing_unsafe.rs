@@ -183,7 +183,7 @@ fn rpc_eth_sign_transaction() {
 		&format!("\"raw\":\"0x{}\",", rlp) +
 		&format!("\"s\":\"0x{:x}\",", U256::from(signature.s())) +
 		&format!("\"standardV\":\"0x{:x}\",", U256::from(t.standard_v())) +
-		r#""to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","transactionIndex":null,"# +
+		r#""to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","transactionIndex":null,"type":"0x0","# +
 		&format!("\"v\":\"0x{:x}\",", U256::from(t.original_v())) +
 		r#""value":"0x9184e72a""# +
 		r#"}},"id":1}"#;
@@ -16,10 +16,11 @@
 
 //! Debug RPC interface.
 
+use ethereum_types::H256;
 use jsonrpc_core::Result;
 use jsonrpc_derive::rpc;
 
-use v1::types::RichBlock;
+use v1::types::{BlockNumber, CallRequest, GethTrace, GethTraceWithTransactionHash, RichBlock, TraceConfig};
 
 /// Debug RPC interface.
 #[rpc(server)]
@@ -27,4 +28,16 @@ pub trait Debug {
 	/// Returns recently seen bad blocks.
 	#[rpc(name = "debug_getBadBlocks")]
 	fn bad_blocks(&self) -> Result<Vec<RichBlock>>;
+
+	/// Replays the given transaction and returns its trace.
+	#[rpc(name = "debug_traceTransaction")]
+	fn trace_transaction(&self, _: H256, _: Option<TraceConfig>) -> Result<GethTrace>;
+
+	/// Executes the given call and returns its trace.
+	#[rpc(name = "debug_traceCall")]
+	fn trace_call(&self, _: CallRequest, _: Option<BlockNumber>, _: Option<TraceConfig>) -> Result<GethTrace>;
+
+	/// Replays the transactions of the given block and returns their traces.
+	#[rpc(name = "debug_traceBlockByNumber")]
+	fn trace_block_by_number(&self, _: BlockNumber, _: Option<TraceConfig>) -> Result<Vec<GethTraceWithTransactionHash>>;
 }
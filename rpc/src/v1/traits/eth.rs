@@ -19,7 +19,7 @@ use jsonrpc_core::{Result, BoxFuture};
 use jsonrpc_derive::rpc;
 use ethereum_types::{H64, H160, H256, U64, U256};
 
-use v1::types::{RichBlock, BlockNumber, Bytes, CallRequest, Filter, FilterChanges, Index, EthAccount};
+use v1::types::{RichBlock, BlockNumber, Bytes, CallRequest, EthFeeHistory, Filter, FilterChanges, Index, EthAccount};
 use v1::types::{Log, Receipt, SyncStatus, Transaction, Work};
 
 /// Eth rpc interface.
@@ -58,6 +58,15 @@ pub trait Eth {
 	#[rpc(name = "eth_gasPrice")]
 	fn gas_price(&self) -> BoxFuture<U256>;
 
+	/// Returns a priority fee per gas to pay on top of the base fee (EIP-1559).
+	#[rpc(name = "eth_maxPriorityFeePerGas")]
+	fn max_priority_fee_per_gas(&self) -> BoxFuture<U256>;
+
+	/// Returns the base fees, the gas usage and the priority fees paid at the given percentiles
+	/// of a range of blocks ending with the given one.
+	#[rpc(name = "eth_feeHistory")]
+	fn fee_history(&self, _: U256, _: BlockNumber, _: Option<Vec<f64>>) -> BoxFuture<EthFeeHistory>;
+
 	/// Returns accounts list.
 	#[rpc(name = "eth_accounts")]
 	fn accounts(&self) -> Result<Vec<H160>>;
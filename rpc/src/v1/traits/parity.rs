@@ -20,7 +20,7 @@ use std::collections::BTreeMap;
 
 use ethereum_types::{H64, H160, H256, H512, U64, U256};
 use ethcore::miner::FilterOptions;
-use jsonrpc_core::{BoxFuture, Result};
+use jsonrpc_core::{BoxFuture, Result, Value};
 use jsonrpc_derive::rpc;
 use v1::types::{
 	Bytes, CallRequest,
@@ -243,4 +243,13 @@ pub trait Parity {
 	/// Submit raw block to be published to the network
 	#[rpc(name = "parity_submitRawBlock")]
 	fn submit_raw_block(&self, _: Bytes) -> Result<H256>;
+
+	/// Returns the number of faults the consensus engine observed from each validator, by kind of fault.
+	#[rpc(name = "parity_validatorFaults")]
+	fn validator_faults(&self) -> Result<BTreeMap<H160, BTreeMap<String, u64>>>;
+
+	/// Returns an engine-specific snapshot of the consensus state, or `null` if the consensus engine
+	/// does not report its state.
+	#[rpc(name = "parity_hbbftStatus")]
+	fn hbbft_status(&self) -> Result<Option<Value>>;
 }
@@ -0,0 +1,69 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethereum_types::{H160, H256};
+use types::transaction;
+
+/// An account and the storage keys of it a transaction plans to access (EIP-2930).
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListItem {
+	/// Accessed account
+	pub address: H160,
+	/// Accessed storage keys of the account
+	pub storage_keys: Vec<H256>,
+}
+
+/// Accounts and storage keys a transaction plans to access (EIP-2930).
+pub type AccessList = Vec<AccessListItem>;
+
+impl Into<transaction::AccessListItem> for AccessListItem {
+	fn into(self) -> transaction::AccessListItem {
+		transaction::AccessListItem {
+			address: self.address,
+			storage_keys: self.storage_keys,
+		}
+	}
+}
+
+impl From<transaction::AccessListItem> for AccessListItem {
+	fn from(item: transaction::AccessListItem) -> Self {
+		AccessListItem {
+			address: item.address,
+			storage_keys: item.storage_keys,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json;
+
+	#[test]
+	fn access_list_deserialization() {
+		let s = r#"[{
+			"address": "0x0000000000000000000000000000000000000001",
+			"storageKeys": ["0x0000000000000000000000000000000000000000000000000000000000000002"]
+		}]"#;
+		let deserialized: AccessList = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized, vec![AccessListItem {
+			address: H160::from_low_u64_be(1),
+			storage_keys: vec![H256::from_low_u64_be(2)],
+		}]);
+	}
+}
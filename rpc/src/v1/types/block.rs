@@ -20,7 +20,7 @@ use std::collections::BTreeMap;
 use ethereum_types::{H160, H256, U256, Bloom as H2048};
 use serde::ser::Error;
 use serde::{Serialize, Serializer};
-use types::encoded::Header as EthHeader;
+use types::{encoded::Header as EthHeader, BlockNumber};
 use v1::types::{Bytes, Transaction};
 
 /// Block Transactions
@@ -79,6 +79,9 @@ pub struct Block {
 	pub difficulty: U256,
 	/// Total difficulty
 	pub total_difficulty: Option<U256>,
+	/// Base fee per gas, if EIP-1559 is active
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub base_fee_per_gas: Option<U256>,
 	/// Seal fields
 	pub seal_fields: Vec<Bytes>,
 	/// Uncles' hashes
@@ -124,20 +127,18 @@ pub struct Header {
 	pub timestamp: U256,
 	/// Difficulty
 	pub difficulty: U256,
+	/// Base fee per gas, if EIP-1559 is active
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub base_fee_per_gas: Option<U256>,
 	/// Seal fields
 	pub seal_fields: Vec<Bytes>,
 	/// Size in bytes
 	pub size: Option<U256>,
 }
 
-impl From<EthHeader> for Header {
-	fn from(h: EthHeader) -> Self {
-		(&h).into()
-	}
-}
-
-impl<'a> From<&'a EthHeader> for Header {
-	fn from(h: &'a EthHeader) -> Self {
+impl Header {
+	/// Create a header representation of the given encoded header.
+	pub fn new(h: &EthHeader, eip1559_transition: BlockNumber) -> Self {
 		Header {
 			hash: Some(h.hash()),
 			size: Some(h.rlp().as_raw().len().into()),
@@ -154,8 +155,9 @@ impl<'a> From<&'a EthHeader> for Header {
 			logs_bloom: h.log_bloom(),
 			timestamp: h.timestamp().into(),
 			difficulty: h.difficulty(),
+			base_fee_per_gas: h.base_fee(eip1559_transition),
 			extra_data: h.extra_data().into(),
-			seal_fields: h.view().decode_seal()
+			seal_fields: h.view().decode_seal(eip1559_transition)
 				.expect("Client/Miner returns only valid headers. We only serialize headers from Client/Miner; qed")
 				.into_iter().map(Into::into).collect(),
 		}
@@ -220,7 +222,7 @@ mod tests {
 	fn test_serialize_block_transactions() {
 		let t = BlockTransactions::Full(vec![Transaction::default()]);
 		let serialized = serde_json::to_string(&t).unwrap();
-		assert_eq!(serialized, r#"[{"hash":"0x0000000000000000000000000000000000000000000000000000000000000000","nonce":"0x0","blockHash":null,"blockNumber":null,"transactionIndex":null,"from":"0x0000000000000000000000000000000000000000","to":null,"value":"0x0","gasPrice":"0x0","gas":"0x0","input":"0x","creates":null,"raw":"0x","publicKey":null,"chainId":null,"standardV":"0x0","v":"0x0","r":"0x0","s":"0x0","condition":null}]"#);
+		assert_eq!(serialized, r#"[{"hash":"0x0000000000000000000000000000000000000000000000000000000000000000","nonce":"0x0","blockHash":null,"blockNumber":null,"transactionIndex":null,"from":"0x0000000000000000000000000000000000000000","to":null,"value":"0x0","gasPrice":"0x0","gas":"0x0","input":"0x","creates":null,"raw":"0x","publicKey":null,"chainId":null,"standardV":"0x0","v":"0x0","r":"0x0","s":"0x0","condition":null,"type":"0x0"}]"#);
 
 		let t = BlockTransactions::Hashes(vec![H256::zero().into()]);
 		let serialized = serde_json::to_string(&t).unwrap();
@@ -246,6 +248,7 @@ mod tests {
 			timestamp: U256::default(),
 			difficulty: U256::default(),
 			total_difficulty: Some(U256::default()),
+			base_fee_per_gas: None,
 			seal_fields: vec![Bytes::default(), Bytes::default()],
 			uncles: vec![],
 			transactions: BlockTransactions::Hashes(vec![].into()),
@@ -281,6 +284,7 @@ mod tests {
 			timestamp: U256::default(),
 			difficulty: U256::default(),
 			total_difficulty: Some(U256::default()),
+			base_fee_per_gas: None,
 			seal_fields: vec![Bytes::default(), Bytes::default()],
 			uncles: vec![],
 			transactions: BlockTransactions::Hashes(vec![].into()),
@@ -315,6 +319,7 @@ mod tests {
 			logs_bloom: H2048::default(),
 			timestamp: U256::default(),
 			difficulty: U256::default(),
+			base_fee_per_gas: None,
 			seal_fields: vec![Bytes::default(), Bytes::default()],
 			size: Some(69.into()),
 		};
@@ -16,7 +16,7 @@
 
 use ethereum_types::{H160, U256};
 use v1::helpers::CallRequest as Request;
-use v1::types::Bytes;
+use v1::types::{AccessList, Bytes};
 
 /// Call request
 #[derive(Debug, Default, PartialEq, Deserialize)]
@@ -37,6 +37,12 @@ pub struct CallRequest {
 	pub data: Option<Bytes>,
 	/// Nonce
 	pub nonce: Option<U256>,
+	/// Access list, executes an access list transaction (EIP-2930) if given.
+	pub access_list: Option<AccessList>,
+	/// Maximum fee per gas of a dynamic fee transaction (EIP-1559), replaces the gas price.
+	pub max_fee_per_gas: Option<U256>,
+	/// Maximum priority fee per gas, executes a dynamic fee transaction (EIP-1559) if given.
+	pub max_priority_fee_per_gas: Option<U256>,
 }
 
 impl Into<Request> for CallRequest {
@@ -44,11 +50,13 @@ impl Into<Request> for CallRequest {
 		Request {
 			from: self.from.map(Into::into),
 			to: self.to.map(Into::into),
-			gas_price: self.gas_price.map(Into::into),
+			gas_price: self.max_fee_per_gas.or(self.gas_price).map(Into::into),
 			gas: self.gas.map(Into::into),
 			value: self.value.map(Into::into),
 			data: self.data.map(Into::into),
 			nonce: self.nonce.map(Into::into),
+			access_list: self.access_list.map(|list| list.into_iter().map(Into::into).collect()),
+			max_priority_fee_per_gas: self.max_priority_fee_per_gas.map(Into::into),
 		}
 	}
 }
@@ -82,6 +90,9 @@ mod tests {
 			value: Some(U256::from(3)),
 			data: Some(vec![0x12, 0x34, 0x56].into()),
 			nonce: Some(U256::from(4)),
+			access_list: None,
+			max_fee_per_gas: None,
+			max_priority_fee_per_gas: None,
 		});
 	}
 
@@ -104,7 +115,10 @@ mod tests {
 			gas: Some(U256::from_str("76c0").unwrap()),
 			value: Some(U256::from_str("9184e72a").unwrap()),
 			data: Some("d46e8dd67c5d32be8d46e8dd67c5d32be8058bb8eb970870f072445675058bb8eb970870f072445675".from_hex::<Vec<u8>>().unwrap().into()),
-			nonce: None
+			nonce: None,
+			access_list: None,
+			max_fee_per_gas: None,
+			max_priority_fee_per_gas: None,
 		});
 	}
 
@@ -121,6 +135,9 @@ mod tests {
 			value: None,
 			data: None,
 			nonce: None,
+			access_list: None,
+			max_fee_per_gas: None,
+			max_priority_fee_per_gas: None,
 		});
 	}
 }
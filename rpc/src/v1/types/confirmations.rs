@@ -332,6 +332,8 @@ mod tests {
 				data: vec![1, 2, 3],
 				nonce: Some(1.into()),
 				condition: None,
+				access_list: None,
+				max_priority_fee_per_gas: None,
 			}),
 			origin: Origin::Signer {
 				session: H256::from_low_u64_be(5),
@@ -361,6 +363,8 @@ mod tests {
 				data: vec![1, 2, 3],
 				nonce: Some(1.into()),
 				condition: None,
+				access_list: None,
+				max_priority_fee_per_gas: None,
 			}),
 			origin: Origin::Unknown,
 		};
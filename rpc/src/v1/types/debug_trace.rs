@@ -0,0 +1,378 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Types of the `debug_trace*` calls, following the tracers of go-ethereum.
+
+use std::collections::BTreeMap;
+
+use ethereum_types::{BigEndianHash, H160, H256, U256};
+use machine::executed::Executed;
+use rustc_hex::ToHex;
+use trace::{FlatTrace, StructLog as EthStructLog};
+use trace::trace::{Action, CallType, CreationMethod, Res};
+use types::call_analytics::CallAnalytics;
+
+use v1::types::Bytes;
+
+/// Built-in tracers.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Tracer {
+	/// Reports the tree of calls made by the transaction.
+	CallTracer,
+	/// Reports the state of the accounts before the transaction, for the values it changes.
+	PrestateTracer,
+}
+
+/// Options of the `debug_trace*` calls.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceConfig {
+	/// Omit the storage from the instruction logs.
+	#[serde(default)]
+	pub disable_storage: bool,
+	/// Omit the memory from the instruction logs.
+	#[serde(default)]
+	pub disable_memory: bool,
+	/// Omit the stack from the instruction logs.
+	#[serde(default)]
+	pub disable_stack: bool,
+	/// Built-in tracer to use instead of the instruction logs.
+	pub tracer: Option<Tracer>,
+}
+
+impl TraceConfig {
+	/// The analytics the requested trace is built from.
+	pub fn call_analytics(&self) -> CallAnalytics {
+		CallAnalytics {
+			transaction_tracing: self.tracer == Some(Tracer::CallTracer),
+			vm_tracing: self.tracer.is_none(),
+			state_diffing: self.tracer == Some(Tracer::PrestateTracer),
+		}
+	}
+}
+
+/// Log of a single instruction.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLog {
+	/// The program counter.
+	pub pc: usize,
+	/// The name of the instruction.
+	pub op: &'static str,
+	/// The gas left before the instruction.
+	pub gas: u64,
+	/// The gas cost of the instruction.
+	pub gas_cost: u64,
+	/// The depth of the call.
+	pub depth: usize,
+	/// The stack, from the bottom to the top.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub stack: Option<Vec<U256>>,
+	/// The memory, in words of 32 bytes.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub memory: Option<Vec<String>>,
+	/// The storage slots of the executed code read or written so far.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub storage: Option<BTreeMap<String, String>>,
+}
+
+impl StructLog {
+	fn new(log: EthStructLog, config: &TraceConfig) -> Self {
+		fn word(value: U256) -> String {
+			format!("{:x}", H256::from_uint(&value))
+		}
+
+		StructLog {
+			pc: log.pc,
+			op: log.op_name(),
+			gas: log.gas.low_u64(),
+			gas_cost: log.gas_cost.low_u64(),
+			depth: log.depth,
+			stack: if config.disable_stack { None } else { Some(log.stack) },
+			memory: if config.disable_memory { None } else {
+				Some(log.memory.chunks(32).map(|chunk| chunk.to_hex()).collect())
+			},
+			storage: if config.disable_storage { None } else {
+				Some(log.storage.into_iter().map(|(key, value)| (word(key), word(value))).collect())
+			},
+		}
+	}
+}
+
+/// Result of the default tracer: the logs of the executed instructions.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionTrace {
+	/// The gas used by the transaction.
+	pub gas: u64,
+	/// Whether the transaction failed.
+	pub failed: bool,
+	/// The output of the transaction.
+	pub return_value: String,
+	/// The logs of the executed instructions.
+	pub struct_logs: Vec<StructLog>,
+}
+
+impl ExecutionTrace {
+	/// Creates the trace of a transaction executed with VM tracing.
+	pub fn new(executed: Executed, config: &TraceConfig) -> Self {
+		let struct_logs = executed.vm_trace
+			.map(|vm_trace| EthStructLog::from_vm_trace(&vm_trace))
+			.unwrap_or_default();
+
+		ExecutionTrace {
+			gas: executed.gas_used.low_u64(),
+			failed: executed.exception.is_some(),
+			return_value: executed.output.to_hex(),
+			struct_logs: struct_logs.into_iter().map(|log| StructLog::new(log, config)).collect(),
+		}
+	}
+}
+
+/// A call made during the transaction, as reported by the call tracer.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallFrame {
+	/// The type of the call, as the name of the instruction making it.
+	#[serde(rename = "type")]
+	pub call_type: &'static str,
+	/// The caller.
+	pub from: H160,
+	/// The callee, or the created contract.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub to: Option<H160>,
+	/// The value transferred.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub value: Option<U256>,
+	/// The gas available to the call.
+	pub gas: U256,
+	/// The gas used by the call.
+	pub gas_used: U256,
+	/// The input of the call, or the init code.
+	pub input: Bytes,
+	/// The output of the call, or the code of the created contract.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub output: Option<Bytes>,
+	/// The reason the call failed.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+	/// The calls made by this call.
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub calls: Vec<CallFrame>,
+}
+
+impl CallFrame {
+	/// Nests the flat traces of a transaction into the tree of its calls.
+	pub fn from_traces(traces: Vec<FlatTrace>) -> Option<Self> {
+		let mut frames: Vec<CallFrame> = Vec::new();
+		for trace in traces {
+			Self::unwind(&mut frames, trace.trace_address.len() + 1);
+			frames.push(CallFrame::from(trace));
+		}
+		Self::unwind(&mut frames, 1);
+		frames.pop()
+	}
+
+	/// Moves the frames above the given depth into the calls of their parents.
+	fn unwind(frames: &mut Vec<CallFrame>, depth: usize) {
+		while frames.len() >= depth && frames.len() > 1 {
+			let frame = frames.pop().expect("frames.len() > 1; qed");
+			frames.last_mut().expect("frames.len() > 0; qed").calls.push(frame);
+		}
+	}
+}
+
+impl From<FlatTrace> for CallFrame {
+	fn from(trace: FlatTrace) -> Self {
+		let mut frame = match trace.action {
+			Action::Call(call) => {
+				let call_type = match call.call_type.0 {
+					Some(CallType::CallCode) => "CALLCODE",
+					Some(CallType::DelegateCall) => "DELEGATECALL",
+					Some(CallType::StaticCall) => "STATICCALL",
+					Some(CallType::Call) | None => "CALL",
+				};
+				CallFrame {
+					call_type,
+					from: call.from,
+					to: Some(call.to),
+					// Delegate and static calls don't transfer value.
+					value: match call.call_type.0 {
+						Some(CallType::DelegateCall) | Some(CallType::StaticCall) => None,
+						_ => Some(call.value),
+					},
+					gas: call.gas,
+					gas_used: call.gas,
+					input: call.input.into(),
+					output: None,
+					error: None,
+					calls: Vec::new(),
+				}
+			},
+			Action::Create(create) => CallFrame {
+				call_type: match create.creation_method {
+					Some(CreationMethod::Create2) => "CREATE2",
+					_ => "CREATE",
+				},
+				from: create.from,
+				to: None,
+				value: Some(create.value),
+				gas: create.gas,
+				gas_used: create.gas,
+				input: create.init.into(),
+				output: None,
+				error: None,
+				calls: Vec::new(),
+			},
+			Action::Suicide(suicide) => CallFrame {
+				call_type: "SELFDESTRUCT",
+				from: suicide.address,
+				to: Some(suicide.refund_address),
+				value: Some(suicide.balance),
+				gas: U256::zero(),
+				gas_used: U256::zero(),
+				input: Bytes::default(),
+				output: None,
+				error: None,
+				calls: Vec::new(),
+			},
+			Action::Reward(reward) => CallFrame {
+				call_type: "REWARD",
+				from: reward.author,
+				to: None,
+				value: Some(reward.value),
+				gas: U256::zero(),
+				gas_used: U256::zero(),
+				input: Bytes::default(),
+				output: None,
+				error: None,
+				calls: Vec::new(),
+			},
+		};
+
+		match trace.result {
+			Res::Call(result) => {
+				frame.gas_used = result.gas_used;
+				frame.output = Some(result.output.into());
+			},
+			Res::Create(result) => {
+				frame.gas_used = result.gas_used;
+				frame.to = Some(result.address);
+				frame.output = Some(result.code.into());
+			},
+			// The gas used by failed calls is not traced, so all of it is reported.
+			Res::FailedCall(error) | Res::FailedCreate(error) => frame.error = Some(error.to_string()),
+			Res::None => {},
+		}
+		frame
+	}
+}
+
+/// State of an account before the transaction, as reported by the prestate tracer.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct PrestateAccount {
+	/// The balance.
+	pub balance: U256,
+	/// The nonce.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub nonce: Option<u64>,
+	/// The code.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub code: Option<Bytes>,
+	/// The storage slots changed by the transaction.
+	#[serde(skip_serializing_if = "BTreeMap::is_empty")]
+	pub storage: BTreeMap<H256, H256>,
+}
+
+/// Result of a `debug_trace*` call.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum GethTrace {
+	/// Instruction logs of the default tracer.
+	Execution(ExecutionTrace),
+	/// Calls reported by the call tracer.
+	Call(CallFrame),
+	/// Accounts reported by the prestate tracer.
+	Prestate(BTreeMap<H160, PrestateAccount>),
+}
+
+/// Trace of a transaction of a block.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GethTraceWithTransactionHash {
+	/// The hash of the transaction.
+	pub tx_hash: H256,
+	/// The trace of the transaction.
+	pub result: GethTrace,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethereum_types::{H160, U256};
+	use trace::FlatTrace;
+	use trace::trace::{Action, Call, CallResult, CallType, Res};
+	use super::{CallFrame, TraceConfig, Tracer};
+
+	fn call_trace(trace_address: Vec<usize>, subtraces: usize, to: u64) -> FlatTrace {
+		FlatTrace {
+			action: Action::Call(Call {
+				from: H160::from_low_u64_be(1),
+				to: H160::from_low_u64_be(to),
+				value: U256::zero(),
+				gas: 100.into(),
+				input: vec![],
+				call_type: Some(CallType::Call).into(),
+			}),
+			result: Res::Call(CallResult { gas_used: 10.into(), output: vec![1] }),
+			subtraces,
+			trace_address,
+		}
+	}
+
+	#[test]
+	fn trace_config_deserialization() {
+		let config: TraceConfig = serde_json::from_str(r#"{"disableStorage":true,"tracer":"callTracer"}"#).unwrap();
+		assert_eq!(config, TraceConfig {
+			disable_storage: true,
+			tracer: Some(Tracer::CallTracer),
+			..Default::default()
+		});
+		assert!(serde_json::from_str::<TraceConfig>(r#"{"tracer":"jsTracer"}"#).is_err());
+	}
+
+	#[test]
+	fn call_frames_are_nested() {
+		let traces = vec![
+			call_trace(vec![], 2, 2),
+			call_trace(vec![0], 1, 3),
+			call_trace(vec![0, 0], 0, 4),
+			call_trace(vec![1], 0, 5),
+		];
+		let frame = CallFrame::from_traces(traces).unwrap();
+
+		assert_eq!(frame.to, Some(H160::from_low_u64_be(2)));
+		assert_eq!(frame.calls.len(), 2);
+		assert_eq!(frame.calls[0].to, Some(H160::from_low_u64_be(3)));
+		assert_eq!(frame.calls[0].calls[0].to, Some(H160::from_low_u64_be(4)));
+		assert_eq!(frame.calls[1].to, Some(H160::from_low_u64_be(5)));
+
+		let serialized = serde_json::to_string(&frame.calls[1]).unwrap();
+		assert_eq!(serialized, r#"{"type":"CALL","from":"0x0000000000000000000000000000000000000001","to":"0x0000000000000000000000000000000000000005","value":"0x0","gas":"0x64","gasUsed":"0xa","input":"0x","output":"0x01"}"#);
+	}
+}
@@ -0,0 +1,54 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Return types for `eth_feeHistory`.
+
+use ethereum_types::U256;
+
+/// Fee history of a range of blocks (EIP-1559).
+#[derive(Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EthFeeHistory {
+	/// Number of the oldest block of the range.
+	pub oldest_block: U256,
+	/// Base fees per gas of the blocks, followed by the base fee of the block after the newest one.
+	/// Zero for blocks before the EIP-1559 transition.
+	pub base_fee_per_gas: Vec<U256>,
+	/// Ratios of the gas used and the gas limit of the blocks.
+	pub gas_used_ratio: Vec<f64>,
+	/// Effective priority fees per gas at the requested percentiles of the gas used in the blocks.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub reward: Option<Vec<Vec<U256>>>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::EthFeeHistory;
+
+	#[test]
+	fn fee_history_serialization() {
+		let history = EthFeeHistory {
+			oldest_block: 5.into(),
+			base_fee_per_gas: vec![7.into(), 8.into()],
+			gas_used_ratio: vec![0.5],
+			reward: Some(vec![vec![1.into(), 2.into()]]),
+		};
+
+		let serialized = serde_json::to_string(&history).unwrap();
+		assert_eq!(serialized, r#"{"oldestBlock":"0x5","baseFeePerGas":["0x7","0x8"],"gasUsedRatio":[0.5],"reward":[["0x1","0x2"]]}"#);
+	}
+}
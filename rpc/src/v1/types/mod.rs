@@ -19,6 +19,7 @@
 #[cfg(test)]
 mod eth_types;
 
+mod access_list;
 mod account_info;
 mod block;
 mod block_number;
@@ -26,7 +27,9 @@ mod bytes;
 mod call_request;
 mod confirmations;
 mod consensus_status;
+mod debug_trace;
 mod derivation;
+mod fee_history;
 mod filter;
 mod histogram;
 mod index;
@@ -50,6 +53,7 @@ mod eip191;
 pub mod pubsub;
 
 pub use self::eip191::{EIP191Version, PresignedTransaction};
+pub use self::access_list::{AccessList, AccessListItem};
 pub use self::account_info::{AccountInfo, ExtAccountInfo, EthAccount, StorageProof, RecoveredAccount};
 pub use self::bytes::Bytes;
 pub use self::block::{RichBlock, Block, BlockTransactions, Header, RichHeader, Rich};
@@ -60,7 +64,12 @@ pub use self::confirmations::{
 	TransactionModification, EIP191SignRequest, EthSignRequest, DecryptRequest, Either
 };
 pub use self::consensus_status::*;
+pub use self::debug_trace::{
+	CallFrame, ExecutionTrace, GethTrace, GethTraceWithTransactionHash, PrestateAccount, StructLog,
+	TraceConfig, Tracer,
+};
 pub use self::derivation::{DeriveHash, DeriveHierarchical, Derive};
+pub use self::fee_history::EthFeeHistory;
 pub use self::filter::{Filter, FilterChanges};
 pub use self::histogram::Histogram;
 pub use self::index::Index;
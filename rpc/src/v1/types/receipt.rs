@@ -52,6 +52,12 @@ pub struct Receipt {
 	// NOTE(niklasad1): Unknown after EIP98 rules, if it's missing then skip serializing it
 	#[serde(skip_serializing_if = "Option::is_none", rename = "status")]
 	pub status_code: Option<U64>,
+	/// Transaction type, 0 for legacy transactions (EIP-2718)
+	#[serde(rename = "type")]
+	pub transaction_type: U64,
+	/// The gas price paid by the sender, the maximum fee is not always paid in full (EIP-1559)
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub effective_gas_price: Option<U256>,
 }
 
 impl Receipt {
@@ -86,6 +92,8 @@ impl From<LocalizedReceipt> for Receipt {
 			status_code: Self::outcome_to_status_code(&r.outcome),
 			state_root: Self::outcome_to_state_root(r.outcome),
 			logs_bloom: r.log_bloom,
+			transaction_type: r.transaction_type.to_u64().into(),
+			effective_gas_price: Some(r.effective_gas_price),
 		}
 	}
 }
@@ -106,6 +114,8 @@ impl From<RichReceipt> for Receipt {
 			status_code: Self::outcome_to_status_code(&r.outcome),
 			state_root: Self::outcome_to_state_root(r.outcome),
 			logs_bloom: r.log_bloom,
+			transaction_type: r.transaction_type.to_u64().into(),
+			effective_gas_price: Some(r.effective_gas_price),
 		}
 	}
 }
@@ -126,6 +136,8 @@ impl From<EthReceipt> for Receipt {
 			status_code: Self::outcome_to_status_code(&r.outcome),
 			state_root: Self::outcome_to_state_root(r.outcome),
 			logs_bloom: r.log_bloom,
+			transaction_type: r.tx_type.to_u64().into(),
+			effective_gas_price: None,
 		}
 	}
 }
@@ -138,7 +150,7 @@ mod tests {
 
 	#[test]
 	fn receipt_serialization() {
-		let s = r#"{"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x0","blockHash":"0xed76641c68a1c641aee09a94b3b471f4dc0316efe5ac19cf488e2674cf8d05b5","from":null,"to":null,"blockNumber":"0x4510c","cumulativeGasUsed":"0x20","gasUsed":"0x10","contractAddress":null,"logs":[{"address":"0x33990122638b9132ca29c723bdf037f1a891a70c","topics":["0xa6697e974e6a320f454390be03f74955e8978f1a6971ea6730542e37b66179bc","0x4861736852656700000000000000000000000000000000000000000000000000"],"data":"0x","blockHash":"0xed76641c68a1c641aee09a94b3b471f4dc0316efe5ac19cf488e2674cf8d05b5","blockNumber":"0x4510c","transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x0","logIndex":"0x1","transactionLogIndex":null,"type":"mined","removed":false}],"root":"0x000000000000000000000000000000000000000000000000000000000000000a","logsBloom":"0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000f","status":"0x1"}"#;
+		let s = r#"{"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x0","blockHash":"0xed76641c68a1c641aee09a94b3b471f4dc0316efe5ac19cf488e2674cf8d05b5","from":null,"to":null,"blockNumber":"0x4510c","cumulativeGasUsed":"0x20","gasUsed":"0x10","contractAddress":null,"logs":[{"address":"0x33990122638b9132ca29c723bdf037f1a891a70c","topics":["0xa6697e974e6a320f454390be03f74955e8978f1a6971ea6730542e37b66179bc","0x4861736852656700000000000000000000000000000000000000000000000000"],"data":"0x","blockHash":"0xed76641c68a1c641aee09a94b3b471f4dc0316efe5ac19cf488e2674cf8d05b5","blockNumber":"0x4510c","transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x0","logIndex":"0x1","transactionLogIndex":null,"type":"mined","removed":false}],"root":"0x000000000000000000000000000000000000000000000000000000000000000a","logsBloom":"0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000f","status":"0x1","type":"0x1","effectiveGasPrice":"0x2"}"#;
 
 		let receipt = Receipt {
 			from: None,
@@ -169,6 +181,8 @@ mod tests {
 			logs_bloom: Bloom::from_low_u64_be(15),
 			state_root: Some(H256::from_low_u64_be(10)),
 			status_code: Some(1u64.into()),
+			transaction_type: 1u64.into(),
+			effective_gas_price: Some(2u64.into()),
 		};
 
 		let serialized = serde_json::to_string(&receipt).unwrap();
@@ -22,8 +22,10 @@ use machine::executive::{contract_address};
 use vm::CreateContractAddress;
 use ethereum_types::{H160, H256, H512, U64, U256};
 use miner;
-use types::transaction::{LocalizedTransaction, Action, PendingTransaction, SignedTransaction};
-use v1::types::{Bytes, TransactionCondition};
+use types::transaction::{
+	LocalizedTransaction, Action, PendingTransaction, SignedTransaction, TypedTxId, UnverifiedTransaction,
+};
+use v1::types::{AccessList, Bytes, TransactionCondition};
 
 /// Transaction
 #[derive(Debug, Default, Clone, PartialEq, Serialize)]
@@ -69,6 +71,18 @@ pub struct Transaction {
 	pub s: U256,
 	/// Transaction activates at specified block.
 	pub condition: Option<TransactionCondition>,
+	/// Transaction type, 0 for legacy transactions (EIP-2718).
+	#[serde(rename = "type")]
+	pub transaction_type: U64,
+	/// Access list of the transaction, if its type has one.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub access_list: Option<AccessList>,
+	/// Maximum fee per gas of a dynamic fee transaction (EIP-1559).
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub max_fee_per_gas: Option<U256>,
+	/// Maximum priority fee per gas of a dynamic fee transaction (EIP-1559).
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub max_priority_fee_per_gas: Option<U256>,
 }
 
 /// Local Transaction Status
@@ -172,6 +186,14 @@ impl RichRawTransaction {
 	}
 }
 
+/// The given fee if the transaction has a dynamic fee (EIP-1559).
+fn dynamic_fee(t: &UnverifiedTransaction, fee: U256) -> Option<U256> {
+	match t.tx_type() {
+		TypedTxId::EIP1559Transaction => Some(fee),
+		_ => None,
+	}
+}
+
 impl Transaction {
 	/// Convert `LocalizedTransaction` into RPC Transaction.
 	pub fn from_localized(mut t: LocalizedTransaction) -> Transaction {
@@ -196,7 +218,7 @@ impl Transaction {
 				Action::Create => Some(contract_address(scheme, &t.sender(), &t.nonce, &t.data).0),
 				Action::Call(_) => None,
 			},
-			raw: ::rlp::encode(&t.signed).into(),
+			raw: t.signed.envelope().into(),
 			public_key: t.recover_public().ok().map(Into::into),
 			chain_id: t.chain_id().map(U64::from),
 			standard_v: t.standard_v().into(),
@@ -204,6 +226,10 @@ impl Transaction {
 			r: signature.r().into(),
 			s: signature.s().into(),
 			condition: None,
+			transaction_type: t.tx_type().to_u64().into(),
+			access_list: t.access_list().map(|list| list.iter().cloned().map(Into::into).collect()),
+			max_fee_per_gas: dynamic_fee(&t, t.gas_price),
+			max_priority_fee_per_gas: dynamic_fee(&t, t.max_priority_fee_per_gas()),
 		}
 	}
 
@@ -230,7 +256,7 @@ impl Transaction {
 				Action::Create => Some(contract_address(scheme, &t.sender(), &t.nonce, &t.data).0),
 				Action::Call(_) => None,
 			},
-			raw: ::rlp::encode(&t).into(),
+			raw: t.envelope().into(),
 			public_key: t.public_key().map(Into::into),
 			chain_id: t.chain_id().map(U64::from),
 			standard_v: t.standard_v().into(),
@@ -238,6 +264,10 @@ impl Transaction {
 			r: signature.r().into(),
 			s: signature.s().into(),
 			condition: None,
+			transaction_type: t.tx_type().to_u64().into(),
+			access_list: t.access_list().map(|list| list.iter().cloned().map(Into::into).collect()),
+			max_fee_per_gas: dynamic_fee(&t, t.gas_price),
+			max_priority_fee_per_gas: dynamic_fee(&t, t.max_priority_fee_per_gas()),
 		}
 	}
 
@@ -282,7 +312,7 @@ mod tests {
 	fn test_transaction_serialize() {
 		let t = Transaction::default();
 		let serialized = serde_json::to_string(&t).unwrap();
-		assert_eq!(serialized, r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000000","nonce":"0x0","blockHash":null,"blockNumber":null,"transactionIndex":null,"from":"0x0000000000000000000000000000000000000000","to":null,"value":"0x0","gasPrice":"0x0","gas":"0x0","input":"0x","creates":null,"raw":"0x","publicKey":null,"chainId":null,"standardV":"0x0","v":"0x0","r":"0x0","s":"0x0","condition":null}"#);
+		assert_eq!(serialized, r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000000","nonce":"0x0","blockHash":null,"blockNumber":null,"transactionIndex":null,"from":"0x0000000000000000000000000000000000000000","to":null,"value":"0x0","gasPrice":"0x0","gas":"0x0","input":"0x","creates":null,"raw":"0x","publicKey":null,"chainId":null,"standardV":"0x0","v":"0x0","r":"0x0","s":"0x0","condition":null,"type":"0x0"}"#);
 	}
 
 	#[test]
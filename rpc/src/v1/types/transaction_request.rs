@@ -17,7 +17,7 @@
 //! `TransactionRequest` type
 
 use ethereum_types::{H160, U256};
-use v1::types::{Bytes, TransactionCondition};
+use v1::types::{AccessList, Bytes, TransactionCondition};
 use v1::helpers;
 use ansi_term::Colour;
 
@@ -44,6 +44,15 @@ pub struct TransactionRequest {
 	pub nonce: Option<U256>,
 	/// Delay until this block condition.
 	pub condition: Option<TransactionCondition>,
+	/// Access list, sends an access list transaction (EIP-2930) if given.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub access_list: Option<AccessList>,
+	/// Maximum fee per gas of a dynamic fee transaction (EIP-1559), replaces the gas price.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub max_fee_per_gas: Option<U256>,
+	/// Maximum priority fee per gas, sends a dynamic fee transaction (EIP-1559) if given.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub max_priority_fee_per_gas: Option<U256>,
 }
 
 pub fn format_ether(i: U256) -> String {
@@ -99,6 +108,9 @@ impl From<helpers::TransactionRequest> for TransactionRequest {
 			data: r.data.map(Into::into),
 			nonce: r.nonce.map(Into::into),
 			condition: r.condition.map(Into::into),
+			access_list: r.access_list.map(|list| list.into_iter().map(Into::into).collect()),
+			max_fee_per_gas: r.max_priority_fee_per_gas.and(r.gas_price),
+			max_priority_fee_per_gas: r.max_priority_fee_per_gas,
 		}
 	}
 }
@@ -114,6 +126,9 @@ impl From<helpers::FilledTransactionRequest> for TransactionRequest {
 			data: Some(r.data.into()),
 			nonce: r.nonce,
 			condition: r.condition,
+			access_list: r.access_list.map(|list| list.into_iter().map(Into::into).collect()),
+			max_fee_per_gas: r.max_priority_fee_per_gas.map(|_| r.gas_price),
+			max_priority_fee_per_gas: r.max_priority_fee_per_gas,
 		}
 	}
 }
@@ -123,12 +138,14 @@ impl Into<helpers::TransactionRequest> for TransactionRequest {
 		helpers::TransactionRequest {
 			from: self.from.map(Into::into),
 			to: self.to.map(Into::into),
-			gas_price: self.gas_price.map(Into::into),
+			gas_price: self.max_fee_per_gas.or(self.gas_price).map(Into::into),
 			gas: self.gas.map(Into::into),
 			value: self.value.map(Into::into),
 			data: self.data.map(Into::into),
 			nonce: self.nonce.map(Into::into),
 			condition: self.condition.map(Into::into),
+			access_list: self.access_list.map(|list| list.into_iter().map(Into::into).collect()),
+			max_priority_fee_per_gas: self.max_priority_fee_per_gas.map(Into::into),
 		}
 	}
 }
@@ -165,6 +182,9 @@ mod tests {
 			data: Some(vec![0x12, 0x34, 0x56].into()),
 			nonce: Some(U256::from(4)),
 			condition: Some(TransactionCondition::Number(0x13)),
+			access_list: None,
+			max_fee_per_gas: None,
+			max_priority_fee_per_gas: None,
 		});
 	}
 
@@ -189,6 +209,9 @@ mod tests {
 			data: Some("d46e8dd67c5d32be8d46e8dd67c5d32be8058bb8eb970870f072445675058bb8eb970870f072445675".from_hex::<Vec<u8>>().unwrap().into()),
 			nonce: None,
 			condition: None,
+			access_list: None,
+			max_fee_per_gas: None,
+			max_priority_fee_per_gas: None,
 		});
 	}
 
@@ -206,6 +229,9 @@ mod tests {
 			data: None,
 			nonce: None,
 			condition: None,
+			access_list: None,
+			max_fee_per_gas: None,
+			max_priority_fee_per_gas: None,
 		});
 	}
 
@@ -230,6 +256,9 @@ mod tests {
 			data: Some(vec![0x85, 0x95, 0xba, 0xb1].into()),
 			nonce: None,
 			condition: None,
+			access_list: None,
+			max_fee_per_gas: None,
+			max_priority_fee_per_gas: None,
 		});
 	}
 
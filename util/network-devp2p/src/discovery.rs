@@ -153,6 +153,13 @@ pub struct Datagram {
 	pub address: SocketAddr,
 }
 
+/// Outgoing datagrams of a discovery protocol, sent by the host when the socket is writable.
+pub trait DatagramQueue {
+	fn any_sends_queued(&self) -> bool;
+	fn dequeue_send(&mut self) -> Option<Datagram>;
+	fn requeue_send(&mut self, datagram: Datagram);
+}
+
 pub struct Discovery {
 	id: NodeId,
 	id_hash: H256,
@@ -181,6 +188,8 @@ pub struct Discovery {
 pub struct TableUpdates {
 	pub added: HashMap<NodeId, NodeEntry>,
 	pub removed: HashSet<NodeId>,
+	/// Signed records of added nodes, where the protocol provides them.
+	pub records: HashMap<NodeId, Enr>,
 }
 
 impl Discovery {
@@ -226,6 +235,11 @@ impl Discovery {
 		}
 	}
 
+	/// Replace the local node record, e.g. after one of its entries changed.
+	pub fn set_enr(&mut self, enr: Enr) {
+		self.enr = enr;
+	}
+
 	fn update_bucket_record(&mut self, e: NodeEntry) -> Result<(), BucketError> {
 		let id_hash = keccak(e.id);
 		let dist = match Discovery::distance(&self.id_hash, &id_hash) {
@@ -268,7 +282,7 @@ impl Discovery {
             if node_entry.endpoint.is_valid_sync_node() {
 				let mut added = HashMap::with_capacity(1);
 				added.insert(node_entry.id, node_entry);
-				Some(TableUpdates { added, removed: HashSet::new() })
+				Some(TableUpdates { added, removed: HashSet::new(), records: HashMap::new() })
 			} else {
 				None
 			}
@@ -848,18 +862,6 @@ impl Discovery {
 		}
 	}
 
-	pub fn any_sends_queued(&self) -> bool {
-		!self.send_queue.is_empty()
-	}
-
-	pub fn dequeue_send(&mut self) -> Option<Datagram> {
-		self.send_queue.pop_front()
-	}
-
-	pub fn requeue_send(&mut self, datagram: Datagram) {
-		self.send_queue.push_front(datagram)
-	}
-
 	/// Add a list of known nodes to the table.
 	#[cfg(test)]
 	pub fn init_node_list(&mut self, nodes: Vec<NodeEntry>) {
@@ -871,6 +873,20 @@ impl Discovery {
 	}
 }
 
+impl DatagramQueue for Discovery {
+	fn any_sends_queued(&self) -> bool {
+		!self.send_queue.is_empty()
+	}
+
+	fn dequeue_send(&mut self) -> Option<Datagram> {
+		self.send_queue.pop_front()
+	}
+
+	fn requeue_send(&mut self, datagram: Datagram) {
+		self.send_queue.push_front(datagram)
+	}
+}
+
 fn append_expiration(rlp: &mut RlpStream) {
 	let expiry = SystemTime::now() + EXPIRY_TIME;
 	let timestamp = expiry.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as u32;
@@ -0,0 +1,1445 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Node discovery protocol v5.
+//!
+//! Packet headers are masked with AES-CTR keyed by the recipient id and messages are encrypted
+//! with AES-GCM session keys. A node that can't decrypt a message answers with a WHOAREYOU
+//! challenge, which the sender signs in a handshake packet along with an ephemeral key; both
+//! sides then derive the session keys from the ephemeral key agreement.
+//!
+//! Lookups collect node records rather than bare endpoints. Only nodes whose record passes the
+//! configured `EnrFilter` are reported to the node table.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use ethereum_types::H256;
+use keccak_hash::keccak;
+use log::{debug, trace, warn};
+use lru_cache::LruCache;
+use parity_bytes::Bytes;
+use parity_crypto::aes;
+use parity_crypto::publickey::{Generator, KeyPair, Random};
+use ring::{aead, digest, hkdf};
+use rlp::{Rlp, RlpStream};
+use secp256k1::{All, Message, PublicKey, Secp256k1, SecretKey, Signature};
+use network::{EnrFilter, Error, IpFilter};
+
+use crate::discovery::{Datagram, DatagramQueue, MAX_DATAGRAM_SIZE, NodeEntry, TableUpdates};
+use crate::node_record::Enr;
+use crate::node_table::{NodeEndpoint, NodeId};
+
+const PROTOCOL_ID: &[u8] = b"discv5";
+const PACKET_VERSION: u16 = 1;
+
+const FLAG_MESSAGE: u8 = 0;
+const FLAG_WHOAREYOU: u8 = 1;
+const FLAG_HANDSHAKE: u8 = 2;
+
+const MESSAGE_PING: u8 = 1;
+const MESSAGE_PONG: u8 = 2;
+const MESSAGE_FIND_NODE: u8 = 3;
+const MESSAGE_NODES: u8 = 4;
+const MESSAGE_TALK_REQUEST: u8 = 5;
+const MESSAGE_TALK_RESPONSE: u8 = 6;
+
+const MASKING_IV_SIZE: usize = 16;
+const STATIC_HEADER_SIZE: usize = 23;		// protocol id, version, flag, nonce and authdata size.
+const FLAG_OFFSET: usize = MASKING_IV_SIZE + 8;
+const NONCE_OFFSET: usize = MASKING_IV_SIZE + 9;
+const AUTHDATA_OFFSET: usize = MASKING_IV_SIZE + STATIC_HEADER_SIZE;
+const NONCE_SIZE: usize = 12;
+const ID_NONCE_SIZE: usize = 16;
+const KEY_SIZE: usize = 16;
+const SIGNATURE_SIZE: usize = 64;
+const COMPRESSED_KEY_SIZE: usize = 33;
+const MIN_PACKET_SIZE: usize = 63;
+
+const ID_SIGNATURE_TEXT: &[u8] = b"discovery v5 identity proof";
+const KEY_AGREEMENT_TEXT: &[u8] = b"discovery v5 key agreement";
+
+const ADDRESS_BITS: usize = 256;
+const BUCKET_SIZE: usize = 16;
+const ALPHA: usize = 3;
+const LOOKUP_MAX_STEPS: u16 = 8;
+const MAX_NODES_PING: usize = 32;
+const MAX_SESSIONS: usize = 1000;
+const RECORDS_PER_PACKET: usize = 3;		// Records are up to 300 bytes each.
+const MAX_NODES_RESPONSES: u64 = 6;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+const NODE_LAST_SEEN_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// Node we can send packets to. Nodes taken from the node table have no record until they send it.
+#[derive(Clone)]
+struct Contact {
+	id: H256,
+	public: PublicKey,
+	address: SocketAddr,
+	enr: Option<Enr>,
+}
+
+impl Contact {
+	fn from_entry(entry: &NodeEntry) -> Result<Self, Error> {
+		let mut serialized = [4u8; 65];
+		serialized[1..].copy_from_slice(entry.id.as_bytes());
+		let public = PublicKey::from_slice(&serialized).map_err(|_| Error::InvalidNodeId)?;
+		Ok(Contact { id: keccak(entry.id), public, address: entry.endpoint.udp_address(), enr: None })
+	}
+
+	fn from_enr(enr: Enr) -> Option<Self> {
+		let address = SocketAddr::new(IpAddr::V4(enr.ip()?), enr.udp()?);
+		Some(Contact::with_address(enr, address))
+	}
+
+	fn with_address(enr: Enr, address: SocketAddr) -> Self {
+		let public = enr.public_key();
+		Contact { id: node_id(&public), public, address, enr: Some(enr) }
+	}
+
+	fn enr_seq(&self) -> u64 {
+		self.enr.as_ref().map_or(0, |enr| enr.seq())
+	}
+
+	fn node_entry(&self) -> NodeEntry {
+		let tcp_port = self.enr.as_ref().and_then(|enr| enr.tcp()).unwrap_or(0);
+		NodeEntry {
+			id: NodeId::from_slice(&self.public.serialize_uncompressed()[1..]),
+			endpoint: NodeEndpoint { address: SocketAddr::new(self.address.ip(), tcp_port), udp_port: self.address.port() },
+		}
+	}
+}
+
+struct Session {
+	contact: Contact,
+	encryption_key: [u8; KEY_SIZE],
+	decryption_key: [u8; KEY_SIZE],
+}
+
+/// WHOAREYOU we sent and expect a handshake for.
+struct Challenge {
+	data: Bytes,
+	address: SocketAddr,
+	contact: Option<Contact>,
+	sent_at: Instant,
+}
+
+/// Message kept until the node either answers or challenges us to do a handshake.
+struct PendingPacket {
+	contact: Contact,
+	message: Bytes,
+	sent_at: Instant,
+}
+
+enum RequestKind {
+	Ping,
+	FindNode { distances: Vec<u16>, responses: u64 },
+}
+
+struct Request {
+	contact: Contact,
+	kind: RequestKind,
+	sent_at: Instant,
+}
+
+struct BucketEntry {
+	contact: Contact,
+	last_seen: Instant,
+}
+
+struct Lookup {
+	target: H256,
+	round: u16,
+	asked: HashSet<H256>,
+}
+
+/// Packet with an unmasked header. The header is prefixed with the masking IV, together they
+/// make the additional data of the message encryption.
+struct Packet {
+	flag: u8,
+	nonce: [u8; NONCE_SIZE],
+	header: Bytes,
+	message: Bytes,
+}
+
+impl Packet {
+	fn authdata(&self) -> &[u8] {
+		&self.header[AUTHDATA_OFFSET..]
+	}
+}
+
+pub struct DiscoveryV5 {
+	id: H256,
+	secret: SecretKey,
+	secp: Secp256k1<All>,
+	enr: Enr,
+	enr_filter: Option<EnrFilter>,
+	ip_filter: IpFilter,
+	buckets: Vec<VecDeque<BucketEntry>>,
+	sessions: LruCache<H256, Session>,
+	challenges: HashMap<H256, Challenge>,
+	pending: HashMap<[u8; NONCE_SIZE], PendingPacket>,
+	requests: HashMap<Bytes, Request>,
+	lookup: Option<Lookup>,
+	lookup_initiated: bool,
+	adding_nodes: Vec<Contact>,
+	send_queue: VecDeque<Datagram>,
+}
+
+impl DiscoveryV5 {
+	pub fn new(key: &KeyPair, enr: Enr, enr_filter: Option<EnrFilter>, ip_filter: IpFilter) -> Result<DiscoveryV5, Error> {
+		Ok(DiscoveryV5 {
+			id: keccak(key.public()),
+			secret: key.secret().to_secp256k1_secret()?,
+			secp: Secp256k1::new(),
+			enr,
+			enr_filter,
+			ip_filter,
+			buckets: (0..ADDRESS_BITS).map(|_| VecDeque::new()).collect(),
+			sessions: LruCache::new(MAX_SESSIONS),
+			challenges: HashMap::new(),
+			pending: HashMap::new(),
+			requests: HashMap::new(),
+			lookup: None,
+			lookup_initiated: false,
+			adding_nodes: Vec::new(),
+			send_queue: VecDeque::new(),
+		})
+	}
+
+	/// Add a node known by its public key. Pings the node, its record is requested once it answers.
+	pub fn add_node(&mut self, e: NodeEntry) {
+		match Contact::from_entry(&e) {
+			Ok(contact) => self.add_contact(contact),
+			Err(_) => debug!(target: "discovery", "Invalid node id: {:?}", e),
+		}
+	}
+
+	pub fn add_node_list(&mut self, nodes: Vec<NodeEntry>) {
+		for node in nodes {
+			self.add_node(node);
+		}
+	}
+
+	/// Replace the local node record, e.g. after one of its entries changed.
+	pub fn set_enr(&mut self, enr: Enr) {
+		self.enr = enr;
+	}
+
+	/// Add a node known by its signed record, e.g. from a DNS node list.
+	pub fn add_node_record(&mut self, enr: Enr) {
+		match Contact::from_enr(enr) {
+			Some(contact) => self.add_contact(contact),
+			None => debug!(target: "discovery", "Node record without UDP endpoint"),
+		}
+	}
+
+	fn add_contact(&mut self, contact: Contact) {
+		if contact.id == self.id || !self.is_allowed(&contact) || self.in_bucket(&contact.id) {
+			return;
+		}
+		if self.adding_nodes.iter().any(|c| c.id == contact.id) {
+			return;
+		}
+		self.adding_nodes.push(contact);
+	}
+
+	/// Check whether a packet received on a shared socket belongs to this protocol.
+	pub fn is_v5_packet(&self, packet: &[u8]) -> bool {
+		packet.len() >= MIN_PACKET_SIZE && unmask_header(&self.id, packet, STATIC_HEADER_SIZE)
+			.map_or(false, |header| header.starts_with(PROTOCOL_ID))
+	}
+
+	pub fn on_packet(&mut self, packet: &[u8], from: SocketAddr) -> Result<Option<TableUpdates>, Error> {
+		let packet = decode_packet(&self.id, packet)?;
+		match packet.flag {
+			FLAG_MESSAGE => self.on_message_packet(&packet, from),
+			FLAG_WHOAREYOU => self.on_whoareyou(&packet, from).map(|_| None),
+			FLAG_HANDSHAKE => self.on_handshake(&packet, from),
+			flag => {
+				debug!(target: "discovery", "Unknown discovery v5 packet flag: {}", flag);
+				Ok(None)
+			}
+		}
+	}
+
+	fn on_message_packet(&mut self, packet: &Packet, from: SocketAddr) -> Result<Option<TableUpdates>, Error> {
+		if packet.authdata().len() != 32 {
+			return Err(Error::BadProtocol);
+		}
+		let node_id = H256::from_slice(packet.authdata());
+		let message = match self.sessions.get_mut(&node_id) {
+			Some(ref session) if session.contact.address == from =>
+				decrypt_message(&session.decryption_key, &packet.nonce, &packet.message, &packet.header).ok(),
+			_ => None,
+		};
+		match message {
+			Some(message) => self.on_message(node_id, &message),
+			None => {
+				trace!(target: "discovery", "No session with {:?}, sending WHOAREYOU", &from);
+				self.send_whoareyou(node_id, from, &packet.nonce)?;
+				Ok(None)
+			}
+		}
+	}
+
+	fn send_whoareyou(&mut self, node_id: H256, address: SocketAddr, nonce: &[u8; NONCE_SIZE]) -> Result<(), Error> {
+		if let Some(challenge) = self.challenges.get(&node_id) {
+			if challenge.sent_at + REQUEST_TIMEOUT > Instant::now() {
+				return Ok(());
+			}
+		}
+		let contact = self.known_contact(&node_id);
+		let id_nonce: [u8; ID_NONCE_SIZE] = rand::random();
+		let mut authdata = id_nonce.to_vec();
+		authdata.extend_from_slice(&contact.as_ref().map_or(0, Contact::enr_seq).to_be_bytes());
+		let header = encode_header(FLAG_WHOAREYOU, nonce, &authdata);
+		let packet = seal_packet(&node_id, &header, None)?;
+		self.challenges.insert(node_id, Challenge { data: header, address, contact, sent_at: Instant::now() });
+		self.send_to(packet, address);
+		Ok(())
+	}
+
+	fn on_whoareyou(&mut self, packet: &Packet, from: SocketAddr) -> Result<(), Error> {
+		let authdata = packet.authdata();
+		if authdata.len() != ID_NONCE_SIZE + 8 {
+			return Err(Error::BadProtocol);
+		}
+		let pending = match self.pending.remove(&packet.nonce) {
+			Some(ref pending) if pending.contact.address != from => {
+				debug!(target: "discovery", "WHOAREYOU from unexpected address {:?}", &from);
+				return Ok(());
+			}
+			Some(pending) => pending,
+			None => {
+				debug!(target: "discovery", "Unexpected WHOAREYOU from {:?}", &from);
+				return Ok(());
+			}
+		};
+		let mut enr_seq = [0u8; 8];
+		enr_seq.copy_from_slice(&authdata[ID_NONCE_SIZE..]);
+		let contact = pending.contact;
+		let challenge_data = &packet.header;
+
+		let ephemeral = Random.generate();
+		let ephemeral_secret = ephemeral.secret().to_secp256k1_secret()?;
+		let ephemeral_key = PublicKey::from_secret_key(&self.secp, &ephemeral_secret).serialize();
+		let (initiator_key, recipient_key) = derive_keys(&ecdh(&self.secp, &contact.public, &ephemeral_secret)?, challenge_data, &self.id, &contact.id);
+		let id_signature = self.secp.sign(&id_signature_hash(challenge_data, &ephemeral_key, &contact.id), &self.secret);
+
+		let mut authdata = self.id.as_bytes().to_vec();
+		authdata.push(SIGNATURE_SIZE as u8);
+		authdata.push(COMPRESSED_KEY_SIZE as u8);
+		authdata.extend_from_slice(&id_signature.serialize_compact());
+		authdata.extend_from_slice(&ephemeral_key);
+		// The challenge tells which version of our record the node has.
+		if u64::from_be_bytes(enr_seq) < self.enr.seq() {
+			authdata.extend(rlp::encode(&self.enr));
+		}
+		let nonce: [u8; NONCE_SIZE] = rand::random();
+		let header = encode_header(FLAG_HANDSHAKE, &nonce, &authdata);
+		let packet = seal_packet(&contact.id, &header, Some((&initiator_key, &pending.message)))?;
+
+		trace!(target: "discovery", "Sending handshake to {:?}", &from);
+		self.sessions.insert(contact.id, Session {
+			contact: contact.clone(),
+			encryption_key: initiator_key,
+			decryption_key: recipient_key,
+		});
+		self.pending.insert(nonce, PendingPacket { contact, message: pending.message, sent_at: Instant::now() });
+		self.send_to(packet, from);
+		Ok(())
+	}
+
+	fn on_handshake(&mut self, packet: &Packet, from: SocketAddr) -> Result<Option<TableUpdates>, Error> {
+		let authdata = packet.authdata();
+		if authdata.len() < 34 || authdata[32] as usize != SIGNATURE_SIZE || authdata[33] as usize != COMPRESSED_KEY_SIZE {
+			return Err(Error::BadProtocol);
+		}
+		let record_offset = 34 + SIGNATURE_SIZE + COMPRESSED_KEY_SIZE;
+		if authdata.len() < record_offset {
+			return Err(Error::BadProtocol);
+		}
+		let node_id = H256::from_slice(&authdata[..32]);
+		let challenge = match self.challenges.remove(&node_id) {
+			Some(challenge) => challenge,
+			None => {
+				debug!(target: "discovery", "Unexpected handshake from {:?}", &from);
+				return Ok(None);
+			}
+		};
+		if challenge.address != from {
+			debug!(target: "discovery", "Handshake from unexpected address {:?}", &from);
+			return Ok(None);
+		}
+
+		let contact = if authdata.len() > record_offset {
+			Contact::with_address(rlp::decode::<Enr>(&authdata[record_offset..])?, from)
+		} else {
+			challenge.contact.ok_or(Error::BadProtocol)?
+		};
+		if contact.id != node_id {
+			return Err(Error::InvalidNodeId);
+		}
+
+		let ephemeral_key = &authdata[34 + SIGNATURE_SIZE..record_offset];
+		let mut id_signature = Signature::from_compact(&authdata[34..34 + SIGNATURE_SIZE]).map_err(|_| Error::Auth)?;
+		id_signature.normalize_s();
+		self.secp.verify(&id_signature_hash(&challenge.data, ephemeral_key, &self.id), &id_signature, &contact.public)
+			.map_err(|_| Error::Auth)?;
+
+		let ephemeral_key = PublicKey::from_slice(ephemeral_key).map_err(|_| Error::Auth)?;
+		let (initiator_key, recipient_key) = derive_keys(&ecdh(&self.secp, &ephemeral_key, &self.secret)?, &challenge.data, &node_id, &self.id);
+		let message = decrypt_message(&initiator_key, &packet.nonce, &packet.message, &packet.header)?;
+
+		trace!(target: "discovery", "Established session with {:?}", &from);
+		self.sessions.insert(node_id, Session {
+			contact: contact.clone(),
+			encryption_key: recipient_key,
+			decryption_key: initiator_key,
+		});
+		let added = self.update_node(contact);
+		let updates = self.on_message(node_id, &message)?;
+		Ok(match (added, updates) {
+			(Some(mut added), Some(updates)) => {
+				added.added.extend(updates.added);
+				Some(added)
+			}
+			(added, updates) => added.or(updates),
+		})
+	}
+
+	fn on_message(&mut self, node_id: H256, message: &[u8]) -> Result<Option<TableUpdates>, Error> {
+		if message.is_empty() {
+			return Err(Error::BadProtocol);
+		}
+		let rlp = Rlp::new(&message[1..]);
+		match message[0] {
+			MESSAGE_PING => self.on_ping(&rlp, node_id).map(|_| None),
+			MESSAGE_PONG => self.on_pong(&rlp, node_id),
+			MESSAGE_FIND_NODE => self.on_find_node(&rlp, node_id).map(|_| None),
+			MESSAGE_NODES => self.on_nodes(&rlp, node_id),
+			MESSAGE_TALK_REQUEST => self.on_talk_request(&rlp, node_id).map(|_| None),
+			MESSAGE_TALK_RESPONSE => Ok(None),
+			message_id => {
+				debug!(target: "discovery", "Unknown discovery v5 message: {}", message_id);
+				Ok(None)
+			}
+		}
+	}
+
+	fn on_ping(&mut self, rlp: &Rlp, node_id: H256) -> Result<(), Error> {
+		let request_id: Bytes = rlp.val_at(0)?;
+		let enr_seq: u64 = rlp.val_at(1)?;
+		let contact = match self.sessions.get_mut(&node_id) {
+			Some(session) => session.contact.clone(),
+			None => return Ok(()),
+		};
+		trace!(target: "discovery", "Got Ping from {:?}", &contact.address);
+
+		let mut response = RlpStream::new_list(4);
+		response.append(&request_id);
+		response.append(&self.enr.seq());
+		match contact.address.ip() {
+			IpAddr::V4(ip) => response.append(&ip.octets().to_vec()),
+			IpAddr::V6(ip) => response.append(&ip.octets().to_vec()),
+		};
+		response.append(&contact.address.port());
+		self.send_response(&node_id, MESSAGE_PONG, response.out())?;
+
+		self.touch(&node_id);
+		if contact.enr_seq() < enr_seq {
+			self.send_find_node(contact, vec![0])?;
+		}
+		Ok(())
+	}
+
+	fn on_pong(&mut self, rlp: &Rlp, node_id: H256) -> Result<Option<TableUpdates>, Error> {
+		let request_id: Bytes = rlp.val_at(0)?;
+		let enr_seq: u64 = rlp.val_at(1)?;
+		let contact = match self.requests.get(&request_id) {
+			Some(&Request { ref contact, kind: RequestKind::Ping, .. }) if contact.id == node_id => contact.clone(),
+			_ => {
+				debug!(target: "discovery", "Got unexpected Pong");
+				return Ok(None);
+			}
+		};
+		self.requests.remove(&request_id);
+		trace!(target: "discovery", "Got Pong from {:?}", &contact.address);
+
+		if contact.enr_seq() < enr_seq {
+			self.send_find_node(contact, vec![0])?;
+			Ok(None)
+		} else {
+			Ok(self.update_node(contact))
+		}
+	}
+
+	fn on_find_node(&mut self, rlp: &Rlp, node_id: H256) -> Result<(), Error> {
+		let request_id: Bytes = rlp.val_at(0)?;
+		let mut distances: Vec<u16> = rlp.list_at(1)?;
+		distances.sort();
+		distances.dedup();
+
+		let mut records = Vec::new();
+		for distance in distances.into_iter().map(|d| d as usize) {
+			if records.len() >= BUCKET_SIZE {
+				break;
+			}
+			if distance == 0 {
+				records.push(self.enr.clone());
+			} else if distance <= ADDRESS_BITS {
+				records.extend(self.buckets[distance - 1].iter().filter_map(|e| e.contact.enr.clone()));
+			}
+		}
+		records.truncate(BUCKET_SIZE);
+		trace!(target: "discovery", "Sending {} node records", records.len());
+
+		let total = std::cmp::max(1, (records.len() + RECORDS_PER_PACKET - 1) / RECORDS_PER_PACKET);
+		let mut chunks = records.chunks(RECORDS_PER_PACKET).collect::<Vec<_>>();
+		if chunks.is_empty() {
+			chunks.push(&[]);
+		}
+		for chunk in chunks {
+			let mut response = RlpStream::new_list(3);
+			response.append(&request_id);
+			response.append(&(total as u64));
+			response.append_list(chunk);
+			self.send_response(&node_id, MESSAGE_NODES, response.out())?;
+		}
+		Ok(())
+	}
+
+	fn on_nodes(&mut self, rlp: &Rlp, node_id: H256) -> Result<Option<TableUpdates>, Error> {
+		let request_id: Bytes = rlp.val_at(0)?;
+		let total = std::cmp::min(rlp.val_at::<u64>(1)?, MAX_NODES_RESPONSES);
+		let expected = match self.requests.get_mut(&request_id) {
+			Some(request) if request.contact.id == node_id => match request.kind {
+				RequestKind::FindNode { ref distances, ref mut responses } => {
+					*responses += 1;
+					Some((request.contact.clone(), distances.clone(), *responses >= total))
+				}
+				RequestKind::Ping => None,
+			},
+			_ => None,
+		};
+		let (contact, distances, done) = match expected {
+			Some(expected) => expected,
+			None => {
+				debug!(target: "discovery", "Got unexpected Nodes");
+				return Ok(None);
+			}
+		};
+		if done {
+			self.requests.remove(&request_id);
+		}
+
+		let mut updates = None;
+		for record in rlp.at(2)?.iter() {
+			let enr = match record.as_val::<Enr>() {
+				Ok(enr) => enr,
+				Err(e) => {
+					debug!(target: "discovery", "Invalid node record: {:?}", e);
+					continue;
+				}
+			};
+			let id = node_id_of(&enr);
+			let distance = log_distance(&contact.id, &id).unwrap_or(0);
+			if !distances.contains(&(distance as u16)) {
+				debug!(target: "discovery", "Got node record at unrequested distance {} from {:?}", distance, &contact.address);
+				continue;
+			}
+			if id == contact.id {
+				// The node sent its own record: it is alive, so it goes right into the table.
+				let mut contact = contact.clone();
+				contact.enr = Some(enr);
+				updates = self.update_node(contact);
+			} else if let Some(found) = Contact::from_enr(enr) {
+				self.add_contact(found);
+			}
+		}
+		Ok(updates)
+	}
+
+	fn on_talk_request(&mut self, rlp: &Rlp, node_id: H256) -> Result<(), Error> {
+		// No talk protocols are supported, an empty response says so.
+		let request_id: Bytes = rlp.val_at(0)?;
+		let mut response = RlpStream::new_list(2);
+		response.append(&request_id);
+		response.append_empty_data();
+		self.send_response(&node_id, MESSAGE_TALK_RESPONSE, response.out())
+	}
+
+	fn send_ping(&mut self, contact: Contact) -> Result<(), Error> {
+		let request_id = new_request_id();
+		let mut rlp = RlpStream::new_list(2);
+		rlp.append(&request_id);
+		rlp.append(&self.enr.seq());
+		self.send_request(contact, request_id, RequestKind::Ping, MESSAGE_PING, rlp.out())
+	}
+
+	fn send_find_node(&mut self, contact: Contact, distances: Vec<u16>) -> Result<(), Error> {
+		let request_id = new_request_id();
+		let mut rlp = RlpStream::new_list(2);
+		rlp.append(&request_id);
+		rlp.append_list(&distances);
+		self.send_request(contact, request_id, RequestKind::FindNode { distances, responses: 0 }, MESSAGE_FIND_NODE, rlp.out())
+	}
+
+	fn send_request(&mut self, contact: Contact, request_id: Bytes, kind: RequestKind, message_id: u8, payload: Bytes) -> Result<(), Error> {
+		let message = encode_message(message_id, payload);
+		// Without a session the node can't decrypt the message whatever the key, and answers with a challenge.
+		let key = match self.sessions.get_mut(&contact.id) {
+			Some(ref session) if session.contact.address == contact.address => session.encryption_key,
+			_ => rand::random(),
+		};
+		let nonce: [u8; NONCE_SIZE] = rand::random();
+		let header = encode_header(FLAG_MESSAGE, &nonce, self.id.as_bytes());
+		let packet = seal_packet(&contact.id, &header, Some((&key, &message)))?;
+
+		let address = contact.address;
+		let now = Instant::now();
+		self.pending.insert(nonce, PendingPacket { contact: contact.clone(), message, sent_at: now });
+		self.requests.insert(request_id, Request { contact, kind, sent_at: now });
+		self.send_to(packet, address);
+		Ok(())
+	}
+
+	fn send_response(&mut self, node_id: &H256, message_id: u8, payload: Bytes) -> Result<(), Error> {
+		let (key, address) = match self.sessions.get_mut(node_id) {
+			Some(session) => (session.encryption_key, session.contact.address),
+			None => return Ok(()),
+		};
+		let nonce: [u8; NONCE_SIZE] = rand::random();
+		let header = encode_header(FLAG_MESSAGE, &nonce, self.id.as_bytes());
+		let packet = seal_packet(node_id, &header, Some((&key, &encode_message(message_id, payload))))?;
+		self.send_to(packet, address);
+		Ok(())
+	}
+
+	fn send_to(&mut self, payload: Bytes, address: SocketAddr) {
+		self.send_queue.push_back(Datagram { payload, address });
+	}
+
+	fn known_contact(&mut self, node_id: &H256) -> Option<Contact> {
+		if let Some(session) = self.sessions.get_mut(node_id) {
+			return Some(session.contact.clone());
+		}
+		log_distance(&self.id, node_id)
+			.and_then(|distance| self.buckets[distance - 1].iter().find(|e| e.contact.id == *node_id))
+			.map(|e| e.contact.clone())
+	}
+
+	fn in_bucket(&self, node_id: &H256) -> bool {
+		log_distance(&self.id, node_id).map_or(true, |distance| self.buckets[distance - 1].iter().any(|e| e.contact.id == *node_id))
+	}
+
+	fn touch(&mut self, node_id: &H256) {
+		if let Some(distance) = log_distance(&self.id, node_id) {
+			if let Some(entry) = self.buckets[distance - 1].iter_mut().find(|e| e.contact.id == *node_id) {
+				entry.last_seen = Instant::now();
+			}
+		}
+	}
+
+	fn is_allowed(&self, contact: &Contact) -> bool {
+		NodeEndpoint { address: contact.address, udp_port: contact.address.port() }.is_allowed(&self.ip_filter)
+	}
+
+	fn matches_filter(&self, enr: &Enr) -> bool {
+		self.enr_filter.as_ref().map_or(true, |filter| filter.matches(enr.get(&filter.key).map(|value| &value[..])))
+	}
+
+	/// Insert a node that answered us into its bucket. Reports it to the node table if its record
+	/// is new and passes the filter.
+	fn update_node(&mut self, contact: Contact) -> Option<TableUpdates> {
+		let distance = log_distance(&self.id, &contact.id)?;
+		if !self.is_allowed(&contact) {
+			return None;
+		}
+		let record = contact.enr.clone()?;
+		let matches = self.matches_filter(&record);
+		let entry = contact.node_entry();
+
+		let bucket = &mut self.buckets[distance - 1];
+		if let Some(known) = bucket.iter_mut().find(|e| e.contact.id == contact.id) {
+			let updated = known.contact.enr_seq() < contact.enr_seq();
+			known.contact = contact;
+			known.last_seen = Instant::now();
+			if !updated {
+				return None;
+			}
+		} else {
+			// Nodes that didn't answer were removed on timeout, only make room by dropping nodes not seen for long.
+			let now = Instant::now();
+			if bucket.len() >= BUCKET_SIZE {
+				match bucket.iter().position(|e| e.last_seen + NODE_LAST_SEEN_TIMEOUT < now) {
+					Some(stale) => { bucket.remove(stale); },
+					None => {
+						trace!(target: "discovery", "Bucket {} is full, dropping {:?}", distance, &entry);
+						return None;
+					}
+				}
+			}
+			trace!(target: "discovery", "Adding a new node {:?} into our bucket {}", &entry, distance);
+			bucket.push_front(BucketEntry { contact, last_seen: now });
+		}
+
+		if matches && entry.endpoint.is_valid_sync_node() {
+			let mut records = HashMap::with_capacity(1);
+			records.insert(entry.id, record);
+			let mut added = HashMap::with_capacity(1);
+			added.insert(entry.id, entry);
+			Some(TableUpdates { added, removed: HashSet::new(), records })
+		} else {
+			None
+		}
+	}
+
+	fn remove_node(&mut self, node_id: &H256) {
+		if let Some(distance) = log_distance(&self.id, node_id) {
+			self.buckets[distance - 1].retain(|e| e.contact.id != *node_id);
+		}
+	}
+
+	fn nearest_nodes(&self, target: &H256) -> Vec<Contact> {
+		let mut entries = self.buckets.iter().flat_map(|bucket| bucket.iter()).collect::<Vec<_>>();
+		entries.sort_by_key(|e| e.contact.id ^ *target);
+		entries.into_iter().take(BUCKET_SIZE).map(|e| e.contact.clone()).collect()
+	}
+
+	fn update_new_nodes(&mut self) {
+		while self.requests.len() < MAX_NODES_PING {
+			match self.adding_nodes.pop() {
+				Some(contact) => if let Err(e) = self.send_ping(contact) {
+					warn!(target: "discovery", "Error sending discovery v5 ping: {:?}", e);
+				},
+				None => break,
+			}
+		}
+	}
+
+	fn lookup_round(&mut self) {
+		let mut lookup = match self.lookup.take() {
+			Some(lookup) => lookup,
+			None => return,
+		};
+		if lookup.round == LOOKUP_MAX_STEPS {
+			trace!(target: "discovery", "Completing lookup");
+			return;
+		}
+		let nearest = self.nearest_nodes(&lookup.target).into_iter()
+			.filter(|c| !lookup.asked.contains(&c.id))
+			.take(ALPHA)
+			.collect::<Vec<_>>();
+		if nearest.is_empty() {
+			trace!(target: "discovery", "Completing lookup");
+			return;
+		}
+		trace!(target: "discovery", "Starting lookup round {}", lookup.round);
+		for contact in nearest {
+			// Ask for the nodes the contact has around the target.
+			let distance = log_distance(&contact.id, &lookup.target).unwrap_or(1);
+			let distances = [distance, distance + 1, distance - 1].iter()
+				.filter(|d| (1..=ADDRESS_BITS).contains(*d))
+				.map(|d| *d as u16)
+				.collect();
+			lookup.asked.insert(contact.id);
+			if let Err(e) = self.send_find_node(contact, distances) {
+				warn!(target: "discovery", "Error sending discovery v5 lookup packet: {:?}", e);
+			}
+		}
+		lookup.round += 1;
+		self.lookup = Some(lookup);
+	}
+
+	fn check_expired(&mut self, now: Instant) {
+		let expired = self.requests.iter()
+			.filter(|(_, request)| request.sent_at + REQUEST_TIMEOUT <= now)
+			.map(|(id, _)| id.clone())
+			.collect::<Vec<_>>();
+		for id in expired {
+			if let Some(request) = self.requests.remove(&id) {
+				match request.kind {
+					// Some answer came, the node is alive.
+					RequestKind::FindNode { responses, .. } if responses > 0 => {}
+					_ => {
+						trace!(target: "discovery", "Request to {:?} timed out", &request.contact.address);
+						self.remove_node(&request.contact.id);
+					}
+				}
+			}
+		}
+		self.pending.retain(|_, pending| pending.sent_at + REQUEST_TIMEOUT > now);
+		self.challenges.retain(|_, challenge| challenge.sent_at + REQUEST_TIMEOUT > now);
+	}
+
+	pub fn round(&mut self) {
+		self.check_expired(Instant::now());
+		self.update_new_nodes();
+
+		if self.lookup.is_some() {
+			self.lookup_round();
+		// Start the first lookup once the initial pings have been answered (or timed out)
+		} else if self.requests.is_empty() && !self.lookup_initiated {
+			self.lookup_initiated = true;
+			self.refresh();
+		}
+	}
+
+	pub fn refresh(&mut self) {
+		if self.lookup.is_none() {
+			trace!(target: "discovery", "Starting discovery v5 lookup");
+			self.lookup = Some(Lookup { target: H256::random(), round: 0, asked: HashSet::new() });
+		}
+	}
+}
+
+impl DatagramQueue for DiscoveryV5 {
+	fn any_sends_queued(&self) -> bool {
+		!self.send_queue.is_empty()
+	}
+
+	fn dequeue_send(&mut self) -> Option<Datagram> {
+		self.send_queue.pop_front()
+	}
+
+	fn requeue_send(&mut self, datagram: Datagram) {
+		self.send_queue.push_front(datagram)
+	}
+}
+
+fn node_id(public: &PublicKey) -> H256 {
+	keccak(&public.serialize_uncompressed()[1..])
+}
+
+fn node_id_of(enr: &Enr) -> H256 {
+	node_id(&enr.public_key())
+}
+
+/// The base 2 log of the distance between a and b using the XOR metric, from 1 to 256.
+fn log_distance(a: &H256, b: &H256) -> Option<usize> {
+	let distance = *a ^ *b;
+	let bytes = distance.as_bytes();
+	bytes.iter().position(|byte| *byte != 0)
+		.map(|i| (bytes.len() - i) * 8 - bytes[i].leading_zeros() as usize)
+}
+
+fn new_request_id() -> Bytes {
+	rand::random::<u64>().to_be_bytes().to_vec()
+}
+
+fn encode_message(message_id: u8, payload: Bytes) -> Bytes {
+	let mut message = Bytes::with_capacity(payload.len() + 1);
+	message.push(message_id);
+	message.extend(payload);
+	message
+}
+
+/// Unmasked packet header, prefixed with a random masking IV.
+fn encode_header(flag: u8, nonce: &[u8; NONCE_SIZE], authdata: &[u8]) -> Bytes {
+	let masking_iv: [u8; MASKING_IV_SIZE] = rand::random();
+	let mut header = Bytes::with_capacity(AUTHDATA_OFFSET + authdata.len());
+	header.extend_from_slice(&masking_iv);
+	header.extend_from_slice(PROTOCOL_ID);
+	header.extend_from_slice(&PACKET_VERSION.to_be_bytes());
+	header.push(flag);
+	header.extend_from_slice(nonce);
+	header.extend_from_slice(&(authdata.len() as u16).to_be_bytes());
+	header.extend_from_slice(authdata);
+	header
+}
+
+/// Mask the header for the recipient and append the encrypted message, if any.
+fn seal_packet(recipient: &H256, header: &[u8], message: Option<(&[u8; KEY_SIZE], &[u8])>) -> Result<Bytes, Error> {
+	let (masking_iv, unmasked) = header.split_at(MASKING_IV_SIZE);
+	let mut packet = vec![0u8; header.len()];
+	packet[..MASKING_IV_SIZE].copy_from_slice(masking_iv);
+	aes::encrypt_128_ctr(&recipient.as_bytes()[..KEY_SIZE], masking_iv, unmasked, &mut packet[MASKING_IV_SIZE..])?;
+	if let Some((key, message)) = message {
+		let mut nonce = [0u8; NONCE_SIZE];
+		nonce.copy_from_slice(&header[NONCE_OFFSET..NONCE_OFFSET + NONCE_SIZE]);
+		packet.extend(encrypt_message(key, &nonce, message, header));
+	}
+	Ok(packet)
+}
+
+fn decode_packet(local_id: &H256, packet: &[u8]) -> Result<Packet, Error> {
+	if packet.len() < MIN_PACKET_SIZE {
+		return Err(Error::BadProtocol);
+	}
+	if packet.len() > MAX_DATAGRAM_SIZE {
+		return Err(Error::OversizedPacket);
+	}
+	let static_header = unmask_header(local_id, packet, STATIC_HEADER_SIZE)?;
+	if !static_header.starts_with(PROTOCOL_ID) || static_header[6..8] != PACKET_VERSION.to_be_bytes() {
+		return Err(Error::BadProtocol);
+	}
+	let authdata_size = u16::from_be_bytes([static_header[21], static_header[22]]) as usize;
+	if packet.len() < AUTHDATA_OFFSET + authdata_size {
+		return Err(Error::BadProtocol);
+	}
+
+	let mut header = packet[..MASKING_IV_SIZE].to_vec();
+	header.extend(unmask_header(local_id, packet, STATIC_HEADER_SIZE + authdata_size)?);
+	let mut nonce = [0u8; NONCE_SIZE];
+	nonce.copy_from_slice(&header[NONCE_OFFSET..NONCE_OFFSET + NONCE_SIZE]);
+	Ok(Packet {
+		flag: header[FLAG_OFFSET],
+		nonce,
+		header,
+		message: packet[AUTHDATA_OFFSET + authdata_size..].to_vec(),
+	})
+}
+
+fn unmask_header(local_id: &H256, packet: &[u8], len: usize) -> Result<Bytes, Error> {
+	let mut header = vec![0u8; len];
+	aes::decrypt_128_ctr(&local_id.as_bytes()[..KEY_SIZE], &packet[..MASKING_IV_SIZE], &packet[MASKING_IV_SIZE..MASKING_IV_SIZE + len], &mut header)?;
+	Ok(header)
+}
+
+fn encrypt_message(key: &[u8; KEY_SIZE], nonce: &[u8; NONCE_SIZE], message: &[u8], ad: &[u8]) -> Bytes {
+	let key = aead::LessSafeKey::new(aead::UnboundKey::new(&aead::AES_128_GCM, key).expect("Key has the AES-128 size; qed"));
+	let mut in_out = message.to_vec();
+	key.seal_in_place_append_tag(aead::Nonce::assume_unique_for_key(*nonce), aead::Aad::from(ad), &mut in_out)
+		.expect("Messages are far below the AES-GCM size limit; qed");
+	in_out
+}
+
+fn decrypt_message(key: &[u8; KEY_SIZE], nonce: &[u8; NONCE_SIZE], message: &[u8], ad: &[u8]) -> Result<Bytes, Error> {
+	let key = aead::LessSafeKey::new(aead::UnboundKey::new(&aead::AES_128_GCM, key).expect("Key has the AES-128 size; qed"));
+	let mut in_out = message.to_vec();
+	let len = key.open_in_place(aead::Nonce::assume_unique_for_key(*nonce), aead::Aad::from(ad), &mut in_out)
+		.map_err(|_| Error::Auth)?
+		.len();
+	in_out.truncate(len);
+	Ok(in_out)
+}
+
+fn ecdh(secp: &Secp256k1<All>, public: &PublicKey, secret: &SecretKey) -> Result<[u8; COMPRESSED_KEY_SIZE], Error> {
+	let mut point = *public;
+	point.mul_assign(secp, &secret[..]).map_err(|_| Error::Auth)?;
+	Ok(point.serialize())
+}
+
+struct KeyLength(usize);
+
+impl hkdf::KeyType for KeyLength {
+	fn len(&self) -> usize {
+		self.0
+	}
+}
+
+/// Derive the (initiator, recipient) keys of a session.
+fn derive_keys(secret: &[u8], challenge_data: &[u8], initiator: &H256, recipient: &H256) -> ([u8; KEY_SIZE], [u8; KEY_SIZE]) {
+	let mut info = KEY_AGREEMENT_TEXT.to_vec();
+	info.extend_from_slice(initiator.as_bytes());
+	info.extend_from_slice(recipient.as_bytes());
+	let mut key_data = [0u8; 2 * KEY_SIZE];
+	hkdf::Salt::new(hkdf::HKDF_SHA256, challenge_data)
+		.extract(secret)
+		.expand(&[&info[..]], KeyLength(key_data.len()))
+		.and_then(|okm| okm.fill(&mut key_data))
+		.expect("Key data is far below the HKDF size limit; qed");
+	let mut initiator_key = [0u8; KEY_SIZE];
+	let mut recipient_key = [0u8; KEY_SIZE];
+	initiator_key.copy_from_slice(&key_data[..KEY_SIZE]);
+	recipient_key.copy_from_slice(&key_data[KEY_SIZE..]);
+	(initiator_key, recipient_key)
+}
+
+fn id_signature_hash(challenge_data: &[u8], ephemeral_key: &[u8], recipient: &H256) -> Message {
+	let mut context = digest::Context::new(&digest::SHA256);
+	context.update(ID_SIGNATURE_TEXT);
+	context.update(challenge_data);
+	context.update(ephemeral_key);
+	context.update(recipient.as_bytes());
+	Message::from_slice(context.finish().as_ref()).expect("SHA256 output is 32 bytes; qed")
+}
+
+#[cfg(test)]
+mod tests {
+	use std::str::FromStr;
+
+	use hex_literal::hex;
+	use parity_crypto::publickey::Secret;
+
+	use crate::node_record::EnrManager;
+	use super::*;
+
+	// Keys and node ids of the wire test vectors:
+	// https://github.com/ethereum/devp2p/blob/master/discv5/discv5-wire-test-vectors.md
+	const NODE_A_KEY: &str = "eef77acb6c6a6eebc5b363a475ac583ec7eccdb42b6481424c60f59aa326547f";
+	const NODE_B_KEY: &str = "66fb62bfbd66b9177a138c1e5cddbe4f7c30c343e94e68df8769459cb1cde628";
+	const NODE_A_ID: [u8; 32] = hex!("aaaa8419e9f49d0083561b48287df592939a8d19947d8c0ef88f2a4856a69fbb");
+	const NODE_B_ID: [u8; 32] = hex!("bbbb9d047f0488c0b5a93c1c3f2d8bafc7c8ff337024a55434a0d0555de64db9");
+	const VECTOR_NONCE: [u8; NONCE_SIZE] = [0xff; NONCE_SIZE];
+	const VECTOR_EPHEMERAL_KEY: [u8; 32] = hex!("0288ef00023598499cb6c940146d050d2b1fb914198c327f76aad590bead68b6");
+
+	fn discovery(key: &KeyPair, endpoint: &NodeEndpoint, eth: Option<Bytes>, enr_filter: Option<EnrFilter>) -> DiscoveryV5 {
+		let mut enr = EnrManager::new(None, key.secret().clone(), 0).unwrap().with_node_endpoint(endpoint);
+		if let Some(eth) = eth {
+			enr.set_entry("eth", eth);
+		}
+		DiscoveryV5::new(key, enr.into_enr(), enr_filter, IpFilter::default()).unwrap()
+	}
+
+	fn endpoint(address: &str) -> NodeEndpoint {
+		let address = SocketAddr::from_str(address).unwrap();
+		NodeEndpoint { address, udp_port: address.port() }
+	}
+
+	fn key_pair(secret: &str) -> KeyPair {
+		KeyPair::from_secret(Secret::from_str(secret).unwrap()).unwrap()
+	}
+
+	/// WHOAREYOU header of the test vectors, asking for a record newer than `enr_seq`.
+	fn vector_challenge_data(enr_seq: u8) -> Bytes {
+		let mut data = hex!("
+		000000000000000000000000000000006469736376350001010102030405060708090a0b0c0018010203040506
+		0708090a0b0c0d0e0f100000000000000000
+		").to_vec();
+		*data.last_mut().unwrap() = enr_seq;
+		data
+	}
+
+	/// Ping of the test vectors, with request id 1.
+	fn vector_ping(enr_seq: u64) -> Bytes {
+		let mut rlp = RlpStream::new_list(2);
+		rlp.append(&vec![0u8, 0, 0, 1]);
+		rlp.append(&enr_seq);
+		encode_message(MESSAGE_PING, rlp.out())
+	}
+
+	/// Header of a test vector packet, which uses a zero masking IV.
+	fn vector_header(flag: u8, nonce: &[u8; NONCE_SIZE], authdata: &[u8]) -> Bytes {
+		let mut header = encode_header(flag, nonce, authdata);
+		header[..MASKING_IV_SIZE].copy_from_slice(&[0u8; MASKING_IV_SIZE]);
+		header
+	}
+
+	/// Checks that node A encodes the handshake packet of the test vectors, and that node B accepts
+	/// it with a challenge for `enr_seq`, answers the ping and keeps a session with the vector keys.
+	fn check_vector_handshake(enr_seq: u8, record: &[u8], read_key: [u8; KEY_SIZE], packet: &[u8]) {
+		let secp = Secp256k1::new();
+		let key_a = key_pair(NODE_A_KEY);
+		let key_b = key_pair(NODE_B_KEY);
+		let id_a = H256(NODE_A_ID);
+		let id_b = H256(NODE_B_ID);
+		let challenge_data = vector_challenge_data(enr_seq);
+
+		let ephemeral_secret = SecretKey::from_slice(&VECTOR_EPHEMERAL_KEY).unwrap();
+		let ephemeral_key = PublicKey::from_secret_key(&secp, &ephemeral_secret).serialize();
+		let id_signature = secp.sign(
+			&id_signature_hash(&challenge_data, &ephemeral_key, &id_b),
+			&key_a.secret().to_secp256k1_secret().unwrap(),
+		);
+		let (initiator_key, _) = derive_keys(&ecdh(&secp, &node_public(&key_b), &ephemeral_secret).unwrap(), &challenge_data, &id_a, &id_b);
+		assert_eq!(initiator_key, read_key);
+
+		let mut authdata = id_a.as_bytes().to_vec();
+		authdata.push(SIGNATURE_SIZE as u8);
+		authdata.push(COMPRESSED_KEY_SIZE as u8);
+		authdata.extend_from_slice(&id_signature.serialize_compact());
+		authdata.extend_from_slice(&ephemeral_key);
+		authdata.extend_from_slice(record);
+		let header = vector_header(FLAG_HANDSHAKE, &VECTOR_NONCE, &authdata);
+		assert_eq!(seal_packet(&id_b, &header, Some((&read_key, &vector_ping(1)))).unwrap(), packet.to_vec());
+
+		let ep_a = endpoint("127.0.0.1:40661");
+		let mut discovery_b = discovery(&key_b, &endpoint("127.0.0.1:40662"), None, None);
+		let contact_a = Contact::from_entry(&NodeEntry { id: *key_a.public(), endpoint: ep_a.clone() }).unwrap();
+		discovery_b.challenges.insert(id_a, Challenge {
+			data: challenge_data,
+			address: ep_a.udp_address(),
+			contact: if record.is_empty() { Some(contact_a) } else { None },
+			sent_at: Instant::now(),
+		});
+		discovery_b.on_packet(packet, ep_a.udp_address()).unwrap();
+		let session = discovery_b.sessions.get_mut(&id_a).expect("session with node A");
+		assert_eq!(session.decryption_key, read_key);
+		assert_eq!(session.contact.enr.is_some(), !record.is_empty());
+		let pong = discovery_b.dequeue_send().unwrap();
+		assert_eq!(pong.address, ep_a.udp_address());
+		assert_eq!(decode_packet(&id_a, &pong.payload).unwrap().flag, FLAG_MESSAGE);
+	}
+
+	fn node_public(key: &KeyPair) -> PublicKey {
+		let mut serialized = [4u8; 65];
+		serialized[1..].copy_from_slice(key.public().as_bytes());
+		PublicKey::from_slice(&serialized).unwrap()
+	}
+
+	fn fork_id_entry(hash: [u8; 4], next: u64) -> Bytes {
+		let mut rlp = RlpStream::new_list(1);
+		rlp.begin_list(2);
+		rlp.append(&hash.to_vec());
+		rlp.append(&next);
+		rlp.out()
+	}
+
+	/// Lets `discovery1` contact `discovery2` until both have a session and know each other's
+	/// records. Returns the table updates of both sides.
+	fn connect(
+		discovery1: &mut DiscoveryV5, ep1: &NodeEndpoint, key2: &KeyPair, discovery2: &mut DiscoveryV5, ep2: &NodeEndpoint,
+	) -> (Option<TableUpdates>, Option<TableUpdates>) {
+		discovery1.add_node(NodeEntry { id: *key2.public(), endpoint: ep2.clone() });
+		discovery1.round();
+		let ping = discovery1.dequeue_send().unwrap();
+		assert_eq!(ping.address, ep2.udp_address());
+		assert!(!discovery1.any_sends_queued());
+
+		// No session yet, `discovery2` challenges the sender.
+		assert!(discovery2.on_packet(&ping.payload, ep1.udp_address()).unwrap().is_none());
+		let whoareyou = discovery2.dequeue_send().unwrap();
+		assert_eq!(decode_packet(&discovery1.id, &whoareyou.payload).unwrap().flag, FLAG_WHOAREYOU);
+
+		assert!(discovery1.on_packet(&whoareyou.payload, ep2.udp_address()).unwrap().is_none());
+		let handshake = discovery1.dequeue_send().unwrap();
+		assert_eq!(decode_packet(&discovery2.id, &handshake.payload).unwrap().flag, FLAG_HANDSHAKE);
+
+		// The handshake carries the record of `discovery1` and the ping.
+		let updates2 = discovery2.on_packet(&handshake.payload, ep1.udp_address()).unwrap();
+		let pong = discovery2.dequeue_send().unwrap();
+		assert_eq!(decode_packet(&discovery1.id, &pong.payload).unwrap().flag, FLAG_MESSAGE);
+		assert!(!discovery2.any_sends_queued());
+
+		// `discovery1` doesn't have the record of `discovery2` yet and asks for it.
+		assert!(discovery1.on_packet(&pong.payload, ep2.udp_address()).unwrap().is_none());
+		let find_node = discovery1.dequeue_send().unwrap();
+		assert!(discovery2.on_packet(&find_node.payload, ep1.udp_address()).unwrap().is_none());
+		let nodes = discovery2.dequeue_send().unwrap();
+		let updates1 = discovery1.on_packet(&nodes.payload, ep2.udp_address()).unwrap();
+		assert!(!discovery1.any_sends_queued());
+		assert!(!discovery2.any_sends_queued());
+		(updates1, updates2)
+	}
+
+	#[test]
+	fn packet_roundtrip() {
+		let recipient = H256::random();
+		let key: [u8; KEY_SIZE] = rand::random();
+		let nonce: [u8; NONCE_SIZE] = rand::random();
+		let authdata = H256::random();
+
+		let header = encode_header(FLAG_MESSAGE, &nonce, authdata.as_bytes());
+		let sealed = seal_packet(&recipient, &header, Some((&key, &b"message"[..]))).unwrap();
+		// The header is masked.
+		assert!(!sealed[MASKING_IV_SIZE..].starts_with(PROTOCOL_ID));
+
+		let packet = decode_packet(&recipient, &sealed).unwrap();
+		assert_eq!(packet.flag, FLAG_MESSAGE);
+		assert_eq!(packet.nonce, nonce);
+		assert_eq!(packet.authdata(), authdata.as_bytes());
+		assert_eq!(packet.header, header);
+		assert_eq!(decrypt_message(&key, &packet.nonce, &packet.message, &packet.header).unwrap(), b"message".to_vec());
+		assert!(decrypt_message(&rand::random(), &packet.nonce, &packet.message, &packet.header).is_err());
+
+		// Only the recipient can unmask the header.
+		assert!(decode_packet(&H256::random(), &sealed).is_err());
+	}
+
+	#[test]
+	fn vector_node_ids() {
+		assert_eq!(keccak(key_pair(NODE_A_KEY).public()), H256(NODE_A_ID));
+		assert_eq!(keccak(key_pair(NODE_B_KEY).public()), H256(NODE_B_ID));
+	}
+
+	#[test]
+	fn vector_ping_message_packet() {
+		let key_a = key_pair(NODE_A_KEY);
+		let key_b = key_pair(NODE_B_KEY);
+		let id_a = H256(NODE_A_ID);
+		let id_b = H256(NODE_B_ID);
+		let read_key = [0u8; KEY_SIZE];
+		let packet = hex!("
+		00000000000000000000000000000000088b3d4342774649325f313964a39e55
+		ea96c005ad52be8c7560413a7008f16c9e6d2f43bbea8814a546b7409ce783d3
+		4c4f53245d08dab84102ed931f66d1492acb308fa1c6715b9d139b81acbdcc
+		");
+
+		let header = vector_header(FLAG_MESSAGE, &VECTOR_NONCE, id_a.as_bytes());
+		assert_eq!(seal_packet(&id_b, &header, Some((&read_key, &vector_ping(2)))).unwrap(), packet.to_vec());
+
+		let decoded = decode_packet(&id_b, &packet).unwrap();
+		assert_eq!(decoded.flag, FLAG_MESSAGE);
+		assert_eq!(decoded.nonce, VECTOR_NONCE);
+		assert_eq!(decoded.authdata(), id_a.as_bytes());
+		assert_eq!(decrypt_message(&read_key, &decoded.nonce, &decoded.message, &decoded.header).unwrap(), vector_ping(2));
+
+		// Node B answers with a pong within the session.
+		let ep_a = endpoint("127.0.0.1:40651");
+		let mut discovery_b = discovery(&key_b, &endpoint("127.0.0.1:40652"), None, None);
+		let contact_a = Contact::from_entry(&NodeEntry { id: *key_a.public(), endpoint: ep_a.clone() }).unwrap();
+		discovery_b.sessions.insert(id_a, Session { contact: contact_a, encryption_key: rand::random(), decryption_key: read_key });
+		assert!(discovery_b.on_packet(&packet, ep_a.udp_address()).unwrap().is_none());
+		let pong = discovery_b.dequeue_send().unwrap();
+		assert_eq!(pong.address, ep_a.udp_address());
+		assert_eq!(decode_packet(&id_a, &pong.payload).unwrap().flag, FLAG_MESSAGE);
+	}
+
+	#[test]
+	fn vector_whoareyou_packet() {
+		let id_b = H256(NODE_B_ID);
+		let packet = hex!("
+		00000000000000000000000000000000088b3d434277464933a1ccc59f5967ad
+		1d6035f15e528627dde75cd68292f9e6c27d6b66c8100a873fcbaed4e16b8d
+		");
+		let challenge_data = vector_challenge_data(0);
+
+		assert_eq!(seal_packet(&id_b, &challenge_data, None).unwrap(), packet.to_vec());
+
+		let decoded = decode_packet(&id_b, &packet).unwrap();
+		assert_eq!(decoded.flag, FLAG_WHOAREYOU);
+		assert_eq!(decoded.nonce, hex!("0102030405060708090a0b0c"));
+		assert_eq!(decoded.authdata(), &hex!("0102030405060708090a0b0c0d0e0f10 0000000000000000")[..]);
+		assert_eq!(decoded.header, challenge_data);
+		assert!(decoded.message.is_empty());
+	}
+
+	#[test]
+	fn vector_ping_handshake_packet() {
+		check_vector_handshake(1, &[], hex!("4f9fac6de7567d1e3b1241dffe90f662"), &hex!("
+		00000000000000000000000000000000088b3d4342774649305f313964a39e55
+		ea96c005ad521d8c7560413a7008f16c9e6d2f43bbea8814a546b7409ce783d3
+		4c4f53245d08da4bb252012b2cba3f4f374a90a75cff91f142fa9be3e0a5f3ef
+		268ccb9065aeecfd67a999e7fdc137e062b2ec4a0eb92947f0d9a74bfbf44dfb
+		a776b21301f8b65efd5796706adff216ab862a9186875f9494150c4ae06fa4d1
+		f0396c93f215fa4ef524f1eadf5f0f4126b79336671cbcf7a885b1f8bd2a5d83
+		9cf8
+		"));
+	}
+
+	#[test]
+	fn vector_ping_handshake_packet_with_record() {
+		// Record of node A with sequence number 1, sent since the challenge asks for a newer one than 0.
+		let record = hex!("
+		f87db84017e1b073918da32d640642c762c0e2781698e4971f8ab39a77746adad83f01e76ffc874c5924808bbe7c5089
+		0882c2b8a01287a0b08312d1d53a17d517f5eb2701826964827634826970847f00000189736563703235366b31a103
+		13d14211e0287b2361a1615890a9b5212080546d0a257ae4cff96cf534992cb9
+		");
+		check_vector_handshake(0, &record, hex!("53b1c075f41876423154e157470c2f48"), &hex!("
+		00000000000000000000000000000000088b3d4342774649305f313964a39e55
+		ea96c005ad539c8c7560413a7008f16c9e6d2f43bbea8814a546b7409ce783d3
+		4c4f53245d08da4bb23698868350aaad22e3ab8dd034f548a1c43cd246be9856
+		2fafa0a1fa86d8e7a3b95ae78cc2b988ded6a5b59eb83ad58097252188b902b2
+		1481e30e5e285f19735796706adff216ab862a9186875f9494150c4ae06fa4d1
+		f0396c93f215fa4ef524e0ed04c3c21e39b1868e1ca8105e585ec17315e755e6
+		cfc4dd6cb7fd8e1a1f55e49b4b5eb024221482105346f3c82b15fdaae36a3bb1
+		2a494683b4a3c7f2ae41306252fed84785e2bbff3b022812d0882f06978df84a
+		80d443972213342d04b9048fc3b1d5fcb1df0f822152eced6da4d3f6df27e70e
+		4539717307a0208cd208d65093ccab5aa596a34d7511401987662d8cf62b1394
+		71
+		"));
+	}
+
+	#[test]
+	fn vector_ecdh() {
+		let secp = Secp256k1::new();
+		let public = PublicKey::from_slice(&hex!("039961e4c2356d61bedb83052c115d311acb3a96f5777296dcf297351130266231")).unwrap();
+		let secret = SecretKey::from_slice(&hex!("fb757dc581730490a1d7a00deea65e9b1936924caaea8f44d476014856b68736")).unwrap();
+		assert_eq!(
+			&ecdh(&secp, &public, &secret).unwrap()[..],
+			&hex!("033b11a2a1f214567e1537ce5e509ffd9b21373247f2a3ff6841f4976f53165e7e")[..],
+		);
+	}
+
+	#[test]
+	fn vector_key_derivation() {
+		let secp = Secp256k1::new();
+		let ephemeral_secret = SecretKey::from_slice(&hex!("fb757dc581730490a1d7a00deea65e9b1936924caaea8f44d476014856b68736")).unwrap();
+		let dest_public = PublicKey::from_slice(&hex!("0317931e6e0840220642f230037d285d122bc59063221ef3226b1f403ddc69ca91")).unwrap();
+		let secret = ecdh(&secp, &dest_public, &ephemeral_secret).unwrap();
+
+		let (initiator_key, recipient_key) = derive_keys(&secret, &vector_challenge_data(0), &H256(NODE_A_ID), &H256(NODE_B_ID));
+		assert_eq!(initiator_key, hex!("dccc82d81bd610f4f76d3ebe97a40571"));
+		assert_eq!(recipient_key, hex!("ac74bb8773749920b0d3a8881c173ec5"));
+	}
+
+	#[test]
+	fn vector_id_nonce_signature() {
+		let secp = Secp256k1::new();
+		let static_key = SecretKey::from_slice(&hex!("fb757dc581730490a1d7a00deea65e9b1936924caaea8f44d476014856b68736")).unwrap();
+		let ephemeral_key = hex!("039961e4c2356d61bedb83052c115d311acb3a96f5777296dcf297351130266231");
+		let message = id_signature_hash(&vector_challenge_data(0), &ephemeral_key, &H256(NODE_B_ID));
+		let signature = hex!("
+		94852a1e2318c4e5e9d422c98eaf19d1d90d876b29cd06ca7cb7546d0fff7b48
+		4fe86c09a064fe72bdbef73ba8e9c34df0cd2b53e9d65528c2c7f336d5dfc6e6
+		");
+
+		assert_eq!(&secp.sign(&message, &static_key).serialize_compact()[..], &signature[..]);
+		let public = PublicKey::from_secret_key(&secp, &static_key);
+		assert!(secp.verify(&message, &Signature::from_compact(&signature).unwrap(), &public).is_ok());
+	}
+
+	#[test]
+	fn vector_message_encryption() {
+		let key = hex!("9f2d77db7004bf8a1a85107ac686990b");
+		let nonce = hex!("27b5af763c446acd2749fe8e");
+		let ad = hex!("93a7400fa0d6a694ebc24d5cf570f65d04215b6ac00757875e3f3a5f42107903");
+		let ciphertext = hex!("a5d12a2d94b8ccb3ba55558229867dc13bfa3648");
+
+		assert_eq!(encrypt_message(&key, &nonce, &hex!("01c20101"), &ad), ciphertext.to_vec());
+		assert_eq!(decrypt_message(&key, &nonce, &ciphertext, &ad).unwrap(), hex!("01c20101").to_vec());
+	}
+
+	#[test]
+	fn recognises_packets_on_shared_socket() {
+		let key = Random.generate();
+		let discovery = discovery(&key, &endpoint("127.0.0.1:40601"), None, None);
+
+		let header = encode_header(FLAG_MESSAGE, &rand::random(), H256::random().as_bytes());
+		let packet = seal_packet(&discovery.id, &header, Some((&rand::random(), &b"ping"[..]))).unwrap();
+		assert!(discovery.is_v5_packet(&packet));
+		let packet = seal_packet(&H256::random(), &header, Some((&rand::random(), &b"ping"[..]))).unwrap();
+		assert!(!discovery.is_v5_packet(&packet));
+		// Discovery v4 packets start with a hash and a signature.
+		assert!(!discovery.is_v5_packet(&[0u8; 32 + 65 + 10]));
+	}
+
+	#[test]
+	fn log_distances() {
+		let a = H256::zero();
+		assert_eq!(log_distance(&a, &a), None);
+		assert_eq!(log_distance(&a, &H256::from_low_u64_be(1)), Some(1));
+		assert_eq!(log_distance(&a, &H256::from_low_u64_be(0x80)), Some(8));
+		let mut b = H256::zero();
+		b.as_bytes_mut()[0] = 0x40;
+		assert_eq!(log_distance(&a, &b), Some(255));
+	}
+
+	#[test]
+	fn handshake() {
+		let key1 = Random.generate();
+		let key2 = Random.generate();
+		let ep1 = endpoint("127.0.0.1:40611");
+		let ep2 = endpoint("127.0.0.1:40612");
+		let mut discovery1 = discovery(&key1, &ep1, None, None);
+		let mut discovery2 = discovery(&key2, &ep2, None, None);
+
+		let (updates1, updates2) = connect(&mut discovery1, &ep1, &key2, &mut discovery2, &ep2);
+		let updates1 = updates1.expect("discovery2 is reported to the node table");
+		assert_eq!(updates1.added.len(), 1);
+		assert_eq!(updates1.added[key2.public()].endpoint, ep2);
+		let updates2 = updates2.expect("discovery1 is reported to the node table");
+		assert_eq!(updates2.added[key1.public()].endpoint, ep1);
+
+		// With a session established, requests are answered directly.
+		discovery1.send_ping(Contact::with_address(discovery2.enr.clone(), ep2.udp_address())).unwrap();
+		let ping = discovery1.dequeue_send().unwrap();
+		assert!(discovery2.on_packet(&ping.payload, ep1.udp_address()).unwrap().is_none());
+		let pong = discovery2.dequeue_send().unwrap();
+		assert_eq!(decode_packet(&discovery1.id, &pong.payload).unwrap().flag, FLAG_MESSAGE);
+		assert!(discovery1.on_packet(&pong.payload, ep2.udp_address()).unwrap().is_none());
+		assert!(discovery1.requests.is_empty());
+	}
+
+	#[test]
+	fn rejects_invalid_id_signature() {
+		let key1 = Random.generate();
+		let key2 = Random.generate();
+		let ep1 = endpoint("127.0.0.1:40621");
+		let ep2 = endpoint("127.0.0.1:40622");
+		let mut discovery1 = discovery(&key1, &ep1, None, None);
+		let mut discovery2 = discovery(&key2, &ep2, None, None);
+
+		discovery1.add_node(NodeEntry { id: *key2.public(), endpoint: ep2.clone() });
+		discovery1.round();
+		let ping = discovery1.dequeue_send().unwrap();
+		discovery2.on_packet(&ping.payload, ep1.udp_address()).unwrap();
+		let whoareyou = discovery2.dequeue_send().unwrap();
+		discovery1.on_packet(&whoareyou.payload, ep2.udp_address()).unwrap();
+		let mut handshake = discovery1.dequeue_send().unwrap().payload;
+
+		// Flip a bit of the signature; the header is masked in counter mode so the bit flips in the plain header too.
+		handshake[AUTHDATA_OFFSET + 34] ^= 1;
+		assert!(discovery2.on_packet(&handshake, ep1.udp_address()).is_err());
+		assert!(!discovery2.any_sends_queued());
+	}
+
+	#[test]
+	fn lookup_finds_nodes_of_peers() {
+		let key1 = Random.generate();
+		let key2 = Random.generate();
+		let key3 = Random.generate();
+		let ep1 = endpoint("127.0.0.1:40631");
+		let ep2 = endpoint("127.0.0.1:40632");
+		let ep3 = endpoint("127.0.0.1:40633");
+		let mut discovery1 = discovery(&key1, &ep1, None, None);
+		let mut discovery2 = discovery(&key2, &ep2, None, None);
+		let mut discovery3 = discovery(&key3, &ep3, None, None);
+
+		connect(&mut discovery2, &ep2, &key3, &mut discovery3, &ep3);
+		connect(&mut discovery1, &ep1, &key2, &mut discovery2, &ep2);
+
+		// Look up `discovery3` through `discovery2`.
+		discovery1.lookup = Some(Lookup { target: discovery3.id, round: 0, asked: HashSet::new() });
+		discovery1.round();
+		let find_node = discovery1.dequeue_send().unwrap();
+		assert_eq!(find_node.address, ep2.udp_address());
+		assert!(discovery2.on_packet(&find_node.payload, ep1.udp_address()).unwrap().is_none());
+		let nodes = discovery2.dequeue_send().unwrap();
+		assert!(discovery1.on_packet(&nodes.payload, ep2.udp_address()).unwrap().is_none());
+
+		// The new node is pinged before it is added.
+		assert_eq!(discovery1.adding_nodes.len(), 1);
+		assert_eq!(discovery1.adding_nodes[0].id, discovery3.id);
+	}
+
+	#[test]
+	fn filters_nodes_by_record() {
+		let fork_hash = [0xfc, 0x64, 0xec, 0x04];
+		let eth_filter = |value: Option<Vec<u8>>| Some(EnrFilter { key: "eth".into(), value });
+		let key1 = Random.generate();
+		let key2 = Random.generate();
+		let ep1 = endpoint("127.0.0.1:40641");
+		let ep2 = endpoint("127.0.0.1:40642");
+
+		// Matching fork hash.
+		let mut discovery1 = discovery(&key1, &ep1, None, eth_filter(Some(fork_hash.to_vec())));
+		let mut discovery2 = discovery(&key2, &ep2, Some(fork_id_entry(fork_hash, 1_150_000)), None);
+		let (updates, _) = connect(&mut discovery1, &ep1, &key2, &mut discovery2, &ep2);
+		assert!(updates.unwrap().added.contains_key(key2.public()));
+
+		// Another fork.
+		let mut discovery1 = discovery(&key1, &ep1, None, eth_filter(Some(fork_hash.to_vec())));
+		let mut discovery2 = discovery(&key2, &ep2, Some(fork_id_entry([0x97, 0xc2, 0xc3, 0x4c], 0)), None);
+		let (updates, _) = connect(&mut discovery1, &ep1, &key2, &mut discovery2, &ep2);
+		assert!(updates.is_none());
+		// The node stays in the discovery table to be used in lookups.
+		assert!(discovery1.in_bucket(&discovery2.id));
+
+		// No `eth` entry at all.
+		let mut discovery1 = discovery(&key1, &ep1, None, eth_filter(None));
+		let mut discovery2 = discovery(&key2, &ep2, None, None);
+		let (updates, _) = connect(&mut discovery1, &ep1, &key2, &mut discovery2, &ep2);
+		assert!(updates.is_none());
+	}
+
+	#[test]
+	fn enr_filter_matches_nested_values() {
+		let filter = EnrFilter { key: "eth".into(), value: Some(vec![0xfc, 0x64, 0xec, 0x04]) };
+		assert!(filter.matches(Some(&fork_id_entry([0xfc, 0x64, 0xec, 0x04], 0))));
+		assert!(filter.matches(Some(&[0xfc, 0x64, 0xec, 0x04])));
+		assert!(!filter.matches(Some(&fork_id_entry([0x97, 0xc2, 0xc3, 0x4c], 0))));
+		assert!(!filter.matches(None));
+
+		let any = EnrFilter { key: "eth".into(), value: None };
+		assert!(any.matches(Some(&[])));
+		assert!(!any.matches(None));
+	}
+}
@@ -0,0 +1,862 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! DNS node lists (EIP-1459).
+//!
+//! A node list is a Merkle tree of node records published as TXT records under a domain and
+//! referred to by an `enrtree://<public key>@<domain>` URL. The root entry at the domain itself
+//! is signed by the key of the URL. Every other entry is published under the hash of its content,
+//! so a resolved entry is checked against the name it was found at. Links to the lists of other
+//! domains are kept in a subtree of their own.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream, UdpSocket};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use keccak_hash::keccak;
+use log::{debug, trace};
+use parity_crypto::publickey::{recover, sign, KeyPair, Public, Secret, Signature};
+use secp256k1::PublicKey;
+use network::{Error, NetworkConfiguration};
+
+use crate::discovery::NodeEntry;
+use crate::node_record::{Enr, EnrManager};
+use crate::node_table::{NodeEndpoint, NodeId, NodeTable};
+use crate::persistence::load;
+
+const ROOT_PREFIX: &str = "enrtree-root:v1";
+const BRANCH_PREFIX: &str = "enrtree-branch:";
+const LINK_PREFIX: &str = "enrtree://";
+const RECORD_PREFIX: &str = "enr:";
+/// Entries are published under the first bytes of their hash.
+const HASH_SIZE: usize = 16;
+/// Branches hold as many hashes as fit a TXT record of 370 bytes.
+const MAX_CHILDREN: usize = 370 / (26 + 1);
+/// How often the root of a list is checked for a new version.
+const ROOT_RECHECK: Duration = Duration::from_secs(30 * 60);
+/// Entries resolved per list in one round.
+const LOOKUPS_PER_ROUND: usize = 16;
+/// Lists reached through links are followed up to this number of lists.
+const MAX_LISTS: usize = 64;
+
+const DNS_PORT: u16 = 53;
+const DNS_TIMEOUT: Duration = Duration::from_secs(2);
+const DNS_TYPE_TXT: u16 = 16;
+const DNS_CLASS_IN: u16 = 1;
+const DNS_FLAG_TRUNCATED: u16 = 0x0200;
+const DNS_RCODE_NAME_ERROR: u16 = 3;
+const RESOLV_CONF: &str = "/etc/resolv.conf";
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Source of DNS TXT records.
+pub trait TxtResolver: Send {
+	/// Returns the TXT records at `name`, each with its character strings joined.
+	fn resolve_txt(&self, name: &str) -> Result<Vec<String>, Error>;
+}
+
+/// Resolver querying the name servers of the system configuration.
+pub struct SystemResolver {
+	servers: Vec<SocketAddr>,
+}
+
+impl SystemResolver {
+	pub fn new() -> Self {
+		let servers = fs::read_to_string(RESOLV_CONF).map(|conf| name_servers(&conf)).unwrap_or_default();
+		if servers.is_empty() {
+			debug!(target: "discovery", "No name servers configured, using the local one");
+			return SystemResolver { servers: vec![SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), DNS_PORT)] };
+		}
+		SystemResolver { servers }
+	}
+
+	fn query(&self, server: &SocketAddr, name: &str) -> Result<Vec<String>, Error> {
+		let id = rand::random::<u16>();
+		let query = dns_query(id, name)?;
+		let socket = UdpSocket::bind(if server.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" })?;
+		socket.send_to(&query, server)?;
+		let mut buf = [0u8; 4096];
+		// Datagrams that aren't the answer don't extend the wait for it.
+		let deadline = Instant::now() + DNS_TIMEOUT;
+		let len = loop {
+			let now = Instant::now();
+			if now >= deadline {
+				return Err(io::Error::new(io::ErrorKind::TimedOut, "no answer from the name server").into());
+			}
+			socket.set_read_timeout(Some(deadline - now))?;
+			let (len, from) = socket.recv_from(&mut buf)?;
+			if from == *server && buf[..len].starts_with(&id.to_be_bytes()) {
+				break len;
+			}
+		};
+		match parse_txt_response(id, &buf[..len])? {
+			DnsResponse::Records(records) => Ok(records),
+			DnsResponse::Truncated => self.query_tcp(server, id, &query),
+		}
+	}
+
+	/// Repeats a query over TCP, for answers that don't fit a datagram.
+	fn query_tcp(&self, server: &SocketAddr, id: u16, query: &[u8]) -> Result<Vec<String>, Error> {
+		let mut stream = TcpStream::connect_timeout(server, DNS_TIMEOUT)?;
+		stream.set_read_timeout(Some(DNS_TIMEOUT))?;
+		stream.set_write_timeout(Some(DNS_TIMEOUT))?;
+		let mut framed = Vec::with_capacity(2 + query.len());
+		framed.extend_from_slice(&(query.len() as u16).to_be_bytes());
+		framed.extend_from_slice(query);
+		stream.write_all(&framed)?;
+		let mut len = [0u8; 2];
+		stream.read_exact(&mut len)?;
+		let mut response = vec![0u8; u16::from_be_bytes(len) as usize];
+		stream.read_exact(&mut response)?;
+		match parse_txt_response(id, &response)? {
+			DnsResponse::Records(records) => Ok(records),
+			DnsResponse::Truncated => Err(Error::InvalidNodeList("truncated DNS response".into())),
+		}
+	}
+}
+
+impl TxtResolver for SystemResolver {
+	fn resolve_txt(&self, name: &str) -> Result<Vec<String>, Error> {
+		let mut result = Err(Error::InvalidNodeList("no name server".into()));
+		for server in &self.servers {
+			result = self.query(server, name);
+			if result.is_ok() {
+				break;
+			}
+			trace!(target: "discovery", "Error resolving {} at {}: {:?}", name, server, result);
+		}
+		result
+	}
+}
+
+/// Reference to a node list: the domain it's published under and the key its root is signed with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Link {
+	domain: String,
+	/// Compressed public key.
+	key: Vec<u8>,
+}
+
+impl Link {
+	fn new(domain: &str, key: &Public) -> Result<Self, Error> {
+		Ok(Link { domain: domain.to_owned(), key: compress(key)? })
+	}
+
+	fn url(&self) -> String {
+		format!("{}{}@{}", LINK_PREFIX, base32_encode(&self.key), self.domain)
+	}
+}
+
+impl FromStr for Link {
+	type Err = Error;
+
+	fn from_str(url: &str) -> Result<Self, Error> {
+		let invalid = || Error::InvalidNodeList(format!("invalid URL {}", url));
+		if !url.starts_with(LINK_PREFIX) {
+			return Err(invalid());
+		}
+		let mut parts = url[LINK_PREFIX.len()..].splitn(2, '@');
+		let key = parts.next().and_then(base32_decode).ok_or_else(invalid)?;
+		let domain = parts.next().filter(|domain| !domain.is_empty()).ok_or_else(invalid)?;
+		PublicKey::from_slice(&key).map_err(|_| invalid())?;
+		Ok(Link { domain: domain.to_owned(), key })
+	}
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Root {
+	enr_root: String,
+	link_root: String,
+	seq: u64,
+	signature: Vec<u8>,
+}
+
+impl Root {
+	fn signed_content(&self) -> String {
+		format!("{} e={} l={} seq={}", ROOT_PREFIX, self.enr_root, self.link_root, self.seq)
+	}
+
+	fn verify(&self, key: &[u8]) -> bool {
+		if self.signature.len() != 65 {
+			return false;
+		}
+		let mut signature = [0u8; 65];
+		signature.copy_from_slice(&self.signature);
+		recover(&Signature::from(signature), &keccak(self.signed_content()))
+			.ok()
+			.and_then(|public| compress(&public).ok())
+			.map_or(false, |public| public == key)
+	}
+
+	fn parse(content: &str) -> Result<Self, Error> {
+		let invalid = || Error::InvalidNodeList(format!("invalid root {}", content));
+		let (mut enr_root, mut link_root, mut seq, mut signature) = (None, None, None, None);
+		for field in content[ROOT_PREFIX.len()..].split_whitespace() {
+			let mut parts = field.splitn(2, '=');
+			match (parts.next(), parts.next()) {
+				(Some("e"), Some(hash)) => enr_root = Some(parse_hash(hash).ok_or_else(invalid)?),
+				(Some("l"), Some(hash)) => link_root = Some(parse_hash(hash).ok_or_else(invalid)?),
+				(Some("seq"), Some(value)) => seq = Some(value.parse().map_err(|_| invalid())?),
+				(Some("sig"), Some(value)) => signature = Some(
+					base64::decode_config(value, base64::URL_SAFE_NO_PAD).map_err(|_| invalid())?
+				),
+				_ => return Err(invalid()),
+			}
+		}
+		Ok(Root {
+			enr_root: enr_root.ok_or_else(invalid)?,
+			link_root: link_root.ok_or_else(invalid)?,
+			seq: seq.ok_or_else(invalid)?,
+			signature: signature.ok_or_else(invalid)?,
+		})
+	}
+}
+
+#[derive(Debug)]
+enum Entry {
+	Root(Root),
+	Branch(Vec<String>),
+	Link(Link),
+	Record(Enr),
+}
+
+impl FromStr for Entry {
+	type Err = Error;
+
+	fn from_str(content: &str) -> Result<Self, Error> {
+		if content.starts_with(ROOT_PREFIX) {
+			Root::parse(content).map(Entry::Root)
+		} else if content.starts_with(BRANCH_PREFIX) {
+			let hashes = &content[BRANCH_PREFIX.len()..];
+			if hashes.is_empty() {
+				return Ok(Entry::Branch(Vec::new()));
+			}
+			hashes.split(',')
+				.map(|hash| parse_hash(hash).ok_or_else(|| Error::InvalidNodeList(format!("invalid branch {}", content))))
+				.collect::<Result<_, _>>()
+				.map(Entry::Branch)
+		} else if content.starts_with(LINK_PREFIX) {
+			content.parse().map(Entry::Link)
+		} else if content.starts_with(RECORD_PREFIX) {
+			content.parse().map(Entry::Record).map_err(|e| Error::InvalidNodeList(format!("invalid record: {}", e)))
+		} else {
+			Err(Error::InvalidNodeList(format!("unknown entry {}", content)))
+		}
+	}
+}
+
+impl fmt::Display for Entry {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Entry::Root(root) => write!(
+				f, "{} sig={}", root.signed_content(), base64::encode_config(&root.signature, base64::URL_SAFE_NO_PAD)
+			),
+			Entry::Branch(hashes) => write!(f, "{}{}", BRANCH_PREFIX, hashes.join(",")),
+			Entry::Link(link) => write!(f, "{}", link.url()),
+			Entry::Record(enr) => write!(f, "{}", enr.to_base64()),
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Subtree {
+	Records,
+	Links,
+}
+
+/// Sync state of one node list.
+struct ListSync {
+	link: Link,
+	root: Option<Root>,
+	root_checked: Option<Instant>,
+	/// Hashes of the entries left to resolve.
+	pending: VecDeque<(String, Subtree)>,
+}
+
+impl ListSync {
+	fn new(link: Link) -> Self {
+		ListSync { link, root: None, root_checked: None, pending: VecDeque::new() }
+	}
+
+	/// Resolve the next entries, starting over if the root changed.
+	fn sync(&mut self, resolver: &dyn TxtResolver, records: &mut Vec<Enr>, links: &mut Vec<Link>) -> Result<(), Error> {
+		let now = Instant::now();
+		if self.root_checked.map_or(true, |checked| checked + ROOT_RECHECK <= now) {
+			self.root_checked = Some(now);
+			let root = self.resolve_root(resolver)?;
+			if self.root.as_ref() != Some(&root) {
+				trace!(target: "discovery", "Syncing node list {} at seq {}", self.link.domain, root.seq);
+				self.pending.clear();
+				self.pending.push_back((root.enr_root.clone(), Subtree::Records));
+				self.pending.push_back((root.link_root.clone(), Subtree::Links));
+				self.root = Some(root);
+			}
+		}
+
+		for _ in 0..LOOKUPS_PER_ROUND {
+			let (hash, subtree) = match self.pending.pop_front() {
+				Some(next) => next,
+				None => break,
+			};
+			let entry = match self.resolve_entry(resolver, &hash) {
+				Ok(entry) => entry,
+				Err(Error::InvalidNodeList(e)) => {
+					debug!(target: "discovery", "Skipping entry {} of node list {}: {}", hash, self.link.domain, e);
+					continue;
+				}
+				Err(e) => {
+					// Try again next round.
+					self.pending.push_front((hash, subtree));
+					return Err(e);
+				}
+			};
+			match (entry, subtree) {
+				(Entry::Branch(children), _) => self.pending.extend(children.into_iter().map(|hash| (hash, subtree))),
+				(Entry::Record(enr), Subtree::Records) => records.push(enr),
+				(Entry::Link(link), Subtree::Links) => links.push(link),
+				(entry, _) => debug!(target: "discovery", "Unexpected entry in node list {}: {}", self.link.domain, entry),
+			}
+		}
+		Ok(())
+	}
+
+	fn resolve_root(&self, resolver: &dyn TxtResolver) -> Result<Root, Error> {
+		let content = resolver.resolve_txt(&self.link.domain)?
+			.into_iter()
+			.find(|content| content.starts_with(ROOT_PREFIX))
+			.ok_or_else(|| Error::InvalidNodeList(format!("no root at {}", self.link.domain)))?;
+		let root = Root::parse(&content)?;
+		if !root.verify(&self.link.key) {
+			return Err(Error::InvalidNodeList(format!("invalid root signature at {}", self.link.domain)));
+		}
+		Ok(root)
+	}
+
+	fn resolve_entry(&self, resolver: &dyn TxtResolver, hash: &str) -> Result<Entry, Error> {
+		let name = format!("{}.{}", hash, self.link.domain);
+		resolver.resolve_txt(&name)?
+			.into_iter()
+			.find(|content| entry_hash(content).eq_ignore_ascii_case(hash))
+			.ok_or_else(|| Error::InvalidNodeList(format!("no entry matching {}", name)))?
+			.parse()
+	}
+}
+
+/// Follows the node lists given as boot nodes and their links.
+pub struct DnsDiscovery {
+	resolver: Box<dyn TxtResolver>,
+	lists: Vec<ListSync>,
+}
+
+impl DnsDiscovery {
+	pub fn new(urls: &[String], resolver: Box<dyn TxtResolver>) -> Result<Self, Error> {
+		let mut discovery = DnsDiscovery { resolver, lists: Vec::new() };
+		for url in urls {
+			discovery.add_list(url.parse()?);
+		}
+		Ok(discovery)
+	}
+
+	fn add_list(&mut self, link: Link) {
+		if self.lists.len() < MAX_LISTS && !self.lists.iter().any(|list| list.link == link) {
+			self.lists.push(ListSync::new(link));
+		}
+	}
+
+	/// Resolve the next entries of every list. Returns the node records found.
+	pub fn round(&mut self) -> Vec<Enr> {
+		let mut records = Vec::new();
+		let mut links = Vec::new();
+		for list in &mut self.lists {
+			if let Err(e) = list.sync(&*self.resolver, &mut records, &mut links) {
+				debug!(target: "discovery", "Error syncing node list {}: {}", list.link.domain, e);
+			}
+		}
+		for link in links {
+			self.add_list(link);
+		}
+		records
+	}
+}
+
+/// Syncs node lists on a thread of its own, since resolving blocks until the name servers answer.
+/// The thread stops when this is dropped.
+pub struct DnsDiscoveryThread {
+	records: mpsc::Receiver<Vec<Enr>>,
+	_stop: mpsc::Sender<()>,
+}
+
+impl DnsDiscoveryThread {
+	/// Starts a thread running a round of `discovery` every `interval`.
+	pub fn spawn(mut discovery: DnsDiscovery, interval: Duration) -> Result<Self, Error> {
+		let (records_tx, records) = mpsc::channel();
+		let (stop, stop_rx) = mpsc::channel::<()>();
+		thread::Builder::new()
+			.name("dns-discovery".into())
+			.spawn(move || loop {
+				let found = discovery.round();
+				if !found.is_empty() && records_tx.send(found).is_err() {
+					break;
+				}
+				match stop_rx.recv_timeout(interval) {
+					Err(mpsc::RecvTimeoutError::Timeout) => {},
+					_ => break,
+				}
+			})?;
+		Ok(DnsDiscoveryThread { records, _stop: stop })
+	}
+
+	/// Node records found since the last call.
+	pub fn records(&self) -> Vec<Enr> {
+		self.records.try_iter().flatten().collect()
+	}
+}
+
+/// Signed node list, ready to be published.
+pub struct NodeList {
+	/// URL of the list, to be used as a boot node.
+	pub url: String,
+	/// TXT records by domain name, the root first.
+	pub records: Vec<(String, String)>,
+}
+
+/// Build the node list of `records` and links to other lists, to be published under `domain`.
+pub fn build_node_list(key: &KeyPair, domain: &str, seq: u64, records: &[Enr], links: &[String]) -> Result<NodeList, Error> {
+	let mut records = records.iter().map(Enr::to_base64).collect::<Vec<_>>();
+	records.sort();
+	records.dedup();
+	let links = links.iter().map(|url| url.parse().map(|link: Link| link.url())).collect::<Result<Vec<_>, _>>()?;
+
+	let mut entries = Vec::new();
+	let enr_root = build_subtree(records, &mut entries);
+	let link_root = build_subtree(links, &mut entries);
+	let mut root = Root { enr_root, link_root, seq, signature: Vec::new() };
+	root.signature = sign(key.secret(), &keccak(root.signed_content()))?.to_vec();
+
+	let mut txt = vec![(domain.to_owned(), Entry::Root(root).to_string())];
+	txt.extend(entries.into_iter().map(|(hash, content)| (format!("{}.{}", hash, domain), content)));
+	Ok(NodeList { url: Link::new(domain, key.public())?.url(), records: txt })
+}
+
+/// Build the node list of the records in the node table at `config.net_config_path` and our own,
+/// signed with the node key.
+///
+/// Only nodes found through discovery v5 or node lists have a record in the table; nodes found
+/// through discovery v4 are left out. Fails if the table has no records at all.
+pub fn export_node_list(config: &NetworkConfiguration, domain: &str, seq: u64) -> Result<NodeList, Error> {
+	let secret = match config.use_secret {
+		Some(ref secret) => Some(secret.clone()),
+		None => config.config_path.as_ref().and_then(|path| load::<Secret>(Path::new(path))),
+	};
+	let key = KeyPair::from_secret(secret.ok_or_else(|| Error::InvalidNodeList("no node key".into()))?)?;
+	let mut records = NodeTable::load_records(config.net_config_path.clone());
+	if records.is_empty() {
+		return Err(Error::InvalidNodeList("the node table has no node records; records are only learned through discovery v5 or node lists".into()));
+	}
+	if let Some(enr) = config.config_path.as_ref().and_then(|path| EnrManager::load(path.as_str(), key.secret().clone())) {
+		records.push(enr.as_enr().clone());
+	}
+	build_node_list(&key, domain, seq, &records, &[])
+}
+
+/// Check if a boot node is a node list URL.
+pub fn is_node_list_url(url: &str) -> bool {
+	url.starts_with(LINK_PREFIX)
+}
+
+/// Check if node list URL is valid
+pub fn validate_node_list_url(url: &str) -> Option<Error> {
+	url.parse::<Link>().err()
+}
+
+/// Endpoint of a node record, for records announcing a TCP port.
+pub fn record_entry(enr: &Enr) -> Option<NodeEntry> {
+	let tcp_port = enr.tcp()?;
+	let address = SocketAddr::new(IpAddr::V4(enr.ip()?), tcp_port);
+	Some(NodeEntry {
+		id: NodeId::from_slice(&enr.public_key().serialize_uncompressed()[1..]),
+		endpoint: NodeEndpoint { address, udp_port: enr.udp().unwrap_or(tcp_port) },
+	})
+}
+
+/// Adds the entries of a subtree and returns the hash of its top entry.
+fn build_subtree(leaves: Vec<String>, entries: &mut Vec<(String, String)>) -> String {
+	let mut add_entry = |content: String| {
+		let hash = entry_hash(&content);
+		entries.push((hash.clone(), content));
+		hash
+	};
+	let mut level = leaves.into_iter().map(&mut add_entry).collect::<Vec<_>>();
+	while level.len() > MAX_CHILDREN {
+		level = level.chunks(MAX_CHILDREN).map(|hashes| add_entry(format!("{}{}", BRANCH_PREFIX, hashes.join(",")))).collect();
+	}
+	if level.len() == 1 {
+		level.remove(0)
+	} else {
+		add_entry(format!("{}{}", BRANCH_PREFIX, level.join(",")))
+	}
+}
+
+fn entry_hash(content: &str) -> String {
+	base32_encode(&keccak(content).as_bytes()[..HASH_SIZE])
+}
+
+fn parse_hash(hash: &str) -> Option<String> {
+	base32_decode(hash).filter(|bytes| bytes.len() == HASH_SIZE).map(|_| hash.to_owned())
+}
+
+fn compress(public: &Public) -> Result<Vec<u8>, Error> {
+	let mut serialized = [4u8; 65];
+	serialized[1..].copy_from_slice(public.as_bytes());
+	let key = PublicKey::from_slice(&serialized).map_err(|_| Error::InvalidNodeId)?;
+	Ok(key.serialize().to_vec())
+}
+
+/// Base32 without padding, as used for hashes and keys.
+fn base32_encode(data: &[u8]) -> String {
+	let mut encoded = String::with_capacity((data.len() * 8 + 4) / 5);
+	let (mut buffer, mut bits) = (0u32, 0);
+	for &byte in data {
+		buffer = (buffer << 8) | byte as u32;
+		bits += 8;
+		while bits >= 5 {
+			bits -= 5;
+			encoded.push(BASE32_ALPHABET[((buffer >> bits) & 31) as usize] as char);
+		}
+	}
+	if bits > 0 {
+		encoded.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+	}
+	encoded
+}
+
+/// Decodes base32 without padding. Domain names are case insensitive, so is the decoding.
+fn base32_decode(encoded: &str) -> Option<Vec<u8>> {
+	let mut data = Vec::with_capacity(encoded.len() * 5 / 8);
+	let (mut buffer, mut bits) = (0u32, 0);
+	for c in encoded.bytes() {
+		let value = BASE32_ALPHABET.iter().position(|&a| a == c.to_ascii_uppercase())?;
+		buffer = (buffer << 5) | value as u32;
+		bits += 5;
+		if bits >= 8 {
+			bits -= 8;
+			data.push((buffer >> bits) as u8);
+		}
+	}
+	Some(data)
+}
+
+fn name_servers(conf: &str) -> Vec<SocketAddr> {
+	conf.lines()
+		.filter_map(|line| {
+			let mut words = line.split_whitespace();
+			match (words.next(), words.next()) {
+				(Some("nameserver"), Some(address)) => address.parse().ok().map(|ip| SocketAddr::new(ip, DNS_PORT)),
+				_ => None,
+			}
+		})
+		.collect()
+}
+
+fn dns_query(id: u16, name: &str) -> Result<Vec<u8>, Error> {
+	let mut query = Vec::with_capacity(18 + name.len());
+	query.extend_from_slice(&id.to_be_bytes());
+	// Recursion desired, one question.
+	query.extend_from_slice(&[0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+	for label in name.trim_end_matches('.').split('.') {
+		if label.is_empty() || label.len() > 63 {
+			return Err(Error::InvalidNodeList(format!("invalid domain name {}", name)));
+		}
+		query.push(label.len() as u8);
+		query.extend_from_slice(label.as_bytes());
+	}
+	query.push(0);
+	query.extend_from_slice(&DNS_TYPE_TXT.to_be_bytes());
+	query.extend_from_slice(&DNS_CLASS_IN.to_be_bytes());
+	Ok(query)
+}
+
+#[derive(Debug)]
+enum DnsResponse {
+	Records(Vec<String>),
+	Truncated,
+}
+
+fn parse_txt_response(id: u16, response: &[u8]) -> Result<DnsResponse, Error> {
+	let invalid = || Error::InvalidNodeList("malformed DNS response".into());
+	let read_u16 = |pos: usize| response.get(pos..pos + 2).map(|b| u16::from_be_bytes([b[0], b[1]])).ok_or_else(invalid);
+
+	if read_u16(0)? != id {
+		return Err(invalid());
+	}
+	let flags = read_u16(2)?;
+	if flags & DNS_FLAG_TRUNCATED != 0 {
+		return Ok(DnsResponse::Truncated);
+	}
+	match flags & 0x000f {
+		0 => {},
+		DNS_RCODE_NAME_ERROR => return Ok(DnsResponse::Records(Vec::new())),
+		code => return Err(Error::InvalidNodeList(format!("DNS error code {}", code))),
+	}
+	let questions = read_u16(4)?;
+	let answers = read_u16(6)?;
+
+	let mut pos = 12;
+	for _ in 0..questions {
+		pos = skip_name(response, pos).ok_or_else(invalid)? + 4;
+	}
+	let mut records = Vec::new();
+	for _ in 0..answers {
+		pos = skip_name(response, pos).ok_or_else(invalid)?;
+		let record_type = read_u16(pos)?;
+		let len = read_u16(pos + 8)? as usize;
+		pos += 10;
+		let data = response.get(pos..pos + len).ok_or_else(invalid)?;
+		pos += len;
+		if record_type != DNS_TYPE_TXT {
+			continue;
+		}
+		// A TXT record holds a sequence of length prefixed character strings.
+		let mut text = Vec::with_capacity(len);
+		let mut i = 0;
+		while i < data.len() {
+			let string_len = data[i] as usize;
+			text.extend_from_slice(data.get(i + 1..i + 1 + string_len).ok_or_else(invalid)?);
+			i += 1 + string_len;
+		}
+		records.push(String::from_utf8(text).map_err(|_| invalid())?);
+	}
+	Ok(DnsResponse::Records(records))
+}
+
+/// Returns the position after the domain name at `pos`.
+fn skip_name(message: &[u8], mut pos: usize) -> Option<usize> {
+	loop {
+		match *message.get(pos)? as usize {
+			0 => return Some(pos + 1),
+			// A compression pointer ends the name.
+			len if len & 0xc0 == 0xc0 => return Some(pos + 2),
+			len => pos += 1 + len,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+
+	use assert_matches::assert_matches;
+	use parity_crypto::publickey::{Generator, Random};
+
+	use super::*;
+
+	/// Resolver answering from a fixed set of records.
+	struct StubResolver {
+		records: HashMap<String, String>,
+	}
+
+	impl StubResolver {
+		fn new(lists: &[&NodeList]) -> Self {
+			StubResolver { records: lists.iter().flat_map(|list| list.records.iter().cloned()).collect() }
+		}
+	}
+
+	impl TxtResolver for StubResolver {
+		fn resolve_txt(&self, name: &str) -> Result<Vec<String>, Error> {
+			Ok(self.records.get(name).cloned().into_iter().collect())
+		}
+	}
+
+	fn record(address: &str) -> Enr {
+		let key = Random.generate();
+		let address: SocketAddr = address.parse().unwrap();
+		let endpoint = NodeEndpoint { address, udp_port: address.port() };
+		EnrManager::new(None, key.secret().clone(), 0).unwrap().with_node_endpoint(&endpoint).into_enr()
+	}
+
+	fn sync_all(discovery: &mut DnsDiscovery) -> Vec<Enr> {
+		let mut records = Vec::new();
+		for _ in 0..100 {
+			records.extend(discovery.round());
+		}
+		records.sort_by_key(|enr| enr.to_base64());
+		records
+	}
+
+	#[test]
+	fn base32() {
+		assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+		assert_eq!(base32_decode("MZXW6YTBOI").unwrap(), b"foobar");
+		assert_eq!(base32_decode("mzxw6ytboi").unwrap(), b"foobar");
+		assert!(base32_decode("MZXW6YTBO1").is_none());
+	}
+
+	#[test]
+	fn entry_hashes() {
+		// Example entries of EIP-1459.
+		assert_eq!(
+			entry_hash("enrtree-branch:2XS2367YHAXJFGLZHVAWLQD4ZY,H4FHT4B454P6UXFD7JCYQ5PWDY,MHTDO6TMUBRIA2XWG5LUDACK24"),
+			"JWXYDBPXYWG6FX3GMDIBFA6CJ4"
+		);
+		assert_eq!(
+			entry_hash("enrtree://AM5FCQLWIZX2QFPNJAP7VUERCCRNGRHWZG3YYHIUV7BVDQ5FDPRT2@morenodes.example.org"),
+			"C7HRFPF3BLGF3YR4DY5KX3SMBE"
+		);
+	}
+
+	#[test]
+	fn parse_entries() {
+		let link = "enrtree://AM5FCQLWIZX2QFPNJAP7VUERCCRNGRHWZG3YYHIUV7BVDQ5FDPRT2@morenodes.example.org";
+		let root = "enrtree-root:v1 e=JWXYDBPXYWG6FX3GMDIBFA6CJ4 l=C7HRFPF3BLGF3YR4DY5KX3SMBE seq=1 sig=o908WmNp7LibOfPsr4btQwatZJ5URBr2ZAuxvK4UWHlsB9sUOTJQaGAlLPVAhM__XJesCHxLISo94z5Z2a463gA";
+		let branch = "enrtree-branch:2XS2367YHAXJFGLZHVAWLQD4ZY,H4FHT4B454P6UXFD7JCYQ5PWDY";
+		let enr = record("127.0.0.1:30303").to_base64();
+
+		assert_matches!(link.parse::<Entry>(), Ok(Entry::Link(ref l)) if l.domain == "morenodes.example.org");
+		assert_matches!(root.parse::<Entry>(), Ok(Entry::Root(ref r)) if r.seq == 1 && r.signature.len() == 65);
+		assert_matches!(branch.parse::<Entry>(), Ok(Entry::Branch(ref hashes)) if hashes.len() == 2);
+		assert_matches!("enrtree-branch:".parse::<Entry>(), Ok(Entry::Branch(ref hashes)) if hashes.is_empty());
+		assert_matches!(enr.parse::<Entry>(), Ok(Entry::Record(_)));
+		for entry in &[link, root, branch, &enr[..]] {
+			assert_eq!(entry.parse::<Entry>().unwrap().to_string(), *entry);
+		}
+
+		assert!("enrtree-branch:2XS2367YHAXJFGLZHVAWLQD4Z".parse::<Entry>().is_err());
+		assert!("enrtree-root:v1 e=JWXYDBPXYWG6FX3GMDIBFA6CJ4 seq=1".parse::<Entry>().is_err());
+		assert!("enrtree://AM5FCQLWIZX2QFPNJAP7VUERCCRNGRHWZG3YYHIUV7BVDQ5FDPRT2".parse::<Entry>().is_err());
+		assert!("something else".parse::<Entry>().is_err());
+		assert!(validate_node_list_url(link).is_none());
+		assert!(validate_node_list_url("enrtree://AAAA@nodes.example.org").is_some());
+	}
+
+	#[test]
+	fn build_and_sync_lists() {
+		let key1 = Random.generate();
+		let key2 = Random.generate();
+		let records1 = (0..40).map(|i| record(&format!("10.0.0.{}:30303", i))).collect::<Vec<_>>();
+		let records2 = vec![record("10.0.1.1:30303")];
+		let list2 = build_node_list(&key2, "more.example.org", 1, &records2, &[]).unwrap();
+		let list1 = build_node_list(&key1, "nodes.example.org", 1, &records1, &[list2.url.clone()]).unwrap();
+		assert_eq!(list1.records[0].0, "nodes.example.org");
+		assert!(list1.records.iter().all(|(_, content)| content.len() <= 370 || content.starts_with(RECORD_PREFIX)));
+
+		let resolver = StubResolver::new(&[&list1, &list2]);
+		let mut discovery = DnsDiscovery::new(&[list1.url.clone()], Box::new(resolver)).unwrap();
+		let mut expected = records1.iter().chain(records2.iter()).cloned().collect::<Vec<_>>();
+		expected.sort_by_key(|enr| enr.to_base64());
+		assert_eq!(sync_all(&mut discovery), expected);
+
+		// Nothing new until the root changes.
+		assert!(discovery.round().is_empty());
+	}
+
+	#[test]
+	fn syncs_lists_on_own_thread() {
+		let key = Random.generate();
+		let records = (0..10).map(|i| record(&format!("10.0.0.{}:30303", i))).collect::<Vec<_>>();
+		let list = build_node_list(&key, "nodes.example.org", 1, &records, &[]).unwrap();
+		let discovery = DnsDiscovery::new(&[list.url.clone()], Box::new(StubResolver::new(&[&list]))).unwrap();
+		let thread = DnsDiscoveryThread::spawn(discovery, Duration::from_millis(1)).unwrap();
+
+		let mut found = Vec::new();
+		let deadline = Instant::now() + Duration::from_secs(10);
+		while found.len() < records.len() && Instant::now() < deadline {
+			found.extend(thread.records());
+			thread::sleep(Duration::from_millis(1));
+		}
+		let mut expected = records.clone();
+		expected.sort_by_key(|enr| enr.to_base64());
+		found.sort_by_key(|enr| enr.to_base64());
+		assert_eq!(found, expected);
+	}
+
+	#[test]
+	fn rejects_lists_with_invalid_signatures() {
+		let key = Random.generate();
+		let list = build_node_list(&key, "nodes.example.org", 1, &[record("10.0.0.1:30303")], &[]).unwrap();
+		let url = Link::new("nodes.example.org", Random.generate().public()).unwrap().url();
+		let mut discovery = DnsDiscovery::new(&[url], Box::new(StubResolver::new(&[&list]))).unwrap();
+		assert!(sync_all(&mut discovery).is_empty());
+	}
+
+	#[test]
+	fn skips_entries_not_matching_their_hash() {
+		let key = Random.generate();
+		let records = vec![record("10.0.0.1:30303"), record("10.0.0.2:30303")];
+		let mut list = build_node_list(&key, "nodes.example.org", 1, &records, &[]).unwrap();
+		let replaced = record("10.0.0.3:30303").to_base64();
+		let leaf = list.records.iter_mut().find(|(_, content)| *content == records[0].to_base64()).unwrap();
+		leaf.1 = replaced;
+
+		let mut discovery = DnsDiscovery::new(&[list.url.clone()], Box::new(StubResolver::new(&[&list]))).unwrap();
+		assert_eq!(sync_all(&mut discovery), vec![records[1].clone()]);
+	}
+
+	#[test]
+	fn refuses_to_export_without_records() {
+		let tempdir = tempfile::TempDir::new().unwrap();
+		let mut config = NetworkConfiguration::new_local();
+		config.use_secret = Some(Random.generate().secret().clone());
+		config.net_config_path = Some(tempdir.path().to_str().unwrap().to_owned());
+		assert!(export_node_list(&config, "nodes.example.org", 1).is_err());
+	}
+
+	#[test]
+	fn record_endpoints() {
+		let enr = record("10.0.0.1:30303");
+		let entry = record_entry(&enr).unwrap();
+		assert_eq!(entry.endpoint.address, "10.0.0.1:30303".parse().unwrap());
+		assert_eq!(entry.endpoint.udp_port, 30303);
+		assert_eq!(entry.id.as_bytes(), &enr.public_key().serialize_uncompressed()[1..]);
+	}
+
+	#[test]
+	fn dns_messages() {
+		let query = dns_query(0x1234, "nodes.example.org").unwrap();
+		assert_eq!(&query[..4], &[0x12, 0x34, 0x01, 0x00]);
+		assert_eq!(&query[12..31], b"\x05nodes\x07example\x03org\x00");
+		assert_eq!(&query[31..], &[0, 16, 0, 1]);
+		assert!(dns_query(1, "nodes..org").is_err());
+
+		let mut response = query.clone();
+		response[2] = 0x81;
+		response[3] = 0x80;
+		response[7] = 1;
+		// Answer with a compressed name and a TXT record split into two strings.
+		response.extend_from_slice(&[0xc0, 0x0c, 0, 16, 0, 1, 0, 0, 0, 60, 0, 8, 3]);
+		response.extend_from_slice(b"foo\x03bar");
+		assert_matches!(parse_txt_response(0x1234, &response), Ok(DnsResponse::Records(ref r)) if *r == vec!["foobar".to_owned()]);
+		assert!(parse_txt_response(0x4321, &response).is_err());
+		assert!(parse_txt_response(0x1234, &response[..response.len() - 1]).is_err());
+
+		response[2] |= 0x02;
+		assert_matches!(parse_txt_response(0x1234, &response), Ok(DnsResponse::Truncated));
+		response[2] = 0x81;
+		response[3] = 0x83;
+		assert_matches!(parse_txt_response(0x1234, &response), Ok(DnsResponse::Records(ref r)) if r.is_empty());
+
+		assert_eq!(
+			name_servers("# comment\nnameserver 10.0.0.1\nsearch example.org\nnameserver ::1\n"),
+			vec!["10.0.0.1:53".parse().unwrap(), "[::1]:53".parse().unwrap()]
+		);
+	}
+}
@@ -47,7 +47,9 @@ use network::{
 
 use crate::{
 	connection::PAYLOAD_SOFT_LIMIT,
-	discovery::{Discovery, MAX_DATAGRAM_SIZE, NodeEntry, TableUpdates},
+	discovery::{DatagramQueue, Discovery, MAX_DATAGRAM_SIZE, NodeEntry, TableUpdates},
+	discovery_v5::DiscoveryV5,
+	dns_discovery::{DnsDiscovery, DnsDiscoveryThread, SystemResolver, is_node_list_url, record_entry},
 	ip_utils::{map_external_address, select_public_address},
 	node_record::*,
 	node_table::*,
@@ -69,6 +71,8 @@ const DISCOVERY_REFRESH: TimerToken = SYS_TIMER + 4;
 const FAST_DISCOVERY_REFRESH: TimerToken = SYS_TIMER + 5;
 const DISCOVERY_ROUND: TimerToken = SYS_TIMER + 6;
 const NODE_TABLE: TimerToken = SYS_TIMER + 7;
+const DISCOVERY_V5: StreamToken = SYS_TIMER + 8;
+const DNS_DISCOVERY: TimerToken = SYS_TIMER + 9;
 const FIRST_SESSION: StreamToken = 0;
 const LAST_SESSION: StreamToken = FIRST_SESSION + MAX_SESSIONS - 1;
 const USER_TIMER: TimerToken = LAST_SESSION + 256;
@@ -83,6 +87,8 @@ const DISCOVERY_REFRESH_TIMEOUT: Duration = Duration::from_secs(60);
 const FAST_DISCOVERY_REFRESH_TIMEOUT: Duration = Duration::from_secs(10);
 // for DISCOVERY_ROUND TimerToken
 const DISCOVERY_ROUND_TIMEOUT: Duration = Duration::from_millis(300);
+// for DNS_DISCOVERY TimerToken
+const DNS_DISCOVERY_TIMEOUT: Duration = Duration::from_secs(5);
 // for NODE_TABLE TimerToken
 const NODE_TABLE_TIMEOUT: Duration = Duration::from_secs(300);
 
@@ -105,6 +111,19 @@ impl Encodable for CapabilityInfo {
 	}
 }
 
+/// Packets and bytes exchanged over a protocol since the network started.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ProtocolTraffic {
+	/// Number of packets sent.
+	pub packets_sent: u64,
+	/// Number of payload bytes sent.
+	pub bytes_sent: u64,
+	/// Number of packets received.
+	pub packets_received: u64,
+	/// Number of payload bytes received.
+	pub bytes_received: u64,
+}
+
 /// IO access point. This is passed to all IO handlers and provides an interface to the IO subsystem.
 pub struct NetworkContext<'s> {
 	io: &'s IoContext<NetworkIoMessage>,
@@ -113,6 +132,7 @@ pub struct NetworkContext<'s> {
 	session: Option<SharedSession>,
 	session_id: Option<StreamToken>,
 	reserved_peers: &'s HashSet<NodeId>,
+	traffic: &'s Mutex<HashMap<ProtocolId, ProtocolTraffic>>,
 }
 
 impl<'s> NetworkContext<'s> {
@@ -123,6 +143,7 @@ impl<'s> NetworkContext<'s> {
 		session: Option<SharedSession>,
 		sessions: Arc<RwLock<Slab<SharedSession>>>,
 		reserved_peers: &'s HashSet<NodeId>,
+		traffic: &'s Mutex<HashMap<ProtocolId, ProtocolTraffic>>,
 	) -> NetworkContext<'s> {
 		let id = session.as_ref().map(|s| s.lock().token());
 		NetworkContext {
@@ -132,6 +153,7 @@ impl<'s> NetworkContext<'s> {
 			session,
 			sessions,
 			reserved_peers: reserved_peers,
+			traffic,
 		}
 	}
 
@@ -152,6 +174,10 @@ impl<'s> NetworkContextTrait for NetworkContext<'s> {
 		let session = self.resolve_session(peer);
 		if let Some(session) = session {
 			session.lock().send_packet(self.io, Some(protocol), packet_id as u8, &data)?;
+			let mut traffic = self.traffic.lock();
+			let traffic = traffic.entry(protocol).or_default();
+			traffic.packets_sent += 1;
+			traffic.bytes_sent += data.len() as u64;
 		} else {
 			trace!(target: "network", "Send: Peer no longer exist")
 		}
@@ -266,14 +292,19 @@ struct ProtocolTimer {
 pub struct Host {
 	pub info: RwLock<HostInfo>,
 	udp_socket: Mutex<Option<UdpSocket>>,
+	/// Socket of discovery v5 if it doesn't share `udp_socket`.
+	udp_socket_v5: Mutex<Option<UdpSocket>>,
 	tcp_listener: Mutex<TcpListener>,
 	sessions: Arc<RwLock<Slab<SharedSession>>>,
 	discovery: Mutex<Option<Discovery>>,
+	discovery_v5: Mutex<Option<DiscoveryV5>>,
+	dns_discovery: Mutex<Option<DnsDiscoveryThread>>,
 	nodes: RwLock<NodeTable>,
 	handlers: RwLock<HashMap<ProtocolId, Arc<dyn NetworkProtocolHandler + Sync>>>,
 	timers: RwLock<HashMap<TimerToken, ProtocolTimer>>,
 	timer_counter: RwLock<usize>,
 	reserved_nodes: RwLock<HashSet<NodeId>>,
+	traffic: Mutex<HashMap<ProtocolId, ProtocolTraffic>>,
 	stopping: AtomicBool,
 	filter: Option<Arc<dyn ConnectionFilter>>,
 }
@@ -307,11 +338,14 @@ impl Host {
 				enr = EnrManager::load(path.as_str(), keys.secret().clone());
 			}
 		}
-		let enr = enr.unwrap_or_else(|| EnrManager::new(
+		let mut enr = enr.unwrap_or_else(|| EnrManager::new(
 			config.config_path.as_ref().map(|v| v.into()),
 			keys.secret().clone(),
 			0)
 			.expect("keys.secret() is a valid secp256k1 secret; Enr does not fail given valid secp256k1 secret; qed"));
+		for (key, value) in &config.enr_entries {
+			enr.set_entry(key, value.clone());
+		}
 		let path = config.net_config_path.clone();
 		// Setup the server socket
 		let tcp_listener = TcpListener::bind(&listen_address)?;
@@ -336,7 +370,10 @@ impl Host {
 				local_endpoint,
 			}),
 			discovery: Mutex::new(None),
+			discovery_v5: Mutex::new(None),
+			dns_discovery: Mutex::new(None),
 			udp_socket: Mutex::new(None),
+			udp_socket_v5: Mutex::new(None),
 			tcp_listener: Mutex::new(tcp_listener),
 			sessions: Arc::new(RwLock::new(Slab::with_capacity(MAX_SESSIONS))),
 			nodes: RwLock::new(NodeTable::new(path)),
@@ -344,12 +381,14 @@ impl Host {
 			timers: RwLock::new(HashMap::new()),
 			timer_counter: RwLock::new(USER_TIMER),
 			reserved_nodes: RwLock::new(HashSet::new()),
+			traffic: Mutex::new(HashMap::new()),
 			stopping: AtomicBool::new(false),
 			filter,
 		};
 
-		for n in boot_nodes {
-			host.add_node(&n);
+		// Node lists among the boot nodes are followed once discovery starts, if it is enabled.
+		for n in boot_nodes.iter().filter(|n| !is_node_list_url(n)) {
+			host.add_node(n);
 		}
 
 		for n in reserved_nodes {
@@ -367,6 +406,9 @@ impl Host {
 				let entry = NodeEntry { endpoint: n.endpoint.clone(), id: n.id };
 
 				self.nodes.write().add_node(n);
+				if let Some(ref mut discovery_v5) = *self.discovery_v5.lock() {
+					discovery_v5.add_node(entry.clone());
+				}
 				if let Some(ref mut discovery) = *self.discovery.lock() {
 					discovery.add_node(entry);
 				}
@@ -381,6 +423,9 @@ impl Host {
 		self.reserved_nodes.write().insert(n.id);
 		self.nodes.write().add_node(Node::new(entry.id, entry.endpoint.clone()));
 
+		if let Some(ref mut discovery_v5) = *self.discovery_v5.lock() {
+			discovery_v5.add_node(entry.clone());
+		}
 		if let Some(ref mut discovery) = *self.discovery.lock() {
 			discovery.add_node(entry);
 		}
@@ -425,6 +470,27 @@ impl Host {
 		Ok(())
 	}
 
+	pub fn is_reserved_node(&self, id: &str) -> Result<bool, Error> {
+		let n = Node::from_str(id)?;
+		Ok(self.reserved_nodes.read().contains(&n.id))
+	}
+
+	/// Set an entry of the local node record. A changed record gets a new sequence number, so
+	/// that discovery peers fetch it again.
+	pub fn set_enr_entry(&self, key: &str, value: Vec<u8>) {
+		let enr = {
+			let mut info = self.info.write();
+			info.enr.set_entry(key, value);
+			info.enr.as_enr().clone()
+		};
+		if let Some(ref mut discovery) = *self.discovery.lock() {
+			discovery.set_enr(enr.clone());
+		}
+		if let Some(ref mut discovery_v5) = *self.discovery_v5.lock() {
+			discovery_v5.set_enr(enr);
+		}
+	}
+
 	pub fn external_url(&self) -> Option<String> {
 		let info = self.info.read();
 		info.public_endpoint.as_ref().map(|e| format!("{}", Node::new(*info.id(), e.clone())))
@@ -447,9 +513,15 @@ impl Host {
 			trace!(target: "network", "Disconnecting on shutdown: {}", p);
 			self.kill_connection(p, io, true);
 		}
+		*self.dns_discovery.lock() = None;
 		io.unregister_handler();
 	}
 
+	/// Returns the packets and bytes exchanged over each protocol.
+	pub fn traffic(&self) -> HashMap<ProtocolId, ProtocolTraffic> {
+		self.traffic.lock().clone()
+	}
+
 	/// Get all connected peers.
 	pub fn connected_peers(&self) -> Vec<PeerId> {
 		let sessions = self.sessions.read();
@@ -490,10 +562,20 @@ impl Host {
 			Some(addr) => NodeEndpoint { address: addr, udp_port: local_endpoint.udp_port }
 		};
 
+		// Discovery v5 on a separate socket advertises its own port in the node record.
+		let discovery_v5_port = {
+			let info = self.info.read();
+			info.config.discovery_v5_port.filter(|port| info.config.discovery_v5_enabled && *port != local_endpoint.udp_port)
+		};
+
 		{
 			let mut info = self.info.write();
 			info.public_endpoint = Some(public_endpoint.clone());
-			info.enr.set_node_endpoint(&public_endpoint);
+			let mut enr_endpoint = public_endpoint.clone();
+			if let Some(port) = discovery_v5_port {
+				enr_endpoint.udp_port = port;
+			}
+			info.enr.set_node_endpoint(&enr_endpoint);
 		}
 
 		if let Some(url) = self.external_url() {
@@ -501,25 +583,67 @@ impl Host {
 		}
 
 		// Initialize discovery.
-		let discovery = {
+		let (discovery, discovery_v5) = {
 			let info = self.info.read();
-			if info.config.discovery_enabled && info.config.non_reserved_mode == NonReservedPeerMode::Accept {
-				Some(Discovery::new(&info.keys, public_endpoint, info.enr.as_enr().clone(), allow_ips))
-			} else { None }
+			if info.config.non_reserved_mode == NonReservedPeerMode::Accept {
+				let discovery = if info.config.discovery_enabled && info.config.discovery_v4_enabled {
+					Some(Discovery::new(&info.keys, public_endpoint, info.enr.as_enr().clone(), allow_ips.clone()))
+				} else { None };
+				let discovery_v5 = if info.config.discovery_v5_enabled {
+					Some(DiscoveryV5::new(&info.keys, info.enr.as_enr().clone(), info.config.discovery_v5_filter.clone(), allow_ips)?)
+				} else { None };
+				(discovery, discovery_v5)
+			} else { (None, None) }
 		};
 
-		if let Some(mut discovery) = discovery {
+		let shared_socket = discovery.is_some() || (discovery_v5.is_some() && discovery_v5_port.is_none());
+		if shared_socket {
 			let mut udp_addr = local_endpoint.address;
 			udp_addr.set_port(local_endpoint.udp_port);
 			let socket = UdpSocket::bind(&udp_addr).expect("Error binding UDP socket");
 			*self.udp_socket.lock() = Some(socket);
+		}
+		if let (Some(_), Some(port)) = (&discovery_v5, discovery_v5_port) {
+			let mut udp_addr = local_endpoint.address;
+			udp_addr.set_port(port);
+			let socket = UdpSocket::bind(&udp_addr).expect("Error binding discovery v5 UDP socket");
+			*self.udp_socket_v5.lock() = Some(socket);
+		}
 
+		let discovery_enabled = discovery.is_some() || discovery_v5.is_some();
+		if let Some(mut discovery) = discovery {
 			discovery.add_node_list(self.nodes.read().entries());
 			*self.discovery.lock() = Some(discovery);
+		}
+		if let Some(mut discovery_v5) = discovery_v5 {
+			discovery_v5.add_node_list(self.nodes.read().entries());
+			*self.discovery_v5.lock() = Some(discovery_v5);
+		}
+		if shared_socket {
 			io.register_stream(DISCOVERY)?;
+		}
+		if self.udp_socket_v5.lock().is_some() {
+			io.register_stream(DISCOVERY_V5)?;
+		}
+		let node_lists = self.info.read().config.boot_nodes.iter().filter(|n| is_node_list_url(n)).cloned().collect::<Vec<_>>();
+		if discovery_enabled {
 			io.register_timer(FAST_DISCOVERY_REFRESH, FAST_DISCOVERY_REFRESH_TIMEOUT)?;
 			io.register_timer(DISCOVERY_REFRESH, DISCOVERY_REFRESH_TIMEOUT)?;
 			io.register_timer(DISCOVERY_ROUND, DISCOVERY_ROUND_TIMEOUT)?;
+
+			if !node_lists.is_empty() {
+				let dns_discovery = DnsDiscovery::new(&node_lists, Box::new(SystemResolver::new()))
+					.and_then(|d| DnsDiscoveryThread::spawn(d, DNS_DISCOVERY_TIMEOUT));
+				match dns_discovery {
+					Ok(dns_discovery) => {
+						*self.dns_discovery.lock() = Some(dns_discovery);
+						io.register_timer(DNS_DISCOVERY, DNS_DISCOVERY_TIMEOUT)?;
+					},
+					Err(e) => warn!(target: "network", "Error reading node lists: {}", e),
+				}
+			}
+		} else if !node_lists.is_empty() {
+			warn!(target: "network", "Discovery is disabled; ignoring node lists among the boot nodes: {}", node_lists.join(", "));
 		}
 		io.register_timer(NODE_TABLE, NODE_TABLE_TIMEOUT)?;
 		io.register_stream(TCP_ACCEPT)?;
@@ -811,6 +935,9 @@ impl Host {
 									let mut nodes = self.nodes.write();
 									if !nodes.contains(&entry.id) {
 										nodes.add_node(Node::new(entry.id, entry.endpoint.clone()));
+										if let Some(ref mut discovery_v5) = *self.discovery_v5.lock() {
+											discovery_v5.add_node(entry.clone());
+										}
 										let mut discovery = self.discovery.lock();
 										if let Some(ref mut discovery) = *discovery {
 											discovery.add_node(entry);
@@ -835,7 +962,13 @@ impl Host {
 						}) => {
 							match self.handlers.read().get(&protocol) {
 								None => { warn!(target: "network", "No handler found for protocol: {:?}", protocol) },
-								Some(_) => packet_data.push((protocol, packet_id, data)),
+								Some(_) => {
+									let mut traffic = self.traffic.lock();
+									let traffic = traffic.entry(protocol).or_default();
+									traffic.packets_received += 1;
+									traffic.bytes_received += data.len() as u64;
+									packet_data.push((protocol, packet_id, data))
+								},
 							}
 						},
 						Ok(SessionData::Continue) => (),
@@ -864,7 +997,7 @@ impl Host {
 				for p in ready_data {
 					let reserved = self.reserved_nodes.read();
 					if let Some(h) = handlers.get(&p) {
-						h.connected(&NetworkContext::new(io, p, Some(session.clone()), self.sessions.clone(), &reserved), &token);
+						h.connected(&NetworkContext::new(io, p, Some(session.clone()), self.sessions.clone(), &reserved, &self.traffic), &token);
 						// accumulate pending packets.
 						let mut session = session.lock();
 						packet_data.extend(session.mark_connected(p));
@@ -875,64 +1008,96 @@ impl Host {
 			for (p, packet_id, data) in packet_data {
 				let reserved = self.reserved_nodes.read();
 				if let Some(h) = handlers.get(&p) {
-					h.read(&NetworkContext::new(io, p, Some(session.clone()), self.sessions.clone(), &reserved), &token, packet_id, &data);
+					h.read(&NetworkContext::new(io, p, Some(session.clone()), self.sessions.clone(), &reserved, &self.traffic), &token, packet_id, &data);
 				}
 			}
 		}
 	}
 
-	fn discovery_readable(&self, io: &IoContext<NetworkIoMessage>) {
-		let node_changes = match (self.udp_socket.lock().as_ref(), self.discovery.lock().as_mut()) {
-			(Some(udp_socket), Some(discovery)) => {
-				let mut buf = [0u8; MAX_DATAGRAM_SIZE];
-				let writable = discovery.any_sends_queued();
-				let res = match udp_socket.recv_from(&mut buf) {
-					Ok(Some((len, address))) => discovery.on_packet(&buf[0..len], address).unwrap_or_else(|e| {
-						debug!(target: "network", "Error processing UDP packet: {:?}", e);
-						None
-					}),
-					Ok(_) => None,
-					Err(e) => {
-						debug!(target: "network", "Error reading UPD socket: {:?}", e);
-						None
-					}
-				};
-				let new_writable = discovery.any_sends_queued();
-				if writable != new_writable {
-					io.update_registration(DISCOVERY)
-						.unwrap_or_else(|e| {
-							debug!(target: "network" ,"Error updating discovery registration: {:?}", e)
-						});
-				}
-				res
-			},
-			_ => None,
+	fn discovery_socket(&self, stream: StreamToken) -> &Mutex<Option<UdpSocket>> {
+		if stream == DISCOVERY_V5 { &self.udp_socket_v5 } else { &self.udp_socket }
+	}
+
+	/// Whether discovery v5 runs on the socket of the stream. It shares the discovery v4 socket unless it has its own.
+	fn runs_discovery_v5(&self, stream: StreamToken) -> bool {
+		stream == DISCOVERY_V5 || self.udp_socket_v5.lock().is_none()
+	}
+
+	fn discovery_sends_queued(&self, stream: StreamToken) -> bool {
+		let v4 = stream == DISCOVERY && self.discovery.lock().as_ref().map_or(false, |d| d.any_sends_queued());
+		v4 || (self.runs_discovery_v5(stream) && self.discovery_v5.lock().as_ref().map_or(false, |d| d.any_sends_queued()))
+	}
+
+	fn update_discovery_registration(&self, io: &IoContext<NetworkIoMessage>) {
+		for stream in &[DISCOVERY, DISCOVERY_V5] {
+			if self.discovery_socket(*stream).lock().is_some() {
+				io.update_registration(*stream)
+					.unwrap_or_else(|e| debug!("Error updating discovery registration: {:?}", e));
+			}
+		}
+	}
+
+	fn discovery_readable(&self, stream: StreamToken, io: &IoContext<NetworkIoMessage>) {
+		let mut buf = [0u8; MAX_DATAGRAM_SIZE];
+		let received = match self.discovery_socket(stream).lock().as_ref() {
+			Some(udp_socket) => udp_socket.recv_from(&mut buf),
+			None => return,
 		};
+		let (len, address) = match received {
+			Ok(Some(received)) => received,
+			Ok(None) => return,
+			Err(e) => {
+				debug!(target: "network", "Error reading UPD socket: {:?}", e);
+				return;
+			}
+		};
+		let packet = &buf[0..len];
+
+		let writable = self.discovery_sends_queued(stream);
+		let is_v5_packet = self.runs_discovery_v5(stream)
+			&& self.discovery_v5.lock().as_ref().map_or(false, |d| d.is_v5_packet(packet));
+		let res = if is_v5_packet {
+			self.discovery_v5.lock().as_mut().map(|discovery_v5| discovery_v5.on_packet(packet, address))
+		} else {
+			self.discovery.lock().as_mut().map(|discovery| discovery.on_packet(packet, address))
+		};
+		let node_changes = match res {
+			Some(Ok(node_changes)) => node_changes,
+			Some(Err(e)) => {
+				debug!(target: "network", "Error processing UDP packet: {:?}", e);
+				None
+			}
+			None => None,
+		};
+		if writable != self.discovery_sends_queued(stream) {
+			io.update_registration(stream)
+				.unwrap_or_else(|e| {
+					debug!(target: "network" ,"Error updating discovery registration: {:?}", e)
+				});
+		}
 		if let Some(node_changes) = node_changes {
 			self.update_nodes(io, node_changes);
 		}
 	}
 
-	fn discovery_writable(&self, io: &IoContext<NetworkIoMessage>) {
-		if let (Some(udp_socket), Some(discovery)) = (self.udp_socket.lock().as_ref(), self.discovery.lock().as_mut()) {
-			while let Some(data) = discovery.dequeue_send() {
-				match udp_socket.send_to(&data.payload, &data.address) {
-					Ok(Some(size)) if size == data.payload.len() => {
-					},
-					Ok(Some(_)) => {
-						warn!(target: "network", "UDP sent incomplete datagram");
-					},
-					Ok(None) => {
-						discovery.requeue_send(data);
+	fn discovery_writable(&self, stream: StreamToken, io: &IoContext<NetworkIoMessage>) {
+		let runs_discovery_v5 = self.runs_discovery_v5(stream);
+		if let Some(udp_socket) = self.discovery_socket(stream).lock().as_ref() {
+			if stream == DISCOVERY {
+				if let Some(discovery) = self.discovery.lock().as_mut() {
+					if !send_datagrams(udp_socket, discovery) {
 						return;
 					}
-					Err(e) => {
-						debug!(target: "network", "UDP send error: {:?}, address: {:?}", e, &data.address);
+				}
+			}
+			if runs_discovery_v5 {
+				if let Some(discovery_v5) = self.discovery_v5.lock().as_mut() {
+					if !send_datagrams(udp_socket, discovery_v5) {
 						return;
 					}
 				}
 			}
-			io.update_registration(DISCOVERY)
+			io.update_registration(stream)
 				.unwrap_or_else(|e| {
 					debug!(target: "network", "Error updating discovery registration: {:?}", e)
 				});
@@ -976,7 +1141,7 @@ impl Host {
 		for p in to_disconnect {
 			let reserved = self.reserved_nodes.read();
 			if let Some(h) = self.handlers.read().get(&p) {
-				h.disconnected(&NetworkContext::new(io, p, expired_session.clone(), self.sessions.clone(), &reserved), &token);
+				h.disconnected(&NetworkContext::new(io, p, expired_session.clone(), self.sessions.clone(), &reserved, &self.traffic), &token);
 			}
 		}
 		if deregister {
@@ -984,6 +1149,24 @@ impl Host {
 		}
 	}
 
+	/// Add nodes of node lists to the node table and let discovery contact them.
+	fn add_node_records(&self, records: Vec<Enr>) {
+		for record in records {
+			let entry = match record_entry(&record) {
+				Some(entry) => entry,
+				None => continue,
+			};
+			trace!(target: "network", "Adding node {:?} from node list", entry);
+			self.nodes.write().add_node(Node { record: Some(record.clone()), ..Node::new(entry.id, entry.endpoint.clone()) });
+			if let Some(ref mut discovery_v5) = *self.discovery_v5.lock() {
+				discovery_v5.add_node_record(record);
+			}
+			if let Some(ref mut discovery) = *self.discovery.lock() {
+				discovery.add_node(entry);
+			}
+		}
+	}
+
 	fn update_nodes(&self, _io: &IoContext<NetworkIoMessage>, node_changes: TableUpdates) {
 		let mut to_remove: Vec<PeerId> = Vec::new();
 		{
@@ -1007,14 +1190,14 @@ impl Host {
 	pub fn with_context<F>(&self, protocol: ProtocolId, io: &IoContext<NetworkIoMessage>, action: F) where F: FnOnce(&dyn NetworkContextTrait) {
 		let reserved = self.reserved_nodes.read();
 
-		let context = NetworkContext::new(io, protocol, None, self.sessions.clone(), &reserved);
+		let context = NetworkContext::new(io, protocol, None, self.sessions.clone(), &reserved, &self.traffic);
 		action(&context);
 	}
 
 	pub fn with_context_eval<F, T>(&self, protocol: ProtocolId, io: &IoContext<NetworkIoMessage>, action: F) -> T where F: FnOnce(&dyn NetworkContextTrait) -> T {
 		let reserved = self.reserved_nodes.read();
 
-		let context = NetworkContext::new(io, protocol, None, self.sessions.clone(), &reserved);
+		let context = NetworkContext::new(io, protocol, None, self.sessions.clone(), &reserved, &self.traffic);
 		action(&context)
 	}
 }
@@ -1041,7 +1224,7 @@ impl IoHandler<NetworkIoMessage> for Host {
 		}
 		match stream {
 			FIRST_SESSION ..= LAST_SESSION => self.session_readable(stream, io),
-			DISCOVERY => self.discovery_readable(io),
+			DISCOVERY | DISCOVERY_V5 => self.discovery_readable(stream, io),
 			TCP_ACCEPT => self.accept(io),
 			_ => panic!("Received unknown readable token"),
 		}
@@ -1053,7 +1236,7 @@ impl IoHandler<NetworkIoMessage> for Host {
 		}
 		match stream {
 			FIRST_SESSION ..= LAST_SESSION => self.session_writable(stream, io),
-			DISCOVERY => self.discovery_writable(io),
+			DISCOVERY | DISCOVERY_V5 => self.discovery_writable(stream, io),
 			_ => panic!("Received unknown writable token"),
 		}
 	}
@@ -1071,7 +1254,8 @@ impl IoHandler<NetworkIoMessage> for Host {
 					return;
 				}
 				self.discovery.lock().as_mut().map(|d| d.refresh());
-				io.update_registration(DISCOVERY).unwrap_or_else(|e| debug!("Error updating discovery registration: {:?}", e));
+				self.discovery_v5.lock().as_mut().map(|d| d.refresh());
+				self.update_discovery_registration(io);
 			},
 			FAST_DISCOVERY_REFRESH => {
 				// Run the fast discovery if not enough peers are connected
@@ -1079,11 +1263,20 @@ impl IoHandler<NetworkIoMessage> for Host {
 					return;
 				}
 				self.discovery.lock().as_mut().map(|d| d.refresh());
-				io.update_registration(DISCOVERY).unwrap_or_else(|e| debug!("Error updating discovery registration: {:?}", e));
+				self.discovery_v5.lock().as_mut().map(|d| d.refresh());
+				self.update_discovery_registration(io);
 			},
 			DISCOVERY_ROUND => {
 				self.discovery.lock().as_mut().map(|d| d.round());
-				io.update_registration(DISCOVERY).unwrap_or_else(|e| debug!("Error updating discovery registration: {:?}", e));
+				self.discovery_v5.lock().as_mut().map(|d| d.round());
+				self.update_discovery_registration(io);
+			},
+			DNS_DISCOVERY => {
+				let records = self.dns_discovery.lock().as_ref().map_or_else(Vec::new, |d| d.records());
+				if !records.is_empty() {
+					self.add_node_records(records);
+					self.update_discovery_registration(io);
+				}
 			},
 			NODE_TABLE => {
 				trace!(target: "network", "Refreshing node table");
@@ -1096,7 +1289,7 @@ impl IoHandler<NetworkIoMessage> for Host {
 					None => { warn!(target: "network", "No handler found for protocol: {:?}", timer.protocol) },
 					Some(h) => {
 						let reserved = self.reserved_nodes.read();
-						h.timeout(&NetworkContext::new(io, timer.protocol, None, self.sessions.clone(), &reserved), timer.token);
+						h.timeout(&NetworkContext::new(io, timer.protocol, None, self.sessions.clone(), &reserved, &self.traffic), timer.token);
 					}
 				},
 				None => { warn!("Unknown timer token: {}", token); } // timer is not registerd through us
@@ -1117,7 +1310,7 @@ impl IoHandler<NetworkIoMessage> for Host {
 				let h = handler.clone();
 				let reserved = self.reserved_nodes.read();
 				h.initialize(
-					&NetworkContext::new(io, *protocol, None, self.sessions.clone(), &reserved),
+					&NetworkContext::new(io, *protocol, None, self.sessions.clone(), &reserved, &self.traffic),
 				);
 				self.handlers.write().insert(*protocol, h);
 				let mut info = self.info.write();
@@ -1186,6 +1379,13 @@ impl IoHandler<NetworkIoMessage> for Host {
 				},
 				_ => panic!("Error registering discovery socket"),
 			}
+			DISCOVERY_V5 => match self.udp_socket_v5.lock().as_ref() {
+				Some(udp_socket) => {
+					event_loop.register(udp_socket, reg, Ready::all(), PollOpt::edge())
+						.expect("Error registering UDP socket");
+				},
+				_ => panic!("Error registering discovery v5 socket"),
+			}
 			TCP_ACCEPT => event_loop.register(&*self.tcp_listener.lock(), Token(TCP_ACCEPT), Ready::all(), PollOpt::edge()).expect("Error registering stream"),
 			_ => warn!("Unexpected stream registration")
 		}
@@ -1203,7 +1403,7 @@ impl IoHandler<NetworkIoMessage> for Host {
 					}
 				}
 			}
-			DISCOVERY => (),
+			DISCOVERY | DISCOVERY_V5 => (),
 			_ => warn!("Unexpected stream deregistration")
 		}
 	}
@@ -1216,17 +1416,19 @@ impl IoHandler<NetworkIoMessage> for Host {
 					connection.lock().update_socket(reg, event_loop).expect("Error updating socket");
 				}
 			}
-			DISCOVERY => match (self.udp_socket.lock().as_ref(), self.discovery.lock().as_ref()) {
-				(Some(udp_socket), Some(discovery)) => {
-					let registration = if discovery.any_sends_queued() {
-						Ready::readable() | Ready::writable()
-					} else {
-						Ready::readable()
-					};
-					event_loop.reregister(udp_socket, reg, registration, PollOpt::edge())
-						.expect("Error reregistering UDP socket");
-				},
-				_ => panic!("Error reregistering discovery socket"),
+			DISCOVERY | DISCOVERY_V5 => {
+				let registration = if self.discovery_sends_queued(stream) {
+					Ready::readable() | Ready::writable()
+				} else {
+					Ready::readable()
+				};
+				match self.discovery_socket(stream).lock().as_ref() {
+					Some(udp_socket) => {
+						event_loop.reregister(udp_socket, reg, registration, PollOpt::edge())
+							.expect("Error reregistering UDP socket");
+					},
+					_ => panic!("Error reregistering discovery socket"),
+				}
 			}
 			TCP_ACCEPT => event_loop.reregister(&*self.tcp_listener.lock(), Token(TCP_ACCEPT), Ready::all(), PollOpt::edge()).expect("Error reregistering stream"),
 			_ => warn!("Unexpected stream update")
@@ -1234,6 +1436,28 @@ impl IoHandler<NetworkIoMessage> for Host {
 	}
 }
 
+/// Sends queued datagrams of a discovery protocol until the queue is empty. Returns `false` if sending stopped early.
+fn send_datagrams<Q: DatagramQueue>(udp_socket: &UdpSocket, queue: &mut Q) -> bool {
+	while let Some(data) = queue.dequeue_send() {
+		match udp_socket.send_to(&data.payload, &data.address) {
+			Ok(Some(size)) if size == data.payload.len() => {
+			},
+			Ok(Some(_)) => {
+				warn!(target: "network", "UDP sent incomplete datagram");
+			},
+			Ok(None) => {
+				queue.requeue_send(data);
+				return false;
+			}
+			Err(e) => {
+				debug!(target: "network", "UDP send error: {:?}, address: {:?}", e, &data.address);
+				return false;
+			}
+		}
+	}
+	true
+}
+
 #[test]
 fn host_client_url() {
 	let mut config = NetworkConfiguration::new_local();
@@ -1242,3 +1466,17 @@ fn host_client_url() {
 	let host: Host = Host::new(config, None).unwrap();
 	assert!(host.local_url().starts_with("enode://101b3ef5a4ea7a1c7928e24c4c75fd053c235d7b80c22ae5c03d145d0ac7396e2a4ffff9adee3133a7b05044a5cee08115fd65145e5165d646bde371010d803c@"));
 }
+
+#[test]
+fn host_enr_entry() {
+	let host: Host = Host::new(NetworkConfiguration::new_local(), None).unwrap();
+	let seq = host.info.read().enr.as_enr().seq();
+
+	host.set_enr_entry("eth", vec![0xc1, 0x01]);
+	assert_eq!(host.info.read().enr.as_enr().get("eth").map(|value| value[..].to_vec()), Some(vec![0xc1, 0x01]));
+	assert_eq!(host.info.read().enr.as_enr().seq(), seq + 1);
+
+	// setting the same value again keeps the record
+	host.set_enr_entry("eth", vec![0xc1, 0x01]);
+	assert_eq!(host.info.read().enr.as_enr().seq(), seq + 1);
+}
@@ -61,8 +61,9 @@
 #![allow(deprecated)]
 
 pub use ethcore_io::TimerToken;
-pub use host::NetworkContext;
+pub use host::{NetworkContext, ProtocolTraffic};
 pub use node_table::{MAX_NODES_IN_TABLE, NodeId, validate_node_url};
+pub use dns_discovery::{NodeList, export_node_list, validate_node_list_url};
 pub use service::NetworkService;
 
 mod host;
@@ -70,6 +71,8 @@ mod connection;
 mod handshake;
 mod session;
 mod discovery;
+mod discovery_v5;
+mod dns_discovery;
 mod service;
 mod node_record;
 mod node_table;
@@ -63,6 +63,22 @@ impl EnrManager {
 		self.save();
 	}
 
+	pub fn set_entry(&mut self, key: &str, value: Vec<u8>) {
+		if self.inner.get(key).map_or(false, |current| current[..] == value[..]) {
+			return;
+		}
+
+		let seq = self.inner.seq();
+		if let Err(e) = self.inner.insert(key, value, &self.secret) {
+			warn!("Could not set ENR entry {}: {:?}", key, e);
+			return;
+		}
+		if let Err(e) = self.inner.set_seq(seq.wrapping_add(1), &self.secret) {
+			warn!("Could not update ENR sequence number: {:?}", e);
+		}
+		self.save();
+	}
+
 	pub fn as_enr(&self) -> &Enr {
 		&self.inner
 	}
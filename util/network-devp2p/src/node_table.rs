@@ -36,6 +36,7 @@ use network::{AllowIP, Error, IpFilter};
 use crate::{
 	discovery::{NodeEntry, TableUpdates},
 	ip_utils::*,
+	node_record::Enr,
 };
 
 /// Node public key
@@ -193,6 +194,8 @@ pub struct Node {
 	pub endpoint: NodeEndpoint,
 	pub peer_type: PeerType,
 	pub last_contact: Option<NodeContact>,
+	/// Signed node record, if the node was found through a protocol that provides one.
+	pub record: Option<Enr>,
 }
 
 impl Node {
@@ -202,6 +205,7 @@ impl Node {
 			endpoint,
 			peer_type: PeerType::Optional,
 			last_contact: None,
+			record: None,
 		}
 	}
 }
@@ -232,6 +236,7 @@ impl FromStr for Node {
 			endpoint,
 			peer_type: PeerType::Optional,
 			last_contact: None,
+			record: None,
 		})
 	}
 }
@@ -275,8 +280,15 @@ impl NodeTable {
 
 	/// Add a node to table
 	pub fn add_node(&mut self, mut node: Node) {
-		// preserve node last_contact
-		node.last_contact = self.nodes.get(&node.id).and_then(|n| n.last_contact);
+		// preserve node last_contact and record
+		if let Some(known) = self.nodes.get_mut(&node.id) {
+			node.last_contact = known.last_contact;
+			if node.record.is_none() {
+				node.record = known.record.take();
+			}
+		} else {
+			node.last_contact = None;
+		}
 		let id = node.id;
 		if self.ordered_ids.len() == MAX_NODES_IN_TABLE {
 			self.nodes.remove(&self.ordered_ids.pop().expect("ordered_ids is not empty; qed"));
@@ -419,6 +431,9 @@ impl NodeTable {
 					Node::new(node.id, node.endpoint.clone())
 				});
 				entry.endpoint = node.endpoint;
+				if let Some(record) = update.records.remove(&node.id) {
+					entry.record = Some(record);
+				}
 			}
 			if add {
 				if self.ordered_ids.len() == MAX_NODES_IN_TABLE {
@@ -498,6 +513,11 @@ impl NodeTable {
 		}
 	}
 
+	/// Signed records of the nodes in the table saved at `path`.
+	pub fn load_records(path: Option<String>) -> Vec<Enr> {
+		NodeTable::load(path).into_iter().filter_map(|(_, node)| node.record).collect()
+	}
+
 	fn load(path: Option<String>) -> HashMap<NodeId, Node> {
 		let path = match path {
 			Some(path) => PathBuf::from(path).join(NODES_FILE),
@@ -574,6 +594,8 @@ mod json {
 	pub struct Node {
 		pub url: String,
 		pub last_contact: Option<NodeContact>,
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		pub record: Option<String>,
 	}
 
 	impl Node {
@@ -581,6 +603,7 @@ mod json {
 			match super::Node::from_str(&self.url) {
 				Ok(mut node) => {
 					node.last_contact = self.last_contact.map(|c| c.into_node_contact());
+					node.record = self.record.and_then(|r| r.parse().ok());
 					Some(node)
 				},
 				_ => None,
@@ -601,7 +624,8 @@ mod json {
 
 			Node {
 				url: format!("{}", node),
-				last_contact
+				last_contact,
+				record: node.record.as_ref().map(|r| r.to_base64()),
 			}
 		}
 	}
@@ -616,10 +640,12 @@ mod tests {
 
 	use ethereum_types::H512;
 	use ipnetwork::IpNetwork;
+	use parity_crypto::publickey::{Generator, Random};
 	use tempfile::TempDir;
 
 	use assert_matches::assert_matches;
 
+	use crate::node_record::EnrManager;
 	use super::*;
 
 	#[test]
@@ -809,6 +835,26 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn table_saves_records() {
+		let tempdir = TempDir::new().unwrap();
+		let path = Some(tempdir.path().to_str().unwrap().to_owned());
+		let key = Random.generate();
+		let endpoint = NodeEndpoint::from_str("22.99.55.44:7770").unwrap();
+		let record = EnrManager::new(None, key.secret().clone(), 0).unwrap().with_node_endpoint(&endpoint).into_enr();
+
+		{
+			let mut table = NodeTable::new(path.clone());
+			table.add_node(Node { record: Some(record.clone()), ..Node::new(*key.public(), endpoint.clone()) });
+			// Nodes added without a record keep the known one.
+			table.add_node(Node::new(*key.public(), endpoint));
+			assert_eq!(table.get(key.public()).unwrap().record, Some(record.clone()));
+		}
+
+		assert_eq!(NodeTable::load_records(path.clone()), vec![record.clone()]);
+		assert_eq!(NodeTable::new(path).get(key.public()).unwrap().record, Some(record));
+	}
+
 	#[test]
 	fn custom_allow() {
 		let filter = IpFilter {
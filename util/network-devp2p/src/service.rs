@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::ops::RangeInclusive;
 use std::sync::Arc;
@@ -29,7 +30,7 @@ use network::{
 
 };
 
-use crate::host::Host;
+use crate::host::{Host, ProtocolTraffic};
 
 struct HostHandler {
 	public_url: RwLock<Option<String>>
@@ -154,6 +155,11 @@ impl NetworkService {
 		self.host.read().as_ref().map(|h| h.connected_peers()).unwrap_or_else(Vec::new)
 	}
 
+	/// Get the packets and bytes exchanged over each protocol.
+	pub fn traffic(&self) -> HashMap<ProtocolId, ProtocolTraffic> {
+		self.host.read().as_ref().map(|h| h.traffic()).unwrap_or_else(HashMap::new)
+	}
+
 	/// Try to add a reserved peer.
 	pub fn add_reserved_peer(&self, peer: &str) -> Result<(), Error> {
 		let host = self.host.read();
@@ -174,6 +180,24 @@ impl NetworkService {
 		}
 	}
 
+	/// Check whether a peer is reserved.
+	pub fn is_reserved_peer(&self, peer: &str) -> Result<bool, Error> {
+		let host = self.host.read();
+		if let Some(ref host) = *host {
+			host.is_reserved_node(peer)
+		} else {
+			Ok(false)
+		}
+	}
+
+	/// Set an entry of the local node record, if the network is running.
+	pub fn set_enr_entry(&self, key: &str, value: Vec<u8>) {
+		let host = self.host.read();
+		if let Some(ref host) = *host {
+			host.set_enr_entry(key, value);
+		}
+	}
+
 	/// Set the non-reserved peer mode.
 	pub fn set_non_reserved_mode(&self, mode: NonReservedPeerMode) {
 		let host = self.host.read();
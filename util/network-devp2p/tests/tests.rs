@@ -142,3 +142,21 @@ fn net_timeout() {
 		thread::sleep(Duration::from_millis(50));
 	}
 }
+
+#[test]
+fn net_reserved_peers() {
+	let configured = format!("enode://{:x}@127.0.0.1:30303", Random.generate().public());
+	let added = format!("enode://{:x}@127.0.0.1:30304", Random.generate().public());
+	let mut config = NetworkConfiguration::new_local();
+	config.reserved_nodes = vec![configured.clone()];
+	let service = NetworkService::new(config, None).unwrap();
+	service.start().unwrap();
+	assert!(service.is_reserved_peer(&configured).unwrap());
+	assert!(!service.is_reserved_peer(&added).unwrap());
+
+	service.add_reserved_peer(&added).unwrap();
+	assert!(service.is_reserved_peer(&added).unwrap());
+	service.remove_reserved_peer(&added).unwrap();
+	assert!(!service.is_reserved_peer(&added).unwrap());
+	assert!(service.is_reserved_peer("not an enode").is_err());
+}
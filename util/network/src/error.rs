@@ -113,6 +113,9 @@ pub enum Error {
 	/// Invalid node id
 	#[display(fmt = "Invalid node id")]
 	InvalidNodeId,
+	/// Invalid EIP-1459 node list URL or entry
+	#[display(fmt = "Invalid node list: {}", _0)]
+	InvalidNodeList(String),
 	/// Packet size is over the protocol limit
 	#[display(fmt = "Packet is too large")]
 	OversizedPacket,
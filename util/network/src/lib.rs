@@ -202,6 +202,16 @@ pub struct NetworkConfiguration {
 	pub nat_type: NatType,
 	/// Enable discovery
 	pub discovery_enabled: bool,
+	/// Enable discovery v4 when discovery is enabled
+	pub discovery_v4_enabled: bool,
+	/// Enable discovery v5
+	pub discovery_v5_enabled: bool,
+	/// Port for discovery v5, shares the discovery socket by default
+	pub discovery_v5_port: Option<u16>,
+	/// Only nodes with a matching record found through discovery v5 are added to the node table
+	pub discovery_v5_filter: Option<EnrFilter>,
+	/// Additional entries of the local node record
+	pub enr_entries: Vec<(String, Vec<u8>)>,
 	/// List of initial node addresses
 	pub boot_nodes: Vec<String>,
 	/// Use provided node key instead of default
@@ -242,6 +252,11 @@ impl NetworkConfiguration {
 			nat_enabled: true,
 			nat_type: NatType::Any,
 			discovery_enabled: true,
+			discovery_v4_enabled: true,
+			discovery_v5_enabled: false,
+			discovery_v5_port: None,
+			discovery_v5_filter: None,
+			enr_entries: Vec::new(),
 			boot_nodes: Vec::new(),
 			use_secret: None,
 			min_peers: 25,
@@ -443,6 +458,35 @@ impl IpFilter {
 	}
 }
 
+/// Requirement on an entry of a node record.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EnrFilter {
+	/// Key of the entry, e.g. `eth`.
+	pub key: String,
+	/// Required value of the entry. If none, any value matches.
+	pub value: Option<Vec<u8>>,
+}
+
+impl EnrFilter {
+	/// Check the raw value of the filtered entry, `None` if the record doesn't have it.
+	/// List values such as the `eth` fork ids also match if any nested item equals the required value.
+	pub fn matches(&self, entry: Option<&[u8]>) -> bool {
+		fn contains(rlp: &Rlp, value: &[u8]) -> bool {
+			if rlp.is_list() {
+				rlp.iter().any(|item| contains(&item, value))
+			} else {
+				rlp.data().map_or(false, |data| data == value)
+			}
+		}
+
+		match (entry, &self.value) {
+			(None, _) => false,
+			(Some(_), None) => true,
+			(Some(entry), Some(value)) => entry == &value[..] || contains(&Rlp::new(entry), value),
+		}
+	}
+}
+
 /// IP fiter
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum AllowIP {
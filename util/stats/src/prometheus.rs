@@ -0,0 +1,136 @@
+// Copyright 2015-2020 Parity Technologies (UK) Ltd.
+// This file is part of Open Ethereum.
+
+// Open Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Open Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Open Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Metrics in the Prometheus text exposition format.
+
+use std::fmt::{Display, Write};
+
+/// Kind of a metric, telling Prometheus how to interpret its samples.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MetricType {
+	/// A value which only ever increases, e.g. the number of imported blocks.
+	Counter,
+	/// A value which can go up and down, e.g. the number of peers.
+	Gauge,
+}
+
+impl MetricType {
+	fn as_str(&self) -> &'static str {
+		match *self {
+			MetricType::Counter => "counter",
+			MetricType::Gauge => "gauge",
+		}
+	}
+}
+
+/// Collects the metrics of the node's components and renders them for a Prometheus scrape.
+#[derive(Debug)]
+pub struct PrometheusRegistry {
+	prefix: String,
+	output: String,
+}
+
+/// Implemented by components which report metrics.
+pub trait PrometheusMetrics {
+	/// Adds the component's metrics to the registry.
+	fn prometheus_metrics(&self, registry: &mut PrometheusRegistry);
+}
+
+impl PrometheusRegistry {
+	/// Create a new registry. The prefix is prepended to the name of every metric.
+	pub fn new(prefix: &str) -> Self {
+		PrometheusRegistry {
+			prefix: prefix.to_owned(),
+			output: String::new(),
+		}
+	}
+
+	/// Add a counter with a single sample.
+	pub fn register_counter<V: Display>(&mut self, name: &str, help: &str, value: V) {
+		self.register_header(MetricType::Counter, name, help);
+		self.register_sample(name, None, value);
+	}
+
+	/// Add a gauge with a single sample.
+	pub fn register_gauge<V: Display>(&mut self, name: &str, help: &str, value: V) {
+		self.register_header(MetricType::Gauge, name, help);
+		self.register_sample(name, None, value);
+	}
+
+	/// Add a metric with one sample per value of the given label.
+	pub fn register_labeled<L, V, I>(&mut self, kind: MetricType, name: &str, help: &str, label: &str, samples: I)
+		where L: Display, V: Display, I: IntoIterator<Item = (L, V)>
+	{
+		self.register_header(kind, name, help);
+		for (label_value, value) in samples {
+			self.register_sample(name, Some((label, &label_value.to_string())), value);
+		}
+	}
+
+	/// The registered metrics in the Prometheus text exposition format.
+	pub fn render(self) -> String {
+		self.output
+	}
+
+	fn register_header(&mut self, kind: MetricType, name: &str, help: &str) {
+		let help = help.replace('\\', "\\\\").replace('\n', "\\n");
+		// Writing to a `String` cannot fail.
+		let _ = writeln!(self.output, "# HELP {}{} {}", self.prefix, name, help);
+		let _ = writeln!(self.output, "# TYPE {}{} {}", self.prefix, name, kind.as_str());
+	}
+
+	fn register_sample<V: Display>(&mut self, name: &str, label: Option<(&str, &str)>, value: V) {
+		let _ = match label {
+			Some((label, label_value)) => {
+				let label_value = label_value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+				writeln!(self.output, "{}{}{{{}=\"{}\"}} {}", self.prefix, name, label, label_value, value)
+			}
+			None => writeln!(self.output, "{}{} {}", self.prefix, name, value),
+		};
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn renders_metrics() {
+		let mut registry = PrometheusRegistry::new("oe_");
+		registry.register_counter("chain_imported_blocks", "Blocks imported\nsince start", 42u64);
+		registry.register_gauge("sync_peers", "Connected peers", 3);
+		registry.register_labeled(
+			MetricType::Counter,
+			"network_received_bytes",
+			"Bytes received per protocol",
+			"protocol",
+			vec![("eth", 10), ("p\"ar", 20)],
+		);
+
+		assert_eq!(registry.render(), "\
+# HELP oe_chain_imported_blocks Blocks imported\\nsince start
+# TYPE oe_chain_imported_blocks counter
+oe_chain_imported_blocks 42
+# HELP oe_sync_peers Connected peers
+# TYPE oe_sync_peers gauge
+oe_sync_peers 3
+# HELP oe_network_received_bytes Bytes received per protocol
+# TYPE oe_network_received_bytes counter
+oe_network_received_bytes{protocol=\"eth\"} 10
+oe_network_received_bytes{protocol=\"p\\\"ar\"} 20
+");
+	}
+}